@@ -0,0 +1,460 @@
+//! Streaming clone packs
+//!
+//! A JSON clone ships the whole repository as one document that both ends hold in memory.
+//! A clone pack is streamed instead. The receiver makes three kinds of request to
+//! [`PACK_PATH`], each answered by a sequence of frames:
+//!
+//! 1. `refs` - a [`PackFrame::Header`] naming the last merged change, then every ref
+//! 2. `objects` - the blobs with the requested hashes, so the receiver only asks for the
+//!    ones it doesn't already have, a batch at a time
+//! 3. `changes` - the merged changes up to the one named in the header
+//!
+//! Each frame is JSON compressed with LZ4, prefixed with its compressed length as a
+//! big-endian `u32`. Every pack closes with [`PackFrame::End`], so a transfer cut off part
+//! way is detected rather than mistaken for a short answer. The receiver verifies and stores
+//! each blob as it arrives, which lets an interrupted transfer resume by asking only for the
+//! blobs still missing.
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::providers::index::IndexProvider;
+use crate::providers::objects::ObjectsProvider;
+use crate::providers::refs::RefsProvider;
+use crate::types::{Change, ChangeStatus, ObjectInfo};
+
+/// Path of the clone pack endpoint
+pub const PACK_PATH: &str = "/api/clone/pack";
+
+/// Content type of a clone pack response
+pub const PACK_CONTENT_TYPE: &str = "application/x-moor-clone-pack";
+
+/// Version of the pack format, sent in the header
+pub const PACK_VERSION: u32 = 1;
+
+/// Uncompressed size a frame of blobs is filled to before it is sent
+const FRAME_TARGET_BYTES: usize = 1024 * 1024;
+
+/// Refs sent per frame
+const REFS_PER_FRAME: usize = 10_000;
+
+/// Changes sent per frame
+const CHANGES_PER_FRAME: usize = 256;
+
+/// Largest frame a reader accepts, compressed or decompressed
+const MAX_FRAME_BYTES: usize = 256 * 1024 * 1024;
+
+/// What a pack request asks for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "section", rename_all = "snake_case")]
+pub enum PackRequest {
+    /// The header and every ref
+    Refs,
+    /// The blobs with these hashes; any the sender doesn't have are left out
+    Objects { hashes: Vec<String> },
+    /// The merged changes, oldest first, up to and including `until`
+    Changes { until: Option<String> },
+}
+
+/// A frame of a clone pack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "frame", rename_all = "snake_case")]
+pub enum PackFrame {
+    /// Opens a refs pack with the last merged change the refs are consistent with
+    Header { version: u32, tip: Option<String> },
+    /// A chunk of the ref list
+    Refs { refs: Vec<(ObjectInfo, String)> },
+    /// A chunk of blobs, keyed by the sender's content hash
+    Objects { objects: Vec<(String, String)> },
+    /// A chunk of merged changes, oldest first
+    Changes { changes: Vec<Change> },
+    /// The sender failed part way and the pack is unusable
+    Error { message: String },
+    /// Closes a pack with the number of refs, blobs or changes it carried
+    End { count: usize },
+}
+
+impl PackFrame {
+    /// Frame name, used in error messages
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PackFrame::Header { .. } => "header",
+            PackFrame::Refs { .. } => "refs",
+            PackFrame::Objects { .. } => "objects",
+            PackFrame::Changes { .. } => "changes",
+            PackFrame::Error { .. } => "error",
+            PackFrame::End { .. } => "end",
+        }
+    }
+}
+
+/// Reasons a pack could not be received
+#[derive(Debug, thiserror::Error)]
+pub enum PackError {
+    /// The transfer was cut off and can be resumed
+    #[error("Clone pack transfer interrupted: {0}")]
+    Interrupted(String),
+    /// The pack is malformed, failed verification or reports a sender error
+    #[error("Invalid clone pack: {0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Database(#[from] ObjectsTreeError),
+}
+
+impl From<PackError> for ObjectsTreeError {
+    fn from(e: PackError) -> Self {
+        match e {
+            PackError::Database(e) => e,
+            other => ObjectsTreeError::SerializationError(other.to_string()),
+        }
+    }
+}
+
+/// Encode a frame: its compressed length as a big-endian `u32`, then the LZ4-compressed JSON
+pub fn encode_frame(frame: &PackFrame) -> Result<Vec<u8>, ObjectsTreeError> {
+    let json = serde_json::to_vec(frame).map_err(|e| {
+        ObjectsTreeError::SerializationError(format!("Failed to encode pack frame: {e}"))
+    })?;
+    let compressed = lz4_flex::compress_prepend_size(&json);
+
+    let mut bytes = Vec::with_capacity(4 + compressed.len());
+    bytes.extend_from_slice(&(compressed.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(&compressed);
+    Ok(bytes)
+}
+
+/// Incremental decoder for a pack arriving in arbitrary chunks
+#[derive(Debug, Default)]
+pub struct PackReader {
+    buffer: Vec<u8>,
+}
+
+impl PackReader {
+    /// Create an empty reader
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append bytes received from the stream
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Decode the next complete frame, or None if more bytes are needed
+    pub fn next_frame(&mut self) -> Result<Option<PackFrame>, PackError> {
+        let Some(length) = read_u32(&self.buffer, u32::from_be_bytes) else {
+            return Ok(None);
+        };
+        let length = length as usize;
+        if !(4..=MAX_FRAME_BYTES).contains(&length) {
+            return Err(PackError::Invalid(format!(
+                "frame length {length} is out of range"
+            )));
+        }
+        if self.buffer.len() < 4 + length {
+            return Ok(None);
+        }
+
+        let payload: Vec<u8> = self.buffer.drain(..4 + length).skip(4).collect();
+        let size = read_u32(&payload, u32::from_le_bytes).unwrap_or_default() as usize;
+        if size > MAX_FRAME_BYTES {
+            return Err(PackError::Invalid(format!(
+                "frame decompresses to {size} bytes, over the {MAX_FRAME_BYTES} byte limit"
+            )));
+        }
+        let json = lz4_flex::decompress_size_prepended(&payload)
+            .map_err(|e| PackError::Invalid(format!("failed to decompress frame: {e}")))?;
+        let frame = serde_json::from_slice(&json)
+            .map_err(|e| PackError::Invalid(format!("failed to parse frame: {e}")))?;
+        Ok(Some(frame))
+    }
+}
+
+/// Read a `u32` from the first four bytes, if there are that many
+fn read_u32(bytes: &[u8], from_bytes: fn([u8; 4]) -> u32) -> Option<u32> {
+    let prefix: [u8; 4] = bytes.get(..4)?.try_into().ok()?;
+    Some(from_bytes(prefix))
+}
+
+/// Write the pack answering `request`, handing each encoded frame to `send` as soon as it
+/// is built. `send` returns false once the receiver has gone away, which stops the pack.
+/// If building the pack fails, an error frame is sent in place of the end frame.
+pub fn write_pack(
+    database: &DatabaseRef,
+    request: &PackRequest,
+    mut send: impl FnMut(Vec<u8>) -> bool,
+) -> Result<(), ObjectsTreeError> {
+    let result = match request {
+        PackRequest::Refs => write_refs(database, &mut send),
+        PackRequest::Objects { hashes } => write_objects(database, hashes, &mut send),
+        PackRequest::Changes { until } => write_changes(database, until.as_deref(), &mut send),
+    };
+
+    match result {
+        Ok(count) => send_frame(&mut send, &PackFrame::End { count }),
+        Err(e) => {
+            let _ = send_frame(
+                &mut send,
+                &PackFrame::Error {
+                    message: e.to_string(),
+                },
+            );
+            Err(e)
+        }
+    }
+}
+
+/// Encode a frame and hand it to the receiver
+fn send_frame(
+    send: &mut dyn FnMut(Vec<u8>) -> bool,
+    frame: &PackFrame,
+) -> Result<(), ObjectsTreeError> {
+    if send(encode_frame(frame)?) {
+        Ok(())
+    } else {
+        Err(ObjectsTreeError::SerializationError(
+            "Clone pack receiver went away".to_string(),
+        ))
+    }
+}
+
+/// Send the header and every ref, returning the number of refs
+fn write_refs(
+    database: &DatabaseRef,
+    send: &mut dyn FnMut(Vec<u8>) -> bool,
+) -> Result<usize, ObjectsTreeError> {
+    // Read the tip before the refs: refs of changes merged in between are harmless extras,
+    // whereas a tip newer than the refs would name changes whose refs are missing
+    let tip = last_merged_change(database)?;
+    send_frame(
+        send,
+        &PackFrame::Header {
+            version: PACK_VERSION,
+            tip,
+        },
+    )?;
+
+    let refs: Vec<(ObjectInfo, String)> = database
+        .refs()
+        .get_all_refs()
+        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+        .into_iter()
+        .collect();
+    for chunk in refs.chunks(REFS_PER_FRAME) {
+        send_frame(
+            send,
+            &PackFrame::Refs {
+                refs: chunk.to_vec(),
+            },
+        )?;
+    }
+    Ok(refs.len())
+}
+
+/// Send the requested blobs, reading each one only when its frame is being filled
+fn write_objects(
+    database: &DatabaseRef,
+    hashes: &[String],
+    send: &mut dyn FnMut(Vec<u8>) -> bool,
+) -> Result<usize, ObjectsTreeError> {
+    let mut objects = Vec::new();
+    let mut frame_bytes = 0;
+    let mut count = 0;
+
+    for hash in hashes {
+        let Some(content) = database
+            .objects()
+            .get(hash)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+        else {
+            warn!("Requested object '{}' not found - leaving it out", hash);
+            continue;
+        };
+        frame_bytes += hash.len() + content.len();
+        objects.push((hash.clone(), content));
+        count += 1;
+
+        if frame_bytes >= FRAME_TARGET_BYTES {
+            send_frame(
+                send,
+                &PackFrame::Objects {
+                    objects: std::mem::take(&mut objects),
+                },
+            )?;
+            frame_bytes = 0;
+        }
+    }
+    if !objects.is_empty() {
+        send_frame(send, &PackFrame::Objects { objects })?;
+    }
+    Ok(count)
+}
+
+/// Send the merged changes up to and including `until`, oldest first
+fn write_changes(
+    database: &DatabaseRef,
+    until: Option<&str>,
+    send: &mut dyn FnMut(Vec<u8>) -> bool,
+) -> Result<usize, ObjectsTreeError> {
+    let Some(until) = until else {
+        return Ok(0);
+    };
+
+    let mut changes = Vec::new();
+    let mut count = 0;
+    let mut reached = false;
+    for change_id in database
+        .index()
+        .get_change_order()
+        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+    {
+        let Some(change) = database
+            .index()
+            .get_change(&change_id)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+        else {
+            continue;
+        };
+        if change.status != ChangeStatus::Merged {
+            continue;
+        }
+        changes.push(change);
+        count += 1;
+
+        if change_id == until {
+            reached = true;
+            break;
+        }
+        if changes.len() >= CHANGES_PER_FRAME {
+            send_frame(
+                send,
+                &PackFrame::Changes {
+                    changes: std::mem::take(&mut changes),
+                },
+            )?;
+        }
+    }
+
+    if !reached {
+        return Err(ObjectsTreeError::SerializationError(format!(
+            "Change '{until}' is not in the merged history"
+        )));
+    }
+    if !changes.is_empty() {
+        send_frame(send, &PackFrame::Changes { changes })?;
+    }
+    Ok(count)
+}
+
+/// The last merged change in the index, if any
+fn last_merged_change(database: &DatabaseRef) -> Result<Option<String>, ObjectsTreeError> {
+    let order = database
+        .index()
+        .get_change_order()
+        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+    for change_id in order.iter().rev() {
+        let change = database
+            .index()
+            .get_change(change_id)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        if change.is_some_and(|change| change.status == ChangeStatus::Merged) {
+            return Ok(Some(change_id.clone()));
+        }
+    }
+    Ok(None)
+}
+
+/// Request a pack and hand each frame to `on_frame` as it is decoded, returning the count
+/// from the end frame. Returns None if the source does not serve clone packs.
+pub async fn fetch_pack(
+    client: &reqwest::Client,
+    url: &str,
+    api_key: Option<&str>,
+    request: &PackRequest,
+    mut on_frame: impl FnMut(PackFrame) -> Result<(), PackError>,
+) -> Result<Option<usize>, PackError> {
+    let mut builder = client.post(url).json(request);
+    if let Some(api_key) = api_key {
+        builder = builder.header("X-API-Key", api_key);
+    }
+    let mut response = builder
+        .send()
+        .await
+        .map_err(|e| PackError::Interrupted(format!("HTTP request failed: {e}")))?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND || status == reqwest::StatusCode::METHOD_NOT_ALLOWED
+    {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        return Err(PackError::Invalid(format!(
+            "HTTP request failed with status: {status}"
+        )));
+    }
+
+    let mut reader = PackReader::new();
+    loop {
+        let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| PackError::Interrupted(format!("failed to read response: {e}")))?
+        else {
+            return Err(PackError::Interrupted(
+                "stream ended before the end of the pack".to_string(),
+            ));
+        };
+        reader.push(&chunk);
+
+        while let Some(frame) = reader.next_frame()? {
+            match frame {
+                PackFrame::End { count } => return Ok(Some(count)),
+                PackFrame::Error { message } => {
+                    return Err(PackError::Invalid(format!("source failed: {message}")));
+                }
+                frame => on_frame(frame)?,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reader_decodes_frames_split_across_chunks() {
+        let frames = [
+            PackFrame::Header {
+                version: PACK_VERSION,
+                tip: Some("abc".to_string()),
+            },
+            PackFrame::Objects {
+                objects: vec![("hash".to_string(), "content ".repeat(1000))],
+            },
+            PackFrame::End { count: 1 },
+        ];
+        let bytes: Vec<u8> = frames
+            .iter()
+            .flat_map(|frame| encode_frame(frame).unwrap())
+            .collect();
+
+        let mut reader = PackReader::new();
+        let mut decoded = Vec::new();
+        for chunk in bytes.chunks(7) {
+            reader.push(chunk);
+            while let Some(frame) = reader.next_frame().unwrap() {
+                decoded.push(frame.kind());
+            }
+        }
+        assert_eq!(decoded, vec!["header", "objects", "end"]);
+    }
+
+    #[test]
+    fn test_reader_rejects_oversized_frames() {
+        let mut reader = PackReader::new();
+        reader.push(&u32::MAX.to_be_bytes());
+        assert!(matches!(reader.next_frame(), Err(PackError::Invalid(_))));
+    }
+}
@@ -1,6 +1,8 @@
 use std::env;
 use std::path::PathBuf;
 
+use crate::util::ContentHash;
+
 /// Configuration for the VCS worker
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -14,8 +16,12 @@ pub struct Config {
     pub git_backup_repo: Option<String>,
     /// Git backup authentication token (optional)
     pub git_backup_token: Option<String>,
-    /// Git backup working directory (for temp clones)
+    /// Git backup working directory (bare mirror pushed to remote backup repositories)
     pub git_backup_work_dir: Option<PathBuf>,
+    /// Hash used to key object blobs (existing objects are re-keyed at startup if it changes)
+    pub content_hash: ContentHash,
+    /// Directory that local import/git sources must be inside (local imports are refused if unset)
+    pub import_root: Option<PathBuf>,
 }
 
 impl Config {
@@ -27,6 +33,8 @@ impl Config {
         let git_backup_repo = Self::get_git_backup_repo();
         let git_backup_token = Self::get_git_backup_token();
         let git_backup_work_dir = Self::get_git_backup_work_dir();
+        let content_hash = Self::get_content_hash();
+        let import_root = Self::get_import_root();
         tracing::info!("VCS database path: {:?}", db_path);
         tracing::info!(
             "Wizard API key configured: {}",
@@ -37,6 +45,7 @@ impl Config {
             }
         );
         tracing::info!("Game name: {}", game_name);
+        tracing::info!("Object content hash: {}", content_hash.name());
         tracing::info!(
             "Git backup configured: {}",
             if git_backup_repo.is_some() {
@@ -52,6 +61,8 @@ impl Config {
             git_backup_repo,
             git_backup_token,
             git_backup_work_dir,
+            content_hash,
+            import_root,
         }
     }
 
@@ -64,6 +75,8 @@ impl Config {
         let git_backup_token = Self::get_git_backup_token();
         // For testing, don't set a default work dir - let it be auto-generated per-instance
         let git_backup_work_dir = None;
+        let content_hash = Self::get_content_hash();
+        let import_root = Self::get_import_root();
         tracing::info!("VCS database path (explicit): {:?}", db_path);
         tracing::info!(
             "Wizard API key configured: {}",
@@ -74,6 +87,7 @@ impl Config {
             }
         );
         tracing::info!("Game name: {}", game_name);
+        tracing::info!("Object content hash: {}", content_hash.name());
         tracing::info!(
             "Git backup configured: {}",
             if git_backup_repo.is_some() {
@@ -89,6 +103,8 @@ impl Config {
             git_backup_repo,
             git_backup_token,
             git_backup_work_dir,
+            content_hash,
+            import_root,
         }
     }
 
@@ -137,6 +153,29 @@ impl Config {
             })
     }
 
+    /// Get the object content hash from environment or use default (blake3)
+    fn get_content_hash() -> ContentHash {
+        match env::var("VCS_CONTENT_HASH") {
+            Ok(name) if !name.is_empty() => ContentHash::from_name(&name).unwrap_or_else(|| {
+                tracing::warn!(
+                    "Unknown VCS_CONTENT_HASH '{}', using {}",
+                    name,
+                    ContentHash::default().name()
+                );
+                ContentHash::default()
+            }),
+            _ => ContentHash::default(),
+        }
+    }
+
+    /// Get the directory local import sources are confined to from environment (optional)
+    fn get_import_root() -> Option<PathBuf> {
+        env::var("VCS_IMPORT_ROOT")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from)
+    }
+
     /// Builder method to set git backup configuration
     #[allow(dead_code)]
     pub fn with_git_backup(mut self, repo: String, token: Option<String>) -> Self {
@@ -151,6 +190,20 @@ impl Config {
         self.git_backup_work_dir = Some(path);
         self
     }
+
+    /// Builder method to set the object content hash
+    #[allow(dead_code)]
+    pub fn with_content_hash(mut self, content_hash: ContentHash) -> Self {
+        self.content_hash = content_hash;
+        self
+    }
+
+    /// Builder method to set the directory local import sources are confined to
+    #[allow(dead_code)]
+    pub fn with_import_root(mut self, path: PathBuf) -> Self {
+        self.import_root = Some(path);
+        self
+    }
 }
 
 impl Default for Config {
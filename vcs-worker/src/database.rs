@@ -1,15 +1,16 @@
 use crate::config::Config;
+use crate::providers::batch::{self, CommitError, OpenBatch};
 use crate::providers::{
-    IndexProviderImpl, ObjectsProviderImpl, RefsProviderImpl, UserProviderImpl,
-    WorkspaceProviderImpl, index::IndexProvider, objects::ObjectsProvider, refs::RefsProvider,
-    workspace::WorkspaceProvider,
+    BackupProviderImpl, IndexProviderImpl, ObjectsProviderImpl, RefsProviderImpl, UserProviderImpl,
+    WorkspaceProviderImpl, backups::BackupProvider, index::IndexProvider, objects::ObjectsProvider,
+    refs::RefsProvider, user::UserProvider, workspace::WorkspaceProvider,
 };
 use fjall::{Config as FjallConfig, Keyspace, PersistMode};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio::time::sleep;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 
 #[derive(Debug, thiserror::Error)]
 #[allow(clippy::enum_variant_names)]
@@ -24,6 +25,12 @@ pub enum ObjectsTreeError {
     CompilationError(#[from] moor_compiler::ObjDefParseError),
     #[error("Serialization error: {0}")]
     SerializationError(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    InvalidArgument(String),
+    #[error("Concurrent modification: {0}")]
+    ConcurrentModification(String),
 }
 
 /// Database coordinator that aggregates providers for different subsystems
@@ -36,8 +43,8 @@ pub struct Database {
     index_provider: Arc<IndexProviderImpl>,
     user_provider: Arc<UserProviderImpl>,
     workspace_provider: Arc<WorkspaceProviderImpl>,
+    backup_provider: Arc<BackupProviderImpl>,
 
-    #[allow(dead_code)]
     flush_sender: mpsc::UnboundedSender<()>,
 
     // Store the database path for partition size calculations
@@ -46,6 +53,9 @@ pub struct Database {
 
     // Store the game name
     game_name: String,
+
+    // Held while a git backup run uses the backup repository
+    backup_lock: Mutex<()>,
 }
 
 impl Database {
@@ -72,6 +82,8 @@ impl Database {
             keyspace.open_partition("workspace", fjall::PartitionCreateOptions::default())?;
         let users_tree =
             keyspace.open_partition("users", fjall::PartitionCreateOptions::default())?;
+        let backups_tree =
+            keyspace.open_partition("backups", fjall::PartitionCreateOptions::default())?;
 
         // Create channel for background flushing
         let (flush_sender, mut flush_receiver) = mpsc::unbounded_channel();
@@ -79,6 +91,7 @@ impl Database {
         // Initialize providers
         let objects_provider = Arc::new(ObjectsProviderImpl::new(
             objects_tree.clone(),
+            config.content_hash,
             flush_sender.clone(),
         ));
         let refs_provider = Arc::new(RefsProviderImpl::new(
@@ -98,6 +111,36 @@ impl Database {
             workspace_tree.clone(),
             flush_sender.clone(),
         ));
+        let backup_provider = Arc::new(BackupProviderImpl::new(
+            backups_tree.clone(),
+            flush_sender.clone(),
+        ));
+
+        // Older databases keep all refs and all users in one blob each; give every ref
+        // and user its own key before anything reads them
+        let migrated_refs = refs_provider
+            .migrate_legacy_storage()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        let migrated_users = user_provider
+            .migrate_legacy_storage()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        if migrated_refs > 0 || migrated_users > 0 {
+            info!(
+                "Migrated {} refs and {} users to per-key storage",
+                migrated_refs, migrated_users
+            );
+        }
+
+        // Older databases have a single source with its credentials; it becomes the
+        // default remote
+        index_provider
+            .migrate_legacy_source()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+
+        // Runs that were queued or running when the worker stopped will never finish
+        if let Err(e) = backup_provider.fail_unfinished_runs() {
+            warn!("Failed to mark interrupted backup runs: {}", e);
+        }
 
         info!(
             "Database initialized with {} objects",
@@ -160,17 +203,37 @@ impl Database {
             }
         });
 
-        Ok(Self {
+        let database = Self {
             keyspace,
             objects_provider,
             refs_provider,
             index_provider,
             user_provider,
             workspace_provider,
+            backup_provider,
             flush_sender,
             db_path: config.db_path.clone(),
             game_name: config.game_name.clone(),
-        })
+            backup_lock: Mutex::new(()),
+        };
+
+        // Objects written under another content hash (or before the hash was recorded)
+        // are re-keyed before anything reads them
+        let stored_hash = database
+            .refs_provider
+            .get_content_hash()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        if stored_hash != Some(config.content_hash) {
+            info!(
+                "Migrating objects from {} to {} content hash",
+                stored_hash.map_or("unrecorded", |hash| hash.name()),
+                config.content_hash.name()
+            );
+            let rekeyed = database.migrate_content_hash()?;
+            info!("Content hash migration re-keyed {} objects", rekeyed);
+        }
+
+        Ok(database)
     }
 
     /// Get direct access to the objects provider
@@ -198,20 +261,99 @@ impl Database {
         &self.workspace_provider
     }
 
+    /// Get direct access to the backup provider
+    pub fn backups(&self) -> &Arc<BackupProviderImpl> {
+        &self.backup_provider
+    }
+
+    /// Get the lock that serializes git backup runs
+    pub fn backup_lock(&self) -> &Mutex<()> {
+        &self.backup_lock
+    }
+
+    /// Run `f` with every provider write it makes staged in one batch across partitions,
+    /// committed only if `f` succeeds. If `f` fails (or panics) nothing it wrote is kept.
+    /// Reads inside `f` see its own staged writes. Calls nested inside an open batch
+    /// join it and commit with the outermost call.
+    ///
+    /// If another batch changed a key `f` read before this one commits, nothing is
+    /// written and `ConcurrentModification` is returned; running `f` again is safe.
+    pub fn write_batch<T, E>(&self, f: impl FnOnce() -> Result<T, E>) -> Result<T, E>
+    where
+        E: From<ObjectsTreeError>,
+    {
+        let Some(batch) = OpenBatch::open() else {
+            return f();
+        };
+
+        let result = f()?;
+        let writes = batch.commit(&self.keyspace).map_err(|e| match e {
+            CommitError::Fjall(e) => ObjectsTreeError::FjallError(e),
+            CommitError::Conflict(msg) => {
+                debug!("Write batch conflicted: {}", msg);
+                ObjectsTreeError::ConcurrentModification(msg)
+            }
+        })?;
+        debug!("Committed write batch of {} writes", writes);
+
+        // Request background flush
+        if writes > 0 && self.flush_sender.send(()).is_err() {
+            warn!("Failed to request background flush - channel closed");
+        }
+        Ok(result)
+    }
+
+    /// Run `f` once the write batch open on this thread commits, or straight away if
+    /// none is open. Use this for side effects, such as starting a backup, that must
+    /// only happen if the operation's writes are kept and must see them.
+    pub fn after_commit(&self, f: impl FnOnce() + Send + 'static) {
+        batch::after_commit(f);
+    }
+
+    /// Run `f` outside the write batch open on this thread, if any, so its writes are kept
+    /// even if the batch fails. Meant for content-addressed blobs, which nothing points at
+    /// until the batch that adds their refs commits.
+    pub fn outside_batch<T>(&self, f: impl FnOnce() -> T) -> T {
+        batch::outside_batch(f)
+    }
+
+    /// Run `f` outside the write batch open on this thread, if any, with every other
+    /// batch's commit held back until it returns. Meant for work like garbage collection
+    /// that decides what to write from a scan of a whole partition.
+    pub fn exclusively<T>(&self, f: impl FnOnce() -> T) -> T {
+        batch::exclusively(f)
+    }
+
+    /// Re-key objects stored under another content hash to the configured one and point
+    /// refs at the new keys. Old entries are only removed once refs no longer use them,
+    /// so an interrupted migration is completed by running it again.
+    pub fn migrate_content_hash(&self) -> Result<usize, ObjectsTreeError> {
+        let rekeyed = self
+            .objects_provider
+            .rehash_objects()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        self.refs_provider
+            .remap_hashes(&rekeyed)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+
+        for old_key in rekeyed.keys() {
+            self.objects_provider
+                .delete(old_key)
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        }
+
+        self.refs_provider
+            .set_content_hash(self.objects_provider.content_hash())
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+
+        Ok(rekeyed.len())
+    }
+
     /// Get the game name
     pub fn game_name(&self) -> &str {
         &self.game_name
     }
 
-    /// Flush all pending writes to disk synchronously
-    /// This ensures all database changes are persisted before returning
-    pub fn flush(&self) -> Result<(), ObjectsTreeError> {
-        info!("Flushing database to disk");
-        self.keyspace.persist(PersistMode::SyncAll)?;
-        info!("Database flush completed");
-        Ok(())
-    }
-
     /// Get the data size of a partition by iterating through all entries
     pub fn get_partition_data_size(&self, partition_name: &str) -> u64 {
         match partition_name {
@@ -251,11 +393,11 @@ impl Database {
             .collect();
 
         match matches.len() {
-            0 => Err(ObjectsTreeError::SerializationError(format!(
+            0 => Err(ObjectsTreeError::NotFound(format!(
                 "Change ID '{short_or_full}' not found"
             ))),
             1 => Ok(matches[0].clone()),
-            _ => Err(ObjectsTreeError::SerializationError(format!(
+            _ => Err(ObjectsTreeError::InvalidArgument(format!(
                 "Ambiguous change ID prefix '{short_or_full}' matches multiple changes. Please provide more characters."
             ))),
         }
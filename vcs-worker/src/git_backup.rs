@@ -1,196 +1,574 @@
 use crate::config::Config;
 use crate::database::DatabaseRef;
-use crate::providers::index::IndexProvider;
+use crate::object_merge::load_merged_changes;
+use crate::providers::backups::BackupProvider;
 use crate::providers::objects::ObjectsProvider;
 use crate::providers::refs::RefsProvider;
-use crate::types::{ObjectInfo, VcsObjectType};
+use crate::types::{BackupRun, BackupRunStatus, Change, VcsObjectType};
+use git2::build::CheckoutBuilder;
+use git2::{
+    Commit, Cred, CredentialType, ErrorCode, FetchOptions, Oid, PushOptions, RemoteCallbacks,
+    Repository, Signature, Time,
+};
 use moor_compiler::{CompileOptions, ObjFileContext, compile_object_definitions};
-use moor_objdef::dump_object;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
-use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::process::Command;
 use tracing::{error, info, warn};
 
+/// Commit message trailer recording which VCS change a backup commit corresponds to
+pub const CHANGE_ID_TRAILER: &str = "VCS-Change-Id";
+
+/// Object and meta versions as of a point in the change history
+#[derive(Debug, Default)]
+struct BackupState {
+    objects: HashMap<String, u64>,
+    metas: HashMap<String, u64>,
+}
+
+impl BackupState {
+    /// Apply a merged change, returning the names of objects whose files need rewriting or removal
+    fn apply_change(&mut self, change: &Change) -> BTreeSet<String> {
+        let mut touched = BTreeSet::new();
+
+        for renamed in &change.renamed_objects {
+            let versions = match renamed.from.object_type {
+                VcsObjectType::MooObject => &mut self.objects,
+                VcsObjectType::MooMetaObject => &mut self.metas,
+            };
+            versions.remove(&renamed.from.name);
+            versions.insert(renamed.to.name.clone(), renamed.to.version);
+            touched.insert(renamed.from.name.clone());
+            touched.insert(renamed.to.name.clone());
+        }
+
+        for obj_info in change
+            .added_objects
+            .iter()
+            .chain(change.modified_objects.iter())
+        {
+            let versions = match obj_info.object_type {
+                VcsObjectType::MooObject => &mut self.objects,
+                VcsObjectType::MooMetaObject => &mut self.metas,
+            };
+            versions.insert(obj_info.name.clone(), obj_info.version);
+            touched.insert(obj_info.name.clone());
+        }
+
+        for obj_info in &change.deleted_objects {
+            let versions = match obj_info.object_type {
+                VcsObjectType::MooObject => &mut self.objects,
+                VcsObjectType::MooMetaObject => &mut self.metas,
+            };
+            versions.remove(&obj_info.name);
+            touched.insert(obj_info.name.clone());
+        }
+
+        touched
+    }
+}
+
+/// Errors raised while backing up to git
+#[derive(Debug, thiserror::Error)]
+pub enum GitBackupError {
+    #[error("Git error: {0}")]
+    Git(#[from] git2::Error),
+    #[error("Backup branch has diverged from the remote: {0}")]
+    Diverged(String),
+    #[error("{0}")]
+    Backup(String),
+}
+
+/// What a successful backup run produced
+struct BackupOutcome {
+    commits: usize,
+    head: Option<String>,
+    object_count: usize,
+}
+
 /// Trigger a git backup in a background thread (non-blocking)
-/// This is the main entry point called from change operations
-pub fn trigger_git_backup(database: DatabaseRef, config: Config) {
+/// This is the main entry point called from change operations. The run is only queued
+/// once the calling operation's writes commit, so it backs up what the operation did.
+pub fn trigger_git_backup(database: DatabaseRef, config: Config, trigger: &str) {
     // Check if git backup is configured
     if config.git_backup_repo.is_none() {
         return;
     }
 
-    info!("Triggering git backup in background thread");
+    let trigger = trigger.to_string();
+    let deferred = database.clone();
+    deferred.after_commit(move || {
+        // Record the run up front so it shows in backup/history while it waits its turn
+        let run = match database.backups().create_run(&trigger) {
+            Ok(run) => run,
+            Err(e) => {
+                error!("Failed to record git backup run: {}", e);
+                return;
+            }
+        };
+
+        info!("Triggering git backup run {} in background thread", run.id);
 
-    // Spawn a background thread to perform the backup
-    std::thread::spawn(move || {
-        if let Err(e) = perform_git_backup(database, config) {
-            error!("Git backup failed: {}", e);
-        }
+        // Spawn a background thread to perform the backup
+        std::thread::spawn(move || {
+            run_git_backup(&database, &config, run);
+        });
     });
 }
 
-/// Perform the actual git backup (runs in background thread)
-fn perform_git_backup(database: DatabaseRef, config: Config) -> Result<(), String> {
+/// Perform a queued backup run, waiting for any run already in progress
+/// The run is recorded in the backups partition as it starts and finishes
+pub fn run_git_backup(database: &DatabaseRef, config: &Config, mut run: BackupRun) -> BackupRun {
+    // Runs share the backup repository, so only one may use it at a time
+    let _guard = database
+        .backup_lock()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    run.status = BackupRunStatus::Running;
+    run.started_at = Some(crate::util::current_unix_timestamp());
+    record_run(database, &run);
+
+    match perform_git_backup(database, config) {
+        Ok(outcome) => {
+            run.status = BackupRunStatus::Succeeded;
+            run.commits = outcome.commits;
+            run.commit = outcome.head;
+            run.object_count = outcome.object_count;
+        }
+        Err(e) => {
+            error!("Git backup run {} failed: {}", run.id, e);
+            run.status = BackupRunStatus::Failed;
+            run.error = Some(e.to_string());
+        }
+    }
+
+    run.finished_at = Some(crate::util::current_unix_timestamp());
+    record_run(database, &run);
+    run
+}
+
+/// Store the current state of a run, logging rather than failing the backup
+fn record_run(database: &DatabaseRef, run: &BackupRun) {
+    if let Err(e) = database.backups().update_run(run) {
+        error!("Failed to record git backup run {}: {}", run.id, e);
+    }
+}
+
+/// Whether the backup target is a remote that commits must be pushed to
+pub fn is_remote_url(repo_path: &str) -> bool {
+    ["http://", "https://", "ssh://", "git://", "file://", "git@"]
+        .iter()
+        .any(|prefix| repo_path.starts_with(prefix))
+}
+
+/// Perform the actual git backup
+fn perform_git_backup(
+    database: &DatabaseRef,
+    config: &Config,
+) -> Result<BackupOutcome, GitBackupError> {
     let repo_path = config
         .git_backup_repo
         .as_ref()
-        .ok_or_else(|| "No git backup repo configured".to_string())?;
+        .ok_or_else(|| GitBackupError::Backup("No git backup repo configured".to_string()))?;
 
     info!("Starting git backup to: {}", repo_path);
 
-    // Set up the git repository (clone or use existing)
-    let work_dir = setup_git_repo(repo_path, &config)?;
-
-    info!("Git repository ready at: {:?}", work_dir);
-
-    // Get the computed object list from the working index (not all refs)
-    // This gives us the current state of objects after applying all changes chronologically
-    let all_objects = database
-        .index()
-        .compute_complete_object_list()
-        .map_err(|e| format!("Failed to compute complete object list: {}", e))?;
+    let target = BackupTarget::open(repo_path, config)?;
+    if let Some(url) = &target.remote_url {
+        target.sync_with_remote(url, config.git_backup_token.as_deref())?;
+    }
 
-    // Filter to only MOO objects (not meta objects)
-    let moo_objects: Vec<&ObjectInfo> = all_objects
-        .iter()
-        .filter(|obj_info| obj_info.object_type == VcsObjectType::MooObject)
-        .collect();
+    // Replay the merged changes in index order, resuming after the last one already backed up
+    let merged_changes = load_merged_changes(database)
+        .map_err(|e| GitBackupError::Backup(format!("Failed to load merged changes: {}", e)))?;
 
-    info!("Found {} MOO objects to backup from working index", moo_objects.len());
+    let mut tip = target.tip()?;
+    let resume_from = match tip
+        .map(|tip| target.last_backed_up_change(tip))
+        .transpose()?
+    {
+        Some(Some(last_change_id)) => {
+            match merged_changes.iter().position(|c| c.id == last_change_id) {
+                Some(position) => Some(position + 1),
+                None => {
+                    warn!(
+                        "Last backed up change '{}' is not in the index history, replaying all changes",
+                        last_change_id
+                    );
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
 
-    // Track which files we've written
-    let mut written_files = HashSet::new();
+    // Nothing to resume from: start from a tree without .moo files so stale objects don't linger
+    let mut tree = match resume_from {
+        Some(_) => target.tip_tree(tip)?,
+        None => Some(target.tree_without_objects(tip)?),
+    };
+    let resume_from = resume_from.unwrap_or(0);
+
+    info!(
+        "Backing up {} of {} merged changes",
+        merged_changes.len().saturating_sub(resume_from),
+        merged_changes.len()
+    );
+
+    let mut state = BackupState::default();
+    let mut commits = 0;
+    for (position, change) in merged_changes.iter().enumerate() {
+        let touched = state.apply_change(change);
+        if position < resume_from {
+            continue;
+        }
 
-    // Dump each object to a file
-    for obj_info in &moo_objects {
-        match dump_object_to_file(&database, obj_info, &work_dir) {
-            Ok(filename) => {
-                written_files.insert(filename);
-            }
-            Err(e) => {
-                warn!("Failed to dump object '{}': {}", obj_info.name, e);
+        let base_tree = tree.map(|oid| target.repo.find_tree(oid)).transpose()?;
+        let mut builder = target.repo.treebuilder(base_tree.as_ref())?;
+        for object_name in &touched {
+            let filename = format!("{}.moo", sanitize_filename(object_name));
+            match state.objects.get(object_name) {
+                Some(&version) => {
+                    let meta_version = state.metas.get(object_name).copied();
+                    match render_object(database, object_name, version, meta_version) {
+                        Ok(content) => {
+                            let blob = target.repo.blob(content.as_bytes())?;
+                            builder.insert(&filename, blob, 0o100644)?;
+                        }
+                        // Committing without it would leave a stale copy of the object behind
+                        Err(e) => {
+                            return Err(GitBackupError::Backup(format!(
+                                "Failed to dump object '{}' for change '{}': {}",
+                                object_name, change.id, e
+                            )));
+                        }
+                    }
+                }
+                None => {
+                    if builder.get(&filename)?.is_some() {
+                        builder.remove(&filename)?;
+                    }
+                }
             }
         }
+        let tree_oid = builder.write()?;
+        tree = Some(tree_oid);
+
+        tip = Some(target.commit_change(change, tree_oid, tip)?);
+        commits += 1;
     }
 
-    info!("Successfully dumped {} objects", written_files.len());
+    info!("Created {} backup commits", commits);
 
-    // Remove any .moo files that no longer have corresponding objects
-    cleanup_old_files(&work_dir, &written_files)?;
+    if commits > 0 || resume_from == 0 {
+        target.update_worktree()?;
+    }
 
-    // Commit and push changes
-    git_commit_and_push(&work_dir, repo_path, &config.git_backup_token)?;
+    // Push to the remote (never forced, so history is only ever appended)
+    if let Some(url) = &target.remote_url {
+        target.push(url, config.git_backup_token.as_deref())?;
+    }
 
     info!("Git backup completed successfully");
 
-    Ok(())
+    Ok(BackupOutcome {
+        commits,
+        head: tip.map(|oid| oid.to_string()),
+        object_count: state.objects.len(),
+    })
 }
 
-/// Set up the git repository (clone or init)
-fn setup_git_repo(repo_path: &str, config: &Config) -> Result<PathBuf, String> {
-    // Determine if this is a remote URL or local path
-    let is_remote = repo_path.starts_with("http://") || repo_path.starts_with("https://");
-
-    // Determine the working directory
-    let work_dir = if is_remote {
-        // For remote repos, use configured work dir or default
-        config.git_backup_work_dir
-            .clone()
-            .unwrap_or_else(|| PathBuf::from("/tmp/vcs-git-backup"))
-    } else {
-        // For local repos, use the repo path itself
-        PathBuf::from(repo_path)
-    };
+/// The repository backups are committed to, and the remote it is pushed to
+struct BackupTarget {
+    repo: Repository,
+    /// The local branch backups are committed to (e.g. `refs/heads/main`)
+    branch: String,
+    remote_url: Option<String>,
+}
 
-    // If it's a remote repo and the directory doesn't exist, clone it
-    if is_remote {
-        if work_dir.exists() {
-            // Directory exists, try to pull latest
-            info!("Git backup directory exists, pulling latest changes");
-            
-            // Try to pull, but don't fail if it doesn't work (we'll force push anyway)
-            let _ = Command::new("git")
-                .current_dir(&work_dir)
-                .args(&["pull", "--rebase"])
-                .output();
+impl BackupTarget {
+    /// Open the backup repository, creating a bare repository if none exists yet
+    /// Remote targets are mirrored in the configured work directory and pushed to afterwards
+    fn open(repo_path: &str, config: &Config) -> Result<Self, GitBackupError> {
+        let (path, remote_url) = if is_remote_url(repo_path) {
+            let work_dir = config
+                .git_backup_work_dir
+                .clone()
+                .unwrap_or_else(|| PathBuf::from("/tmp/vcs-git-backup"));
+            (work_dir, Some(repo_path.to_string()))
         } else {
-            // Clone the repository
-            info!("Cloning git repository: {}", repo_path);
+            (PathBuf::from(repo_path), None)
+        };
 
-            let clone_url = if let Some(ref tok) = config.git_backup_token {
-                // Insert token into URL for authentication
-                inject_token_into_url(repo_path, tok)
-            } else {
-                repo_path.to_string()
-            };
+        let is_empty_dir = |path: &Path| {
+            fs::read_dir(path)
+                .map(|mut entries| entries.next().is_none())
+                .unwrap_or(false)
+        };
 
-            let output = Command::new("git")
-                .args(&["clone", &clone_url, work_dir.to_str().unwrap()])
-                .output()
-                .map_err(|e| format!("Failed to execute git clone: {}", e))?;
+        let repo = if !path.exists() || is_empty_dir(&path) {
+            fs::create_dir_all(&path).map_err(|e| {
+                GitBackupError::Backup(format!("Failed to create {:?}: {}", path, e))
+            })?;
+            info!("Initializing bare backup repository at {:?}", path);
+            Repository::init_bare(&path)?
+        } else {
+            Repository::open(&path)?
+        };
+
+        // Older backups stored the remote (with its token) in the repository config
+        if remote_url.is_some() && repo.find_remote("origin").is_ok() {
+            repo.remote_delete("origin")?;
+        }
 
-            if !output.status.success() {
-                return Err(format!(
-                    "Git clone failed: {}",
-                    String::from_utf8_lossy(&output.stderr)
-                ));
+        let branch = repo
+            .find_reference("HEAD")?
+            .symbolic_target()
+            .unwrap_or("refs/heads/main")
+            .to_string();
+
+        Ok(Self {
+            repo,
+            branch,
+            remote_url,
+        })
+    }
+
+    /// The current head of the backup branch, if it has any commits
+    fn tip(&self) -> Result<Option<Oid>, GitBackupError> {
+        match self.repo.refname_to_id(&self.branch) {
+            Ok(oid) => Ok(Some(oid)),
+            Err(e) if e.code() == ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// The tree of a commit, if there is one
+    fn tip_tree(&self, tip: Option<Oid>) -> Result<Option<Oid>, GitBackupError> {
+        Ok(tip
+            .map(|oid| self.repo.find_commit(oid))
+            .transpose()?
+            .map(|commit| commit.tree_id()))
+    }
+
+    /// The tree of a commit with every `.moo` file removed
+    fn tree_without_objects(&self, tip: Option<Oid>) -> Result<Oid, GitBackupError> {
+        let base_tree = self
+            .tip_tree(tip)?
+            .map(|oid| self.repo.find_tree(oid))
+            .transpose()?;
+        let mut builder = self.repo.treebuilder(base_tree.as_ref())?;
+        if let Some(tree) = &base_tree {
+            for entry in tree.iter() {
+                if let Some(name) = entry.name().filter(|n| n.ends_with(".moo")) {
+                    builder.remove(name)?;
+                }
             }
         }
-    } else {
-        // Local path - create if doesn't exist and init
-        if !work_dir.exists() {
-            fs::create_dir_all(&work_dir)
-                .map_err(|e| format!("Failed to create directory: {}", e))?;
-
-            let output = Command::new("git")
-                .current_dir(&work_dir)
-                .args(&["init"])
-                .output()
-                .map_err(|e| format!("Failed to execute git init: {}", e))?;
-
-            if !output.status.success() {
-                return Err(format!(
-                    "Git init failed: {}",
-                    String::from_utf8_lossy(&output.stderr)
-                ));
+        Ok(builder.write()?)
+    }
+
+    /// Find the ID of the most recent change recorded in the backup history
+    fn last_backed_up_change(&self, tip: Oid) -> Result<Option<String>, GitBackupError> {
+        let mut walk = self.repo.revwalk()?;
+        walk.push(tip)?;
+        walk.simplify_first_parent()?;
+
+        for oid in walk {
+            let commit = self.repo.find_commit(oid?)?;
+            if let Some(change_id) = commit.message().and_then(parse_change_id_trailer) {
+                return Ok(Some(change_id));
             }
+        }
+
+        Ok(None)
+    }
 
-            info!("Initialized new git repository at {:?}", work_dir);
+    /// Commit a tree as a single change, authored by the change's author at its timestamp
+    fn commit_change(
+        &self,
+        change: &Change,
+        tree: Oid,
+        parent: Option<Oid>,
+    ) -> Result<Oid, GitBackupError> {
+        let author_name = if change.author.trim().is_empty() {
+            "VCS Backup"
+        } else {
+            change.author.trim()
+        };
+        let author = Signature::new(
+            author_name,
+            "vcs-backup@localhost",
+            &Time::new(change.timestamp as i64, 0),
+        )?;
+        let committer = Signature::now("VCS Backup", "vcs-backup@localhost")?;
+
+        let tree = self.repo.find_tree(tree)?;
+        let parent = parent.map(|oid| self.repo.find_commit(oid)).transpose()?;
+        let parents: Vec<&Commit> = parent.iter().collect();
+
+        // Changes that don't alter any file (e.g. meta-only) still get a commit to record their ID
+        let oid = self.repo.commit(
+            Some(self.branch.as_str()),
+            &author,
+            &committer,
+            &change_commit_message(change),
+            &tree,
+            &parents,
+        )?;
+
+        info!(
+            "Committed change '{}' ({}) as {}",
+            change.name, change.id, oid
+        );
+
+        Ok(oid)
+    }
+
+    /// Bring the working tree of a non-bare target in line with the backup branch
+    fn update_worktree(&self) -> Result<(), GitBackupError> {
+        let Some(work_dir) = self.repo.workdir() else {
+            return Ok(());
+        };
+        if self.tip()?.is_none() {
+            return Ok(());
         }
+
+        self.repo
+            .checkout_head(Some(CheckoutBuilder::new().force()))?;
+
+        // Untracked .moo files are left over from before the backup took over the directory
+        let head_tree = self.repo.head()?.peel_to_tree()?;
+        let current_files: HashSet<String> = head_tree
+            .iter()
+            .filter_map(|entry| entry.name().map(str::to_string))
+            .collect();
+        cleanup_old_files(work_dir, &current_files).map_err(GitBackupError::Backup)?;
+
+        Ok(())
     }
 
-    Ok(work_dir)
-}
+    /// Fetch the remote branch and fast-forward the backup branch to it
+    /// Fails if the remote has commits the backup branch doesn't, and vice versa
+    fn sync_with_remote(&self, url: &str, token: Option<&str>) -> Result<(), GitBackupError> {
+        let mut remote = self.repo.remote_anonymous(url)?;
+        let mut fetch_options = FetchOptions::new();
+        fetch_options.remote_callbacks(remote_callbacks(token));
+        let refspec = format!("+refs/heads/main:{}", REMOTE_TRACKING_REF);
+        remote.fetch(&[refspec.as_str()], Some(&mut fetch_options), None)?;
+
+        let remote_tip = match self.repo.refname_to_id(REMOTE_TRACKING_REF) {
+            Ok(oid) => oid,
+            // The remote is empty, so there is nothing to reconcile
+            Err(e) if e.code() == ErrorCode::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
 
-/// Inject authentication token into git URL
-pub fn inject_token_into_url(url: &str, token: &str) -> String {
-    if url.starts_with("https://") {
-        // Replace https:// with https://token@
-        url.replacen("https://", &format!("https://{}@", token), 1)
-    } else if url.starts_with("http://") {
-        // Replace http:// with http://token@
-        url.replacen("http://", &format!("http://{}@", token), 1)
-    } else {
-        url.to_string()
+        match self.tip()? {
+            Some(local_tip) if local_tip == remote_tip => Ok(()),
+            // Local commits that haven't been pushed yet
+            Some(local_tip) if self.repo.graph_descendant_of(local_tip, remote_tip)? => Ok(()),
+            Some(local_tip) if !self.repo.graph_descendant_of(remote_tip, local_tip)? => {
+                Err(GitBackupError::Diverged(format!(
+                    "remote main is at {} but the backup branch is at {}",
+                    remote_tip, local_tip
+                )))
+            }
+            _ => {
+                info!(
+                    "Fast-forwarding backup branch to remote main ({})",
+                    remote_tip
+                );
+                self.repo.reference(
+                    &self.branch,
+                    remote_tip,
+                    true,
+                    "backup: fast-forward to remote",
+                )?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Push the backup branch to the remote's main branch without forcing
+    fn push(&self, url: &str, token: Option<&str>) -> Result<(), GitBackupError> {
+        if self.tip()?.is_none() {
+            return Ok(());
+        }
+
+        let mut remote = self.repo.remote_anonymous(url)?;
+        let refspec = format!("{}:refs/heads/main", self.branch);
+        let mut rejection = None;
+        {
+            let mut callbacks = remote_callbacks(token);
+            callbacks.push_update_reference(|refname, status| {
+                if let Some(message) = status {
+                    rejection = Some(format!("{}: {}", refname, message));
+                }
+                Ok(())
+            });
+            let mut push_options = PushOptions::new();
+            push_options.remote_callbacks(callbacks);
+            remote.push(&[refspec.as_str()], Some(&mut push_options))?;
+        }
+
+        if let Some(rejection) = rejection {
+            return Err(GitBackupError::Diverged(format!(
+                "remote rejected the push ({})",
+                rejection
+            )));
+        }
+
+        info!("Pushed backup commits to remote repository");
+
+        Ok(())
     }
 }
 
-/// Dump a single object to a file in objdef format with meta filtering
-fn dump_object_to_file(
+/// Where the remote's main branch is fetched to before pushing
+const REMOTE_TRACKING_REF: &str = "refs/remotes/backup/main";
+
+/// Build remote callbacks that authenticate with the configured token or the SSH agent
+/// The token is handed to git directly rather than embedded in the URL or repository config
+fn remote_callbacks(token: Option<&str>) -> RemoteCallbacks<'_> {
+    let mut callbacks = RemoteCallbacks::new();
+    let mut attempted = false;
+    callbacks.credentials(move |_url, username_from_url, allowed_types| {
+        // libgit2 keeps asking while credentials are rejected, so only offer them once
+        if attempted {
+            return Err(git2::Error::from_str(
+                "Authentication with the git backup remote failed",
+            ));
+        }
+        attempted = true;
+
+        if let Some(token) =
+            token.filter(|_| allowed_types.contains(CredentialType::USER_PASS_PLAINTEXT))
+        {
+            return Cred::userpass_plaintext(username_from_url.unwrap_or("x-access-token"), token);
+        }
+        if allowed_types.contains(CredentialType::SSH_KEY) {
+            return Cred::ssh_key_from_agent(username_from_url.unwrap_or("git"));
+        }
+        Cred::default()
+    });
+    callbacks
+}
+
+/// Render a single object version in objdef format with meta filtering
+fn render_object(
     database: &DatabaseRef,
-    obj_info: &ObjectInfo,
-    work_dir: &Path,
+    object_name: &str,
+    version: u64,
+    meta_version: Option<u64>,
 ) -> Result<String, String> {
-    let object_name = &obj_info.name;
-
-    // Get the object SHA256 from refs using the specific version from the working index
+    // Get the object SHA256 from refs using the version recorded by the change
     let sha256 = database
         .refs()
-        .get_ref(VcsObjectType::MooObject, object_name, Some(obj_info.version))
-        .map_err(|e| format!("Failed to get ref for '{}' version {}: {}", object_name, obj_info.version, e))?
-        .ok_or_else(|| format!("Object '{}' version {} not found in refs", object_name, obj_info.version))?;
+        .get_ref(VcsObjectType::MooObject, object_name, Some(version))
+        .map_err(|e| format!("Failed to get ref for '{}' version {}: {}", object_name, version, e))?
+        .ok_or_else(|| format!("Object '{}' version {} not found in refs", object_name, version))?;
 
     // Get the object content (it's already in objdef format)
     let obj_content = database
@@ -199,12 +577,15 @@ fn dump_object_to_file(
         .map_err(|e| format!("Failed to get object content: {}", e))?
         .ok_or_else(|| format!("Object content not found for sha256: {}", sha256))?;
 
-    // Check if meta exists and apply filtering if needed
-    let final_content = match database
-        .refs()
-        .get_ref(VcsObjectType::MooMetaObject, object_name, None)
-        .map_err(|e| format!("Failed to check for meta: {}", e))?
-    {
+    // Check if meta existed at this point in history and apply filtering if needed
+    let meta_sha256 = match meta_version {
+        Some(meta_version) => database
+            .refs()
+            .get_ref(VcsObjectType::MooMetaObject, object_name, Some(meta_version))
+            .map_err(|e| format!("Failed to check for meta: {}", e))?,
+        None => None,
+    };
+    let final_content = match meta_sha256 {
         Some(meta_sha256) => {
             // Meta exists, load it
             let meta_yaml = database
@@ -226,7 +607,7 @@ fn dump_object_to_file(
                     meta.ignored_properties.len(),
                     meta.ignored_verbs.len()
                 );
-                apply_meta_filtering(&obj_content, &meta)?
+                apply_meta_filtering(database, &obj_content, &meta)?
             } else {
                 obj_content
             }
@@ -234,24 +615,15 @@ fn dump_object_to_file(
         None => obj_content,
     };
 
-    // Sanitize object name for filename
-    let filename = format!("{}.moo", sanitize_filename(object_name));
-    let file_path = work_dir.join(&filename);
-
-    // Write to file
-    let mut file = fs::File::create(&file_path)
-        .map_err(|e| format!("Failed to create file '{}': {}", filename, e))?;
-
-    file.write_all(final_content.as_bytes())
-        .map_err(|e| format!("Failed to write to file '{}': {}", filename, e))?;
-
-    info!("Wrote object '{}' to file '{}'", object_name, filename);
-
-    Ok(filename)
+    Ok(final_content)
 }
 
 /// Apply meta filtering to an object definition in objdef format
-fn apply_meta_filtering(obj_content: &str, meta: &crate::types::MooMetaObject) -> Result<String, String> {
+fn apply_meta_filtering(
+    database: &DatabaseRef,
+    obj_content: &str,
+    meta: &crate::types::MooMetaObject,
+) -> Result<String, String> {
     // Parse the object dump
     let mut context = ObjFileContext::new();
     let mut compiled_defs =
@@ -286,11 +658,10 @@ fn apply_meta_filtering(obj_content: &str, meta: &crate::types::MooMetaObject) -
     });
 
     // Re-dump the filtered object
-    let index_names = HashMap::new(); // Empty index for simple object names
-    let lines = dump_object(&index_names, &obj_def)
-        .map_err(|e| format!("Failed to dump filtered object: {}", e))?;
-
-    Ok(lines.join("\n"))
+    database
+        .objects()
+        .generate_object_dump(&obj_def)
+        .map_err(|e| format!("Failed to dump filtered object: {}", e))
 }
 
 /// Sanitize a filename by replacing invalid characters
@@ -347,124 +718,37 @@ fn cleanup_old_files(work_dir: &Path, current_files: &HashSet<String>) -> Result
     Ok(())
 }
 
-/// Commit and push changes to git
-fn git_commit_and_push(
-    work_dir: &Path,
-    repo_url: &str,
-    token: &Option<String>,
-) -> Result<(), String> {
-    let is_remote = repo_url.starts_with("http://") || repo_url.starts_with("https://");
-
-    // Configure git user if not already configured (needed for commits)
-    let _ = Command::new("git")
-        .current_dir(work_dir)
-        .args(&["config", "user.email", "vcs-backup@localhost"])
-        .output();
-
-    let _ = Command::new("git")
-        .current_dir(work_dir)
-        .args(&["config", "user.name", "VCS Backup"])
-        .output();
-
-    // Add all changes
-    let output = Command::new("git")
-        .current_dir(work_dir)
-        .args(&["add", "-A"])
-        .output()
-        .map_err(|e| format!("Failed to execute git add: {}", e))?;
-
-    if !output.status.success() {
-        return Err(format!(
-            "Git add failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
-    }
-
-    // Check if there are changes to commit
-    let status_output = Command::new("git")
-        .current_dir(work_dir)
-        .args(&["status", "--porcelain"])
-        .output()
-        .map_err(|e| format!("Failed to execute git status: {}", e))?;
-
-    if status_output.stdout.is_empty() {
-        info!("No changes to commit");
-        return Ok(());
-    }
-
-    // Commit changes
-    let timestamp = chrono::Utc::now().to_rfc3339();
-    let commit_message = format!("VCS backup: {}", timestamp);
-
-    let output = Command::new("git")
-        .current_dir(work_dir)
-        .args(&["commit", "-m", &commit_message])
-        .output()
-        .map_err(|e| format!("Failed to execute git commit: {}", e))?;
-
-    if !output.status.success() {
-        return Err(format!(
-            "Git commit failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
-    }
-
-    info!("Committed changes: {}", commit_message);
-
-    // Push if remote repository
-    if is_remote {
-        let push_url = if let Some(tok) = token {
-            inject_token_into_url(repo_url, tok)
-        } else {
-            repo_url.to_string()
-        };
-
-        // Set the remote URL (in case it changed or wasn't set)
-        let _ = Command::new("git")
-            .current_dir(work_dir)
-            .args(&["remote", "remove", "origin"])
-            .output();
-
-        let output = Command::new("git")
-            .current_dir(work_dir)
-            .args(&["remote", "add", "origin", &push_url])
-            .output()
-            .map_err(|e| format!("Failed to set git remote: {}", e))?;
-
-        if !output.status.success() {
-            warn!(
-                "Git remote add failed (might already exist): {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-        }
+/// Extract the change ID trailer from a commit message
+pub fn parse_change_id_trailer(message: &str) -> Option<String> {
+    let prefix = format!("{}: ", CHANGE_ID_TRAILER);
+    message
+        .lines()
+        .rev()
+        .find_map(|line| line.trim().strip_prefix(&prefix))
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+}
 
-        // Force push to main branch
-        let output = Command::new("git")
-            .current_dir(work_dir)
-            .args(&["push", "--force", "origin", "HEAD:main"])
-            .output()
-            .map_err(|e| format!("Failed to execute git push: {}", e))?;
-
-        if !output.status.success() {
-            // Try master branch as fallback
-            let output = Command::new("git")
-                .current_dir(work_dir)
-                .args(&["push", "--force", "origin", "HEAD:master"])
-                .output()
-                .map_err(|e| format!("Failed to execute git push: {}", e))?;
-
-            if !output.status.success() {
-                return Err(format!(
-                    "Git push failed: {}",
-                    String::from_utf8_lossy(&output.stderr)
-                ));
-            }
-        }
+/// Build the commit message for a change, ending with the change ID trailer
+pub fn change_commit_message(change: &Change) -> String {
+    let subject = if change.name.trim().is_empty() {
+        change.id.as_str()
+    } else {
+        change.name.trim()
+    };
 
-        info!("Force pushed changes to remote repository");
+    let mut message = format!("{}\n\n", subject);
+    if let Some(description) = change
+        .description
+        .as_deref()
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+    {
+        message.push_str(description);
+        message.push_str("\n\n");
     }
-
-    Ok(())
+    message.push_str(&format!("{}: {}\n", CHANGE_ID_TRAILER, change.id));
+    message
 }
 
 #[cfg(test)]
@@ -487,30 +771,12 @@ mod tests {
     }
 
     #[test]
-    fn test_inject_token_into_url() {
-        // HTTPS URLs
-        assert_eq!(
-            inject_token_into_url("https://github.com/user/repo.git", "my_token"),
-            "https://my_token@github.com/user/repo.git"
-        );
-
-        // HTTP URLs
-        assert_eq!(
-            inject_token_into_url("http://example.com/repo.git", "token123"),
-            "http://token123@example.com/repo.git"
-        );
-
-        // Non-HTTP URLs should remain unchanged
-        assert_eq!(
-            inject_token_into_url("git@github.com:user/repo.git", "token"),
-            "git@github.com:user/repo.git"
-        );
-
-        // Already has auth should be replaced
-        assert_eq!(
-            inject_token_into_url("https://oldtoken@github.com/user/repo.git", "newtoken"),
-            "https://newtoken@oldtoken@github.com/user/repo.git"
-        );
+    fn test_is_remote_url() {
+        assert!(is_remote_url("https://github.com/user/repo.git"));
+        assert!(is_remote_url("git@github.com:user/repo.git"));
+        assert!(is_remote_url("file:///srv/git/backup.git"));
+        assert!(!is_remote_url("/srv/git/backup.git"));
+        assert!(!is_remote_url("relative/backup"));
     }
 
     #[test]
@@ -532,4 +798,42 @@ mod tests {
     fn test_sanitize_filename_only_special_chars() {
         assert_eq!(sanitize_filename("$*?:"), "_");
     }
+
+    fn test_change(name: &str, description: Option<&str>) -> Change {
+        Change {
+            id: "abc123".to_string(),
+            name: name.to_string(),
+            description: description.map(str::to_string),
+            author: "wizard".to_string(),
+            timestamp: 1700000000,
+            status: crate::types::ChangeStatus::Merged,
+            added_objects: Vec::new(),
+            modified_objects: Vec::new(),
+            deleted_objects: Vec::new(),
+            renamed_objects: Vec::new(),
+            index_change_id: None,
+            verb_rename_hints: Vec::new(),
+            property_rename_hints: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_change_commit_message_round_trips_change_id() {
+        let message = change_commit_message(&test_change("fix-look", Some("Fixes the look verb")));
+        assert_eq!(
+            message,
+            "fix-look\n\nFixes the look verb\n\nVCS-Change-Id: abc123\n"
+        );
+        assert_eq!(parse_change_id_trailer(&message), Some("abc123".to_string()));
+
+        // Unnamed changes fall back to their ID as the subject
+        let message = change_commit_message(&test_change("", None));
+        assert_eq!(message, "abc123\n\nVCS-Change-Id: abc123\n");
+    }
+
+    #[test]
+    fn test_parse_change_id_trailer_missing() {
+        assert_eq!(parse_change_id_trailer("VCS backup: 2024-01-01"), None);
+        assert_eq!(parse_change_id_trailer(""), None);
+    }
 }
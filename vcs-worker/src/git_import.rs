@@ -0,0 +1,328 @@
+use crate::git_backup::{CHANGE_ID_TRAILER, parse_change_id_trailer};
+use git2::build::RepoBuilder;
+use git2::{Commit, Delta, DiffFile, DiffFindOptions, Oid, Repository, Sort};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// A change to a single `.moo` file, keyed by object name (the file stem)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileChange {
+    /// The file was added or its content changed
+    Written { name: String, content: String },
+    /// The file was removed
+    Deleted { name: String },
+    /// The file was renamed, possibly with edits
+    Renamed {
+        from: String,
+        to: String,
+        content: String,
+    },
+}
+
+/// A commit (or a plain directory) read from an import source
+#[derive(Debug, Clone)]
+pub struct SourceCommit {
+    /// The git commit hash, or the directory path for plain directories
+    pub commit: String,
+    /// The change ID recorded by a git backup, if any
+    pub change_id: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    pub author: String,
+    pub timestamp: u64,
+    pub files: Vec<FileChange>,
+}
+
+/// Read the history of `.moo` files from a git URL, a local git repository or a plain directory
+/// Commits are returned oldest first; commits that don't touch any `.moo` file are skipped
+pub fn read_import_source(source: &str) -> Result<Vec<SourceCommit>, String> {
+    if is_remote_source(source) {
+        let clone_dir = std::env::temp_dir().join(format!(
+            "vcs-git-import-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ));
+
+        info!("Cloning git repository {} into {:?}", source, clone_dir);
+        let result = clone_repository(source, &clone_dir).and_then(|repo| read_git_history(&repo));
+
+        if let Err(e) = fs::remove_dir_all(&clone_dir) {
+            warn!("Failed to remove import clone {:?}: {}", clone_dir, e);
+        }
+        return result;
+    }
+
+    let path = PathBuf::from(source);
+    if !path.is_dir() {
+        return Err(format!("Import source '{}' is not a directory", source));
+    }
+
+    if let Some(repo) = open_with_history(&path) {
+        read_git_history(&repo)
+    } else {
+        read_directory(&path).map(|commit| commit.into_iter().collect())
+    }
+}
+
+/// Whether the source must be cloned rather than read in place
+fn is_remote_source(source: &str) -> bool {
+    ["http://", "https://", "ssh://", "git://", "file://", "git@"]
+        .iter()
+        .any(|prefix| source.starts_with(prefix))
+}
+
+/// The local directory a source reads from, or None for a network URL
+pub fn local_source_path(source: &str) -> Option<PathBuf> {
+    if let Some(path) = source.strip_prefix("file://") {
+        return Some(PathBuf::from(path));
+    }
+    (!is_remote_source(source)).then(|| PathBuf::from(source))
+}
+
+/// Map a file path to the object name it was dumped from, if it is a `.moo` file
+pub fn object_name_from_path(path: &str) -> Option<String> {
+    let path = Path::new(path);
+    if path.extension().and_then(|e| e.to_str()) != Some("moo") {
+        return None;
+    }
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// Split a commit message into the change name and description, dropping the change ID trailer
+pub fn split_commit_message(message: &str) -> (String, Option<String>) {
+    let trailer_prefix = format!("{}: ", CHANGE_ID_TRAILER);
+    let mut lines = message
+        .lines()
+        .filter(|line| !line.trim().starts_with(&trailer_prefix));
+
+    let name = lines.next().unwrap_or_default().trim().to_string();
+    let description = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+
+    (name, Some(description).filter(|d| !d.is_empty()))
+}
+
+/// Clone a repository into a fresh directory
+fn clone_repository(url: &str, target: &Path) -> Result<Repository, String> {
+    RepoBuilder::new()
+        .clone(url, target)
+        .map_err(|e| format!("Failed to clone {}: {}", url, e.message()))
+}
+
+/// Open a directory as a git repository, if it is one with at least one commit
+fn open_with_history(path: &Path) -> Option<Repository> {
+    Repository::open(path)
+        .ok()
+        .filter(|repo| repo.head().is_ok())
+}
+
+/// Format a git error for the import response
+fn git_error(context: &str, e: git2::Error) -> String {
+    format!("{}: {}", context, e.message())
+}
+
+/// Read every first-parent commit of HEAD that touches `.moo` files
+fn read_git_history(repo: &Repository) -> Result<Vec<SourceCommit>, String> {
+    let mut revwalk = repo
+        .revwalk()
+        .map_err(|e| git_error("Failed to walk history", e))?;
+    revwalk
+        .push_head()
+        .map_err(|e| git_error("Failed to read HEAD", e))?;
+    revwalk
+        .simplify_first_parent()
+        .map_err(|e| git_error("Failed to walk history", e))?;
+    revwalk
+        .set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)
+        .map_err(|e| git_error("Failed to walk history", e))?;
+    let commits = revwalk
+        .collect::<Result<Vec<Oid>, _>>()
+        .map_err(|e| git_error("Failed to walk history", e))?;
+
+    info!("Reading {} commits from {:?}", commits.len(), repo.path());
+
+    let mut source_commits = Vec::new();
+    for oid in commits {
+        let commit = repo
+            .find_commit(oid)
+            .map_err(|e| git_error(&format!("Failed to read commit {}", oid), e))?;
+        let files = read_commit_files(repo, &commit)?;
+        if files.is_empty() {
+            continue;
+        }
+
+        let author = commit.author();
+        let timestamp = u64::try_from(author.when().seconds())
+            .map_err(|_| format!("Commit {} has no valid timestamp", oid))?;
+        let message = String::from_utf8_lossy(commit.message_bytes());
+        let (name, description) = split_commit_message(&message);
+
+        source_commits.push(SourceCommit {
+            commit: oid.to_string(),
+            change_id: parse_change_id_trailer(&message),
+            name,
+            description,
+            author: String::from_utf8_lossy(author.name_bytes())
+                .trim()
+                .to_string(),
+            timestamp,
+            files,
+        });
+    }
+
+    Ok(source_commits)
+}
+
+/// Read the `.moo` file changes a commit makes relative to its first parent
+fn read_commit_files(repo: &Repository, commit: &Commit) -> Result<Vec<FileChange>, String> {
+    let tree = commit
+        .tree()
+        .map_err(|e| git_error(&format!("Failed to read tree of {}", commit.id()), e))?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(
+            parent
+                .tree()
+                .map_err(|e| git_error(&format!("Failed to read tree of {}", parent.id()), e))?,
+        ),
+        Err(_) => None,
+    };
+
+    let mut diff = repo
+        .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+        .map_err(|e| git_error(&format!("Failed to diff {}", commit.id()), e))?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))
+        .map_err(|e| git_error(&format!("Failed to detect renames in {}", commit.id()), e))?;
+
+    let read_blob = |file: &DiffFile| -> Result<String, String> {
+        let path = file.path().unwrap_or(Path::new("")).display().to_string();
+        let blob = repo
+            .find_blob(file.id())
+            .map_err(|e| git_error(&format!("Failed to read '{}'", path), e))?;
+        String::from_utf8(blob.content().to_vec())
+            .map_err(|e| format!("File '{}' is not valid UTF-8: {}", path, e))
+    };
+    let name_of = |file: &DiffFile| {
+        file.path()
+            .and_then(|p| p.to_str())
+            .and_then(object_name_from_path)
+    };
+
+    let mut files = Vec::new();
+    for delta in diff.deltas() {
+        let (old_file, new_file) = (delta.old_file(), delta.new_file());
+        match delta.status() {
+            Delta::Renamed | Delta::Copied => {
+                let old_name = name_of(&old_file);
+                let is_rename = delta.status() == Delta::Renamed;
+                let Some(new_name) = name_of(&new_file) else {
+                    // Renamed away from `.moo`, so the object is gone
+                    if let (Some(name), true) = (old_name, is_rename) {
+                        files.push(FileChange::Deleted { name });
+                    }
+                    continue;
+                };
+                let content = read_blob(&new_file)?;
+                match old_name {
+                    Some(from) if is_rename => files.push(FileChange::Renamed {
+                        from,
+                        to: new_name,
+                        content,
+                    }),
+                    _ => files.push(FileChange::Written {
+                        name: new_name,
+                        content,
+                    }),
+                }
+            }
+            Delta::Deleted => {
+                if let Some(name) = name_of(&old_file) {
+                    files.push(FileChange::Deleted { name });
+                }
+            }
+            Delta::Added | Delta::Modified | Delta::Typechange => {
+                if let Some(name) = name_of(&new_file) {
+                    files.push(FileChange::Written {
+                        name,
+                        content: read_blob(&new_file)?,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(files)
+}
+
+/// Read the `.moo` files of a plain directory as a single commit
+fn read_directory(path: &Path) -> Result<Option<SourceCommit>, String> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(path)
+        .map_err(|e| format!("Failed to read directory {:?}: {}", path, e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file())
+        .collect();
+    entries.sort();
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let Some(name) = entry.to_str().and_then(object_name_from_path) else {
+            continue;
+        };
+        let content =
+            fs::read_to_string(&entry).map_err(|e| format!("Failed to read {:?}: {}", entry, e))?;
+        files.push(FileChange::Written { name, content });
+    }
+
+    if files.is_empty() {
+        return Ok(None);
+    }
+
+    info!("Read {} objects from directory {:?}", files.len(), path);
+
+    Ok(Some(SourceCommit {
+        commit: path.display().to_string(),
+        change_id: None,
+        name: format!("Import from {}", path.display()),
+        description: None,
+        author: String::new(),
+        timestamp: crate::util::current_unix_timestamp(),
+        files,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_object_name_from_path() {
+        assert_eq!(
+            object_name_from_path("player.moo"),
+            Some("player".to_string())
+        );
+        assert_eq!(
+            object_name_from_path("objects/#1.moo"),
+            Some("#1".to_string())
+        );
+        assert_eq!(object_name_from_path("README.md"), None);
+        assert_eq!(object_name_from_path(".moo"), None);
+    }
+
+    #[test]
+    fn test_split_commit_message() {
+        let (name, description) =
+            split_commit_message("fix-look\n\nFixes the look verb\n\nVCS-Change-Id: abc123\n");
+        assert_eq!(name, "fix-look");
+        assert_eq!(description, Some("Fixes the look verb".to_string()));
+
+        let (name, description) = split_commit_message("Edited in a text editor\n");
+        assert_eq!(name, "Edited in a text editor");
+        assert_eq!(description, None);
+    }
+}
@@ -1,8 +1,12 @@
 // Public modules for integration tests and library usage
+pub mod clone_pack;
 pub mod config;
 pub mod database;
 pub mod git_backup;
+pub mod git_import;
+pub mod local_work;
 pub mod object_diff;
+pub mod object_merge;
 pub mod operations;
 pub mod providers;
 pub mod router;
@@ -0,0 +1,320 @@
+//! Preservation of unmerged local work across clone and index/update
+//!
+//! Both operations replace the merged history with the remote's, and a clone clears the
+//! objects, refs and index partitions outright. [`LocalWork::capture`] takes a copy of
+//! every unmerged change in the index and workspace, together with the refs and blobs
+//! they record, before that happens. [`LocalWork::restore`] puts them back afterwards,
+//! rebased onto the new index tip, and reports what happened to each change.
+
+use moor_var::{Var, v_list, v_map, v_str};
+use std::collections::{HashMap, HashSet};
+use tracing::{info, warn};
+
+use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::object_merge::{ObjectConflict, load_merged_changes, rebase_change};
+use crate::providers::index::IndexProvider;
+use crate::providers::objects::ObjectsProvider;
+use crate::providers::refs::RefsProvider;
+use crate::providers::workspace::WorkspaceProvider;
+use crate::types::{Change, ChangeStatus, ObjectInfo};
+
+/// Where a captured change was stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Location {
+    Index,
+    Workspace,
+}
+
+/// Unmerged changes with the refs and blobs they record, captured before an import
+#[derive(Debug)]
+pub struct LocalWork {
+    /// Unmerged index changes (in index order) followed by workspace changes
+    changes: Vec<(Location, Change)>,
+    /// Every object version the changes record, and the hash it pointed at
+    refs: HashMap<ObjectInfo, String>,
+    /// Blob contents by hash
+    objects: HashMap<String, String>,
+}
+
+/// What became of a preserved change once it was restored
+#[derive(Debug, Clone)]
+pub struct PreservedChange {
+    pub change_id: String,
+    pub name: String,
+    /// "rebased", "up_to_date", "conflict", "kept" (under review, left as is) or
+    /// "merged" (the remote already merged it, so the local copy was dropped)
+    pub status: &'static str,
+    /// Conflicts that stopped the change from being rebased
+    pub conflicts: Vec<ObjectConflict>,
+}
+
+impl PreservedChange {
+    /// Convert to a MOO map
+    pub fn to_moo_var(&self) -> Var {
+        let conflicts: Vec<Var> = self.conflicts.iter().map(|c| c.to_moo_var()).collect();
+        v_map(&[
+            (v_str("change_id"), v_str(&self.change_id)),
+            (v_str("name"), v_str(&self.name)),
+            (v_str("status"), v_str(self.status)),
+            (v_str("conflicts"), v_list(&conflicts)),
+        ])
+    }
+
+    /// Short human-readable description, used in text responses
+    pub fn summary(&self) -> String {
+        if self.conflicts.is_empty() {
+            format!("'{}' ({})", self.name, self.status)
+        } else {
+            let objects: Vec<String> = self.conflicts.iter().map(|c| c.summary()).collect();
+            format!("'{}' ({}: {})", self.name, self.status, objects.join(", "))
+        }
+    }
+}
+
+impl LocalWork {
+    /// Capture every unmerged change in the index and workspace, and the refs and blobs
+    /// of the object versions they record
+    pub fn capture(database: &DatabaseRef) -> Result<Self, ObjectsTreeError> {
+        let mut changes = Vec::new();
+        for change_id in database
+            .index()
+            .get_change_order()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+        {
+            let Some(change) = database
+                .index()
+                .get_change(&change_id)
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+            else {
+                continue;
+            };
+            if change.status != ChangeStatus::Merged {
+                changes.push((Location::Index, change));
+            }
+        }
+        for change in database
+            .workspace()
+            .list_all_workspace_changes()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+        {
+            changes.push((Location::Workspace, change));
+        }
+
+        let mut refs = HashMap::new();
+        let mut objects = HashMap::new();
+        for (_, change) in &changes {
+            for obj in recorded_versions(change) {
+                if refs.contains_key(obj) {
+                    continue;
+                }
+                let Some(hash) = database
+                    .refs()
+                    .get_ref(obj.object_type, &obj.name, Some(obj.version))
+                    .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+                else {
+                    warn!(
+                        "Change '{}' records version {} of '{}', which has no ref - not preserving it",
+                        change.id, obj.version, obj.name
+                    );
+                    continue;
+                };
+                if !objects.contains_key(&hash) {
+                    match database
+                        .objects()
+                        .get(&hash)
+                        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+                    {
+                        Some(content) => {
+                            objects.insert(hash.clone(), content);
+                        }
+                        None => warn!(
+                            "Object '{}' for '{}' version {} is missing - not preserving it",
+                            hash, obj.name, obj.version
+                        ),
+                    }
+                }
+                refs.insert(obj.clone(), hash);
+            }
+        }
+
+        if !changes.is_empty() {
+            info!(
+                "Captured {} unmerged changes with {} refs and {} objects",
+                changes.len(),
+                refs.len(),
+                objects.len()
+            );
+        }
+
+        Ok(Self {
+            changes,
+            refs,
+            objects,
+        })
+    }
+
+    /// Restore the captured work on top of the imported index
+    ///
+    /// Changes the remote has merged are dropped. For the rest, blobs are stored again and
+    /// refs put back; a version the remote now uses for other content is moved to the next
+    /// free version and the change updated to match. Local and idle changes are then rebased
+    /// onto the new index tip, and one that conflicts is kept as it was and reported.
+    /// Unmerged index changes go back after the merged history, so the local change stays
+    /// on top.
+    pub fn restore(self, database: &DatabaseRef) -> Result<Vec<PreservedChange>, ObjectsTreeError> {
+        if self.changes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let merged_ids: HashSet<String> = load_merged_changes(database)?
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+
+        // A change the remote has merged (one submitted from here) arrives with the import
+        let mut preserved = Vec::new();
+        let mut changes = Vec::new();
+        for (location, change) in self.changes {
+            if !merged_ids.contains(&change.id) {
+                changes.push((location, change));
+                continue;
+            }
+            if location == Location::Workspace {
+                database
+                    .workspace()
+                    .delete_workspace_change(&change.id)
+                    .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+            }
+            info!(
+                "Change '{}' ({}) was merged upstream - dropping the local copy",
+                change.name, change.id
+            );
+            preserved.push(PreservedChange {
+                change_id: change.id,
+                name: change.name,
+                status: "merged",
+                conflicts: Vec::new(),
+            });
+        }
+
+        // Put back the refs and blobs of the rest, moving a version the remote now uses
+        // for other content to the next free version
+        let mut moved = HashMap::new();
+        let mut restored = HashSet::new();
+        for (_, change) in &changes {
+            for obj in recorded_versions(change) {
+                let Some(hash) = self.refs.get(obj) else {
+                    continue;
+                };
+                if !restored.insert(obj.clone()) {
+                    continue;
+                }
+                if let Some(content) = self.objects.get(hash) {
+                    database
+                        .objects()
+                        .store(hash, content)
+                        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+                }
+
+                let current = database
+                    .refs()
+                    .get_ref(obj.object_type, &obj.name, Some(obj.version))
+                    .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+                let version = match current {
+                    Some(existing) if existing == *hash => continue,
+                    Some(_) => {
+                        let version = database
+                            .refs()
+                            .get_next_version(obj.object_type, &obj.name)
+                            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+                        info!(
+                            "Version {} of '{}' is taken upstream - moving local work to version {}",
+                            obj.version, obj.name, version
+                        );
+                        moved.insert(obj.clone(), version);
+                        version
+                    }
+                    None => obj.version,
+                };
+                database
+                    .refs()
+                    .update_ref(obj.object_type, &obj.name, version, hash)
+                    .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+            }
+        }
+
+        let mut index_ids = Vec::new();
+        for (location, mut change) in changes {
+            for obj in change
+                .added_objects
+                .iter_mut()
+                .chain(change.modified_objects.iter_mut())
+                .chain(change.renamed_objects.iter_mut().map(|r| &mut r.to))
+            {
+                if let Some(version) = moved.get(&*obj) {
+                    obj.version = *version;
+                }
+            }
+
+            // Changes under review are left for their reviewers
+            let (status, conflicts) = match change.status {
+                ChangeStatus::Local | ChangeStatus::Idle => {
+                    let outcome = rebase_change(database, &mut change)?;
+                    (outcome.status, outcome.conflicts)
+                }
+                _ => ("kept", Vec::new()),
+            };
+
+            match location {
+                Location::Index => {
+                    database
+                        .index()
+                        .store_change(&change)
+                        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+                    index_ids.push(change.id.clone());
+                }
+                Location::Workspace => {
+                    database
+                        .workspace()
+                        .update_workspace_change(&change)
+                        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+                }
+            }
+
+            info!(
+                "Preserved change '{}' ({}) with status '{}'",
+                change.name, change.id, status
+            );
+            preserved.push(PreservedChange {
+                change_id: change.id,
+                name: change.name,
+                status,
+                conflicts,
+            });
+        }
+
+        if !index_ids.is_empty() {
+            let mut order = database
+                .index()
+                .get_change_order()
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+            order.retain(|id| !index_ids.contains(id));
+            order.extend(index_ids);
+            database
+                .index()
+                .set_change_order(order)
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        }
+
+        Ok(preserved)
+    }
+}
+
+/// Object versions a change records content for
+fn recorded_versions(change: &Change) -> impl Iterator<Item = &ObjectInfo> {
+    change
+        .added_objects
+        .iter()
+        .chain(&change.modified_objects)
+        .chain(change.renamed_objects.iter().map(|r| &r.to))
+}
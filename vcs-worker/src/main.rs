@@ -10,10 +10,14 @@ use tokio::signal::unix::{SignalKind, signal};
 use tracing::{error, info};
 use uuid::Uuid;
 
+mod clone_pack;
 mod config;
 mod database;
 mod git_backup;
+mod git_import;
+mod local_work;
 mod object_diff;
+mod object_merge;
 mod operations;
 mod providers;
 mod router;
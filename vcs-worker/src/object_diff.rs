@@ -1,9 +1,11 @@
 use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::object_merge::{RecordedObjectState, load_object_definition};
 use crate::providers::index::IndexProvider;
 use crate::providers::objects::ObjectsProvider;
 use crate::providers::refs::RefsProvider;
 use crate::types::{Change, VcsObjectType};
-use moor_compiler::ObjectDefinition;
+use moor_compiler::{ObjectDefinition, program_to_tree, to_literal, unparse};
+use moor_var::program::ProgramType;
 use moor_var::{Var, v_map, v_str, v_objid};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -38,6 +40,30 @@ pub struct ObjectChange {
     pub meta_unignored_properties: HashSet<String>,
     /// Meta: Verbs that were unignored in this change
     pub meta_unignored_verbs: HashSet<String>,
+    /// Unified diffs of decompiled verb code, keyed by verb name (only filled when requested)
+    #[serde(default)]
+    pub verb_diffs: HashMap<String, Vec<String>>,
+    /// Before/after literal values of changed properties (only filled when requested)
+    #[serde(default)]
+    pub prop_diffs: HashMap<String, PropertyValueChange>,
+}
+
+/// Before and after literal values of a single property
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PropertyValueChange {
+    /// Literal value before the change (None if the property was added or had no value)
+    pub before: Option<String>,
+    /// Literal value after the change (None if the property was deleted or has no value)
+    pub after: Option<String>,
+}
+
+/// A single line in a line-level diff
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffLine {
+    Added(String),
+    Removed(String),
+    Changed(String, String), // (old, new)
+    Unchanged(String),
 }
 
 /// Represents a complete set of object changes/deltas for communication to MOO
@@ -72,6 +98,8 @@ impl ObjectChange {
             meta_ignored_verbs: HashSet::new(),
             meta_unignored_properties: HashSet::new(),
             meta_unignored_verbs: HashSet::new(),
+            verb_diffs: HashMap::new(),
+            prop_diffs: HashMap::new(),
         }
     }
 
@@ -111,6 +139,25 @@ impl ObjectChange {
             meta_ignored_verbs: self.meta_unignored_verbs.clone(),
             meta_unignored_properties: self.meta_ignored_properties.clone(),
             meta_unignored_verbs: self.meta_ignored_verbs.clone(),
+            // Line diffs run the other way: swap removed ↔ added lines and before ↔ after
+            verb_diffs: self
+                .verb_diffs
+                .iter()
+                .map(|(verb, lines)| (verb.clone(), invert_unified_diff(lines)))
+                .collect(),
+            prop_diffs: self
+                .prop_diffs
+                .iter()
+                .map(|(prop, change)| {
+                    (
+                        prop.clone(),
+                        PropertyValueChange {
+                            before: change.after.clone(),
+                            after: change.before.clone(),
+                        },
+                    )
+                })
+                .collect(),
         }
     }
 
@@ -228,6 +275,41 @@ impl ObjectChange {
             pairs.push((v_str("meta"), v_map(&meta_pairs)));
         }
 
+        // Line-level details, only present when requested
+        if !self.verb_diffs.is_empty() {
+            let verb_diffs_map: Vec<(Var, Var)> = self
+                .verb_diffs
+                .iter()
+                .map(|(verb, lines)| {
+                    let lines_list: Vec<Var> = lines.iter().map(|l| v_str(l)).collect();
+                    (v_str(verb), moor_var::v_list(&lines_list))
+                })
+                .collect();
+            pairs.push((v_str("verb_diffs"), v_map(&verb_diffs_map)));
+        }
+        if !self.prop_diffs.is_empty() {
+            let prop_diffs_map: Vec<(Var, Var)> = self
+                .prop_diffs
+                .iter()
+                .map(|(prop, change)| (v_str(prop), change.to_moo_var()))
+                .collect();
+            pairs.push((v_str("prop_diffs"), v_map(&prop_diffs_map)));
+        }
+
+        v_map(&pairs)
+    }
+}
+
+impl PropertyValueChange {
+    /// Convert this PropertyValueChange to a MOO v_map, omitting missing sides
+    pub fn to_moo_var(&self) -> Var {
+        let mut pairs = Vec::new();
+        if let Some(before) = &self.before {
+            pairs.push((v_str("before"), v_str(before)));
+        }
+        if let Some(after) = &self.after {
+            pairs.push((v_str("after"), v_str(after)));
+        }
         v_map(&pairs)
     }
 }
@@ -246,6 +328,11 @@ impl ObjectDiffModel {
 
     /// Convert this ObjectDiffModel to a MOO v_map
     pub fn to_moo_var(&self) -> Var {
+        v_map(&self.to_moo_pairs())
+    }
+
+    /// The key/value pairs of the MOO map, for responses that extend the diff
+    pub fn to_moo_pairs(&self) -> Vec<(Var, Var)> {
         let mut pairs = Vec::new();
 
         // objects_renamed - use helper to convert object IDs to v_obj if they're numeric
@@ -287,7 +374,7 @@ impl ObjectDiffModel {
             .collect();
         pairs.push((v_str("changes"), moor_var::v_list(&changes_list)));
 
-        v_map(&pairs)
+        pairs
     }
 
     /// Add a renamed object to the model
@@ -461,19 +548,7 @@ pub fn compare_object_versions(
         );
     } else {
         // No baseline version - this is a new object, mark all as added
-        for verb in &local_def.verbs {
-            for verb_name in &verb.names {
-                object_change.verbs_added.insert(verb_name.as_string());
-            }
-        }
-        for prop_def in &local_def.property_definitions {
-            object_change.props_added.insert(prop_def.name.as_string());
-        }
-        for prop_override in &local_def.property_overrides {
-            object_change
-                .props_added
-                .insert(prop_override.name.as_string());
-        }
+        mark_all_members_added(&local_def, &mut object_change);
     }
 
     // Add meta changes to the object change
@@ -482,6 +557,23 @@ pub fn compare_object_versions(
     Ok(object_change)
 }
 
+/// Mark every verb and property of a definition as added (used when there is no baseline)
+fn mark_all_members_added(local_def: &ObjectDefinition, object_change: &mut ObjectChange) {
+    for verb in &local_def.verbs {
+        for verb_name in &verb.names {
+            object_change.verbs_added.insert(verb_name.as_string());
+        }
+    }
+    for prop_def in &local_def.property_definitions {
+        object_change.props_added.insert(prop_def.name.as_string());
+    }
+    for prop_override in &local_def.property_overrides {
+        object_change
+            .props_added
+            .insert(prop_override.name.as_string());
+    }
+}
+
 /// Compare meta object versions to determine what meta changes occurred
 /// Updates the object_change with meta_ignored_* and meta_unignored_* fields
 pub fn compare_meta_versions(
@@ -868,6 +960,367 @@ pub fn property_overrides_differ(
     baseline.value != local.value || baseline.perms_update != local.perms_update
 }
 
+/// Compute a line-level diff using the longest common subsequence of the two inputs
+pub fn compute_line_diff(old_lines: &[String], new_lines: &[String]) -> Vec<DiffLine> {
+    let mut diff_lines = Vec::new();
+
+    let old_len = old_lines.len();
+    let new_len = new_lines.len();
+
+    // Build LCS (Longest Common Subsequence) table
+    let mut lcs = vec![vec![0; new_len + 1]; old_len + 1];
+
+    for i in 1..=old_len {
+        for j in 1..=new_len {
+            if old_lines[i - 1] == new_lines[j - 1] {
+                lcs[i][j] = lcs[i - 1][j - 1] + 1;
+            } else {
+                lcs[i][j] = std::cmp::max(lcs[i - 1][j], lcs[i][j - 1]);
+            }
+        }
+    }
+
+    // Backtrack to build diff
+    let mut i = old_len;
+    let mut j = new_len;
+
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 && old_lines[i - 1] == new_lines[j - 1] {
+            diff_lines.push(DiffLine::Unchanged(old_lines[i - 1].clone()));
+            i -= 1;
+            j -= 1;
+        } else if j > 0 && (i == 0 || lcs[i][j - 1] >= lcs[i - 1][j]) {
+            diff_lines.push(DiffLine::Added(new_lines[j - 1].clone()));
+            j -= 1;
+        } else if i > 0 {
+            diff_lines.push(DiffLine::Removed(old_lines[i - 1].clone()));
+            i -= 1;
+        }
+    }
+
+    diff_lines.reverse();
+    diff_lines
+}
+
+/// Number of context lines around each hunk in unified diffs
+pub const UNIFIED_DIFF_CONTEXT: usize = 3;
+
+/// Render a unified diff (`@@ -a,b +c,d @@` hunks with ` `, `-` and `+` prefixed lines)
+/// Returns an empty list when the inputs are identical
+pub fn unified_diff(old_lines: &[String], new_lines: &[String], context: usize) -> Vec<String> {
+    let diff_lines = compute_line_diff(old_lines, new_lines);
+
+    // Line numbers in the old and new text at each diff position
+    let mut old_pos = Vec::with_capacity(diff_lines.len() + 1);
+    let mut new_pos = Vec::with_capacity(diff_lines.len() + 1);
+    let (mut old_line, mut new_line) = (0usize, 0usize);
+    for line in &diff_lines {
+        old_pos.push(old_line);
+        new_pos.push(new_line);
+        match line {
+            DiffLine::Added(_) => new_line += 1,
+            DiffLine::Removed(_) => old_line += 1,
+            DiffLine::Changed(_, _) | DiffLine::Unchanged(_) => {
+                old_line += 1;
+                new_line += 1;
+            }
+        }
+    }
+    old_pos.push(old_line);
+    new_pos.push(new_line);
+
+    // Group changed positions (plus context) into hunk ranges, merging overlaps
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for (idx, line) in diff_lines.iter().enumerate() {
+        if matches!(line, DiffLine::Unchanged(_)) {
+            continue;
+        }
+        let start = idx.saturating_sub(context);
+        let end = (idx + context + 1).min(diff_lines.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    let mut output = Vec::new();
+    for (start, end) in ranges {
+        let old_count = old_pos[end] - old_pos[start];
+        let new_count = new_pos[end] - new_pos[start];
+        // An empty side points at the line before the hunk
+        let old_start = if old_count == 0 { old_pos[start] } else { old_pos[start] + 1 };
+        let new_start = if new_count == 0 { new_pos[start] } else { new_pos[start] + 1 };
+        output.push(format!("@@ -{old_start},{old_count} +{new_start},{new_count} @@"));
+
+        for line in &diff_lines[start..end] {
+            match line {
+                DiffLine::Added(l) => output.push(format!("+{l}")),
+                DiffLine::Removed(l) => output.push(format!("-{l}")),
+                DiffLine::Changed(old, new) => {
+                    output.push(format!("-{old}"));
+                    output.push(format!("+{new}"));
+                }
+                DiffLine::Unchanged(l) => output.push(format!(" {l}")),
+            }
+        }
+    }
+
+    output
+}
+
+/// Invert a unified diff so it describes the reverse change
+fn invert_unified_diff(lines: &[String]) -> Vec<String> {
+    lines
+        .iter()
+        .map(|line| {
+            if let Some(header) = line
+                .strip_prefix("@@ -")
+                .and_then(|rest| rest.strip_suffix(" @@"))
+            {
+                // "@@ -a,b +c,d @@" becomes "@@ -c,d +a,b @@"
+                if let Some((old, new)) = header.split_once(" +") {
+                    return format!("@@ -{new} +{old} @@");
+                }
+            }
+            if let Some(rest) = line.strip_prefix('+') {
+                format!("-{rest}")
+            } else if let Some(rest) = line.strip_prefix('-') {
+                format!("+{rest}")
+            } else {
+                line.clone()
+            }
+        })
+        .collect()
+}
+
+/// Decompile a verb into source code lines
+pub fn decompile_verb(verb: &moor_compiler::ObjVerbDef) -> Result<Vec<String>, ObjectsTreeError> {
+    let ProgramType::MooR(program) = &verb.program;
+
+    if program.main_vector().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let ast = program_to_tree(program).map_err(|e| {
+        ObjectsTreeError::SerializationError(format!("Failed to decompile verb: {}", e))
+    })?;
+
+    let lines = unparse(&ast, false, true).map_err(|e| {
+        ObjectsTreeError::SerializationError(format!("Failed to unparse verb: {}", e))
+    })?;
+
+    Ok(lines)
+}
+
+/// Fill in the unified verb diffs and property before/after values of an ObjectChange
+/// whose verb and property lists were computed by comparing `baseline` against `local`
+pub fn add_line_diffs(
+    object_change: &mut ObjectChange,
+    baseline: Option<&ObjectDefinition>,
+    local: &ObjectDefinition,
+) -> Result<(), ObjectsTreeError> {
+    fn find_verb<'a>(
+        def: Option<&'a ObjectDefinition>,
+        name: &str,
+    ) -> Option<&'a moor_compiler::ObjVerbDef> {
+        def?.verbs
+            .iter()
+            .find(|v| v.names.iter().any(|n| n.as_string() == name))
+    }
+    let verb_code = |verb: Option<&moor_compiler::ObjVerbDef>| {
+        verb.map(decompile_verb)
+            .transpose()
+            .map(Option::unwrap_or_default)
+    };
+
+    // Verbs keep their name on both sides, except renames which map old -> new
+    let mut verb_pairs: Vec<(String, String)> = object_change
+        .verbs_modified
+        .iter()
+        .chain(&object_change.verbs_added)
+        .chain(&object_change.verbs_deleted)
+        .map(|name| (name.clone(), name.clone()))
+        .collect();
+    verb_pairs.extend(
+        object_change
+            .verbs_renamed
+            .iter()
+            .map(|(old, new)| (old.clone(), new.clone())),
+    );
+
+    for (old_name, new_name) in verb_pairs {
+        let old_code = verb_code(find_verb(baseline, &old_name))?;
+        let new_code = verb_code(find_verb(Some(local), &new_name))?;
+        let diff = unified_diff(&old_code, &new_code, UNIFIED_DIFF_CONTEXT);
+        if !diff.is_empty() {
+            object_change.verb_diffs.insert(new_name, diff);
+        }
+    }
+
+    // Property values come from either a definition or an override
+    let prop_literal = |def: Option<&ObjectDefinition>, name: &str| -> Option<String> {
+        let def = def?;
+        def.property_definitions
+            .iter()
+            .find(|p| p.name.as_string() == name)
+            .and_then(|p| p.value.as_ref())
+            .or_else(|| {
+                def.property_overrides
+                    .iter()
+                    .find(|p| p.name.as_string() == name)
+                    .and_then(|p| p.value.as_ref())
+            })
+            .map(to_literal)
+    };
+
+    let mut prop_pairs: Vec<(String, String)> = object_change
+        .props_modified
+        .iter()
+        .chain(&object_change.props_added)
+        .chain(&object_change.props_deleted)
+        .map(|name| (name.clone(), name.clone()))
+        .collect();
+    prop_pairs.extend(
+        object_change
+            .props_renamed
+            .iter()
+            .map(|(old, new)| (old.clone(), new.clone())),
+    );
+
+    for (old_name, new_name) in prop_pairs {
+        let change = PropertyValueChange {
+            before: prop_literal(baseline, &old_name),
+            after: prop_literal(Some(local), &new_name),
+        };
+        if change.before != change.after {
+            object_change.prop_diffs.insert(new_name, change);
+        }
+    }
+
+    Ok(())
+}
+
+/// Fill in line-level details for every object of a diff model built from `change`
+/// Each object's version in the change is compared against the previous version,
+/// the same baseline used by compare_object_versions
+pub fn add_line_diffs_for_change(
+    database: &DatabaseRef,
+    diff_model: &mut ObjectDiffModel,
+    change: &Change,
+) -> Result<(), ObjectsTreeError> {
+    for obj_info in change
+        .added_objects
+        .iter()
+        .chain(change.modified_objects.iter())
+        .filter(|o| o.object_type == VcsObjectType::MooObject)
+    {
+        let obj_name = obj_id_to_object_name(&obj_info.name, Some(&obj_info.name));
+        let Some(object_change) = diff_model
+            .changes
+            .iter_mut()
+            .find(|c| c.obj_id == obj_name)
+        else {
+            continue;
+        };
+
+        let Some(local) =
+            load_object_definition(database, &obj_info.name, obj_info.version)?
+        else {
+            continue;
+        };
+        let baseline = match obj_info.version.saturating_sub(1) {
+            0 => None,
+            version => load_object_definition(database, &obj_info.name, version)?,
+        };
+
+        add_line_diffs(object_change, baseline.as_ref(), &local)?;
+    }
+
+    Ok(())
+}
+
+/// Build an ObjectDiffModel, with line-level details, comparing a change against the
+/// object states recorded by the merged changes up to a baseline change.
+/// Objects missing from the baseline are reported as added.
+pub fn build_object_diff_against_baseline(
+    database: &DatabaseRef,
+    change: &Change,
+    baseline_states: &HashMap<String, RecordedObjectState>,
+) -> Result<ObjectDiffModel, ObjectsTreeError> {
+    let mut diff_model = ObjectDiffModel::new();
+    let verb_hints_ref = Some(change.verb_rename_hints.as_slice());
+    let prop_hints_ref = Some(change.property_rename_hints.as_slice());
+
+    for obj_info in change
+        .added_objects
+        .iter()
+        .chain(change.modified_objects.iter())
+        .filter(|o| o.object_type == VcsObjectType::MooObject)
+    {
+        let obj_name = obj_id_to_object_name(&obj_info.name, Some(&obj_info.name));
+        let local = load_object_definition(database, &obj_info.name, obj_info.version)?
+            .ok_or_else(|| {
+                ObjectsTreeError::SerializationError(format!(
+                    "Version {} of object '{}' not found",
+                    obj_info.version, obj_info.name
+                ))
+            })?;
+
+        // Objects renamed by this change are recorded under their old name at the baseline
+        let baseline_name = change
+            .renamed_objects
+            .iter()
+            .find(|r| r.to.object_type == VcsObjectType::MooObject && r.to.name == obj_info.name)
+            .map(|r| r.from.name.as_str())
+            .unwrap_or(&obj_info.name);
+        let baseline = match baseline_states.get(baseline_name) {
+            Some(RecordedObjectState::Version(version)) => {
+                load_object_definition(database, baseline_name, *version)?
+            }
+            _ => None,
+        };
+
+        let mut object_change = ObjectChange::new(obj_name.clone());
+        if let Some(baseline_def) = &baseline {
+            diff_model.add_object_modified(obj_name);
+            compare_object_definitions_with_meta(
+                baseline_def,
+                &local,
+                &mut object_change,
+                Some(database),
+                Some(&obj_info.name),
+                verb_hints_ref,
+                prop_hints_ref,
+            );
+        } else {
+            diff_model.add_object_added(obj_name);
+            mark_all_members_added(&local, &mut object_change);
+        }
+        add_line_diffs(&mut object_change, baseline.as_ref(), &local)?;
+        diff_model.add_object_change(object_change);
+    }
+
+    for obj_info in change
+        .deleted_objects
+        .iter()
+        .filter(|o| o.object_type == VcsObjectType::MooObject)
+    {
+        let obj_name = obj_id_to_object_name(&obj_info.name, Some(&obj_info.name));
+        diff_model.add_object_deleted(obj_name);
+    }
+
+    for renamed in change.renamed_objects.iter().filter(|r| {
+        r.from.object_type == VcsObjectType::MooObject
+            && r.to.object_type == VcsObjectType::MooObject
+    }) {
+        let from_name = obj_id_to_object_name(&renamed.from.name, Some(&renamed.from.name));
+        let to_name = obj_id_to_object_name(&renamed.to.name, Some(&renamed.to.name));
+        diff_model.add_object_renamed(from_name, to_name);
+    }
+
+    Ok(diff_model)
+}
+
 /// Build an ObjectDiffModel by comparing a change against the compiled state
 /// This is the shared logic used by approve and status operations
 pub fn build_object_diff_from_change(
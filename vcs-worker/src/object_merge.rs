@@ -0,0 +1,857 @@
+use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::object_diff::{
+    ObjectChange, compare_object_definitions_with_meta, object_id_to_var,
+    property_definitions_differ, property_overrides_differ, verbs_differ,
+};
+use crate::providers::index::IndexProvider;
+use crate::providers::objects::ObjectsProvider;
+use crate::providers::refs::RefsProvider;
+use crate::types::{Change, ChangeStatus, ObjectInfo, VcsObjectType};
+use moor_compiler::ObjectDefinition;
+use moor_var::{Var, v_list, v_map, v_str};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// State of an object as recorded by a sequence of merged changes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordedObjectState {
+    /// The object exists at this version
+    Version(u64),
+    /// The object was deleted
+    Deleted,
+    /// The object was renamed to another name
+    RenamedTo(String),
+}
+
+/// Conflicts found while three-way merging a single object
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ObjectConflict {
+    /// Object ID - either the OID as string (e.g., "#4") or object name
+    pub obj_id: String,
+    /// Object-level conflict (e.g. modified locally but deleted upstream)
+    pub reason: Option<String>,
+    /// Object attributes (parent, owner, location, name, flags) changed on both sides
+    pub attributes: Vec<String>,
+    /// Verbs changed differently on both sides (by their full name list)
+    pub verbs: Vec<String>,
+    /// Properties (definitions or overrides) changed differently on both sides
+    pub props: Vec<String>,
+}
+
+/// Result of three-way merging a single object definition
+pub struct ObjectMerge {
+    /// The merged definition (local side wins wherever there is a conflict)
+    pub merged: ObjectDefinition,
+    /// Conflicts found during the merge; empty when the merge is clean
+    pub conflict: ObjectConflict,
+}
+
+impl ObjectConflict {
+    /// Create a new empty ObjectConflict
+    pub fn new(obj_id: String) -> Self {
+        Self {
+            obj_id,
+            ..Default::default()
+        }
+    }
+
+    /// Create an object-level conflict with the given reason
+    pub fn with_reason(obj_id: String, reason: String) -> Self {
+        Self {
+            obj_id,
+            reason: Some(reason),
+            ..Default::default()
+        }
+    }
+
+    /// Check if no conflicts were recorded
+    pub fn is_empty(&self) -> bool {
+        self.reason.is_none()
+            && self.attributes.is_empty()
+            && self.verbs.is_empty()
+            && self.props.is_empty()
+    }
+
+    /// Short human-readable description, used in error messages
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(reason) = &self.reason {
+            parts.push(reason.clone());
+        }
+        if !self.attributes.is_empty() {
+            parts.push(format!("attributes: {}", self.attributes.join(", ")));
+        }
+        if !self.verbs.is_empty() {
+            parts.push(format!("verbs: {}", self.verbs.join(", ")));
+        }
+        if !self.props.is_empty() {
+            parts.push(format!("props: {}", self.props.join(", ")));
+        }
+        format!("{} ({})", self.obj_id, parts.join("; "))
+    }
+
+    /// Convert this ObjectConflict to a MOO v_map
+    pub fn to_moo_var(&self) -> Var {
+        let to_list =
+            |names: &[String]| v_list(&names.iter().map(|n| v_str(n)).collect::<Vec<Var>>());
+
+        v_map(&[
+            (v_str("obj_id"), object_id_to_var(&self.obj_id)),
+            (v_str("reason"), v_str(self.reason.as_deref().unwrap_or(""))),
+            (v_str("attributes"), to_list(&self.attributes)),
+            (v_str("verbs"), to_list(&self.verbs)),
+            (v_str("props"), to_list(&self.props)),
+        ])
+    }
+}
+
+/// Load the merged history of the index, oldest first
+pub fn load_merged_changes(database: &DatabaseRef) -> Result<Vec<Change>, ObjectsTreeError> {
+    let mut merged_changes = Vec::new();
+    for change_id in database
+        .index()
+        .get_change_order()
+        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+    {
+        if let Some(change) = database
+            .index()
+            .get_change(&change_id)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+        {
+            if change.status == ChangeStatus::Merged {
+                merged_changes.push(change);
+            }
+        }
+    }
+    Ok(merged_changes)
+}
+
+/// Walk the given changes in order and record the resulting state of every object they touched
+/// Only merged changes are considered; local, idle and review changes are skipped
+pub fn merged_object_states(changes: &[Change]) -> HashMap<String, RecordedObjectState> {
+    let mut states = HashMap::new();
+
+    for change in changes.iter().filter(|c| c.status == ChangeStatus::Merged) {
+        for renamed in &change.renamed_objects {
+            if renamed.from.object_type == VcsObjectType::MooObject {
+                states.insert(
+                    renamed.from.name.clone(),
+                    RecordedObjectState::RenamedTo(renamed.to.name.clone()),
+                );
+                states.insert(
+                    renamed.to.name.clone(),
+                    RecordedObjectState::Version(renamed.to.version),
+                );
+            }
+        }
+        for obj in change
+            .added_objects
+            .iter()
+            .chain(change.modified_objects.iter())
+            .filter(|o| o.object_type == VcsObjectType::MooObject)
+        {
+            states.insert(obj.name.clone(), RecordedObjectState::Version(obj.version));
+        }
+        for obj in change
+            .deleted_objects
+            .iter()
+            .filter(|o| o.object_type == VcsObjectType::MooObject)
+        {
+            states.insert(obj.name.clone(), RecordedObjectState::Deleted);
+        }
+    }
+
+    states
+}
+
+/// Load and parse a specific version of an object, returning None if that version doesn't exist
+pub fn load_object_definition(
+    database: &DatabaseRef,
+    obj_name: &str,
+    version: u64,
+) -> Result<Option<ObjectDefinition>, ObjectsTreeError> {
+    let Some(sha256) = database
+        .refs()
+        .get_ref(VcsObjectType::MooObject, obj_name, Some(version))
+        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+    else {
+        return Ok(None);
+    };
+
+    let content = database
+        .objects()
+        .get(&sha256)
+        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+        .ok_or_else(|| {
+            ObjectsTreeError::SerializationError(format!(
+                "Object content for SHA256 '{sha256}' not found"
+            ))
+        })?;
+
+    let object_def = database
+        .objects()
+        .parse_object_dump(&content)
+        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+
+    Ok(Some(object_def))
+}
+
+/// Three-way merge an object definition at the attribute, verb and property level
+///
+/// `base` is the common ancestor of `ours` and `theirs`; pass None when both sides added
+/// the object independently. A member changed on only one side takes that side's value.
+/// A member changed identically on both sides is kept once. Anything else is a conflict,
+/// in which case the merged definition keeps the local (`ours`) value for that member.
+pub fn merge_object_definitions(
+    obj_id: &str,
+    base: Option<ObjectDefinition>,
+    ours: ObjectDefinition,
+    theirs: ObjectDefinition,
+) -> ObjectMerge {
+    let mut conflict = ObjectConflict::new(obj_id.to_string());
+
+    let (base_attrs, base_verbs, base_prop_defs, base_prop_overrides) = match base {
+        Some(b) => (
+            Some((b.parent, b.owner, b.location, b.name, b.flags)),
+            b.verbs,
+            b.property_definitions,
+            b.property_overrides,
+        ),
+        None => (None, Vec::new(), Vec::new(), Vec::new()),
+    };
+
+    let parent = merge_attribute(
+        "parent",
+        base_attrs.as_ref().map(|b| &b.0),
+        ours.parent,
+        theirs.parent,
+        &mut conflict.attributes,
+    );
+    let owner = merge_attribute(
+        "owner",
+        base_attrs.as_ref().map(|b| &b.1),
+        ours.owner,
+        theirs.owner,
+        &mut conflict.attributes,
+    );
+    let location = merge_attribute(
+        "location",
+        base_attrs.as_ref().map(|b| &b.2),
+        ours.location,
+        theirs.location,
+        &mut conflict.attributes,
+    );
+    let name = merge_attribute(
+        "name",
+        base_attrs.as_ref().map(|b| &b.3),
+        ours.name,
+        theirs.name,
+        &mut conflict.attributes,
+    );
+    let flags = merge_attribute(
+        "flags",
+        base_attrs.as_ref().map(|b| &b.4),
+        ours.flags,
+        theirs.flags,
+        &mut conflict.attributes,
+    );
+
+    let verbs = merge_members(
+        base_verbs,
+        ours.verbs,
+        theirs.verbs,
+        // Verbs may share names, so they are told apart by their full name list and argspec
+        |v| format!("{} {:?}", verb_label(v), v.argspec),
+        verb_label,
+        verbs_differ,
+        &mut conflict.verbs,
+    );
+    let property_definitions = merge_members(
+        base_prop_defs,
+        ours.property_definitions,
+        theirs.property_definitions,
+        |p| p.name.as_string(),
+        |p| p.name.as_string(),
+        property_definitions_differ,
+        &mut conflict.props,
+    );
+    let property_overrides = merge_members(
+        base_prop_overrides,
+        ours.property_overrides,
+        theirs.property_overrides,
+        |p| p.name.as_string(),
+        |p| p.name.as_string(),
+        property_overrides_differ,
+        &mut conflict.props,
+    );
+
+    ObjectMerge {
+        merged: ObjectDefinition {
+            oid: ours.oid,
+            parent,
+            location,
+            owner,
+            name,
+            flags,
+            verbs,
+            property_definitions,
+            property_overrides,
+        },
+        conflict,
+    }
+}
+
+/// Merge a single attribute, recording a conflict if both sides changed it differently
+fn merge_attribute<T: PartialEq>(
+    attribute: &str,
+    base: Option<&T>,
+    ours: T,
+    theirs: T,
+    conflicts: &mut Vec<String>,
+) -> T {
+    if ours == theirs {
+        return ours;
+    }
+    match base {
+        Some(b) if *b == ours => theirs,
+        Some(b) if *b == theirs => ours,
+        _ => {
+            conflicts.push(attribute.to_string());
+            ours
+        }
+    }
+}
+
+/// Names of a verb as written in its declaration, e.g. "look l*"
+fn verb_label(verb: &moor_compiler::ObjVerbDef) -> String {
+    verb.names
+        .iter()
+        .map(|n| n.as_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Pair each member with its merge key, numbering repeats so identical keys stay distinct
+fn keyed_members<T>(members: Vec<T>, key: &impl Fn(&T) -> String) -> Vec<(String, T)> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    members
+        .into_iter()
+        .map(|m| {
+            let k = key(&m);
+            let count = seen.entry(k.clone()).or_default();
+            *count += 1;
+            let k = if *count == 1 {
+                k
+            } else {
+                format!("{k} #{count}")
+            };
+            (k, m)
+        })
+        .collect()
+}
+
+/// Merge a list of named members (verbs, property definitions or overrides)
+///
+/// Members are matched across the three sides by `key`, and conflicts are reported using
+/// `label`. The local ordering is preserved, with members only added upstream appended at
+/// the end.
+fn merge_members<T>(
+    base: Vec<T>,
+    ours: Vec<T>,
+    theirs: Vec<T>,
+    key: impl Fn(&T) -> String,
+    label: impl Fn(&T) -> String,
+    differ: impl Fn(&T, &T) -> bool,
+    conflicts: &mut Vec<String>,
+) -> Vec<T> {
+    let base: HashMap<String, T> = keyed_members(base, &key).into_iter().collect();
+    let mut theirs_order = Vec::new();
+    let mut theirs: HashMap<String, T> = keyed_members(theirs, &key)
+        .into_iter()
+        .map(|(k, m)| {
+            theirs_order.push(k.clone());
+            (k, m)
+        })
+        .collect();
+
+    let mut merged = Vec::new();
+
+    for (k, our) in keyed_members(ours, &key) {
+        let base_member = base.get(&k);
+        match theirs.remove(&k) {
+            Some(their) => {
+                let ours_changed = base_member.is_none_or(|b| differ(b, &our));
+                let theirs_changed = base_member.is_none_or(|b| differ(b, &their));
+                if !differ(&our, &their) || !theirs_changed {
+                    merged.push(our);
+                } else if !ours_changed {
+                    merged.push(their);
+                } else {
+                    conflicts.push(label(&our));
+                    merged.push(our);
+                }
+            }
+            None => match base_member {
+                // Deleted upstream: drop it unless we also changed it
+                Some(b) if differ(b, &our) => {
+                    conflicts.push(label(&our));
+                    merged.push(our);
+                }
+                Some(_) => {}
+                // Added locally
+                None => merged.push(our),
+            },
+        }
+    }
+
+    for k in theirs_order {
+        let Some(their) = theirs.remove(&k) else {
+            continue;
+        };
+        match base.get(&k) {
+            // Deleted locally but changed upstream
+            Some(b) if differ(b, &their) => {
+                conflicts.push(label(&their));
+            }
+            // Deleted locally, unchanged upstream
+            Some(_) => {}
+            // Added upstream
+            None => merged.push(their),
+        }
+    }
+
+    merged
+}
+
+/// Outcome of rebasing a change onto the merged history of the index
+#[derive(Debug)]
+pub struct RebaseOutcome {
+    /// "up_to_date", "rebased" or "conflict"
+    pub status: &'static str,
+    /// The merged change the change is based on afterwards
+    pub index_change_id: Option<String>,
+    /// Objects three-way merged into the change as new versions
+    pub merged_objects: Vec<String>,
+    /// Conflicts that stopped the rebase
+    pub conflicts: Vec<ObjectConflict>,
+}
+
+/// Replay a change on top of the merged history of the index
+///
+/// Every object the change touched that was also changed upstream since its base
+/// (`index_change_id`) is three-way merged and stored as a new version, and the change is
+/// re-based on the last merged change. When the base is unknown the previous version of
+/// each object is used instead. If any object conflicts the change is left untouched. The
+/// caller is responsible for persisting the updated change.
+pub fn rebase_change(
+    database: &DatabaseRef,
+    change: &mut Change,
+) -> Result<RebaseOutcome, ObjectsTreeError> {
+    let merged_changes = load_merged_changes(database)?;
+
+    let new_base = merged_changes.last().map(|c| c.id.clone());
+
+    // Already based on the current index tip - nothing to do
+    if change.index_change_id.is_some() && change.index_change_id == new_base {
+        tracing::info!("Change '{}' is already up to date", change.name);
+        return Ok(RebaseOutcome {
+            status: "up_to_date",
+            index_change_id: new_base,
+            merged_objects: Vec::new(),
+            conflicts: Vec::new(),
+        });
+    }
+
+    // Object state at the merge base. When the base is unknown (older changes, or a base
+    // no longer in the index) fall back to the previous version of each object.
+    let base_states = change.index_change_id.as_ref().and_then(|base_id| {
+        merged_changes
+            .iter()
+            .position(|c| &c.id == base_id)
+            .map(|pos| merged_object_states(&merged_changes[..=pos]))
+    });
+    let upstream_states = merged_object_states(&merged_changes);
+
+    let base_version_of = |name: &str, local_version: Option<u64>| match &base_states {
+        Some(states) => match states.get(name) {
+            Some(RecordedObjectState::Version(v)) => Some(*v),
+            _ => None,
+        },
+        None => local_version
+            .map(|v| v.saturating_sub(1))
+            .filter(|v| *v > 0),
+    };
+
+    let mut merged_defs = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for (obj, was_added) in change
+        .modified_objects
+        .iter()
+        .map(|o| (o, false))
+        .chain(change.added_objects.iter().map(|o| (o, true)))
+        .filter(|(o, _)| o.object_type == VcsObjectType::MooObject)
+    {
+        let base_version = if was_added {
+            None
+        } else {
+            base_version_of(&obj.name, Some(obj.version))
+        };
+
+        // Upstream hasn't touched the object since the base
+        if base_states
+            .as_ref()
+            .is_some_and(|states| states.get(&obj.name) == upstream_states.get(&obj.name))
+        {
+            continue;
+        }
+
+        let theirs_version = match upstream_states.get(&obj.name) {
+            None => continue,
+            Some(RecordedObjectState::Version(v)) => *v,
+            Some(RecordedObjectState::Deleted) => {
+                if base_version.is_some() {
+                    conflicts.push(ObjectConflict::with_reason(
+                        obj.name.clone(),
+                        "modified locally but deleted upstream".to_string(),
+                    ));
+                }
+                continue;
+            }
+            Some(RecordedObjectState::RenamedTo(to)) => {
+                conflicts.push(ObjectConflict::with_reason(
+                    obj.name.clone(),
+                    format!("modified locally but renamed upstream to '{to}'"),
+                ));
+                continue;
+            }
+        };
+
+        if Some(theirs_version) == base_version || theirs_version == obj.version {
+            continue;
+        }
+
+        let ours = load_object_definition(database, &obj.name, obj.version)?.ok_or_else(|| {
+            ObjectsTreeError::SerializationError(format!(
+                "Version {} of object '{}' not found",
+                obj.version, obj.name
+            ))
+        })?;
+        let theirs =
+            load_object_definition(database, &obj.name, theirs_version)?.ok_or_else(|| {
+                ObjectsTreeError::SerializationError(format!(
+                    "Version {} of object '{}' not found",
+                    theirs_version, obj.name
+                ))
+            })?;
+        let base = match base_version {
+            Some(v) => load_object_definition(database, &obj.name, v)?,
+            None => None,
+        };
+
+        let merge = merge_object_definitions(&obj.name, base, ours, theirs);
+        if merge.conflict.is_empty() {
+            merged_defs.push((obj.name.clone(), merge.merged));
+        } else {
+            conflicts.push(merge.conflict);
+        }
+    }
+
+    // Objects deleted locally must not have been changed upstream
+    for obj in change
+        .deleted_objects
+        .iter()
+        .filter(|o| o.object_type == VcsObjectType::MooObject)
+    {
+        let base_version = base_version_of(&obj.name, None).unwrap_or(obj.version);
+        if upstream_states
+            .get(&obj.name)
+            .is_some_and(|s| matches!(s, RecordedObjectState::Version(v) if *v != base_version))
+        {
+            conflicts.push(ObjectConflict::with_reason(
+                obj.name.clone(),
+                "deleted locally but modified upstream".to_string(),
+            ));
+        }
+    }
+
+    if !conflicts.is_empty() {
+        tracing::info!(
+            "Rebase of change '{}' found conflicts in {} objects, leaving it untouched",
+            change.name,
+            conflicts.len()
+        );
+        return Ok(RebaseOutcome {
+            status: "conflict",
+            index_change_id: change.index_change_id.clone(),
+            merged_objects: Vec::new(),
+            conflicts,
+        });
+    }
+
+    // Store each merged object as a new version and point the change at it
+    let mut merged_objects = Vec::new();
+    for (obj_name, object_def) in merged_defs {
+        let dump = database
+            .objects()
+            .generate_object_dump(&object_def)
+            .map_err(|e| {
+                ObjectsTreeError::SerializationError(format!(
+                    "Failed to dump merged object '{obj_name}': {e}"
+                ))
+            })?;
+
+        let sha256_key = database.objects().generate_hash(&dump);
+        database
+            .objects()
+            .store(&sha256_key, &dump)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+
+        let version = database
+            .refs()
+            .get_next_version(VcsObjectType::MooObject, &obj_name)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        database
+            .refs()
+            .update_ref(VcsObjectType::MooObject, &obj_name, version, &sha256_key)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+
+        // The object now exists upstream, so a local addition becomes a modification
+        let merged_info = ObjectInfo {
+            object_type: VcsObjectType::MooObject,
+            name: obj_name.clone(),
+            version,
+        };
+        change
+            .added_objects
+            .retain(|o| !(o.object_type == VcsObjectType::MooObject && o.name == obj_name));
+        match change
+            .modified_objects
+            .iter_mut()
+            .find(|o| o.object_type == VcsObjectType::MooObject && o.name == obj_name)
+        {
+            Some(existing) => existing.version = version,
+            None => change.modified_objects.push(merged_info),
+        }
+
+        tracing::info!(
+            "Merged object '{}' into change '{}' as version {}",
+            obj_name,
+            change.name,
+            version
+        );
+        merged_objects.push(obj_name);
+    }
+
+    change.index_change_id = new_base.clone();
+
+    Ok(RebaseOutcome {
+        status: "rebased",
+        index_change_id: new_base,
+        merged_objects,
+        conflicts: Vec::new(),
+    })
+}
+
+/// Find objects whose verbs or properties were changed both by `change` and by changes
+/// merged into the index after the change's base (`index_change_id`)
+///
+/// Changes without a recorded base, or whose base is no longer in the index, can't be
+/// checked and report no conflicts.
+pub fn detect_change_conflicts(
+    database: &DatabaseRef,
+    change: &Change,
+) -> Result<Vec<ObjectConflict>, ObjectsTreeError> {
+    let Some(base_id) = change.index_change_id.as_deref() else {
+        return Ok(Vec::new());
+    };
+
+    let merged_changes = load_merged_changes(database)?;
+    let Some(base_pos) = merged_changes.iter().position(|c| c.id == base_id) else {
+        tracing::debug!(
+            "Base '{}' of change '{}' not in index, skipping conflict detection",
+            base_id,
+            change.id
+        );
+        return Ok(Vec::new());
+    };
+    if base_pos + 1 == merged_changes.len() {
+        return Ok(Vec::new());
+    }
+
+    let base_states = merged_object_states(&merged_changes[..=base_pos]);
+    let upstream_states = merged_object_states(&merged_changes);
+    let mut conflicts = Vec::new();
+
+    for obj in change
+        .added_objects
+        .iter()
+        .chain(change.modified_objects.iter())
+        .filter(|o| o.object_type == VcsObjectType::MooObject)
+    {
+        let base_state = base_states.get(&obj.name);
+        let upstream_state = upstream_states.get(&obj.name);
+        if base_state == upstream_state {
+            continue;
+        }
+
+        let base_version = match base_state {
+            Some(RecordedObjectState::Version(v)) => Some(*v),
+            _ => None,
+        };
+        match upstream_state {
+            Some(RecordedObjectState::Version(v)) if *v != obj.version => {
+                let conflict =
+                    compare_member_edits(database, &obj.name, base_version, obj.version, *v)?;
+                if !conflict.is_empty() {
+                    conflicts.push(conflict);
+                }
+            }
+            Some(RecordedObjectState::Deleted) => {
+                conflicts.push(ObjectConflict::with_reason(
+                    obj.name.clone(),
+                    "modified locally but deleted upstream".to_string(),
+                ));
+            }
+            Some(RecordedObjectState::RenamedTo(to)) => {
+                conflicts.push(ObjectConflict::with_reason(
+                    obj.name.clone(),
+                    format!("modified locally but renamed upstream to '{to}'"),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    for obj in change
+        .deleted_objects
+        .iter()
+        .filter(|o| o.object_type == VcsObjectType::MooObject)
+    {
+        let base_state = base_states.get(&obj.name);
+        let upstream_state = upstream_states.get(&obj.name);
+        if base_state != upstream_state
+            && matches!(upstream_state, Some(RecordedObjectState::Version(_)))
+        {
+            conflicts.push(ObjectConflict::with_reason(
+                obj.name.clone(),
+                "deleted locally but modified upstream".to_string(),
+            ));
+        }
+    }
+
+    Ok(conflicts)
+}
+
+/// Compare both sides of an object against their common base and report the verbs and
+/// properties that both sides changed to different results
+fn compare_member_edits(
+    database: &DatabaseRef,
+    obj_name: &str,
+    base_version: Option<u64>,
+    ours_version: u64,
+    theirs_version: u64,
+) -> Result<ObjectConflict, ObjectsTreeError> {
+    let load = |version: u64| -> Result<ObjectDefinition, ObjectsTreeError> {
+        load_object_definition(database, obj_name, version)?.ok_or_else(|| {
+            ObjectsTreeError::SerializationError(format!(
+                "Version {version} of object '{obj_name}' not found"
+            ))
+        })
+    };
+    let ours = load(ours_version)?;
+    let theirs = load(theirs_version)?;
+
+    // Members whose final state differs between the two sides
+    let mut sides = ObjectChange::new(obj_name.to_string());
+    compare_object_definitions_with_meta(
+        &theirs,
+        &ours,
+        &mut sides,
+        Some(database),
+        Some(obj_name),
+        None,
+        None,
+    );
+
+    let mut conflict = ObjectConflict::new(obj_name.to_string());
+    match base_version {
+        Some(base_version) => {
+            let base = load(base_version)?;
+            let mut ours_change = ObjectChange::new(obj_name.to_string());
+            compare_object_definitions_with_meta(
+                &base,
+                &ours,
+                &mut ours_change,
+                Some(database),
+                Some(obj_name),
+                None,
+                None,
+            );
+            let mut theirs_change = ObjectChange::new(obj_name.to_string());
+            compare_object_definitions_with_meta(
+                &base,
+                &theirs,
+                &mut theirs_change,
+                Some(database),
+                Some(obj_name),
+                None,
+                None,
+            );
+
+            let (ours_verbs, theirs_verbs, sides_verbs) = (
+                touched_verbs(&ours_change),
+                touched_verbs(&theirs_change),
+                touched_verbs(&sides),
+            );
+            conflict.verbs = ours_verbs
+                .intersection(&theirs_verbs)
+                .filter(|v| sides_verbs.contains(*v))
+                .map(|v| v.to_string())
+                .collect();
+
+            let (ours_props, theirs_props, sides_props) = (
+                touched_props(&ours_change),
+                touched_props(&theirs_change),
+                touched_props(&sides),
+            );
+            conflict.props = ours_props
+                .intersection(&theirs_props)
+                .filter(|p| sides_props.contains(*p))
+                .map(|p| p.to_string())
+                .collect();
+        }
+        None => {
+            // Both sides added the object independently: anything present on both sides
+            // with different definitions conflicts
+            conflict.verbs = sides.verbs_modified.into_iter().collect();
+            conflict.props = sides.props_modified.into_iter().collect();
+        }
+    }
+    conflict.verbs.sort();
+    conflict.props.sort();
+
+    Ok(conflict)
+}
+
+/// Verbs added, modified or deleted in an ObjectChange
+fn touched_verbs(change: &ObjectChange) -> HashSet<&str> {
+    change
+        .verbs_added
+        .iter()
+        .chain(&change.verbs_modified)
+        .chain(&change.verbs_deleted)
+        .map(String::as_str)
+        .collect()
+}
+
+/// Properties added, modified or deleted in an ObjectChange
+fn touched_props(change: &ObjectChange) -> HashSet<&str> {
+    change
+        .props_added
+        .iter()
+        .chain(&change.props_modified)
+        .chain(&change.props_deleted)
+        .map(String::as_str)
+        .collect()
+}
@@ -0,0 +1,142 @@
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+};
+use axum::http::Method;
+use tracing::{error, info};
+
+use super::backup_run_to_var;
+use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::providers::backups::BackupProvider;
+use crate::types::{BackupHistoryRequest, User};
+
+/// Default number of runs returned by backup/history
+const DEFAULT_HISTORY_LIMIT: usize = 20;
+
+/// Backup history operation that lists recorded git backup runs, newest first
+#[derive(Clone)]
+pub struct BackupHistoryOperation {
+    database: DatabaseRef,
+}
+
+impl BackupHistoryOperation {
+    /// Create a new backup history operation
+    pub fn new(database: DatabaseRef) -> Self {
+        Self { database }
+    }
+
+    /// Process the backup history request
+    fn process_backup_history(
+        &self,
+        request: BackupHistoryRequest,
+    ) -> Result<moor_var::Var, ObjectsTreeError> {
+        let runs = self
+            .database
+            .backups()
+            .list_runs(request.limit)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+
+        info!("Retrieved {} backup runs", runs.len());
+
+        let runs: Vec<moor_var::Var> = runs.iter().map(backup_run_to_var).collect();
+        Ok(moor_var::v_list(&runs))
+    }
+}
+
+impl Operation for BackupHistoryOperation {
+    fn name(&self) -> &'static str {
+        "backup/history"
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> &'static str {
+        "List recorded git backup runs (newest first) with their trigger, timing, resulting commit, object count and error"
+    }
+
+    fn response_content_type(&self) -> &'static str {
+        "text/x-moo"
+    }
+
+    fn philosophy(&self) -> &'static str {
+        "Every git backup run is recorded in the database when it is queued and updated as it starts and \
+        finishes, so the history survives worker restarts. Runs that were interrupted by a restart are marked \
+        as failed when the worker starts again. Use this to find when backups started failing and why."
+    }
+
+    fn parameters(&self) -> Vec<OperationParameter> {
+        vec![OperationParameter {
+            name: "limit".to_string(),
+            description: format!(
+                "Maximum number of runs to return (default: {DEFAULT_HISTORY_LIMIT})"
+            ),
+            required: false,
+        }]
+    }
+
+    fn examples(&self) -> Vec<OperationExample> {
+        vec![OperationExample {
+            description: "List the last 5 backup runs".to_string(),
+            moocode: r#"runs = worker_request("vcs", {"backup/history", "5"});
+for run in (runs)
+    player:tell("#", run["id"], " ", run["status"], " (", run["trigger"], ") ", run["error"]);
+endfor"#
+                .to_string(),
+            http_curl: Some(
+                r#"curl -X GET "http://localhost:8081/api/backup/history?limit=5""#.to_string(),
+            ),
+        }]
+    }
+
+    fn routes(&self) -> Vec<OperationRoute> {
+        vec![OperationRoute {
+            path: "/api/backup/history".to_string(),
+            method: Method::GET,
+            is_json: false,
+        }]
+    }
+
+    fn responses(&self) -> Vec<crate::operations::OperationResponse> {
+        use crate::operations::OperationResponse;
+        vec![
+            OperationResponse::success(
+                "Operation executed successfully - returns list of backup runs",
+                r#"{["id" -> 2, "status" -> "succeeded", "trigger" -> "backup/run", "queued_at" -> 1697040100, "started_at" -> 1697040100, "finished_at" -> 1697040101, "commit" -> "4b825dc6...", "commits" -> 1, "object_count" -> 42, "error" -> ""], ["id" -> 1, "status" -> "failed", ...]}"#,
+            ),
+            OperationResponse::bad_request(
+                "Bad Request - Invalid limit",
+                r#"E_INVARG("Invalid limit 'abc': expected a positive number")"#,
+            ),
+            OperationResponse::internal_error(
+                "Internal Server Error - Database error",
+                r#"E_INVARG("Serialization error: ...")"#,
+            ),
+        ]
+    }
+
+    fn execute(&self, args: Vec<String>, _user: &User) -> Result<moor_var::Var, OperationError> {
+        let limit = match args.first().filter(|s| !s.is_empty()) {
+            Some(limit) => limit.parse::<usize>().map_err(|_| {
+                error!("Invalid backup history limit '{}'", limit);
+                OperationError::InvalidArgs(format!(
+                    "Invalid limit '{limit}': expected a positive number"
+                ))
+            })?,
+            None => DEFAULT_HISTORY_LIMIT,
+        };
+
+        let request = BackupHistoryRequest { limit };
+
+        match self.process_backup_history(request) {
+            Ok(result) => {
+                info!("Backup history operation completed successfully");
+                Ok(result)
+            }
+            Err(e) => {
+                error!("Backup history operation failed: {}", e);
+                Err(e.into())
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
+use axum::http::Method;
+use tracing::{error, info};
+
+use super::backup_run_to_var;
+use crate::config::Config;
+use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::git_backup;
+use crate::providers::backups::BackupProvider;
+use crate::types::{BackupRun, BackupRunStatus, Permission, User};
+
+/// Backup run operation that performs a git backup immediately and reports its outcome
+#[derive(Clone)]
+pub struct BackupRunOperation {
+    database: DatabaseRef,
+    config: Config,
+}
+
+impl BackupRunOperation {
+    /// Create a new backup run operation
+    pub fn new(database: DatabaseRef, config: Config) -> Self {
+        Self { database, config }
+    }
+
+    /// Process the backup run request
+    fn process_backup_run(&self, user: &User) -> Result<BackupRun, OperationError> {
+        require_permission(user, Permission::ApproveChanges, "run backups")?;
+
+        if self.config.git_backup_repo.is_none() {
+            return Err(OperationError::InvalidArgs(
+                "Git backup is not configured (set VCS_GIT_BACKUP_REPO)".to_string(),
+            ));
+        }
+
+        let run = self
+            .database
+            .backups()
+            .create_run(self.name())
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+
+        // Waits for any run already in progress, then backs up whatever it left
+        let run = git_backup::run_git_backup(&self.database, &self.config, run);
+
+        if run.status == BackupRunStatus::Failed {
+            return Err(OperationError::Internal(format!(
+                "Backup run {} failed: {}",
+                run.id,
+                run.error.as_deref().unwrap_or("unknown error")
+            )));
+        }
+
+        Ok(run)
+    }
+}
+
+impl Operation for BackupRunOperation {
+    fn name(&self) -> &'static str {
+        "backup/run"
+    }
+
+    fn description(&self) -> &'static str {
+        "Run a git backup now, waiting for it to finish, and return the recorded run"
+    }
+
+    fn response_content_type(&self) -> &'static str {
+        "text/x-moo"
+    }
+
+    fn philosophy(&self) -> &'static str {
+        "Backups normally run in the background after each approval or submission. Use this to retry after \
+        fixing the cause of a failure (credentials, a diverged remote) without waiting for the next change. \
+        Runs never overlap: if a background run is in progress, this one waits for it and then backs up \
+        anything it left. The run is recorded in backup/history whether or not it succeeds."
+    }
+
+    fn parameters(&self) -> Vec<OperationParameter> {
+        vec![]
+    }
+
+    fn examples(&self) -> Vec<OperationExample> {
+        vec![OperationExample {
+            description: "Run a backup now".to_string(),
+            moocode: r#"run = worker_request("vcs", {"backup/run"});
+player:tell("Backup ", run["id"], " created ", run["commits"], " commits, now at ", run["commit"]);"#
+                .to_string(),
+            http_curl: Some(r#"curl -X POST http://localhost:8081/api/backup/run"#.to_string()),
+        }]
+    }
+
+    fn routes(&self) -> Vec<OperationRoute> {
+        vec![OperationRoute {
+            path: "/api/backup/run".to_string(),
+            method: Method::POST,
+            is_json: false,
+        }]
+    }
+
+    fn responses(&self) -> Vec<crate::operations::OperationResponse> {
+        use crate::operations::OperationResponse;
+        vec![
+            OperationResponse::success(
+                "Backup completed - returns the recorded run",
+                r#"["id" -> 3, "status" -> "succeeded", "trigger" -> "backup/run", "queued_at" -> 1697040100, "started_at" -> 1697040100, "finished_at" -> 1697040101, "commit" -> "4b825dc6...", "commits" -> 1, "object_count" -> 42, "error" -> ""]"#,
+            ),
+            OperationResponse::bad_request(
+                "Bad Request - Git backup is not configured",
+                r#"E_INVARG("Git backup is not configured (set VCS_GIT_BACKUP_REPO)")"#,
+            ),
+            OperationResponse::forbidden(
+                "Forbidden - User lacks ApproveChanges permission",
+                r#"E_PERM("User 'player' does not have permission to run backups")"#,
+            ),
+            OperationResponse::internal_error(
+                "Internal Server Error - The backup run failed",
+                r#"E_INVARG("Backup run 3 failed: Backup branch has diverged from the remote: ...")"#,
+            ),
+        ]
+    }
+
+    fn execute(&self, _args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
+        info!("Backup run operation received for user: {}", user.id);
+
+        match self.process_backup_run(user) {
+            Ok(run) => {
+                info!(
+                    "Backup run {} completed with {} commits",
+                    run.id, run.commits
+                );
+                Ok(backup_run_to_var(&run))
+            }
+            Err(e) => {
+                error!("Backup run operation failed: {}", e);
+                Err(e)
+            }
+        }
+    }
+}
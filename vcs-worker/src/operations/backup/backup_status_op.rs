@@ -0,0 +1,136 @@
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+};
+use axum::http::Method;
+use tracing::{error, info};
+
+use super::backup_run_to_var;
+use crate::config::Config;
+use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::providers::backups::BackupProvider;
+use crate::types::{BackupRunStatus, User};
+
+/// Backup status operation that reports whether git backups are configured and how they are doing
+#[derive(Clone)]
+pub struct BackupStatusOperation {
+    database: DatabaseRef,
+    config: Config,
+}
+
+impl BackupStatusOperation {
+    /// Create a new backup status operation
+    pub fn new(database: DatabaseRef, config: Config) -> Self {
+        Self { database, config }
+    }
+
+    /// Get the most recent run with one of the given statuses as a map, or "" if there is none
+    fn last_run(&self, statuses: &[BackupRunStatus]) -> Result<moor_var::Var, ObjectsTreeError> {
+        let run = self
+            .database
+            .backups()
+            .last_run_with_status(statuses)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        Ok(match run {
+            Some(run) => backup_run_to_var(&run),
+            None => moor_var::v_str(""),
+        })
+    }
+
+    /// Process the backup status request
+    fn process_backup_status(&self) -> Result<moor_var::Var, ObjectsTreeError> {
+        let target = self.config.git_backup_repo.clone().unwrap_or_default();
+
+        let current_run = self.last_run(&[BackupRunStatus::Running, BackupRunStatus::Queued])?;
+        let last_run = self.last_run(&[BackupRunStatus::Succeeded, BackupRunStatus::Failed])?;
+        let last_success = self.last_run(&[BackupRunStatus::Succeeded])?;
+
+        Ok(moor_var::v_map(&[
+            (
+                moor_var::v_str("enabled"),
+                moor_var::v_int(if target.is_empty() { 0 } else { 1 }),
+            ),
+            (moor_var::v_str("target"), moor_var::v_str(&target)),
+            (moor_var::v_str("current_run"), current_run),
+            (moor_var::v_str("last_run"), last_run),
+            (moor_var::v_str("last_success"), last_success),
+        ]))
+    }
+}
+
+impl Operation for BackupStatusOperation {
+    fn name(&self) -> &'static str {
+        "backup/status"
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> &'static str {
+        "Report whether git backups are configured, the run in progress, the last finished run and the last successful run"
+    }
+
+    fn response_content_type(&self) -> &'static str {
+        "text/x-moo"
+    }
+
+    fn philosophy(&self) -> &'static str {
+        "Git backups run in the background after changes are approved or submitted, so failures never block \
+        the change itself. This operation makes those failures visible: comparing last_run with last_success \
+        shows at a glance whether backups have been failing and since when, and last_run carries the error \
+        that stopped it (for example a remote that has diverged)."
+    }
+
+    fn parameters(&self) -> Vec<OperationParameter> {
+        vec![]
+    }
+
+    fn examples(&self) -> Vec<OperationExample> {
+        vec![OperationExample {
+            description: "Check on git backups".to_string(),
+            moocode: r#"status = worker_request("vcs", {"backup/status"});
+if (status["enabled"] && typeof(status["last_run"]) == MAP && status["last_run"]["status"] == "failed")
+    player:tell("Backups are failing: ", status["last_run"]["error"]);
+endif"#
+                .to_string(),
+            http_curl: Some(r#"curl -X GET http://localhost:8081/api/backup/status"#.to_string()),
+        }]
+    }
+
+    fn routes(&self) -> Vec<OperationRoute> {
+        vec![OperationRoute {
+            path: "/api/backup/status".to_string(),
+            method: Method::GET,
+            is_json: false,
+        }]
+    }
+
+    fn responses(&self) -> Vec<crate::operations::OperationResponse> {
+        use crate::operations::OperationResponse;
+        vec![
+            OperationResponse::success(
+                "Operation executed successfully",
+                r#"["enabled" -> 1, "target" -> "https://github.com/example/world-backup.git", "current_run" -> "", "last_run" -> ["id" -> 12, "status" -> "failed", "trigger" -> "change/approve", "queued_at" -> 1697040000, "started_at" -> 1697040000, "finished_at" -> 1697040002, "commit" -> "", "commits" -> 0, "object_count" -> 0, "error" -> "Backup branch has diverged from the remote: ..."], "last_success" -> ["id" -> 11, "status" -> "succeeded", ...]]"#,
+            ),
+            OperationResponse::internal_error(
+                "Internal Server Error - Database error",
+                r#"E_INVARG("Serialization error: ...")"#,
+            ),
+        ]
+    }
+
+    fn execute(&self, _args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
+        info!("Backup status operation received for user: {}", user.id);
+
+        match self.process_backup_status() {
+            Ok(result) => {
+                info!("Backup status operation completed successfully");
+                Ok(result)
+            }
+            Err(e) => {
+                error!("Backup status operation failed: {}", e);
+                Err(e.into())
+            }
+        }
+    }
+}
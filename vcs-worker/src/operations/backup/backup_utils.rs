@@ -0,0 +1,46 @@
+use crate::types::BackupRun;
+
+/// Convert a backup run to a MOO map
+pub fn backup_run_to_var(run: &BackupRun) -> moor_var::Var {
+    let optional_timestamp = |timestamp: Option<u64>| match timestamp {
+        Some(timestamp) => moor_var::v_int(timestamp as i64),
+        None => moor_var::v_int(0),
+    };
+
+    moor_var::v_map(&[
+        (moor_var::v_str("id"), moor_var::v_int(run.id as i64)),
+        (
+            moor_var::v_str("status"),
+            moor_var::v_str(run.status.as_str()),
+        ),
+        (moor_var::v_str("trigger"), moor_var::v_str(&run.trigger)),
+        (
+            moor_var::v_str("queued_at"),
+            moor_var::v_int(run.queued_at as i64),
+        ),
+        (
+            moor_var::v_str("started_at"),
+            optional_timestamp(run.started_at),
+        ),
+        (
+            moor_var::v_str("finished_at"),
+            optional_timestamp(run.finished_at),
+        ),
+        (
+            moor_var::v_str("commit"),
+            moor_var::v_str(run.commit.as_deref().unwrap_or("")),
+        ),
+        (
+            moor_var::v_str("commits"),
+            moor_var::v_int(run.commits as i64),
+        ),
+        (
+            moor_var::v_str("object_count"),
+            moor_var::v_int(run.object_count as i64),
+        ),
+        (
+            moor_var::v_str("error"),
+            moor_var::v_str(run.error.as_deref().unwrap_or("")),
+        ),
+    ])
+}
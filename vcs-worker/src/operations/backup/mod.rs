@@ -0,0 +1,9 @@
+mod backup_history_op;
+mod backup_run_op;
+mod backup_status_op;
+mod backup_utils;
+
+pub(crate) use backup_utils::backup_run_to_var;
+pub use backup_history_op::BackupHistoryOperation;
+pub use backup_run_op::BackupRunOperation;
+pub use backup_status_op::BackupStatusOperation;
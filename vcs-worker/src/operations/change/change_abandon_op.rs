@@ -1,4 +1,7 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use axum::http::Method;
 use tracing::{error, info};
 
@@ -6,7 +9,7 @@ use crate::database::{DatabaseRef, ObjectsTreeError};
 use crate::object_diff::{ObjectDiffModel, build_abandon_diff_from_change};
 use crate::providers::index::IndexProvider;
 use crate::types::User;
-use crate::types::{ChangeAbandonRequest, ChangeStatus};
+use crate::types::{ChangeAbandonRequest, ChangeStatus, Permission};
 
 /// Change abandon operation that abandons the top change in the index
 #[derive(Clone)]
@@ -24,7 +27,7 @@ impl ChangeAbandonOperation {
     fn process_change_abandon(
         &self,
         _request: ChangeAbandonRequest,
-    ) -> Result<ObjectDiffModel, ObjectsTreeError> {
+    ) -> Result<ObjectDiffModel, OperationError> {
         // Get the current change from the top of the index
         let top_change_id = self
             .database
@@ -39,9 +42,7 @@ impl ChangeAbandonOperation {
                 .get_change(&change_id)
                 .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
                 .ok_or_else(|| {
-                    ObjectsTreeError::SerializationError(format!(
-                        "Top change '{change_id}' not found"
-                    ))
+                    OperationError::NotFound(format!("Top change '{change_id}' not found"))
                 })?;
 
             info!("Attempting to abandon current change: {}", change.id);
@@ -51,7 +52,7 @@ impl ChangeAbandonOperation {
                     "Cannot abandon change '{}' ({}) - it has already been merged",
                     change.name, change.id
                 );
-                return Err(ObjectsTreeError::SerializationError(format!(
+                return Err(OperationError::Conflict(format!(
                     "Cannot abandon merged change '{}'",
                     change.name
                 )));
@@ -86,9 +87,7 @@ impl ChangeAbandonOperation {
             Ok(undo_delta)
         } else {
             error!("No current change to abandon");
-            Err(ObjectsTreeError::SerializationError(
-                "Error: No change to abandon".to_string(),
-            ))
+            Err(OperationError::NotFound("No change to abandon".to_string()))
         }
     }
 }
@@ -147,38 +146,44 @@ player:tell("Change abandoned. You need to revert ", length(diff["modified_objec
                 "Operation executed successfully",
                 r#"["objects_renamed" -> [], "objects_deleted" -> {}, "objects_added" -> {}, "objects_modified" -> {"obj1"}, "changes" -> {["obj_id" -> "obj1", "verbs_modified" -> {}, "verbs_added" -> {}, "verbs_renamed" -> [], "verbs_deleted" -> {}, "props_modified" -> {}, "props_added" -> {}, "props_renamed" -> [], "props_deleted" -> {}]}]"#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - Cannot abandon merged change",
-                r#"E_INVARG("Error: Cannot abandon merged change 'my-change'")"#,
+            OperationResponse::forbidden(
+                "Forbidden - User lacks SubmitChanges permission",
+                r#"E_PERM("User 'player' does not have permission to manage changes")"#,
             ),
-            OperationResponse::new(
-                404,
+            OperationResponse::not_found(
                 "Not Found - No change to abandon",
-                r#"E_INVARG("Error: No change to abandon")"#,
+                r#"E_INVIND("No change to abandon")"#,
             ),
-            OperationResponse::new(
-                500,
+            OperationResponse::conflict(
+                "Conflict - Cannot abandon merged change",
+                r#"E_NACC("Cannot abandon merged change 'my-change'")"#,
+            ),
+            OperationResponse::internal_error(
                 "Internal Server Error - Database or system error",
-                r#"E_INVARG("Error: Database error: failed to abandon change")"#,
+                r#"E_INVARG("Database error: failed to abandon change")"#,
             ),
         ]
     }
 
-    fn execute(&self, args: Vec<String>, _user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!("Change abandon operation received {} arguments", args.len());
 
+        require_permission(user, Permission::SubmitChanges, "manage changes")?;
+
         let request = ChangeAbandonRequest {};
 
-        match self.process_change_abandon(request) {
+        match self
+            .database
+            .write_batch(|| self.process_change_abandon(request))
+        {
             Ok(delta_model) => {
                 info!("Change abandon operation completed successfully, returning undo delta");
                 // Return the ObjectDiffModel as a MOO variable showing what needs to be undone
-                delta_model.to_moo_var()
+                Ok(delta_model.to_moo_var())
             }
             Err(e) => {
                 error!("Change abandon operation failed: {}", e);
-                moor_var::v_error(moor_var::E_INVARG.msg(format!("Error: {e}")))
+                Err(e)
             }
         }
     }
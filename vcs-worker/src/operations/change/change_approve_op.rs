@@ -1,15 +1,20 @@
 use crate::config::Config;
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use axum::http::Method;
 use tracing::{error, info};
 
 use crate::database::{DatabaseRef, ObjectsTreeError};
 use crate::git_backup;
-use crate::object_diff::{ObjectDiffModel, build_object_diff_from_change};
+use crate::object_diff::{
+    ObjectDiffModel, add_line_diffs_for_change, build_object_diff_from_change,
+};
+use crate::object_merge::detect_change_conflicts;
 use crate::providers::index::IndexProvider;
 use crate::providers::workspace::WorkspaceProvider;
 use crate::types::{ChangeApproveRequest, ChangeStatus, Permission, User};
-use moor_var::{E_INVARG, v_error};
 
 /// Change approve operation that approves a local change and marks it as merged
 #[derive(Clone)]
@@ -29,21 +34,12 @@ impl ChangeApproveOperation {
         &self,
         request: ChangeApproveRequest,
         user: &User,
-    ) -> Result<ObjectDiffModel, ObjectsTreeError> {
+    ) -> Result<ObjectDiffModel, OperationError> {
         // Resolve short or full hash to full hash
         let change_id = self.database.resolve_change_id(&request.change_id)?;
 
         // Check if user has permission to approve changes
-        if !user.has_permission(&Permission::ApproveChanges) {
-            error!(
-                "User '{}' does not have permission to approve changes",
-                user.id
-            );
-            return Err(ObjectsTreeError::SerializationError(format!(
-                "User '{}' does not have permission to approve changes",
-                user.id
-            )));
-        }
+        require_permission(user, Permission::ApproveChanges, "approve changes")?;
 
         // Try to get the change from workspace first (it has the most recent version)
         // If a change is submitted to remote, it's moved to workspace with Review status
@@ -62,7 +58,7 @@ impl ChangeApproveOperation {
                     .get_change(&change_id)
                     .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
                     .ok_or_else(|| {
-                        ObjectsTreeError::SerializationError(format!(
+                        OperationError::NotFound(format!(
                             "Change '{change_id}' not found in workspace or index"
                         ))
                     })?
@@ -80,7 +76,7 @@ impl ChangeApproveOperation {
                 "Cannot approve change '{}' ({}) - it must be Local or Review status (current: {:?})",
                 change.name, change.id, change.status
             );
-            return Err(ObjectsTreeError::SerializationError(format!(
+            return Err(OperationError::Conflict(format!(
                 "Cannot approve change '{}' - it must be Local or Review status (current: {:?})",
                 change.name, change.status
             )));
@@ -109,7 +105,7 @@ impl ChangeApproveOperation {
                             "Cannot approve change '{}' - there's already a local change '{}' on top of the index",
                             change.name, top_change.name
                         );
-                        return Err(ObjectsTreeError::SerializationError(format!(
+                        return Err(OperationError::Conflict(format!(
                             "Cannot approve change '{}' - there's already a local change '{}' on top of the index",
                             change.name, top_change.name
                         )));
@@ -118,17 +114,49 @@ impl ChangeApproveOperation {
             }
         }
 
+        // Refuse to merge over verbs and properties changed since the change's base,
+        // unless forced
+        if request.force.unwrap_or(false) {
+            info!("Force-approving change '{}', skipping conflict detection", change.name);
+        } else {
+            let conflicts = detect_change_conflicts(&self.database, &change)?;
+            if !conflicts.is_empty() {
+                let summary = conflicts
+                    .iter()
+                    .map(|c| c.summary())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                error!(
+                    "Cannot approve change '{}' - conflicts with changes merged since its base: {}",
+                    change.name, summary
+                );
+                return Err(OperationError::Conflict(format!(
+                    "Cannot approve change '{}' - conflicts with changes merged since its base: {}. Use force=true to approve anyway.",
+                    change.name, summary
+                )));
+            }
+        }
+
         // Build the ObjectDiffModel before changing the status
         // If this is the top change (current working change), return an empty diff
-        // because there are no NEW changes relative to the current state
-        let diff_model = if is_top_change {
+        // because there are no NEW changes relative to the current state, unless the
+        // caller asked for details of what was approved
+        let details = request.details.unwrap_or(false);
+        let diff_model = if is_top_change && !details {
             info!(
                 "Approving top change - returning empty diff (no new changes relative to current state)"
             );
             ObjectDiffModel::new()
         } else {
-            info!("Approving non-top change - building diff model");
-            build_object_diff_from_change(&self.database, &change)?
+            info!(
+                "Approving change - building diff model (details: {})",
+                details
+            );
+            let mut diff_model = build_object_diff_from_change(&self.database, &change)?;
+            if details {
+                add_line_diffs_for_change(&self.database, &mut diff_model, &change)?;
+            }
+            diff_model
         };
 
         // Remember original status to determine if we need to add to change_order
@@ -142,59 +170,64 @@ impl ChangeApproveOperation {
         // Update the change status to Merged
         change.status = ChangeStatus::Merged;
 
-        // If the change was in workspace (Review status), add it back to the index
-        if was_in_workspace {
-            info!("Processing workspace approval - will add back to index");
+        // Merge the change across the index, changes and workspace all-or-nothing
+        self.database.write_batch(|| -> Result<(), OperationError> {
+            // If the change was in workspace (Review status), add it back to the index
+            if was_in_workspace {
+                info!("Processing workspace approval - will add back to index");
 
-            // Store the change in the index
-            self.database
-                .index()
-                .store_change(&change)
-                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+                // Store the change in the index
+                self.database
+                    .index()
+                    .store_change(&change)
+                    .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
 
-            info!("Stored change in index, now calling append_change_to_order");
+                info!("Stored change in index, now calling append_change_to_order");
 
-            // Add the change to change_order (as merged history)
-            // Use append_change_to_order which adds to the end without setting as top_change
-            self.database
-                .index()
-                .append_change_to_order(&change_id)
-                .map_err(|e| {
-                    error!("Failed to append change to order: {}", e);
-                    ObjectsTreeError::SerializationError(e.to_string())
-                })?;
+                // Add the change to change_order (as merged history)
+                // Use append_change_to_order which adds to the end without setting as top_change
+                self.database
+                    .index()
+                    .append_change_to_order(&change_id)
+                    .map_err(|e| {
+                        error!("Failed to append change to order: {}", e);
+                        ObjectsTreeError::SerializationError(e.to_string())
+                    })?;
 
-            info!("Added change '{}' back to index as merged", change.name);
-        } else {
-            info!("Processing local approval - will update in place");
+                info!("Added change '{}' back to index as merged", change.name);
+            } else {
+                info!("Processing local approval - will update in place");
+
+                // Change was already in index (Local status), just update it
+                self.database
+                    .index()
+                    .update_change(&change)
+                    .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+            }
 
-            // Change was already in index (Local status), just update it
+            // Clear the top_change pointer (change stays in history as merged)
             self.database
                 .index()
-                .update_change(&change)
+                .clear_top_change_if(&change_id)
                 .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
-        }
-
-        // Clear the top_change pointer (change stays in history as merged)
-        self.database
-            .index()
-            .clear_top_change_if(&change_id)
-            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
 
-        // Remove the change from workspace if it exists there
-        if self
-            .database
-            .workspace()
-            .get_workspace_change(&change_id)
-            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
-            .is_some()
-        {
-            self.database
+            // Remove the change from workspace if it exists there
+            if self
+                .database
                 .workspace()
-                .delete_workspace_change(&change_id)
-                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
-            info!("Removed change '{}' from workspace", change.name);
-        }
+                .get_workspace_change(&change_id)
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+                .is_some()
+            {
+                self.database
+                    .workspace()
+                    .delete_workspace_change(&change_id)
+                    .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+                info!("Removed change '{}' from workspace", change.name);
+            }
+
+            Ok(())
+        })?;
 
         if was_in_workspace {
             info!(
@@ -208,14 +241,12 @@ impl ChangeApproveOperation {
             );
         }
 
-        // Flush database to ensure all writes are persisted before git backup
-        // This prevents race conditions where git backup reads before writes are committed
-        self.database
-            .flush()
-            .map_err(|e| ObjectsTreeError::SerializationError(format!("Failed to flush database: {}", e)))?;
-
-        // Trigger git backup in background (non-blocking)
-        git_backup::trigger_git_backup(self.database.clone(), self.config.clone());
+        // Trigger git backup in background once the approval commits
+        git_backup::trigger_git_backup(
+            self.database.clone(),
+            self.config.clone(),
+            "change/approve",
+        );
 
         Ok(diff_model)
     }
@@ -239,16 +270,31 @@ impl Operation for ChangeApproveOperation {
         history. This operation is typically used by reviewers or administrators to accept changes that have \
         been submitted for review (with 'Review' status). Once approved, the change becomes part of the permanent \
         repository history with 'Merged' status. For changes in workspace (submitted remotely), approval adds \
-        them to the index. For local changes, approval updates them in place. This is a privileged operation - \
+        them to the index. For local changes, approval updates them in place. Before merging, each object in \
+        the change is compared against the index change it was based on: if a change merged since then \
+        modified the same verb or property, approval is refused with a list of the conflicting objects and \
+        members (rebase the change, or pass force=true to approve anyway). This is a privileged operation - \
         users must have the ApproveChanges permission to execute it."
     }
 
     fn parameters(&self) -> Vec<OperationParameter> {
-        vec![OperationParameter {
-            name: "change_id".to_string(),
-            description: "The ID of the change to approve (get from workspace/list)".to_string(),
-            required: true,
-        }]
+        vec![
+            OperationParameter {
+                name: "change_id".to_string(),
+                description: "The ID of the change to approve (get from workspace/list)".to_string(),
+                required: true,
+            },
+            OperationParameter {
+                name: "force".to_string(),
+                description: "Optional. Set to 'true' to approve even if verbs or properties conflict with changes merged since the change's base (default: false)".to_string(),
+                required: false,
+            },
+            OperationParameter {
+                name: "details".to_string(),
+                description: "Optional. Set to 'true' to include unified verb code diffs (verb_diffs) and property before/after literal values (prop_diffs) in the returned diff (default: false)".to_string(),
+                required: false,
+            },
+        ]
     }
 
     fn examples(&self) -> Vec<OperationExample> {
@@ -289,30 +335,30 @@ player:tell("Added: ", length(diff["added_objects"]), " objects");"#
                 "Operation executed successfully",
                 r#"["objects_renamed" -> [], "objects_deleted" -> {}, "objects_added" -> {}, "objects_modified" -> {}, "changes" -> {}]"#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - Cannot approve change in current state",
-                r#"E_INVARG("Error: Cannot approve change 'my-change' - it must be Local or Review status (current: Merged)")"#,
+            OperationResponse::bad_request(
+                "Bad Request - Missing change ID argument",
+                r#"E_INVARG("Change approve operation requires a change ID argument")"#,
             ),
-            OperationResponse::new(
-                403,
+            OperationResponse::forbidden(
                 "Forbidden - User lacks permission to approve changes",
-                r#"E_INVARG("Error: User 'player123' does not have permission to approve changes)"#,
+                r#"E_PERM("User 'player123' does not have permission to approve changes")"#,
             ),
-            OperationResponse::new(
-                404,
+            OperationResponse::not_found(
                 "Not Found - Change not found in workspace or index",
-                r#"E_INVARG("Error: Change 'abc-123-def...' not found in workspace or index)"#,
+                r#"E_INVIND("Change 'abc-123-def...' not found in workspace or index")"#,
+            ),
+            OperationResponse::conflict(
+                "Conflict - Change not in an approvable state, or verbs/properties changed upstream since its base",
+                r#"E_NACC("Cannot approve change 'my-change' - conflicts with changes merged since its base: $room (verbs: look; props: description). Use force=true to approve anyway.")"#,
             ),
-            OperationResponse::new(
-                500,
+            OperationResponse::internal_error(
                 "Internal Server Error - Database or system error",
-                r#"E_INVARG("Error: Database error: failed to serialize change")"#,
+                r#"E_INVARG("Serialization error: failed to serialize change")"#,
             ),
         ]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!(
             "Change approve operation received {} arguments for user: {}",
             args.len(),
@@ -321,21 +367,29 @@ player:tell("Added: ", length(diff["added_objects"]), " objects");"#
 
         if args.is_empty() {
             error!("Change approve operation requires a change ID argument");
-            return v_error(E_INVARG.msg("Change approve operation requires a change ID argument"));
+            return Err(OperationError::InvalidArgs(
+                "Change approve operation requires a change ID argument".to_string(),
+            ));
         }
 
         let change_id = args[0].clone();
-        let request = ChangeApproveRequest { change_id };
+        let force = args.get(1).and_then(|s| s.parse::<bool>().ok());
+        let details = args.get(2).and_then(|s| s.parse::<bool>().ok());
+        let request = ChangeApproveRequest {
+            change_id,
+            force,
+            details,
+        };
 
         match self.process_change_approve(request, user) {
             Ok(diff_model) => {
                 info!("Change approve operation completed successfully, returning change diff");
                 // Return the ObjectDiffModel as a MOO variable showing what was approved
-                diff_model.to_moo_var()
+                Ok(diff_model.to_moo_var())
             }
             Err(e) => {
                 error!("Change approve operation failed: {}", e);
-                v_error(E_INVARG.msg(format!("Error: {e}")))
+                Err(e)
             }
         }
     }
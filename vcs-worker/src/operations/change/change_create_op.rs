@@ -1,11 +1,14 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use axum::http::Method;
 use tracing::{error, info};
 
 use crate::database::{DatabaseRef, ObjectsTreeError};
 use crate::providers::index::IndexProvider;
 use crate::types::User;
-use crate::types::{Change, ChangeCreateRequest, ChangeStatus};
+use crate::types::{Change, ChangeCreateRequest, ChangeStatus, Permission};
 
 /// Change create operation that creates a new change
 #[derive(Clone)]
@@ -23,7 +26,7 @@ impl ChangeCreateOperation {
     fn process_change_create(
         &self,
         request: ChangeCreateRequest,
-    ) -> Result<String, ObjectsTreeError> {
+    ) -> Result<String, OperationError> {
         info!(
             "Creating new change '{}' with author '{}'",
             request.name, request.author
@@ -47,7 +50,7 @@ impl ChangeCreateOperation {
                         "Cannot create new change '{}' - already in a local change '{}' ({})",
                         request.name, existing_change.name, existing_change.id
                     );
-                    return Err(ObjectsTreeError::SerializationError(format!(
+                    return Err(OperationError::Conflict(format!(
                         "Already in a local change '{}' ({}). Abandon the current change before creating a new one.",
                         existing_change.name, existing_change.id
                     )));
@@ -63,6 +66,15 @@ impl ChangeCreateOperation {
             timestamp,
         );
 
+        // Record the index tip this change is based on (the merge base for change/rebase)
+        let index_change_id = self
+            .database
+            .index()
+            .get_change_order()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+            .last()
+            .cloned();
+
         let change = Change {
             id: change_id,
             name: request.name.clone(),
@@ -74,7 +86,7 @@ impl ChangeCreateOperation {
             modified_objects: Vec::new(),
             deleted_objects: Vec::new(),
             renamed_objects: Vec::new(),
-            index_change_id: None,
+            index_change_id,
             verb_rename_hints: Vec::new(),
             property_rename_hints: Vec::new(),
         };
@@ -184,31 +196,39 @@ impl Operation for ChangeCreateOperation {
                 "Operation executed successfully",
                 r#""Created change 'fix-login-bug' with ID: abc-123...""#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - Already in a local change or invalid arguments",
-                r#"E_INVARG("Error: Already in a local change 'existing-change' (abc-123)")"#,
+            OperationResponse::bad_request(
+                "Bad Request - Name and author are required",
+                r#"E_INVARG("Name and author are required")"#,
             ),
-            OperationResponse::new(
-                500,
+            OperationResponse::forbidden(
+                "Forbidden - User lacks SubmitChanges permission",
+                r#"E_PERM("User 'player' does not have permission to manage changes")"#,
+            ),
+            OperationResponse::conflict(
+                "Conflict - Already in a local change",
+                r#"E_NACC("Already in a local change 'existing-change' (abc-123). Abandon the current change before creating a new one.")"#,
+            ),
+            OperationResponse::internal_error(
                 "Internal Server Error - Database or system error",
-                r#"E_INVARG("Error: Database error: failed to create change")"#,
+                r#"E_INVARG("Serialization error: failed to create change")"#,
             ),
         ]
     }
 
-    fn execute(&self, args: Vec<String>, _user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!(
             "Change create operation received {} arguments: {:?}",
             args.len(),
             args
         );
 
+        require_permission(user, Permission::SubmitChanges, "manage changes")?;
+
         if args.len() < 2 {
             error!("Change create operation requires at least name and author");
-            return moor_var::v_error(
-                moor_var::E_INVARG.msg("Error: Name and author are required"),
-            );
+            return Err(OperationError::InvalidArgs(
+                "Name and author are required".to_string(),
+            ));
         }
 
         let name = args[0].clone();
@@ -228,11 +248,11 @@ impl Operation for ChangeCreateOperation {
         match self.process_change_create(request) {
             Ok(result) => {
                 info!("Change create operation completed successfully");
-                moor_var::v_str(&result)
+                Ok(moor_var::v_str(&result))
             }
             Err(e) => {
                 error!("Change create operation failed: {}", e);
-                moor_var::v_error(moor_var::E_INVARG.msg(format!("Error: {e}")))
+                Err(e)
             }
         }
     }
@@ -0,0 +1,254 @@
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+};
+use axum::http::Method;
+use moor_var::{Var, v_list, v_map, v_str};
+use tracing::{error, info};
+
+use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::object_diff::{
+    add_line_diffs_for_change, build_object_diff_against_baseline, build_object_diff_from_change,
+};
+use crate::object_merge::{load_merged_changes, merged_object_states};
+use crate::providers::index::IndexProvider;
+use crate::providers::workspace::WorkspaceProvider;
+use crate::types::{Change, ChangeDiffRequest, User};
+
+/// Change diff operation that returns the complete, line-level diff of a change
+#[derive(Clone)]
+pub struct ChangeDiffOperation {
+    database: DatabaseRef,
+}
+
+impl ChangeDiffOperation {
+    /// Create a new change diff operation
+    pub fn new(database: DatabaseRef) -> Self {
+        Self { database }
+    }
+
+    /// Look up a change in the index, falling back to the workspace
+    fn find_change(&self, change_id: &str) -> Result<Change, OperationError> {
+        let change_id = self.database.resolve_change_id(change_id)?;
+
+        if let Some(change) = self
+            .database
+            .index()
+            .get_change(&change_id)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+        {
+            return Ok(change);
+        }
+
+        self.database
+            .workspace()
+            .get_workspace_change(&change_id)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+            .ok_or_else(|| {
+                OperationError::NotFound(format!(
+                    "Change '{change_id}' not found in index or workspace"
+                ))
+            })
+    }
+
+    /// Process the change diff request
+    fn process_change_diff(&self, request: ChangeDiffRequest) -> Result<Var, OperationError> {
+        let change = self.find_change(&request.change_id)?;
+
+        let (diff_model, baseline_id) = match &request.baseline_change_id {
+            None => {
+                // Compare each object against its previous version, as change/status does
+                let mut diff_model = build_object_diff_from_change(&self.database, &change)?;
+                add_line_diffs_for_change(&self.database, &mut diff_model, &change)?;
+                (diff_model, None)
+            }
+            Some(baseline) => {
+                let baseline_id = self.database.resolve_change_id(baseline)?;
+                let merged_changes = load_merged_changes(&self.database)?;
+                let position = merged_changes
+                    .iter()
+                    .position(|c| c.id == baseline_id)
+                    .ok_or_else(|| {
+                        OperationError::NotFound(format!(
+                            "Baseline change '{baseline_id}' is not a merged change in the index"
+                        ))
+                    })?;
+                let baseline_states = merged_object_states(&merged_changes[..=position]);
+                let diff_model =
+                    build_object_diff_against_baseline(&self.database, &change, &baseline_states)?;
+                (diff_model, Some(baseline_id))
+            }
+        };
+
+        info!(
+            "Built diff for change '{}' ({}) with {} object changes",
+            change.name,
+            change.id,
+            diff_model.changes.len()
+        );
+
+        let verb_hints: Vec<Var> = change
+            .verb_rename_hints
+            .iter()
+            .map(|hint| {
+                v_map(&[
+                    (v_str("object_name"), v_str(&hint.object_name)),
+                    (v_str("from_verb"), v_str(&hint.from_verb)),
+                    (v_str("to_verb"), v_str(&hint.to_verb)),
+                ])
+            })
+            .collect();
+        let prop_hints: Vec<Var> = change
+            .property_rename_hints
+            .iter()
+            .map(|hint| {
+                v_map(&[
+                    (v_str("object_name"), v_str(&hint.object_name)),
+                    (v_str("from_prop"), v_str(&hint.from_prop)),
+                    (v_str("to_prop"), v_str(&hint.to_prop)),
+                ])
+            })
+            .collect();
+
+        Ok(v_map(&[
+            (v_str("change_id"), v_str(&change.id)),
+            (v_str("name"), v_str(&change.name)),
+            (
+                v_str("baseline_change_id"),
+                v_str(baseline_id.as_deref().unwrap_or("")),
+            ),
+            (v_str("diff"), diff_model.to_moo_var()),
+            (v_str("verb_rename_hints"), v_list(&verb_hints)),
+            (v_str("property_rename_hints"), v_list(&prop_hints)),
+        ]))
+    }
+}
+
+impl Operation for ChangeDiffOperation {
+    fn name(&self) -> &'static str {
+        "change/diff"
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> &'static str {
+        "Returns the complete diff of a change: the ObjectDiffModel plus unified verb diffs, property before/after values and rename hints for every object it touches."
+    }
+
+    fn response_content_type(&self) -> &'static str {
+        "text/x-moo"
+    }
+
+    fn philosophy(&self) -> &'static str {
+        "Gives review tooling everything it needs to render a changelist in one call. Where change/status \
+        only says which verbs and properties changed, change/diff also includes a unified diff of the \
+        decompiled code of every touched verb (verb_diffs) and the before/after literal values of every \
+        touched property (prop_diffs), together with the verb and property rename hints recorded on the \
+        change. By default each object is compared against its previous version. Pass a baseline change \
+        (a merged change in the index) to instead compare against the state of the index as of that \
+        change; objects that did not exist at the baseline are reported as added."
+    }
+
+    fn parameters(&self) -> Vec<OperationParameter> {
+        vec![
+            OperationParameter {
+                name: "change_id".to_string(),
+                description: "The change ID (full 64-char hash or short 12-char prefix) to diff. The change may be in the index or the workspace.".to_string(),
+                required: true,
+            },
+            OperationParameter {
+                name: "baseline_change_id".to_string(),
+                description: "Optional merged change ID to compare against. If not provided, each object is compared against its previous version.".to_string(),
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> Vec<OperationExample> {
+        vec![
+            OperationExample {
+                description: "Render every verb diff of a change".to_string(),
+                moocode: r#"result = worker_request("vcs", {"change/diff", "abcdef123456"});
+for change in (result["diff"]["changes"])
+  player:tell("Object ", change["obj_id"], ":");
+  for verb in (mapkeys(change["verb_diffs"]))
+    player:tell("  Verb ", verb, ":");
+    for line in (change["verb_diffs"][verb])
+      player:tell("    ", line);
+    endfor
+  endfor
+endfor"#
+                    .to_string(),
+                http_curl: Some(
+                    r#"curl -X GET 'http://localhost:8081/api/change/diff?change_id=abcdef123456'"#
+                        .to_string(),
+                ),
+            },
+            OperationExample {
+                description: "Diff a change against an older merged change".to_string(),
+                moocode: r#"result = worker_request("vcs", {"change/diff", "abcdef123456", "123456abcdef"});"#
+                    .to_string(),
+                http_curl: Some(
+                    r#"curl -X GET 'http://localhost:8081/api/change/diff?change_id=abcdef123456&baseline_change_id=123456abcdef'"#
+                        .to_string(),
+                ),
+            },
+        ]
+    }
+
+    fn routes(&self) -> Vec<OperationRoute> {
+        vec![OperationRoute {
+            path: "/api/change/diff".to_string(),
+            method: Method::GET,
+            is_json: false,
+        }]
+    }
+
+    fn responses(&self) -> Vec<crate::operations::OperationResponse> {
+        use crate::operations::OperationResponse;
+        vec![
+            OperationResponse::success(
+                "Operation executed successfully",
+                r#"["change_id" -> "abc123...", "name" -> "fix-look", "baseline_change_id" -> "", "diff" -> ["objects_renamed" -> [], "objects_deleted" -> {}, "objects_added" -> {}, "objects_modified" -> {"obj3"}, "changes" -> {["obj_id" -> "obj3", "verbs_modified" -> {"look"}, "verbs_added" -> {}, "verbs_renamed" -> [], "verbs_deleted" -> {}, "props_modified" -> {"description"}, "props_added" -> {}, "props_renamed" -> [], "props_deleted" -> {}, "verb_diffs" -> ["look" -> {"@@ -1,1 +1,1 @@", "-return 1;", "+return 2;"}], "prop_diffs" -> ["description" -> ["before" -> "\"old\"", "after" -> "\"new\""]]]}], "verb_rename_hints" -> {}, "property_rename_hints" -> {}]"#,
+            ),
+            OperationResponse::bad_request(
+                "Bad Request - Missing change ID",
+                r#"E_INVARG("Change ID is required")"#,
+            ),
+            OperationResponse::not_found(
+                "Not Found - The change or the baseline change does not exist",
+                r#"E_INVIND("Change 'abc123' not found in index or workspace")"#,
+            ),
+            OperationResponse::internal_error(
+                "Internal Server Error - Database or system error",
+                r#"E_INVARG("Serialization error: failed to build diff model")"#,
+            ),
+        ]
+    }
+
+    fn execute(&self, args: Vec<String>, _user: &User) -> Result<Var, OperationError> {
+        let Some(change_id) = args.first().filter(|s| !s.is_empty()) else {
+            error!("Change diff operation requires a change ID");
+            return Err(OperationError::InvalidArgs(
+                "Change ID is required".to_string(),
+            ));
+        };
+
+        let request = ChangeDiffRequest {
+            change_id: change_id.clone(),
+            baseline_change_id: args.get(1).filter(|s| !s.is_empty()).cloned(),
+        };
+
+        match self.process_change_diff(request) {
+            Ok(result) => {
+                info!("Change diff operation completed successfully");
+                Ok(result)
+            }
+            Err(e) => {
+                error!("Change diff operation failed: {}", e);
+                Err(e)
+            }
+        }
+    }
+}
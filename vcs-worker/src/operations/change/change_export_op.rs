@@ -0,0 +1,310 @@
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
+use axum::http::Method;
+use tracing::{error, info};
+
+use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::providers::index::IndexProvider;
+use crate::providers::objects::ObjectsProvider;
+use crate::providers::refs::RefsProvider;
+use crate::providers::workspace::WorkspaceProvider;
+use crate::types::{
+    CHANGE_PATCH_FORMAT_VERSION, Change, ChangeExportRequest, ChangePatch, PatchObject, Permission,
+    User, VcsObjectType,
+};
+
+/// Change export operation that packages a single change as a portable patch
+#[derive(Clone)]
+pub struct ChangeExportOperation {
+    database: DatabaseRef,
+}
+
+impl ChangeExportOperation {
+    /// Create a new change export operation
+    pub fn new(database: DatabaseRef) -> Self {
+        Self { database }
+    }
+
+    /// Load the content of an object version, or the current version when `version` is None
+    fn load_content(
+        &self,
+        object_type: VcsObjectType,
+        name: &str,
+        version: Option<u64>,
+    ) -> Result<Option<String>, ObjectsTreeError> {
+        let Some(sha256) = self
+            .database
+            .refs()
+            .get_ref(object_type, name, version)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+        else {
+            return Ok(None);
+        };
+        self.database
+            .objects()
+            .get(&sha256)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))
+    }
+
+    /// Build the patch for a change
+    fn build_patch(&self, change: Change) -> Result<ChangePatch, OperationError> {
+        let mut objects = Vec::new();
+
+        // Renamed objects are recorded under their old name in the base
+        let rename_source = |object_type: VcsObjectType, name: &str| {
+            change
+                .renamed_objects
+                .iter()
+                .find(|r| r.to.object_type == object_type && r.to.name == name)
+                .map(|r| r.from.clone())
+        };
+
+        for obj_info in &change.added_objects {
+            let content = self
+                .load_content(obj_info.object_type, &obj_info.name, Some(obj_info.version))?
+                .ok_or_else(|| {
+                    OperationError::Internal(format!(
+                        "Version {} of '{}' not found",
+                        obj_info.version, obj_info.name
+                    ))
+                })?;
+            objects.push(PatchObject {
+                object_type: obj_info.object_type,
+                name: obj_info.name.clone(),
+                base_name: obj_info.name.clone(),
+                content: Some(content),
+                base_content: None,
+            });
+        }
+
+        for obj_info in &change.modified_objects {
+            let content = self
+                .load_content(obj_info.object_type, &obj_info.name, Some(obj_info.version))?
+                .ok_or_else(|| {
+                    OperationError::Internal(format!(
+                        "Version {} of '{}' not found",
+                        obj_info.version, obj_info.name
+                    ))
+                })?;
+            let (base_name, base_content) =
+                match rename_source(obj_info.object_type, &obj_info.name) {
+                    Some(from) => {
+                        let base =
+                            self.load_content(from.object_type, &from.name, Some(from.version))?;
+                        (from.name, base)
+                    }
+                    None => {
+                        let base = match obj_info.version.saturating_sub(1) {
+                            0 => None,
+                            version => self.load_content(
+                                obj_info.object_type,
+                                &obj_info.name,
+                                Some(version),
+                            )?,
+                        };
+                        (obj_info.name.clone(), base)
+                    }
+                };
+            objects.push(PatchObject {
+                object_type: obj_info.object_type,
+                name: obj_info.name.clone(),
+                base_name,
+                content: Some(content),
+                base_content,
+            });
+        }
+
+        // Deleted metas follow their object, so only object deletions are exported
+        for obj_info in change
+            .deleted_objects
+            .iter()
+            .filter(|o| o.object_type == VcsObjectType::MooObject)
+        {
+            objects.push(PatchObject {
+                object_type: obj_info.object_type,
+                name: obj_info.name.clone(),
+                base_name: obj_info.name.clone(),
+                content: None,
+                base_content: self.load_content(obj_info.object_type, &obj_info.name, None)?,
+            });
+        }
+
+        // Objects that were only renamed still need their base for conflict detection
+        let rename_only: Vec<_> = change
+            .renamed_objects
+            .iter()
+            .filter(|r| {
+                r.from.object_type == VcsObjectType::MooObject
+                    && !objects
+                        .iter()
+                        .any(|o| o.object_type == r.to.object_type && o.name == r.to.name)
+            })
+            .collect();
+        for renamed in rename_only {
+            objects.push(PatchObject {
+                object_type: renamed.to.object_type,
+                name: renamed.to.name.clone(),
+                base_name: renamed.from.name.clone(),
+                content: None,
+                base_content: self.load_content(
+                    renamed.from.object_type,
+                    &renamed.from.name,
+                    Some(renamed.from.version),
+                )?,
+            });
+        }
+
+        Ok(ChangePatch {
+            format_version: CHANGE_PATCH_FORMAT_VERSION,
+            change,
+            objects,
+        })
+    }
+
+    /// Process the change export request
+    fn process_change_export(
+        &self,
+        request: ChangeExportRequest,
+        user: &User,
+    ) -> Result<String, OperationError> {
+        require_permission(user, Permission::Clone, "export changes")?;
+
+        let change_id = self.database.resolve_change_id(&request.change_id)?;
+        let change = match self
+            .database
+            .index()
+            .get_change(&change_id)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+        {
+            Some(change) => change,
+            None => self
+                .database
+                .workspace()
+                .get_workspace_change(&change_id)
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+                .ok_or_else(|| {
+                    OperationError::NotFound(format!(
+                        "Change '{change_id}' not found in index or workspace"
+                    ))
+                })?,
+        };
+
+        let patch = self.build_patch(change)?;
+        info!(
+            "Exported change '{}' ({}) with {} objects",
+            patch.change.name,
+            patch.change.id,
+            patch.objects.len()
+        );
+
+        serde_json::to_string(&patch)
+            .map_err(|e| OperationError::Internal(format!("Failed to serialize: {e}")))
+    }
+}
+
+impl Operation for ChangeExportOperation {
+    fn name(&self) -> &'static str {
+        "change/export"
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> &'static str {
+        "Export a single change as a self-contained JSON patch that change/import can apply on another worker"
+    }
+
+    fn response_content_type(&self) -> &'static str {
+        "text/x-moo"
+    }
+
+    fn philosophy(&self) -> &'static str {
+        "Moves a single change between unrelated worlds without a full clone. The patch contains the change \
+        metadata (including verb and property rename hints), the objdef text of every object version the \
+        change records, meta YAML, and the content each object had before the change. That base content \
+        lets change/import on the receiving worker detect objects that have diverged there, so teams can \
+        share fixes across game instances safely."
+    }
+
+    fn parameters(&self) -> Vec<OperationParameter> {
+        vec![OperationParameter {
+            name: "change_id".to_string(),
+            description: "The change ID (full 64-char hash or short 12-char prefix) to export. The change may be in the index or the workspace.".to_string(),
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> Vec<OperationExample> {
+        vec![OperationExample {
+            description: "Export a change as a patch".to_string(),
+            moocode: r#"patch = worker_request("vcs", {"change/export", "abcdef123456"});
+// Returns the patch as a JSON string; pass it to change/import on another worker"#
+                .to_string(),
+            http_curl: Some(
+                r#"curl -X GET 'http://localhost:8081/api/change/export?change_id=abcdef123456' > fix.patch.json"#
+                    .to_string(),
+            ),
+        }]
+    }
+
+    fn routes(&self) -> Vec<OperationRoute> {
+        vec![OperationRoute {
+            path: "/api/change/export".to_string(),
+            method: Method::GET,
+            is_json: false,
+        }]
+    }
+
+    fn responses(&self) -> Vec<crate::operations::OperationResponse> {
+        use crate::operations::OperationResponse;
+        vec![
+            OperationResponse::success(
+                "Returns the change patch as a JSON string",
+                r#""{\"format_version\":1,\"change\":{\"id\":\"abc123...\",\"name\":\"fix-look\",...},\"objects\":[{\"object_type\":\"MooObject\",\"name\":\"$room\",\"base_name\":\"$room\",\"content\":\"object #3...\",\"base_content\":\"object #3...\"}]}""#,
+            ),
+            OperationResponse::bad_request(
+                "Bad Request - Missing change ID",
+                r#"E_INVARG("Change ID is required")"#,
+            ),
+            OperationResponse::forbidden(
+                "Forbidden - User lacks Clone permission",
+                r#"E_PERM("User 'player' does not have permission to export changes")"#,
+            ),
+            OperationResponse::not_found(
+                "Not Found - Change does not exist",
+                r#"E_INVIND("Change 'abc123' not found in index or workspace")"#,
+            ),
+            OperationResponse::internal_error(
+                "Internal Server Error - Database or serialization error",
+                r#"E_INVARG("Failed to serialize: ...")"#,
+            ),
+        ]
+    }
+
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
+        let Some(change_id) = args.first().filter(|s| !s.is_empty()) else {
+            error!("Change export operation requires a change ID");
+            return Err(OperationError::InvalidArgs(
+                "Change ID is required".to_string(),
+            ));
+        };
+
+        let request = ChangeExportRequest {
+            change_id: change_id.clone(),
+        };
+
+        match self.process_change_export(request, user) {
+            Ok(json) => {
+                info!("Change export operation completed ({} bytes)", json.len());
+                Ok(moor_var::v_str(&json))
+            }
+            Err(e) => {
+                error!("Change export operation failed: {}", e);
+                Err(e)
+            }
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use crate::operations::{
+    ChangeCreateOperation, ObjectDeleteOperation, ObjectRenameOperation, ObjectUpdateOperation,
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
+use axum::http::Method;
+use moor_var::{Var, v_list, v_map, v_str};
+use tracing::{error, info};
+
+use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::object_merge::ObjectConflict;
+use crate::operations::meta::save_and_track_meta;
+use crate::providers::index::IndexProvider;
+use crate::providers::objects::ObjectsProvider;
+use crate::providers::refs::RefsProvider;
+use crate::types::{
+    CHANGE_PATCH_FORMAT_VERSION, ChangeImportRequest, ChangePatch, PatchObject, Permission, User,
+    VcsObjectType,
+};
+
+/// Change import operation that applies a change patch as a new local change
+#[derive(Clone)]
+pub struct ChangeImportOperation {
+    database: DatabaseRef,
+}
+
+impl ChangeImportOperation {
+    /// Create a new change import operation
+    pub fn new(database: DatabaseRef) -> Self {
+        Self { database }
+    }
+
+    /// Load the current content of an object on this worker
+    fn current_content(
+        &self,
+        object_type: VcsObjectType,
+        name: &str,
+    ) -> Result<Option<String>, ObjectsTreeError> {
+        let Some(sha256) = self
+            .database
+            .refs()
+            .get_ref(object_type, name, None)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+        else {
+            return Ok(None);
+        };
+        self.database
+            .objects()
+            .get(&sha256)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))
+    }
+
+    /// Check a patched object against this worker's current refs
+    /// Returns None when the patch applies cleanly
+    fn check_object(&self, object: &PatchObject) -> Result<Option<String>, ObjectsTreeError> {
+        let current = self.current_content(object.object_type, &object.base_name)?;
+
+        // A patch object without content deletes (or only renames) the object
+        let removes = object.content.is_none();
+        let reason = match (&object.base_content, &current) {
+            // Already in the state the patch produces
+            (_, Some(current)) if object.content.as_ref() == Some(current) => None,
+            (Some(base), Some(current)) if base != current => Some("modified locally"),
+            (Some(_), Some(_)) => None,
+            // Deletions exported without a known base only need the object to exist
+            (None, Some(_)) if removes => None,
+            (None, Some(_)) => Some("already exists"),
+            (Some(_), None) => Some("missing"),
+            (None, None) if removes => Some("missing"),
+            (None, None) => None,
+        };
+        if let Some(reason) = reason {
+            return Ok(Some(reason.to_string()));
+        }
+
+        if object.base_name != object.name
+            && self
+                .current_content(object.object_type, &object.name)?
+                .is_some()
+        {
+            return Ok(Some(format!(
+                "rename target '{}' already exists",
+                object.name
+            )));
+        }
+
+        Ok(None)
+    }
+
+    /// Content of an object recorded by the patch
+    fn patch_content<'a>(
+        patch: &'a ChangePatch,
+        object_type: VcsObjectType,
+        name: &str,
+    ) -> Result<&'a str, OperationError> {
+        patch
+            .objects
+            .iter()
+            .find(|o| o.object_type == object_type && o.name == name)
+            .and_then(|o| o.content.as_deref())
+            .ok_or_else(|| {
+                OperationError::InvalidArgs(format!("Patch is missing the content of '{name}'"))
+            })
+    }
+
+    /// Process the change import request
+    fn process_change_import(
+        &self,
+        request: ChangeImportRequest,
+        user: &User,
+    ) -> Result<Var, OperationError> {
+        require_permission(user, Permission::SubmitChanges, "import changes")?;
+
+        let patch: ChangePatch = serde_json::from_str(&request.patch)
+            .map_err(|e| OperationError::InvalidArgs(format!("Invalid change patch: {e}")))?;
+        if patch.format_version != CHANGE_PATCH_FORMAT_VERSION {
+            return Err(OperationError::InvalidArgs(format!(
+                "Unsupported change patch format version {} (expected {})",
+                patch.format_version, CHANGE_PATCH_FORMAT_VERSION
+            )));
+        }
+
+        // Detect objects that diverged on this worker before touching anything
+        let mut conflicts = Vec::new();
+        for object in &patch.objects {
+            if let Some(reason) = self.check_object(object)? {
+                let obj_id = match object.object_type {
+                    VcsObjectType::MooObject => object.name.clone(),
+                    VcsObjectType::MooMetaObject => format!("{} (meta)", object.name),
+                };
+                conflicts.push(ObjectConflict::with_reason(obj_id, reason));
+            }
+        }
+        if !conflicts.is_empty() {
+            let summaries: Vec<String> = conflicts.iter().map(|c| c.summary()).collect();
+            error!(
+                "Cannot import change '{}' - {} conflicting objects",
+                patch.change.name,
+                conflicts.len()
+            );
+            return Err(OperationError::Conflict(format!(
+                "Cannot import change '{}' - conflicts with this worker's objects: {}",
+                patch.change.name,
+                summaries.join(", ")
+            )));
+        }
+
+        let source = &patch.change;
+        info!(
+            "User '{}' importing change '{}' ({}) with {} objects",
+            user.id,
+            source.name,
+            source.id,
+            patch.objects.len()
+        );
+
+        // Create the new local change; this fails if one is already in progress
+        ChangeCreateOperation::new(self.database.clone()).execute(
+            vec![
+                source.name.clone(),
+                source.author.clone(),
+                source.description.clone().unwrap_or_default(),
+            ],
+            user,
+        )?;
+
+        // Replay the change through the regular object operations
+        let rename_op = ObjectRenameOperation::new(self.database.clone());
+        for renamed in source
+            .renamed_objects
+            .iter()
+            .filter(|r| r.from.object_type == VcsObjectType::MooObject)
+        {
+            rename_op.execute(
+                vec![renamed.from.name.clone(), renamed.to.name.clone()],
+                user,
+            )?;
+        }
+
+        let update_op = ObjectUpdateOperation::new(self.database.clone());
+        for obj_info in source
+            .added_objects
+            .iter()
+            .chain(source.modified_objects.iter())
+            .filter(|o| o.object_type == VcsObjectType::MooObject)
+        {
+            let content = Self::patch_content(&patch, obj_info.object_type, &obj_info.name)?;
+            update_op.execute(vec![obj_info.name.clone(), content.to_string()], user)?;
+        }
+
+        let delete_op = ObjectDeleteOperation::new(self.database.clone());
+        for obj_info in source
+            .deleted_objects
+            .iter()
+            .filter(|o| o.object_type == VcsObjectType::MooObject)
+        {
+            delete_op.execute(vec![obj_info.name.clone()], user)?;
+        }
+
+        let mut change = self
+            .database
+            .index()
+            .get_or_create_local_change(Some(user.id.clone()))
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+
+        for obj_info in source
+            .added_objects
+            .iter()
+            .chain(source.modified_objects.iter())
+            .filter(|o| o.object_type == VcsObjectType::MooMetaObject)
+        {
+            let yaml = Self::patch_content(&patch, obj_info.object_type, &obj_info.name)?;
+            let meta =
+                self.database.objects().parse_meta_dump(yaml).map_err(|e| {
+                    OperationError::InvalidArgs(format!("Invalid meta in patch: {e}"))
+                })?;
+            let meta_existed_before = self
+                .current_content(VcsObjectType::MooMetaObject, &obj_info.name)?
+                .is_some();
+            save_and_track_meta(
+                &self.database,
+                &meta,
+                &obj_info.name,
+                meta_existed_before,
+                &mut change,
+            )?;
+        }
+
+        // Carry over the rename hints so diffs of the imported change match the original
+        for hint in &source.verb_rename_hints {
+            if !change.verb_rename_hints.contains(hint) {
+                change.verb_rename_hints.push(hint.clone());
+            }
+        }
+        for hint in &source.property_rename_hints {
+            if !change.property_rename_hints.contains(hint) {
+                change.property_rename_hints.push(hint.clone());
+            }
+        }
+        self.database
+            .index()
+            .update_change(&change)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+
+        let objects: Vec<Var> = patch
+            .objects
+            .iter()
+            .filter(|o| o.object_type == VcsObjectType::MooObject)
+            .map(|o| v_str(&o.name))
+            .collect();
+
+        info!(
+            "Imported change '{}' as local change '{}'",
+            source.id, change.id
+        );
+        Ok(v_map(&[
+            (v_str("change_id"), v_str(&change.id)),
+            (v_str("name"), v_str(&change.name)),
+            (v_str("source_change_id"), v_str(&source.id)),
+            (v_str("objects"), v_list(&objects)),
+        ]))
+    }
+}
+
+impl Operation for ChangeImportOperation {
+    fn name(&self) -> &'static str {
+        "change/import"
+    }
+
+    fn description(&self) -> &'static str {
+        "Apply a patch produced by change/export as a new local change, refusing if any object diverged on this worker"
+    }
+
+    fn response_content_type(&self) -> &'static str {
+        "text/x-moo"
+    }
+
+    fn philosophy(&self) -> &'static str {
+        "The receiving half of change/export. Before anything is written, every object in the patch is \
+        checked against this worker's current refs: an object the patch modifies, renames or deletes must \
+        still have the content the change was made against, and an object the patch adds must not already \
+        exist with different content. Any mismatch is reported as a conflict and nothing is imported. A \
+        clean patch is replayed through the regular object operations into a new local change with the \
+        original name, author, description and rename hints, ready to review and submit like any other \
+        change. There must be no local change in progress."
+    }
+
+    fn parameters(&self) -> Vec<OperationParameter> {
+        vec![OperationParameter {
+            name: "patch".to_string(),
+            description: "The JSON patch returned by change/export".to_string(),
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> Vec<OperationExample> {
+        vec![OperationExample {
+            description: "Import a patch exported from another worker".to_string(),
+            moocode: r#"result = worker_request("vcs", {"change/import", patch});
+player:tell("Imported as change ", result["change_id"]);"#
+                .to_string(),
+            http_curl: Some(
+                r#"curl -X POST http://localhost:8081/api/change/import \
+  -H "Content-Type: application/json" \
+  -d "$(jq -n --rawfile patch fix.patch.json '{args: [$patch]}')""#
+                    .to_string(),
+            ),
+        }]
+    }
+
+    fn routes(&self) -> Vec<OperationRoute> {
+        vec![OperationRoute {
+            path: "/api/change/import".to_string(),
+            method: Method::POST,
+            is_json: true,
+        }]
+    }
+
+    fn responses(&self) -> Vec<crate::operations::OperationResponse> {
+        use crate::operations::OperationResponse;
+        vec![
+            OperationResponse::success(
+                "Patch imported as a new local change",
+                r#"["change_id" -> "def456...", "name" -> "fix-look", "source_change_id" -> "abc123...", "objects" -> {"$room"}]"#,
+            ),
+            OperationResponse::bad_request(
+                "Bad Request - Missing or malformed patch",
+                r#"E_INVARG("Invalid change patch: expected value at line 1 column 1")"#,
+            ),
+            OperationResponse::forbidden(
+                "Forbidden - User lacks permission to submit changes",
+                r#"E_PERM("User 'player' does not have permission to import changes")"#,
+            ),
+            OperationResponse::conflict(
+                "Conflict - Objects diverged on this worker, or a local change is already in progress",
+                r#"E_NACC("Cannot import change 'fix-look' - conflicts with this worker's objects: $room (modified locally)")"#,
+            ),
+            OperationResponse::internal_error(
+                "Internal Server Error - Database error",
+                r#"E_INVARG("Serialization error: failed to store object")"#,
+            ),
+        ]
+    }
+
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<Var, OperationError> {
+        let Some(patch) = args.first().filter(|s| !s.is_empty()) else {
+            error!("Change import operation requires a patch");
+            return Err(OperationError::InvalidArgs("Patch is required".to_string()));
+        };
+
+        let request = ChangeImportRequest {
+            patch: patch.clone(),
+        };
+
+        match self.process_change_import(request, user) {
+            Ok(result) => {
+                info!("Change import operation completed successfully");
+                Ok(result)
+            }
+            Err(e) => {
+                error!("Change import operation failed: {}", e);
+                Err(e)
+            }
+        }
+    }
+}
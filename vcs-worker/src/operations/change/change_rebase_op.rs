@@ -0,0 +1,231 @@
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
+use axum::http::Method;
+use moor_var::{Var, v_list, v_map, v_str};
+use tracing::{error, info};
+
+use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::object_merge::{ObjectConflict, rebase_change};
+use crate::providers::workspace::WorkspaceProvider;
+use crate::types::{ChangeRebaseRequest, ChangeStatus, Permission, User};
+
+/// Outcome of a change rebase
+#[derive(Debug)]
+struct RebaseResult {
+    change_id: String,
+    status: &'static str,
+    index_change_id: Option<String>,
+    merged_objects: Vec<String>,
+    conflicts: Vec<ObjectConflict>,
+}
+
+impl RebaseResult {
+    /// Convert the result to a MOO map
+    fn to_moo_var(&self) -> Var {
+        let merged: Vec<Var> = self.merged_objects.iter().map(|n| v_str(n)).collect();
+        let conflicts: Vec<Var> = self.conflicts.iter().map(|c| c.to_moo_var()).collect();
+        v_map(&[
+            (v_str("change_id"), v_str(&self.change_id)),
+            (v_str("status"), v_str(self.status)),
+            (
+                v_str("index_change_id"),
+                v_str(self.index_change_id.as_deref().unwrap_or("")),
+            ),
+            (v_str("merged_objects"), v_list(&merged)),
+            (v_str("conflicts"), v_list(&conflicts)),
+        ])
+    }
+}
+
+/// Change rebase operation that replays an idle change on top of the current index
+#[derive(Clone)]
+pub struct ChangeRebaseOperation {
+    database: DatabaseRef,
+}
+
+impl ChangeRebaseOperation {
+    /// Create a new change rebase operation
+    pub fn new(database: DatabaseRef) -> Self {
+        Self { database }
+    }
+
+    /// Process the change rebase request
+    fn process_change_rebase(
+        &self,
+        request: ChangeRebaseRequest,
+        user: &User,
+    ) -> Result<RebaseResult, OperationError> {
+        // Check if user has permission to rebase changes
+        require_permission(user, Permission::SubmitChanges, "rebase changes")?;
+
+        // Resolve short or full hash to full hash
+        let change_id = self.database.resolve_change_id(&request.change_id)?;
+
+        let mut change = self
+            .database
+            .workspace()
+            .get_workspace_change(&change_id)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+            .ok_or_else(|| {
+                OperationError::NotFound(format!("Change '{change_id}' not found in workspace"))
+            })?;
+
+        if change.status != ChangeStatus::Idle {
+            error!(
+                "Cannot rebase change '{}' ({}) - it is not idle (status: {:?})",
+                change.name, change.id, change.status
+            );
+            return Err(OperationError::Conflict(format!(
+                "Cannot rebase change '{}' - it is not idle (status: {:?})",
+                change.name, change.status
+            )));
+        }
+
+        info!(
+            "User '{}' rebasing change '{}' ({}) based on {:?}",
+            user.id, change.name, change.id, change.index_change_id
+        );
+
+        let outcome = rebase_change(&self.database, &mut change)?;
+        if outcome.status == "rebased" {
+            self.database
+                .workspace()
+                .update_workspace_change(&change)
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+
+            info!(
+                "Successfully rebased change '{}' ({}) onto {:?}",
+                change.name, change.id, outcome.index_change_id
+            );
+        }
+
+        Ok(RebaseResult {
+            change_id: change.id,
+            status: outcome.status,
+            index_change_id: outcome.index_change_id,
+            merged_objects: outcome.merged_objects,
+            conflicts: outcome.conflicts,
+        })
+    }
+}
+
+impl Operation for ChangeRebaseOperation {
+    fn name(&self) -> &'static str {
+        "change/rebase"
+    }
+
+    fn description(&self) -> &'static str {
+        "Rebases an idle workspace change onto the current index, three-way merging each affected object at the verb and property level. Returns the merged objects, or a conflict report leaving the change untouched."
+    }
+
+    fn response_content_type(&self) -> &'static str {
+        "text/x-moo"
+    }
+
+    fn philosophy(&self) -> &'static str {
+        "While a change sits idle in the workspace, other changes can be merged into the index underneath \
+        it. Switching back to it with change/switch would silently overwrite those upstream edits. Rebase \
+        uses the index change the idle change was based on as the merge base, and merges each object the \
+        change touched that was also modified upstream. Verbs, property definitions and property overrides \
+        are merged individually: an edit made on only one side is kept, while the same verb or property \
+        edited differently on both sides is a conflict. If everything merges cleanly the change is updated \
+        in place and re-based on the current index tip. Otherwise nothing is modified and a structured \
+        report lists the conflicting verbs and properties per object, so they can be resolved by hand."
+    }
+
+    fn parameters(&self) -> Vec<OperationParameter> {
+        vec![OperationParameter {
+            name: "change_id".to_string(),
+            description: "The ID of the idle workspace change to rebase".to_string(),
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> Vec<OperationExample> {
+        vec![OperationExample {
+            description: "Rebase a stashed change before resuming it".to_string(),
+            moocode: r#"result = worker_request("vcs", {"change/rebase", "abc12345"});
+if (result["status"] == "conflict")
+  for conflict in (result["conflicts"])
+    player:tell(conflict["obj_id"], ": verbs ", toliteral(conflict["verbs"]), " props ", toliteral(conflict["props"]));
+  endfor
+else
+  worker_request("vcs", {"change/switch", "abc12345"});
+endif"#
+                .to_string(),
+            http_curl: Some(
+                r#"curl -X POST http://localhost:8081/api/change/rebase \
+  -H "Content-Type: application/json" \
+  -d '{"operation": "change/rebase", "args": ["abc12345"]}'"#
+                    .to_string(),
+            ),
+        }]
+    }
+
+    fn routes(&self) -> Vec<OperationRoute> {
+        vec![OperationRoute {
+            path: "/api/change/rebase".to_string(),
+            method: Method::POST,
+            is_json: true,
+        }]
+    }
+
+    fn responses(&self) -> Vec<crate::operations::OperationResponse> {
+        use crate::operations::OperationResponse;
+        vec![
+            OperationResponse::success(
+                "Rebase completed (status is \"rebased\", \"up_to_date\" or \"conflict\")",
+                r#"["change_id" -> "abc12345", "status" -> "conflict", "index_change_id" -> "def67890", "merged_objects" -> {}, "conflicts" -> {["obj_id" -> #4, "reason" -> "", "attributes" -> {}, "verbs" -> {"look"}, "props" -> {"description"}]}]"#,
+            ),
+            OperationResponse::bad_request(
+                "Bad Request - Missing change ID",
+                r#"E_INVARG("Change ID is required")"#,
+            ),
+            OperationResponse::forbidden(
+                "Forbidden - User lacks permission to rebase changes",
+                r#"E_PERM("User 'player123' does not have permission to rebase changes")"#,
+            ),
+            OperationResponse::not_found(
+                "Not Found - Change not in workspace",
+                r#"E_INVIND("Change 'abc12345' not found in workspace")"#,
+            ),
+            OperationResponse::conflict(
+                "Conflict - Change is not idle",
+                r#"E_NACC("Cannot rebase change 'my-change' - it is not idle (status: Review)")"#,
+            ),
+            OperationResponse::internal_error(
+                "Internal Server Error - Database or system error",
+                r#"E_INVARG("Serialization error: failed to store merged object")"#,
+            ),
+        ]
+    }
+
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
+        if args.is_empty() {
+            error!("Change rebase operation requires a change ID");
+            return Err(OperationError::InvalidArgs(
+                "Change ID is required".to_string(),
+            ));
+        }
+
+        let request = ChangeRebaseRequest {
+            change_id: args[0].clone(),
+        };
+
+        match self.process_change_rebase(request, user) {
+            Ok(result) => {
+                info!(
+                    "Change rebase operation completed with status '{}'",
+                    result.status
+                );
+                Ok(result.to_moo_var())
+            }
+            Err(e) => {
+                error!("Change rebase operation failed: {}", e);
+                Err(e)
+            }
+        }
+    }
+}
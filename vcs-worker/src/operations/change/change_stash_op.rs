@@ -1,4 +1,7 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use axum::http::Method;
 use tracing::{error, info};
 
@@ -7,7 +10,6 @@ use crate::object_diff::{ObjectDiffModel, build_abandon_diff_from_change};
 use crate::providers::index::IndexProvider;
 use crate::providers::workspace::WorkspaceProvider;
 use crate::types::{ChangeStashRequest, ChangeStatus, Permission, User};
-use moor_var::{E_INVARG, v_error};
 
 /// Change stash operation that stashes a local change to workspace for later resumption
 #[derive(Clone)]
@@ -26,18 +28,9 @@ impl ChangeStashOperation {
         &self,
         _request: ChangeStashRequest,
         user: &User,
-    ) -> Result<ObjectDiffModel, ObjectsTreeError> {
+    ) -> Result<ObjectDiffModel, OperationError> {
         // Check if user has permission to stash changes
-        if !user.has_permission(&Permission::SubmitChanges) {
-            error!(
-                "User '{}' does not have permission to stash changes",
-                user.id
-            );
-            return Err(ObjectsTreeError::SerializationError(format!(
-                "User '{}' does not have permission to stash changes",
-                user.id
-            )));
-        }
+        require_permission(user, Permission::SubmitChanges, "stash changes")?;
 
         // Get the top change from the index
         let top_change_id = self
@@ -45,9 +38,7 @@ impl ChangeStashOperation {
             .index()
             .get_top_change()
             .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
-            .ok_or_else(|| {
-                ObjectsTreeError::SerializationError("No change to stash".to_string())
-            })?;
+            .ok_or_else(|| OperationError::NotFound("No change to stash".to_string()))?;
 
         // Get the change
         let mut change = self
@@ -56,7 +47,7 @@ impl ChangeStashOperation {
             .get_change(&top_change_id)
             .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
             .ok_or_else(|| {
-                ObjectsTreeError::SerializationError(format!("Change '{top_change_id}' not found"))
+                OperationError::NotFound(format!("Change '{top_change_id}' not found"))
             })?;
 
         info!(
@@ -70,7 +61,7 @@ impl ChangeStashOperation {
                 "Cannot stash change '{}' ({}) - it is not local (status: {:?})",
                 change.name, change.id, change.status
             );
-            return Err(ObjectsTreeError::SerializationError(format!(
+            return Err(OperationError::Conflict(format!(
                 "Cannot stash change '{}' - it is not local (status: {:?})",
                 change.name, change.status
             )));
@@ -170,30 +161,26 @@ worker_request("vcs", {"change/switch", stashed_id});"#
                 "Operation executed successfully",
                 r#"["objects_renamed" -> [], "objects_deleted" -> {}, "objects_added" -> {}, "objects_modified" -> {"obj1"}, "changes" -> {["obj_id" -> "obj1", "verbs_modified" -> {}, "verbs_added" -> {}, "verbs_renamed" -> [], "verbs_deleted" -> {}, "props_modified" -> {}, "props_added" -> {}, "props_renamed" -> [], "props_deleted" -> {}]}]"#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - Cannot stash non-local change",
-                r#"E_INVARG("Error: Cannot stash change 'my-change' - it is not local (status: Review)")"#,
-            ),
-            OperationResponse::new(
-                403,
+            OperationResponse::forbidden(
                 "Forbidden - User lacks permission to stash changes",
-                r#"E_INVARG("Error: User 'player123' does not have permission to submit changes)"#,
+                r#"E_PERM("User 'player123' does not have permission to stash changes")"#,
             ),
-            OperationResponse::new(
-                404,
+            OperationResponse::not_found(
                 "Not Found - No change to stash",
-                r#"E_INVARG("Error: No change to stash")"#,
+                r#"E_INVIND("No change to stash")"#,
+            ),
+            OperationResponse::conflict(
+                "Conflict - Cannot stash non-local change",
+                r#"E_NACC("Cannot stash change 'my-change' - it is not local (status: Review)")"#,
             ),
-            OperationResponse::new(
-                500,
+            OperationResponse::internal_error(
                 "Internal Server Error - Database or system error",
-                r#"E_INVARG("Error: Database error: failed to stash change")"#,
+                r#"E_INVARG("Serialization error: failed to stash change")"#,
             ),
         ]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!(
             "Change stash operation received {} arguments for user: {}",
             args.len(),
@@ -206,11 +193,11 @@ worker_request("vcs", {"change/switch", stashed_id});"#
             Ok(undo_diff) => {
                 info!("Change stash operation completed successfully, returning undo diff");
                 // Return the ObjectDiffModel as a MOO variable showing what needs to be undone
-                undo_diff.to_moo_var()
+                Ok(undo_diff.to_moo_var())
             }
             Err(e) => {
                 error!("Change stash operation failed: {}", e);
-                v_error(E_INVARG.msg(format!("Error: {e}")))
+                Err(e)
             }
         }
     }
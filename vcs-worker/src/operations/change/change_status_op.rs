@@ -1,15 +1,16 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+};
 use axum::http::Method;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
 
 use crate::database::{DatabaseRef, ObjectsTreeError};
-use crate::object_diff::build_object_diff_from_change;
+use crate::object_diff::{add_line_diffs_for_change, build_object_diff_from_change};
 use crate::providers::index::IndexProvider;
 use crate::providers::workspace::WorkspaceProvider;
 use crate::types::ChangeStatus;
 use crate::types::User;
-use moor_var::{E_INVARG, v_error};
 
 /// Request structure for change status operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +18,9 @@ pub struct ChangeStatusRequest {
     /// Optional change ID to query. If not provided, queries the current top local change.
     #[serde(default)]
     pub change_id: Option<String>,
+    /// Include unified verb diffs and property before/after values
+    #[serde(default)]
+    pub details: bool,
 }
 
 /// Response structure for change status
@@ -43,7 +47,7 @@ impl ChangeStatusOperation {
     fn process_change_status(
         &self,
         request: ChangeStatusRequest,
-    ) -> Result<moor_var::Var, ObjectsTreeError> {
+    ) -> Result<moor_var::Var, OperationError> {
         // If a specific change_id was provided, fetch that change
         let current_change = if let Some(ref change_id) = request.change_id {
             info!("Fetching status for specific change: {}", change_id);
@@ -70,7 +74,7 @@ impl ChangeStatusOperation {
                     .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
                 
                 change_from_workspace.ok_or_else(|| {
-                    ObjectsTreeError::SerializationError(format!(
+                    OperationError::NotFound(format!(
                         "Change '{}' not found in index or workspace",
                         resolved_change_id
                     ))
@@ -92,7 +96,7 @@ impl ChangeStatusOperation {
                     .get_change(&change_id)
                     .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
                     .ok_or_else(|| {
-                        ObjectsTreeError::SerializationError(format!("Change '{change_id}' not found"))
+                        OperationError::NotFound(format!("Change '{change_id}' not found"))
                     })?;
 
                 // Check if the top change is local status
@@ -101,16 +105,16 @@ impl ChangeStatusOperation {
                         "Top change '{}' is not local status (status: {:?}), returning error",
                         change.name, change.status
                     );
-                    return Ok(v_error(
-                        E_INVARG.msg("No local change on top of index - nothing to do"),
+                    return Err(OperationError::NotFound(
+                        "No local change on top of index - nothing to do".to_string(),
                     ));
                 }
                 
                 change
             } else {
                 info!("No top change found, returning error");
-                return Ok(v_error(
-                    E_INVARG.msg("No change on top of index - nothing to do"),
+                return Err(OperationError::NotFound(
+                    "No change on top of index - nothing to do".to_string(),
                 ));
             }
         };
@@ -118,7 +122,10 @@ impl ChangeStatusOperation {
         info!("Getting status for change: {} ({})", current_change.name, current_change.id);
 
         // Build the ObjectDiffModel by comparing change against the compiled state
-        let diff_model = build_object_diff_from_change(&self.database, &current_change)?;
+        let mut diff_model = build_object_diff_from_change(&self.database, &current_change)?;
+        if request.details {
+            add_line_diffs_for_change(&self.database, &mut diff_model, &current_change)?;
+        }
 
         // Convert to MOO Var and return
         let status_map = diff_model.to_moo_var();
@@ -136,6 +143,10 @@ impl Operation for ChangeStatusOperation {
         "change/status"
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &'static str {
         "Lists all objects that have been modified in a change (added, modified, deleted, renamed). Can query current change or a specific change by ID."
     }
@@ -160,6 +171,11 @@ impl Operation for ChangeStatusOperation {
                 description: "Optional change ID (full 64-char hash or short 12-char prefix) to query. If not provided, queries the current top local change.".to_string(),
                 required: false,
             },
+            OperationParameter {
+                name: "details".to_string(),
+                description: "Optional. Set to 'true' to include unified verb code diffs (verb_diffs) and property before/after literal values (prop_diffs) for each object (default: false)".to_string(),
+                required: false,
+            },
         ]
     }
 
@@ -192,6 +208,20 @@ player:tell("Modified: ", length(diff["modified_objects"]), " objects");"#
                     .to_string(),
                 http_curl: Some(r#"curl -X GET 'http://localhost:8081/api/change/status?change_id=abcdef123456'"#.to_string()),
             },
+            OperationExample {
+                description: "Review the code changes of the current change".to_string(),
+                moocode: r#"diff = worker_request("vcs", {"change/status", "", "true"});
+for change in (diff["changes"])
+  for verb in (mapkeys(change["verb_diffs"]))
+    player:tell("Verb ", verb, ":");
+    for line in (change["verb_diffs"][verb])
+      player:tell("  ", line);
+    endfor
+  endfor
+endfor"#
+                    .to_string(),
+                http_curl: Some(r#"curl -X GET 'http://localhost:8081/api/change/status?details=true'"#.to_string()),
+            },
         ]
     }
 
@@ -210,29 +240,27 @@ player:tell("Modified: ", length(diff["modified_objects"]), " objects");"#
                 "Operation executed successfully",
                 r#"["objects_renamed" -> ["old_obj" -> "new_obj"], "objects_deleted" -> {"obj1"}, "objects_added" -> {"obj2"}, "objects_modified" -> {"obj3"}, "changes" -> {["obj_id" -> "obj3", "verbs_modified" -> {"verb1"}, "verbs_added" -> {}, "verbs_renamed" -> [], "verbs_deleted" -> {}, "props_modified" -> {"prop1"}, "props_added" -> {}, "props_renamed" -> [], "props_deleted" -> {}]}]"#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - No local change available",
-                r#"E_INVARG("No local change on top of index - nothing to do)"#,
-            ),
-            OperationResponse::new(
-                404,
-                "Not Found - No change to query",
-                r#"E_INVARG("Error: No change on top of index - nothing to do)"#,
+            OperationResponse::not_found(
+                "Not Found - No local change on top of index, or the requested change does not exist",
+                r#"E_INVIND("No local change on top of index - nothing to do")"#,
             ),
-            OperationResponse::new(
-                500,
+            OperationResponse::internal_error(
                 "Internal Server Error - Database or system error",
-                r#"E_INVARG("Error: Database error: failed to build diff model")"#,
+                r#"E_INVARG("Serialization error: failed to build diff model")"#,
             ),
         ]
     }
 
-    fn execute(&self, args: Vec<String>, _user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, _user: &User) -> Result<moor_var::Var, OperationError> {
         info!("Change status operation executed with {} args", args.len());
 
+        // An empty change ID (e.g. when only `details` is given) means the current change
         let request = ChangeStatusRequest {
-            change_id: args.get(0).map(|s| s.to_string()),
+            change_id: args.first().filter(|s| !s.is_empty()).cloned(),
+            details: args
+                .get(1)
+                .and_then(|s| s.parse::<bool>().ok())
+                .unwrap_or(false),
         };
 
         if let Some(ref cid) = request.change_id {
@@ -244,11 +272,11 @@ player:tell("Modified: ", length(diff["modified_objects"]), " objects");"#
         match self.process_change_status(request) {
             Ok(result_var) => {
                 info!("Change status operation completed successfully");
-                result_var
+                Ok(result_var)
             }
             Err(e) => {
                 error!("Change status operation failed: {}", e);
-                moor_var::v_error(moor_var::E_INVARG.msg(format!("Error: {e}")))
+                Err(e)
             }
         }
     }
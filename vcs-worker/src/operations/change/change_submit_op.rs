@@ -1,15 +1,18 @@
 use crate::config::Config;
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use axum::http::Method;
 use tracing::{error, info, warn};
 
 use crate::database::{DatabaseRef, ObjectsTreeError};
 use crate::git_backup;
 use crate::object_diff::{ObjectDiffModel, build_abandon_diff_from_change};
+use crate::operations::remote::resolve_remote;
 use crate::providers::index::IndexProvider;
 use crate::providers::workspace::WorkspaceProvider;
-use crate::types::{ChangeStatus, ChangeSubmitRequest, Permission, User};
-use moor_var::{E_INVARG, v_error};
+use crate::types::{ChangeStatus, ChangeSubmitRequest, DEFAULT_REMOTE, Permission, Remote, User};
 
 /// Change submit operation that submits a local change for review
 #[derive(Clone)]
@@ -29,18 +32,9 @@ impl ChangeSubmitOperation {
         &self,
         request: ChangeSubmitRequest,
         user: &User,
-    ) -> Result<ObjectDiffModel, ObjectsTreeError> {
+    ) -> Result<ObjectDiffModel, OperationError> {
         // Check if user has permission to submit changes
-        if !user.has_permission(&Permission::SubmitChanges) {
-            error!(
-                "User '{}' does not have permission to submit changes",
-                user.id
-            );
-            return Err(ObjectsTreeError::SerializationError(format!(
-                "User '{}' does not have permission to submit changes",
-                user.id
-            )));
-        }
+        require_permission(user, Permission::SubmitChanges, "submit changes")?;
 
         // Get the top change from the index
         let top_change_id = self
@@ -48,9 +42,7 @@ impl ChangeSubmitOperation {
             .index()
             .get_top_change()
             .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
-            .ok_or_else(|| {
-                ObjectsTreeError::SerializationError("No change to submit".to_string())
-            })?;
+            .ok_or_else(|| OperationError::NotFound("No change to submit".to_string()))?;
 
         // Get the change
         let mut change = self
@@ -59,7 +51,7 @@ impl ChangeSubmitOperation {
             .get_change(&top_change_id)
             .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
             .ok_or_else(|| {
-                ObjectsTreeError::SerializationError(format!("Change '{top_change_id}' not found"))
+                OperationError::NotFound(format!("Change '{top_change_id}' not found"))
             })?;
 
         info!(
@@ -70,7 +62,7 @@ impl ChangeSubmitOperation {
         // Validate that author is set
         if change.author.is_empty() {
             error!("Cannot submit change '{}' - author is not set", change.name);
-            return Err(ObjectsTreeError::SerializationError(
+            return Err(OperationError::Conflict(
                 "Cannot submit change - author is not set".to_string(),
             ));
         }
@@ -103,22 +95,29 @@ impl ChangeSubmitOperation {
                 "Cannot submit change '{}' ({}) - it is not local (status: {:?})",
                 change.name, change.id, change.status
             );
-            return Err(ObjectsTreeError::SerializationError(format!(
+            return Err(OperationError::Conflict(format!(
                 "Cannot submit change '{}' - it is not local (status: {:?})",
                 change.name, change.status
             )));
         }
 
-        // Check if there's a source URL to determine the workflow
-        let source_url = self
-            .database
-            .index()
-            .get_source()
-            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        // Find the remote to determine the workflow: a named remote must exist, and
+        // without one the default remote decides whether this is a remote index
+        let remote = match request.remote.as_deref() {
+            Some(name) => Some(resolve_remote(&self.database, Some(name))?),
+            None => self
+                .database
+                .index()
+                .get_remote(DEFAULT_REMOTE)
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?,
+        };
 
-        if let Some(url) = source_url {
+        if let Some(remote) = remote {
             // REMOTE INDEX: Submit for review (existing behavior)
-            info!("Source URL found: {}, submitting change for review", url);
+            info!(
+                "Remote '{}' found at {}, submitting change for review",
+                remote.name, remote.url
+            );
 
             // Build the undo diff (like abandon does)
             let undo_diff = build_abandon_diff_from_change(&self.database, &change)?;
@@ -145,23 +144,28 @@ impl ChangeSubmitOperation {
 
             info!("Removed change '{}' from top of index", change.name);
 
-            // Make a REST call to submit the change remotely
-            match self.submit_to_remote(&url, &change, user) {
-                Ok(_) => {
-                    info!(
-                        "Successfully submitted change '{}' to remote: {}",
-                        change.name, url
-                    );
+            // Make a REST call to submit the change remotely, once the local submission
+            // has committed, so a request retried after a conflict can't send it twice
+            let operation = self.clone();
+            let submitted = change.clone();
+            self.database.after_commit(move || {
+                match operation.submit_to_remote(&remote, &submitted) {
+                    Ok(_) => {
+                        info!(
+                            "Successfully submitted change '{}' to remote '{}': {}",
+                            submitted.name, remote.name, remote.url
+                        );
+                    }
+                    Err(e) => {
+                        warn!(
+                            "Failed to submit change '{}' to remote '{}' at {}: {}. Change still submitted locally.",
+                            submitted.name, remote.name, remote.url, e
+                        );
+                        // Don't fail the whole operation if remote submission fails
+                        // The local submission succeeded, remote is best-effort
+                    }
                 }
-                Err(e) => {
-                    warn!(
-                        "Failed to submit change '{}' to remote {}: {}. Change still submitted locally.",
-                        change.name, url, e
-                    );
-                    // Don't fail the whole operation if remote submission fails
-                    // The local submission succeeded, remote is best-effort
-                }
-            }
+            });
 
             info!(
                 "Successfully submitted change '{}' ({}), moved to workspace for review",
@@ -171,7 +175,7 @@ impl ChangeSubmitOperation {
             Ok(undo_diff)
         } else {
             // NON-REMOTE INDEX: Instantly approve the change
-            info!("No source URL configured, instantly approving change");
+            info!("No remote configured, instantly approving change");
 
             // When submitting the top change (current working change), return an empty diff
             // because there are no NEW changes relative to the current state - the change
@@ -217,14 +221,12 @@ impl ChangeSubmitOperation {
                 change.name, change.id
             );
 
-            // Flush database to ensure all writes are persisted before git backup
-            // This prevents race conditions where git backup reads before writes are committed
-            self.database
-                .flush()
-                .map_err(|e| ObjectsTreeError::SerializationError(format!("Failed to flush database: {}", e)))?;
-
-            // Trigger git backup in background (non-blocking) for instant approval
-            git_backup::trigger_git_backup(self.database.clone(), self.config.clone());
+            // Trigger git backup in background once the approval commits
+            git_backup::trigger_git_backup(
+                self.database.clone(),
+                self.config.clone(),
+                "change/submit",
+            );
 
             Ok(diff_model)
         }
@@ -233,17 +235,20 @@ impl ChangeSubmitOperation {
     /// Submit the change to a remote server via REST API
     fn submit_to_remote(
         &self,
-        source_url: &str,
+        remote: &Remote,
         change: &crate::types::Change,
-        _user: &User,
     ) -> Result<(), ObjectsTreeError> {
         // Build the URL for the remote workspace/submit endpoint
+        let source_url = &remote.url;
         let submit_url = if source_url.ends_with('/') {
-            format!("{source_url}workspace/submit")
+            format!("{source_url}api/workspace/submit")
         } else {
-            format!("{source_url}/workspace/submit")
+            format!("{source_url}/api/workspace/submit")
         };
 
+        // Authenticate with the remote using its stored credentials
+        let api_key = remote.api_key.clone();
+
         info!(
             "Submitting change '{}' to remote URL: {}",
             change.id, submit_url
@@ -274,23 +279,27 @@ impl ChangeSubmitOperation {
                 .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
 
             // Make the PUT request to workspace/submit
-            let response = client
-                .put(&url_clone)
-                .json(&payload_clone)
+            let mut request = client.put(&url_clone).json(&payload_clone);
+            if let Some(api_key) = api_key {
+                request = request.header("X-API-Key", api_key);
+            }
+            let response = request
                 .send()
                 .map_err(|e| format!("HTTP request failed: {e}"))?;
 
-            if response.status().is_success() {
-                Ok(())
-            } else {
-                let status = response.status();
-                let error_text = response
-                    .text()
-                    .unwrap_or_else(|_| "Unknown error".to_string());
-                Err(format!(
-                    "Remote submission failed with status {status}: {error_text}"
-                ))
+            let status = response.status();
+            if status.is_success() {
+                return Ok(());
             }
+
+            // Failures carry a machine-readable `error` object alongside the status
+            let body: serde_json::Value = response.json().unwrap_or_default();
+            let code = body["error"]["code"].as_str().unwrap_or("unknown");
+            let message = body["error"]["message"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("submission failed with status {status}"));
+            Err(format!("Remote rejected submission ({code}): {message}"))
         })
         .join()
         .map_err(|_| {
@@ -318,7 +327,7 @@ impl Operation for ChangeSubmitOperation {
     }
 
     fn description(&self) -> &'static str {
-        "Submits the top local change. Requires author to be set. If a source URL is configured (remote index), moves it to workspace with Review status for remote approval. If no source URL is configured (non-remote index), instantly approves and merges the change. Returns an ObjectDiffModel. Optional message argument can be provided to set/override the commit message."
+        "Submits the top local change. Requires author to be set. If a remote is named or origin is configured (remote index), moves it to workspace with Review status for approval on that remote. If no remote is configured (non-remote index), instantly approves and merges the change. Returns an ObjectDiffModel. Optional message argument can be provided to set/override the commit message."
     }
 
     fn response_content_type(&self) -> &'static str {
@@ -327,22 +336,31 @@ impl Operation for ChangeSubmitOperation {
 
     fn philosophy(&self) -> &'static str {
         "Completes the change workflow by submitting your local changelist for permanent inclusion in the \
-        repository. The behavior depends on your repository type: For local repositories (no source URL), \
-        the change is instantly approved and merged into history. For remote repositories (with source URL), \
-        the change is submitted for review and must be approved before merging. In either case, this finalizes \
+        repository. The behavior depends on your repository type: For local repositories (no origin remote), \
+        the change is instantly approved and merged into history. For remote repositories (with origin remote), \
+        the change is submitted for review and must be approved before merging. Naming another remote submits \
+        the change for review there instead, using that remote's credentials. In either case, this finalizes \
         your work and makes it part of the permanent record. After submission, the change is removed from your \
         local working state - use change/switch if you want to continue working on other changes. Always verify \
         your changes with change/status before submitting."
     }
 
     fn parameters(&self) -> Vec<OperationParameter> {
-        vec![OperationParameter {
-            name: "message".to_string(),
-            description:
-                "Optional commit message describing the change (overrides the change description)"
+        vec![
+            OperationParameter {
+                name: "message".to_string(),
+                description:
+                    "Optional commit message describing the change (overrides the change description)"
+                        .to_string(),
+                required: false,
+            },
+            OperationParameter {
+                name: "remote".to_string(),
+                description: "Name of the remote to submit the change to (defaults to origin)"
                     .to_string(),
-            required: false,
-        }]
+                required: false,
+            },
+        ]
     }
 
     fn examples(&self) -> Vec<OperationExample> {
@@ -363,6 +381,12 @@ diff = worker_request("vcs", {"change/submit"});
                 moocode: r#"diff = worker_request("vcs", {"change/submit", "Fixed critical bug in login system"});
 // The message becomes part of the permanent change record"#.to_string(),
                 http_curl: None,
+            },
+            OperationExample {
+                description: "Submit for review on a named remote".to_string(),
+                moocode: r#"// An empty message keeps the change description
+diff = worker_request("vcs", {"change/submit", "", "production"});"#.to_string(),
+                http_curl: Some(r#"curl -X POST "http://localhost:8081/api/change/submit?message=Fixed%20login&remote=production""#.to_string()),
             }
         ]
     }
@@ -382,60 +406,54 @@ diff = worker_request("vcs", {"change/submit"});
                 "Operation executed successfully",
                 r#"["objects_renamed" -> [], "objects_deleted" -> {}, "objects_added" -> {}, "objects_modified" -> {"obj1"}, "changes" -> {["obj_id" -> "obj1", "verbs_modified" -> {}, "verbs_added" -> {}, "verbs_renamed" -> [], "verbs_deleted" -> {}, "props_modified" -> {}, "props_added" -> {}, "props_renamed" -> [], "props_deleted" -> {}]}]"#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - Cannot submit change in current state",
-                r#"E_INVARG("Error: Cannot submit change 'my-change' - it is not local (status: Merged)")"#,
-            ),
-            OperationResponse::new(
-                403,
+            OperationResponse::forbidden(
                 "Forbidden - User lacks permission to submit changes",
-                r#"E_INVARG("Error: User 'player123' does not have permission to submit changes)"#,
+                r#"E_PERM("User 'player123' does not have permission to submit changes")"#,
             ),
-            OperationResponse::new(
-                404,
+            OperationResponse::not_found(
                 "Not Found - No change to submit",
-                r#"E_INVARG("Error: No change to submit)"#,
+                r#"E_INVIND("No change to submit")"#,
+            ),
+            OperationResponse::not_found(
+                "Not Found - The named remote does not exist",
+                r#"E_INVIND("Remote 'production' not found")"#,
+            ),
+            OperationResponse::conflict(
+                "Conflict - Cannot submit change in current state",
+                r#"E_NACC("Cannot submit change 'my-change' - it is not local (status: Merged)")"#,
             ),
-            OperationResponse::new(
-                500,
+            OperationResponse::internal_error(
                 "Internal Server Error - Database or system error",
-                r#"E_INVARG("Error: Database error: failed to submit change")"#,
+                r#"E_INVARG("Serialization error: failed to submit change")"#,
             ),
         ]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!(
             "Change submit operation received {} arguments for user: {}",
             args.len(),
             user.id
         );
 
-        // Parse optional message argument
-        let message = if args.is_empty() {
-            None
-        } else {
-            // Join all arguments as the message
-            let message_text = args.join(" ").trim().to_string();
-            if message_text.is_empty() {
-                None
-            } else {
-                Some(message_text)
-            }
-        };
+        // Parse optional message and remote arguments
+        let message = args
+            .first()
+            .map(|message| message.trim().to_string())
+            .filter(|message| !message.is_empty());
+        let remote = args.get(1).filter(|name| !name.is_empty()).cloned();
 
-        let request = ChangeSubmitRequest { message };
+        let request = ChangeSubmitRequest { message, remote };
 
         match self.process_change_submit(request, user) {
             Ok(undo_diff) => {
                 info!("Change submit operation completed successfully, returning undo diff");
                 // Return the ObjectDiffModel as a MOO variable showing what needs to be undone
-                undo_diff.to_moo_var()
+                Ok(undo_diff.to_moo_var())
             }
             Err(e) => {
                 error!("Change submit operation failed: {}", e);
-                v_error(E_INVARG.msg(format!("Error: {e}")))
+                Err(e)
             }
         }
     }
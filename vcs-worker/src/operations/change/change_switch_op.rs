@@ -1,4 +1,7 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use axum::http::Method;
 use tracing::{error, info};
 
@@ -8,8 +11,7 @@ use crate::object_diff::{
 };
 use crate::providers::index::IndexProvider;
 use crate::providers::workspace::WorkspaceProvider;
-use crate::types::{ChangeStatus, ChangeSwitchRequest, User};
-use moor_var::{E_INVARG, v_error};
+use crate::types::{ChangeStatus, ChangeSwitchRequest, Permission, User};
 
 /// Change switch operation that switches from the current change to a workspace change
 #[derive(Clone)]
@@ -28,7 +30,7 @@ impl ChangeSwitchOperation {
         &self,
         request: ChangeSwitchRequest,
         _user: &User,
-    ) -> Result<ObjectDiffModel, ObjectsTreeError> {
+    ) -> Result<ObjectDiffModel, OperationError> {
         // Resolve short or full hash to full hash
         let target_change_id = self.database.resolve_change_id(&request.change_id)?;
 
@@ -41,7 +43,7 @@ impl ChangeSwitchOperation {
             .get_workspace_change(&target_change_id)
             .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
             .ok_or_else(|| {
-                ObjectsTreeError::SerializationError(format!(
+                OperationError::NotFound(format!(
                     "Change '{target_change_id}' not found in workspace"
                 ))
             })?;
@@ -69,7 +71,7 @@ impl ChangeSwitchOperation {
                 .get_change(&current_change_id)
                 .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
                 .ok_or_else(|| {
-                    ObjectsTreeError::SerializationError(format!(
+                    OperationError::NotFound(format!(
                         "Current change '{current_change_id}' not found"
                     ))
                 })?;
@@ -116,7 +118,7 @@ impl ChangeSwitchOperation {
                     "Cannot switch away from non-local change '{}' (status: {:?})",
                     current_change.name, current_change.status
                 );
-                return Err(ObjectsTreeError::SerializationError(format!(
+                return Err(OperationError::Conflict(format!(
                     "Cannot switch away from non-local change '{}' (status: {:?})",
                     current_change.name, current_change.status
                 )));
@@ -243,36 +245,43 @@ player:tell("Need to update ", length(diff["modified_objects"]), " objects");"#
                 "Operation executed successfully",
                 r#"["objects_renamed" -> [], "objects_deleted" -> {}, "objects_added" -> {}, "objects_modified" -> {"obj1"}, "changes" -> {["obj_id" -> "obj1", "verbs_modified" -> {}, "verbs_added" -> {}, "verbs_renamed" -> [], "verbs_deleted" -> {}, "props_modified" -> {}, "props_added" -> {}, "props_renamed" -> [], "props_deleted" -> {}]}]"#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - Invalid switch operation",
-                r#"E_INVARG("Error: Cannot switch away from non-local change)"#,
+            OperationResponse::bad_request(
+                "Bad Request - Missing change_id argument",
+                r#"E_INVARG("Workspace change switch operation requires a change_id argument")"#,
+            ),
+            OperationResponse::forbidden(
+                "Forbidden - User lacks SubmitChanges permission",
+                r#"E_PERM("User 'player' does not have permission to manage changes")"#,
             ),
-            OperationResponse::new(
-                404,
+            OperationResponse::not_found(
                 "Not Found - Target change not found",
-                r#"E_INVARG("Error: Change 'abc-123-def...' not found in workspace)"#,
+                r#"E_INVIND("Change 'abc-123-def...' not found in workspace")"#,
             ),
-            OperationResponse::new(
-                500,
+            OperationResponse::conflict(
+                "Conflict - Cannot switch away from non-local change",
+                r#"E_NACC("Cannot switch away from non-local change 'my-change' (status: Review)")"#,
+            ),
+            OperationResponse::internal_error(
                 "Internal Server Error - Database or system error",
-                r#"E_INVARG("Error: Database error: failed to switch change")"#,
+                r#"E_INVARG("Serialization error: failed to switch change")"#,
             ),
         ]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!(
             "Workspace change switch operation received {} arguments for user: {}",
             args.len(),
             user.id
         );
 
+        require_permission(user, Permission::SubmitChanges, "manage changes")?;
+
         if args.is_empty() {
             error!("Workspace change switch operation requires a change_id argument");
-            return v_error(
-                E_INVARG.msg("Workspace change switch operation requires a change_id argument"),
-            );
+            return Err(OperationError::InvalidArgs(
+                "Workspace change switch operation requires a change_id argument".to_string(),
+            ));
         }
 
         let change_id = args[0].clone();
@@ -284,11 +293,11 @@ player:tell("Need to update ", length(diff["modified_objects"]), " objects");"#
                     "Workspace change switch operation completed successfully, returning merged diff"
                 );
                 // Return the merged ObjectDiffModel as a MOO variable
-                merged_diff.to_moo_var()
+                Ok(merged_diff.to_moo_var())
             }
             Err(e) => {
                 error!("Workspace change switch operation failed: {}", e);
-                v_error(E_INVARG.msg(format!("Error: {e}")))
+                Err(e)
             }
         }
     }
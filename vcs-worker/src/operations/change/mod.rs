@@ -1,6 +1,10 @@
 mod change_abandon_op;
 mod change_approve_op;
 mod change_create_op;
+mod change_diff_op;
+mod change_export_op;
+mod change_import_op;
+mod change_rebase_op;
 mod change_stash_op;
 mod change_status_op;
 mod change_submit_op;
@@ -9,6 +13,10 @@ mod change_switch_op;
 pub use change_abandon_op::ChangeAbandonOperation;
 pub use change_approve_op::ChangeApproveOperation;
 pub use change_create_op::ChangeCreateOperation;
+pub use change_diff_op::ChangeDiffOperation;
+pub use change_export_op::ChangeExportOperation;
+pub use change_import_op::ChangeImportOperation;
+pub use change_rebase_op::ChangeRebaseOperation;
 pub use change_stash_op::ChangeStashOperation;
 pub use change_status_op::ChangeStatusOperation;
 pub use change_submit_op::ChangeSubmitOperation;
@@ -1,13 +1,30 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use axum::http::Method;
-use tracing::{error, info};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tracing::{error, info, warn};
 
+use crate::clone_pack::{PACK_PATH, PACK_VERSION, PackError, PackFrame, PackRequest, fetch_pack};
 use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::local_work::{LocalWork, PreservedChange};
+use crate::operations::remote::{remote_base_url, validate_remote_name};
 use crate::providers::index::IndexProvider;
 use crate::providers::objects::ObjectsProvider;
 use crate::providers::refs::RefsProvider;
-use crate::types::{CloneData, ObjectInfo, User};
-use moor_var::{E_INVARG, v_error};
+use crate::types::{Change, CloneData, DEFAULT_REMOTE, ObjectInfo, Remote, User};
+use crate::util::ContentHash;
+
+/// Hashes requested per clone pack objects request
+const HASHES_PER_REQUEST: usize = 1024;
+
+/// Attempts at an interrupted clone pack transfer before giving up
+const MAX_PACK_ATTEMPTS: u32 = 5;
+
+/// Delay before resuming an interrupted transfer, multiplied by the attempt number
+const PACK_RETRY_DELAY: Duration = Duration::from_millis(500);
 
 /// Clone operation that exports or imports repository state
 #[derive(Clone)]
@@ -78,8 +95,9 @@ impl CloneOperation {
         let source = self
             .database
             .index()
-            .get_source()
-            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+            .get_remote(DEFAULT_REMOTE)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+            .map(|remote| remote.url);
 
         Ok(CloneData {
             refs,
@@ -91,7 +109,7 @@ impl CloneOperation {
     }
 
     /// Validate external user API key by calling stat on remote server
-    async fn validate_external_user(
+    pub async fn validate_external_user(
         &self,
         base_url: &str,
         api_key: &str,
@@ -160,17 +178,371 @@ impl CloneOperation {
         ))
     }
 
-    /// Import repository state from a URL (async version)
+    /// Import repository state from a URL (async version), recording the URL and
+    /// credentials as the named remote
     pub async fn import_from_url_async(
         &self,
         url: &str,
         external_user_api_key: Option<&str>,
+        remote_name: &str,
     ) -> Result<String, ObjectsTreeError> {
-        info!("Importing repository state from URL: {}", url);
+        info!(
+            "Importing repository state from URL: {} (remote '{}')",
+            url, remote_name
+        );
+
+        // Extract base URL from source_url (remove /api/clone or /clone suffix)
+        let base_url = remote_base_url(url);
+
+        // Stream a clone pack if the source serves them, otherwise fetch the JSON export
+        let preserved = match self
+            .import_from_pack_async(&base_url, external_user_api_key, remote_name)
+            .await?
+        {
+            Some(preserved) => preserved,
+            None => {
+                info!("Source does not serve clone packs - falling back to a JSON clone");
+                self.import_from_json_async(url, &base_url, external_user_api_key, remote_name)
+                    .await?
+            }
+        };
+
+        if preserved.is_empty() {
+            return Ok(format!("Successfully cloned from {url}"));
+        }
+        let summaries: Vec<String> = preserved.iter().map(|p| p.summary()).collect();
+        Ok(format!(
+            "Successfully cloned from {url}, preserving local changes: {}",
+            summaries.join("; ")
+        ))
+    }
+
+    /// Validate the external user API key, if there is one, returning the key and user ID
+    async fn external_user_info(
+        &self,
+        base_url: &str,
+        external_user_api_key: Option<&str>,
+    ) -> Result<Option<(String, String)>, ObjectsTreeError> {
+        let Some(api_key) = external_user_api_key else {
+            return Ok(None);
+        };
+        let (user_id, _email) = self.validate_external_user(base_url, api_key).await?;
+        Ok(Some((api_key.to_string(), user_id)))
+    }
+
+    /// Import repository state as a streamed clone pack, returning None if the source does
+    /// not serve clone packs. Blobs are verified and stored as they arrive, and only the
+    /// ones we don't already have are requested, so an interrupted clone resumes where it
+    /// stopped. The refs and history are then swapped in all-or-nothing.
+    async fn import_from_pack_async(
+        &self,
+        base_url: &str,
+        external_user_api_key: Option<&str>,
+        remote_name: &str,
+    ) -> Result<Option<Vec<PreservedChange>>, ObjectsTreeError> {
+        let client = reqwest::Client::new();
+        let pack_url = format!("{}{PACK_PATH}", base_url.trim_end_matches('/'));
+
+        // Fetch the ref list and the last merged change it is consistent with
+        let Some((tip, refs)) = self
+            .fetch_refs(&client, &pack_url, external_user_api_key)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let external_user_info = self
+            .external_user_info(base_url, external_user_api_key)
+            .await?;
+
+        // Download the blobs we don't already have
+        let objects_provider = self.database.objects();
+        let mut seen = HashSet::new();
+        let mut missing = Vec::new();
+        for (_, hash) in &refs {
+            if seen.insert(hash.clone())
+                && objects_provider
+                    .get_object_size(hash)
+                    .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+                    .is_none()
+            {
+                missing.push(hash.clone());
+            }
+        }
+        info!(
+            "Downloading {} of {} objects in the clone",
+            missing.len(),
+            seen.len()
+        );
+
+        let mut rekeyed = HashMap::new();
+        for batch in missing.chunks(HASHES_PER_REQUEST) {
+            self.fetch_objects(
+                &client,
+                &pack_url,
+                external_user_api_key,
+                batch,
+                &mut rekeyed,
+            )
+            .await?;
+        }
+        if !rekeyed.is_empty() {
+            info!(
+                "Re-keyed {} cloned objects to {} content hash",
+                rekeyed.len(),
+                objects_provider.content_hash().name()
+            );
+        }
+
+        // Fetch the merged history up to the tip
+        let changes = self
+            .fetch_changes(&client, &pack_url, external_user_api_key, tip)
+            .await?;
+
+        let refs: Vec<(ObjectInfo, String)> = refs
+            .into_iter()
+            .map(|(obj_info, hash)| {
+                let hash = rekeyed.get(&hash).cloned().unwrap_or(hash);
+                (obj_info, hash)
+            })
+            .collect();
+        let change_order: Vec<String> = changes.iter().map(|c| c.id.clone()).collect();
+
+        // Replace the refs and history all-or-nothing; the blobs are already in place
+        let preserved = self.database.write_batch(|| {
+            // A gc running alongside the download sweeps blobs nothing referenced yet
+            for (obj_info, hash) in &refs {
+                if objects_provider
+                    .get_object_size(hash)
+                    .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+                    .is_none()
+                {
+                    return Err(ObjectsTreeError::SerializationError(format!(
+                        "Object '{hash}' for '{}' was removed during the clone - clone again to fetch it",
+                        obj_info.name
+                    )));
+                }
+            }
+            let local_work = LocalWork::capture(&self.database)?;
+            self.clear_history()?;
+            self.import_history(
+                local_work,
+                refs,
+                changes,
+                change_order,
+                source_remote(remote_name, base_url, external_user_info.as_ref()),
+            )
+        })?;
+        Ok(Some(preserved))
+    }
+
+    /// Download a batch of blobs, verifying each against its hash and storing it as soon as
+    /// it arrives. An interrupted transfer is resumed with the blobs still outstanding.
+    async fn fetch_objects(
+        &self,
+        client: &reqwest::Client,
+        pack_url: &str,
+        api_key: Option<&str>,
+        hashes: &[String],
+        rekeyed: &mut HashMap<String, String>,
+    ) -> Result<(), ObjectsTreeError> {
+        let objects_provider = self.database.objects();
+        let mut remaining = hashes.to_vec();
+        let mut attempt = 1;
+        loop {
+            let mut received = HashSet::new();
+            let request = PackRequest::Objects {
+                hashes: remaining.clone(),
+            };
+            let result = fetch_pack(client, pack_url, api_key, &request, |frame| {
+                let objects = match frame {
+                    PackFrame::Objects { objects } => objects,
+                    other => return Err(unexpected_frame(&other)),
+                };
+                for (remote_key, object_data) in objects {
+                    // The source may use another algorithm, but every key must still be a
+                    // hash of its content - anything else was corrupted on the way
+                    if ContentHash::identify(&remote_key, object_data.as_bytes()).is_none() {
+                        return Err(PackError::Invalid(format!(
+                            "cloned object '{remote_key}' does not match its content hash - refusing corrupted clone data"
+                        )));
+                    }
+                    // Stored straight away, outside the operation's batch, so a clone that
+                    // fails later still keeps them to resume from
+                    let local_key = objects_provider.generate_hash(&object_data);
+                    self.database
+                        .outside_batch(|| objects_provider.store(&local_key, &object_data))
+                        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+                    // Read it back inside the batch, so a gc sweeping it before the refs
+                    // that use it commit makes the clone conflict instead
+                    objects_provider
+                        .get_object_size(&local_key)
+                        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+                    if local_key != remote_key {
+                        rekeyed.insert(remote_key.clone(), local_key);
+                    }
+                    received.insert(remote_key);
+                }
+                Ok(())
+            })
+            .await;
+            remaining.retain(|hash| !received.contains(hash));
+
+            match result {
+                Ok(Some(_)) => break,
+                Ok(None) => {
+                    return Err(ObjectsTreeError::SerializationError(
+                        "Source stopped serving clone packs during the clone".to_string(),
+                    ));
+                }
+                Err(PackError::Interrupted(_)) if remaining.is_empty() => break,
+                Err(PackError::Interrupted(reason)) if attempt < MAX_PACK_ATTEMPTS => {
+                    warn!(
+                        "Clone pack transfer interrupted ({}) - resuming with {} objects left (attempt {}/{})",
+                        reason,
+                        remaining.len(),
+                        attempt + 1,
+                        MAX_PACK_ATTEMPTS
+                    );
+                    tokio::time::sleep(PACK_RETRY_DELAY * attempt).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        if let Some(hash) = remaining.first() {
+            return Err(ObjectsTreeError::SerializationError(format!(
+                "Source is missing {} objects its refs point to, including '{hash}'",
+                remaining.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Download the ref list and the last merged change it is consistent with, retrying an
+    /// interrupted transfer. Returns None if the source does not serve clone packs.
+    async fn fetch_refs(
+        &self,
+        client: &reqwest::Client,
+        pack_url: &str,
+        api_key: Option<&str>,
+    ) -> Result<Option<(Option<String>, Vec<(ObjectInfo, String)>)>, ObjectsTreeError> {
+        let mut attempt = 1;
+        loop {
+            let mut tip = None;
+            let mut refs = Vec::new();
+            let result = fetch_pack(client, pack_url, api_key, &PackRequest::Refs, |frame| {
+                match frame {
+                    PackFrame::Header { version, .. } if version > PACK_VERSION => {
+                        return Err(PackError::Invalid(format!(
+                            "unsupported pack version {version}"
+                        )));
+                    }
+                    PackFrame::Header {
+                        tip: header_tip, ..
+                    } => tip = header_tip,
+                    PackFrame::Refs { refs: chunk } => refs.extend(chunk),
+                    other => return Err(unexpected_frame(&other)),
+                }
+                Ok(())
+            })
+            .await;
+
+            match result {
+                Ok(Some(count)) if count == refs.len() => {
+                    info!("Received {} refs (tip: {:?})", refs.len(), tip);
+                    return Ok(Some((tip, refs)));
+                }
+                Ok(Some(count)) => {
+                    return Err(ObjectsTreeError::SerializationError(format!(
+                        "Clone pack announced {count} refs but carried {}",
+                        refs.len()
+                    )));
+                }
+                Ok(None) => return Ok(None),
+                Err(PackError::Interrupted(reason)) if attempt < MAX_PACK_ATTEMPTS => {
+                    warn!(
+                        "Clone pack transfer interrupted ({}) - retrying (attempt {}/{})",
+                        reason,
+                        attempt + 1,
+                        MAX_PACK_ATTEMPTS
+                    );
+                    tokio::time::sleep(PACK_RETRY_DELAY * attempt).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Download the merged changes up to and including `tip`, retrying an interrupted transfer
+    async fn fetch_changes(
+        &self,
+        client: &reqwest::Client,
+        pack_url: &str,
+        api_key: Option<&str>,
+        tip: Option<String>,
+    ) -> Result<Vec<Change>, ObjectsTreeError> {
+        let request = PackRequest::Changes { until: tip };
+        let mut attempt = 1;
+        loop {
+            let mut changes = Vec::new();
+            let result = fetch_pack(client, pack_url, api_key, &request, |frame| {
+                match frame {
+                    PackFrame::Changes { changes: chunk } => changes.extend(chunk),
+                    other => return Err(unexpected_frame(&other)),
+                }
+                Ok(())
+            })
+            .await;
+
+            match result {
+                Ok(Some(count)) if count == changes.len() => {
+                    info!("Received {} changes", changes.len());
+                    return Ok(changes);
+                }
+                Ok(Some(count)) => {
+                    return Err(ObjectsTreeError::SerializationError(format!(
+                        "Clone pack announced {count} changes but carried {}",
+                        changes.len()
+                    )));
+                }
+                Ok(None) => {
+                    return Err(ObjectsTreeError::SerializationError(
+                        "Source stopped serving clone packs during the clone".to_string(),
+                    ));
+                }
+                Err(PackError::Interrupted(reason)) if attempt < MAX_PACK_ATTEMPTS => {
+                    warn!(
+                        "Clone pack transfer interrupted ({}) - retrying (attempt {}/{})",
+                        reason,
+                        attempt + 1,
+                        MAX_PACK_ATTEMPTS
+                    );
+                    tokio::time::sleep(PACK_RETRY_DELAY * attempt).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
 
-        // Make GET request to the URL using async client
+    /// Import repository state from the JSON export of a source without clone packs
+    async fn import_from_json_async(
+        &self,
+        url: &str,
+        base_url: &str,
+        external_user_api_key: Option<&str>,
+        remote_name: &str,
+    ) -> Result<Vec<PreservedChange>, ObjectsTreeError> {
+        // Make GET request to the URL using async client, authenticating if we have a key
         let client = reqwest::Client::new();
-        let response = client.get(url).send().await.map_err(|e| {
+        let mut request = client.get(url);
+        if let Some(api_key) = external_user_api_key {
+            request = request.header("X-API-Key", api_key);
+        }
+        let response = request.send().await.map_err(|e| {
             ObjectsTreeError::SerializationError(format!("HTTP request failed: {e}"))
         })?;
 
@@ -219,24 +591,15 @@ impl CloneOperation {
             )));
         };
 
-        // Extract base URL from source_url (remove /api/clone or /clone suffix)
-        let base_url = url
-            .trim_end_matches("/api/clone")
-            .trim_end_matches("/clone")
-            .to_string();
-
         // If external user API key is provided, validate it and get user info
-        let external_user_info = if let Some(api_key) = external_user_api_key {
-            let (user_id, _email) = self.validate_external_user(&base_url, api_key).await?;
-            Some((api_key.to_string(), user_id))
-        } else {
-            None
-        };
-
-        // Import the data
-        self.import_state(clone_data, url, external_user_info.as_ref())?;
+        let external_user_info = self
+            .external_user_info(base_url, external_user_api_key)
+            .await?;
 
-        Ok(format!("Successfully cloned from {url}"))
+        // Import the data, replacing local state all-or-nothing
+        let remote = source_remote(remote_name, base_url, external_user_info.as_ref());
+        self.database
+            .write_batch(|| self.import_state(clone_data, remote.clone()))
     }
 
     /// Import repository state from a URL (sync wrapper for use in execute())
@@ -244,67 +607,111 @@ impl CloneOperation {
         &self,
         url: &str,
         external_user_api_key: Option<String>,
+        remote_name: &str,
     ) -> Result<String, ObjectsTreeError> {
-        let url = url.to_string();
-        let self_clone = self.clone();
-
-        // Spawn an async task and wait for it
-        let (tx, rx) = std::sync::mpsc::sync_channel(1);
-
-        tokio::spawn(async move {
-            let api_key_ref = external_user_api_key.as_deref();
-            let result = self_clone.import_from_url_async(&url, api_key_ref).await;
-            let _ = tx.send(result);
-        });
-
-        rx.recv().map_err(|_| {
-            ObjectsTreeError::SerializationError("Channel closed during clone import".to_string())
-        })?
+        crate::util::block_on(self.import_from_url_async(
+            url,
+            external_user_api_key.as_deref(),
+            remote_name,
+        ))
     }
 
-    /// Import repository state from CloneData
+    /// Import repository state from CloneData, carrying unmerged local work over onto it
     fn import_state(
         &self,
         data: CloneData,
-        source_url: &str,
-        external_user_info: Option<&(String, String)>,
-    ) -> Result<(), ObjectsTreeError> {
+        remote: Remote,
+    ) -> Result<Vec<PreservedChange>, ObjectsTreeError> {
         let object_count = data.objects.len();
-        let refs_count = data.refs.len();
-        let changes_count = data.changes.len();
 
         info!(
             "Importing {} refs, {} objects, {} changes",
-            refs_count, object_count, changes_count
+            data.refs.len(),
+            object_count,
+            data.changes.len()
         );
 
-        // Clear existing state first
+        // Key incoming objects by our own content hash. The source may use another
+        // algorithm, but every key must still be a hash of its content - anything else
+        // was corrupted on the source or in transit and is refused before we clear state
+        let objects_provider = self.database.objects();
+        let mut objects = Vec::with_capacity(object_count);
+        let mut rekeyed = HashMap::new();
+        for (remote_key, object_data) in data.objects {
+            if ContentHash::identify(&remote_key, object_data.as_bytes()).is_none() {
+                return Err(ObjectsTreeError::SerializationError(format!(
+                    "Cloned object '{remote_key}' does not match its content hash - refusing corrupted clone data"
+                )));
+            }
+            let local_key = objects_provider.generate_hash(&object_data);
+            if local_key != remote_key {
+                rekeyed.insert(remote_key, local_key.clone());
+            }
+            objects.push((local_key, object_data));
+        }
+        if !rekeyed.is_empty() {
+            info!(
+                "Re-keyed {} cloned objects to {} content hash",
+                rekeyed.len(),
+                objects_provider.content_hash().name()
+            );
+        }
+
+        // Keep unmerged local changes and their blobs aside, then clear existing state
+        let local_work = LocalWork::capture(&self.database)?;
         info!("Clearing existing state...");
         self.database
             .objects()
             .clear()
             .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
-        self.database
-            .refs()
-            .clear()
-            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
-        self.database
-            .index()
-            .clear()
-            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        self.clear_history()?;
         info!("Existing state cleared");
 
         // Import objects first
-        for (sha256, object_data) in data.objects {
+        for (hash_key, object_data) in objects {
             self.database
                 .objects()
-                .store(&sha256, &object_data)
+                .store(&hash_key, &object_data)
                 .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
         }
         info!("Imported {} objects", object_count);
 
+        let refs = data
+            .refs
+            .into_iter()
+            .map(|(obj_info, hash)| {
+                let hash = rekeyed.get(&hash).cloned().unwrap_or(hash);
+                (obj_info, hash)
+            })
+            .collect();
+        self.import_history(local_work, refs, data.changes, data.change_order, remote)
+    }
+
+    /// Clear the refs and index ahead of an import
+    fn clear_history(&self) -> Result<(), ObjectsTreeError> {
+        self.database
+            .refs()
+            .clear()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        self.database
+            .index()
+            .clear()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Import refs and merged history into the cleared index, put local work back on top
+    /// and record where the clone came from as a remote
+    fn import_history(
+        &self,
+        local_work: LocalWork,
+        refs: Vec<(ObjectInfo, String)>,
+        changes: Vec<Change>,
+        change_order: Vec<String>,
+        remote: Remote,
+    ) -> Result<Vec<PreservedChange>, ObjectsTreeError> {
         // Import refs
-        for (obj_info, sha256) in &data.refs {
+        for (obj_info, sha256) in &refs {
             self.database
                 .refs()
                 .update_ref(
@@ -315,46 +722,33 @@ impl CloneOperation {
                 )
                 .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
         }
-        info!("Imported {} refs", refs_count);
+        info!("Imported {} refs", refs.len());
 
         // Import changes
-        for change in &data.changes {
+        for change in &changes {
             self.database
                 .index()
                 .store_change(change)
                 .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
         }
-        info!("Imported {} changes", data.changes.len());
+        info!("Imported {} changes", changes.len());
 
         // Set the change order directly
         self.database
             .index()
-            .set_change_order(data.change_order)
+            .set_change_order(change_order)
             .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
 
-        // Extract base URL from source_url (remove /api/clone or /clone suffix)
-        let base_url = source_url
-            .trim_end_matches("/api/clone")
-            .trim_end_matches("/clone")
-            .to_string();
+        // Put local work back on top of the imported history
+        let preserved = local_work.restore(&self.database)?;
 
-        // Set the source URL (base URL only, for use with /rpc endpoint)
+        // Record the source (base URL only, for use with /rpc endpoint) and the
+        // credentials it accepted, replacing whatever the remote had before
         self.database
             .index()
-            .set_source(&base_url)
+            .set_remote(&remote)
             .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
-
-        // If external user info was provided, store it
-        if let Some((api_key, user_id)) = external_user_info {
-            self.database
-                .index()
-                .set_external_user_api_key(api_key)
-                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
-            self.database
-                .index()
-                .set_external_user_id(user_id)
-                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
-
+        if let Some(user_id) = &remote.user_id {
             info!(
                 "Stored external user credentials (user_id: {}) for future operations",
                 user_id
@@ -362,13 +756,32 @@ impl CloneOperation {
         }
 
         info!(
-            "Successfully imported repository from {} (base: {})",
-            source_url, base_url
+            "Successfully imported repository from {} as remote '{}'",
+            remote.url, remote.name
         );
-        Ok(())
+        Ok(preserved)
     }
 }
 
+/// The remote a clone records its source under, with the credentials it validated
+fn source_remote(
+    name: &str,
+    base_url: &str,
+    external_user_info: Option<&(String, String)>,
+) -> Remote {
+    Remote {
+        name: name.to_string(),
+        url: base_url.to_string(),
+        api_key: external_user_info.map(|(api_key, _)| api_key.clone()),
+        user_id: external_user_info.map(|(_, user_id)| user_id.clone()),
+    }
+}
+
+/// Error for a frame that doesn't belong in the pack being read
+fn unexpected_frame(frame: &PackFrame) -> PackError {
+    PackError::Invalid(format!("unexpected {} frame", frame.kind()))
+}
+
 impl Operation for CloneOperation {
     fn name(&self) -> &'static str {
         "clone"
@@ -379,7 +792,7 @@ impl Operation for CloneOperation {
     }
 
     fn description(&self) -> &'static str {
-        "Export repository state (no URL) or import from a URL"
+        "Export repository state (no URL) or import from a URL or named remote"
     }
 
     fn philosophy(&self) -> &'static str {
@@ -387,7 +800,17 @@ impl Operation for CloneOperation {
         repository state including all objects, refs, and merged changes into a portable JSON format. Import \
         mode (with URL) fetches repository data from a remote source and loads it locally. This is essential \
         for setting up new repository clones, creating backups, or synchronizing between different MOO \
-        instances. The operation preserves complete history and maintains referential integrity across the clone."
+        instances. The operation preserves complete history and maintains referential integrity across the clone. \
+        Imports stream a compressed clone pack from the source's /api/clone/pack endpoint when it has one: the \
+        ref list first, then only the blobs missing locally, each checked against its hash and stored as it \
+        arrives, then the merged changes. A dropped connection resumes with the blobs still outstanding or fetches the refs or changes again, and \
+        running the clone again skips everything already downloaded. Sources without packs are cloned from \
+        the JSON export. Importing never destroys work in progress: unmerged local and workspace changes, with the object versions \
+        they record, are set aside before the import and put back afterwards, rebased onto the imported index. \
+        Changes that no longer apply cleanly are kept untouched and listed in the result. The source and the \
+        credentials it accepted are recorded as the remote named origin, or as the remote given by name; other \
+        remotes are left as they are. Given a remote name without a URL, the clone comes from that remote's URL \
+        with its stored credentials."
     }
 
     fn parameters(&self) -> Vec<OperationParameter> {
@@ -401,6 +824,11 @@ impl Operation for CloneOperation {
                 name: "external_user_api_key".to_string(),
                 description: "Optional API key for authenticating with the remote VCS worker. When provided, validates the key and stores it for future update operations.".to_string(),
                 required: false,
+            },
+            OperationParameter {
+                name: "remote".to_string(),
+                description: "Optional name of the remote to record the source under (default: origin). Without a URL, clones from this remote's URL and credentials.".to_string(),
+                required: false,
             }
         ]
     }
@@ -427,6 +855,13 @@ impl Operation for CloneOperation {
 // Imports repository and validates/stores the API key for future updates
 // The remote server is queried to verify the key and get user info"#.to_string(),
                 http_curl: None,
+            },
+            OperationExample {
+                description: "Import from a named remote".to_string(),
+                moocode: r#"worker_request("vcs", {"remote/add", "staging", "http://staging-server:8081", "staging-api-key"});
+result = worker_request("vcs", {"clone", "", "", "staging"});
+// Clones from the staging remote with its stored credentials"#.to_string(),
+                http_curl: None,
             }
         ]
     }
@@ -446,6 +881,11 @@ impl Operation for CloneOperation {
         ]
     }
 
+    fn query_parameters(&self) -> Vec<String> {
+        // GET only exports; importing from a URL must go through POST
+        vec![]
+    }
+
     fn responses(&self) -> Vec<crate::operations::OperationResponse> {
         use crate::operations::OperationResponse;
         vec![
@@ -457,14 +897,16 @@ impl Operation for CloneOperation {
                 "Import - Returns success message after importing from URL",
                 r#""Successfully cloned from http://source-server:8081/api/clone""#,
             ),
-            OperationResponse::new(
-                403,
+            OperationResponse::success(
+                "Import - Local changes were kept and rebased onto the imported index",
+                r#""Successfully cloned from http://source-server:8081/api/clone, preserving local changes: 'my-change' (rebased); 'stashed' (conflict: $room (verbs: look))""#,
+            ),
+            OperationResponse::forbidden(
                 "Forbidden - User lacks Clone permission",
-                r#"E_INVARG("You do not have permission to clone repositories")"#,
+                r#"E_PERM("User 'player' does not have permission to clone repositories")"#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - Failed to serialize clone data during export",
+            OperationResponse::internal_error(
+                "Internal Server Error - Failed to serialize clone data during export",
                 r#"E_INVARG("Failed to serialize: invalid UTF-8 sequence")"#,
             ),
             OperationResponse::new(
@@ -480,7 +922,7 @@ impl Operation for CloneOperation {
         ]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!(
             "Clone operation received {} arguments: {:?}",
             args.len(),
@@ -488,49 +930,81 @@ impl Operation for CloneOperation {
         );
 
         // Check if user has Clone permission
-        if !user.has_permission(&crate::types::Permission::Clone) {
-            error!("User {} does not have Clone permission", user.id);
-            return v_error(E_INVARG.msg("You do not have permission to clone repositories"));
+        require_permission(user, crate::types::Permission::Clone, "clone repositories")?;
+
+        // Optional name of the remote to clone from and record the source as
+        let remote_name = args
+            .get(2)
+            .map(String::as_str)
+            .filter(|name| !name.is_empty());
+        if let Some(name) = remote_name {
+            validate_remote_name(name)?;
         }
 
-        // If no URL provided, export state
-        if args.is_empty() || args[0].is_empty() {
+        // If no URL or remote provided, export state
+        if (args.is_empty() || args[0].is_empty()) && remote_name.is_none() {
             match self.export_state() {
                 Ok(clone_data) => match serde_json::to_string(&clone_data) {
                     Ok(json) => {
                         info!("Exported repository state as JSON ({} bytes)", json.len());
-                        moor_var::v_str(&json)
+                        Ok(moor_var::v_str(&json))
                     }
                     Err(e) => {
                         error!("Failed to serialize clone data: {}", e);
-                        v_error(E_INVARG.msg(format!("Failed to serialize: {e}")))
+                        Err(OperationError::Internal(format!(
+                            "Failed to serialize: {e}"
+                        )))
                     }
                 },
                 Err(e) => {
                     error!("Failed to export repository state: {}", e);
-                    v_error(E_INVARG.msg(format!("{e}")))
+                    Err(e.into())
                 }
             }
         } else {
-            // URL provided, import from URL
-            let url = args[0].clone();
+            // URL or remote provided, import from URL
+            let known_remote = match remote_name {
+                Some(name) => self
+                    .database
+                    .index()
+                    .get_remote(name)
+                    .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?,
+                None => None,
+            };
+            let url = match (args.first().filter(|url| !url.is_empty()), &known_remote) {
+                (Some(url), _) => url.clone(),
+                (None, Some(remote)) => format!("{}/api/clone", remote.url),
+                (None, None) => {
+                    return Err(OperationError::NotFound(format!(
+                        "Remote '{}' not found",
+                        remote_name.unwrap_or(DEFAULT_REMOTE)
+                    )));
+                }
+            };
 
-            // Get optional external_user_api_key
+            // Get optional external_user_api_key, falling back to the one stored for the
+            // remote as long as the clone is from that remote's URL
             let external_user_api_key = if args.len() > 1 && !args[1].is_empty() {
                 Some(args[1].clone())
             } else {
-                None
+                known_remote
+                    .filter(|remote| remote.url == remote_base_url(&url))
+                    .and_then(|remote| remote.api_key)
             };
 
             // Call the synchronous import_from_url
-            match self.import_from_url(&url, external_user_api_key) {
+            match self.import_from_url(
+                &url,
+                external_user_api_key,
+                remote_name.unwrap_or(DEFAULT_REMOTE),
+            ) {
                 Ok(result) => {
                     info!("Clone operation completed successfully");
-                    moor_var::v_str(&result)
+                    Ok(moor_var::v_str(&result))
                 }
                 Err(e) => {
                     error!("Clone operation failed: {}", e);
-                    v_error(E_INVARG.msg(format!("{e}")))
+                    Err(e.into())
                 }
             }
         }
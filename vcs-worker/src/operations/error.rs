@@ -0,0 +1,122 @@
+use moor_var::{E_INVARG, E_INVIND, E_NACC, E_PERM, v_error};
+use thiserror::Error;
+
+use crate::database::ObjectsTreeError;
+use crate::providers::ProviderError;
+use crate::types::{Permission, User};
+
+/// Error type returned by `Operation::execute`
+///
+/// Each variant maps to an HTTP status code and machine-readable code for HTTP clients,
+/// and to a MOO error value for RPC callers.
+#[derive(Debug, Clone, Error)]
+pub enum OperationError {
+    /// The referenced change, object, user or operation does not exist
+    #[error("{0}")]
+    NotFound(String),
+    /// The caller lacks the permission required for the operation
+    #[error("{0}")]
+    PermissionDenied(String),
+    /// The operation is not allowed in the current repository state
+    #[error("{0}")]
+    Conflict(String),
+    /// Another operation changed data this one read before it could commit; nothing was
+    /// written and the request can be retried as is
+    #[error("{0}")]
+    ConcurrentModification(String),
+    /// The arguments were missing or malformed
+    #[error("{0}")]
+    InvalidArgs(String),
+    /// Database, network or other unexpected failure
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl OperationError {
+    /// Machine-readable error code reported as `error.code` in HTTP responses
+    pub fn code(&self) -> &'static str {
+        match self {
+            OperationError::NotFound(_) => "not_found",
+            OperationError::PermissionDenied(_) => "permission_denied",
+            OperationError::Conflict(_) => "conflict",
+            OperationError::ConcurrentModification(_) => "concurrent_modification",
+            OperationError::InvalidArgs(_) => "invalid_args",
+            OperationError::Internal(_) => "internal",
+        }
+    }
+
+    /// HTTP status code for this error
+    pub fn status_code(&self) -> u16 {
+        match self {
+            OperationError::NotFound(_) => 404,
+            OperationError::PermissionDenied(_) => 403,
+            OperationError::Conflict(_) | OperationError::ConcurrentModification(_) => 409,
+            OperationError::InvalidArgs(_) => 400,
+            OperationError::Internal(_) => 500,
+        }
+    }
+
+    /// Convert to the MOO error value returned to RPC callers
+    pub fn to_moo_error(&self) -> moor_var::Var {
+        let code = match self {
+            OperationError::NotFound(_) => E_INVIND,
+            OperationError::PermissionDenied(_) => E_PERM,
+            OperationError::Conflict(_) | OperationError::ConcurrentModification(_) => E_NACC,
+            OperationError::InvalidArgs(_) | OperationError::Internal(_) => E_INVARG,
+        };
+        v_error(code.msg(self.to_string()))
+    }
+}
+
+/// Fail with `PermissionDenied` unless the user holds the permission needed to `action`
+pub fn require_permission(
+    user: &User,
+    permission: Permission,
+    action: &str,
+) -> Result<(), OperationError> {
+    if user.has_permission(&permission) {
+        return Ok(());
+    }
+    tracing::error!("User '{}' does not have permission to {}", user.id, action);
+    Err(OperationError::PermissionDenied(format!(
+        "User '{}' does not have permission to {action}",
+        user.id
+    )))
+}
+
+impl From<ObjectsTreeError> for OperationError {
+    fn from(e: ObjectsTreeError) -> Self {
+        match e {
+            ObjectsTreeError::NotFound(msg) => OperationError::NotFound(msg),
+            ObjectsTreeError::InvalidArgument(msg) => OperationError::InvalidArgs(msg),
+            ObjectsTreeError::CompilationError(e) => OperationError::InvalidArgs(e.to_string()),
+            ObjectsTreeError::ConcurrentModification(msg) => {
+                OperationError::ConcurrentModification(msg)
+            }
+            other => OperationError::Internal(other.to_string()),
+        }
+    }
+}
+
+impl From<crate::types::ObjectsTreeError> for OperationError {
+    fn from(e: crate::types::ObjectsTreeError) -> Self {
+        use crate::types::ObjectsTreeError as TreeError;
+        match e {
+            TreeError::ObjectNotFound(msg) => OperationError::NotFound(msg),
+            TreeError::InvalidOperation(msg) => OperationError::InvalidArgs(msg),
+            TreeError::ProviderError(e) => e.into(),
+            other => OperationError::Internal(other.to_string()),
+        }
+    }
+}
+
+impl From<ProviderError> for OperationError {
+    fn from(e: ProviderError) -> Self {
+        match e {
+            ProviderError::ObjectNotFound(msg) => OperationError::NotFound(msg),
+            ProviderError::InvalidOperation(msg) => OperationError::InvalidArgs(msg),
+            ProviderError::CompilationError(e) => OperationError::InvalidArgs(e.to_string()),
+            other => OperationError::Internal(other.to_string()),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Operation, OperationExample, OperationParameter, OperationRoute};
+use super::{Operation, OperationError, OperationExample, OperationParameter, OperationRoute};
 use crate::types::User;
 use axum::http::Method;
 
@@ -11,6 +11,10 @@ impl Operation for HelloOperation {
         "hello"
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &'static str {
         "A simple greeting operation that returns goodbye"
     }
@@ -68,8 +72,8 @@ impl Operation for HelloOperation {
         ]
     }
 
-    fn execute(&self, _args: Vec<String>, _user: &User) -> moor_var::Var {
+    fn execute(&self, _args: Vec<String>, _user: &User) -> Result<moor_var::Var, OperationError> {
         tracing::info!("Executing hello operation");
-        moor_var::v_str("goodbye")
+        Ok(moor_var::v_str("goodbye"))
     }
 }
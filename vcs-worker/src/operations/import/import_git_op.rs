@@ -0,0 +1,482 @@
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
+use axum::http::Method;
+use moor_var::{Var, v_int, v_list, v_map, v_str};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path};
+use tracing::{error, info};
+
+use crate::config::Config;
+use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::git_import::{FileChange, SourceCommit, local_source_path, read_import_source};
+use crate::local_work::{LocalWork, PreservedChange};
+use crate::providers::index::IndexProvider;
+use crate::providers::objects::ObjectsProvider;
+use crate::providers::refs::RefsProvider;
+use crate::types::{
+    Change, ChangeStatus, ImportGitRequest, ObjectInfo, Permission, RenamedObject, User,
+    VcsObjectType,
+};
+
+/// An object version created by the import, waiting to be stored
+struct ImportedRef {
+    name: String,
+    version: u64,
+    sha256: String,
+    content: String,
+}
+
+/// Changes and refs built from the import source, before anything is written
+#[derive(Default)]
+struct ImportPlan {
+    changes: Vec<Change>,
+    refs: Vec<ImportedRef>,
+    /// Current version and content hash of every live object
+    current: HashMap<String, (u64, String)>,
+    /// Highest version ever used per object name, so re-added names keep their history
+    latest: HashMap<String, u64>,
+}
+
+impl ImportPlan {
+    /// Record a new version of an object as its current version
+    fn record_version(&mut self, name: &str, version: u64, sha256: String, content: String) {
+        self.current
+            .insert(name.to_string(), (version, sha256.clone()));
+        let latest = self.latest.entry(name.to_string()).or_default();
+        *latest = (*latest).max(version);
+        self.refs.push(ImportedRef {
+            name: name.to_string(),
+            version,
+            sha256,
+            content,
+        });
+    }
+
+    /// Write an object's content, as an addition or a modification
+    fn write_object(&mut self, change: &mut Change, name: &str, sha256: String, content: String) {
+        let version = match self.current.get(name) {
+            // Unchanged content (e.g. a mode change) is not a new version
+            Some((_, current_sha256)) if *current_sha256 == sha256 => return,
+            Some((version, _)) => {
+                let version = version + 1;
+                change.modified_objects.push(object_info(name, version));
+                version
+            }
+            None => {
+                let version = self.latest.get(name).map_or(1, |v| v + 1);
+                change.added_objects.push(object_info(name, version));
+                version
+            }
+        };
+        self.record_version(name, version, sha256, content);
+    }
+
+    /// Delete an object, if it exists
+    fn delete_object(&mut self, change: &mut Change, name: &str) {
+        if let Some((version, _)) = self.current.remove(name) {
+            change.deleted_objects.push(object_info(name, version));
+        }
+    }
+}
+
+/// Build the ObjectInfo for a MOO object version
+fn object_info(name: &str, version: u64) -> ObjectInfo {
+    ObjectInfo {
+        object_type: VcsObjectType::MooObject,
+        name: name.to_string(),
+        version,
+    }
+}
+
+/// Import git operation that builds the index from the history of a git repository of `.moo` files
+#[derive(Clone)]
+pub struct ImportGitOperation {
+    database: DatabaseRef,
+    config: Config,
+}
+
+impl ImportGitOperation {
+    /// Create a new import git operation
+    pub fn new(database: DatabaseRef, config: Config) -> Self {
+        Self { database, config }
+    }
+
+    /// Refuse a local source unless it lies inside the configured import root
+    fn check_import_root(&self, source: &str, path: &Path) -> Result<(), OperationError> {
+        let Some(root) = &self.config.import_root else {
+            error!(
+                "Refusing local import source '{}': no import root configured",
+                source
+            );
+            return Err(OperationError::PermissionDenied(
+                "Importing from local paths is disabled (no import root configured)".to_string(),
+            ));
+        };
+        let root = root.canonicalize().map_err(|e| {
+            OperationError::Internal(format!("Import root {:?} is not accessible: {}", root, e))
+        })?;
+
+        // Resolve symlinks and `..` so the source can't point outside the root
+        let inside = match path.canonicalize() {
+            Ok(path) => path.starts_with(&root),
+            // A missing source is reported by the reader, as long as it would be inside
+            Err(_) => {
+                path.starts_with(&root) && !path.components().any(|c| c == Component::ParentDir)
+            }
+        };
+        if !inside {
+            error!("Refusing import source '{}' outside {:?}", source, root);
+            return Err(OperationError::PermissionDenied(format!(
+                "Import source '{}' is outside the import root",
+                source
+            )));
+        }
+        Ok(())
+    }
+
+    /// Validate an object file and return its content hash
+    fn hash_object(
+        &self,
+        commit: &SourceCommit,
+        name: &str,
+        content: &str,
+    ) -> Result<String, OperationError> {
+        self.database
+            .objects()
+            .parse_object_dump(content)
+            .map_err(|e| {
+                OperationError::InvalidArgs(format!(
+                    "Invalid object '{}' in {}: {}",
+                    name, commit.commit, e
+                ))
+            })?;
+        Ok(self.database.objects().generate_hash(content))
+    }
+
+    /// Turn the source commits into merged changes without touching the database
+    fn build_plan(
+        &self,
+        commits: Vec<SourceCommit>,
+        user: &User,
+    ) -> Result<ImportPlan, OperationError> {
+        let mut plan = ImportPlan::default();
+        let mut change_ids = HashSet::new();
+
+        for commit in commits {
+            let author = if commit.author.is_empty() {
+                user.id.clone()
+            } else {
+                commit.author.clone()
+            };
+
+            // Reuse the change ID recorded by a git backup so round-trips keep their identity
+            let mut change_id = commit.change_id.clone().unwrap_or_else(|| {
+                crate::util::generate_change_id(
+                    &commit.name,
+                    commit.description.as_deref(),
+                    &author,
+                    commit.timestamp,
+                )
+            });
+            if change_ids.contains(&change_id) {
+                change_id = crate::util::generate_change_id(
+                    &commit.commit,
+                    commit.description.as_deref(),
+                    &author,
+                    commit.timestamp,
+                );
+            }
+            change_ids.insert(change_id.clone());
+
+            let mut change = Change {
+                id: change_id,
+                name: commit.name.clone(),
+                description: commit.description.clone(),
+                author,
+                timestamp: commit.timestamp,
+                status: ChangeStatus::Merged,
+                added_objects: Vec::new(),
+                modified_objects: Vec::new(),
+                deleted_objects: Vec::new(),
+                renamed_objects: Vec::new(),
+                index_change_id: plan.changes.last().map(|c| c.id.clone()),
+                verb_rename_hints: Vec::new(),
+                property_rename_hints: Vec::new(),
+            };
+
+            // Renames first, so a new file reusing the old name is seen as an addition
+            for file in &commit.files {
+                let FileChange::Renamed { from, to, content } = file else {
+                    continue;
+                };
+                let sha256 = self.hash_object(&commit, to, content)?;
+                // The renamed object keeps its version, so the new name must not have used it yet
+                let can_rename = match plan.current.get(from) {
+                    Some((version, _)) => {
+                        !plan.current.contains_key(to)
+                            && plan.latest.get(to).is_none_or(|latest| latest < version)
+                    }
+                    None => false,
+                };
+                let rename_source = if can_rename {
+                    plan.current.remove(from)
+                } else {
+                    None
+                };
+                let Some((version, old_sha256)) = rename_source else {
+                    // The rename can't be carried over, so record it as delete + add
+                    plan.delete_object(&mut change, from);
+                    plan.write_object(&mut change, to, sha256, content.clone());
+                    continue;
+                };
+
+                change.renamed_objects.push(RenamedObject {
+                    from: object_info(from, version),
+                    to: object_info(to, version),
+                });
+                let old_content = plan
+                    .refs
+                    .iter()
+                    .rev()
+                    .find(|r| r.sha256 == old_sha256)
+                    .map(|r| r.content.clone())
+                    .unwrap_or_default();
+                plan.record_version(to, version, old_sha256, old_content);
+                plan.write_object(&mut change, to, sha256, content.clone());
+            }
+
+            for file in &commit.files {
+                if let FileChange::Deleted { name } = file {
+                    plan.delete_object(&mut change, name);
+                }
+            }
+
+            for file in &commit.files {
+                if let FileChange::Written { name, content } = file {
+                    let sha256 = self.hash_object(&commit, name, content)?;
+                    plan.write_object(&mut change, name, sha256, content.clone());
+                }
+            }
+
+            if change.added_objects.is_empty()
+                && change.modified_objects.is_empty()
+                && change.deleted_objects.is_empty()
+                && change.renamed_objects.is_empty()
+            {
+                continue;
+            }
+            plan.changes.push(change);
+        }
+
+        Ok(plan)
+    }
+
+    /// Replace the merged history with the import plan, keeping unmerged local work on top
+    fn write_plan(&self, plan: &ImportPlan) -> Result<Vec<PreservedChange>, ObjectsTreeError> {
+        // Keep unmerged local changes and their blobs aside, then clear existing state as
+        // clone does
+        let local_work = LocalWork::capture(&self.database)?;
+        info!("Clearing existing state...");
+        self.database
+            .objects()
+            .clear()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        self.database
+            .refs()
+            .clear()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        self.database
+            .index()
+            .clear()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+
+        for imported in &plan.refs {
+            self.database
+                .objects()
+                .store(&imported.sha256, &imported.content)
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+            self.database
+                .refs()
+                .update_ref(
+                    VcsObjectType::MooObject,
+                    &imported.name,
+                    imported.version,
+                    &imported.sha256,
+                )
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        }
+        info!("Imported {} object versions", plan.refs.len());
+
+        for change in &plan.changes {
+            self.database
+                .index()
+                .store_change(change)
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        }
+        self.database
+            .index()
+            .set_change_order(plan.changes.iter().map(|c| c.id.clone()).collect())
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        info!("Imported {} changes", plan.changes.len());
+
+        local_work.restore(&self.database)
+    }
+
+    /// Process the import git request
+    fn process_import_git(
+        &self,
+        request: ImportGitRequest,
+        user: &User,
+    ) -> Result<Var, OperationError> {
+        require_permission(user, Permission::Clone, "import repositories")?;
+
+        info!("Importing world from git source: {}", request.source);
+        if let Some(path) = local_source_path(&request.source) {
+            self.check_import_root(&request.source, &path)?;
+        }
+        let commits = read_import_source(&request.source).map_err(OperationError::InvalidArgs)?;
+        if commits.is_empty() {
+            return Err(OperationError::InvalidArgs(format!(
+                "No .moo files found in '{}'",
+                request.source
+            )));
+        }
+
+        // Parse everything before clearing the current state
+        let plan = self.build_plan(commits, user)?;
+        let preserved = self.write_plan(&plan)?;
+
+        info!(
+            "Imported {} changes and {} objects from {}",
+            plan.changes.len(),
+            plan.current.len(),
+            request.source
+        );
+
+        Ok(v_map(&[
+            (v_str("source"), v_str(&request.source)),
+            (v_str("changes"), v_int(plan.changes.len() as i64)),
+            (v_str("objects"), v_int(plan.current.len() as i64)),
+            (
+                v_str("local_changes"),
+                v_list(&preserved.iter().map(|p| p.to_moo_var()).collect::<Vec<_>>()),
+            ),
+        ]))
+    }
+}
+
+impl Operation for ImportGitOperation {
+    fn name(&self) -> &'static str {
+        "import/git"
+    }
+
+    fn description(&self) -> &'static str {
+        "Replace the repository with the history of a git repository (or directory) of .moo objdef files"
+    }
+
+    fn response_content_type(&self) -> &'static str {
+        "text/x-moo"
+    }
+
+    fn philosophy(&self) -> &'static str {
+        "The reverse of git backup, so objdefs edited in a text editor can round-trip through git and back \
+        into the worker. Every first-parent commit of the repository that touches .moo files becomes a \
+        merged change with the commit's author, timestamp and message; added, modified, deleted and renamed \
+        objects are detected from the tree diff, and each object is named after its file. Commits written by \
+        git backup keep their original change IDs. A plain directory without git history is imported as a \
+        single change. Every file is parsed before anything is written, and like clone the import replaces \
+        the merged history while unmerged local changes are kept and rebased on top of it. Local paths \
+        (including file:// URLs) must be inside the configured import root (VCS_IMPORT_ROOT)."
+    }
+
+    fn parameters(&self) -> Vec<OperationParameter> {
+        vec![OperationParameter {
+            name: "source".to_string(),
+            description: "A git URL (http, https, ssh, git or file://), a local git repository, or a directory of .moo files inside the import root".to_string(),
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> Vec<OperationExample> {
+        vec![
+            OperationExample {
+                description: "Import a world from a git repository".to_string(),
+                moocode: r#"result = worker_request("vcs", {"import/git", "https://github.com/example/world.git"});
+player:tell("Imported ", result["changes"], " changes");"#
+                    .to_string(),
+                http_curl: Some(
+                    r#"curl -X POST http://localhost:8081/api/import/git \
+  -H "Content-Type: application/json" \
+  -d '{"args": ["https://github.com/example/world.git"]}'"#
+                        .to_string(),
+                ),
+            },
+            OperationExample {
+                description: "Import a local directory of .moo files".to_string(),
+                moocode: r#"result = worker_request("vcs", {"import/git", "/srv/world-objdefs"});"#
+                    .to_string(),
+                http_curl: None,
+            },
+        ]
+    }
+
+    fn routes(&self) -> Vec<OperationRoute> {
+        vec![OperationRoute {
+            path: "/api/import/git".to_string(),
+            method: Method::POST,
+            is_json: true,
+        }]
+    }
+
+    fn responses(&self) -> Vec<crate::operations::OperationResponse> {
+        use crate::operations::OperationResponse;
+        vec![
+            OperationResponse::success(
+                "Repository imported",
+                r#"["source" -> "https://github.com/example/world.git", "changes" -> 42, "objects" -> 97, "local_changes" -> {["change_id" -> "abc123", "name" -> "fix-look", "status" -> "rebased", "conflicts" -> {}]}]"#,
+            ),
+            OperationResponse::bad_request(
+                "Bad Request - Missing source, unreadable repository or invalid object file",
+                r#"E_INVARG("Invalid object 'room' in 3f2a9c...: parse error at line 4")"#,
+            ),
+            OperationResponse::forbidden(
+                "Forbidden - User lacks Clone permission",
+                r#"E_PERM("User 'player' does not have permission to import repositories")"#,
+            ),
+            OperationResponse::forbidden(
+                "Forbidden - Local source outside the import root",
+                r#"E_PERM("Import source '/etc' is outside the import root")"#,
+            ),
+            OperationResponse::internal_error(
+                "Internal Server Error - Database error",
+                r#"E_INVARG("Serialization error: failed to store object")"#,
+            ),
+        ]
+    }
+
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<Var, OperationError> {
+        let Some(source) = args.first().filter(|s| !s.is_empty()) else {
+            error!("Import git operation requires a source");
+            return Err(OperationError::InvalidArgs(
+                "Source is required".to_string(),
+            ));
+        };
+
+        let request = ImportGitRequest {
+            source: source.clone(),
+        };
+
+        match self.process_import_git(request, user) {
+            Ok(result) => {
+                info!("Import git operation completed successfully");
+                Ok(result)
+            }
+            Err(e) => {
+                error!("Import git operation failed: {}", e);
+                Err(e)
+            }
+        }
+    }
+}
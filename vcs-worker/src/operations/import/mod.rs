@@ -0,0 +1,3 @@
+mod import_git_op;
+
+pub use import_git_op::ImportGitOperation;
@@ -1,12 +1,16 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use axum::http::Method;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info, warn};
 
 use crate::database::{DatabaseRef, ObjectsTreeError};
 use crate::providers::index::IndexProvider;
-use crate::types::{ChangeStatus, User};
-use moor_var::{E_INVARG, v_error};
+use crate::providers::objects::ObjectsProvider;
+use crate::providers::refs::RefsProvider;
+use crate::types::{ChangeStatus, IndexDelta, ObjectInfo, Permission, User, VcsObjectType};
 
 /// Request structure for index calc delta operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +27,9 @@ pub struct IndexCalcDeltaRequest {
 ///   - change_ids: List of change IDs that are merged and chronologically after the specified change
 ///   - ref_pairs: List of ref pairs from those changes
 ///   - objects_added: List of objects added to the database from those changes
+///   - delta: JSON-encoded `IndexDelta` with the change records, the refs of every object
+///     they touch up to the last version they record and the content of the object
+///     versions they record, for index/update
 ///
 /// Example: `index/calc_delta "abc123"` returns delta information for changes after abc123
 #[derive(Clone)]
@@ -40,7 +47,7 @@ impl IndexCalcDeltaOperation {
     fn process_calc_delta(
         &self,
         request: IndexCalcDeltaRequest,
-    ) -> Result<moor_var::Var, ObjectsTreeError> {
+    ) -> Result<moor_var::Var, OperationError> {
         info!(
             "Processing index calc delta request for change_id: {}",
             request.change_id
@@ -63,7 +70,7 @@ impl IndexCalcDeltaOperation {
             Some(pos) => pos,
             None => {
                 error!("Change '{}' not found in index order", change_id);
-                return Err(ObjectsTreeError::SerializationError(format!(
+                return Err(OperationError::NotFound(format!(
                     "Change '{change_id}' does not exist in index"
                 )));
             }
@@ -81,6 +88,7 @@ impl IndexCalcDeltaOperation {
         let mut change_ids = Vec::new();
         let mut ref_pairs = Vec::new();
         let mut objects_added = Vec::new();
+        let mut delta = IndexDelta::default();
 
         // Process each subsequent change
         for change_id in subsequent_changes {
@@ -186,6 +194,8 @@ impl IndexCalcDeltaOperation {
                         ]);
                         objects_added.push(object_info);
                     }
+
+                    delta.changes.push(change);
                 } else {
                     info!(
                         "Skipping non-merged change '{}' (status: {:?})",
@@ -200,10 +210,17 @@ impl IndexCalcDeltaOperation {
             }
         }
 
+        self.fill_delta_contents(&mut delta)?;
+        let delta_json = serde_json::to_string(&delta).map_err(|e| {
+            ObjectsTreeError::SerializationError(format!("Failed to serialize delta: {e}"))
+        })?;
+
         info!(
-            "Successfully processed {} merged changes after '{}'",
+            "Successfully processed {} merged changes after '{}' ({} refs, {} objects)",
             change_ids.len(),
-            change_id
+            change_id,
+            delta.refs.len(),
+            delta.objects.len()
         );
 
         // Return the result as a map
@@ -214,8 +231,95 @@ impl IndexCalcDeltaOperation {
                 moor_var::v_str("objects_added"),
                 moor_var::v_list(&objects_added),
             ),
+            (moor_var::v_str("delta"), moor_var::v_str(&delta_json)),
         ]))
     }
+
+    /// Add the refs of every object the delta's changes touch, up to the last version
+    /// they record, and the content of the object versions they record. Blobs of older
+    /// versions are left out: the receiver already has them from the changes before the
+    /// delta. Later versions belong to unmerged local or review changes and stay here.
+    pub fn fill_delta_contents(&self, delta: &mut IndexDelta) -> Result<(), ObjectsTreeError> {
+        let mut last_recorded: std::collections::HashMap<(VcsObjectType, String), u64> =
+            std::collections::HashMap::new();
+        for change in &delta.changes {
+            for obj in change
+                .added_objects
+                .iter()
+                .chain(&change.modified_objects)
+                .chain(&change.deleted_objects)
+                .chain(change.renamed_objects.iter().map(|r| &r.to))
+            {
+                let last = last_recorded
+                    .entry((obj.object_type, obj.name.clone()))
+                    .or_default();
+                *last = (*last).max(obj.version);
+            }
+        }
+
+        for (object_type, name) in delta.touched_objects() {
+            // Names only renamed away from get no refs, so the receiver drops theirs too
+            let Some(&last) = last_recorded.get(&(object_type, name.clone())) else {
+                continue;
+            };
+            let refs = self
+                .database
+                .refs()
+                .get_object_refs(object_type, &name)
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+            for (version, hash) in refs.into_iter().filter(|(version, _)| *version <= last) {
+                delta.refs.push((
+                    ObjectInfo {
+                        object_type,
+                        name: name.clone(),
+                        version,
+                    },
+                    hash,
+                ));
+            }
+        }
+
+        // Renames move older versions to the new name, so send every version of a rename
+        // target along with the versions the changes record
+        let mut needed = std::collections::HashSet::new();
+        for change in &delta.changes {
+            for obj in change
+                .added_objects
+                .iter()
+                .chain(&change.modified_objects)
+                .chain(change.renamed_objects.iter().map(|r| &r.to))
+            {
+                needed.insert((obj.object_type, obj.name.clone(), Some(obj.version)));
+            }
+            for renamed in &change.renamed_objects {
+                needed.insert((renamed.to.object_type, renamed.to.name.clone(), None));
+            }
+        }
+        let hashes: std::collections::HashSet<&String> = delta
+            .refs
+            .iter()
+            .filter(|(info, _)| {
+                needed.contains(&(info.object_type, info.name.clone(), Some(info.version)))
+                    || needed.contains(&(info.object_type, info.name.clone(), None))
+            })
+            .map(|(_, hash)| hash)
+            .collect();
+
+        for hash in hashes {
+            match self
+                .database
+                .objects()
+                .get(hash)
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+            {
+                Some(content) => {
+                    delta.objects.insert(hash.clone(), content);
+                }
+                None => warn!("Object blob '{}' referenced by the delta is missing", hash),
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Operation for IndexCalcDeltaOperation {
@@ -223,12 +327,16 @@ impl Operation for IndexCalcDeltaOperation {
         "index/calc_delta"
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn response_content_type(&self) -> &'static str {
         "text/x-moo"
     }
 
     fn description(&self) -> &'static str {
-        "Calculates delta information for changes chronologically after a specified change ID, returning change IDs, ref pairs, and objects added to the database"
+        "Calculates delta information for changes chronologically after a specified change ID, returning change IDs, ref pairs, objects added to the database, and the encoded delta index/update applies"
     }
 
     fn routes(&self) -> Vec<OperationRoute> {
@@ -243,11 +351,18 @@ impl Operation for IndexCalcDeltaOperation {
         "Calculates the delta (difference) of all merged changes that occurred chronologically after a specified change ID. \
         This operation returns comprehensive delta information including change IDs, ref pairs showing object name mappings, \
         and all objects added or modified in those changes. This is primarily used for synchronization between repositories, \
-        allowing efficient transfer of only new changes rather than entire repository contents."
+        allowing efficient transfer of only new changes rather than entire repository contents. The `delta` entry is a JSON \
+        encoding of the change records themselves, every ref of the objects they touch and the content of the object versions \
+        they record - exactly what index/update needs to apply the changes on top of an existing index."
     }
 
     fn parameters(&self) -> Vec<OperationParameter> {
-        vec![]
+        vec![OperationParameter {
+            name: "change_id".to_string(),
+            description: "The change ID to calculate the delta from (changes after this one are included)"
+                .to_string(),
+            required: true,
+        }]
     }
 
     fn examples(&self) -> Vec<OperationExample> {
@@ -289,21 +404,25 @@ endif"#
         vec![
             OperationResponse::success(
                 "Operation executed successfully - Returns delta information",
-                r#"["change_ids" -> {"abc123def", "def456ghi"}, "ref_pairs" -> {["from" -> "", "to" -> "obj1"], ["from" -> "obj2", "to" -> "obj2"], ["from" -> "obj3", "to" -> "obj3_renamed"]}, "objects_added" -> {["name" -> "obj1", "version" -> 1], ["name" -> "obj2", "version" -> 2], ["name" -> "obj3_renamed", "version" -> 1]}]"#,
+                r#"["change_ids" -> {"abc123def", "def456ghi"}, "ref_pairs" -> {["from" -> "", "to" -> "obj1"], ["from" -> "obj2", "to" -> "obj2"], ["from" -> "obj3", "to" -> "obj3_renamed"]}, "objects_added" -> {["name" -> "obj1", "version" -> 1], ["name" -> "obj2", "version" -> 2], ["name" -> "obj3_renamed", "version" -> 1]}, "delta" -> "{\"changes\":[...],\"refs\":[...],\"objects\":{...}}"]"#,
             ),
             OperationResponse::success(
                 "Operation executed successfully - No changes after specified change",
-                r#"["change_ids" -> {}, "ref_pairs" -> {}, "objects_added" -> {}]"#,
+                r#"["change_ids" -> {}, "ref_pairs" -> {}, "objects_added" -> {}, "delta" -> "{\"changes\":[],\"refs\":[],\"objects\":{}}"]"#,
             ),
             OperationResponse::new(
                 400,
                 "Bad Request - Missing change_id argument",
                 r#"E_INVARG("change_id argument is required")"#,
             ),
+            OperationResponse::forbidden(
+                "Forbidden - User lacks Clone permission",
+                r#"E_PERM("User 'Everyone' does not have permission to clone repositories")"#,
+            ),
             OperationResponse::new(
                 404,
                 "Not Found - Change does not exist in index",
-                r#"E_INVARG("Change 'xyz789' does not exist in index")"#,
+                r#"E_INVIND("Change 'xyz789' does not exist in index")"#,
             ),
             OperationResponse::new(
                 500,
@@ -313,17 +432,22 @@ endif"#
         ]
     }
 
-    fn execute(&self, args: Vec<String>, _user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!(
             "Index calc delta operation received {} arguments: {:?}",
             args.len(),
             args
         );
 
+        // The delta carries full change records and object content, like a clone
+        require_permission(user, Permission::Clone, "clone repositories")?;
+
         // Parse change_id argument
         if args.is_empty() || args[0].is_empty() {
             error!("Index calc delta operation requires a change_id argument");
-            return v_error(E_INVARG.msg("change_id argument is required"));
+            return Err(OperationError::InvalidArgs(
+                "change_id argument is required".to_string(),
+            ));
         }
 
         let change_id = args[0].clone();
@@ -332,11 +456,11 @@ endif"#
         match self.process_calc_delta(request) {
             Ok(result_var) => {
                 info!("Index calc delta operation completed successfully");
-                result_var
+                Ok(result_var)
             }
             Err(e) => {
                 error!("Index calc delta operation failed: {}", e);
-                v_error(E_INVARG.msg(format!("{e}")))
+                Err(e)
             }
         }
     }
@@ -1,4 +1,6 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+};
 use axum::http::Method;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info, warn};
@@ -6,7 +8,6 @@ use tracing::{error, info, warn};
 use crate::database::{DatabaseRef, ObjectsTreeError};
 use crate::providers::index::IndexProvider;
 use crate::types::User;
-use moor_var::{E_INVARG, v_error};
 
 /// Request structure for index list operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -144,6 +145,10 @@ impl Operation for IndexListOperation {
         "index/list"
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn response_content_type(&self) -> &'static str {
         "text/x-moo"
     }
@@ -168,7 +173,18 @@ impl Operation for IndexListOperation {
     }
 
     fn parameters(&self) -> Vec<OperationParameter> {
-        vec![]
+        vec![
+            OperationParameter {
+                name: "limit".to_string(),
+                description: "Maximum number of merged changes to return (default: 20)".to_string(),
+                required: false,
+            },
+            OperationParameter {
+                name: "page".to_string(),
+                description: "Zero-based page number to return (default: 0)".to_string(),
+                required: false,
+            },
+        ]
     }
 
     fn examples(&self) -> Vec<OperationExample> {
@@ -213,7 +229,7 @@ player:tell("Found ", length(changes), " merged changes on page 2");"#
         ]
     }
 
-    fn execute(&self, args: Vec<String>, _user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, _user: &User) -> Result<moor_var::Var, OperationError> {
         info!(
             "Index list operation received {} arguments: {:?}",
             args.len(),
@@ -247,11 +263,11 @@ player:tell("Found ", length(changes), " merged changes on page 2");"#
         match self.process_index_list(request) {
             Ok(result_var) => {
                 info!("Index list operation completed successfully");
-                result_var
+                Ok(result_var)
             }
             Err(e) => {
                 error!("Index list operation failed: {}", e);
-                v_error(E_INVARG.msg(format!("{e}")))
+                Err(e.into())
             }
         }
     }
@@ -0,0 +1,247 @@
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
+use axum::http::Method;
+use moor_var::{v_list, v_map, v_str};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use super::index_calc_delta_op::IndexCalcDeltaOperation;
+use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::object_merge::load_merged_changes;
+use crate::operations::remote::{call_remote, remote_tip, resolve_remote};
+use crate::types::{IndexDelta, Permission, User};
+
+/// Request structure for index push operations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexPushRequest {
+    /// Remote to push to; the default remote if None
+    pub remote: Option<String>,
+}
+
+/// Index push operation that sends merged local changes the source lacks to the source,
+/// which fast-forwards its index with them (the reverse of index/update)
+///
+/// Usage:
+/// - `index/push` or `index/push "{remote}"`
+/// - Requires the remote (origin if none is named) to be configured with credentials
+///   that carry ApproveChanges permission on it
+/// - Finds the source's last merged change in the local history and sends the merged
+///   changes after it, packaged as index/calc_delta would, to the source's index/receive
+/// - Fails with a conflict if the source has merged changes we don't have, or merged
+///   something else in the meantime
+/// - Returns the IDs of the pushed changes
+///
+/// Example: `index/push` publishes locally approved changes to the upstream repository
+#[derive(Clone)]
+pub struct IndexPushOperation {
+    database: DatabaseRef,
+}
+
+impl IndexPushOperation {
+    /// Create a new index push operation
+    pub fn new(database: DatabaseRef) -> Self {
+        Self { database }
+    }
+
+    /// Process the index push request (async version)
+    async fn process_push_async(
+        &self,
+        request: IndexPushRequest,
+    ) -> Result<moor_var::Var, OperationError> {
+        let remote = resolve_remote(&self.database, request.remote.as_deref())?;
+        let source_url = remote.url.clone();
+        let client = reqwest::Client::new();
+
+        // The source must not have merged anything we haven't seen
+        let remote_tip = remote_tip(&client, &remote).await?;
+        let merged = load_merged_changes(&self.database)?;
+        let base_position = match &remote_tip {
+            Some(tip) => match merged.iter().position(|change| &change.id == tip) {
+                Some(position) => position + 1,
+                None => {
+                    error!(
+                        "Source's last merged change '{}' is not in our history",
+                        tip
+                    );
+                    return Err(OperationError::Conflict(format!(
+                        "Push rejected: the source has merged change '{tip}', which is not in the local history. Run index/update and push again"
+                    )));
+                }
+            },
+            None => 0,
+        };
+
+        let changes: Vec<_> = merged.into_iter().skip(base_position).collect();
+        if changes.is_empty() {
+            info!("Source already has every merged change - nothing to push");
+            return Ok(push_response(&source_url, remote_tip.as_deref(), &[]));
+        }
+        let change_ids: Vec<String> = changes.iter().map(|c| c.id.clone()).collect();
+
+        // Package the changes exactly as index/calc_delta would for the source to pull
+        let mut delta = IndexDelta {
+            changes,
+            ..IndexDelta::default()
+        };
+        IndexCalcDeltaOperation::new(self.database.clone()).fill_delta_contents(&mut delta)?;
+        info!(
+            "Pushing {} changes with {} refs and {} objects to {} on top of {:?}",
+            delta.changes.len(),
+            delta.refs.len(),
+            delta.objects.len(),
+            source_url,
+            remote_tip
+        );
+
+        let delta_json = serde_json::to_string(&delta).map_err(|e| {
+            ObjectsTreeError::SerializationError(format!("Failed to serialize delta: {e}"))
+        })?;
+        let base = remote_tip.clone().unwrap_or_default();
+        call_remote(&client, &remote, "index/receive", vec![base, delta_json]).await?;
+
+        info!("Pushed {} changes to {}", change_ids.len(), source_url);
+        Ok(push_response(
+            &source_url,
+            remote_tip.as_deref(),
+            &change_ids,
+        ))
+    }
+
+    /// Process the index push request (sync wrapper)
+    fn process_push(&self, request: IndexPushRequest) -> Result<moor_var::Var, OperationError> {
+        crate::util::block_on(self.process_push_async(request))
+    }
+}
+
+/// Build the push response: where we pushed, what it was based on and what was sent
+fn push_response(
+    source_url: &str,
+    base_change_id: Option<&str>,
+    change_ids: &[String],
+) -> moor_var::Var {
+    let pushed: Vec<_> = change_ids.iter().map(|id| v_str(id)).collect();
+    v_map(&[
+        (v_str("source"), v_str(source_url)),
+        (v_str("base_change_id"), v_str(base_change_id.unwrap_or(""))),
+        (v_str("change_ids"), v_list(&pushed)),
+    ])
+}
+
+impl Operation for IndexPushOperation {
+    fn name(&self) -> &'static str {
+        "index/push"
+    }
+
+    fn response_content_type(&self) -> &'static str {
+        "text/x-moo"
+    }
+
+    fn description(&self) -> &'static str {
+        "Pushes merged local changes the source lacks to the source URL, which fast-forwards its index with them"
+    }
+
+    fn routes(&self) -> Vec<OperationRoute> {
+        vec![OperationRoute {
+            path: "/api/index/push".to_string(),
+            method: Method::POST,
+            is_json: false,
+        }]
+    }
+
+    fn philosophy(&self) -> &'static str {
+        "The counterpart of index/update, similar to 'git push' in Git. Where change/submit puts a single change \
+        in the source's review queue, index/push publishes history that is already merged here - for a team whose \
+        downstream worker has direct merge rights upstream. It asks the source for its last merged change, finds \
+        it in the local history and sends every merged change after it, with the refs of the objects they touch \
+        and the object versions they record, exactly as index/calc_delta packages changes for a pull. The source \
+        only applies them as a fast-forward of its own history: if it has merged changes we don't have, the push \
+        is rejected with a conflict and index/update must be run first. Pushes go to origin unless another remote \
+        is named, using that remote's credentials, which need ApproveChanges permission there."
+    }
+
+    fn parameters(&self) -> Vec<OperationParameter> {
+        vec![OperationParameter {
+            name: "remote".to_string(),
+            description: "Name of the remote to push to (defaults to origin)".to_string(),
+            required: false,
+        }]
+    }
+
+    fn examples(&self) -> Vec<OperationExample> {
+        vec![
+            OperationExample {
+                description: "Push locally merged changes to origin".to_string(),
+                moocode: r#"// Publish changes approved on this worker to the upstream repository
+result = worker_request("vcs", {"index/push"});
+player:tell("Pushed ", length(result["change_ids"]), " changes to ", result["source"]);"#
+                    .to_string(),
+                http_curl: Some(r#"curl -X POST http://localhost:8081/api/index/push"#.to_string()),
+            },
+            OperationExample {
+                description: "Push to a named remote".to_string(),
+                moocode: r#"result = worker_request("vcs", {"index/push", "production"});"#
+                    .to_string(),
+                http_curl: Some(
+                    r#"curl -X POST "http://localhost:8081/api/index/push?remote=production""#
+                        .to_string(),
+                ),
+            },
+        ]
+    }
+
+    fn responses(&self) -> Vec<crate::operations::OperationResponse> {
+        use crate::operations::OperationResponse;
+        vec![
+            OperationResponse::success(
+                "Operation executed successfully - Returns the pushed change IDs",
+                r#"["source" -> "http://source-server:8081", "base_change_id" -> "abc123def", "change_ids" -> {"def456ghi", "ghi789jkl"}]"#,
+            ),
+            OperationResponse::success(
+                "Operation executed successfully - Source is up to date",
+                r#"["source" -> "http://source-server:8081", "base_change_id" -> "def456ghi", "change_ids" -> {}]"#,
+            ),
+            OperationResponse::forbidden(
+                "Forbidden - Missing permission here or on the source",
+                r#"E_PERM("Remote 'origin' rejected index/receive: User 'player' does not have permission to merge pushed changes")"#,
+            ),
+            OperationResponse::not_found(
+                "Not Found - The named remote does not exist",
+                r#"E_INVIND("Remote 'production' not found")"#,
+            ),
+            OperationResponse::conflict(
+                "Conflict - No source URL configured",
+                r#"E_NACC("No source URL configured. This repository was not cloned from a remote source.")"#,
+            ),
+            OperationResponse::conflict(
+                "Conflict - The source has changes we don't have",
+                r#"E_NACC("Push rejected: the source has merged change 'xyz789', which is not in the local history. Run index/update and push again")"#,
+            ),
+            OperationResponse::internal_error(
+                "Internal Server Error - The source could not be reached",
+                r#"E_INVARG("Failed to reach remote 'origin': error sending request for url (http://source-server:8081/rpc)")"#,
+            ),
+        ]
+    }
+
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
+        info!("Index push operation received for user: {}", user.id);
+
+        require_permission(user, Permission::SubmitChanges, "push changes")?;
+
+        let request = IndexPushRequest {
+            remote: args.first().filter(|name| !name.is_empty()).cloned(),
+        };
+        match self.process_push(request) {
+            Ok(result_var) => {
+                info!("Index push operation completed successfully");
+                Ok(result_var)
+            }
+            Err(e) => {
+                error!("Index push operation failed: {}", e);
+                Err(e)
+            }
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
+use axum::http::Method;
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use super::index_update_op::{IndexUpdateOperation, update_response};
+use crate::database::DatabaseRef;
+use crate::object_diff::ObjectDiffModel;
+use crate::object_merge::load_merged_changes;
+use crate::types::{ChangeStatus, IndexDelta, Permission, User};
+
+/// Request structure for index receive operations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexReceiveRequest {
+    /// The last merged change the pusher shares with us; None if it expects an empty index
+    pub base_change_id: Option<String>,
+    pub delta: IndexDelta,
+}
+
+/// Index receive operation that fast-forwards the index with merged changes pushed by a
+/// downstream worker (the receiving end of index/push)
+///
+/// Usage:
+/// - `index/receive "{base_change_id}" "{delta_json}"`
+/// - Requires ApproveChanges permission, since the changes are merged without review
+/// - The delta is an `IndexDelta` as built by index/calc_delta, holding the changes after
+///   `base_change_id`; an empty base means the pusher expects an index with no merged changes
+/// - Rejected with a conflict if our last merged change is not the base, meaning the
+///   histories have diverged
+/// - Returns the object diff of the received changes, as index/update does
+#[derive(Clone)]
+pub struct IndexReceiveOperation {
+    database: DatabaseRef,
+}
+
+impl IndexReceiveOperation {
+    /// Create a new index receive operation
+    pub fn new(database: DatabaseRef) -> Self {
+        Self { database }
+    }
+
+    /// Process the index receive request
+    fn process_receive(
+        &self,
+        request: IndexReceiveRequest,
+        user: &User,
+    ) -> Result<moor_var::Var, OperationError> {
+        require_permission(user, Permission::ApproveChanges, "merge pushed changes")?;
+
+        if let Some(change) = request
+            .delta
+            .changes
+            .iter()
+            .find(|c| c.status != ChangeStatus::Merged)
+        {
+            return Err(OperationError::InvalidArgs(format!(
+                "Only merged changes can be pushed - '{}' is {:?}",
+                change.name, change.status
+            )));
+        }
+
+        // Fast-forward only: the pushed changes must follow our last merged change
+        let tip = load_merged_changes(&self.database)?
+            .pop()
+            .map(|change| change.id);
+        if tip != request.base_change_id {
+            error!(
+                "Rejecting push based on {:?} - last merged change is {:?}",
+                request.base_change_id, tip
+            );
+            return Err(OperationError::Conflict(format!(
+                "Push rejected: histories have diverged - the last merged change here is '{}', not '{}'. Run index/update and push again",
+                tip.as_deref().unwrap_or("none"),
+                request.base_change_id.as_deref().unwrap_or("none")
+            )));
+        }
+
+        if request.delta.changes.is_empty() {
+            info!("Push carries no changes - index is up to date");
+            return Ok(update_response(&ObjectDiffModel::new(), &[]));
+        }
+
+        info!(
+            "User '{}' pushed {} changes on top of {:?}",
+            user.id,
+            request.delta.changes.len(),
+            tip
+        );
+        let (diff, preserved) =
+            IndexUpdateOperation::new(self.database.clone()).apply_delta(request.delta)?;
+        Ok(update_response(&diff, &preserved))
+    }
+}
+
+impl Operation for IndexReceiveOperation {
+    fn name(&self) -> &'static str {
+        "index/receive"
+    }
+
+    fn response_content_type(&self) -> &'static str {
+        "text/x-moo"
+    }
+
+    fn description(&self) -> &'static str {
+        "Fast-forwards the index with merged changes pushed from a downstream worker, rejecting the push if the histories have diverged"
+    }
+
+    fn routes(&self) -> Vec<OperationRoute> {
+        vec![OperationRoute {
+            path: "/api/index/receive".to_string(),
+            method: Method::POST,
+            is_json: true,
+        }]
+    }
+
+    fn philosophy(&self) -> &'static str {
+        "The receiving end of index/push. A downstream worker that cloned this repository sends the merged changes \
+        it has and we lack, together with their refs and object versions, naming the last merged change it shares \
+        with us. The changes are only applied if that is still our last merged change, so a push can never rewrite \
+        or interleave with history merged here in the meantime - it is a fast-forward or nothing. A diverged push \
+        is rejected with a conflict, and the pusher must run index/update first. Because pushed changes are merged \
+        without review, the caller needs ApproveChanges permission. Unmerged local work here is rebased onto the \
+        received changes exactly as index/update does."
+    }
+
+    fn parameters(&self) -> Vec<OperationParameter> {
+        vec![
+            OperationParameter {
+                name: "base_change_id".to_string(),
+                description: "The last merged change the pusher shares with this index, or empty if it expects no merged changes"
+                    .to_string(),
+                required: true,
+            },
+            OperationParameter {
+                name: "delta".to_string(),
+                description: "JSON-encoded IndexDelta with the pushed changes, their refs and object contents"
+                    .to_string(),
+                required: true,
+            },
+        ]
+    }
+
+    fn examples(&self) -> Vec<OperationExample> {
+        vec![OperationExample {
+            description: "Receive changes pushed on top of a known change".to_string(),
+            moocode: r#"// Normally called by index/push on a downstream worker
+diff = worker_request("vcs", {"index/receive", "abc123def", delta_json});"#
+                .to_string(),
+            http_curl: Some(
+                r#"curl -X POST http://localhost:8081/api/index/receive \
+  -H "X-API-Key: your-api-key" \
+  -H "Content-Type: application/json" \
+  -d '{"operation": "index/receive", "args": ["abc123def", "{\"changes\":[...],\"refs\":[...],\"objects\":{...}}"]}'"#
+                    .to_string(),
+            ),
+        }]
+    }
+
+    fn responses(&self) -> Vec<crate::operations::OperationResponse> {
+        use crate::operations::OperationResponse;
+        vec![
+            OperationResponse::success(
+                "Operation executed successfully - Returns the object diff of the received changes",
+                r#"["objects_renamed" -> [], "objects_deleted" -> {}, "objects_added" -> {"$new_object"}, "objects_modified" -> {}, "changes" -> {}, "local_changes" -> {}]"#,
+            ),
+            OperationResponse::bad_request(
+                "Bad Request - Missing or malformed delta",
+                r#"E_INVARG("Invalid delta: expected value at line 1 column 1")"#,
+            ),
+            OperationResponse::forbidden(
+                "Forbidden - User lacks ApproveChanges permission",
+                r#"E_PERM("User 'player' does not have permission to merge pushed changes")"#,
+            ),
+            OperationResponse::conflict(
+                "Conflict - The histories have diverged",
+                r#"E_NACC("Push rejected: histories have diverged - the last merged change here is 'def456', not 'abc123def'. Run index/update and push again")"#,
+            ),
+            OperationResponse::internal_error(
+                "Internal Server Error - Database or system error",
+                r#"E_INVARG("Database error: failed to get change order")"#,
+            ),
+        ]
+    }
+
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
+        info!("Index receive operation received {} arguments", args.len());
+
+        if args.len() < 2 {
+            error!("Index receive operation requires base_change_id and delta arguments");
+            return Err(OperationError::InvalidArgs(
+                "base_change_id and delta arguments are required".to_string(),
+            ));
+        }
+
+        let base_change_id = Some(args[0].clone()).filter(|id| !id.is_empty());
+        let delta: IndexDelta = serde_json::from_str(&args[1])
+            .map_err(|e| OperationError::InvalidArgs(format!("Invalid delta: {e}")))?;
+        let request = IndexReceiveRequest {
+            base_change_id,
+            delta,
+        };
+
+        match self.process_receive(request, user) {
+            Ok(result_var) => {
+                info!("Index receive operation completed successfully");
+                Ok(result_var)
+            }
+            Err(e) => {
+                error!("Index receive operation failed: {}", e);
+                Err(e)
+            }
+        }
+    }
+}
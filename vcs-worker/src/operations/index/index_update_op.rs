@@ -1,27 +1,40 @@
-use crate::operations::{Operation, OperationRoute, OperationParameter, OperationExample};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use axum::http::Method;
+use std::collections::{HashMap, HashSet};
 use tracing::{error, info, warn};
 use serde::{Deserialize, Serialize};
 
 use crate::database::{DatabaseRef, ObjectsTreeError};
-use crate::types::User;
+use crate::local_work::{LocalWork, PreservedChange};
+use crate::types::{ChangeStatus, IndexDelta, Permission, Remote, User, VcsObjectType};
 use crate::providers::index::IndexProvider;
+use crate::providers::objects::ObjectsProvider;
+use crate::providers::refs::RefsProvider;
+use crate::util::ContentHash;
 use crate::object_diff::{ObjectDiffModel, build_object_diff_from_change};
-use moor_var::{v_error, E_INVARG};
+use crate::operations::remote::resolve_remote;
+use moor_var::{Var, v_list, v_map, v_str};
 
 /// Request structure for index update operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexUpdateRequest {
-    // No parameters needed - uses source URL from index
+    /// Remote to update from; the default remote if None
+    pub remote: Option<String>,
 }
 
 /// Index update operation that fetches deltas from the source URL and applies them to the local index
 /// 
 /// Usage:
-/// - `index/update`
-/// - Requires a source URL to be set in the index
-/// - Calculates delta from the last known change and applies it to index, refs, and objects
-/// - Returns an object diff containing all changes that were applied
+/// - `index/update` or `index/update "{remote}"`
+/// - Requires the remote (origin if none is named) to be configured
+/// - Calculates delta from the last merged change and applies it to index, refs, and objects
+///   in one write batch, rebasing unmerged local changes onto the new ones
+/// - Falls back to a full clone if the index has no merged changes or the remote sends no delta
+/// - Returns an object diff containing all changes that were applied, and what became of
+///   each unmerged local change under `local_changes`
 /// 
 /// Example: `index/update` updates the local repository with changes from the remote source and returns the diff
 #[derive(Clone)]
@@ -37,81 +50,63 @@ impl IndexUpdateOperation {
     
     /// Public async method for testing and direct async use
     #[allow(dead_code)]
-    pub async fn update_async(&self) -> Result<moor_var::Var, ObjectsTreeError> {
-        let request = IndexUpdateRequest {};
+    pub async fn update_async(&self) -> Result<moor_var::Var, OperationError> {
+        let request = IndexUpdateRequest { remote: None };
         self.process_update_async(request).await
     }
 
     /// Process the index update request (async version)
-    async fn process_update_async(&self, _request: IndexUpdateRequest) -> Result<moor_var::Var, ObjectsTreeError> {
+    async fn process_update_async(&self, request: IndexUpdateRequest) -> Result<moor_var::Var, OperationError> {
         info!("Processing index update request");
         
-        // Check if there's a source URL in the index
-        let source_url = match self.database.index().get_source()
-            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))? {
-            Some(url) => {
-                info!("Found source URL: {}", url);
-                url
-            }
-            None => {
-                error!("No source URL found in index - nothing to update");
-                return Err(ObjectsTreeError::SerializationError("No source URL configured. This repository was not cloned from a remote source.".to_string()));
-            }
-        };
+        // Find the remote to update from
+        let remote = resolve_remote(&self.database, request.remote.as_deref())
+            .inspect_err(|_| error!("Remote {:?} not configured - nothing to update", request.remote))?;
+        info!("Updating from remote '{}' at {}", remote.name, remote.url);
         
-        // Get the current change order to find the last known change
+        // The remote only knows merged changes, so the delta starts after the last one
         let change_order = self.database.index().get_change_order()
             .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
-        
-        if change_order.is_empty() {
-            info!("No changes in index - performing full clone");
-            return self.perform_full_clone_async(&source_url).await;
+        let mut last_merged = None;
+        for change_id in change_order.iter().rev() {
+            let change = self.database.index().get_change(change_id)
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+            if change.is_some_and(|change| change.status == ChangeStatus::Merged) {
+                last_merged = Some(change_id.clone());
+                break;
+            }
         }
+
+        let Some(last_change_id) = last_merged else {
+            info!("No merged changes in index - performing full clone");
+            return Ok(self.perform_full_clone_async(&remote).await?);
+        };
+        info!("Last merged change ID: {}", last_change_id);
         
-        // Get the most recent change ID (last in the list since ordering is oldest first, newest last)
-        let last_change_id = change_order.last().unwrap(); // Safe because we checked is_empty above
-        info!("Last known change ID: {}", last_change_id);
-        
-        // Calculate delta using the index_calc_delta operation
-        let delta_result = self.calculate_delta_from_remote_async(&source_url, last_change_id).await?;
-        
-        // Check if there are new changes
-        let delta_str = delta_result.as_string()
-            .ok_or_else(|| ObjectsTreeError::SerializationError("Expected string delta result".to_string()))?;
-        let delta_data: serde_json::Value = serde_json::from_str(delta_str)
-            .map_err(|e| ObjectsTreeError::SerializationError(format!("Failed to parse delta: {e}")))?;
-        
-        let change_ids = delta_data.get("change_ids")
-            .and_then(|v| v.as_array())
-            .map(|arr| arr.len())
-            .unwrap_or(0);
+        // Fetch the changes after it, with their refs and objects
+        let Some(delta) = self.fetch_delta_async(&remote, &last_change_id).await? else {
+            warn!("Remote did not send an applicable delta - performing full clone");
+            return Ok(self.perform_full_clone_async(&remote).await?);
+        };
         
-        if change_ids > 0 {
-            info!("Delta contains {} new changes - performing full clone for consistency", change_ids);
-            // Since incremental update isn't implemented yet, do a full clone
-            self.perform_full_clone_async(&source_url).await
-        } else {
+        if delta.changes.is_empty() {
             info!("No new changes in delta - index is up to date");
-            Ok(moor_var::v_str("Index is up to date"))
+            return Ok(update_response(&ObjectDiffModel::new(), &[]));
         }
+
+        info!("Delta contains {} new changes - applying them to the local index", delta.changes.len());
+        let (diff, preserved) = self.database.write_batch(|| self.apply_delta(delta))?;
+        Ok(update_response(&diff, &preserved))
     }
     
     /// Process the index update request (sync wrapper)
-    fn process_update(&self, request: IndexUpdateRequest) -> Result<moor_var::Var, ObjectsTreeError> {
-        let (tx, rx) = std::sync::mpsc::sync_channel(1);
-        let self_clone = self.clone();
-        
-        tokio::spawn(async move {
-            let result = self_clone.process_update_async(request).await;
-            let _ = tx.send(result);
-        });
-        
-        rx.recv()
-            .map_err(|_| ObjectsTreeError::SerializationError("Channel closed during update".to_string()))?
+    fn process_update(&self, request: IndexUpdateRequest) -> Result<moor_var::Var, OperationError> {
+        crate::util::block_on(self.process_update_async(request))
     }
     
     /// Perform a full clone if no changes exist (async version)
-    async fn perform_full_clone_async(&self, source_url: &str) -> Result<moor_var::Var, ObjectsTreeError> {
+    async fn perform_full_clone_async(&self, remote: &Remote) -> Result<moor_var::Var, ObjectsTreeError> {
+        let source_url = &remote.url;
         info!("Performing full clone from source URL: {}", source_url);
         
         // Use the existing clone operation logic
@@ -126,8 +121,11 @@ impl IndexUpdateOperation {
         
         info!("Cloning from: {}", clone_url);
         
+        // Reuse the stored external user credentials so the remote knows who we are
+        let api_key = remote.api_key.as_deref();
+
         // Import from URL (this will clear existing state and import everything)
-        match clone_op.import_from_url_async(&clone_url, None).await {
+        match clone_op.import_from_url_async(&clone_url, api_key, &remote.name).await {
             Ok(result) => {
                 info!("Full clone completed successfully");
                 Ok(moor_var::v_str(&result))
@@ -139,8 +137,10 @@ impl IndexUpdateOperation {
         }
     }
     
-    /// Calculate delta from remote source (async version)
-    async fn calculate_delta_from_remote_async(&self, source_url: &str, last_change_id: &str) -> Result<moor_var::Var, ObjectsTreeError> {
+    /// Fetch the delta after a change from the remote source (async version).
+    /// Returns None if the remote's answer carries no encoded delta (it predates them).
+    async fn fetch_delta_async(&self, remote: &Remote, last_change_id: &str) -> Result<Option<IndexDelta>, ObjectsTreeError> {
+        let source_url = &remote.url;
         info!("Calculating delta from remote source: {} since change: {}", source_url, last_change_id);
         
         // Construct the RPC URL
@@ -159,8 +159,12 @@ impl IndexUpdateOperation {
             "args": [last_change_id]
         });
         
-        let response = client.post(&rpc_url)
-            .json(&request_body)
+        let mut request = client.post(&rpc_url).json(&request_body);
+        if let Some(api_key) = &remote.api_key {
+            request = request.header("X-API-Key", api_key);
+        }
+
+        let response = request
             .send()
             .await
             .map_err(|e| ObjectsTreeError::SerializationError(format!("HTTP request failed: {e}")))?;
@@ -179,75 +183,123 @@ impl IndexUpdateOperation {
         let result = response_json.get("result")
             .ok_or_else(|| ObjectsTreeError::SerializationError("No result in RPC response".to_string()))?;
         
-        // Convert result to JSON string for compatibility
-        let result_str = serde_json::to_string(result)
-            .map_err(|e| ObjectsTreeError::SerializationError(format!("Failed to serialize result: {e}")))?;
+        let Some(delta_json) = result.get("delta").and_then(|v| v.as_str()) else {
+            return Ok(None);
+        };
+        let delta: IndexDelta = serde_json::from_str(delta_json)
+            .map_err(|e| ObjectsTreeError::SerializationError(format!("Failed to parse delta: {e}")))?;
         
-        info!("Successfully fetched delta from remote");
-        Ok(moor_var::v_str(&result_str))
+        info!("Fetched delta with {} changes, {} refs and {} objects from remote",
+              delta.changes.len(), delta.refs.len(), delta.objects.len());
+        Ok(Some(delta))
     }
     
-    /// Apply delta to local index, refs, and objects
-    #[allow(dead_code)]
-    fn apply_delta(&self, delta_result: moor_var::Var) -> Result<ObjectDiffModel, ObjectsTreeError> {
+    /// Apply a delta on top of the local index: store its objects, replace the refs of the
+    /// objects it touches and insert its changes after the last merged change. Unmerged
+    /// local work is set aside first and rebased onto the new changes afterwards. Run inside
+    /// a write batch so the update lands all-or-nothing.
+    pub fn apply_delta(&self, delta: IndexDelta) -> Result<(ObjectDiffModel, Vec<PreservedChange>), ObjectsTreeError> {
         info!("Applying delta to local index, refs, and objects");
         
-        // Parse the delta result - convert to string
-        let delta_str = match delta_result.as_string() {
-            Some(s) => s.to_string(),
-            None => return Err(ObjectsTreeError::SerializationError("Expected string delta result".to_string())),
-        };
+        let index = self.database.index();
+        let refs = self.database.refs();
+        let objects = self.database.objects();
         
-        let _delta_data: serde_json::Value = serde_json::from_str(&delta_str)
-            .map_err(|e| ObjectsTreeError::SerializationError(format!("Failed to parse delta: {e}")))?;
+        let local_work = LocalWork::capture(&self.database)?;
         
-        // Extract change IDs, ref pairs, and objects from delta
-        let empty_vec = vec![];
-        let change_ids = _delta_data.get("change_ids")
-            .and_then(|v| v.as_array())
-            .unwrap_or(&empty_vec);
-        
-        let _ref_pairs = _delta_data.get("ref_pairs")
-            .and_then(|v| v.as_array())
-            .unwrap_or(&empty_vec);
+        // Find what is already merged
+        let mut order = index.get_change_order()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        let mut merged_ids = HashSet::new();
+        for change_id in &order {
+            let change = index.get_change(change_id)
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+            if change.is_some_and(|change| change.status == ChangeStatus::Merged) {
+                merged_ids.insert(change_id.clone());
+            }
+        }
         
-        let _objects_added = _delta_data.get("objects_added")
-            .and_then(|v| v.as_array())
-            .unwrap_or(&empty_vec);
+        // Key incoming objects by our own content hash, refusing any that were corrupted
+        let mut rekeyed = HashMap::new();
+        for (remote_key, content) in &delta.objects {
+            if ContentHash::identify(remote_key, content.as_bytes()).is_none() {
+                return Err(ObjectsTreeError::SerializationError(format!(
+                    "Delta object '{remote_key}' does not match its content hash - refusing corrupted delta"
+                )));
+            }
+            let local_key = objects.generate_hash(content);
+            objects.store(&local_key, content)
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+            rekeyed.insert(remote_key.clone(), local_key);
+        }
         
-        info!("Delta contains {} change IDs, {} ref pairs, {} objects", 
-              change_ids.len(), _ref_pairs.len(), _objects_added.len());
+        // Every incoming ref must point at a blob we now have
+        let mut incoming: HashMap<(VcsObjectType, String), Vec<(u64, String)>> = HashMap::new();
+        for (info, remote_hash) in &delta.refs {
+            let hash = match rekeyed.get(remote_hash) {
+                Some(local_key) => local_key.clone(),
+                None => {
+                    let stored = objects.get_object_size(remote_hash)
+                        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+                    if stored.is_none() {
+                        return Err(ObjectsTreeError::SerializationError(format!(
+                            "Delta ref for '{}' version {} points at object '{}', which was not sent and is not stored locally - clone again to recover",
+                            info.name, info.version, remote_hash
+                        )));
+                    }
+                    remote_hash.clone()
+                }
+            };
+            incoming.entry((info.object_type, info.name.clone())).or_default().push((info.version, hash));
+        }
         
-        // Collect commit IDs for object diff
-        let mut commit_ids = Vec::new();
-        for change_id in change_ids {
-            if let Some(id_str) = change_id.as_str() {
-                commit_ids.push(id_str.to_string());
+        // Replace the refs of each touched object with the remote's, so renames and
+        // deletions carry over. Versions local work still uses are restored below.
+        let mut touched = delta.touched_objects();
+        touched.extend(incoming.keys().cloned());
+        for (object_type, name) in touched {
+            let keep = incoming.remove(&(object_type, name.clone())).unwrap_or_default();
+            let existing = refs.get_object_refs(object_type, &name)
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+            for (version, _) in existing {
+                if !keep.iter().any(|(v, _)| *v == version) {
+                    refs.delete_ref(object_type, &name, version)
+                        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+                }
+            }
+            for (version, hash) in keep {
+                refs.update_ref(object_type, &name, version, &hash)
+                    .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
             }
         }
         
-        // For now, we'll implement a basic version that fetches the full state
-        // In a more sophisticated implementation, we would:
-        // 1. Fetch only the new changes from the remote
-        // 2. Apply ref updates
-        // 3. Fetch and store new objects
-        // 4. Update the change order
-        
-        if !change_ids.is_empty() {
-            info!("Delta contains {} new changes - building diff", change_ids.len());
-            // TODO: Implement incremental update logic
-            // For now, the full clone happens at the higher level in process_update_async
-            
-            // Build object diff from the commit IDs
-            self.build_object_diff_from_commit_ids(&commit_ids)
-        } else {
-            info!("No new changes in delta - index is up to date");
-            Ok(ObjectDiffModel::new())
+        // Store the new changes and slot them in after the last merged change
+        let mut new_ids = Vec::new();
+        for change in delta.changes.iter().filter(|c| !merged_ids.contains(&c.id)) {
+            index.store_change(change)
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+            new_ids.push(change.id.clone());
         }
+        // A change we submitted comes back merged; move it out of the unmerged tail
+        order.retain(|id| !new_ids.contains(id));
+        let insert_at = order.iter()
+            .rposition(|id| merged_ids.contains(id))
+            .map_or(0, |position| position + 1);
+        let unmerged_tail = order.split_off(insert_at);
+        order.extend(new_ids.iter().cloned());
+        order.extend(unmerged_tail);
+        index.set_change_order(order)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        
+        info!("Applied {} new changes, {} objects and {} refs from delta",
+              new_ids.len(), delta.objects.len(), delta.refs.len());
+        
+        let diff = self.build_object_diff_from_commit_ids(&new_ids)?;
+        let preserved = local_work.restore(&self.database)?;
+        Ok((diff, preserved))
     }
     
     /// Build object diff from commit IDs
-    #[allow(dead_code)]
     fn build_object_diff_from_commit_ids(&self, commit_ids: &[String]) -> Result<ObjectDiffModel, ObjectsTreeError> {
         info!("Building object diff from {} commit IDs", commit_ids.len());
         
@@ -280,6 +332,14 @@ impl IndexUpdateOperation {
     }
 }
 
+/// Build the update response: the object diff, plus what became of each local change
+pub fn update_response(diff: &ObjectDiffModel, preserved: &[PreservedChange]) -> Var {
+    let mut pairs = diff.to_moo_pairs();
+    let local_changes: Vec<Var> = preserved.iter().map(|p| p.to_moo_var()).collect();
+    pairs.push((v_str("local_changes"), v_list(&local_changes)));
+    v_map(&pairs)
+}
+
 impl Operation for IndexUpdateOperation {
     fn name(&self) -> &'static str {
         "index/update"
@@ -304,14 +364,25 @@ impl Operation for IndexUpdateOperation {
     }
     
     fn philosophy(&self) -> &'static str {
-        "Updates the local index by fetching and applying changes from the configured remote source URL. This operation \
+        "Updates the local index by fetching and applying changes from a remote - origin unless another remote is named. This operation \
         calculates the delta between the local repository and the remote, then synchronizes new changes, refs, and objects. \
-        If the repository was not cloned from a remote source, this operation will fail. This is the primary mechanism for \
-        keeping a local repository synchronized with upstream changes, similar to 'git pull' in Git."
+        If the repository was not cloned from a remote source and no remote is named, this operation will fail. This is the primary mechanism for \
+        keeping a local repository synchronized with upstream changes, similar to 'git pull' in Git. Only the new changes, \
+        the refs of the objects they touch and the object versions they record are downloaded, and they are applied in one \
+        batch on top of the local index. Unmerged local and workspace changes are never lost: they are rebased onto the new \
+        changes, and any that conflict are kept untouched and reported under local_changes. The merged object diff of \
+        the new changes is returned so the MOO side can patch the live database. A full clone is only performed when the \
+        index has no merged changes to build on."
     }
     
     fn parameters(&self) -> Vec<OperationParameter> {
-        vec![]
+        vec![
+            OperationParameter {
+                name: "remote".to_string(),
+                description: "Name of the remote to update from (defaults to origin)".to_string(),
+                required: false,
+            }
+        ]
     }
     
     fn examples(&self) -> Vec<OperationExample> {
@@ -319,36 +390,51 @@ impl Operation for IndexUpdateOperation {
             OperationExample {
                 description: "Update local repository from remote source".to_string(),
                 moocode: r#"// Fetch and apply changes from the remote repository
-result = worker_request("vcs", {"index/update"});
-// If up to date, returns: "Index is up to date"
-// If changes fetched, returns: "Cloned successfully from http://example.com:8081 - 5 changes, 42 objects"
-player:tell("Update result: ", result);"#
+diff = worker_request("vcs", {"index/update"});
+// Returns the object diff of the new changes (empty lists if already up to date)
+player:tell("Added: ", length(diff["objects_added"]), ", modified: ", length(diff["objects_modified"]));"#
                     .to_string(),
                 http_curl: Some(r#"curl -X POST http://localhost:8081/api/index/update"#.to_string()),
             },
+            OperationExample {
+                description: "Update from a named remote using its credentials".to_string(),
+                moocode: r#"diff = worker_request("vcs", {"index/update", "staging"});"#.to_string(),
+                http_curl: Some(r#"curl -X POST "http://localhost:8081/api/index/update?remote=staging""#.to_string()),
+            },
         ]
     }
 
     fn responses(&self) -> Vec<crate::operations::OperationResponse> {
         use crate::operations::OperationResponse;
         vec![
+            OperationResponse::success(
+                "Operation executed successfully - Returns the object diff of the applied changes",
+                r#"["objects_renamed" -> [], "objects_deleted" -> {}, "objects_added" -> {"$new_object"}, "objects_modified" -> {"$existing_object"}, "changes" -> {["obj_id" -> "$existing_object", "verbs_modified" -> {"look"}, "verbs_added" -> {}, "verbs_renamed" -> [], "verbs_deleted" -> {}, "props_modified" -> {}, "props_added" -> {}, "props_renamed" -> [], "props_deleted" -> {}]}, "local_changes" -> {["change_id" -> "abc12345", "name" -> "my-change", "status" -> "rebased", "conflicts" -> {}]}]"#
+            ),
             OperationResponse::success(
                 "Operation executed successfully - Index is up to date",
-                r#""Index is up to date""#
+                r#"["objects_renamed" -> [], "objects_deleted" -> {}, "objects_added" -> {}, "objects_modified" -> {}, "changes" -> {}, "local_changes" -> {}]"#
             ),
             OperationResponse::success(
-                "Operation executed successfully - Full clone completed",
+                "Operation executed successfully - Full clone completed (index had no merged changes)",
                 r#""Cloned successfully from http://example.com:8081 - 5 changes, 42 objects""#
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - No source URL configured",
-                r#"E_INVARG("No source URL configured. This repository was not cloned from a remote source.")"#
+            OperationResponse::forbidden(
+                "Forbidden - User lacks Clone permission",
+                r#"E_PERM("User 'player' does not have permission to update the index from a remote")"#,
+            ),
+            OperationResponse::conflict(
+                "Conflict - No source URL configured",
+                r#"E_NACC("No source URL configured. This repository was not cloned from a remote source.")"#
+            ),
+            OperationResponse::not_found(
+                "Not Found - The named remote does not exist",
+                r#"E_INVIND("Remote 'staging' not found")"#
             ),
             OperationResponse::new(
-                400,
-                "Bad Request - Failed to parse delta from remote",
-                r#"E_INVARG("Failed to parse delta: invalid JSON")"#
+                500,
+                "Internal Server Error - Failed to parse delta from remote",
+                r#"E_INVARG("Serialization error: Failed to parse delta: invalid JSON")"#
             ),
             OperationResponse::new(
                 500,
@@ -368,19 +454,22 @@ player:tell("Update result: ", result);"#
         ]
     }
 
-    fn execute(&self, _args: Vec<String>, _user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!("Index update operation received");
+        require_permission(user, Permission::Clone, "update the index from a remote")?;
         
-        let request = IndexUpdateRequest {};
+        let request = IndexUpdateRequest {
+            remote: args.first().filter(|name| !name.is_empty()).cloned(),
+        };
 
         match self.process_update(request) {
             Ok(result_var) => {
                 info!("Index update operation completed successfully");
-                result_var
+                Ok(result_var)
             }
             Err(e) => {
                 error!("Index update operation failed: {}", e);
-                v_error(E_INVARG.msg(format!("{e}")))
+                Err(e)
             }
         }
     }
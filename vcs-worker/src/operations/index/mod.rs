@@ -1,7 +1,11 @@
 mod index_calc_delta_op;
 mod index_list_op;
+mod index_push_op;
+mod index_receive_op;
 mod index_update_op;
 
 pub use index_calc_delta_op::IndexCalcDeltaOperation;
 pub use index_list_op::IndexListOperation;
+pub use index_push_op::IndexPushOperation;
+pub use index_receive_op::IndexReceiveOperation;
 pub use index_update_op::IndexUpdateOperation;
@@ -1,11 +1,14 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use axum::http::Method;
 use tracing::{error, info};
 
 use super::meta_utils;
 use crate::database::DatabaseRef;
 use crate::providers::index::IndexProvider;
-use crate::types::{MetaAddIgnoredVerbRequest, ObjectsTreeError, User};
+use crate::types::{MetaAddIgnoredVerbRequest, ObjectsTreeError, Permission, User};
 
 /// Meta operation that adds an ignored verb to an object's meta
 #[derive(Clone)]
@@ -138,41 +141,39 @@ result = worker_request("vcs", {"meta/add_ignored_verb", "obj123", "debug_state"
                 "Verb was already in ignored list",
                 r#""Verb 'verb_name' was already ignored for object 'object_name'""#,
             ),
-            OperationResponse::new(
-                400,
+            OperationResponse::bad_request(
                 "Bad Request - Missing required arguments",
-                r#"E_INVARG("Error: Object name and verb name are required")"#,
+                r#"E_INVARG("Object name and verb name are required")"#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - Object validation failed",
-                r#"E_INVARG("Error: <object validation error>")"#,
+            OperationResponse::forbidden(
+                "Forbidden - User lacks SubmitChanges permission",
+                r#"E_PERM("User 'player' does not have permission to edit object meta")"#,
             ),
-            OperationResponse::new(
-                500,
-                "Internal Server Error - Serialization error",
-                r#"E_INVARG("Error: <serialization error>")"#,
+            OperationResponse::not_found(
+                "Not Found - Object does not exist",
+                r#"E_INVIND("Object 'my_object' not found")"#,
             ),
-            OperationResponse::new(
-                500,
-                "Internal Server Error - Meta operation failed",
-                r#"E_INVARG("Error: <meta load/save error>")"#,
+            OperationResponse::internal_error(
+                "Internal Server Error - Database or serialization error",
+                r#"E_INVARG("Serialization error: failed to save meta")"#,
             ),
         ]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!(
             "Meta add ignored verb operation received {} arguments: {:?}",
             args.len(),
             args
         );
 
+        require_permission(user, Permission::SubmitChanges, "edit object meta")?;
+
         if args.len() < 2 {
             error!("Meta add ignored verb operation requires object name and verb name");
-            return moor_var::v_error(
-                moor_var::E_INVARG.msg("Error: Object name and verb name are required"),
-            );
+            return Err(OperationError::InvalidArgs(
+                "Object name and verb name are required".to_string(),
+            ));
         }
 
         let object_name = args[0].clone();
@@ -186,11 +187,11 @@ result = worker_request("vcs", {"meta/add_ignored_verb", "obj123", "debug_state"
         match self.process_meta_add_ignored_verb(request, Some(user.id.clone())) {
             Ok(result) => {
                 info!("Meta add ignored verb operation completed successfully");
-                moor_var::v_str(&result)
+                Ok(moor_var::v_str(&result))
             }
             Err(e) => {
                 error!("Meta add ignored verb operation failed: {}", e);
-                moor_var::v_error(moor_var::E_INVARG.msg(format!("Error: {e}")))
+                Err(e.into())
             }
         }
     }
@@ -1,11 +1,14 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use axum::http::Method;
 use tracing::{error, info};
 
 use super::meta_utils;
 use crate::database::DatabaseRef;
 use crate::providers::index::IndexProvider;
-use crate::types::{MetaClearIgnoredVerbsRequest, ObjectsTreeError, User};
+use crate::types::{MetaClearIgnoredVerbsRequest, ObjectsTreeError, Permission, User};
 
 /// Meta operation that clears all ignored verbs from an object's meta
 #[derive(Clone)]
@@ -136,34 +139,39 @@ result = worker_request("vcs", {"meta/clear_ignored_verbs", "obj123"});
                 "Operation executed successfully",
                 r#""Operation completed successfully""#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - Invalid arguments",
-                r#"E_INVARG("Error: Invalid operation arguments")"#,
+            OperationResponse::bad_request(
+                "Bad Request - Missing required arguments",
+                r#"E_INVARG("Object name is required")"#,
             ),
-            OperationResponse::new(
-                404,
-                "Not Found - Resource not found",
-                r#"E_INVARG("Error: Resource not found")"#,
+            OperationResponse::forbidden(
+                "Forbidden - User lacks SubmitChanges permission",
+                r#"E_PERM("User 'player' does not have permission to edit object meta")"#,
             ),
-            OperationResponse::new(
-                500,
-                "Internal Server Error - Database or system error",
-                r#"E_INVARG("Error: Database error: operation failed")"#,
+            OperationResponse::not_found(
+                "Not Found - Object does not exist",
+                r#"E_INVIND("Object 'my_object' not found")"#,
+            ),
+            OperationResponse::internal_error(
+                "Internal Server Error - Database or serialization error",
+                r#"E_INVARG("Serialization error: failed to save meta")"#,
             ),
         ]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!(
             "Meta clear ignored verbs operation received {} arguments: {:?}",
             args.len(),
             args
         );
 
+        require_permission(user, Permission::SubmitChanges, "edit object meta")?;
+
         if args.is_empty() {
             error!("Meta clear ignored verbs operation requires object name");
-            return moor_var::v_error(moor_var::E_INVARG.msg("Error: Object name is required"));
+            return Err(OperationError::InvalidArgs(
+                "Object name is required".to_string(),
+            ));
         }
 
         let object_name = args[0].clone();
@@ -173,11 +181,11 @@ result = worker_request("vcs", {"meta/clear_ignored_verbs", "obj123"});
         match self.process_meta_clear_ignored_verbs(request, Some(user.id.clone())) {
             Ok(result) => {
                 info!("Meta clear ignored verbs operation completed successfully");
-                moor_var::v_str(&result)
+                Ok(moor_var::v_str(&result))
             }
             Err(e) => {
                 error!("Meta clear ignored verbs operation failed: {}", e);
-                moor_var::v_error(moor_var::E_INVARG.msg(format!("Error: {e}")))
+                Err(e.into())
             }
         }
     }
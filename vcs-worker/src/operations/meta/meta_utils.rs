@@ -123,7 +123,7 @@ pub fn save_and_track_meta(
         .objects()
         .generate_meta_dump(meta)
         .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
-    let sha256_key = database.objects().generate_sha256_hash(&yaml_dump);
+    let sha256_key = database.objects().generate_hash(&yaml_dump);
 
     // Store the YAML content
     database
@@ -6,6 +6,7 @@ mod meta_remove_ignored_property_op;
 mod meta_remove_ignored_verb_op;
 mod meta_utils;
 
+pub(crate) use meta_utils::save_and_track_meta;
 pub use meta_add_ignored_property_op::MetaAddIgnoredPropertyOperation;
 pub use meta_add_ignored_verb_op::MetaAddIgnoredVerbOperation;
 pub use meta_clear_ignored_properties_op::MetaClearIgnoredPropertiesOperation;
@@ -1,21 +1,32 @@
+mod backup;
 mod change;
 mod clone_op;
+mod error;
 mod hello_op;
+mod import;
 mod index;
 mod meta;
 mod object;
 mod registry;
+mod remote;
 mod system;
 mod user;
 mod workspace;
 
+pub use backup::{BackupHistoryOperation, BackupRunOperation, BackupStatusOperation};
 pub use change::{
-    ChangeAbandonOperation, ChangeApproveOperation, ChangeCreateOperation, ChangeStashOperation,
+    ChangeAbandonOperation, ChangeApproveOperation, ChangeCreateOperation, ChangeDiffOperation,
+    ChangeExportOperation, ChangeImportOperation, ChangeRebaseOperation, ChangeStashOperation,
     ChangeStatusOperation, ChangeSubmitOperation, ChangeSwitchOperation,
 };
 pub use clone_op::CloneOperation;
+pub use error::{OperationError, require_permission};
 pub use hello_op::HelloOperation;
-pub use index::{IndexCalcDeltaOperation, IndexListOperation, IndexUpdateOperation};
+pub use import::ImportGitOperation;
+pub use index::{
+    IndexCalcDeltaOperation, IndexListOperation, IndexPushOperation, IndexReceiveOperation,
+    IndexUpdateOperation,
+};
 pub use meta::{
     MetaAddIgnoredPropertyOperation, MetaAddIgnoredVerbOperation,
     MetaClearIgnoredPropertiesOperation, MetaClearIgnoredVerbsOperation,
@@ -26,8 +37,9 @@ pub use object::{
     ObjectListOperation, ObjectRenameOperation, ObjectUpdateOperation, ObjectVerbRenameOperation, 
     ObjectPropertyRenameOperation, ObjectSwitchOperation,
 };
-pub use registry::OperationRegistry;
-pub use system::StatusOperation;
+pub use registry::{AuthenticationError, OperationRegistry};
+pub use remote::{RemoteAddOperation, RemoteListOperation, RemoteRemoveOperation};
+pub use system::{FsckOperation, GcOperation, StatusOperation};
 pub use user::{
     StatOperation, UserAddPermissionOperation, UserCreateOperation, UserDeleteApiKeyOperation,
     UserDeleteOperation, UserDisableOperation, UserEnableOperation, UserGenerateApiKeyOperation,
@@ -98,6 +110,18 @@ impl OperationResponse {
         Self::new(400, description, example)
     }
 
+    pub fn forbidden(description: impl Into<String>, example: impl Into<String>) -> Self {
+        Self::new(403, description, example)
+    }
+
+    pub fn not_found(description: impl Into<String>, example: impl Into<String>) -> Self {
+        Self::new(404, description, example)
+    }
+
+    pub fn conflict(description: impl Into<String>, example: impl Into<String>) -> Self {
+        Self::new(409, description, example)
+    }
+
     pub fn internal_error(description: impl Into<String>, example: impl Into<String>) -> Self {
         Self::new(500, description, example)
     }
@@ -122,8 +146,17 @@ pub trait Operation: Send + Sync {
     /// HTTP routing information for this operation
     fn routes(&self) -> Vec<OperationRoute>;
 
-    /// Execute the operation with the given arguments and user context, returning a moor Var
-    fn execute(&self, args: Vec<String>, user: &User) -> moor_var::Var;
+    /// Names of the path/query-string parameters accepted by GET routes, in argument order.
+    /// A route segment like `:object_name` fills the parameter of the same name from the path;
+    /// all others are read from the query string. Defaults to the names of `parameters()`.
+    fn query_parameters(&self) -> Vec<String> {
+        self.parameters().into_iter().map(|p| p.name).collect()
+    }
+
+    /// Execute the operation with the given arguments and user context, returning a moor Var.
+    /// Failures are reported as a typed `OperationError`, which the registry turns into an
+    /// HTTP status code or a MOO error value depending on the caller.
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError>;
 
     /// The response content type for this operation's HTTP responses
     /// Defaults to "application/json", but can be overridden to "text/x-moo" for MOO code responses
@@ -131,6 +164,13 @@ pub trait Operation: Send + Sync {
         "application/json"
     }
 
+    /// Whether the operation only reads. Read-only operations run outside any write batch, so
+    /// their scans stream straight from the database and they never wait for the commit lock.
+    /// Defaults to false; override it only if no argument makes the operation write.
+    fn read_only(&self) -> bool {
+        false
+    }
+
     /// All responses (success and errors) that this operation can return
     /// Operations should override this to provide complete response documentation
     fn responses(&self) -> Vec<OperationResponse> {
@@ -140,12 +180,24 @@ pub trait Operation: Send + Sync {
                 r#""Operation completed successfully""#,
             ),
             OperationResponse::bad_request(
-                "Bad Request - Invalid arguments or operation not allowed in current state",
-                r#"E_INVARG("Error: Invalid operation arguments")"#,
+                "Bad Request - Invalid arguments",
+                r#"E_INVARG("Invalid operation arguments")"#,
+            ),
+            OperationResponse::forbidden(
+                "Forbidden - User lacks the required permission",
+                r#"E_PERM("User 'player' does not have permission to edit objects")"#,
+            ),
+            OperationResponse::not_found(
+                "Not Found - The referenced object or change does not exist",
+                r#"E_INVIND("Object 'MyObject' not found")"#,
+            ),
+            OperationResponse::conflict(
+                "Conflict - Not allowed in the current repository state",
+                r#"E_NACC("Current change is not Local (status: Review)")"#,
             ),
             OperationResponse::internal_error(
                 "Internal Server Error - Database or system error",
-                r#""Error: Database error: operation failed""#,
+                r#"E_INVARG("Database error: operation failed")"#,
             ),
         ]
     }
@@ -169,6 +221,7 @@ pub fn create_registry_with_config(
 
     // Set the user provider in the registry
     registry.set_user_provider(database.users().clone());
+    registry.set_database(database.clone());
 
     // Ensure the Everyone user exists
     if let Err(e) = database.users().ensure_everyone_user() {
@@ -204,7 +257,13 @@ pub fn create_registry_with_config(
     registry.register(IndexListOperation::new(database.clone()));
     registry.register(IndexCalcDeltaOperation::new(database.clone()));
     registry.register(IndexUpdateOperation::new(database.clone()));
+    registry.register(IndexPushOperation::new(database.clone()));
+    registry.register(IndexReceiveOperation::new(database.clone()));
     registry.register(CloneOperation::new(database.clone()));
+    registry.register(RemoteAddOperation::new(database.clone()));
+    registry.register(RemoteListOperation::new(database.clone()));
+    registry.register(RemoteRemoveOperation::new(database.clone()));
+    registry.register(ImportGitOperation::new(database.clone(), config.clone()));
     registry.register(StatOperation);
     registry.register(UserCreateOperation::new(database.users().clone()));
     registry.register(UserDisableOperation::new(database.users().clone()));
@@ -218,6 +277,10 @@ pub fn create_registry_with_config(
     registry.register(WorkspaceSubmitOperation::new(database.clone()));
     registry.register(WorkspaceListOperation::new(database.clone()));
     registry.register(ChangeSwitchOperation::new(database.clone()));
+    registry.register(ChangeRebaseOperation::new(database.clone()));
+    registry.register(ChangeDiffOperation::new(database.clone()));
+    registry.register(ChangeExportOperation::new(database.clone()));
+    registry.register(ChangeImportOperation::new(database.clone()));
     registry.register(MetaAddIgnoredPropertyOperation::new(database.clone()));
     registry.register(MetaAddIgnoredVerbOperation::new(database.clone()));
     registry.register(MetaRemoveIgnoredPropertyOperation::new(database.clone()));
@@ -225,6 +288,11 @@ pub fn create_registry_with_config(
     registry.register(MetaClearIgnoredPropertiesOperation::new(database.clone()));
     registry.register(MetaClearIgnoredVerbsOperation::new(database.clone()));
     registry.register(StatusOperation::new(database.clone()));
+    registry.register(FsckOperation::new(database.clone()));
+    registry.register(GcOperation::new(database.clone()));
+    registry.register(BackupStatusOperation::new(database.clone(), config.clone()));
+    registry.register(BackupHistoryOperation::new(database.clone()));
+    registry.register(BackupRunOperation::new(database.clone(), config.clone()));
 
     Ok((registry, database))
 }
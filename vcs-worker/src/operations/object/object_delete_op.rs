@@ -1,4 +1,7 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use axum::http::Method;
 use tracing::{error, info};
 
@@ -6,8 +9,7 @@ use crate::database::DatabaseRef;
 use crate::providers::index::IndexProvider;
 use crate::providers::refs::RefsProvider;
 use crate::types::ObjectDeleteRequest;
-use crate::types::{ObjectsTreeError, User, VcsObjectType};
-use moor_var::{E_INVARG, v_error};
+use crate::types::{ObjectsTreeError, Permission, User, VcsObjectType};
 
 /// Object delete operation that marks an object for deletion within the current change
 #[derive(Clone)]
@@ -296,15 +298,17 @@ impl Operation for ObjectDeleteOperation {
                 "Operation executed successfully - Object deletion queued",
                 r#""Object '$player' deletion queued successfully in change 'local'""#,
             ),
-            OperationResponse::new(
-                400,
+            OperationResponse::bad_request(
                 "Bad Request - Missing required argument",
                 r#"E_INVARG("Object name is required")"#,
             ),
-            OperationResponse::new(
-                404,
+            OperationResponse::forbidden(
+                "Forbidden - User lacks SubmitChanges permission",
+                r#"E_PERM("User 'player' does not have permission to edit objects")"#,
+            ),
+            OperationResponse::not_found(
                 "Not Found - Object does not exist",
-                r#"E_INVARG("Object '$nonexistent' not found")"#,
+                r#"E_INVIND("Object '$nonexistent' not found")"#,
             ),
             OperationResponse::new(
                 500,
@@ -314,16 +318,20 @@ impl Operation for ObjectDeleteOperation {
         ]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!(
             "Object delete operation received {} arguments: {:?}",
             args.len(),
             args
         );
 
+        require_permission(user, Permission::SubmitChanges, "edit objects")?;
+
         if args.is_empty() {
             error!("Object delete operation requires object name");
-            return v_error(E_INVARG.msg("Object name is required"));
+            return Err(OperationError::InvalidArgs(
+                "Object name is required".to_string(),
+            ));
         }
 
         let object_name = args[0].clone();
@@ -333,11 +341,11 @@ impl Operation for ObjectDeleteOperation {
         match self.process_object_delete(request, Some(user.id.clone())) {
             Ok(result) => {
                 info!("Object delete operation completed successfully");
-                moor_var::v_str(&result)
+                Ok(moor_var::v_str(&result))
             }
             Err(e) => {
                 error!("Object delete operation failed: {}", e);
-                v_error(E_INVARG.msg(format!("{e}")))
+                Err(e.into())
             }
         }
     }
@@ -1,17 +1,17 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+};
 use axum::http::Method;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
 
 use crate::database::{DatabaseRef, ObjectsTreeError};
-use crate::object_diff::{compare_object_definitions_with_meta, ObjectChange};
+use crate::object_diff::{DiffLine, add_line_diffs, compute_line_diff, decompile_verb};
 use crate::providers::index::IndexProvider;
 use crate::providers::objects::ObjectsProvider;
 use crate::providers::refs::RefsProvider;
 use crate::types::{User, VcsObjectType};
-use moor_compiler::{program_to_tree, unparse, ObjectDefinition};
-use moor_var::{v_error, v_int, v_list, v_map, v_str, E_INVARG, Var};
-use moor_var::program::ProgramType;
+use moor_var::{v_int, v_list, v_map, v_str, Var};
 
 /// Request structure for object diff operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,15 +21,6 @@ pub struct ObjectDiffRequest {
     pub baseline_change_id: Option<String>,
 }
 
-/// Represents a single line in a diff
-#[derive(Debug, Clone, PartialEq)]
-enum DiffLine {
-    Added(String),
-    Removed(String),
-    Changed(String, String), // (old, new)
-    Unchanged(String),
-}
-
 /// Represents a hunk of consecutive diff lines
 #[derive(Debug, Clone)]
 struct DiffHunk {
@@ -63,49 +54,6 @@ impl ObjectDiffOperation {
     }
 
 
-    /// Compute verb-level diff using Myers algorithm
-    fn compute_verb_diff(&self, old_lines: &[String], new_lines: &[String]) -> Vec<DiffLine> {
-        // Simple Myers diff implementation
-        let mut diff_lines = Vec::new();
-        
-        let old_len = old_lines.len();
-        let new_len = new_lines.len();
-        
-        // Build LCS (Longest Common Subsequence) table
-        let mut lcs = vec![vec![0; new_len + 1]; old_len + 1];
-        
-        for i in 1..=old_len {
-            for j in 1..=new_len {
-                if old_lines[i - 1] == new_lines[j - 1] {
-                    lcs[i][j] = lcs[i - 1][j - 1] + 1;
-                } else {
-                    lcs[i][j] = std::cmp::max(lcs[i - 1][j], lcs[i][j - 1]);
-                }
-            }
-        }
-        
-        // Backtrack to build diff
-        let mut i = old_len;
-        let mut j = new_len;
-        
-        while i > 0 || j > 0 {
-            if i > 0 && j > 0 && old_lines[i - 1] == new_lines[j - 1] {
-                diff_lines.push(DiffLine::Unchanged(old_lines[i - 1].clone()));
-                i -= 1;
-                j -= 1;
-            } else if j > 0 && (i == 0 || lcs[i][j - 1] >= lcs[i - 1][j]) {
-                diff_lines.push(DiffLine::Added(new_lines[j - 1].clone()));
-                j -= 1;
-            } else if i > 0 {
-                diff_lines.push(DiffLine::Removed(old_lines[i - 1].clone()));
-                i -= 1;
-            }
-        }
-        
-        diff_lines.reverse();
-        diff_lines
-    }
-
     /// Generate hunks from diff lines
     fn generate_hunks(&self, diff_lines: &[DiffLine]) -> Vec<DiffHunk> {
         let mut hunks = Vec::new();
@@ -189,7 +137,7 @@ impl ObjectDiffOperation {
     fn process_object_diff(
         &self,
         request: ObjectDiffRequest,
-    ) -> Result<Var, ObjectsTreeError> {
+    ) -> Result<Var, OperationError> {
         // Resolve change IDs
         let target_change_id = self.database.resolve_change_id(&request.change_id)?;
         info!(
@@ -218,7 +166,7 @@ impl ObjectDiffOperation {
             .get_change(&target_change_id)
             .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
             .ok_or_else(|| {
-                ObjectsTreeError::SerializationError(format!(
+                OperationError::NotFound(format!(
                     "Change '{}' not found",
                     target_change_id
                 ))
@@ -233,7 +181,7 @@ impl ObjectDiffOperation {
                 obj.object_type == VcsObjectType::MooObject && obj.name == request.object_name
             })
             .ok_or_else(|| {
-                ObjectsTreeError::SerializationError(format!(
+                OperationError::NotFound(format!(
                     "Object '{}' not found in change '{}'",
                     request.object_name, target_change_id
                 ))
@@ -241,7 +189,7 @@ impl ObjectDiffOperation {
 
         // Use compare_object_versions to get the correct diff (same as change/status)
         use crate::object_diff::compare_object_versions;
-        let object_change = compare_object_versions(
+        let mut object_change = compare_object_versions(
             &self.database,
             &request.object_name,
             object_info.version,
@@ -326,6 +274,17 @@ impl ObjectDiffOperation {
             None
         };
 
+        // Unified diffs of verb code and before/after property values
+        add_line_diffs(&mut object_change, baseline_obj.as_ref(), &target_obj)?;
+        let verb_diff_lines = |verb_name: &str| -> Var {
+            let lines: Vec<Var> = object_change
+                .verb_diffs
+                .get(verb_name)
+                .map(|lines| lines.iter().map(|l| v_str(l)).collect())
+                .unwrap_or_default();
+            v_list(&lines)
+        };
+
         // Build the response with verb diffs
         let mut verb_changes = Vec::new();
 
@@ -340,11 +299,11 @@ impl ObjectDiffOperation {
                     v.names.iter().any(|n| n.as_string() == *verb_name)
                 }) {
                     // Decompile both versions
-                    let baseline_code = self.decompile_verb(baseline_verb)?;
-                    let target_code = self.decompile_verb(target_verb)?;
+                    let baseline_code = decompile_verb(baseline_verb)?;
+                    let target_code = decompile_verb(target_verb)?;
 
                     // Compute diff
-                    let diff_lines = self.compute_verb_diff(&baseline_code, &target_code);
+                    let diff_lines = compute_line_diff(&baseline_code, &target_code);
                     let hunks = self.generate_hunks(&diff_lines);
 
                     if !hunks.is_empty() {
@@ -352,6 +311,7 @@ impl ObjectDiffOperation {
                         verb_changes.push(v_map(&[
                             (v_str("verb"), v_str(verb_name)),
                             (v_str("hunks"), v_list(&hunks_list)),
+                            (v_str("diff"), verb_diff_lines(verb_name.as_str())),
                         ]));
                     }
                 }
@@ -363,7 +323,7 @@ impl ObjectDiffOperation {
             if let Some(target_verb) = target_obj.verbs.iter().find(|v| {
                 v.names.iter().any(|n| n.as_string() == *verb_name)
             }) {
-                let target_code = self.decompile_verb(target_verb)?;
+                let target_code = decompile_verb(target_verb)?;
                 let diff_lines: Vec<DiffLine> = target_code
                     .iter()
                     .map(|line| DiffLine::Added(line.clone()))
@@ -374,6 +334,7 @@ impl ObjectDiffOperation {
                 verb_changes.push(v_map(&[
                     (v_str("verb"), v_str(verb_name)),
                     (v_str("hunks"), v_list(&hunks_list)),
+                    (v_str("diff"), verb_diff_lines(verb_name.as_str())),
                 ]));
             }
         }
@@ -385,7 +346,7 @@ impl ObjectDiffOperation {
                     v.names.iter().any(|n| n.as_string() == *verb_name)
                 })
             }) {
-                let baseline_code = self.decompile_verb(baseline_verb)?;
+                let baseline_code = decompile_verb(baseline_verb)?;
                 let diff_lines: Vec<DiffLine> = baseline_code
                     .iter()
                     .map(|line| DiffLine::Removed(line.clone()))
@@ -396,6 +357,7 @@ impl ObjectDiffOperation {
                 verb_changes.push(v_map(&[
                     (v_str("verb"), v_str(verb_name)),
                     (v_str("hunks"), v_list(&hunks_list)),
+                    (v_str("diff"), verb_diff_lines(verb_name.as_str())),
                 ]));
             }
         }
@@ -411,11 +373,11 @@ impl ObjectDiffOperation {
                     })
                 }) {
                     // Decompile both versions
-                    let baseline_code = self.decompile_verb(baseline_verb)?;
-                    let target_code = self.decompile_verb(target_verb)?;
+                    let baseline_code = decompile_verb(baseline_verb)?;
+                    let target_code = decompile_verb(target_verb)?;
 
                     // Compute diff
-                    let diff_lines = self.compute_verb_diff(&baseline_code, &target_code);
+                    let diff_lines = compute_line_diff(&baseline_code, &target_code);
                     let hunks = self.generate_hunks(&diff_lines);
 
                     let hunks_list: Vec<Var> = hunks.iter().map(|h| h.to_moo_var()).collect();
@@ -423,6 +385,7 @@ impl ObjectDiffOperation {
                         (v_str("verb"), v_str(new_name)),
                         (v_str("old_verb"), v_str(old_name)),
                         (v_str("hunks"), v_list(&hunks_list)),
+                        (v_str("diff"), verb_diff_lines(new_name.as_str())),
                     ]));
                 }
             }
@@ -439,33 +402,30 @@ impl ObjectDiffOperation {
             v_str(&request.object_name)
         };
 
+        // Property value changes, sorted by name for stable output
+        let mut prop_names: Vec<&String> = object_change.prop_diffs.keys().collect();
+        prop_names.sort();
+        let prop_changes: Vec<Var> = prop_names
+            .into_iter()
+            .map(|prop_name| {
+                let change = &object_change.prop_diffs[prop_name];
+                let mut pairs = vec![(v_str("prop"), v_str(prop_name))];
+                if let Some(before) = &change.before {
+                    pairs.push((v_str("before"), v_str(before)));
+                }
+                if let Some(after) = &change.after {
+                    pairs.push((v_str("after"), v_str(after)));
+                }
+                v_map(&pairs)
+            })
+            .collect();
+
         Ok(v_map(&[
             (v_str("obj_id"), obj_id_var),
             (v_str("changes"), v_list(&verb_changes)),
+            (v_str("props"), v_list(&prop_changes)),
         ]))
     }
-
-    /// Decompile a verb into source code lines
-    fn decompile_verb(
-        &self,
-        verb: &moor_compiler::ObjVerbDef,
-    ) -> Result<Vec<String>, ObjectsTreeError> {
-        let ProgramType::MooR(program) = &verb.program;
-        
-        if program.main_vector().is_empty() {
-            return Ok(Vec::new());
-        }
-
-        let ast = program_to_tree(program).map_err(|e| {
-            ObjectsTreeError::SerializationError(format!("Failed to decompile verb: {}", e))
-        })?;
-
-        let lines = unparse(&ast, false, true).map_err(|e| {
-            ObjectsTreeError::SerializationError(format!("Failed to unparse verb: {}", e))
-        })?;
-
-        Ok(lines)
-    }
 }
 
 impl Operation for ObjectDiffOperation {
@@ -473,6 +433,10 @@ impl Operation for ObjectDiffOperation {
         "object/diff"
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &'static str {
         "Compares verb code between two commits and returns detailed line-by-line diffs"
     }
@@ -482,8 +446,9 @@ impl Operation for ObjectDiffOperation {
         two commits. By default, it compares the specified commit against the state immediately \
         before it. You can also provide a custom baseline commit to compare against. The operation \
         returns detailed hunks showing added, removed, and changed lines of code for each verb \
-        that has differences. This is useful for code review, understanding changes, and tracking \
-        the evolution of your MOO objects over time."
+        that has differences, along with a unified diff of the decompiled code (\"diff\") and the \
+        before/after literal values of every changed property (\"props\"). This is useful for code \
+        review, understanding changes, and tracking the evolution of your MOO objects over time."
     }
 
     fn parameters(&self) -> Vec<OperationParameter> {
@@ -556,17 +521,15 @@ endfor"#
         vec![
             OperationResponse::success(
                 "Operation executed successfully",
-                r##"{"obj_id": "#123", "changes": [{"verb": "look", "hunks": [{"content": ["new code line"], "start": 5, "type": "added"}]}]}"##,
+                r##"{"obj_id": "#123", "changes": [{"verb": "look", "hunks": [{"content": ["new code line"], "start": 5, "type": "added"}], "diff": ["@@ -3,2 +3,3 @@", " x = 1;", "+new code line", " return x;"]}], "props": [{"prop": "description", "before": "\"old\"", "after": "\"new\""}]}"##,
             ),
-            OperationResponse::new(
-                400,
+            OperationResponse::bad_request(
                 "Bad Request - Missing required arguments",
                 r#"E_INVARG("Object name and change ID are required")"#,
             ),
-            OperationResponse::new(
-                404,
+            OperationResponse::not_found(
                 "Not Found - Object not found in change",
-                r#"E_INVARG("Object '$player' not found in change 'abc123'")"#,
+                r#"E_INVIND("Object '$player' not found in change 'abc123'")"#,
             ),
             OperationResponse::new(
                 500,
@@ -576,10 +539,12 @@ endfor"#
         ]
     }
 
-    fn execute(&self, args: Vec<String>, _user: &User) -> Var {
+    fn execute(&self, args: Vec<String>, _user: &User) -> Result<Var, OperationError> {
         if args.len() < 2 {
             error!("Object diff operation requires at least 2 arguments");
-            return v_error(E_INVARG.msg("Object name and change ID are required"));
+            return Err(OperationError::InvalidArgs(
+                "Object name and change ID are required".to_string(),
+            ));
         }
 
         let object_name = args[0].clone();
@@ -595,11 +560,11 @@ endfor"#
         match self.process_object_diff(request) {
             Ok(result) => {
                 info!("Object diff operation completed successfully");
-                result
+                Ok(result)
             }
             Err(e) => {
                 error!("Object diff operation failed: {}", e);
-                v_error(E_INVARG.msg(format!("{}", e)))
+                Err(e)
             }
         }
     }
@@ -1,7 +1,8 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+};
 use axum::http::Method;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use tracing::{error, info};
 
 use crate::database::{DatabaseRef, ObjectsTreeError};
@@ -10,8 +11,7 @@ use crate::providers::objects::ObjectsProvider;
 use crate::providers::refs::RefsProvider;
 use crate::types::{User, VcsObjectType};
 use moor_compiler::{CompileOptions, ObjFileContext, compile_object_definitions};
-use moor_objdef::dump_object;
-use moor_var::{E_INVARG, v_error, v_list, v_str, Var};
+use moor_var::{v_list, v_str, Var};
 
 /// Request structure for object get operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,7 +33,7 @@ impl ObjectGetOperation {
     }
 
     /// Process the object get request
-    fn process_object_get(&self, request: ObjectGetRequest) -> Result<String, ObjectsTreeError> {
+    fn process_object_get(&self, request: ObjectGetRequest) -> Result<String, OperationError> {
         // If change_id is provided, resolve it and get object state at that change
         let (sha256_key, meta_version) = if let Some(ref change_id_str) = request.change_id {
             // Resolve short or full hash to full hash
@@ -55,7 +55,7 @@ impl ObjectGetOperation {
                 .get(&request.object_name)
                 .copied()
                 .ok_or_else(|| {
-                    ObjectsTreeError::SerializationError(format!(
+                    OperationError::NotFound(format!(
                         "Object '{}' not found in compiled state at change '{}'",
                         request.object_name, resolved_change_id
                     ))
@@ -101,7 +101,7 @@ impl ObjectGetOperation {
                 })
                 .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
                 .ok_or_else(|| {
-                    ObjectsTreeError::SerializationError(format!(
+                    OperationError::NotFound(format!(
                         "Object '{}' not found",
                         request.object_name
                     ))
@@ -178,7 +178,7 @@ impl ObjectGetOperation {
                 })?;
 
         if compiled_defs.len() != 1 {
-            return Err(ObjectsTreeError::SerializationError(format!(
+            return Err(OperationError::Internal(format!(
                 "Expected exactly 1 object definition, got {}",
                 compiled_defs.len()
             )));
@@ -206,16 +206,18 @@ impl ObjectGetOperation {
         });
 
         // Re-dump the filtered object
-        let index_names = HashMap::new(); // Empty index for simple object names
-        let lines = dump_object(&index_names, &obj_def).map_err(|e| {
-            ObjectsTreeError::SerializationError(format!("Failed to dump object: {e}"))
-        })?;
+        let filtered_dump = self
+            .database
+            .objects()
+            .generate_object_dump(&obj_def)
+            .map_err(|e| {
+                ObjectsTreeError::SerializationError(format!("Failed to dump object: {e}"))
+            })?;
 
-        let filtered_dump = lines.join("\n");
         info!(
             "Successfully filtered object '{}', returning {} lines",
             request.object_name,
-            lines.len()
+            filtered_dump.lines().count()
         );
 
         Ok(filtered_dump)
@@ -227,6 +229,10 @@ impl Operation for ObjectGetOperation {
         "object/get"
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &'static str {
         "Retrieves a MOO object definition by name from the database"
     }
@@ -282,16 +288,29 @@ impl Operation for ObjectGetOperation {
                 http_curl: Some(r#"curl -X POST http://localhost:8081/api/object/get \
   -H "Content-Type: application/json" \
   -d '{"operation": "object/get", "args": ["$player", "abc123def456"]}'"#.to_string()),
+            },
+            OperationExample {
+                description: "Retrieve an object over GET with path and query parameters".to_string(),
+                moocode: r#"objdef = worker_request("vcs", {"object/get", "$player", "abc123def456"});
+// Equivalent to the GET request below"#.to_string(),
+                http_curl: Some(r#"curl -X GET "http://localhost:8081/api/object/get/%24player?change_id=abc123def456""#.to_string()),
             }
         ]
     }
 
     fn routes(&self) -> Vec<OperationRoute> {
-        vec![OperationRoute {
-            path: "/api/object/get".to_string(),
-            method: Method::POST,
-            is_json: true,
-        }]
+        vec![
+            OperationRoute {
+                path: "/api/object/get".to_string(),
+                method: Method::POST,
+                is_json: true,
+            },
+            OperationRoute {
+                path: "/api/object/get/:object_name".to_string(),
+                method: Method::GET,
+                is_json: false,
+            },
+        ]
     }
 
     fn responses(&self) -> Vec<crate::operations::OperationResponse> {
@@ -308,15 +327,13 @@ verb \"look\" this none this
   player:tell(\"You look at \", this.name);
 end""#,
             ),
-            OperationResponse::new(
-                400,
+            OperationResponse::bad_request(
                 "Bad Request - Object name is required",
                 r#"E_INVARG("Object name is required")"#,
             ),
-            OperationResponse::new(
-                404,
+            OperationResponse::not_found(
                 "Not Found - Object not found or has been deleted",
-                r#"E_INVARG("Object '$player' not found")"#,
+                r#"E_INVIND("Object '$player' not found")"#,
             ),
             OperationResponse::new(
                 500,
@@ -331,14 +348,16 @@ end""#,
         ]
     }
 
-    fn execute(&self, args: Vec<String>, _user: &User) -> Var {
+    fn execute(&self, args: Vec<String>, _user: &User) -> Result<Var, OperationError> {
         // For RPC calls, we expect the args to contain:
         // args[0] = object_name
         // args[1] = change_id (optional)
 
         if args.is_empty() {
             error!("Object get operation requires object name");
-            return v_error(E_INVARG.msg("Object name is required"));
+            return Err(OperationError::InvalidArgs(
+                "Object name is required".to_string(),
+            ));
         }
 
         let object_name = args[0].clone();
@@ -361,11 +380,11 @@ end""#,
                     .lines()
                     .map(|line| v_str(line))
                     .collect();
-                v_list(&lines)
+                Ok(v_list(&lines))
             }
             Err(e) => {
                 error!("Object get operation failed: {}", e);
-                v_error(E_INVARG.msg(format!("{e}")))
+                Err(e)
             }
         }
     }
@@ -1,5 +1,7 @@
 use crate::object_diff::{compare_object_versions, ObjectChange};
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+};
 use axum::http::Method;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
@@ -7,7 +9,7 @@ use tracing::{error, info};
 use crate::database::{DatabaseRef, ObjectsTreeError};
 use crate::providers::index::IndexProvider;
 use crate::types::{User, VcsObjectType};
-use moor_var::{v_error, v_list, v_map, v_str, v_int, Var, E_INVARG};
+use moor_var::{v_list, v_map, v_str, v_int, Var};
 
 /// Request structure for object history operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -336,6 +338,10 @@ impl Operation for ObjectHistoryOperation {
         "object/history"
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &'static str {
         "Retrieves the complete change history for a specific MOO object, showing all modifications, renames, and state changes"
     }
@@ -458,26 +464,26 @@ endfor"#
   }
 ]"#,
             ),
-            OperationResponse::new(
-                400,
+            OperationResponse::success(
+                "Object has no history (never existed) - returns an empty list",
+                r#"{}"#,
+            ),
+            OperationResponse::bad_request(
                 "Bad Request - Object name is required",
                 r#"E_INVARG("Object name is required")"#,
             ),
-            OperationResponse::new(
-                404,
-                "Not Found - Object has no history (never existed)",
-                r#"[]"#,
-            ),
         ]
     }
 
-    fn execute(&self, args: Vec<String>, _user: &User) -> Var {
+    fn execute(&self, args: Vec<String>, _user: &User) -> Result<Var, OperationError> {
         // For RPC calls, we expect the args to contain:
         // args[0] = object_name
 
         if args.is_empty() {
             error!("Object history operation requires object name");
-            return v_error(E_INVARG.msg("Object name is required"));
+            return Err(OperationError::InvalidArgs(
+                "Object name is required".to_string(),
+            ));
         }
 
         let object_name = args[0].clone();
@@ -497,11 +503,11 @@ endfor"#
                     .map(|entry| entry.to_moo_var())
                     .collect();
 
-                v_list(&history_vars)
+                Ok(v_list(&history_vars))
             }
             Err(e) => {
                 error!("Object history operation failed: {}", e);
-                v_error(E_INVARG.msg(format!("{e}")))
+                Err(e.into())
             }
         }
     }
@@ -1,4 +1,6 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+};
 use axum::http::Method;
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
@@ -6,7 +8,7 @@ use tracing::{error, info};
 use crate::database::{DatabaseRef, ObjectsTreeError};
 use crate::providers::index::IndexProvider;
 use crate::types::{ObjectInfo, User};
-use moor_var::{E_INVARG, Var, v_error, v_list, v_str};
+use moor_var::{Var, v_list, v_str};
 
 /// Request structure for object list operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +51,10 @@ impl Operation for ObjectListOperation {
         "object/list"
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &'static str {
         "Lists all objects by walking through the entire change history chronologically, tracking names, renames, additions, and deletions. Returns a MOO list of object names."
     }
@@ -107,7 +113,7 @@ endfor"##
         ]
     }
 
-    fn execute(&self, args: Vec<String>, _user: &User) -> Var {
+    fn execute(&self, args: Vec<String>, _user: &User) -> Result<Var, OperationError> {
         info!("Executing object list operation with {} args", args.len());
 
         let _request = ObjectListRequest {};
@@ -123,11 +129,11 @@ endfor"##
                 let object_names: Vec<Var> =
                     object_list.iter().map(|obj| v_str(&obj.name)).collect();
 
-                v_list(&object_names)
+                Ok(v_list(&object_names))
             }
             Err(e) => {
                 error!("Object list operation failed: {}", e);
-                v_error(E_INVARG.msg(format!("{e}")))
+                Err(e.into())
             }
         }
     }
@@ -1,12 +1,15 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use axum::http::Method;
 use tracing::{error, info};
 
 use crate::database::DatabaseRef;
 use crate::providers::index::IndexProvider;
 use crate::providers::refs::RefsProvider;
-use crate::types::{ObjectsTreeError, PropertyRenameHint, User, VcsObjectType};
-use moor_var::{v_err, v_str, E_INVARG, Var};
+use crate::types::{ObjectsTreeError, Permission, PropertyRenameHint, User, VcsObjectType};
+use moor_var::{v_str, Var};
 
 /// Object property rename operation that adds a hint for a property rename
 #[derive(Clone)]
@@ -211,9 +214,13 @@ impl Operation for ObjectPropertyRenameOperation {
         }]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<Var, OperationError> {
+        require_permission(user, Permission::SubmitChanges, "edit objects")?;
+
         if args.len() != 3 {
-            return v_err(E_INVARG);
+            return Err(OperationError::InvalidArgs(
+                "Expected exactly 3 arguments: object name, from property and to property".to_string(),
+            ));
         }
 
         let object_name = &args[0];
@@ -222,10 +229,10 @@ impl Operation for ObjectPropertyRenameOperation {
         let author = Some(user.id.clone());
 
         match self.process_property_rename(object_name, from_prop, to_prop, author) {
-            Ok(message) => v_str(&message),
+            Ok(message) => Ok(v_str(&message)),
             Err(e) => {
                 error!("Failed to add property rename hint: {}", e);
-                v_err(E_INVARG)
+                Err(e.into())
             }
         }
     }
@@ -1,4 +1,7 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use axum::http::Method;
 use tracing::{error, info};
 
@@ -6,9 +9,8 @@ use crate::database::DatabaseRef;
 use crate::providers::index::IndexProvider;
 use crate::providers::objects::ObjectsProvider;
 use crate::providers::refs::RefsProvider;
-use crate::types::{ObjectRenameRequest, RenamedObject};
+use crate::types::{ObjectRenameRequest, Permission, RenamedObject};
 use crate::types::{ObjectsTreeError, User, VcsObjectType};
-use moor_var::{E_INVARG, v_error};
 
 /// Object rename operation that renames an object from one name to another
 #[derive(Clone)]
@@ -27,7 +29,7 @@ impl ObjectRenameOperation {
         &self,
         request: ObjectRenameRequest,
         author: Option<String>,
-    ) -> Result<String, ObjectsTreeError> {
+    ) -> Result<String, OperationError> {
         info!(
             "Processing object rename from '{}' to '{}'",
             request.from_name, request.to_name
@@ -36,7 +38,7 @@ impl ObjectRenameOperation {
         // Validate that names are not empty
         if request.from_name.is_empty() || request.to_name.is_empty() {
             error!("Cannot rename with empty names");
-            return Err(ObjectsTreeError::InvalidOperation(
+            return Err(OperationError::InvalidArgs(
                 "Object names cannot be empty".to_string(),
             ));
         }
@@ -44,7 +46,7 @@ impl ObjectRenameOperation {
         // Check that we're not using the same name
         if request.from_name == request.to_name {
             error!("Cannot rename object to the same name");
-            return Err(ObjectsTreeError::InvalidOperation(
+            return Err(OperationError::InvalidArgs(
                 "Cannot rename object to the same name".to_string(),
             ));
         }
@@ -65,7 +67,7 @@ impl ObjectRenameOperation {
 
         if source_in_deleted {
             error!("Cannot rename deleted object '{}'", request.from_name);
-            return Err(ObjectsTreeError::ObjectNotFound(format!(
+            return Err(OperationError::NotFound(format!(
                 "Object '{}' not found",
                 request.from_name
             )));
@@ -120,7 +122,7 @@ impl ObjectRenameOperation {
                     "Cannot rename object '{}' - object does not exist",
                     request.from_name
                 );
-                return Err(ObjectsTreeError::ObjectNotFound(format!(
+                return Err(OperationError::NotFound(format!(
                     "Object '{}' not found",
                     request.from_name
                 )));
@@ -164,7 +166,7 @@ impl ObjectRenameOperation {
                     "Cannot rename to '{}' - object already exists",
                     request.to_name
                 );
-                return Err(ObjectsTreeError::InvalidOperation(format!(
+                return Err(OperationError::Conflict(format!(
                     "Object '{}' already exists",
                     request.to_name
                 )));
@@ -767,30 +769,29 @@ impl Operation for ObjectRenameOperation {
                 "Operation executed successfully",
                 r#""Object '$old_utility' rename to '$new_utility' queued successfully in change 'local'""#,
             ),
-            OperationResponse::new(
-                400,
+            OperationResponse::bad_request(
                 "Bad Request - Missing required arguments",
                 r#"E_INVARG("From name and to name are required")"#,
             ),
-            OperationResponse::new(
-                400,
+            OperationResponse::bad_request(
                 "Bad Request - Empty object names",
                 r#"E_INVARG("Object names cannot be empty")"#,
             ),
-            OperationResponse::new(
-                400,
+            OperationResponse::bad_request(
                 "Bad Request - Cannot rename to same name",
                 r#"E_INVARG("Cannot rename object to the same name")"#,
             ),
-            OperationResponse::new(
-                404,
+            OperationResponse::forbidden(
+                "Forbidden - User lacks SubmitChanges permission",
+                r#"E_PERM("User 'player' does not have permission to edit objects")"#,
+            ),
+            OperationResponse::not_found(
                 "Not Found - Source object not found",
-                r#"E_INVARG("Object '$missing_object' not found")"#,
+                r#"E_INVIND("Object '$missing_object' not found")"#,
             ),
-            OperationResponse::new(
-                409,
+            OperationResponse::conflict(
                 "Conflict - Target object already exists",
-                r#"E_INVARG("Object '$existing_object' already exists")"#,
+                r#"E_NACC("Object '$existing_object' already exists")"#,
             ),
             OperationResponse::new(
                 500,
@@ -800,16 +801,20 @@ impl Operation for ObjectRenameOperation {
         ]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!(
             "Object rename operation received {} arguments: {:?}",
             args.len(),
             args
         );
 
+        require_permission(user, Permission::SubmitChanges, "edit objects")?;
+
         if args.len() < 2 {
             error!("Object rename operation requires at least from_name and to_name");
-            return v_error(E_INVARG.msg("From name and to name are required"));
+            return Err(OperationError::InvalidArgs(
+                "From name and to name are required".to_string(),
+            ));
         }
 
         let from_name = args[0].clone();
@@ -820,11 +825,11 @@ impl Operation for ObjectRenameOperation {
         match self.process_object_rename(request, Some(user.id.clone())) {
             Ok(result) => {
                 info!("Object rename operation completed successfully");
-                moor_var::v_str(&result)
+                Ok(moor_var::v_str(&result))
             }
             Err(e) => {
                 error!("Object rename operation failed: {}", e);
-                v_error(E_INVARG.msg(e.to_string()))
+                Err(e)
             }
         }
     }
@@ -1,4 +1,7 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use axum::http::Method;
 use tracing::{error, info};
 
@@ -6,9 +9,8 @@ use crate::database::DatabaseRef;
 use crate::object_diff::{ObjectChange, ObjectDiffModel};
 use crate::providers::index::IndexProvider;
 use crate::providers::workspace::WorkspaceProvider;
-use crate::types::{ChangeStatus, ObjectSwitchRequest};
+use crate::types::{ChangeStatus, ObjectSwitchRequest, Permission};
 use crate::types::{ObjectsTreeError, User, VcsObjectType};
-use moor_var::{v_error, E_INVARG};
 
 /// Object switch operation that moves an object from the local change to a target workspace change
 #[derive(Clone)]
@@ -26,7 +28,7 @@ impl ObjectSwitchOperation {
     fn process_object_switch(
         &self,
         request: ObjectSwitchRequest,
-    ) -> Result<ObjectDiffModel, ObjectsTreeError> {
+    ) -> Result<ObjectDiffModel, OperationError> {
         info!(
             "Processing object switch for '{}' to change '{}'",
             request.object_name, request.change_id
@@ -41,7 +43,7 @@ impl ObjectSwitchOperation {
             .get_top_change()
             .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
             .ok_or_else(|| {
-                ObjectsTreeError::InvalidOperation(
+                OperationError::NotFound(
                     "No local change exists. Cannot switch object without an active change."
                         .to_string(),
                 )
@@ -53,14 +55,14 @@ impl ObjectSwitchOperation {
             .get_change(&top_change_id)
             .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
             .ok_or_else(|| {
-                ObjectsTreeError::SerializationError(format!(
+                OperationError::NotFound(format!(
                     "Local change '{top_change_id}' not found"
                 ))
             })?;
 
         // Verify it's a local change
         if current_change.status != ChangeStatus::Local {
-            return Err(ObjectsTreeError::InvalidOperation(format!(
+            return Err(OperationError::Conflict(format!(
                 "Current change is not Local (status: {:?})",
                 current_change.status
             )));
@@ -86,7 +88,7 @@ impl ObjectSwitchOperation {
         } else if let Some(obj) = obj_in_modified {
             (obj, false)
         } else {
-            return Err(ObjectsTreeError::InvalidOperation(format!(
+            return Err(OperationError::NotFound(format!(
                 "Object '{}' not found in current change's added or modified objects",
                 request.object_name
             )));
@@ -123,10 +125,7 @@ impl ObjectSwitchOperation {
         };
 
         // Step 3: Resolve target change_id (short or long)
-        let target_change_id = self
-            .database
-            .resolve_change_id(&request.change_id)
-            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        let target_change_id = self.database.resolve_change_id(&request.change_id)?;
 
         // Step 4: Get target change from workspace
         let mut target_change = self
@@ -135,14 +134,14 @@ impl ObjectSwitchOperation {
             .get_workspace_change(&target_change_id)
             .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
             .ok_or_else(|| {
-                ObjectsTreeError::SerializationError(format!(
+                OperationError::NotFound(format!(
                     "Target change '{target_change_id}' not found in workspace"
                 ))
             })?;
 
         // Step 5: Verify target change status is not Merged
         if target_change.status == ChangeStatus::Merged {
-            return Err(ObjectsTreeError::InvalidOperation(format!(
+            return Err(OperationError::Conflict(format!(
                 "Cannot switch object to a Merged change (change '{}' has status: {:?})",
                 target_change.name, target_change.status
             )));
@@ -167,7 +166,7 @@ impl ObjectSwitchOperation {
             .any(|o| o.name == request.object_name);
 
         if (obj_exists_in_target_added || obj_exists_in_target_modified) && !force {
-            return Err(ObjectsTreeError::InvalidOperation(format!(
+            return Err(OperationError::Conflict(format!(
                 "Object '{}' already exists in target change '{}'. Use force=true to overwrite.",
                 request.object_name, target_change.name
             )));
@@ -388,45 +387,42 @@ player:tell("Object forcibly switched");"#
                 "Operation executed successfully - Object switched to target change",
                 r#"["objects_renamed" -> [], "objects_deleted" -> {"$my_object"}, "objects_added" -> {}, "objects_modified" -> {}, "changes" -> {["obj_id" -> "$my_object", "verbs_modified" -> {}, "verbs_added" -> {}, "verbs_renamed" -> [], "verbs_deleted" -> {}, "props_modified" -> {}, "props_added" -> {}, "props_renamed" -> [], "props_deleted" -> {}]}]"#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - Object not in local change or already in target",
-                r#"E_INVARG("Error: Object '$my_object' not found in current change's added or modified objects")"#,
+            OperationResponse::bad_request(
+                "Bad Request - Missing object_name or change_id argument",
+                r#"E_INVARG("Object switch operation requires object_name and change_id arguments")"#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - No local change exists",
-                r#"E_INVARG("Error: No local change exists. Cannot switch object without an active change.")"#,
+            OperationResponse::forbidden(
+                "Forbidden - User lacks SubmitChanges permission",
+                r#"E_PERM("User 'player' does not have permission to edit objects")"#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - Target change is merged",
-                r#"E_INVARG("Error: Cannot switch object to a Merged change")"#,
+            OperationResponse::not_found(
+                "Not Found - No local change, object not in local change, or target change not in workspace",
+                r#"E_INVIND("Object '$my_object' not found in current change's added or modified objects")"#,
             ),
-            OperationResponse::new(
-                404,
-                "Not Found - Target change not found",
-                r#"E_INVARG("Error: Target change 'abc-123-def...' not found in workspace")"#,
+            OperationResponse::conflict(
+                "Conflict - Target change is merged, or object already in target without force",
+                r#"E_NACC("Object '$my_object' already exists in target change 'other-change'. Use force=true to overwrite.")"#,
             ),
-            OperationResponse::new(
-                500,
+            OperationResponse::internal_error(
                 "Internal Server Error - Database or system error",
-                r#"E_INVARG("Error: Database error: failed to update change")"#,
+                r#"E_INVARG("Serialization error: failed to update change")"#,
             ),
         ]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!(
             "Object switch operation received {} arguments for user: {}",
             args.len(),
             user.id
         );
 
+        require_permission(user, Permission::SubmitChanges, "edit objects")?;
+
         if args.len() < 2 {
             error!("Object switch operation requires object_name and change_id arguments");
-            return v_error(E_INVARG.msg(
-                "Object switch operation requires object_name and change_id arguments",
+            return Err(OperationError::InvalidArgs(
+                "Object switch operation requires object_name and change_id arguments".to_string(),
             ));
         }
 
@@ -443,11 +439,11 @@ player:tell("Object forcibly switched");"#
         match self.process_object_switch(request) {
             Ok(diff_model) => {
                 info!("Object switch operation completed successfully, returning diff");
-                diff_model.to_moo_var()
+                Ok(diff_model.to_moo_var())
             }
             Err(e) => {
                 error!("Object switch operation failed: {}", e);
-                v_error(E_INVARG.msg(format!("Error: {e}")))
+                Err(e)
             }
         }
     }
@@ -1,16 +1,16 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use axum::http::Method;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use tracing::{debug, error, info};
 
 use crate::database::{DatabaseRef, ObjectsTreeError};
 use crate::providers::index::IndexProvider;
 use crate::providers::objects::ObjectsProvider;
 use crate::providers::refs::RefsProvider;
-use crate::types::{User, VcsObjectType};
-use moor_objdef::dump_object;
-use moor_var::{E_INVARG, v_error};
+use crate::types::{Permission, User, VcsObjectType};
 
 /// Request structure for object update operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,7 +36,7 @@ impl ObjectUpdateOperation {
         &self,
         request: ObjectUpdateRequest,
         author: Option<String>,
-    ) -> Result<String, ObjectsTreeError> {
+    ) -> Result<String, OperationError> {
         info!(
             "Processing object update for '{}' with {} var(s)",
             request.object_name,
@@ -66,7 +66,7 @@ impl ObjectUpdateOperation {
             .database
             .objects()
             .parse_object_dump(&object_dump)
-            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+            .map_err(|e| OperationError::InvalidArgs(e.to_string()))?;
 
         // Check if meta exists for this object
         let meta = match self
@@ -127,19 +127,21 @@ impl ObjectUpdateOperation {
                 });
 
                 // Re-dump the filtered object
-                let index_names = HashMap::new(); // Empty index for simple object names
-                let lines = dump_object(&index_names, &object_def).map_err(|e| {
-                    ObjectsTreeError::SerializationError(format!(
-                        "Failed to dump filtered object: {e}"
-                    ))
-                })?;
-
-                let filtered = lines.join("\n");
+                let filtered = self
+                    .database
+                    .objects()
+                    .generate_object_dump(&object_def)
+                    .map_err(|e| {
+                        ObjectsTreeError::SerializationError(format!(
+                            "Failed to dump filtered object: {e}"
+                        ))
+                    })?;
+
                 info!(
                     "Filtered object '{}', reduced from {} to {} lines",
                     request.object_name,
                     object_dump.lines().count(),
-                    lines.len()
+                    filtered.lines().count()
                 );
                 filtered
             } else {
@@ -158,7 +160,7 @@ impl ObjectUpdateOperation {
         };
 
         // Generate SHA256 hash for the (potentially filtered) object dump
-        let sha256_key = self.database.objects().generate_sha256_hash(&final_dump);
+        let sha256_key = self.database.objects().generate_hash(&final_dump);
         info!(
             "Generated SHA256 key '{}' for object '{}'",
             sha256_key, request.object_name
@@ -541,21 +543,22 @@ result = worker_request("vcs", {"object/update", "$my_new_object", new_obj});
                 "Operation executed successfully - object unchanged",
                 r#""Object '$player' unchanged (no modifications)""#,
             ),
-            OperationResponse::new(
-                400,
+            OperationResponse::bad_request(
                 "Bad Request - Missing object name",
                 r#"E_INVARG("Object name is required")"#,
             ),
-            OperationResponse::new(
-                400,
+            OperationResponse::bad_request(
                 "Bad Request - Missing object vars",
                 r#"E_INVARG("At least one var is required")"#,
             ),
-            OperationResponse::new(
-                400,
+            OperationResponse::bad_request(
                 "Bad Request - Failed to parse object dump",
                 r#"E_INVARG("Failed to parse object: invalid syntax")"#,
             ),
+            OperationResponse::forbidden(
+                "Forbidden - User lacks SubmitChanges permission",
+                r#"E_PERM("User 'player' does not have permission to edit objects")"#,
+            ),
             OperationResponse::new(
                 500,
                 "Internal Server Error - Meta SHA256 exists but data not found",
@@ -574,7 +577,9 @@ result = worker_request("vcs", {"object/update", "$my_new_object", new_obj});
         ]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
+        require_permission(user, Permission::SubmitChanges, "edit objects")?;
+
         // For RPC calls, we expect the args to contain:
         // args[0] = object_name
         // args[1..] = the var strings (either JSON encoded or individual strings)
@@ -587,7 +592,9 @@ result = worker_request("vcs", {"object/update", "$my_new_object", new_obj});
 
         if args.is_empty() {
             error!("Object update operation requires at least object name");
-            return v_error(E_INVARG.msg("Object name is required"));
+            return Err(OperationError::InvalidArgs(
+                "Object name is required".to_string(),
+            ));
         }
 
         let object_name = args[0].clone();
@@ -607,24 +614,32 @@ result = worker_request("vcs", {"object/update", "$my_new_object", new_obj});
             vars = args[1..].to_vec();
         } else {
             error!("Object update operation requires at least one var");
-            return v_error(E_INVARG.msg("At least one var is required"));
+            return Err(OperationError::InvalidArgs(
+                "At least one var is required".to_string(),
+            ));
         }
 
         if vars.is_empty() {
             error!("Object update operation requires at least one var");
-            return v_error(E_INVARG.msg("At least one var is required"));
+            return Err(OperationError::InvalidArgs(
+                "At least one var is required".to_string(),
+            ));
         }
 
         let request = ObjectUpdateRequest { object_name, vars };
 
-        match self.process_object_update(request, Some(user.id.clone())) {
+        // The blob, ref and change are written all-or-nothing
+        match self
+            .database
+            .write_batch(|| self.process_object_update(request, Some(user.id.clone())))
+        {
             Ok(result) => {
                 info!("Object update operation completed successfully");
-                moor_var::v_str(&result)
+                Ok(moor_var::v_str(&result))
             }
             Err(e) => {
                 error!("Object update operation failed: {}", e);
-                v_error(E_INVARG.msg(e.to_string()))
+                Err(e)
             }
         }
     }
@@ -1,12 +1,15 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use axum::http::Method;
 use tracing::{error, info};
 
 use crate::database::DatabaseRef;
 use crate::providers::index::IndexProvider;
 use crate::providers::refs::RefsProvider;
-use crate::types::{ObjectsTreeError, User, VcsObjectType, VerbRenameHint};
-use moor_var::{v_err, v_str, E_INVARG, Var};
+use crate::types::{ObjectsTreeError, Permission, User, VcsObjectType, VerbRenameHint};
+use moor_var::{v_str, Var};
 
 /// Object verb rename operation that adds a hint for a verb rename
 #[derive(Clone)]
@@ -211,9 +214,13 @@ impl Operation for ObjectVerbRenameOperation {
         }]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<Var, OperationError> {
+        require_permission(user, Permission::SubmitChanges, "edit objects")?;
+
         if args.len() != 3 {
-            return v_err(E_INVARG);
+            return Err(OperationError::InvalidArgs(
+                "Expected exactly 3 arguments: object name, from verb and to verb".to_string(),
+            ));
         }
 
         let object_name = &args[0];
@@ -222,10 +229,10 @@ impl Operation for ObjectVerbRenameOperation {
         let author = Some(user.id.clone());
 
         match self.process_verb_rename(object_name, from_verb, to_verb, author) {
-            Ok(message) => v_str(&message),
+            Ok(message) => Ok(v_str(&message)),
             Err(e) => {
                 error!("Failed to add verb rename hint: {}", e);
-                v_err(E_INVARG)
+                Err(e.into())
             }
         }
     }
@@ -1,15 +1,40 @@
+use moor_var::Obj;
 use std::collections::HashMap;
 use tracing::{error, info};
 
-use super::{Operation, OperationRoute};
+use super::{Operation, OperationError, OperationRoute};
+use crate::database::DatabaseRef;
 use crate::providers::user::UserProvider;
-use crate::types::{OperationRequest, OperationResponse};
+use crate::types::{OperationErrorInfo, OperationRequest, OperationResponse, User};
+
+/// Reasons a caller could not be resolved to a user
+#[derive(Debug, thiserror::Error)]
+pub enum AuthenticationError {
+    #[error("Invalid API key")]
+    InvalidApiKey,
+    #[error("User '{0}' is disabled")]
+    UserDisabled(String),
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
+impl AuthenticationError {
+    /// Machine-readable error code reported as `error.code` in HTTP responses
+    pub fn code(&self) -> &'static str {
+        match self {
+            AuthenticationError::InvalidApiKey => "unauthorized",
+            AuthenticationError::UserDisabled(_) => "permission_denied",
+            AuthenticationError::Internal(_) => "internal",
+        }
+    }
+}
 
 /// Registry that holds all registered operations
 #[derive(Default)]
 pub struct OperationRegistry {
     operations: HashMap<String, Box<dyn Operation>>,
     user_provider: Option<std::sync::Arc<dyn UserProvider>>,
+    database: Option<DatabaseRef>,
 }
 
 impl OperationRegistry {
@@ -23,6 +48,16 @@ impl OperationRegistry {
         self.user_provider = Some(user_provider);
     }
 
+    /// Set the database whose write batch each operation runs in
+    pub fn set_database(&mut self, database: DatabaseRef) {
+        self.database = Some(database);
+    }
+
+    /// The database operations run against, if one is set
+    pub fn database(&self) -> Option<&DatabaseRef> {
+        self.database.as_ref()
+    }
+
     /// Register a new operation
     pub fn register<O: Operation + 'static>(&mut self, operation: O) {
         let name = operation.name().to_string();
@@ -35,25 +70,66 @@ impl OperationRegistry {
         self.operations.keys().cloned().collect()
     }
 
-    /// Execute an operation by name and return a moor Var
-    pub fn execute_var(&self, request: OperationRequest) -> moor_var::Var {
-        let op_name = request.operation.clone();
+    /// Resolve the caller of a request from an optional API key.
+    /// Requests without a key run as the "Everyone" user; a key that matches no user is rejected,
+    /// as is a key belonging to a disabled user.
+    pub fn authenticate(&self, api_key: Option<&str>) -> Result<User, AuthenticationError> {
+        let provider = self.user_provider.as_ref().ok_or_else(|| {
+            AuthenticationError::Internal("No user provider configured".to_string())
+        })?;
 
-        // Get the Wizard user for operations (has all permissions by default)
-        let user = match &self.user_provider {
-            Some(provider) => match provider.get_wizard_user() {
-                Ok(user) => user,
-                Err(e) => {
-                    error!("Failed to get Wizard user: {}", e);
-                    return moor_var::v_str("Internal error: Unable to get user context");
-                }
-            },
-            None => {
-                error!("No user provider configured");
-                return moor_var::v_str("Internal error: No user provider configured");
-            }
+        let user = match api_key {
+            Some(key) => provider
+                .get_user_by_api_key(key)
+                .map_err(|e| AuthenticationError::Internal(e.to_string()))?
+                .ok_or(AuthenticationError::InvalidApiKey)?,
+            None => provider
+                .get_everyone_user()
+                .map_err(|e| AuthenticationError::Internal(e.to_string()))?,
         };
 
+        if user.is_disabled {
+            return Err(AuthenticationError::UserDisabled(user.id));
+        }
+
+        Ok(user)
+    }
+
+    /// Resolve the caller of a MOO RPC request from the `perms` object of the calling task.
+    /// Players without a VCS user whose `v_obj` matches run as the "Everyone" user.
+    pub fn authenticate_perms(&self, perms: Obj) -> Result<User, AuthenticationError> {
+        let provider = self.user_provider.as_ref().ok_or_else(|| {
+            AuthenticationError::Internal("No user provider configured".to_string())
+        })?;
+
+        let user = match provider
+            .get_user_by_v_obj(perms)
+            .map_err(|e| AuthenticationError::Internal(e.to_string()))?
+        {
+            Some(user) => user,
+            None => provider
+                .get_everyone_user()
+                .map_err(|e| AuthenticationError::Internal(e.to_string()))?,
+        };
+
+        if user.is_disabled {
+            return Err(AuthenticationError::UserDisabled(user.id));
+        }
+
+        Ok(user)
+    }
+
+    /// Execute an operation by name on behalf of the given user.
+    /// Each operation that may write runs in its own write batch, so its writes land together
+    /// and it fails with `ConcurrentModification` rather than overwrite a concurrent
+    /// operation's writes. Read-only operations run outside any batch.
+    pub fn execute_as(
+        &self,
+        request: OperationRequest,
+        user: &User,
+    ) -> Result<moor_var::Var, OperationError> {
+        let op_name = request.operation;
+
         match self.operations.get(&op_name) {
             Some(operation) => {
                 info!(
@@ -62,27 +138,60 @@ impl OperationRegistry {
                     request.args.len(),
                     user.id
                 );
-                operation.execute(request.args, &user)
+                match &self.database {
+                    Some(database) if !operation.read_only() => {
+                        database.write_batch(|| operation.execute(request.args, user))
+                    }
+                    _ => operation.execute(request.args, user),
+                }
             }
             None => {
                 error!("Operation '{}' not found", op_name);
-                moor_var::v_str(&format!("Operation '{op_name}' not found"))
+                Err(OperationError::NotFound(format!(
+                    "Operation '{op_name}' not found"
+                )))
             }
         }
     }
 
-    /// Execute an operation by name and return an HTTP response with JSON
-    pub fn execute_http(&self, request: OperationRequest) -> OperationResponse {
-        let var_result = self.execute_var(request.clone());
-        let operation_name = request.operation;
+    /// Execute an operation by name on behalf of the given user and return a moor Var.
+    /// Failures are returned as MOO error values.
+    pub fn execute_var_as(&self, request: OperationRequest, user: &User) -> moor_var::Var {
+        self.execute_as(request, user)
+            .unwrap_or_else(|e| e.to_moo_error())
+    }
 
-        // Convert moor Var to JSON Value
-        let result_json = var_to_json_value(var_result.clone());
+    /// Execute an operation by name on behalf of the given user and return an HTTP response with JSON,
+    /// along with the HTTP status code the response should be sent with
+    pub fn execute_http_as(
+        &self,
+        request: OperationRequest,
+        user: &User,
+    ) -> (u16, OperationResponse) {
+        let operation_name = request.operation.clone();
 
-        OperationResponse {
-            result: result_json,
-            success: true, // For simplicity, we assume operations succeed
-            operation: operation_name,
+        match self.execute_as(request, user) {
+            Ok(var_result) => (
+                200,
+                OperationResponse {
+                    result: var_to_json_value(var_result),
+                    success: true,
+                    operation: operation_name,
+                    error: None,
+                },
+            ),
+            Err(e) => (
+                e.status_code(),
+                OperationResponse {
+                    result: serde_json::Value::String(format!("Error: {e}")),
+                    success: false,
+                    operation: operation_name,
+                    error: Some(OperationErrorInfo {
+                        code: e.code().to_string(),
+                        message: e.to_string(),
+                    }),
+                },
+            ),
         }
     }
 
@@ -0,0 +1,12 @@
+mod remote_add_op;
+mod remote_list_op;
+mod remote_remove_op;
+mod remote_utils;
+
+pub use remote_add_op::RemoteAddOperation;
+pub use remote_list_op::RemoteListOperation;
+pub use remote_remove_op::RemoteRemoveOperation;
+pub(crate) use remote_utils::{
+    ahead_behind, call_remote, remote_base_url, remote_tip, remote_to_var, resolve_remote,
+    validate_remote_name,
+};
@@ -0,0 +1,207 @@
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
+use axum::http::Method;
+use tracing::{error, info};
+
+use super::remote_utils::{remote_base_url, remote_to_var, validate_remote_name};
+use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::operations::clone_op::CloneOperation;
+use crate::providers::index::IndexProvider;
+use crate::types::{Permission, Remote, User};
+
+/// Remote add operation that records a named remote repository with its credentials
+#[derive(Clone)]
+pub struct RemoteAddOperation {
+    database: DatabaseRef,
+}
+
+impl RemoteAddOperation {
+    /// Create a new remote add operation
+    pub fn new(database: DatabaseRef) -> Self {
+        Self { database }
+    }
+
+    /// Validate the API key with the remote, if there is one, returning the remote's user ID
+    fn validate_api_key(
+        &self,
+        base_url: &str,
+        api_key: Option<&str>,
+    ) -> Result<Option<String>, ObjectsTreeError> {
+        let Some(api_key) = api_key else {
+            return Ok(None);
+        };
+
+        let clone_op = CloneOperation::new(self.database.clone());
+        let (user_id, _email) =
+            crate::util::block_on(clone_op.validate_external_user(base_url, api_key))?;
+        Ok(Some(user_id))
+    }
+
+    /// Process the remote add request
+    fn process_remote_add(
+        &self,
+        name: &str,
+        url: &str,
+        api_key: Option<&str>,
+    ) -> Result<moor_var::Var, OperationError> {
+        validate_remote_name(name)?;
+        let base_url = remote_base_url(url);
+        if base_url.is_empty() {
+            return Err(OperationError::InvalidArgs(
+                "Remote URL must not be empty".to_string(),
+            ));
+        }
+
+        if self
+            .database
+            .index()
+            .get_remote(name)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+            .is_some()
+        {
+            return Err(OperationError::Conflict(format!(
+                "Remote '{name}' already exists - remove it first to change it"
+            )));
+        }
+
+        let user_id = self.validate_api_key(&base_url, api_key)?;
+        let remote = Remote {
+            name: name.to_string(),
+            url: base_url,
+            api_key: api_key.map(str::to_string),
+            user_id,
+        };
+        self.database
+            .index()
+            .set_remote(&remote)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+
+        info!("Added remote '{}' at {}", remote.name, remote.url);
+        Ok(remote_to_var(&remote))
+    }
+}
+
+impl Operation for RemoteAddOperation {
+    fn name(&self) -> &'static str {
+        "remote/add"
+    }
+
+    fn response_content_type(&self) -> &'static str {
+        "text/x-moo"
+    }
+
+    fn description(&self) -> &'static str {
+        "Adds a named remote repository with optional credentials, validating the API key with the remote"
+    }
+
+    fn philosophy(&self) -> &'static str {
+        "Remotes are the other workers this repository exchanges changes with, similar to 'git remote add' in Git. \
+        Each has its own URL and its own credentials, so a team can pull from a staging world and submit to a \
+        production one. index/update, index/push, change/submit and clone take a remote name and use that remote's \
+        credentials; without one they use the remote called origin, which is where clone records its source. An API \
+        key is checked against the remote when the remote is added, and the remote's user it belongs to is stored \
+        with it. Remotes are configuration: they survive clones and are never sent to other workers."
+    }
+
+    fn parameters(&self) -> Vec<OperationParameter> {
+        vec![
+            OperationParameter {
+                name: "name".to_string(),
+                description: "Name of the remote (letters, digits, '-', '_' and '.')".to_string(),
+                required: true,
+            },
+            OperationParameter {
+                name: "url".to_string(),
+                description: "Base URL of the remote worker (a trailing /api/clone is removed)"
+                    .to_string(),
+                required: true,
+            },
+            OperationParameter {
+                name: "api_key".to_string(),
+                description: "API key of a user on the remote, sent with every request to it"
+                    .to_string(),
+                required: false,
+            },
+        ]
+    }
+
+    fn examples(&self) -> Vec<OperationExample> {
+        vec![OperationExample {
+            description: "Add a production remote to submit changes to".to_string(),
+            moocode: r#"remote = worker_request("vcs", {"remote/add", "production", "http://prod-server:8081", "your-api-key"});
+// Submit the current change there instead of to origin
+worker_request("vcs", {"change/submit", "Fixed the login bug", "production"});"#
+                .to_string(),
+            http_curl: Some(
+                r#"curl -X POST http://localhost:8081/api/remote/add \
+  -H "Content-Type: application/json" \
+  -d '{"operation": "remote/add", "args": ["production", "http://prod-server:8081", "your-api-key"]}'"#
+                    .to_string(),
+            ),
+        }]
+    }
+
+    fn routes(&self) -> Vec<OperationRoute> {
+        vec![OperationRoute {
+            path: "/api/remote/add".to_string(),
+            method: Method::POST,
+            is_json: true,
+        }]
+    }
+
+    fn responses(&self) -> Vec<crate::operations::OperationResponse> {
+        use crate::operations::OperationResponse;
+        vec![
+            OperationResponse::success(
+                "Operation executed successfully - Returns the added remote",
+                r#"["name" -> "production", "url" -> "http://prod-server:8081", "user_id" -> "deployer", "has_api_key" -> 1]"#,
+            ),
+            OperationResponse::bad_request(
+                "Bad Request - Missing arguments or invalid remote name",
+                r#"E_INVARG("Invalid remote name 'prod server' - use letters, digits, '-', '_' and '.'")"#,
+            ),
+            OperationResponse::forbidden(
+                "Forbidden - User lacks Clone permission",
+                r#"E_PERM("User 'player' does not have permission to manage remotes")"#,
+            ),
+            OperationResponse::conflict(
+                "Conflict - A remote with this name already exists",
+                r#"E_NACC("Remote 'production' already exists - remove it first to change it")"#,
+            ),
+            OperationResponse::internal_error(
+                "Internal Server Error - The remote rejected the API key or could not be reached",
+                r#"E_INVARG("Remote server stat failed with status: 401 Unauthorized (invalid API key?)")"#,
+            ),
+        ]
+    }
+
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
+        info!("Remote add operation received for user: {}", user.id);
+
+        require_permission(user, Permission::Clone, "manage remotes")?;
+
+        if args.len() < 2 {
+            error!("Remote add operation requires name and url arguments");
+            return Err(OperationError::InvalidArgs(
+                "Expected at least 2 arguments: name, url".to_string(),
+            ));
+        }
+        let api_key = args
+            .get(2)
+            .map(String::as_str)
+            .filter(|key| !key.is_empty());
+
+        match self.process_remote_add(&args[0], &args[1], api_key) {
+            Ok(result) => {
+                info!("Remote add operation completed successfully");
+                Ok(result)
+            }
+            Err(e) => {
+                error!("Remote add operation failed: {}", e);
+                Err(e)
+            }
+        }
+    }
+}
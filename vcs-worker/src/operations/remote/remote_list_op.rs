@@ -0,0 +1,116 @@
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+};
+use axum::http::Method;
+use tracing::{error, info};
+
+use super::remote_utils::remote_to_var;
+use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::providers::index::IndexProvider;
+use crate::types::User;
+
+/// Remote list operation that lists the configured remotes without their API keys
+#[derive(Clone)]
+pub struct RemoteListOperation {
+    database: DatabaseRef,
+}
+
+impl RemoteListOperation {
+    /// Create a new remote list operation
+    pub fn new(database: DatabaseRef) -> Self {
+        Self { database }
+    }
+
+    /// Process the remote list request
+    fn process_remote_list(&self) -> Result<moor_var::Var, ObjectsTreeError> {
+        let remotes = self
+            .database
+            .index()
+            .list_remotes()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        info!("Listing {} remotes", remotes.len());
+
+        let remotes: Vec<_> = remotes.iter().map(remote_to_var).collect();
+        Ok(moor_var::v_list(&remotes))
+    }
+}
+
+impl Operation for RemoteListOperation {
+    fn name(&self) -> &'static str {
+        "remote/list"
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn response_content_type(&self) -> &'static str {
+        "text/x-moo"
+    }
+
+    fn description(&self) -> &'static str {
+        "Lists the configured remotes, ordered by name, with the remote user their credentials belong to"
+    }
+
+    fn philosophy(&self) -> &'static str {
+        "Shows where this repository can pull from, push and submit to, similar to 'git remote -v' in Git. \
+        API keys are never returned - only whether a remote has one and which of the remote's users it belongs \
+        to. Use system/status to see how far each remote is ahead of or behind the local history."
+    }
+
+    fn parameters(&self) -> Vec<OperationParameter> {
+        vec![]
+    }
+
+    fn examples(&self) -> Vec<OperationExample> {
+        vec![OperationExample {
+            description: "List remotes".to_string(),
+            moocode: r#"for remote in (worker_request("vcs", {"remote/list"}))
+  player:tell(remote["name"], ": ", remote["url"]);
+endfor"#
+                .to_string(),
+            http_curl: Some(r#"curl -X GET http://localhost:8081/api/remote/list"#.to_string()),
+        }]
+    }
+
+    fn routes(&self) -> Vec<OperationRoute> {
+        vec![OperationRoute {
+            path: "/api/remote/list".to_string(),
+            method: Method::GET,
+            is_json: false,
+        }]
+    }
+
+    fn responses(&self) -> Vec<crate::operations::OperationResponse> {
+        use crate::operations::OperationResponse;
+        vec![
+            OperationResponse::success(
+                "Operation executed successfully",
+                r#"{["name" -> "origin", "url" -> "http://staging-server:8081", "user_id" -> "builder", "has_api_key" -> 1], ["name" -> "production", "url" -> "http://prod-server:8081", "user_id" -> "deployer", "has_api_key" -> 1]}"#,
+            ),
+            OperationResponse::success(
+                "Operation executed successfully - No remotes configured",
+                r#"{}"#,
+            ),
+            OperationResponse::internal_error(
+                "Internal Server Error - Database error",
+                r#"E_INVARG("Database error: failed to read remotes")"#,
+            ),
+        ]
+    }
+
+    fn execute(&self, _args: Vec<String>, _user: &User) -> Result<moor_var::Var, OperationError> {
+        info!("Remote list operation received");
+
+        match self.process_remote_list() {
+            Ok(result) => {
+                info!("Remote list operation completed successfully");
+                Ok(result)
+            }
+            Err(e) => {
+                error!("Remote list operation failed: {}", e);
+                Err(e.into())
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
+use axum::http::Method;
+use tracing::{error, info};
+
+use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::providers::index::IndexProvider;
+use crate::types::{Permission, User};
+
+/// Remote remove operation that forgets a named remote and its credentials
+#[derive(Clone)]
+pub struct RemoteRemoveOperation {
+    database: DatabaseRef,
+}
+
+impl RemoteRemoveOperation {
+    /// Create a new remote remove operation
+    pub fn new(database: DatabaseRef) -> Self {
+        Self { database }
+    }
+}
+
+impl Operation for RemoteRemoveOperation {
+    fn name(&self) -> &'static str {
+        "remote/remove"
+    }
+
+    fn response_content_type(&self) -> &'static str {
+        "text/x-moo"
+    }
+
+    fn description(&self) -> &'static str {
+        "Removes a named remote and its stored credentials"
+    }
+
+    fn philosophy(&self) -> &'static str {
+        "Forgets a remote, similar to 'git remote remove' in Git. Its stored API key is deleted with it. \
+        History pulled from the remote stays, as do changes submitted to it. Removing origin turns the repository \
+        into a local one: change/submit then approves changes instantly unless it is given another remote."
+    }
+
+    fn parameters(&self) -> Vec<OperationParameter> {
+        vec![OperationParameter {
+            name: "name".to_string(),
+            description: "Name of the remote to remove".to_string(),
+            required: true,
+        }]
+    }
+
+    fn examples(&self) -> Vec<OperationExample> {
+        vec![OperationExample {
+            description: "Remove a remote".to_string(),
+            moocode: r#"worker_request("vcs", {"remote/remove", "staging"});"#.to_string(),
+            http_curl: Some(
+                r#"curl -X POST http://localhost:8081/api/remote/remove \
+  -H "Content-Type: application/json" \
+  -d '{"operation": "remote/remove", "args": ["staging"]}'"#
+                    .to_string(),
+            ),
+        }]
+    }
+
+    fn routes(&self) -> Vec<OperationRoute> {
+        vec![OperationRoute {
+            path: "/api/remote/remove".to_string(),
+            method: Method::POST,
+            is_json: true,
+        }]
+    }
+
+    fn responses(&self) -> Vec<crate::operations::OperationResponse> {
+        use crate::operations::OperationResponse;
+        vec![
+            OperationResponse::success(
+                "Operation executed successfully",
+                r#""Successfully removed remote 'staging'""#,
+            ),
+            OperationResponse::bad_request(
+                "Bad Request - Missing remote name",
+                r#"E_INVARG("Expected 1 argument: name")"#,
+            ),
+            OperationResponse::forbidden(
+                "Forbidden - User lacks Clone permission",
+                r#"E_PERM("User 'player' does not have permission to manage remotes")"#,
+            ),
+            OperationResponse::not_found(
+                "Not Found - No remote with this name",
+                r#"E_INVIND("Remote 'staging' not found")"#,
+            ),
+        ]
+    }
+
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
+        info!("Remote remove operation received for user: {}", user.id);
+
+        require_permission(user, Permission::Clone, "manage remotes")?;
+
+        let Some(name) = args.first() else {
+            error!("Remote remove operation requires a name argument");
+            return Err(OperationError::InvalidArgs(
+                "Expected 1 argument: name".to_string(),
+            ));
+        };
+
+        let removed = self
+            .database
+            .index()
+            .remove_remote(name)
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        if !removed {
+            error!("Remote '{}' not found", name);
+            return Err(OperationError::NotFound(format!(
+                "Remote '{name}' not found"
+            )));
+        }
+
+        info!("Removed remote '{}'", name);
+        Ok(moor_var::v_str(&format!(
+            "Successfully removed remote '{name}'"
+        )))
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use tracing::error;
+
+use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::operations::OperationError;
+use crate::providers::index::IndexProvider;
+use crate::types::{Change, DEFAULT_REMOTE, Remote};
+
+/// Merged changes requested per index/list page when comparing histories with a remote
+const LIST_PAGE_SIZE: usize = 100;
+
+/// Look up the named remote, or the default remote if no name is given
+pub fn resolve_remote(
+    database: &DatabaseRef,
+    name: Option<&str>,
+) -> Result<Remote, OperationError> {
+    let remote = database
+        .index()
+        .get_remote(name.unwrap_or(DEFAULT_REMOTE))
+        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+
+    match (remote, name) {
+        (Some(remote), _) => Ok(remote),
+        (None, Some(name)) => Err(OperationError::NotFound(format!(
+            "Remote '{name}' not found"
+        ))),
+        (None, None) => Err(OperationError::Conflict(
+            "No source URL configured. This repository was not cloned from a remote source."
+                .to_string(),
+        )),
+    }
+}
+
+/// Check that a remote name can be used as a storage key and typed in MOO code
+pub fn validate_remote_name(name: &str) -> Result<(), OperationError> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+    {
+        return Err(OperationError::InvalidArgs(format!(
+            "Invalid remote name '{name}' - use letters, digits, '-', '_' and '.'"
+        )));
+    }
+    Ok(())
+}
+
+/// Strip the clone endpoint from a URL, leaving the base URL the remote is stored under
+pub fn remote_base_url(url: &str) -> String {
+    url.trim_end_matches("/api/clone")
+        .trim_end_matches("/clone")
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Run an operation on a remote over RPC with its credentials and return the result.
+/// Failures reported by the remote keep their kind, so a conflict there is a conflict here.
+pub async fn call_remote(
+    client: &reqwest::Client,
+    remote: &Remote,
+    operation: &str,
+    args: Vec<String>,
+) -> Result<serde_json::Value, OperationError> {
+    let rpc_url = format!("{}/rpc", remote.url.trim_end_matches('/'));
+    let mut request = client.post(&rpc_url).json(&serde_json::json!({
+        "operation": operation,
+        "args": args
+    }));
+    if let Some(api_key) = &remote.api_key {
+        request = request.header("X-API-Key", api_key);
+    }
+    let response = request.send().await.map_err(|e| {
+        OperationError::Internal(format!("Failed to reach remote '{}': {e}", remote.name))
+    })?;
+
+    let status = response.status();
+    let body: serde_json::Value = response.json().await.map_err(|e| {
+        ObjectsTreeError::SerializationError(format!(
+            "Failed to read {operation} response (status {status}): {e}"
+        ))
+    })?;
+    if status.is_success() {
+        return Ok(body.get("result").cloned().unwrap_or_default());
+    }
+
+    let code = body["error"]["code"].as_str().unwrap_or_default();
+    let message = body["error"]["message"]
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{operation} failed with status {status}"));
+    let message = format!("Remote '{}' rejected {operation}: {message}", remote.name);
+    error!("{}", message);
+    Err(match code {
+        "conflict" | "concurrent_modification" => OperationError::Conflict(message),
+        "permission_denied" | "unauthorized" => OperationError::PermissionDenied(message),
+        "not_found" => OperationError::NotFound(message),
+        _ => OperationError::Internal(message),
+    })
+}
+
+/// Fetch one page of a remote's merged change IDs, newest first
+async fn list_remote_changes(
+    client: &reqwest::Client,
+    remote: &Remote,
+    limit: usize,
+    page: usize,
+) -> Result<Vec<String>, OperationError> {
+    let result = call_remote(
+        client,
+        remote,
+        "index/list",
+        vec![limit.to_string(), page.to_string()],
+    )
+    .await?;
+    let Some(changes) = result.as_array() else {
+        return Ok(Vec::new());
+    };
+
+    changes
+        .iter()
+        .map(|change| {
+            change["change_id"]
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    OperationError::Internal(format!(
+                        "Invalid index/list response from remote '{}': missing change_id",
+                        remote.name
+                    ))
+                })
+        })
+        .collect()
+}
+
+/// The last merged change on a remote, if it has any
+pub async fn remote_tip(
+    client: &reqwest::Client,
+    remote: &Remote,
+) -> Result<Option<String>, OperationError> {
+    Ok(list_remote_changes(client, remote, 1, 0)
+        .await?
+        .into_iter()
+        .next())
+}
+
+/// Count the local merged changes a remote lacks (ahead) and the remote's merged changes
+/// we lack (behind). The remote's history is read newest first until it reaches a change
+/// we also have, so comparing with an up-to-date remote costs one request.
+pub async fn ahead_behind(
+    client: &reqwest::Client,
+    remote: &Remote,
+    merged: &[Change],
+) -> Result<(usize, usize), OperationError> {
+    let positions: HashMap<&str, usize> = merged
+        .iter()
+        .enumerate()
+        .map(|(position, change)| (change.id.as_str(), position))
+        .collect();
+
+    let mut behind = 0;
+    for page in 0.. {
+        let change_ids = list_remote_changes(client, remote, LIST_PAGE_SIZE, page).await?;
+        for change_id in &change_ids {
+            if let Some(position) = positions.get(change_id.as_str()) {
+                return Ok((merged.len() - position - 1, behind));
+            }
+            behind += 1;
+        }
+        if change_ids.len() < LIST_PAGE_SIZE {
+            break;
+        }
+    }
+
+    // No shared history at all
+    Ok((merged.len(), behind))
+}
+
+/// Convert a remote to a MOO map. The API key itself is never shown.
+pub fn remote_to_var(remote: &Remote) -> moor_var::Var {
+    moor_var::v_map(&[
+        (moor_var::v_str("name"), moor_var::v_str(&remote.name)),
+        (moor_var::v_str("url"), moor_var::v_str(&remote.url)),
+        (
+            moor_var::v_str("user_id"),
+            moor_var::v_str(remote.user_id.as_deref().unwrap_or("")),
+        ),
+        (
+            moor_var::v_str("has_api_key"),
+            moor_var::v_int(if remote.api_key.is_some() { 1 } else { 0 }),
+        ),
+    ])
+}
@@ -0,0 +1,482 @@
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
+use axum::http::Method;
+use std::collections::{HashMap, HashSet};
+use tracing::{error, info, warn};
+
+use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::operations::system::reachable_blobs;
+use crate::providers::ProviderError;
+use crate::providers::index::IndexProvider;
+use crate::providers::objects::ObjectsProvider;
+use crate::providers::refs::RefsProvider;
+use crate::providers::workspace::WorkspaceProvider;
+use crate::types::{
+    Change, ChangeStatus, FsckRequest, ObjectInfo, Permission, User, VcsObjectType,
+};
+
+/// Problems found by a repository check, grouped by kind, plus the repairs made
+#[derive(Debug, Default)]
+struct FsckReport {
+    dangling_refs: Vec<String>,
+    missing_blobs: Vec<String>,
+    orphaned_blobs: Vec<String>,
+    corrupted_blobs: Vec<String>,
+    missing_changes: Vec<String>,
+    duplicate_local_changes: Vec<String>,
+    rename_loops: Vec<String>,
+    repairs: Vec<String>,
+}
+
+impl FsckReport {
+    /// Every problem category with its key in the result map
+    fn categories(&self) -> [(&'static str, &Vec<String>); 7] {
+        [
+            ("dangling_refs", &self.dangling_refs),
+            ("missing_blobs", &self.missing_blobs),
+            ("orphaned_blobs", &self.orphaned_blobs),
+            ("corrupted_blobs", &self.corrupted_blobs),
+            ("missing_changes", &self.missing_changes),
+            ("duplicate_local_changes", &self.duplicate_local_changes),
+            ("rename_loops", &self.rename_loops),
+        ]
+    }
+
+    /// Total number of problems found
+    fn problem_count(&self) -> usize {
+        self.categories().iter().map(|(_, found)| found.len()).sum()
+    }
+
+    fn to_var(&self) -> moor_var::Var {
+        let to_list = |items: &Vec<String>| {
+            let items: Vec<moor_var::Var> = items.iter().map(|s| moor_var::v_str(s)).collect();
+            moor_var::v_list(&items)
+        };
+
+        let mut entries = vec![(
+            moor_var::v_str("clean"),
+            moor_var::v_int(if self.problem_count() == 0 { 1 } else { 0 }),
+        )];
+        for (key, found) in self.categories() {
+            entries.push((moor_var::v_str(key), to_list(found)));
+        }
+        entries.push((moor_var::v_str("repairs"), to_list(&self.repairs)));
+
+        moor_var::v_map(&entries)
+    }
+}
+
+/// Describe an object version, e.g. "room v3" or "room v2 (meta)"
+fn describe_object(info: &ObjectInfo) -> String {
+    match info.object_type {
+        VcsObjectType::MooObject => format!("{} v{}", info.name, info.version),
+        VcsObjectType::MooMetaObject => format!("{} v{} (meta)", info.name, info.version),
+    }
+}
+
+/// Describe a change by name and short ID
+fn describe_change(change: &Change) -> String {
+    format!(
+        "'{}' ({})",
+        change.name,
+        crate::util::short_hash(&change.id)
+    )
+}
+
+/// Find renames within a change that lead back to where they started, e.g. a -> b -> a
+fn find_rename_loops(change: &Change) -> Vec<String> {
+    let renames: HashMap<(VcsObjectType, &str), &str> = change
+        .renamed_objects
+        .iter()
+        .map(|r| {
+            (
+                (r.from.object_type, r.from.name.as_str()),
+                r.to.name.as_str(),
+            )
+        })
+        .collect();
+
+    let mut starts: Vec<_> = renames.keys().copied().collect();
+    starts.sort();
+
+    let mut visited = HashSet::new();
+    let mut loops = Vec::new();
+    for start in starts {
+        let mut path: Vec<&str> = Vec::new();
+        let mut current = Some(start);
+        while let Some(node) = current {
+            if !visited.insert(node) {
+                // Reached a name seen before - it is a loop if this walk passed it
+                if let Some(pos) = path.iter().position(|name| *name == node.1) {
+                    let mut cycle = path[pos..].to_vec();
+                    cycle.push(node.1);
+                    loops.push(format!(
+                        "change {}: {}",
+                        describe_change(change),
+                        cycle.join(" -> ")
+                    ));
+                }
+                break;
+            }
+            path.push(node.1);
+            current = renames.get(&node).map(|next| (node.0, *next));
+        }
+    }
+    loops
+}
+
+/// Repository check operation that cross-checks refs, objects, the index order and the workspace
+#[derive(Clone)]
+pub struct FsckOperation {
+    database: DatabaseRef,
+}
+
+impl FsckOperation {
+    /// Create a new fsck operation
+    pub fn new(database: DatabaseRef) -> Self {
+        Self { database }
+    }
+
+    /// Check refs against stored blobs: refs to missing blobs, blobs nothing can reach, and
+    /// blobs whose content no longer matches their key
+    fn check_objects(
+        &self,
+        refs: &HashMap<ObjectInfo, String>,
+        report: &mut FsckReport,
+    ) -> Result<(), ObjectsTreeError> {
+        let objects = self.database.objects();
+        let keys: HashSet<String> = objects
+            .list_keys()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+            .into_iter()
+            .collect();
+
+        let mut sorted_keys: Vec<&String> = keys.iter().collect();
+        sorted_keys.sort();
+        for key in sorted_keys {
+            match objects.get(key) {
+                Ok(_) => {}
+                Err(ProviderError::Corrupted(_)) => report.corrupted_blobs.push(key.clone()),
+                Err(e) => return Err(ObjectsTreeError::SerializationError(e.to_string())),
+            }
+        }
+
+        let mut sorted_refs: Vec<(&ObjectInfo, &String)> = refs.iter().collect();
+        sorted_refs.sort_by(|a, b| {
+            (a.0.object_type, &a.0.name, a.0.version).cmp(&(
+                b.0.object_type,
+                &b.0.name,
+                b.0.version,
+            ))
+        });
+        for (info, hash) in &sorted_refs {
+            if !keys.contains(*hash) {
+                report
+                    .dangling_refs
+                    .push(format!("{} -> {}", describe_object(info), hash));
+            }
+        }
+
+        let reachable = reachable_blobs(&self.database)?;
+        let mut orphaned: Vec<String> = keys
+            .iter()
+            .filter(|key| !reachable.contains(*key))
+            .cloned()
+            .collect();
+        orphaned.sort();
+        report.orphaned_blobs = orphaned;
+
+        Ok(())
+    }
+
+    /// Check the index order: entries whose change record is gone, and more than one Local change
+    fn check_index(
+        &self,
+        repair: bool,
+        report: &mut FsckReport,
+    ) -> Result<Vec<Change>, ObjectsTreeError> {
+        let index = self.database.index();
+        let order = index
+            .get_change_order()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+
+        let mut changes = Vec::new();
+        for change_id in &order {
+            match index
+                .get_change(change_id)
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+            {
+                Some(change) => changes.push(change),
+                None => {
+                    report.missing_changes.push(change_id.clone());
+                    if repair {
+                        index
+                            .remove_from_index(change_id)
+                            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+                        report.repairs.push(format!(
+                            "Removed missing change {change_id} from the index order"
+                        ));
+                    }
+                }
+            }
+        }
+
+        // Only one Local change may be in the index: keep the top change (or the newest
+        // Local one) and stash the rest as Idle, like change/stash does
+        let top_change = index
+            .get_top_change()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        let locals: Vec<&Change> = changes
+            .iter()
+            .filter(|c| c.status == ChangeStatus::Local)
+            .collect();
+        if locals.len() > 1 {
+            let keep = locals
+                .iter()
+                .find(|c| Some(&c.id) == top_change.as_ref())
+                .or(locals.last())
+                .map(|c| c.id.clone())
+                .unwrap_or_default();
+
+            for change in locals.into_iter().filter(|c| c.id != keep) {
+                report.duplicate_local_changes.push(format!(
+                    "change {} is Local in the index alongside {}",
+                    describe_change(change),
+                    crate::util::short_hash(&keep)
+                ));
+                if repair {
+                    let mut stashed = change.clone();
+                    stashed.status = ChangeStatus::Idle;
+                    self.database
+                        .workspace()
+                        .store_workspace_change(&stashed)
+                        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+                    index
+                        .remove_from_index(&change.id)
+                        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+                    report.repairs.push(format!(
+                        "Stashed duplicate Local change {} as Idle",
+                        describe_change(change)
+                    ));
+                }
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Check workspace entries: only Review and Idle changes belong there
+    fn check_workspace(
+        &self,
+        repair: bool,
+        report: &mut FsckReport,
+    ) -> Result<Vec<Change>, ObjectsTreeError> {
+        let workspace = self.database.workspace();
+        let changes = workspace
+            .list_all_workspace_changes()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+
+        for change in changes.iter().filter(|c| c.status == ChangeStatus::Local) {
+            report.duplicate_local_changes.push(format!(
+                "change {} is Local in the workspace",
+                describe_change(change)
+            ));
+            if repair {
+                let mut idle = change.clone();
+                idle.status = ChangeStatus::Idle;
+                workspace
+                    .update_workspace_change(&idle)
+                    .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+                report.repairs.push(format!(
+                    "Marked workspace change {} as Idle",
+                    describe_change(change)
+                ));
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Process the fsck request
+    fn process_fsck(
+        &self,
+        request: FsckRequest,
+        user: &User,
+    ) -> Result<moor_var::Var, OperationError> {
+        if request.repair {
+            require_permission(user, Permission::ApproveChanges, "repair the repository")?;
+        }
+
+        let mut report = FsckReport::default();
+
+        let refs = self
+            .database
+            .refs()
+            .get_all_refs()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        self.check_objects(&refs, &mut report)?;
+
+        let index_changes = self.check_index(request.repair, &mut report)?;
+        let workspace_changes = self.check_workspace(request.repair, &mut report)?;
+
+        // Every object version a change added or modified needs stored content
+        for change in index_changes.iter().chain(workspace_changes.iter()) {
+            for info in change
+                .added_objects
+                .iter()
+                .chain(change.modified_objects.iter())
+            {
+                if !refs.contains_key(info) {
+                    report.missing_blobs.push(format!(
+                        "change {} needs {}",
+                        describe_change(change),
+                        describe_object(info)
+                    ));
+                }
+            }
+            report.rename_loops.extend(find_rename_loops(change));
+        }
+
+        let problems = report.problem_count();
+        if problems > 0 {
+            warn!(
+                "Repository check found {} problems ({} repaired)",
+                problems,
+                report.repairs.len()
+            );
+        } else {
+            info!("Repository check found no problems");
+        }
+
+        Ok(report.to_var())
+    }
+}
+
+impl Operation for FsckOperation {
+    fn name(&self) -> &'static str {
+        "system/fsck"
+    }
+
+    fn description(&self) -> &'static str {
+        "Check that refs, stored objects, the index order and workspace changes are consistent, optionally repairing what can be fixed safely"
+    }
+
+    fn response_content_type(&self) -> &'static str {
+        "text/x-moo"
+    }
+
+    fn philosophy(&self) -> &'static str {
+        "The repository is spread over several partitions - object blobs, refs, the index order, change \
+        records and the workspace - that are written one after another. A crash or a bug between those writes \
+        can leave them disagreeing. This operation walks all of them and reports: refs whose blob is missing \
+        (dangling_refs), object versions a change needs but no ref provides (missing_blobs), blobs nothing can \
+        reach (orphaned_blobs, exactly what system/gc would sweep), blobs whose content no longer matches their hash \
+        (corrupted_blobs), index entries without a change record (missing_changes), more than one Local change \
+        (duplicate_local_changes) and renames within a change that loop back on themselves (rename_loops). \
+        With repair, missing changes are dropped from the index order, extra Local changes in the index are \
+        stashed as Idle and Local workspace entries are marked Idle. Nothing that could hold content is deleted; \
+        the remaining problems are reported for a human to resolve."
+    }
+
+    fn parameters(&self) -> Vec<OperationParameter> {
+        vec![OperationParameter {
+            name: "repair".to_string(),
+            description: "Set to true (or --repair) to fix what can be fixed safely (requires ApproveChanges permission, default: false)".to_string(),
+            required: false,
+        }]
+    }
+
+    fn examples(&self) -> Vec<OperationExample> {
+        vec![
+            OperationExample {
+                description: "Check the repository".to_string(),
+                moocode: r#"report = worker_request("vcs", {"system/fsck"});
+if (!report["clean"])
+    player:tell("Dangling refs: ", toliteral(report["dangling_refs"]));
+endif"#
+                    .to_string(),
+                http_curl: Some(r#"curl -X GET http://localhost:8081/api/system/fsck"#.to_string()),
+            },
+            OperationExample {
+                description: "Check the repository and repair what can be fixed safely".to_string(),
+                moocode: r#"report = worker_request("vcs", {"system/fsck", "--repair"});
+for repair in (report["repairs"])
+    player:tell(repair);
+endfor"#
+                    .to_string(),
+                http_curl: Some(
+                    r#"curl -X POST "http://localhost:8081/api/system/fsck?repair=true""#
+                        .to_string(),
+                ),
+            },
+        ]
+    }
+
+    fn routes(&self) -> Vec<OperationRoute> {
+        vec![
+            OperationRoute {
+                path: "/api/system/fsck".to_string(),
+                method: Method::GET,
+                is_json: false,
+            },
+            OperationRoute {
+                path: "/api/system/fsck".to_string(),
+                method: Method::POST,
+                is_json: false,
+            },
+        ]
+    }
+
+    fn responses(&self) -> Vec<crate::operations::OperationResponse> {
+        use crate::operations::OperationResponse;
+        vec![
+            OperationResponse::success(
+                "Operation executed successfully - returns the problems found and the repairs made",
+                r#"["clean" -> 0, "dangling_refs" -> {"room v3 -> 9f2c..."}, "missing_blobs" -> {}, "orphaned_blobs" -> {"41d7..."}, "corrupted_blobs" -> {}, "missing_changes" -> {"c0ffee..."}, "duplicate_local_changes" -> {}, "rename_loops" -> {}, "repairs" -> {"Removed missing change c0ffee... from the index order"}]"#,
+            ),
+            OperationResponse::bad_request(
+                "Bad Request - Invalid repair flag",
+                r#"E_INVARG("Invalid repair flag 'maybe': expected true, false or --repair")"#,
+            ),
+            OperationResponse::forbidden(
+                "Forbidden - User lacks ApproveChanges permission to repair",
+                r#"E_PERM("User 'player' does not have permission to repair the repository")"#,
+            ),
+            OperationResponse::internal_error(
+                "Internal Server Error - Database error",
+                r#"E_INVARG("Serialization error: ...")"#,
+            ),
+        ]
+    }
+
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
+        let repair = match args.first().map(|s| s.as_str()) {
+            None | Some("") | Some("false") => false,
+            Some("true") | Some("--repair") => true,
+            Some(other) => {
+                error!("Invalid fsck repair flag '{}'", other);
+                return Err(OperationError::InvalidArgs(format!(
+                    "Invalid repair flag '{other}': expected true, false or --repair"
+                )));
+            }
+        };
+
+        info!(
+            "Fsck operation received for user: {} (repair: {})",
+            user.id, repair
+        );
+
+        match self.process_fsck(FsckRequest { repair }, user) {
+            Ok(result) => {
+                info!("Fsck operation completed successfully");
+                Ok(result)
+            }
+            Err(e) => {
+                error!("Fsck operation failed: {}", e);
+                Err(e)
+            }
+        }
+    }
+}
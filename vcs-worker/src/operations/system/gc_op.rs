@@ -0,0 +1,231 @@
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
+use axum::http::Method;
+use tracing::{error, info};
+
+use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::operations::system::reachable_blobs;
+use crate::providers::objects::ObjectsProvider;
+use crate::types::{GcRequest, Permission, User};
+
+/// Garbage collection operation that sweeps object blobs nothing can reach
+#[derive(Clone)]
+pub struct GcOperation {
+    database: DatabaseRef,
+}
+
+impl GcOperation {
+    /// Create a new gc operation
+    pub fn new(database: DatabaseRef) -> Self {
+        Self { database }
+    }
+
+    /// Process the gc request
+    fn process_gc(&self, request: GcRequest, user: &User) -> Result<moor_var::Var, OperationError> {
+        if !request.dry_run {
+            require_permission(
+                user,
+                Permission::ApproveChanges,
+                "garbage collect the repository",
+            )?;
+        }
+
+        // Hold back every batch's commit while marking and sweeping, so nothing can come to
+        // point at a blob between it being found unreachable and it being swept
+        let (total_blobs, unreachable, reclaimed_bytes) =
+            self.database.exclusively(|| -> Result<_, OperationError> {
+                let objects = self.database.objects();
+
+                // List blobs before marking, so a blob stored while we mark is never a sweep
+                // candidate
+                let keys = objects
+                    .list_keys()
+                    .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+                let total_blobs = keys.len();
+                let marked = reachable_blobs(&self.database)?;
+
+                let mut unreachable: Vec<String> = keys
+                    .into_iter()
+                    .filter(|key| !marked.contains(key))
+                    .collect();
+                unreachable.sort();
+
+                let size_before = self.database.get_partition_data_size("objects");
+                let reclaimed_bytes = if request.dry_run {
+                    let mut bytes = 0u64;
+                    for key in &unreachable {
+                        bytes += objects
+                            .get_object_size(key)
+                            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+                            .unwrap_or(0);
+                    }
+                    info!(
+                        "Dry run: {} of {} blobs are unreachable ({} bytes)",
+                        unreachable.len(),
+                        total_blobs,
+                        bytes
+                    );
+                    bytes
+                } else {
+                    for key in &unreachable {
+                        objects
+                            .delete(key)
+                            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+                    }
+                    let size_after = self.database.get_partition_data_size("objects");
+                    let bytes = size_before.saturating_sub(size_after);
+                    info!(
+                        "Swept {} of {} blobs ({} bytes reclaimed)",
+                        unreachable.len(),
+                        total_blobs,
+                        bytes
+                    );
+                    bytes
+                };
+                Ok((total_blobs, unreachable, reclaimed_bytes))
+            })?;
+
+        let swept: Vec<moor_var::Var> = unreachable.iter().map(|k| moor_var::v_str(k)).collect();
+        Ok(moor_var::v_map(&[
+            (
+                moor_var::v_str("dry_run"),
+                moor_var::v_int(if request.dry_run { 1 } else { 0 }),
+            ),
+            (
+                moor_var::v_str("total_blobs"),
+                moor_var::v_int(total_blobs as i64),
+            ),
+            (
+                moor_var::v_str("reachable_blobs"),
+                moor_var::v_int((total_blobs - unreachable.len()) as i64),
+            ),
+            (
+                moor_var::v_str("swept_blobs"),
+                moor_var::v_int(unreachable.len() as i64),
+            ),
+            (moor_var::v_str("swept"), moor_var::v_list(&swept)),
+            (
+                moor_var::v_str("reclaimed_bytes"),
+                moor_var::v_int(reclaimed_bytes as i64),
+            ),
+            (
+                moor_var::v_str("objects_partition_size"),
+                moor_var::v_int(self.database.get_partition_data_size("objects") as i64),
+            ),
+        ]))
+    }
+}
+
+impl Operation for GcOperation {
+    fn name(&self) -> &'static str {
+        "system/gc"
+    }
+
+    fn description(&self) -> &'static str {
+        "Delete object blobs that no ref, index change or workspace change can reach, reporting the bytes reclaimed"
+    }
+
+    fn response_content_type(&self) -> &'static str {
+        "text/x-moo"
+    }
+
+    fn philosophy(&self) -> &'static str {
+        "Abandoning a change, deleting an object or updating the same version of an object twice within a \
+        Local change can leave blobs in the objects partition that nothing points at any more. This operation \
+        is a mark-and-sweep collector: it marks every blob named by a ref or by an object version of any index \
+        or workspace change, then deletes the rest. Use dry_run first to see what would be swept and how much \
+        space it would free. system/fsck uses the same reachability check, so its orphaned_blobs are exactly \
+        the blobs a sweep would delete."
+    }
+
+    fn parameters(&self) -> Vec<OperationParameter> {
+        vec![OperationParameter {
+            name: "dry_run".to_string(),
+            description: "Set to true (or --dry-run) to report unreachable blobs without deleting them (default: false; sweeping requires ApproveChanges permission)".to_string(),
+            required: false,
+        }]
+    }
+
+    fn examples(&self) -> Vec<OperationExample> {
+        vec![
+            OperationExample {
+                description: "See what garbage collection would reclaim".to_string(),
+                moocode: r#"report = worker_request("vcs", {"system/gc", "--dry-run"});
+player:tell(report["swept_blobs"], " unreachable blobs, ", report["reclaimed_bytes"], " bytes");"#
+                    .to_string(),
+                http_curl: Some(
+                    r#"curl -X POST "http://localhost:8081/api/system/gc?dry_run=true""#
+                        .to_string(),
+                ),
+            },
+            OperationExample {
+                description: "Sweep unreachable blobs".to_string(),
+                moocode: r#"report = worker_request("vcs", {"system/gc"});
+player:tell("Reclaimed ", report["reclaimed_bytes"], " bytes");"#
+                    .to_string(),
+                http_curl: Some(r#"curl -X POST http://localhost:8081/api/system/gc"#.to_string()),
+            },
+        ]
+    }
+
+    fn routes(&self) -> Vec<OperationRoute> {
+        vec![OperationRoute {
+            path: "/api/system/gc".to_string(),
+            method: Method::POST,
+            is_json: false,
+        }]
+    }
+
+    fn responses(&self) -> Vec<crate::operations::OperationResponse> {
+        use crate::operations::OperationResponse;
+        vec![
+            OperationResponse::success(
+                "Operation executed successfully - returns what was (or would be) swept",
+                r#"["dry_run" -> 0, "total_blobs" -> 120, "reachable_blobs" -> 117, "swept_blobs" -> 3, "swept" -> {"41d7...", "9a0e...", "c3f1..."}, "reclaimed_bytes" -> 18432, "objects_partition_size" -> 8370176]"#,
+            ),
+            OperationResponse::bad_request(
+                "Bad Request - Invalid dry_run flag",
+                r#"E_INVARG("Invalid dry_run flag 'maybe': expected true, false or --dry-run")"#,
+            ),
+            OperationResponse::forbidden(
+                "Forbidden - User lacks ApproveChanges permission to sweep",
+                r#"E_PERM("User 'player' does not have permission to garbage collect the repository")"#,
+            ),
+            OperationResponse::internal_error(
+                "Internal Server Error - Database error",
+                r#"E_INVARG("Serialization error: ...")"#,
+            ),
+        ]
+    }
+
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
+        let dry_run = match args.first().map(|s| s.as_str()) {
+            None | Some("") | Some("false") => false,
+            Some("true") | Some("--dry-run") => true,
+            Some(other) => {
+                error!("Invalid gc dry_run flag '{}'", other);
+                return Err(OperationError::InvalidArgs(format!(
+                    "Invalid dry_run flag '{other}': expected true, false or --dry-run"
+                )));
+            }
+        };
+
+        info!(
+            "Gc operation received for user: {} (dry run: {})",
+            user.id, dry_run
+        );
+
+        match self.process_gc(GcRequest { dry_run }, user) {
+            Ok(result) => {
+                info!("Gc operation completed successfully");
+                Ok(result)
+            }
+            Err(e) => {
+                error!("Gc operation failed: {}", e);
+                Err(e)
+            }
+        }
+    }
+}
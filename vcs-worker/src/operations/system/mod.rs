@@ -1,3 +1,9 @@
+mod fsck_op;
+mod gc_op;
+mod reachability;
 mod status_op;
 
+pub use fsck_op::FsckOperation;
+pub use gc_op::GcOperation;
+pub(crate) use reachability::reachable_blobs;
 pub use status_op::StatusOperation;
@@ -0,0 +1,52 @@
+use std::collections::HashSet;
+use tracing::info;
+
+use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::providers::index::IndexProvider;
+use crate::providers::refs::RefsProvider;
+use crate::providers::workspace::WorkspaceProvider;
+use crate::types::{Change, ObjectInfo};
+
+/// Every object version a change mentions, including both sides of renames
+fn change_objects(change: &Change) -> impl Iterator<Item = &ObjectInfo> {
+    change
+        .added_objects
+        .iter()
+        .chain(change.modified_objects.iter())
+        .chain(change.deleted_objects.iter())
+        .chain(change.renamed_objects.iter().flat_map(|r| [&r.from, &r.to]))
+}
+
+/// Every blob reachable from refs, index changes and workspace changes
+///
+/// system/gc sweeps the blobs outside this set and system/fsck reports them as orphaned_blobs.
+pub fn reachable_blobs(database: &DatabaseRef) -> Result<HashSet<String>, ObjectsTreeError> {
+    let refs = database
+        .refs()
+        .get_all_refs()
+        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+
+    let mut changes = database
+        .index()
+        .list_changes()
+        .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+    changes.extend(
+        database
+            .workspace()
+            .list_all_workspace_changes()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?,
+    );
+
+    let mut reachable: HashSet<String> = refs.values().cloned().collect();
+    for change in &changes {
+        reachable.extend(change_objects(change).filter_map(|info| refs.get(info).cloned()));
+    }
+
+    info!(
+        "Marked {} reachable blobs from {} refs and {} changes",
+        reachable.len(),
+        refs.len(),
+        changes.len()
+    );
+    Ok(reachable)
+}
@@ -1,12 +1,28 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+};
 use axum::http::Method;
-use tracing::{error, info};
+use std::time::Duration;
+use tracing::{error, info, warn};
 
 use crate::database::{DatabaseRef, ObjectsTreeError};
+use crate::object_merge::load_merged_changes;
+use crate::operations::backup::backup_run_to_var;
+use crate::operations::remote::{ahead_behind, remote_to_var};
+use crate::providers::backups::BackupProvider;
 use crate::providers::index::IndexProvider;
 use crate::providers::workspace::WorkspaceProvider;
-use crate::types::{ChangeStatus, User};
-use moor_var::{E_INVARG, v_error};
+use crate::types::{BackupRunStatus, ChangeStatus, DEFAULT_REMOTE, User};
+
+/// How long to wait for each remote to be compared before reporting it as unreachable
+const REMOTE_STATUS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Request structure for status operations
+#[derive(Debug, Clone)]
+pub struct StatusRequest {
+    /// Contact each remote to count how far it is ahead and behind
+    pub check_remotes: bool,
+}
 
 /// System status operation that provides comprehensive repository status information
 #[derive(Clone)]
@@ -26,7 +42,11 @@ impl StatusOperation {
     }
 
     /// Process the status request
-    fn process_status(&self, user: &User) -> Result<moor_var::Var, ObjectsTreeError> {
+    fn process_status(
+        &self,
+        request: StatusRequest,
+        user: &User,
+    ) -> Result<moor_var::Var, ObjectsTreeError> {
         info!("Processing system status request for user: {}", user.id);
 
         // Get top change ID
@@ -70,12 +90,19 @@ impl StatusOperation {
         // Get latest non-local merged change
         let latest_merged_change = self.get_latest_merged_change()?;
 
+        // Get the outcome of the last finished git backup run
+        let git_backup = self.get_last_backup_run()?;
+
+        // List the remotes, comparing the local history with each one only when asked to
+        let (remotes, pending_updates) = self.get_remotes_status(request.check_remotes)?;
+
         // Get remote repository URL if present
         let remote_url = self
             .database
             .index()
-            .get_source()
+            .get_remote(DEFAULT_REMOTE)
             .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?
+            .map(|remote| remote.url)
             .unwrap_or_else(String::new);
 
         // Get partition data sizes (sum of all keys and values in each partition)
@@ -83,15 +110,6 @@ impl StatusOperation {
         let refs_partition_size = self.get_partition_data_size("refs") as i64;
         let objects_partition_size = self.get_partition_data_size("objects") as i64;
 
-        // Check if we're behind remote (only if we have a source)
-        let pending_updates = if !remote_url.is_empty() {
-            // TODO: Implement checking remote for pending updates
-            // For now, return 0 as we'd need to query the remote
-            0i64
-        } else {
-            0i64
-        };
-
         // Get short IDs
         let top_change_short_id = if !top_change_id.is_empty() {
             crate::util::short_hash(&top_change_id)
@@ -144,12 +162,80 @@ impl StatusOperation {
                 moor_var::v_str("pending_updates"),
                 moor_var::v_int(pending_updates),
             ),
+            (moor_var::v_str("remotes"), moor_var::v_list(&remotes)),
+            (moor_var::v_str("git_backup"), git_backup),
         ]);
 
         info!("Status request completed successfully");
         Ok(status_map)
     }
 
+    /// List the configured remotes. When `check_remotes` is set, also count how far each one is
+    /// ahead of and behind the local history, returning the number of merged changes origin has
+    /// that we don't. Remotes are compared concurrently, each within `REMOTE_STATUS_TIMEOUT`; one
+    /// that cannot be reached in time reports -1 and its error.
+    fn get_remotes_status(
+        &self,
+        check_remotes: bool,
+    ) -> Result<(Vec<moor_var::Var>, i64), ObjectsTreeError> {
+        let remotes = self
+            .database
+            .index()
+            .list_remotes()
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+        if !check_remotes || remotes.is_empty() {
+            return Ok((remotes.iter().map(remote_to_var).collect(), 0));
+        }
+
+        let merged = load_merged_changes(&self.database)?;
+        let client = reqwest::Client::builder().build().map_err(|e| {
+            ObjectsTreeError::SerializationError(format!("Failed to create HTTP client: {e}"))
+        })?;
+
+        let comparisons = remotes.iter().map(|remote| {
+            tokio::time::timeout(
+                REMOTE_STATUS_TIMEOUT,
+                ahead_behind(&client, remote, &merged),
+            )
+        });
+        let counts = crate::util::block_on(futures::future::join_all(comparisons));
+
+        let mut pending_updates = 0;
+        let mut statuses = Vec::with_capacity(remotes.len());
+        for (remote, count) in remotes.iter().zip(counts) {
+            let count = count.unwrap_or_else(|_| {
+                Err(OperationError::Internal(format!(
+                    "Remote did not respond within {} seconds",
+                    REMOTE_STATUS_TIMEOUT.as_secs()
+                )))
+            });
+            let (ahead, behind, error) = match count {
+                Ok((ahead, behind)) => (ahead as i64, behind as i64, String::new()),
+                Err(e) => {
+                    warn!("Could not compare with remote '{}': {}", remote.name, e);
+                    (-1, -1, e.to_string())
+                }
+            };
+            if remote.name == DEFAULT_REMOTE {
+                pending_updates = behind.max(0);
+            }
+
+            statuses.push(moor_var::v_map(&[
+                (moor_var::v_str("name"), moor_var::v_str(&remote.name)),
+                (moor_var::v_str("url"), moor_var::v_str(&remote.url)),
+                (
+                    moor_var::v_str("user_id"),
+                    moor_var::v_str(remote.user_id.as_deref().unwrap_or("")),
+                ),
+                (moor_var::v_str("ahead"), moor_var::v_int(ahead)),
+                (moor_var::v_str("behind"), moor_var::v_int(behind)),
+                (moor_var::v_str("error"), moor_var::v_str(&error)),
+            ]));
+        }
+
+        Ok((statuses, pending_updates))
+    }
+
     /// Get the latest merged change (non-local)
     fn get_latest_merged_change(&self) -> Result<moor_var::Var, ObjectsTreeError> {
         let change_order = self
@@ -190,6 +276,21 @@ impl StatusOperation {
         // No merged changes found - return empty string
         Ok(moor_var::v_str(""))
     }
+
+    /// Get the last finished git backup run
+    fn get_last_backup_run(&self) -> Result<moor_var::Var, ObjectsTreeError> {
+        let run = self
+            .database
+            .backups()
+            .last_run_with_status(&[BackupRunStatus::Succeeded, BackupRunStatus::Failed])
+            .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))?;
+
+        // No backup has finished yet - return empty string
+        Ok(match run {
+            Some(run) => backup_run_to_var(&run),
+            None => moor_var::v_str(""),
+        })
+    }
 }
 
 impl Operation for StatusOperation {
@@ -197,6 +298,10 @@ impl Operation for StatusOperation {
         "status"
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn response_content_type(&self) -> &'static str {
         "text/x-moo"
     }
@@ -208,17 +313,26 @@ impl Operation for StatusOperation {
     fn philosophy(&self) -> &'static str {
         "Provides a complete overview of the VCS repository state, including local changes, workspace status, \
         partition sizes, and remote repository information. This operation is useful for monitoring system health, \
-        understanding current repository state, and determining if synchronization with remote repositories is needed."
+        understanding current repository state, and determining if synchronization with remote repositories is needed. \
+        Remotes are only contacted when asked for with the remotes flag, so a routine status check never waits on \
+        the network. Each remote is then asked for its recent history to count the merged changes it lacks (ahead - \
+        push them with index/push) and the ones we lack (behind - pull them with index/update). A remote that cannot \
+        be reached in time is reported with its error instead of failing the status request."
     }
 
     fn parameters(&self) -> Vec<OperationParameter> {
-        vec![]
+        vec![OperationParameter {
+            name: "remotes".to_string(),
+            description: "Set to true (or --remotes) to contact each remote and count how far it is ahead and behind (default: false)".to_string(),
+            required: false,
+        }]
     }
 
     fn examples(&self) -> Vec<OperationExample> {
-        vec![OperationExample {
-            description: "Get repository status".to_string(),
-            moocode: r#"status = worker_request("vcs", {"status"});
+        vec![
+            OperationExample {
+                description: "Get repository status".to_string(),
+                moocode: r#"status = worker_request("vcs", {"status"});
 // Returns a map with:
 // - game_name: Name of the game/world
 // - top_change_id: ID of current local change (empty if none)
@@ -231,10 +345,26 @@ impl Operation for StatusOperation {
 // - refs_partition_size: Size of refs partition in bytes
 // - objects_partition_size: Size of objects partition in bytes
 // - remote_url: Remote repository URL (empty if not cloned)
-// - pending_updates: Number of updates available from remote"#
-                .to_string(),
-            http_curl: Some(r#"curl -X GET http://localhost:8081/api/status"#.to_string()),
-        }]
+// - pending_updates: Number of updates available from origin (0 unless remotes are checked)
+// - remotes: Per-remote name, url, user_id and has_api_key, as in remote/list
+// - git_backup: Last finished git backup run, as in backup/history (empty if none)"#
+                    .to_string(),
+                http_curl: Some(r#"curl -X GET http://localhost:8081/api/status"#.to_string()),
+            },
+            OperationExample {
+                description: "Compare the repository with its remotes".to_string(),
+                moocode: r#"status = worker_request("vcs", {"status", "--remotes"});
+for remote in (status["remotes"])
+  player:tell(remote["name"], ": ", remote["ahead"], " ahead, ", remote["behind"], " behind");
+endfor
+// Each remote reports name, url, user_id, ahead and behind counts, and error
+// (ahead and behind are -1 with an error message if the remote could not be reached)"#
+                    .to_string(),
+                http_curl: Some(
+                    r#"curl -X GET "http://localhost:8081/api/status?remotes=true""#.to_string(),
+                ),
+            },
+        ]
     }
 
     fn routes(&self) -> Vec<OperationRoute> {
@@ -250,27 +380,44 @@ impl Operation for StatusOperation {
         vec![
             OperationResponse::success(
                 "Operation executed successfully",
-                r#"["game_name" -> "MyGame", "top_change_id" -> "abc123def456...", "top_change_short_id" -> "abc123", "idle_changes" -> 2, "pending_review" -> 1, "current_username" -> "player", "changes_in_index" -> 5, "latest_merged_change" -> ["id" -> "def789ghi012...", "short_id" -> "def789", "author" -> "player", "timestamp" -> 1697040000, "message" -> "Fixed login bug"], "index_partition_size" -> 1048576, "refs_partition_size" -> 4096, "objects_partition_size" -> 8388608, "remote_url" -> "http://example.com/repo", "pending_updates" -> 0]"#,
+                r#"["game_name" -> "MyGame", "top_change_id" -> "abc123def456...", "top_change_short_id" -> "abc123", "idle_changes" -> 2, "pending_review" -> 1, "current_username" -> "player", "changes_in_index" -> 5, "latest_merged_change" -> ["id" -> "def789ghi012...", "short_id" -> "def789", "author" -> "player", "timestamp" -> 1697040000, "message" -> "Fixed login bug"], "index_partition_size" -> 1048576, "refs_partition_size" -> 4096, "objects_partition_size" -> 8388608, "remote_url" -> "http://example.com/repo", "pending_updates" -> 0, "remotes" -> {["name" -> "origin", "url" -> "http://example.com/repo", "user_id" -> "builder", "has_api_key" -> 1]}, "git_backup" -> ["id" -> 3, "status" -> "succeeded", "trigger" -> "change/approve", "queued_at" -> 1697040000, "started_at" -> 1697040000, "finished_at" -> 1697040001, "commit" -> "4b825dc6...", "commits" -> 1, "object_count" -> 42, "error" -> ""]]"#,
             ),
-            OperationResponse::new(
-                500,
+            OperationResponse::bad_request(
+                "Bad Request - Invalid remotes flag",
+                r#"E_INVARG("Invalid remotes flag 'maybe': expected true, false or --remotes")"#,
+            ),
+            OperationResponse::internal_error(
                 "Internal Server Error - Database error",
                 r#"E_INVARG("Database error: failed to get change order")"#,
             ),
         ]
     }
 
-    fn execute(&self, _args: Vec<String>, user: &User) -> moor_var::Var {
-        info!("System status operation received");
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
+        let check_remotes = match args.first().map(|s| s.as_str()) {
+            None | Some("") | Some("false") => false,
+            Some("true") | Some("--remotes") => true,
+            Some(other) => {
+                error!("Invalid status remotes flag '{}'", other);
+                return Err(OperationError::InvalidArgs(format!(
+                    "Invalid remotes flag '{other}': expected true, false or --remotes"
+                )));
+            }
+        };
+
+        info!(
+            "System status operation received (checking remotes: {})",
+            check_remotes
+        );
 
-        match self.process_status(user) {
+        match self.process_status(StatusRequest { check_remotes }, user) {
             Ok(result) => {
                 info!("System status operation completed successfully");
-                result
+                Ok(result)
             }
             Err(e) => {
                 error!("System status operation failed: {}", e);
-                v_error(E_INVARG.msg(format!("{e}")))
+                Err(e.into())
             }
         }
     }
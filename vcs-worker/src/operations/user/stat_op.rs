@@ -1,4 +1,6 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+};
 use axum::http::Method;
 use tracing::info;
 
@@ -13,6 +15,10 @@ impl Operation for StatOperation {
         "user/stat"
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn response_content_type(&self) -> &'static str {
         "text/x-moo"
     }
@@ -60,25 +66,14 @@ player:tell("Permissions: ", tostr(user_info[4]));"#
                 "Operation executed successfully",
                 r#""Operation completed successfully""#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - Invalid arguments",
-                r#"E_INVARG("Error: Invalid operation arguments")"#,
-            ),
-            OperationResponse::new(
-                404,
-                "Not Found - Resource not found",
-                r#"E_INVARG("Error: Resource not found")"#,
-            ),
-            OperationResponse::new(
-                500,
+            OperationResponse::internal_error(
                 "Internal Server Error - Database or system error",
-                r#"E_INVARG("Error: Database error: operation failed")"#,
+                r#"E_INVARG("Database error: operation failed")"#,
             ),
         ]
     }
 
-    fn execute(&self, _args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, _args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!("Executing user/stat operation for user: {}", user.id);
 
         // Build a list representing the user information
@@ -100,6 +95,6 @@ player:tell("Permissions: ", tostr(user_info[4]));"#
         ]);
 
         info!("User stat operation completed successfully");
-        result
+        Ok(result)
     }
 }
@@ -1,4 +1,8 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
+use crate::providers::ProviderError;
 use crate::providers::user::UserProvider;
 use axum::http::Method;
 use std::sync::Arc;
@@ -87,34 +91,30 @@ impl Operation for UserCreateOperation {
                 "Operation executed successfully",
                 r#""Operation completed successfully""#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - Invalid arguments",
-                r#"E_INVARG("Error: Invalid operation arguments")"#,
+            OperationResponse::bad_request(
+                "Bad Request - Missing or invalid arguments",
+                r#"E_INVARG("Expected 3 arguments: user_id, email, v_obj")"#,
             ),
-            OperationResponse::new(
-                404,
-                "Not Found - Resource not found",
-                r#"E_INVARG("Error: Resource not found")"#,
+            OperationResponse::forbidden(
+                "Forbidden - User lacks CreateUser permission",
+                r#"E_PERM("User 'player' does not have permission to create users")"#,
             ),
-            OperationResponse::new(
-                500,
+            OperationResponse::conflict(
+                "Conflict - User ID, email or v_obj already in use",
+                r#"E_NACC("User 'alice' already exists")"#,
+            ),
+            OperationResponse::internal_error(
                 "Internal Server Error - Database or system error",
-                r#"E_INVARG("Error: Database error: operation failed")"#,
+                r#"E_INVARG("Serialization error: JSON parse error")"#,
             ),
         ]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!("Executing user/create operation for user: {}", user.id);
 
         // Check permission
-        if !user.has_permission(&Permission::CreateUser) {
-            error!("User {} does not have CreateUser permission", user.id);
-            return moor_var::v_error(
-                moor_var::E_INVARG.msg("Error: You do not have permission to create users"),
-            );
-        }
+        require_permission(user, Permission::CreateUser, "create users")?;
 
         // Validate arguments
         if args.len() < 3 {
@@ -122,9 +122,9 @@ impl Operation for UserCreateOperation {
                 "Invalid arguments for user/create: expected 3, got {}",
                 args.len()
             );
-            return moor_var::v_error(
-                moor_var::E_INVARG.msg("Error: Expected 3 arguments: user_id, email, v_obj"),
-            );
+            return Err(OperationError::InvalidArgs(
+                "Expected 3 arguments: user_id, email, v_obj".to_string(),
+            ));
         }
 
         let user_id = &args[0];
@@ -136,8 +136,8 @@ impl Operation for UserCreateOperation {
             Ok(n) => n,
             Err(_) => {
                 error!("Invalid v_obj: {}", v_obj_str);
-                return moor_var::v_error(moor_var::E_INVARG.msg(format!(
-                    "Error: Invalid v_obj '{v_obj_str}', must be an integer"
+                return Err(OperationError::InvalidArgs(format!(
+                    "Invalid v_obj '{v_obj_str}', must be an integer"
                 )));
             }
         };
@@ -154,11 +154,17 @@ impl Operation for UserCreateOperation {
                     "Created user '{}' with email '{}' and v_obj {:?}",
                     created_user.id, created_user.email, created_user.v_obj
                 );
-                moor_var::v_str(&format!("Successfully created user '{user_id}'"))
+                Ok(moor_var::v_str(&format!(
+                    "Successfully created user '{user_id}'"
+                )))
             }
             Err(e) => {
                 error!("Failed to create user: {}", e);
-                moor_var::v_error(moor_var::E_INVARG.msg(format!("Error: {e}")))
+                // The provider rejects duplicate IDs, emails and v_objs as invalid operations
+                Err(match e {
+                    ProviderError::InvalidOperation(msg) => OperationError::Conflict(msg),
+                    other => other.into(),
+                })
             }
         }
     }
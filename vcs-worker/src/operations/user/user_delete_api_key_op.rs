@@ -1,4 +1,7 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use crate::providers::user::UserProvider;
 use axum::http::Method;
 use std::sync::Arc;
@@ -88,25 +91,26 @@ impl Operation for UserDeleteApiKeyOperation {
                 "Operation executed successfully",
                 r#""Operation completed successfully""#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - Invalid arguments",
-                r#"E_INVARG("Error: Invalid operation arguments")"#,
+            OperationResponse::bad_request(
+                "Bad Request - Missing API key",
+                r#"E_INVARG("Expected at least 1 argument: api_key")"#,
             ),
-            OperationResponse::new(
-                404,
-                "Not Found - Resource not found",
-                r#"E_INVARG("Error: Resource not found")"#,
+            OperationResponse::forbidden(
+                "Forbidden - User lacks ManageApiKeys permission",
+                r#"E_PERM("User 'player' does not have permission to manage API keys for other users")"#,
             ),
-            OperationResponse::new(
-                500,
+            OperationResponse::not_found(
+                "Not Found - User not found",
+                r#"E_INVIND("User 'alice' not found")"#,
+            ),
+            OperationResponse::internal_error(
                 "Internal Server Error - Database or system error",
-                r#"E_INVARG("Error: Database error: operation failed")"#,
+                r#"E_INVARG("Serialization error: JSON parse error")"#,
             ),
         ]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!(
             "Executing user/delete_api_key operation for user: {}",
             user.id
@@ -115,9 +119,9 @@ impl Operation for UserDeleteApiKeyOperation {
         // Validate arguments
         if args.is_empty() {
             error!("Invalid arguments for user/delete_api_key: expected at least 1 argument");
-            return moor_var::v_error(
-                moor_var::E_INVARG.msg("Error: Expected at least 1 argument: api_key"),
-            );
+            return Err(OperationError::InvalidArgs(
+                "Expected at least 1 argument: api_key".to_string(),
+            ));
         }
 
         let api_key = &args[0];
@@ -135,12 +139,11 @@ impl Operation for UserDeleteApiKeyOperation {
                 target
             } else {
                 // Deleting from another user requires ManageApiKeys permission
-                if !user.has_permission(&Permission::ManageApiKeys) {
-                    error!("User {} does not have ManageApiKeys permission", user.id);
-                    return moor_var::v_error(moor_var::E_INVARG.msg(
-                        "Error: You do not have permission to manage API keys for other users",
-                    ));
-                }
+                require_permission(
+                    user,
+                    Permission::ManageApiKeys,
+                    "manage API keys for other users",
+                )?;
                 target
             }
         };
@@ -150,17 +153,19 @@ impl Operation for UserDeleteApiKeyOperation {
             Ok(deleted) => {
                 if deleted {
                     info!("Deleted API key from user '{}'", target_user_id);
-                    moor_var::v_str(&format!(
+                    Ok(moor_var::v_str(&format!(
                         "Successfully deleted API key from user '{target_user_id}'"
-                    ))
+                    )))
                 } else {
                     info!("API key not found for user '{}'", target_user_id);
-                    moor_var::v_str(&format!("API key not found for user '{target_user_id}'"))
+                    Ok(moor_var::v_str(&format!(
+                        "API key not found for user '{target_user_id}'"
+                    )))
                 }
             }
             Err(e) => {
                 error!("Failed to delete API key: {}", e);
-                moor_var::v_error(moor_var::E_INVARG.msg(format!("Error: {e}")))
+                Err(e.into())
             }
         }
     }
@@ -1,4 +1,7 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use crate::providers::user::UserProvider;
 use axum::http::Method;
 use std::sync::Arc;
@@ -78,41 +81,37 @@ impl Operation for UserDisableOperation {
                 "Operation executed successfully",
                 r#""Operation completed successfully""#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - Invalid arguments",
-                r#"E_INVARG("Error: Invalid operation arguments")"#,
+            OperationResponse::bad_request(
+                "Bad Request - Missing user ID",
+                r#"E_INVARG("Expected 1 argument: user_id")"#,
             ),
-            OperationResponse::new(
-                404,
-                "Not Found - Resource not found",
-                r#"E_INVARG("Error: Resource not found")"#,
+            OperationResponse::forbidden(
+                "Forbidden - User lacks DisableUser permission",
+                r#"E_PERM("User 'player' does not have permission to disable users")"#,
             ),
-            OperationResponse::new(
-                500,
+            OperationResponse::not_found(
+                "Not Found - User not found",
+                r#"E_INVIND("User 'alice' not found")"#,
+            ),
+            OperationResponse::internal_error(
                 "Internal Server Error - Database or system error",
-                r#"E_INVARG("Error: Database error: operation failed")"#,
+                r#"E_INVARG("Serialization error: JSON parse error")"#,
             ),
         ]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!("Executing user/disable operation for user: {}", user.id);
 
         // Check permission
-        if !user.has_permission(&Permission::DisableUser) {
-            error!("User {} does not have DisableUser permission", user.id);
-            return moor_var::v_error(
-                moor_var::E_INVARG.msg("Error: You do not have permission to disable users"),
-            );
-        }
+        require_permission(user, Permission::DisableUser, "disable users")?;
 
         // Validate arguments
         if args.is_empty() {
             error!("Invalid arguments for user/disable: expected 1, got 0");
-            return moor_var::v_error(
-                moor_var::E_INVARG.msg("Error: Expected 1 argument: user_id"),
-            );
+            return Err(OperationError::InvalidArgs(
+                "Expected 1 argument: user_id".to_string(),
+            ));
         }
 
         let target_user_id = &args[0];
@@ -121,11 +120,13 @@ impl Operation for UserDisableOperation {
         match self.user_provider.disable_user(target_user_id) {
             Ok(()) => {
                 info!("Disabled user '{}'", target_user_id);
-                moor_var::v_str(&format!("Successfully disabled user '{target_user_id}'"))
+                Ok(moor_var::v_str(&format!(
+                    "Successfully disabled user '{target_user_id}'"
+                )))
             }
             Err(e) => {
                 error!("Failed to disable user: {}", e);
-                moor_var::v_error(moor_var::E_INVARG.msg(format!("Error: {e}")))
+                Err(e.into())
             }
         }
     }
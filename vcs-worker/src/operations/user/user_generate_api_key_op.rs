@@ -1,4 +1,7 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use crate::providers::user::UserProvider;
 use axum::http::Method;
 use std::sync::Arc;
@@ -92,25 +95,22 @@ player:tell("New API key for alice: ", api_key);"#
                 "Operation executed successfully",
                 r#""Operation completed successfully""#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - Invalid arguments",
-                r#"E_INVARG("Error: Invalid operation arguments")"#,
+            OperationResponse::forbidden(
+                "Forbidden - User lacks ManageApiKeys permission",
+                r#"E_PERM("User 'player' does not have permission to manage API keys for other users")"#,
             ),
-            OperationResponse::new(
-                404,
-                "Not Found - Resource not found",
-                r#"E_INVARG("Error: Resource not found")"#,
+            OperationResponse::not_found(
+                "Not Found - User not found",
+                r#"E_INVIND("User 'alice' not found")"#,
             ),
-            OperationResponse::new(
-                500,
+            OperationResponse::internal_error(
                 "Internal Server Error - Database or system error",
-                r#"E_INVARG("Error: Database error: operation failed")"#,
+                r#"E_INVARG("Serialization error: JSON parse error")"#,
             ),
         ]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!(
             "Executing user/generate_api_key operation for user: {}",
             user.id
@@ -129,12 +129,11 @@ player:tell("New API key for alice: ", api_key);"#
                 target
             } else {
                 // Generating for another user requires ManageApiKeys permission
-                if !user.has_permission(&Permission::ManageApiKeys) {
-                    error!("User {} does not have ManageApiKeys permission", user.id);
-                    return moor_var::v_error(moor_var::E_INVARG.msg(
-                        "Error: You do not have permission to manage API keys for other users",
-                    ));
-                }
+                require_permission(
+                    user,
+                    Permission::ManageApiKeys,
+                    "manage API keys for other users",
+                )?;
                 target
             }
         };
@@ -144,11 +143,11 @@ player:tell("New API key for alice: ", api_key);"#
             Ok(api_key) => {
                 info!("Generated new API key for user '{}'", target_user_id);
                 // Return just the API key string
-                moor_var::v_str(&api_key)
+                Ok(moor_var::v_str(&api_key))
             }
             Err(e) => {
                 error!("Failed to generate API key: {}", e);
-                moor_var::v_error(moor_var::E_INVARG.msg(format!("Error: {e}")))
+                Err(e.into())
             }
         }
     }
@@ -1,4 +1,7 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use crate::providers::user::UserProvider;
 use axum::http::Method;
 use std::sync::Arc;
@@ -23,6 +26,10 @@ impl Operation for UserListOperation {
         "user/list"
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn response_content_type(&self) -> &'static str {
         "text/x-moo"
     }
@@ -70,37 +77,22 @@ endfor"#
                 "Operation executed successfully",
                 r#""Operation completed successfully""#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - Invalid arguments",
-                r#"E_INVARG("Error: Invalid operation arguments")"#,
+            OperationResponse::forbidden(
+                "Forbidden - User lacks ManagePermissions permission",
+                r#"E_PERM("User 'player' does not have permission to list users")"#,
             ),
-            OperationResponse::new(
-                404,
-                "Not Found - Resource not found",
-                r#"E_INVARG("Error: Resource not found")"#,
-            ),
-            OperationResponse::new(
-                500,
+            OperationResponse::internal_error(
                 "Internal Server Error - Database or system error",
-                r#"E_INVARG("Error: Database error: operation failed")"#,
+                r#"E_INVARG("Serialization error: JSON parse error")"#,
             ),
         ]
     }
 
-    fn execute(&self, _args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, _args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!("Executing user/list operation for user: {}", user.id);
 
         // Check permission
-        if !user.has_permission(&Permission::ManagePermissions) {
-            error!(
-                "User {} does not have ManagePermissions permission",
-                user.id
-            );
-            return moor_var::v_error(
-                moor_var::E_INVARG.msg("Error: You do not have permission to list users"),
-            );
-        }
+        require_permission(user, Permission::ManagePermissions, "list users")?;
 
         // Get all users
         match self.user_provider.list_users() {
@@ -130,11 +122,11 @@ endfor"#
                 }
 
                 // Return list of users
-                moor_var::v_list(&user_list)
+                Ok(moor_var::v_list(&user_list))
             }
             Err(e) => {
                 error!("Failed to list users: {}", e);
-                moor_var::v_error(moor_var::E_INVARG.msg(format!("Error: {e}")))
+                Err(e.into())
             }
         }
     }
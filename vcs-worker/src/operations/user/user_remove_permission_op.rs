@@ -1,4 +1,7 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use crate::providers::user::UserProvider;
 use axum::http::Method;
 use std::sync::Arc;
@@ -97,40 +100,33 @@ impl Operation for UserRemovePermissionOperation {
                 "Operation executed successfully",
                 r#""Operation completed successfully""#,
             ),
-            OperationResponse::new(
-                400,
-                "Bad Request - Invalid arguments",
-                r#"E_INVARG("Error: Invalid operation arguments")"#,
+            OperationResponse::bad_request(
+                "Bad Request - Missing arguments or unknown permission",
+                r#"E_INVARG("Expected 2 arguments: user_id, permission")"#,
             ),
-            OperationResponse::new(
-                404,
-                "Not Found - Resource not found",
-                r#"E_INVARG("Error: Resource not found")"#,
+            OperationResponse::forbidden(
+                "Forbidden - User lacks ManagePermissions permission",
+                r#"E_PERM("User 'player' does not have permission to manage permissions")"#,
             ),
-            OperationResponse::new(
-                500,
+            OperationResponse::not_found(
+                "Not Found - User not found",
+                r#"E_INVIND("User 'alice' not found")"#,
+            ),
+            OperationResponse::internal_error(
                 "Internal Server Error - Database or system error",
-                r#"E_INVARG("Error: Database error: operation failed")"#,
+                r#"E_INVARG("Serialization error: JSON parse error")"#,
             ),
         ]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!(
             "Executing user/remove_permission operation for user: {}",
             user.id
         );
 
         // Check permission
-        if !user.has_permission(&Permission::ManagePermissions) {
-            error!(
-                "User {} does not have ManagePermissions permission",
-                user.id
-            );
-            return moor_var::v_error(
-                moor_var::E_INVARG.msg("Error: You do not have permission to manage permissions"),
-            );
-        }
+        require_permission(user, Permission::ManagePermissions, "manage permissions")?;
 
         // Validate arguments
         if args.len() < 2 {
@@ -138,9 +134,9 @@ impl Operation for UserRemovePermissionOperation {
                 "Invalid arguments for user/remove_permission: expected 2, got {}",
                 args.len()
             );
-            return moor_var::v_error(
-                moor_var::E_INVARG.msg("Error: Expected 2 arguments: user_id, permission"),
-            );
+            return Err(OperationError::InvalidArgs(
+                "Expected 2 arguments: user_id, permission".to_string(),
+            ));
         }
 
         let target_user_id = &args[0];
@@ -151,7 +147,7 @@ impl Operation for UserRemovePermissionOperation {
             Ok(p) => p,
             Err(e) => {
                 error!("Invalid permission: {}", e);
-                return moor_var::v_error(moor_var::E_INVARG.msg(format!("Error: {e}")));
+                return Err(OperationError::InvalidArgs(e));
             }
         };
 
@@ -166,22 +162,22 @@ impl Operation for UserRemovePermissionOperation {
                         "Removed permission {:?} from user '{}'",
                         permission, target_user_id
                     );
-                    moor_var::v_str(&format!(
+                    Ok(moor_var::v_str(&format!(
                         "Successfully removed permission '{permission_str}' from user '{target_user_id}'"
-                    ))
+                    )))
                 } else {
                     info!(
                         "User '{}' did not have permission {:?}",
                         target_user_id, permission
                     );
-                    moor_var::v_str(&format!(
+                    Ok(moor_var::v_str(&format!(
                         "User '{target_user_id}' did not have permission '{permission_str}'"
-                    ))
+                    )))
                 }
             }
             Err(e) => {
                 error!("Failed to remove permission: {}", e);
-                moor_var::v_error(moor_var::E_INVARG.msg(format!("Error: {e}")))
+                Err(e.into())
             }
         }
     }
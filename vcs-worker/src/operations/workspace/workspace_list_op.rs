@@ -1,11 +1,14 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use axum::http::Method;
 use tracing::{error, info};
 
 use crate::database::{DatabaseRef, ObjectsTreeError};
 use crate::providers::workspace::WorkspaceProvider;
 use crate::types::{Change, ChangeStatus, Permission, User};
-use moor_var::{E_INVARG, Var, v_error, v_int, v_list, v_map, v_str};
+use moor_var::{Var, v_int, v_list, v_map, v_str};
 
 /// Workspace list operation that lists all changes in the workspace (stashed, in review, etc.)
 #[derive(Clone)]
@@ -24,18 +27,9 @@ impl WorkspaceListOperation {
         &self,
         user: &User,
         status_filter: Option<ChangeStatus>,
-    ) -> Result<Vec<Change>, ObjectsTreeError> {
+    ) -> Result<Vec<Change>, OperationError> {
         // Check if user has permission to view workspace changes (using SubmitChanges as it's the closest permission)
-        if !user.has_permission(&Permission::SubmitChanges) {
-            error!(
-                "User '{}' does not have permission to view workspace changes",
-                user.id
-            );
-            return Err(ObjectsTreeError::SerializationError(format!(
-                "User '{}' does not have permission to view workspace changes",
-                user.id
-            )));
-        }
+        require_permission(user, Permission::SubmitChanges, "view workspace changes")?;
 
         info!("User '{}' requesting workspace changes list", user.id);
 
@@ -152,6 +146,10 @@ impl Operation for WorkspaceListOperation {
         "workspace/list"
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn response_content_type(&self) -> &'static str {
         "text/x-moo"
     }
@@ -176,7 +174,12 @@ impl Operation for WorkspaceListOperation {
     }
 
     fn parameters(&self) -> Vec<OperationParameter> {
-        vec![]
+        vec![OperationParameter {
+            name: "status".to_string(),
+            description: "Optional status filter: 'review' or 'idle'. If not provided, lists all workspace changes."
+                .to_string(),
+            required: false,
+        }]
     }
 
     fn examples(&self) -> Vec<OperationExample> {
@@ -235,25 +238,22 @@ player:tell("Changes awaiting review: ", length(review_changes));"#
                 "Operation executed successfully - no changes found",
                 r#"{}"#,
             ),
-            OperationResponse::new(
-                400,
+            OperationResponse::bad_request(
                 "Bad Request - Invalid status filter",
                 r#"E_INVARG("Invalid status filter: pending. Valid options: review, idle")"#,
             ),
-            OperationResponse::new(
-                403,
+            OperationResponse::forbidden(
                 "Forbidden - User lacks permission to view workspace changes",
-                r#"E_INVARG("User 'player123' does not have permission to view workspace changes")"#,
+                r#"E_PERM("User 'player123' does not have permission to view workspace changes")"#,
             ),
-            OperationResponse::new(
-                500,
+            OperationResponse::internal_error(
                 "Internal Server Error - Database or system error",
                 r#"E_INVARG("Database error: failed to list workspace changes")"#,
             ),
         ]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!(
             "Workspace list operation received {} arguments for user: {}",
             args.len(),
@@ -272,7 +272,7 @@ player:tell("Changes awaiting review: ", length(review_changes));"#
                         "Invalid status filter: {}. Valid options: review, idle",
                         args[0]
                     );
-                    return v_error(E_INVARG.msg(format!(
+                    return Err(OperationError::InvalidArgs(format!(
                         "Invalid status filter: {}. Valid options: review, idle",
                         args[0]
                     )));
@@ -291,11 +291,11 @@ player:tell("Changes awaiting review: ", length(review_changes));"#
                     .iter()
                     .map(|change| self.change_to_moo_map(change))
                     .collect();
-                v_list(&change_maps)
+                Ok(v_list(&change_maps))
             }
             Err(e) => {
                 error!("Workspace list operation failed: {}", e);
-                v_error(E_INVARG.msg(format!("{e}")))
+                Err(e)
             }
         }
     }
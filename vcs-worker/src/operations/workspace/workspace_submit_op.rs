@@ -1,11 +1,14 @@
-use crate::operations::{Operation, OperationExample, OperationParameter, OperationRoute};
+use crate::operations::{
+    Operation, OperationError, OperationExample, OperationParameter, OperationRoute,
+    require_permission,
+};
 use axum::http::Method;
 use tracing::{error, info};
 
 use crate::database::{DatabaseRef, ObjectsTreeError};
 use crate::providers::workspace::WorkspaceProvider;
 use crate::types::{Change, Permission, User};
-use moor_var::{E_INVARG, v_error, v_str};
+use moor_var::v_str;
 
 /// Workspace submit operation that accepts a serialized change and stores it for review
 #[derive(Clone)]
@@ -24,22 +27,13 @@ impl WorkspaceSubmitOperation {
         &self,
         serialized_change: &str,
         user: &User,
-    ) -> Result<String, ObjectsTreeError> {
+    ) -> Result<String, OperationError> {
         // Check if user has permission to submit changes
-        if !user.has_permission(&Permission::SubmitChanges) {
-            error!(
-                "User '{}' does not have permission to submit changes",
-                user.id
-            );
-            return Err(ObjectsTreeError::SerializationError(format!(
-                "User '{}' does not have permission to submit changes",
-                user.id
-            )));
-        }
+        require_permission(user, Permission::SubmitChanges, "submit changes")?;
 
         // Deserialize the change from the provided string
         let change: Change = serde_json::from_str(serialized_change).map_err(|e| {
-            ObjectsTreeError::SerializationError(format!("Failed to deserialize change: {e}"))
+            OperationError::InvalidArgs(format!("Failed to deserialize change: {e}"))
         })?;
 
         info!(
@@ -94,7 +88,11 @@ impl Operation for WorkspaceSubmitOperation {
     }
 
     fn parameters(&self) -> Vec<OperationParameter> {
-        vec![]
+        vec![OperationParameter {
+            name: "change".to_string(),
+            description: "The JSON-serialized Change to store for review".to_string(),
+            required: true,
+        }]
     }
 
     fn examples(&self) -> Vec<OperationExample> {
@@ -109,7 +107,8 @@ result = worker_request("vcs", {"workspace/submit", change_json});
             http_curl: Some(
                 r#"curl -X PUT http://localhost:8081/api/workspace/submit \
   -H "Content-Type: application/json" \
-  -d '{"id":"abc123...","name":"my-feature","description":"Added new login system",...}'"#
+  -H "X-API-Key: your-api-key" \
+  -d '{"operation": "workspace/submit", "args": ["{\"id\":\"abc123...\",\"name\":\"my-feature\",...}"]}'"#
                     .to_string(),
             ),
         }]
@@ -122,30 +121,26 @@ result = worker_request("vcs", {"workspace/submit", change_json});
                 "Operation executed successfully",
                 r#""Change 'my-feature' (change-abc123) successfully submitted for review""#,
             ),
-            OperationResponse::new(
-                400,
+            OperationResponse::bad_request(
                 "Bad Request - No serialized change argument provided",
                 r#"E_INVARG("Workspace submit operation requires a serialized change argument")"#,
             ),
-            OperationResponse::new(
-                400,
+            OperationResponse::bad_request(
                 "Bad Request - Failed to deserialize change",
                 r#"E_INVARG("Failed to deserialize change: invalid JSON at line 1 column 5")"#,
             ),
-            OperationResponse::new(
-                403,
+            OperationResponse::forbidden(
                 "Forbidden - User lacks permission to submit changes",
-                r#"E_INVARG("User 'player123' does not have permission to submit changes")"#,
+                r#"E_PERM("User 'player123' does not have permission to submit changes")"#,
             ),
-            OperationResponse::new(
-                500,
+            OperationResponse::internal_error(
                 "Internal Server Error - Database error",
                 r#"E_INVARG("Database error: failed to store workspace change")"#,
             ),
         ]
     }
 
-    fn execute(&self, args: Vec<String>, user: &User) -> moor_var::Var {
+    fn execute(&self, args: Vec<String>, user: &User) -> Result<moor_var::Var, OperationError> {
         info!(
             "Workspace submit operation received {} arguments for user: {}",
             args.len(),
@@ -154,9 +149,9 @@ result = worker_request("vcs", {"workspace/submit", change_json});
 
         if args.is_empty() {
             error!("Workspace submit operation requires a serialized change argument");
-            return v_error(
-                E_INVARG.msg("Workspace submit operation requires a serialized change argument"),
-            );
+            return Err(OperationError::InvalidArgs(
+                "Workspace submit operation requires a serialized change argument".to_string(),
+            ));
         }
 
         let serialized_change = &args[0];
@@ -164,11 +159,11 @@ result = worker_request("vcs", {"workspace/submit", change_json});
         match self.process_workspace_submit(serialized_change, user) {
             Ok(message) => {
                 info!("Workspace submit operation completed successfully");
-                v_str(&message)
+                Ok(v_str(&message))
             }
             Err(e) => {
                 error!("Workspace submit operation failed: {}", e);
-                v_error(E_INVARG.msg(format!("{e}")))
+                Err(e)
             }
         }
     }
@@ -0,0 +1,169 @@
+use fjall::Partition;
+use std::sync::Mutex;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use super::{ProviderError, ProviderResult};
+use crate::types::{BackupRun, BackupRunStatus};
+
+/// Backup provider - records git backup runs so their outcome outlives the thread that ran them
+pub trait BackupProvider: Send + Sync {
+    /// Record a new queued run, assigning it the next run ID
+    fn create_run(&self, trigger: &str) -> ProviderResult<BackupRun>;
+
+    /// Store an updated run
+    fn update_run(&self, run: &BackupRun) -> ProviderResult<()>;
+
+    /// Get a run by ID
+    fn get_run(&self, id: u64) -> ProviderResult<Option<BackupRun>>;
+
+    /// List the most recent runs, newest first
+    fn list_runs(&self, limit: usize) -> ProviderResult<Vec<BackupRun>>;
+
+    /// Get the most recent run with one of the given statuses
+    fn last_run_with_status(
+        &self,
+        statuses: &[BackupRunStatus],
+    ) -> ProviderResult<Option<BackupRun>>;
+
+    /// Mark runs left queued or running by a previous process as failed
+    fn fail_unfinished_runs(&self) -> ProviderResult<usize>;
+}
+
+pub struct BackupProviderImpl {
+    // Run records track background work rather than repository state, so they are
+    // written straight to fjall and never join an operation's write batch
+    backups_tree: Partition,
+    flush_sender: mpsc::UnboundedSender<()>,
+    // Serializes run ID allocation
+    id_lock: Mutex<()>,
+}
+
+impl BackupProviderImpl {
+    pub fn new(backups_tree: Partition, flush_sender: mpsc::UnboundedSender<()>) -> Self {
+        Self {
+            backups_tree,
+            flush_sender,
+            id_lock: Mutex::new(()),
+        }
+    }
+
+    /// Runs are keyed by zero-padded ID so keys sort in run order
+    fn run_key(id: u64) -> Vec<u8> {
+        format!("run:{id:020}").into_bytes()
+    }
+
+    fn deserialize_run(value: &[u8]) -> ProviderResult<BackupRun> {
+        serde_json::from_slice(value).map_err(|e| {
+            ProviderError::SerializationError(format!("JSON deserialization error: {e}"))
+        })
+    }
+
+    /// Iterate over all runs, newest first
+    fn runs_newest_first(&self) -> impl Iterator<Item = ProviderResult<BackupRun>> + '_ {
+        self.backups_tree
+            .prefix("run:")
+            .rev()
+            .map(|result| -> ProviderResult<BackupRun> {
+                let (_, value) = result?;
+                Self::deserialize_run(&value)
+            })
+    }
+}
+
+impl BackupProvider for BackupProviderImpl {
+    fn create_run(&self, trigger: &str) -> ProviderResult<BackupRun> {
+        let _guard = self
+            .id_lock
+            .lock()
+            .map_err(|_| ProviderError::InvalidOperation("Backup run lock poisoned".to_string()))?;
+
+        let id = match self.runs_newest_first().next() {
+            Some(last) => last?.id + 1,
+            None => 1,
+        };
+
+        let run = BackupRun {
+            id,
+            trigger: trigger.to_string(),
+            queued_at: crate::util::current_unix_timestamp(),
+            started_at: None,
+            finished_at: None,
+            status: BackupRunStatus::Queued,
+            commit: None,
+            commits: 0,
+            object_count: 0,
+            error: None,
+        };
+        self.update_run(&run)?;
+
+        debug!("Queued backup run {} (triggered by {})", id, trigger);
+        Ok(run)
+    }
+
+    fn update_run(&self, run: &BackupRun) -> ProviderResult<()> {
+        let json = serde_json::to_vec(run).map_err(|e| {
+            ProviderError::SerializationError(format!("JSON serialization error: {e}"))
+        })?;
+        self.backups_tree.insert(Self::run_key(run.id), json)?;
+
+        // Request background flush
+        if self.flush_sender.send(()).is_err() {
+            warn!("Failed to request flush for backup run {}", run.id);
+        }
+
+        Ok(())
+    }
+
+    fn get_run(&self, id: u64) -> ProviderResult<Option<BackupRun>> {
+        match self.backups_tree.get(Self::run_key(id))? {
+            Some(value) => Ok(Some(Self::deserialize_run(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn list_runs(&self, limit: usize) -> ProviderResult<Vec<BackupRun>> {
+        self.runs_newest_first().take(limit).collect()
+    }
+
+    fn last_run_with_status(
+        &self,
+        statuses: &[BackupRunStatus],
+    ) -> ProviderResult<Option<BackupRun>> {
+        for run in self.runs_newest_first() {
+            let run = run?;
+            if statuses.contains(&run.status) {
+                return Ok(Some(run));
+            }
+        }
+        Ok(None)
+    }
+
+    fn fail_unfinished_runs(&self) -> ProviderResult<usize> {
+        let unfinished: Vec<BackupRun> = self
+            .runs_newest_first()
+            .filter(|run| {
+                !matches!(
+                    run,
+                    Ok(BackupRun {
+                        status: BackupRunStatus::Succeeded | BackupRunStatus::Failed,
+                        ..
+                    })
+                )
+            })
+            .collect::<ProviderResult<_>>()?;
+
+        let count = unfinished.len();
+        for mut run in unfinished {
+            run.status = BackupRunStatus::Failed;
+            run.finished_at = Some(crate::util::current_unix_timestamp());
+            run.error = Some("Interrupted by a worker restart".to_string());
+            self.update_run(&run)?;
+        }
+
+        if count > 0 {
+            info!("Marked {} interrupted backup runs as failed", count);
+        }
+        Ok(count)
+    }
+}
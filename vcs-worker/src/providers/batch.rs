@@ -0,0 +1,459 @@
+//! Cross-partition write batches
+//!
+//! Providers read and write through a [`BatchPartition`] rather than a bare fjall
+//! `Partition`. Outside a batch every write goes straight to fjall. While a batch is
+//! open on the current thread (see `Database::write_batch`), writes are staged instead
+//! and committed together as one fjall batch, so an operation that touches objects,
+//! refs and changes lands all-or-nothing. Reads on the same thread see staged writes;
+//! other threads see none of them until the batch commits.
+//!
+//! Batches are also optimistic transactions. A digest of the first value a batch reads
+//! for each key (through `get`), and of everything it first finds under each scanned
+//! prefix (through `iter` and `prefix`), is remembered. Before the batch commits every
+//! remembered key and prefix is read again under a global commit lock. If another batch
+//! changed one of them in the meantime the commit fails with [`CommitError::Conflict`]
+//! instead of overwriting that batch's work, and the caller can retry from scratch.
+//!
+//! Work that cannot be retried cheaply, such as a garbage collection sweeping every
+//! blob, runs [`exclusively`] instead: no batch commits until it is done.
+
+use fjall::{Keyspace, Partition, Slice};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Key/value pairs yielded by partition scans
+pub type KvIter = Box<dyn DoubleEndedIterator<Item = fjall::Result<(Slice, Slice)>>>;
+
+/// Writes staged against one partition; `None` marks a removal
+type StagedWrites = BTreeMap<Vec<u8>, Option<Vec<u8>>>;
+
+/// Digests of the values a batch read from one partition, `None` if absent
+type ReadSet = BTreeMap<Vec<u8>, Option<blake3::Hash>>;
+
+/// Digests of what a batch found under each prefix it scanned in one partition
+type ScanSet = BTreeMap<Vec<u8>, blake3::Hash>;
+
+/// What a batch has done to one partition
+struct Touched {
+    partition: Partition,
+    writes: StagedWrites,
+    reads: ReadSet,
+    scans: ScanSet,
+}
+
+/// State of the batch open on a thread
+#[derive(Default)]
+struct Batch {
+    /// Keyed by partition ID
+    partitions: HashMap<u64, Touched>,
+    /// Work deferred until the batch's writes are visible to other threads
+    after_commit: Vec<Box<dyn FnOnce() + Send>>,
+}
+
+thread_local! {
+    /// The batch open on this thread
+    static OPEN_BATCH: RefCell<Option<Batch>> = const { RefCell::new(None) };
+
+    /// Whether this thread is running work `exclusively`, holding the commit lock
+    static EXCLUSIVE: Cell<bool> = const { Cell::new(false) };
+}
+
+static NEXT_PARTITION_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Held while a batch validates its reads and commits, so no other batch can commit in
+/// between
+static COMMIT_LOCK: Mutex<()> = Mutex::new(());
+
+/// Why a batch could not be committed
+#[derive(Debug, thiserror::Error)]
+pub enum CommitError {
+    #[error("Fjall database error: {0}")]
+    Fjall(#[from] fjall::Error),
+    /// A key the batch read was changed by another batch before this one committed
+    #[error("{0}")]
+    Conflict(String),
+}
+
+/// Run `f` once the batch open on this thread commits, or straight away if none is
+/// open. If the batch fails, `f` is dropped without running.
+pub fn after_commit(f: impl FnOnce() + Send + 'static) {
+    let deferred = OPEN_BATCH.with(|batch| match batch.borrow_mut().as_mut() {
+        Some(batch) => {
+            batch.after_commit.push(Box::new(f));
+            None
+        }
+        None => Some(f),
+    });
+    if let Some(f) = deferred {
+        f();
+    }
+}
+
+/// Run `f` with the batch open on this thread set aside, so its writes go straight to
+/// fjall and are kept whatever becomes of the batch
+pub fn outside_batch<T>(f: impl FnOnce() -> T) -> T {
+    /// Puts the set-aside batch back, even if `f` panics
+    struct Resume(Option<Batch>);
+    impl Drop for Resume {
+        fn drop(&mut self) {
+            if let Some(suspended) = self.0.take() {
+                OPEN_BATCH.with(|batch| *batch.borrow_mut() = Some(suspended));
+            }
+        }
+    }
+
+    let _resume = Resume(OPEN_BATCH.with(|batch| batch.borrow_mut().take()));
+    f()
+}
+
+/// Run `f` on a thread of its own, lending it the batch open on this thread, if any, so its
+/// writes and deferred work join the batch as if `f` had run here
+pub fn on_other_thread<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+    let lent = OPEN_BATCH.with(|batch| batch.borrow_mut().take());
+    let (result, returned) = std::thread::scope(|scope| {
+        scope
+            .spawn(move || {
+                OPEN_BATCH.with(|batch| *batch.borrow_mut() = lent);
+                let result = f();
+                (result, OPEN_BATCH.with(|batch| batch.borrow_mut().take()))
+            })
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    });
+    OPEN_BATCH.with(|batch| *batch.borrow_mut() = returned);
+    result
+}
+
+/// Run `f` outside the batch open on this thread, if any, while holding the commit lock,
+/// so no batch commits while `f` runs and `f` sees and writes a state nothing else can
+/// change. Writes made outside batches are not held back. `f` must not commit a batch of
+/// its own, which would wait for the lock forever; debug builds panic if it tries.
+pub fn exclusively<T>(f: impl FnOnce() -> T) -> T {
+    /// Clears the exclusive flag, even if `f` panics
+    struct Release;
+    impl Drop for Release {
+        fn drop(&mut self) {
+            EXCLUSIVE.with(|exclusive| exclusive.set(false));
+        }
+    }
+
+    let _guard = COMMIT_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    EXCLUSIVE.with(|exclusive| exclusive.set(true));
+    let _release = Release;
+    outside_batch(f)
+}
+
+/// Digest of a value, as remembered in a read set
+fn value_digest(value: &Option<Slice>) -> Option<blake3::Hash> {
+    value.as_deref().map(blake3::hash)
+}
+
+/// Digest of the entries found by a scan, as remembered in a scan set
+fn scan_digest<'a>(entries: impl IntoIterator<Item = &'a (Slice, Slice)>) -> blake3::Hash {
+    let mut hasher = blake3::Hasher::new();
+    for (key, value) in entries {
+        hasher.update(&(key.len() as u64).to_le_bytes());
+        hasher.update(key);
+        hasher.update(blake3::hash(value).as_bytes());
+    }
+    hasher.finalize()
+}
+
+/// A fjall partition whose writes join the batch open on the current thread, if any
+#[derive(Clone)]
+pub struct BatchPartition {
+    id: u64,
+    partition: Partition,
+}
+
+impl BatchPartition {
+    pub fn new(partition: Partition) -> Self {
+        Self {
+            id: NEXT_PARTITION_ID.fetch_add(1, Ordering::Relaxed),
+            partition,
+        }
+    }
+
+    /// This partition's entry in the open batch, created on first use
+    fn touched<'a>(&self, batch: &'a mut Batch) -> &'a mut Touched {
+        batch.partitions.entry(self.id).or_insert_with(|| Touched {
+            partition: self.partition.clone(),
+            writes: BTreeMap::new(),
+            reads: BTreeMap::new(),
+            scans: BTreeMap::new(),
+        })
+    }
+
+    /// Look up a staged write: `Some(None)` if the key was removed in the open batch
+    fn staged(&self, key: &[u8]) -> Option<Option<Vec<u8>>> {
+        OPEN_BATCH.with(|batch| {
+            batch
+                .borrow()
+                .as_ref()
+                .and_then(|batch| batch.partitions.get(&self.id))
+                .and_then(|touched| touched.writes.get(key).cloned())
+        })
+    }
+
+    /// Stage a write in the open batch, returning false if no batch is open
+    fn stage(&self, key: &[u8], value: Option<&[u8]>) -> bool {
+        OPEN_BATCH.with(|batch| match batch.borrow_mut().as_mut() {
+            Some(batch) => {
+                self.touched(batch)
+                    .writes
+                    .insert(key.to_vec(), value.map(<[u8]>::to_vec));
+                true
+            }
+            None => false,
+        })
+    }
+
+    /// Remember the first value the open batch read for a key, for validation at commit
+    fn record_read(&self, key: &[u8], value: &Option<Slice>) {
+        OPEN_BATCH.with(|batch| {
+            if let Some(batch) = batch.borrow_mut().as_mut() {
+                self.touched(batch)
+                    .reads
+                    .entry(key.to_vec())
+                    .or_insert_with(|| value_digest(value));
+            }
+        });
+    }
+
+    /// Whether a batch is open on this thread
+    fn in_batch() -> bool {
+        OPEN_BATCH.with(|batch| batch.borrow().is_some())
+    }
+
+    /// Scan `prefix` in fjall and, inside a batch, remember what was found there for
+    /// validation at commit. The scan is read in full so the caller sees exactly the
+    /// entries that were remembered.
+    fn scan(&self, prefix: &[u8]) -> KvIter {
+        let scan = self.partition.prefix(prefix);
+        if !Self::in_batch() {
+            return Box::new(scan);
+        }
+
+        let entries = match scan.collect::<fjall::Result<Vec<_>>>() {
+            Ok(entries) => entries,
+            Err(e) => return Box::new(std::iter::once(Err(e))),
+        };
+        let digest = scan_digest(&entries);
+        OPEN_BATCH.with(|batch| {
+            if let Some(batch) = batch.borrow_mut().as_mut() {
+                self.touched(batch)
+                    .scans
+                    .entry(prefix.to_vec())
+                    .or_insert(digest);
+            }
+        });
+        Box::new(entries.into_iter().map(Ok))
+    }
+
+    /// Whether the open batch has staged writes against this partition
+    fn has_staged_writes(&self) -> bool {
+        OPEN_BATCH.with(|batch| {
+            batch
+                .borrow()
+                .as_ref()
+                .and_then(|batch| batch.partitions.get(&self.id))
+                .is_some_and(|touched| !touched.writes.is_empty())
+        })
+    }
+
+    /// Overlay staged writes under `prefix` onto a scan of the partition
+    fn merged(&self, prefix: &[u8]) -> KvIter {
+        let scan = self.scan(prefix);
+        let staged: Option<Vec<(Vec<u8>, Option<Vec<u8>>)>> = OPEN_BATCH.with(|batch| {
+            batch
+                .borrow()
+                .as_ref()
+                .and_then(|batch| batch.partitions.get(&self.id))
+                .filter(|touched| !touched.writes.is_empty())
+                .map(|touched| {
+                    touched
+                        .writes
+                        .range(prefix.to_vec()..)
+                        .take_while(|(key, _)| key.starts_with(prefix))
+                        .map(|(key, value)| (key.clone(), value.clone()))
+                        .collect()
+                })
+        });
+        let Some(staged) = staged else {
+            return scan;
+        };
+
+        let mut entries = BTreeMap::new();
+        for result in scan {
+            match result {
+                Ok((key, value)) => {
+                    entries.insert(key.to_vec(), value);
+                }
+                Err(e) => return Box::new(std::iter::once(Err(e))),
+            }
+        }
+        for (key, value) in staged {
+            match value {
+                Some(value) => {
+                    entries.insert(key, Slice::from(value));
+                }
+                None => {
+                    entries.remove(&key);
+                }
+            }
+        }
+        Box::new(
+            entries
+                .into_iter()
+                .map(|(key, value)| Ok((Slice::from(key), value))),
+        )
+    }
+
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> fjall::Result<Option<Slice>> {
+        match self.staged(key.as_ref()) {
+            Some(value) => Ok(value.map(Slice::from)),
+            None => {
+                let value = self.partition.get(key.as_ref())?;
+                self.record_read(key.as_ref(), &value);
+                Ok(value)
+            }
+        }
+    }
+
+    pub fn insert<K: AsRef<[u8]>, V: AsRef<[u8]>>(&self, key: K, value: V) -> fjall::Result<()> {
+        if self.stage(key.as_ref(), Some(value.as_ref())) {
+            return Ok(());
+        }
+        self.partition.insert(key.as_ref(), value.as_ref())
+    }
+
+    pub fn remove<K: AsRef<[u8]>>(&self, key: K) -> fjall::Result<()> {
+        if self.stage(key.as_ref(), None) {
+            return Ok(());
+        }
+        self.partition.remove(key.as_ref())
+    }
+
+    pub fn iter(&self) -> KvIter {
+        self.merged(&[])
+    }
+
+    pub fn prefix<K: AsRef<[u8]>>(&self, prefix: K) -> KvIter {
+        self.merged(prefix.as_ref())
+    }
+
+    pub fn is_empty(&self) -> fjall::Result<bool> {
+        Ok(self.iter().next().transpose()?.is_none())
+    }
+
+    pub fn len(&self) -> fjall::Result<usize> {
+        if !self.has_staged_writes() {
+            return self.partition.len();
+        }
+        let mut count = 0;
+        for result in self.iter() {
+            result?;
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+/// A batch open on the current thread. Dropping it without committing discards every
+/// staged write and deferred callback, so an operation that fails (or panics) part way
+/// leaves nothing behind.
+pub struct OpenBatch {
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl OpenBatch {
+    /// Open a batch on this thread, or return None if one is already open (nested
+    /// batches join the outer one and commit with it)
+    pub fn open() -> Option<Self> {
+        OPEN_BATCH.with(|batch| {
+            let mut batch = batch.borrow_mut();
+            if batch.is_some() {
+                return None;
+            }
+            *batch = Some(Batch::default());
+            Some(Self {
+                _not_send: std::marker::PhantomData,
+            })
+        })
+    }
+
+    /// Check that nothing the batch read has changed since, then write every staged
+    /// change to fjall atomically and run the deferred callbacks. Returns the number of
+    /// writes. A batch that only read commits nothing and is not validated.
+    pub fn commit(self, keyspace: &Keyspace) -> Result<usize, CommitError> {
+        let Batch {
+            partitions,
+            after_commit,
+        } = OPEN_BATCH
+            .with(|batch| batch.borrow_mut().take())
+            .unwrap_or_default();
+
+        let writes = partitions
+            .values()
+            .map(|touched| touched.writes.len())
+            .sum::<usize>();
+        if writes > 0 {
+            debug_assert!(
+                !EXCLUSIVE.with(Cell::get),
+                "a write batch must not commit inside exclusively"
+            );
+            let _guard = COMMIT_LOCK
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+            for touched in partitions.values() {
+                for (key, observed) in &touched.reads {
+                    if value_digest(&touched.partition.get(key)?) != *observed {
+                        return Err(CommitError::Conflict(format!(
+                            "'{}' was changed by another operation",
+                            String::from_utf8_lossy(key)
+                        )));
+                    }
+                }
+                for (prefix, observed) in &touched.scans {
+                    let entries = touched
+                        .partition
+                        .prefix(prefix)
+                        .collect::<fjall::Result<Vec<_>>>()?;
+                    if scan_digest(&entries) != *observed {
+                        return Err(CommitError::Conflict(format!(
+                            "Keys under '{}' were changed by another operation",
+                            String::from_utf8_lossy(prefix)
+                        )));
+                    }
+                }
+            }
+
+            let mut batch = keyspace.batch();
+            for touched in partitions.into_values() {
+                for (key, value) in touched.writes {
+                    match value {
+                        Some(value) => batch.insert(&touched.partition, key, value),
+                        None => batch.remove(&touched.partition, key),
+                    }
+                }
+            }
+            batch.commit()?;
+        }
+
+        for f in after_commit {
+            f();
+        }
+        Ok(writes)
+    }
+}
+
+impl Drop for OpenBatch {
+    fn drop(&mut self) {
+        OPEN_BATCH.with(|batch| batch.borrow_mut().take());
+    }
+}
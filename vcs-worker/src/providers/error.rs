@@ -13,11 +13,12 @@ pub enum ProviderError {
     CompilationError(#[from] moor_compiler::ObjDefParseError),
     #[error("Serialization error: {0}")]
     SerializationError(String),
-    #[error("Object not found: {0}")]
-    #[allow(dead_code)]
+    #[error("{0}")]
     ObjectNotFound(String),
     #[error("Invalid operation: {0}")]
     InvalidOperation(String),
+    #[error("Corrupted data: {0}")]
+    Corrupted(String),
 }
 
 /// Alias for Result using ProviderError
@@ -2,6 +2,7 @@ use fjall::Partition;
 use tokio::sync::mpsc;
 use tracing::{info, warn};
 
+use super::batch::BatchPartition;
 use super::{ProviderError, ProviderResult};
 
 /// Combined Index and Changes provider - manages both change storage and ordering
@@ -81,28 +82,26 @@ pub trait IndexProvider: Send + Sync {
         change_id: &str,
     ) -> ProviderResult<std::collections::HashMap<String, u64>>;
 
-    // ===== SOURCE METHODS =====
-    /// Get the source URL if this is a clone
-    fn get_source(&self) -> ProviderResult<Option<String>>;
+    // ===== REMOTE METHODS =====
+    /// Get a remote by name
+    fn get_remote(&self, name: &str) -> ProviderResult<Option<crate::types::Remote>>;
 
-    /// Set the source URL for this clone
-    fn set_source(&self, url: &str) -> ProviderResult<()>;
+    /// List every remote, ordered by name
+    fn list_remotes(&self) -> ProviderResult<Vec<crate::types::Remote>>;
 
-    // ===== EXTERNAL USER METHODS =====
-    /// Get the external user API key for outbound requests
-    fn get_external_user_api_key(&self) -> ProviderResult<Option<String>>;
+    /// Add a remote, replacing any remote with the same name
+    fn set_remote(&self, remote: &crate::types::Remote) -> ProviderResult<()>;
 
-    /// Set the external user API key for outbound requests
-    fn set_external_user_api_key(&self, key: &str) -> ProviderResult<()>;
+    /// Remove a remote, returning whether it existed
+    fn remove_remote(&self, name: &str) -> ProviderResult<bool>;
 
-    /// Get the external user ID
-    fn get_external_user_id(&self) -> ProviderResult<Option<String>>;
-
-    /// Set the external user ID
-    fn set_external_user_id(&self, user_id: &str) -> ProviderResult<()>;
+    /// Move the legacy single source URL and external user credentials to the default
+    /// remote, returning whether there was anything to migrate
+    fn migrate_legacy_source(&self) -> ProviderResult<bool>;
 
     // ===== CLEAR METHODS =====
-    /// Clear all changes and index data
+    /// Clear all changes and index data. Remotes are configuration rather than history
+    /// and are kept.
     fn clear(&self) -> ProviderResult<()>;
 
     // ===== SIZE METHODS =====
@@ -116,7 +115,7 @@ pub trait IndexProvider: Send + Sync {
 /// Implementation of the IndexProvider trait using two separate Fjall partitions:
 ///
 /// **Architecture:**
-/// - `working_index`: Tracks the active working set (change_order list, top_change pointer, source_url)
+/// - `working_index`: Tracks the active working set (change_order list, top_change pointer, remotes)
 /// - `history_storage`: Permanent storage for all Change objects (never deleted)
 ///
 /// **Key Distinction:**
@@ -126,11 +125,11 @@ pub struct IndexProviderImpl {
     /// **Working Index Metadata** - Stores the change index structure:
     /// - `change_order`: Chronological list of ALL changes (merged history + current local, if any)
     /// - `top_change`: Pointer to the ONE active Local change (if one exists)
-    /// - `source_url`: Optional remote source for this repository
+    /// - `remote/{name}`: Named remote repositories with their credentials
     ///
     /// When a change is approved: stays in change_order (becomes part of history), top_change cleared
     /// When a change is abandoned: removed from change_order entirely, top_change cleared
-    working_index: Partition,
+    working_index: BatchPartition,
 
     /// **Permanent History Storage** - Stores Change objects:
     /// - Stores all committed/merged changes permanently
@@ -139,7 +138,7 @@ pub struct IndexProviderImpl {
     /// - Contains changes in all states (Local, Merged, Review, Idle)
     ///
     /// This is the authoritative source of truth for change data
-    history_storage: Partition,
+    history_storage: BatchPartition,
 
     /// Channel for requesting background database flushes
     flush_sender: mpsc::UnboundedSender<()>,
@@ -152,17 +151,20 @@ impl IndexProviderImpl {
         flush_sender: mpsc::UnboundedSender<()>,
     ) -> Self {
         Self {
-            working_index: index_tree,
-            history_storage: changes_tree,
+            working_index: BatchPartition::new(index_tree),
+            history_storage: BatchPartition::new(changes_tree),
             flush_sender,
         }
     }
 
     const ORDER_KEY: &'static str = "change_order";
     const TOP_KEY: &'static str = "top_change";
-    const SOURCE_KEY: &'static str = "source_url";
-    const EXTERNAL_USER_API_KEY: &'static str = "external_user_api_key";
-    const EXTERNAL_USER_ID: &'static str = "external_user_id";
+    const REMOTE_PREFIX: &'static str = "remote/";
+
+    /// Keys the single source and its credentials were kept under before named remotes
+    const LEGACY_SOURCE_KEY: &'static str = "source_url";
+    const LEGACY_EXTERNAL_USER_API_KEY: &'static str = "external_user_api_key";
+    const LEGACY_EXTERNAL_USER_ID: &'static str = "external_user_id";
 
     // ===== DRY HELPER METHODS =====
 
@@ -176,6 +178,21 @@ impl IndexProviderImpl {
         }
     }
 
+    /// Key a remote is stored under
+    fn remote_key(name: &str) -> String {
+        format!("{}{name}", Self::REMOTE_PREFIX)
+    }
+
+    /// Read a UTF-8 value, as the legacy source keys were stored
+    fn get_string(&self, key: &str) -> ProviderResult<Option<String>> {
+        let Some(data) = self.working_index.get(key)? else {
+            return Ok(None);
+        };
+        String::from_utf8(data.to_vec())
+            .map(Some)
+            .map_err(|e| ProviderError::SerializationError(e.to_string()))
+    }
+
     /// Save the change order to storage
     fn save_change_order(&self, order: &Vec<String>) -> ProviderResult<()> {
         self.working_index.insert(
@@ -474,7 +491,8 @@ impl IndexProvider for IndexProviderImpl {
             modified_objects: Vec::new(),
             deleted_objects: Vec::new(),
             renamed_objects: Vec::new(),
-            index_change_id: None,
+            // The index tip this change is based on
+            index_change_id: self.get_change_order_internal()?.last().cloned(),
             verb_rename_hints: Vec::new(),
             property_rename_hints: Vec::new(),
         };
@@ -737,64 +755,90 @@ impl IndexProvider for IndexProviderImpl {
         Ok(processor.objects)
     }
 
-    fn get_source(&self) -> ProviderResult<Option<String>> {
-        if let Some(data) = self.working_index.get(Self::SOURCE_KEY)? {
-            Ok(Some(String::from_utf8(data.to_vec()).map_err(|e| {
-                ProviderError::SerializationError(e.to_string())
-            })?))
-        } else {
-            Ok(None)
+    fn get_remote(&self, name: &str) -> ProviderResult<Option<crate::types::Remote>> {
+        let Some(data) = self.working_index.get(Self::remote_key(name))? else {
+            return Ok(None);
+        };
+        serde_json::from_slice(&data)
+            .map(Some)
+            .map_err(|e| ProviderError::SerializationError(e.to_string()))
+    }
+
+    fn list_remotes(&self) -> ProviderResult<Vec<crate::types::Remote>> {
+        let mut remotes = Vec::new();
+        for result in self.working_index.prefix(Self::REMOTE_PREFIX) {
+            let (_, data) = result?;
+            remotes.push(
+                serde_json::from_slice(&data)
+                    .map_err(|e| ProviderError::SerializationError(e.to_string()))?,
+            );
         }
+        Ok(remotes)
     }
 
-    fn set_source(&self, url: &str) -> ProviderResult<()> {
-        self.working_index
-            .insert(Self::SOURCE_KEY, url.as_bytes())?;
-        info!("Set source URL to: {}", url);
+    fn set_remote(&self, remote: &crate::types::Remote) -> ProviderResult<()> {
+        self.working_index.insert(
+            Self::remote_key(&remote.name),
+            serde_json::to_vec(remote)
+                .map_err(|e| ProviderError::SerializationError(e.to_string()))?,
+        )?;
+        info!("Set remote '{}' to: {}", remote.name, remote.url);
         Ok(())
     }
 
-    fn get_external_user_api_key(&self) -> ProviderResult<Option<String>> {
-        if let Some(data) = self.working_index.get(Self::EXTERNAL_USER_API_KEY)? {
-            Ok(Some(String::from_utf8(data.to_vec()).map_err(|e| {
-                ProviderError::SerializationError(e.to_string())
-            })?))
-        } else {
-            Ok(None)
+    fn remove_remote(&self, name: &str) -> ProviderResult<bool> {
+        let key = Self::remote_key(name);
+        if self.working_index.get(&key)?.is_none() {
+            return Ok(false);
         }
+        self.working_index.remove(&key)?;
+        info!("Removed remote '{}'", name);
+        Ok(true)
     }
 
-    fn set_external_user_api_key(&self, key: &str) -> ProviderResult<()> {
-        self.working_index
-            .insert(Self::EXTERNAL_USER_API_KEY, key.as_bytes())?;
-        info!("Set external user API key");
-        Ok(())
-    }
+    fn migrate_legacy_source(&self) -> ProviderResult<bool> {
+        let Some(url) = self.get_string(Self::LEGACY_SOURCE_KEY)? else {
+            return Ok(false);
+        };
 
-    fn get_external_user_id(&self) -> ProviderResult<Option<String>> {
-        if let Some(data) = self.working_index.get(Self::EXTERNAL_USER_ID)? {
-            Ok(Some(String::from_utf8(data.to_vec()).map_err(|e| {
-                ProviderError::SerializationError(e.to_string())
-            })?))
-        } else {
-            Ok(None)
+        // A default remote added since takes precedence over the legacy source
+        if self.get_remote(crate::types::DEFAULT_REMOTE)?.is_none() {
+            self.set_remote(&crate::types::Remote {
+                name: crate::types::DEFAULT_REMOTE.to_string(),
+                url,
+                api_key: self.get_string(Self::LEGACY_EXTERNAL_USER_API_KEY)?,
+                user_id: self.get_string(Self::LEGACY_EXTERNAL_USER_ID)?,
+            })?;
         }
-    }
 
-    fn set_external_user_id(&self, user_id: &str) -> ProviderResult<()> {
-        self.working_index
-            .insert(Self::EXTERNAL_USER_ID, user_id.as_bytes())?;
-        info!("Set external user ID to: {}", user_id);
-        Ok(())
+        for key in [
+            Self::LEGACY_SOURCE_KEY,
+            Self::LEGACY_EXTERNAL_USER_API_KEY,
+            Self::LEGACY_EXTERNAL_USER_ID,
+        ] {
+            self.working_index.remove(key)?;
+        }
+
+        // Request background flush
+        if self.flush_sender.send(()).is_err() {
+            warn!("Failed to request background flush - channel closed");
+        }
+
+        info!(
+            "Migrated legacy source URL to remote '{}'",
+            crate::types::DEFAULT_REMOTE
+        );
+        Ok(true)
     }
 
     fn clear(&self) -> ProviderResult<()> {
-        // Clear the index tree (change order, top change, source)
+        // Clear the index tree (change order, top change), keeping the remotes
         let index_keys: Vec<_> = self
             .working_index
             .iter()
             .filter_map(|result| result.ok())
             .map(|(key, _)| key.to_vec())
+            .filter(|key| !key.starts_with(Self::REMOTE_PREFIX.as_bytes()))
             .collect();
 
         for key in index_keys {
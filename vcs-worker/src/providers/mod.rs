@@ -2,10 +2,16 @@
 //!
 //! Each provider focuses on a specific concern:
 //! - ObjectsProvider: Pure CRUD operations for object content
-//! - RefsProvider: Object name + version resolution to SHA256
+//! - RefsProvider: Object name + version resolution to content hash
 //! - IndexProvider: Ordered change management and current working change tracking
 //! - WorkspaceProvider: Changes that aren't yet on index (review/approval queue, idle changes)
+//! - BackupProvider: History of git backup runs
+//!
+//! Providers store their data through `batch::BatchPartition`, so the writes of one
+//! operation can be committed across partitions all-or-nothing (see `Database::write_batch`)
 
+pub mod backups;
+pub mod batch;
 pub mod index;
 pub mod objects;
 pub mod refs;
@@ -14,6 +20,7 @@ pub mod workspace;
 
 pub mod error;
 
+pub use backups::BackupProviderImpl;
 pub use error::{ProviderError, ProviderResult};
 pub use index::IndexProviderImpl;
 pub use objects::ObjectsProviderImpl;
@@ -1,37 +1,58 @@
 use fjall::Partition;
 use moor_compiler::{CompileOptions, ObjFileContext, ObjectDefinition, compile_object_definitions};
-use sha2::{Digest, Sha256};
+use moor_objdef::dump_object;
 use std::collections::HashMap;
 use tokio::sync::mpsc;
 use tracing::{info, warn};
 
+use super::batch::BatchPartition;
 use super::{ProviderError, ProviderResult};
 use crate::types::MooMetaObject;
+use crate::util::ContentHash;
 
 /// Provider trait for object CRUD operations
 pub trait ObjectsProvider: Send + Sync {
     /// Parse a MOO object dump string into an ObjectDefinition
     fn parse_object_dump(&self, dump: &str) -> ProviderResult<ObjectDefinition>;
 
-    /// Generate SHA256 hash from object dump
-    fn generate_sha256_hash(&self, dump: &str) -> String;
+    /// Generate a MOO object dump string from an ObjectDefinition
+    fn generate_object_dump(&self, obj_def: &ObjectDefinition) -> ProviderResult<String>;
 
-    /// Store object by SHA256 key
-    fn store(&self, sha256_key: &str, dump: &str) -> ProviderResult<()>;
+    /// Get the content hash used to key objects
+    fn content_hash(&self) -> ContentHash;
 
-    /// Retrieve object by SHA256 key
-    fn get(&self, sha256_key: &str) -> ProviderResult<Option<String>>;
+    /// Generate the content hash key for an object dump
+    fn generate_hash(&self, dump: &str) -> String;
 
-    /// Delete object by SHA256 key
+    /// Store object by content hash key
+    fn store(&self, hash_key: &str, dump: &str) -> ProviderResult<()>;
+
+    /// Retrieve object by content hash key, failing with a corruption error if the
+    /// stored content no longer matches its key
+    fn get(&self, hash_key: &str) -> ProviderResult<Option<String>>;
+
+    /// Delete object by content hash key
     #[allow(dead_code)]
-    fn delete(&self, sha256_key: &str) -> ProviderResult<bool>;
+    fn delete(&self, hash_key: &str) -> ProviderResult<bool>;
 
     /// Get count of stored objects
     fn count(&self) -> usize;
 
-    /// Get all objects as a HashMap (for export/cloning)
+    /// List the keys of all stored objects without reading their content
+    fn list_keys(&self) -> ProviderResult<Vec<String>>;
+
+    /// Get the stored size of an object (key and value in bytes), without verifying it
+    fn get_object_size(&self, hash_key: &str) -> ProviderResult<Option<u64>>;
+
+    /// Get all objects as a HashMap (for export/cloning), verifying each one
     fn get_all_objects(&self) -> ProviderResult<HashMap<String, String>>;
 
+    /// Store every object whose key was produced by another content hash under its
+    /// current key, returning the old -> new key mapping. Old entries are left in
+    /// place so an interrupted migration can be resumed; remove them once refs point
+    /// at the new keys. Objects whose key matches no known hash are skipped.
+    fn rehash_objects(&self) -> ProviderResult<HashMap<String, String>>;
+
     /// Clear all objects from storage
     fn clear(&self) -> ProviderResult<()>;
 
@@ -47,18 +68,38 @@ pub trait ObjectsProvider: Send + Sync {
 
 /// Implementation of ObjectsProvider using Fjall
 pub struct ObjectsProviderImpl {
-    objects_tree: Partition,
+    objects_tree: BatchPartition,
+    content_hash: ContentHash,
     flush_sender: mpsc::UnboundedSender<()>,
 }
 
 impl ObjectsProviderImpl {
     /// Create a new objects provider
-    pub fn new(objects_tree: Partition, flush_sender: mpsc::UnboundedSender<()>) -> Self {
+    pub fn new(
+        objects_tree: Partition,
+        content_hash: ContentHash,
+        flush_sender: mpsc::UnboundedSender<()>,
+    ) -> Self {
         Self {
-            objects_tree,
+            objects_tree: BatchPartition::new(objects_tree),
+            content_hash,
             flush_sender,
         }
     }
+
+    /// Decode a stored object, checking that its content still hashes to its key
+    fn verify(&self, key: &str, data: &[u8]) -> ProviderResult<String> {
+        let actual = self.content_hash.hash(data);
+        if actual != key {
+            return Err(ProviderError::Corrupted(format!(
+                "object '{}' does not match its {} content hash (stored content hashes to '{}')",
+                key,
+                self.content_hash.name(),
+                actual
+            )));
+        }
+        Ok(String::from_utf8(data.to_vec())?)
+    }
 }
 
 impl ObjectsProvider for ObjectsProviderImpl {
@@ -77,15 +118,24 @@ impl ObjectsProvider for ObjectsProviderImpl {
         Ok(compiled_defs.into_iter().next().unwrap())
     }
 
-    fn generate_sha256_hash(&self, dump: &str) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(dump.as_bytes());
-        format!("{:x}", hasher.finalize())
+    fn generate_object_dump(&self, obj_def: &ObjectDefinition) -> ProviderResult<String> {
+        let index_names = HashMap::new(); // Empty index for simple object names
+        let lines = dump_object(&index_names, obj_def)
+            .map_err(|e| ProviderError::SerializationError(format!("Object dump error: {e}")))?;
+        Ok(lines.join("\n"))
+    }
+
+    fn content_hash(&self) -> ContentHash {
+        self.content_hash
+    }
+
+    fn generate_hash(&self, dump: &str) -> String {
+        self.content_hash.hash(dump.as_bytes())
     }
 
-    fn store(&self, sha256_key: &str, dump: &str) -> ProviderResult<()> {
+    fn store(&self, hash_key: &str, dump: &str) -> ProviderResult<()> {
         self.objects_tree
-            .insert(sha256_key.as_bytes(), dump.as_bytes())?;
+            .insert(hash_key.as_bytes(), dump.as_bytes())?;
 
         // Request background flush
         if self.flush_sender.send(()).is_err() {
@@ -93,28 +143,26 @@ impl ObjectsProvider for ObjectsProviderImpl {
         }
 
         info!(
-            "Stored object with SHA256 key '{}' ({} bytes)",
-            sha256_key,
+            "Stored object with {} key '{}' ({} bytes)",
+            self.content_hash.name(),
+            hash_key,
             dump.len()
         );
         Ok(())
     }
 
-    fn get(&self, sha256_key: &str) -> ProviderResult<Option<String>> {
-        match self.objects_tree.get(sha256_key.as_bytes())? {
-            Some(data) => {
-                let stored_data = String::from_utf8(data.to_vec())?;
-                Ok(Some(stored_data))
-            }
+    fn get(&self, hash_key: &str) -> ProviderResult<Option<String>> {
+        match self.objects_tree.get(hash_key.as_bytes())? {
+            Some(data) => Ok(Some(self.verify(hash_key, &data)?)),
             None => Ok(None),
         }
     }
 
-    fn delete(&self, sha256_key: &str) -> ProviderResult<bool> {
+    fn delete(&self, hash_key: &str) -> ProviderResult<bool> {
         // Check if the key exists first
-        let exists = self.objects_tree.get(sha256_key.as_bytes())?.is_some();
+        let exists = self.objects_tree.get(hash_key.as_bytes())?.is_some();
         if exists {
-            self.objects_tree.remove(sha256_key.as_bytes())?;
+            self.objects_tree.remove(hash_key.as_bytes())?;
         }
         Ok(exists)
     }
@@ -123,19 +171,75 @@ impl ObjectsProvider for ObjectsProviderImpl {
         self.objects_tree.len().unwrap_or(0)
     }
 
+    fn list_keys(&self) -> ProviderResult<Vec<String>> {
+        let mut keys = Vec::new();
+        for result in self.objects_tree.iter() {
+            let (key, _) = result?;
+            keys.push(String::from_utf8(key.to_vec())?);
+        }
+        Ok(keys)
+    }
+
+    fn get_object_size(&self, hash_key: &str) -> ProviderResult<Option<u64>> {
+        Ok(self
+            .objects_tree
+            .get(hash_key.as_bytes())?
+            .map(|value| (hash_key.len() + value.len()) as u64))
+    }
+
     fn get_all_objects(&self) -> ProviderResult<HashMap<String, String>> {
         let mut objects = std::collections::HashMap::new();
 
         for result in self.objects_tree.iter() {
             let (key, value) = result?;
-            let sha256 = String::from_utf8(key.to_vec())?;
-            let data = String::from_utf8(value.to_vec())?;
-            objects.insert(sha256, data);
+            let hash_key = String::from_utf8(key.to_vec())?;
+            let data = self.verify(&hash_key, &value)?;
+            objects.insert(hash_key, data);
         }
 
         Ok(objects)
     }
 
+    fn rehash_objects(&self) -> ProviderResult<HashMap<String, String>> {
+        // Collect first so new keys are not written into the partition being iterated
+        let entries = self.objects_tree.iter().collect::<Result<Vec<_>, _>>()?;
+
+        let mut rekeyed = HashMap::new();
+        for (key, value) in entries {
+            let old_key = String::from_utf8(key.to_vec())?;
+            let new_key = self.content_hash.hash(&value);
+            if new_key == old_key {
+                continue;
+            }
+
+            match ContentHash::identify(&old_key, &value) {
+                Some(previous) => {
+                    self.objects_tree.insert(new_key.as_bytes(), &*value)?;
+                    info!(
+                        "Re-keyed object '{}' from {} to {} '{}'",
+                        old_key,
+                        previous.name(),
+                        self.content_hash.name(),
+                        new_key
+                    );
+                    rekeyed.insert(old_key, new_key);
+                }
+                None => {
+                    warn!(
+                        "Object '{}' matches no known content hash - leaving it in place",
+                        old_key
+                    );
+                }
+            }
+        }
+
+        if !rekeyed.is_empty() && self.flush_sender.send(()).is_err() {
+            warn!("Failed to request background flush - channel closed");
+        }
+
+        Ok(rekeyed)
+    }
+
     fn clear(&self) -> ProviderResult<()> {
         let keys: Vec<_> = self
             .objects_tree
@@ -4,10 +4,13 @@ use std::collections::HashMap;
 use tokio::sync::mpsc;
 use tracing::{info, warn};
 
+use super::batch::BatchPartition;
 use super::{ProviderError, ProviderResult};
 use crate::types::{ObjectInfo, VcsObjectType};
+use crate::util::ContentHash;
 
-/// Represents the refs storage as a HashMap where key is ObjectInfo and value is sha256
+/// Legacy refs storage: every ref in one HashMap serialized under a single key, where
+/// key is ObjectInfo and value is sha256. Only read to migrate older databases.
 /// Custom serialization converts HashMap to Vec for JSON compatibility
 #[derive(Debug, Clone)]
 pub struct RefsStorage {
@@ -79,6 +82,13 @@ pub trait RefsProvider: Send + Sync {
         exclude_version: u64,
     ) -> ProviderResult<bool>;
 
+    /// Get every version of an object and the hash it points at, oldest first
+    fn get_object_refs(
+        &self,
+        object_type: VcsObjectType,
+        object_name: &str,
+    ) -> ProviderResult<Vec<(u64, String)>>;
+
     /// Get all refs as a HashMap (for export/cloning)
     fn get_all_refs(&self) -> ProviderResult<HashMap<ObjectInfo, String>>;
 
@@ -93,48 +103,118 @@ pub trait RefsProvider: Send + Sync {
         version: u64,
     ) -> ProviderResult<()>;
 
+    /// Point refs at new object keys, returning the number of refs changed
+    fn remap_hashes(&self, rekeyed: &HashMap<String, String>) -> ProviderResult<usize>;
+
+    /// Get the content hash that produced the stored ref values (None for databases
+    /// created before the algorithm was recorded)
+    fn get_content_hash(&self) -> ProviderResult<Option<ContentHash>>;
+
+    /// Record the content hash that produced the stored ref values
+    fn set_content_hash(&self, content_hash: ContentHash) -> ProviderResult<()>;
+
+    /// Move refs from the legacy single-blob storage to one key per ref, returning
+    /// the number of refs migrated (0 if there was nothing to migrate)
+    fn migrate_legacy_storage(&self) -> ProviderResult<usize>;
+
     /// Get the total data size (sum of all keys and values in bytes)
     fn get_data_size(&self) -> u64;
 }
 
 /// Implementation of RefsProvider using Fjall
+///
+/// Each ref is stored under its own `type/name/version` key, with the version zero-padded
+/// so the versions of an object sort in order under its `type/name/` prefix. The latest
+/// version of an object is the last key of that prefix.
 pub struct RefsProviderImpl {
-    refs_tree: Partition,
+    refs_tree: BatchPartition,
     flush_sender: mpsc::UnboundedSender<()>,
 }
 
 impl RefsProviderImpl {
+    /// Key the legacy single-blob storage was kept under
+    const LEGACY_STORAGE_KEY: &'static [u8] = b"refs_storage";
+
+    /// Object types refs are stored for, each under its own key prefix
+    const OBJECT_TYPES: [VcsObjectType; 2] =
+        [VcsObjectType::MooObject, VcsObjectType::MooMetaObject];
+
     /// Create a new refs provider
     pub fn new(refs_tree: Partition, flush_sender: mpsc::UnboundedSender<()>) -> Self {
         Self {
-            refs_tree,
+            refs_tree: BatchPartition::new(refs_tree),
             flush_sender,
         }
     }
 
-    /// Load the refs storage from the database
-    fn load_refs_storage(&self) -> ProviderResult<RefsStorage> {
-        match self.refs_tree.get(b"refs_storage")? {
-            Some(data) => {
-                let json = String::from_utf8(data.to_vec())?;
-                let storage: RefsStorage = serde_json::from_str(&json).map_err(|e| {
-                    ProviderError::SerializationError(format!("JSON parse error: {e}"))
-                })?;
-                Ok(storage)
-            }
-            None => Ok(RefsStorage {
-                refs: HashMap::new(),
-            }),
+    /// Prefix shared by every ref of an object type
+    fn type_prefix(object_type: VcsObjectType) -> String {
+        format!("{object_type:?}/")
+    }
+
+    /// Prefix shared by every version of an object
+    fn object_prefix(object_type: VcsObjectType, object_name: &str) -> String {
+        format!("{}{object_name}/", Self::type_prefix(object_type))
+    }
+
+    /// Key of a single ref
+    fn ref_key(object_type: VcsObjectType, object_name: &str, version: u64) -> Vec<u8> {
+        format!(
+            "{}{version:020}",
+            Self::object_prefix(object_type, object_name)
+        )
+        .into_bytes()
+    }
+
+    /// Parse a ref key found under the prefix of `object_type`
+    fn parse_ref_key(object_type: VcsObjectType, key: &[u8]) -> Option<ObjectInfo> {
+        let key = std::str::from_utf8(key).ok()?;
+        let (name, version) = key
+            .strip_prefix(&Self::type_prefix(object_type))?
+            .rsplit_once('/')?;
+        Some(ObjectInfo {
+            object_type,
+            name: name.to_string(),
+            version: version.parse().ok()?,
+        })
+    }
+
+    /// Find the latest version of an object and the hash it points at
+    fn latest_ref(
+        &self,
+        object_type: VcsObjectType,
+        object_name: &str,
+    ) -> ProviderResult<Option<(u64, String)>> {
+        let prefix = Self::object_prefix(object_type, object_name);
+        for result in self.refs_tree.prefix(prefix.as_str()).rev() {
+            let (key, value) = result?;
+            // Objects whose names continue past a '/' share this prefix; skip their keys
+            let Some(version) = std::str::from_utf8(&key[prefix.len()..])
+                .ok()
+                .and_then(|version| version.parse::<u64>().ok())
+            else {
+                continue;
+            };
+            return Ok(Some((version, String::from_utf8(value.to_vec())?)));
         }
+        Ok(None)
     }
 
-    /// Save the refs storage to the database
-    fn save_refs_storage(&self, storage: &RefsStorage) -> ProviderResult<()> {
-        let json = serde_json::to_string(storage).map_err(|e| {
-            ProviderError::SerializationError(format!("JSON serialization error: {e}"))
-        })?;
-        self.refs_tree.insert(b"refs_storage", json.as_bytes())?;
-        Ok(())
+    /// Read every ref by scanning the prefix of each object type
+    fn scan_refs(&self) -> ProviderResult<Vec<(ObjectInfo, String)>> {
+        let mut refs = Vec::new();
+        for object_type in Self::OBJECT_TYPES {
+            for result in self
+                .refs_tree
+                .prefix(Self::type_prefix(object_type).as_str())
+            {
+                let (key, value) = result?;
+                if let Some(info) = Self::parse_ref_key(object_type, &key) {
+                    refs.push((info, String::from_utf8(value.to_vec())?));
+                }
+            }
+        }
+        Ok(refs)
     }
 }
 
@@ -145,35 +225,21 @@ impl RefsProvider for RefsProviderImpl {
         object_name: &str,
         version: Option<u64>,
     ) -> ProviderResult<Option<String>> {
-        let storage = self.load_refs_storage()?;
-
-        if let Some(target_version) = version {
+        match version {
             // Specific version requested
-            let key = ObjectInfo {
-                object_type,
-                name: object_name.to_string(),
-                version: target_version,
-            };
-            Ok(storage.refs.get(&key).cloned())
-        } else {
-            // Latest version requested
-            let latest_version = storage
-                .refs
-                .keys()
-                .filter(|info| info.object_type == object_type && info.name == object_name)
-                .map(|info| info.version)
-                .max();
-
-            if let Some(ver) = latest_version {
-                let key = ObjectInfo {
-                    object_type,
-                    name: object_name.to_string(),
-                    version: ver,
-                };
-                Ok(storage.refs.get(&key).cloned())
-            } else {
-                Ok(None)
+            Some(target_version) => {
+                match self
+                    .refs_tree
+                    .get(Self::ref_key(object_type, object_name, target_version))?
+                {
+                    Some(value) => Ok(Some(String::from_utf8(value.to_vec())?)),
+                    None => Ok(None),
+                }
             }
+            // Latest version requested
+            None => Ok(self
+                .latest_ref(object_type, object_name)?
+                .map(|(_, hash)| hash)),
         }
     }
 
@@ -184,18 +250,10 @@ impl RefsProvider for RefsProviderImpl {
         version: u64,
         sha256: &str,
     ) -> ProviderResult<()> {
-        let mut storage = self.load_refs_storage()?;
-
-        // Add the new reference to the HashMap
-        let key = ObjectInfo {
-            object_type,
-            name: object_name.to_string(),
-            version,
-        };
-        storage.refs.insert(key, sha256.to_string());
-
-        // Save the updated storage
-        self.save_refs_storage(&storage)?;
+        self.refs_tree.insert(
+            Self::ref_key(object_type, object_name, version),
+            sha256.as_bytes(),
+        )?;
 
         // Request background flush
         if self.flush_sender.send(()).is_err() {
@@ -214,16 +272,7 @@ impl RefsProvider for RefsProviderImpl {
         object_type: VcsObjectType,
         object_name: &str,
     ) -> ProviderResult<u64> {
-        let storage = self.load_refs_storage()?;
-
-        let latest_version = storage
-            .refs
-            .keys()
-            .filter(|info| info.object_type == object_type && info.name == object_name)
-            .map(|info| info.version)
-            .max();
-
-        match latest_version {
+        match self.get_current_version(object_type, object_name)? {
             Some(version) => Ok(version + 1),
             None => Ok(1), // First version
         }
@@ -234,16 +283,9 @@ impl RefsProvider for RefsProviderImpl {
         object_type: VcsObjectType,
         object_name: &str,
     ) -> ProviderResult<Option<u64>> {
-        let storage = self.load_refs_storage()?;
-
-        let latest_version = storage
-            .refs
-            .keys()
-            .filter(|info| info.object_type == object_type && info.name == object_name)
-            .map(|info| info.version)
-            .max();
-
-        Ok(latest_version)
+        Ok(self
+            .latest_ref(object_type, object_name)?
+            .map(|(version, _)| version))
     }
 
     fn is_sha256_referenced_excluding(
@@ -253,8 +295,6 @@ impl RefsProvider for RefsProviderImpl {
         exclude_object: &str,
         exclude_version: u64,
     ) -> ProviderResult<bool> {
-        let storage = self.load_refs_storage()?;
-
         let exclude_key = ObjectInfo {
             object_type: exclude_object_type,
             name: exclude_object.to_string(),
@@ -262,25 +302,52 @@ impl RefsProvider for RefsProviderImpl {
         };
 
         // Check if any ref (except the excluded one) points to this SHA256
-        for (key, ref_sha256) in &storage.refs {
-            if key != &exclude_key && ref_sha256 == sha256 {
-                return Ok(true);
-            }
-        }
+        Ok(self
+            .scan_refs()?
+            .iter()
+            .any(|(key, ref_sha256)| key != &exclude_key && ref_sha256 == sha256))
+    }
 
-        Ok(false)
+    fn get_object_refs(
+        &self,
+        object_type: VcsObjectType,
+        object_name: &str,
+    ) -> ProviderResult<Vec<(u64, String)>> {
+        let prefix = Self::object_prefix(object_type, object_name);
+        let mut refs = Vec::new();
+        for result in self.refs_tree.prefix(prefix.as_str()) {
+            let (key, value) = result?;
+            // Skip objects whose names continue past a '/', as in latest_ref
+            let Some(version) = std::str::from_utf8(&key[prefix.len()..])
+                .ok()
+                .and_then(|version| version.parse::<u64>().ok())
+            else {
+                continue;
+            };
+            refs.push((version, String::from_utf8(value.to_vec())?));
+        }
+        Ok(refs)
     }
 
     fn get_all_refs(&self) -> ProviderResult<HashMap<ObjectInfo, String>> {
-        let storage = self.load_refs_storage()?;
-        Ok(storage.refs.clone())
+        Ok(self.scan_refs()?.into_iter().collect())
     }
 
     fn clear(&self) -> ProviderResult<()> {
-        let empty_storage = RefsStorage {
-            refs: HashMap::new(),
-        };
-        self.save_refs_storage(&empty_storage)?;
+        let mut keys = Vec::new();
+        for object_type in Self::OBJECT_TYPES {
+            for result in self
+                .refs_tree
+                .prefix(Self::type_prefix(object_type).as_str())
+            {
+                let (key, _) = result?;
+                keys.push(key.to_vec());
+            }
+        }
+        for key in keys {
+            self.refs_tree.remove(&key)?;
+        }
+        self.refs_tree.remove(Self::LEGACY_STORAGE_KEY)?;
 
         info!("Cleared all refs from storage");
         Ok(())
@@ -292,18 +359,8 @@ impl RefsProvider for RefsProviderImpl {
         object_name: &str,
         version: u64,
     ) -> ProviderResult<()> {
-        let mut storage = self.load_refs_storage()?;
-
-        // Remove the reference
-        let key = ObjectInfo {
-            object_type,
-            name: object_name.to_string(),
-            version,
-        };
-        storage.refs.remove(&key);
-
-        // Save the updated storage
-        self.save_refs_storage(&storage)?;
+        self.refs_tree
+            .remove(&Self::ref_key(object_type, object_name, version))?;
 
         // Request background flush
         if self.flush_sender.send(()).is_err() {
@@ -317,6 +374,83 @@ impl RefsProvider for RefsProviderImpl {
         Ok(())
     }
 
+    fn remap_hashes(&self, rekeyed: &HashMap<String, String>) -> ProviderResult<usize> {
+        let mut changed = 0;
+        for (info, hash) in self.scan_refs()? {
+            if let Some(new_hash) = rekeyed.get(&hash) {
+                self.refs_tree.insert(
+                    Self::ref_key(info.object_type, &info.name, info.version),
+                    new_hash.as_bytes(),
+                )?;
+                changed += 1;
+            }
+        }
+
+        if changed > 0 {
+            // Request background flush
+            if self.flush_sender.send(()).is_err() {
+                warn!("Failed to request background flush - channel closed");
+            }
+        }
+
+        info!("Remapped {} refs to new object keys", changed);
+        Ok(changed)
+    }
+
+    fn get_content_hash(&self) -> ProviderResult<Option<ContentHash>> {
+        match self.refs_tree.get(b"content_hash")? {
+            Some(data) => {
+                let name = String::from_utf8(data.to_vec())?;
+                ContentHash::from_name(&name).map(Some).ok_or_else(|| {
+                    ProviderError::SerializationError(format!("Unknown content hash '{name}'"))
+                })
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn set_content_hash(&self, content_hash: ContentHash) -> ProviderResult<()> {
+        self.refs_tree
+            .insert(b"content_hash", content_hash.name().as_bytes())?;
+
+        // Request background flush
+        if self.flush_sender.send(()).is_err() {
+            warn!("Failed to request background flush - channel closed");
+        }
+        Ok(())
+    }
+
+    fn migrate_legacy_storage(&self) -> ProviderResult<usize> {
+        let data = match self.refs_tree.get(Self::LEGACY_STORAGE_KEY)? {
+            Some(data) => data,
+            None => return Ok(0),
+        };
+        let storage: RefsStorage = serde_json::from_slice(&data)
+            .map_err(|e| ProviderError::SerializationError(format!("JSON parse error: {e}")))?;
+
+        for (info, hash) in &storage.refs {
+            self.refs_tree.insert(
+                Self::ref_key(info.object_type, &info.name, info.version),
+                hash.as_bytes(),
+            )?;
+        }
+
+        // The blob goes only once every ref has its own key, so an interrupted
+        // migration simply runs again
+        self.refs_tree.remove(Self::LEGACY_STORAGE_KEY)?;
+
+        // Request background flush
+        if self.flush_sender.send(()).is_err() {
+            warn!("Failed to request background flush - channel closed");
+        }
+
+        info!(
+            "Migrated {} refs from legacy storage to per-ref keys",
+            storage.refs.len()
+        );
+        Ok(storage.refs.len())
+    }
+
     fn get_data_size(&self) -> u64 {
         let mut total_size = 0u64;
         for (key, value) in self.refs_tree.iter().flatten() {
@@ -326,3 +460,104 @@ impl RefsProvider for RefsProviderImpl {
         total_size
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fjall::PartitionCreateOptions;
+
+    /// Open a refs provider over a fresh keyspace, which must outlive it
+    fn open_provider(dir: &tempfile::TempDir) -> (fjall::Keyspace, RefsProviderImpl) {
+        let keyspace = fjall::Config::new(dir.path())
+            .open()
+            .expect("Failed to open keyspace");
+        let refs_tree = keyspace
+            .open_partition("refs", PartitionCreateOptions::default())
+            .expect("Failed to open partition");
+        let (flush_sender, _) = mpsc::unbounded_channel();
+        (keyspace, RefsProviderImpl::new(refs_tree, flush_sender))
+    }
+
+    #[test]
+    fn test_latest_version_by_prefix() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let (_keyspace, refs) = open_provider(&dir);
+        let obj = VcsObjectType::MooObject;
+
+        for (version, hash) in [(1, "a"), (2, "b"), (10, "c")] {
+            refs.update_ref(obj, "room", version, hash).unwrap();
+        }
+        // Names that extend "room" must not be mistaken for its versions
+        refs.update_ref(obj, "room/annex", 99, "d").unwrap();
+        refs.update_ref(obj, "roomy", 50, "e").unwrap();
+        refs.update_ref(VcsObjectType::MooMetaObject, "room", 20, "f")
+            .unwrap();
+
+        assert_eq!(refs.get_current_version(obj, "room").unwrap(), Some(10));
+        assert_eq!(refs.get_ref(obj, "room", None).unwrap(), Some("c".into()));
+        assert_eq!(
+            refs.get_ref(obj, "room", Some(2)).unwrap(),
+            Some("b".into())
+        );
+        assert_eq!(refs.get_next_version(obj, "room").unwrap(), 11);
+        assert_eq!(refs.get_next_version(obj, "hall").unwrap(), 1);
+        assert_eq!(refs.get_all_refs().unwrap().len(), 6);
+        assert_eq!(
+            refs.get_object_refs(obj, "room").unwrap(),
+            vec![(1, "a".into()), (2, "b".into()), (10, "c".into())]
+        );
+
+        refs.delete_ref(obj, "room", 10).unwrap();
+        assert_eq!(refs.get_ref(obj, "room", None).unwrap(), Some("b".into()));
+
+        refs.set_content_hash(ContentHash::Blake3).unwrap();
+        refs.clear().unwrap();
+        assert!(refs.get_all_refs().unwrap().is_empty());
+        assert_eq!(refs.get_content_hash().unwrap(), Some(ContentHash::Blake3));
+    }
+
+    #[test]
+    fn test_migrate_legacy_storage() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let (_keyspace, refs) = open_provider(&dir);
+
+        let legacy = RefsStorage {
+            refs: [(1, "a"), (2, "b")]
+                .into_iter()
+                .map(|(version, hash)| {
+                    (
+                        ObjectInfo {
+                            object_type: VcsObjectType::MooObject,
+                            name: "room".to_string(),
+                            version,
+                        },
+                        hash.to_string(),
+                    )
+                })
+                .collect(),
+        };
+        refs.refs_tree
+            .insert(
+                RefsProviderImpl::LEGACY_STORAGE_KEY,
+                serde_json::to_vec(&legacy).unwrap(),
+            )
+            .unwrap();
+
+        assert_eq!(refs.migrate_legacy_storage().unwrap(), 2);
+        assert_eq!(
+            refs.get_ref(VcsObjectType::MooObject, "room", None)
+                .unwrap(),
+            Some("b".into())
+        );
+        assert_eq!(refs.get_all_refs().unwrap(), legacy.refs);
+        assert!(
+            refs.refs_tree
+                .get(RefsProviderImpl::LEGACY_STORAGE_KEY)
+                .unwrap()
+                .is_none()
+        );
+
+        // Nothing left to migrate
+        assert_eq!(refs.migrate_legacy_storage().unwrap(), 0);
+    }
+}
@@ -4,10 +4,12 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
 
+use super::batch::BatchPartition;
 use super::{ProviderError, ProviderResult};
 use crate::types::{Permission, User};
 
-/// Represents the user storage as a HashMap where key is user ID and value is User
+/// Legacy user storage: every user in one HashMap serialized under a single key, where
+/// key is user ID and value is User. Only read to migrate older databases.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserStorage {
     pub users: std::collections::HashMap<String, User>,
@@ -41,6 +43,9 @@ pub trait UserProvider: Send + Sync {
     /// Get a user by v_obj
     fn get_user_by_v_obj(&self, v_obj: Obj) -> ProviderResult<Option<User>>;
 
+    /// Get the user that owns the given API key (one of its authorized keys)
+    fn get_user_by_api_key(&self, api_key: &str) -> ProviderResult<Option<User>>;
+
     /// Update an existing user
     fn update_user(&self, user: &User) -> ProviderResult<()>;
 
@@ -91,43 +96,71 @@ pub trait UserProvider: Send + Sync {
 
     /// Delete an API key from a user (alias for remove_authorized_key but returns the key if found)
     fn delete_api_key(&self, user_id: &str, key: &str) -> ProviderResult<bool>;
+
+    /// Move users from the legacy single-blob storage to one key per user, returning
+    /// the number of users migrated (0 if there was nothing to migrate)
+    fn migrate_legacy_storage(&self) -> ProviderResult<usize>;
 }
 
-/// Implementation of UserProvider using Fjall
+/// Implementation of UserProvider using Fjall, storing each user under its own `user:{id}` key
 pub struct UserProviderImpl {
-    users_tree: Partition,
+    users_tree: BatchPartition,
     flush_sender: mpsc::UnboundedSender<()>,
 }
 
 impl UserProviderImpl {
+    /// Key the legacy single-blob storage was kept under
+    const LEGACY_STORAGE_KEY: &'static [u8] = b"user_storage";
+
     /// Create a new user provider
     pub fn new(users_tree: Partition, flush_sender: mpsc::UnboundedSender<()>) -> Self {
         Self {
-            users_tree,
+            users_tree: BatchPartition::new(users_tree),
             flush_sender,
         }
     }
 
-    /// Load the user storage from the database
-    fn load_user_storage(&self) -> ProviderResult<UserStorage> {
-        match self.users_tree.get(b"user_storage")? {
-            Some(data) => {
-                let json = String::from_utf8(data.to_vec())?;
-                let storage: UserStorage = serde_json::from_str(&json).map_err(|e| {
-                    ProviderError::SerializationError(format!("JSON parse error: {e}"))
-                })?;
-                Ok(storage)
-            }
-            None => Ok(UserStorage::new()),
+    /// Key a user is stored under
+    fn user_key(user_id: &str) -> Vec<u8> {
+        format!("user:{user_id}").into_bytes()
+    }
+
+    fn deserialize_user(value: &[u8]) -> ProviderResult<User> {
+        serde_json::from_slice(value)
+            .map_err(|e| ProviderError::SerializationError(format!("JSON parse error: {e}")))
+    }
+
+    /// Load a single user from the database
+    fn load_user(&self, user_id: &str) -> ProviderResult<Option<User>> {
+        match self.users_tree.get(Self::user_key(user_id))? {
+            Some(data) => Ok(Some(Self::deserialize_user(&data)?)),
+            None => Ok(None),
         }
     }
 
-    /// Save the user storage to the database
-    fn save_user_storage(&self, storage: &UserStorage) -> ProviderResult<()> {
-        let json = serde_json::to_string(storage).map_err(|e| {
+    /// Load a user that must exist
+    fn require_user(&self, user_id: &str) -> ProviderResult<User> {
+        self.load_user(user_id)?
+            .ok_or_else(|| ProviderError::ObjectNotFound(format!("User '{user_id}' not found")))
+    }
+
+    /// Load every user from the database
+    fn load_users(&self) -> ProviderResult<Vec<User>> {
+        self.users_tree
+            .prefix("user:")
+            .map(|result| -> ProviderResult<User> {
+                let (_, value) = result?;
+                Self::deserialize_user(&value)
+            })
+            .collect()
+    }
+
+    /// Save a single user to the database
+    fn save_user(&self, user: &User) -> ProviderResult<()> {
+        let json = serde_json::to_vec(user).map_err(|e| {
             ProviderError::SerializationError(format!("JSON serialization error: {e}"))
         })?;
-        self.users_tree.insert(b"user_storage", json.as_bytes())?;
+        self.users_tree.insert(Self::user_key(&user.id), json)?;
 
         // Request background flush
         if self.flush_sender.send(()).is_err() {
@@ -171,33 +204,31 @@ impl UserProviderImpl {
 
 impl UserProvider for UserProviderImpl {
     fn create_user(&self, id: String, email: String, v_obj: Obj) -> ProviderResult<User> {
-        let mut storage = self.load_user_storage()?;
-
         // Check if user already exists
-        if storage.users.contains_key(&id) {
+        if self.load_user(&id)?.is_some() {
             return Err(ProviderError::InvalidOperation(format!(
                 "User '{id}' already exists"
             )));
         }
 
+        let users = self.load_users()?;
+
         // Check if email is already taken
-        if storage.users.values().any(|u| u.email == email) {
+        if users.iter().any(|u| u.email == email) {
             return Err(ProviderError::InvalidOperation(format!(
                 "Email '{email}' is already taken"
             )));
         }
 
         // Check if v_obj is already taken
-        if storage.users.values().any(|u| u.v_obj == v_obj) {
+        if users.iter().any(|u| u.v_obj == v_obj) {
             return Err(ProviderError::InvalidOperation(format!(
                 "v_obj {v_obj:?} is already taken"
             )));
         }
 
-        let user = User::new(id.clone(), email, v_obj);
-        storage.users.insert(id.clone(), user.clone());
-
-        self.save_user_storage(&storage)?;
+        let user = User::new(id, email, v_obj);
+        self.save_user(&user)?;
 
         info!(
             "Created user '{}' with email '{}' and v_obj {:?}",
@@ -207,87 +238,76 @@ impl UserProvider for UserProviderImpl {
     }
 
     fn get_user(&self, user_id: &str) -> ProviderResult<Option<User>> {
-        let storage = self.load_user_storage()?;
-        Ok(storage.users.get(user_id).cloned())
+        self.load_user(user_id)
     }
 
     fn get_user_by_email(&self, email: &str) -> ProviderResult<Option<User>> {
-        let storage = self.load_user_storage()?;
-        Ok(storage.users.values().find(|u| u.email == email).cloned())
+        Ok(self.load_users()?.into_iter().find(|u| u.email == email))
     }
 
     fn get_user_by_v_obj(&self, v_obj: Obj) -> ProviderResult<Option<User>> {
-        let storage = self.load_user_storage()?;
-        Ok(storage.users.values().find(|u| u.v_obj == v_obj).cloned())
+        Ok(self.load_users()?.into_iter().find(|u| u.v_obj == v_obj))
     }
 
-    fn update_user(&self, user: &User) -> ProviderResult<()> {
-        let mut storage = self.load_user_storage()?;
-
-        if !storage.users.contains_key(&user.id) {
-            return Err(ProviderError::InvalidOperation(format!(
-                "User '{}' not found",
-                user.id
-            )));
-        }
+    fn get_user_by_api_key(&self, api_key: &str) -> ProviderResult<Option<User>> {
+        Ok(self
+            .load_users()?
+            .into_iter()
+            .find(|u| u.authorized_keys.iter().any(|k| k == api_key)))
+    }
 
-        storage.users.insert(user.id.clone(), user.clone());
-        self.save_user_storage(&storage)?;
+    fn update_user(&self, user: &User) -> ProviderResult<()> {
+        self.require_user(&user.id)?;
+        self.save_user(user)?;
 
         debug!("Updated user '{}'", user.id);
         Ok(())
     }
 
     fn delete_user(&self, user_id: &str) -> ProviderResult<bool> {
-        let mut storage = self.load_user_storage()?;
+        let user = match self.load_user(user_id)? {
+            Some(user) => user,
+            None => return Ok(false),
+        };
 
         // Check if the user is a system user before deletion
-        if let Some(user) = storage.users.get(user_id) {
-            if user.is_system_user {
-                return Err(ProviderError::InvalidOperation(format!(
-                    "Cannot delete system user '{user_id}'"
-                )));
-            }
+        if user.is_system_user {
+            return Err(ProviderError::InvalidOperation(format!(
+                "Cannot delete system user '{user_id}'"
+            )));
         }
 
-        let removed = storage.users.remove(user_id).is_some();
-        if removed {
-            self.save_user_storage(&storage)?;
-            info!("Deleted user '{}'", user_id);
+        self.users_tree.remove(&Self::user_key(user_id))?;
+
+        // Request background flush
+        if self.flush_sender.send(()).is_err() {
+            warn!("Failed to request background flush - channel closed");
         }
 
-        Ok(removed)
+        info!("Deleted user '{}'", user_id);
+        Ok(true)
     }
 
     fn list_users(&self) -> ProviderResult<Vec<User>> {
-        let storage = self.load_user_storage()?;
-        Ok(storage.users.values().cloned().collect())
+        self.load_users()
     }
 
     fn add_authorized_key(&self, user_id: &str, key: String) -> ProviderResult<()> {
-        let mut storage = self.load_user_storage()?;
-
-        let user = storage.users.get_mut(user_id).ok_or_else(|| {
-            ProviderError::InvalidOperation(format!("User '{user_id}' not found"))
-        })?;
+        let mut user = self.require_user(user_id)?;
 
-        user.add_authorized_key(key.clone());
-        self.save_user_storage(&storage)?;
+        user.add_authorized_key(key);
+        self.save_user(&user)?;
 
         debug!("Added authorized key to user '{}'", user_id);
         Ok(())
     }
 
     fn remove_authorized_key(&self, user_id: &str, key: &str) -> ProviderResult<bool> {
-        let mut storage = self.load_user_storage()?;
-
-        let user = storage.users.get_mut(user_id).ok_or_else(|| {
-            ProviderError::InvalidOperation(format!("User '{user_id}' not found"))
-        })?;
+        let mut user = self.require_user(user_id)?;
 
         let removed = user.remove_authorized_key(key);
         if removed {
-            self.save_user_storage(&storage)?;
+            self.save_user(&user)?;
             debug!("Removed authorized key from user '{}'", user_id);
         }
 
@@ -295,29 +315,21 @@ impl UserProvider for UserProviderImpl {
     }
 
     fn add_permission(&self, user_id: &str, permission: Permission) -> ProviderResult<()> {
-        let mut storage = self.load_user_storage()?;
-
-        let user = storage.users.get_mut(user_id).ok_or_else(|| {
-            ProviderError::InvalidOperation(format!("User '{user_id}' not found"))
-        })?;
+        let mut user = self.require_user(user_id)?;
 
         user.add_permission(permission.clone());
-        self.save_user_storage(&storage)?;
+        self.save_user(&user)?;
 
         debug!("Added permission {:?} to user '{}'", permission, user_id);
         Ok(())
     }
 
     fn remove_permission(&self, user_id: &str, permission: &Permission) -> ProviderResult<bool> {
-        let mut storage = self.load_user_storage()?;
-
-        let user = storage.users.get_mut(user_id).ok_or_else(|| {
-            ProviderError::InvalidOperation(format!("User '{user_id}' not found"))
-        })?;
+        let mut user = self.require_user(user_id)?;
 
         let removed = user.remove_permission(permission);
         if removed {
-            self.save_user_storage(&storage)?;
+            self.save_user(&user)?;
             debug!(
                 "Removed permission {:?} from user '{}'",
                 permission, user_id
@@ -328,13 +340,7 @@ impl UserProvider for UserProviderImpl {
     }
 
     fn has_permission(&self, user_id: &str, permission: &Permission) -> ProviderResult<bool> {
-        let storage = self.load_user_storage()?;
-
-        let user = storage.users.get(user_id).ok_or_else(|| {
-            ProviderError::InvalidOperation(format!("User '{user_id}' not found"))
-        })?;
-
-        Ok(user.has_permission(permission))
+        Ok(self.require_user(user_id)?.has_permission(permission))
     }
 
     fn get_everyone_user(&self) -> ProviderResult<User> {
@@ -346,15 +352,8 @@ impl UserProvider for UserProviderImpl {
     }
 
     fn ensure_everyone_user(&self) -> ProviderResult<()> {
-        let storage = self.load_user_storage()?;
-
-        if !storage.users.contains_key("Everyone") {
-            let everyone_user = Self::create_everyone_user();
-            let mut new_storage = storage;
-            new_storage
-                .users
-                .insert("Everyone".to_string(), everyone_user);
-            self.save_user_storage(&new_storage)?;
+        if self.load_user("Everyone")?.is_none() {
+            self.save_user(&Self::create_everyone_user())?;
             info!("Created default 'Everyone' user");
         }
 
@@ -368,16 +367,13 @@ impl UserProvider for UserProviderImpl {
     }
 
     fn ensure_wizard_user(&self, api_key: String) -> ProviderResult<()> {
-        let mut storage = self.load_user_storage()?;
-
-        if !storage.users.contains_key("Wizard") {
-            let wizard_user = Self::create_wizard_user(api_key.clone());
-            storage.users.insert("Wizard".to_string(), wizard_user);
-            self.save_user_storage(&storage)?;
-            info!("Created default 'Wizard' user with all permissions");
-        } else {
+        match self.load_user("Wizard")? {
+            None => {
+                self.save_user(&Self::create_wizard_user(api_key))?;
+                info!("Created default 'Wizard' user with all permissions");
+            }
             // Update existing wizard user to ensure it has the correct API key
-            if let Some(wizard_user) = storage.users.get_mut("Wizard") {
+            Some(mut wizard_user) => {
                 // Ensure wizard has the API key if not already present
                 if !wizard_user.authorized_keys.contains(&api_key) {
                     wizard_user.add_authorized_key(api_key);
@@ -392,7 +388,7 @@ impl UserProvider for UserProviderImpl {
                     wizard_user.add_permission(Permission::ManageApiKeys);
                     // Ensure it's marked as a system user
                     wizard_user.is_system_user = true;
-                    self.save_user_storage(&storage)?;
+                    self.save_user(&wizard_user)?;
                     info!("Updated 'Wizard' user with API key and permissions");
                 }
             }
@@ -402,11 +398,7 @@ impl UserProvider for UserProviderImpl {
     }
 
     fn disable_user(&self, user_id: &str) -> ProviderResult<()> {
-        let mut storage = self.load_user_storage()?;
-
-        let user = storage.users.get_mut(user_id).ok_or_else(|| {
-            ProviderError::InvalidOperation(format!("User '{user_id}' not found"))
-        })?;
+        let mut user = self.require_user(user_id)?;
 
         if user.is_system_user {
             return Err(ProviderError::InvalidOperation(format!(
@@ -415,48 +407,34 @@ impl UserProvider for UserProviderImpl {
         }
 
         user.is_disabled = true;
-        self.save_user_storage(&storage)?;
+        self.save_user(&user)?;
 
         info!("Disabled user '{}'", user_id);
         Ok(())
     }
 
     fn enable_user(&self, user_id: &str) -> ProviderResult<()> {
-        let mut storage = self.load_user_storage()?;
-
-        let user = storage.users.get_mut(user_id).ok_or_else(|| {
-            ProviderError::InvalidOperation(format!("User '{user_id}' not found"))
-        })?;
+        let mut user = self.require_user(user_id)?;
 
         user.is_disabled = false;
-        self.save_user_storage(&storage)?;
+        self.save_user(&user)?;
 
         info!("Enabled user '{}'", user_id);
         Ok(())
     }
 
     fn is_disabled(&self, user_id: &str) -> ProviderResult<bool> {
-        let storage = self.load_user_storage()?;
-
-        let user = storage.users.get(user_id).ok_or_else(|| {
-            ProviderError::InvalidOperation(format!("User '{user_id}' not found"))
-        })?;
-
-        Ok(user.is_disabled)
+        Ok(self.require_user(user_id)?.is_disabled)
     }
 
     fn generate_api_key(&self, user_id: &str) -> ProviderResult<String> {
         use uuid::Uuid;
 
-        let mut storage = self.load_user_storage()?;
-
-        let user = storage.users.get_mut(user_id).ok_or_else(|| {
-            ProviderError::InvalidOperation(format!("User '{user_id}' not found"))
-        })?;
+        let mut user = self.require_user(user_id)?;
 
         let api_key = Uuid::new_v4().to_string();
         user.add_authorized_key(api_key.clone());
-        self.save_user_storage(&storage)?;
+        self.save_user(&user)?;
 
         info!("Generated new API key for user '{}'", user_id);
         Ok(api_key)
@@ -465,6 +443,29 @@ impl UserProvider for UserProviderImpl {
     fn delete_api_key(&self, user_id: &str, key: &str) -> ProviderResult<bool> {
         self.remove_authorized_key(user_id, key)
     }
+
+    fn migrate_legacy_storage(&self) -> ProviderResult<usize> {
+        let data = match self.users_tree.get(Self::LEGACY_STORAGE_KEY)? {
+            Some(data) => data,
+            None => return Ok(0),
+        };
+        let storage: UserStorage = serde_json::from_slice(&data)
+            .map_err(|e| ProviderError::SerializationError(format!("JSON parse error: {e}")))?;
+
+        for user in storage.users.values() {
+            self.save_user(user)?;
+        }
+
+        // The blob goes only once every user has its own key, so an interrupted
+        // migration simply runs again
+        self.users_tree.remove(Self::LEGACY_STORAGE_KEY)?;
+
+        info!(
+            "Migrated {} users from legacy storage to per-user keys",
+            storage.users.len()
+        );
+        Ok(storage.users.len())
+    }
 }
 
 // Helper trait extension for Arc wrapping
@@ -485,6 +486,10 @@ impl<T: UserProvider> UserProvider for std::sync::Arc<T> {
         (**self).get_user_by_v_obj(v_obj)
     }
 
+    fn get_user_by_api_key(&self, api_key: &str) -> ProviderResult<Option<User>> {
+        (**self).get_user_by_api_key(api_key)
+    }
+
     fn update_user(&self, user: &User) -> ProviderResult<()> {
         (**self).update_user(user)
     }
@@ -552,4 +557,60 @@ impl<T: UserProvider> UserProvider for std::sync::Arc<T> {
     fn delete_api_key(&self, user_id: &str, key: &str) -> ProviderResult<bool> {
         (**self).delete_api_key(user_id, key)
     }
+
+    fn migrate_legacy_storage(&self) -> ProviderResult<usize> {
+        (**self).migrate_legacy_storage()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fjall::PartitionCreateOptions;
+
+    #[test]
+    fn test_migrate_legacy_storage() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let keyspace = fjall::Config::new(dir.path())
+            .open()
+            .expect("Failed to open keyspace");
+        let users_tree = keyspace
+            .open_partition("users", PartitionCreateOptions::default())
+            .expect("Failed to open partition");
+        let (flush_sender, _) = mpsc::unbounded_channel();
+        let users = UserProviderImpl::new(users_tree.clone(), flush_sender);
+
+        let mut legacy = UserStorage::new();
+        let mut builder = User::new(
+            "builder".to_string(),
+            "builder@example.com".to_string(),
+            Obj::mk_id(5),
+        );
+        builder.add_authorized_key("builder-key".to_string());
+        legacy.users.insert(builder.id.clone(), builder);
+        users_tree
+            .insert(
+                UserProviderImpl::LEGACY_STORAGE_KEY,
+                serde_json::to_vec(&legacy).unwrap(),
+            )
+            .unwrap();
+
+        assert_eq!(users.migrate_legacy_storage().unwrap(), 1);
+        assert!(
+            users_tree
+                .get(UserProviderImpl::LEGACY_STORAGE_KEY)
+                .unwrap()
+                .is_none()
+        );
+        let migrated = users
+            .get_user_by_api_key("builder-key")
+            .unwrap()
+            .expect("Migrated user should be found by API key");
+        assert_eq!(migrated.email, "builder@example.com");
+
+        // New users land beside migrated ones, and nothing is left to migrate
+        users.ensure_everyone_user().unwrap();
+        assert_eq!(users.list_users().unwrap().len(), 2);
+        assert_eq!(users.migrate_legacy_storage().unwrap(), 0);
+    }
 }
@@ -3,6 +3,7 @@ use std::sync::Arc;
 use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
 
+use super::batch::BatchPartition;
 use super::{ProviderError, ProviderResult};
 use crate::types::{Change, ChangeStatus};
 
@@ -61,14 +62,14 @@ pub trait WorkspaceProvider: Send + Sync {
 }
 
 pub struct WorkspaceProviderImpl {
-    workspace_tree: Partition,
+    workspace_tree: BatchPartition,
     flush_sender: mpsc::UnboundedSender<()>,
 }
 
 impl WorkspaceProviderImpl {
     pub fn new(workspace_tree: Partition, flush_sender: mpsc::UnboundedSender<()>) -> Self {
         Self {
-            workspace_tree,
+            workspace_tree: BatchPartition::new(workspace_tree),
             flush_sender,
         }
     }
@@ -231,7 +232,7 @@ impl WorkspaceProvider for WorkspaceProviderImpl {
 
     fn promote_change_to_index(&self, change_id: &str) -> ProviderResult<Change> {
         let change = self.get_workspace_change(change_id)?.ok_or_else(|| {
-            ProviderError::InvalidOperation(format!("Workspace change '{change_id}' not found"))
+            ProviderError::ObjectNotFound(format!("Workspace change '{change_id}' not found"))
         })?;
 
         info!("Promoting workspace change '{}' to index", change_id);
@@ -275,7 +276,7 @@ impl WorkspaceProvider for WorkspaceProviderImpl {
 
     fn update_approval_status(&self, change_id: &str, status: ChangeStatus) -> ProviderResult<()> {
         let mut change = self.get_workspace_change(change_id)?.ok_or_else(|| {
-            ProviderError::InvalidOperation(format!("Workspace change '{change_id}' not found"))
+            ProviderError::ObjectNotFound(format!("Workspace change '{change_id}' not found"))
         })?;
 
         let old_status = change.status.clone();
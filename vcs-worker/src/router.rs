@@ -1,25 +1,34 @@
 use axum::{
     Router,
-    response::{Json, Redirect},
-    routing::{get, post},
+    body::Body,
+    extract::{Path, Query},
+    http::{HeaderMap, StatusCode, header::AUTHORIZATION, header::CONTENT_TYPE},
+    response::{IntoResponse, Json, Redirect, Response},
+    routing::{MethodFilter, get, on, post},
 };
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::net::TcpListener;
-use tracing::info;
+use tracing::{error, info, warn};
 use utoipa::OpenApi;
 use utoipa::openapi::{
     ContentBuilder, HttpMethod, InfoBuilder, PathsBuilder, RefOr, ResponseBuilder,
-    path::{OperationBuilder, PathItemBuilder},
+    path::{OperationBuilder, ParameterBuilder, ParameterIn, PathItemBuilder},
     request_body::RequestBodyBuilder,
+    schema::{ObjectBuilder, Type},
+    security::{ApiKey, ApiKeyValue, Http, HttpAuthScheme, SecurityRequirement, SecurityScheme},
 };
 use utoipa_swagger_ui::SwaggerUi;
 
-use crate::operations::{OperationRegistry, OperationRequest};
-use crate::types::{HttpRequest, OperationResponse};
+use crate::clone_pack::{PACK_CONTENT_TYPE, PACK_PATH, PackRequest, write_pack};
+use crate::operations::{
+    AuthenticationError, OperationError, OperationRegistry, OperationRequest, require_permission,
+};
+use crate::types::{HttpRequest, OperationErrorInfo, OperationResponse, Permission};
 
 // Import moor types for RPC
 use moor_common::tasks::WorkerError;
-use moor_var::{Obj, Symbol, Var, v_str};
+use moor_var::{E_PERM, Obj, Symbol, Var, v_error, v_str};
 use rpc_common::WorkerToken;
 use uuid::Uuid;
 
@@ -28,9 +37,18 @@ type RpcHandler = std::pin::Pin<
     Box<dyn std::future::Future<Output = Result<Var, WorkerError>> + Send + Sync + 'static>,
 >;
 
+/// Result type for HTTP handlers - authentication failures carry their own status code
+type HttpResult = Result<Json<OperationResponse>, (StatusCode, Json<OperationResponse>)>;
+
+/// Header carrying the caller's API key
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
+/// Encoded clone pack frames buffered between the database reader and the response stream
+const PACK_CHANNEL_FRAMES: usize = 8;
+
 /// Base OpenAPI documentation for VCS Worker API
 #[derive(OpenApi)]
-#[openapi(components(schemas(HttpRequest, OperationResponse)))]
+#[openapi(components(schemas(HttpRequest, OperationResponse, OperationErrorInfo)))]
 struct BaseApiDoc;
 
 /// Generate OpenAPI spec dynamically from registered operations
@@ -150,6 +168,28 @@ result = worker_request("vcs", {"object/update", "$player", objdef_lines});
 4. **Review your work**: Use `change/status` to see pending changes
 5. **Submit when ready**: Use `change/submit` to finalize
 
+## Authentication
+
+Requests are authenticated with an API key sent in the `X-API-Key` header (or as `Authorization: Bearer <key>`).
+The key is matched against the authorized keys of each user, and the operation runs with that user's permissions.
+Requests without a key run as the `Everyone` user, which has no permissions. An unknown key is rejected with
+`401 Unauthorized` and a key belonging to a disabled user with `403 Forbidden`.
+
+## Errors
+
+Failed operations return `success: false` with a non-2xx status and a machine-readable `error` object:
+
+| Status | `error.code`        | MOO error  | Meaning                                           |
+|--------|---------------------|------------|---------------------------------------------------|
+| 400    | `invalid_args`      | `E_INVARG` | Missing or malformed arguments                    |
+| 401    | `unauthorized`      | -          | Unknown API key                                   |
+| 403    | `permission_denied` | `E_PERM`   | The caller lacks the required permission          |
+| 404    | `not_found`         | `E_INVIND` | The change, object, user or operation is unknown  |
+| 409    | `conflict`          | `E_NACC`   | Not allowed in the current state of the repository |
+| 500    | `internal`          | `E_INVARG` | Database or system error                          |
+
+MOO callers receive the corresponding error value from `worker_request` instead.
+
 For more details on each operation, see the categorized endpoints below."#))
         .build();
 
@@ -185,6 +225,22 @@ For more details on each operation, see the categorized endpoints below."#))
             .build(),
     ]);
 
+    // Register the API key authentication schemes
+    if let Some(components) = openapi.components.as_mut() {
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(API_KEY_HEADER))),
+        );
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+        );
+    }
+    openapi.security = Some(vec![
+        SecurityRequirement::new("api_key", Vec::<String>::new()),
+        SecurityRequirement::new("bearer", Vec::<String>::new()),
+    ]);
+
     let mut paths = PathsBuilder::new();
 
     // Add the generic RPC endpoint
@@ -231,7 +287,7 @@ For more details on each operation, see the categorized endpoints below."#))
     // Add dynamic routes from operations
     let mut operation_routes: std::collections::HashMap<
         String,
-        Vec<(String, axum::http::Method, String)>,
+        Vec<(String, axum::http::Method, bool, String)>,
     > = std::collections::HashMap::new();
 
     for (route, op_name) in registry.get_all_routes() {
@@ -241,7 +297,12 @@ For more details on each operation, see the categorized endpoints below."#))
                 operation_routes
                     .entry(route.path.clone())
                     .or_default()
-                    .push((op_name.clone(), route.method.clone(), desc.to_string()));
+                    .push((
+                        op_name.clone(),
+                        route.method.clone(),
+                        route.is_json,
+                        desc.to_string(),
+                    ));
             }
         }
     }
@@ -249,7 +310,7 @@ For more details on each operation, see the categorized endpoints below."#))
     for (path, ops) in operation_routes {
         let mut path_item = PathItemBuilder::new();
 
-        for (op_name, method, description) in ops {
+        for (op_name, method, is_json, description) in ops {
             // Get the full operation to extract detailed documentation
             let operation_opt = registry.get_operation(&op_name);
 
@@ -268,7 +329,7 @@ For more details on each operation, see the categorized endpoints below."#))
 
             if let Some(op) = operation_opt.as_ref() {
                 let params = op.parameters();
-                if !params.is_empty() && method != "GET" {
+                if !params.is_empty() && is_json {
                     // Build a JSON schema example for the request body
                     let mut example_obj = serde_json::Map::new();
                     example_obj.insert("operation".to_string(), serde_json::json!(op_name));
@@ -365,6 +426,36 @@ For more details on each operation, see the categorized endpoints below."#))
                 .summary(Some(op_name.clone()))
                 .description(Some(full_description));
 
+            // Non-JSON routes take their arguments from the path and query string
+            if !is_json {
+                if let Some(op) = operation_opt.as_ref() {
+                    let params = op.parameters();
+                    for name in op.query_parameters() {
+                        let param = params.iter().find(|p| p.name == name);
+                        let in_path = path_parameter_names(&path).contains(&name);
+                        let required = in_path || param.is_some_and(|p| p.required);
+
+                        operation_builder = operation_builder.parameter(
+                            ParameterBuilder::new()
+                                .name(&name)
+                                .parameter_in(if in_path {
+                                    ParameterIn::Path
+                                } else {
+                                    ParameterIn::Query
+                                })
+                                .required(if required {
+                                    utoipa::openapi::Required::True
+                                } else {
+                                    utoipa::openapi::Required::False
+                                })
+                                .description(param.map(|p| p.description.clone()))
+                                .schema(Some(ObjectBuilder::new().schema_type(Type::String)))
+                                .build(),
+                        );
+                    }
+                }
+            }
+
             // Add all responses from the operation
             if let Some(op) = operation_opt.as_ref() {
                 for response in op.responses() {
@@ -439,92 +530,295 @@ For more details on each operation, see the categorized endpoints below."#))
             path_item = path_item.operation(http_method, operation);
         }
 
-        paths = paths.path(path, path_item.build());
+        paths = paths.path(openapi_path(&path), path_item.build());
     }
 
     openapi.paths = paths.build();
     openapi
 }
 
+/// Names of the `:param` segments in a route path
+fn path_parameter_names(path: &str) -> Vec<String> {
+    path.split('/')
+        .filter_map(|segment| segment.strip_prefix(':'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Convert an axum route path (`/api/object/get/:object_name`) to OpenAPI form (`/api/object/get/{object_name}`)
+fn openapi_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(name) => format!("{{{name}}}"),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Build ordered operation args for a non-JSON request from its path and query-string parameters.
+/// Unset parameters between set ones become empty strings; trailing unset ones are dropped so
+/// optional arguments keep their defaults.
+fn collect_query_args(
+    param_names: &[String],
+    path_params: &HashMap<String, String>,
+    query_params: &HashMap<String, String>,
+) -> Vec<String> {
+    let mut args: Vec<String> = param_names
+        .iter()
+        .map(|name| {
+            path_params
+                .get(name)
+                .or_else(|| query_params.get(name))
+                .cloned()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    while args.last().is_some_and(|arg| arg.is_empty()) {
+        args.pop();
+    }
+
+    args
+}
+
+/// Extract the caller's API key from the `X-API-Key` or `Authorization: Bearer` header
+fn extract_api_key(headers: &HeaderMap) -> Option<String> {
+    if let Some(key) = headers
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+    {
+        return Some(key.to_string());
+    }
+
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(str::to_string)
+}
+
+/// Authenticate the request from its headers and execute the operation as that user. The
+/// operation runs on the blocking pool, so neither its database work nor a remote it waits
+/// on holds up the runtime serving other requests.
+async fn execute_authenticated(
+    registry: Arc<OperationRegistry>,
+    headers: HeaderMap,
+    request: OperationRequest,
+) -> HttpResult {
+    let api_key = extract_api_key(&headers);
+    let operation = request.operation.clone();
+
+    tokio::task::spawn_blocking(move || match registry.authenticate(api_key.as_deref()) {
+        Ok(user) => {
+            let (status, response) = registry.execute_http_as(request, &user);
+            match StatusCode::from_u16(status) {
+                Ok(StatusCode::OK) => Ok(Json(response)),
+                Ok(status) => Err((status, Json(response))),
+                Err(_) => Err((StatusCode::INTERNAL_SERVER_ERROR, Json(response))),
+            }
+        }
+        Err(e) => {
+            warn!(
+                "Rejected request for operation '{}': {}",
+                request.operation, e
+            );
+            Err(authentication_error_response(request.operation, &e))
+        }
+    })
+    .await
+    .unwrap_or_else(|e| {
+        error!("Operation '{}' did not complete: {}", operation, e);
+        let e = OperationError::Internal(format!("Operation did not complete: {e}"));
+        Err(operation_error_response(operation, &e))
+    })
+}
+
+/// Build the response for a request whose caller could not be authenticated
+fn authentication_error_response(
+    operation: String,
+    e: &AuthenticationError,
+) -> (StatusCode, Json<OperationResponse>) {
+    let status = match e {
+        AuthenticationError::InvalidApiKey => StatusCode::UNAUTHORIZED,
+        AuthenticationError::UserDisabled(_) => StatusCode::FORBIDDEN,
+        AuthenticationError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, error_response(operation, e.code(), e.to_string()))
+}
+
+/// Build the response for a request that failed with an operation error
+fn operation_error_response(
+    operation: String,
+    e: &OperationError,
+) -> (StatusCode, Json<OperationResponse>) {
+    let status = StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    (status, error_response(operation, e.code(), e.to_string()))
+}
+
+/// Build a failed operation response with a machine-readable error
+fn error_response(operation: String, code: &str, message: String) -> Json<OperationResponse> {
+    Json(OperationResponse {
+        result: serde_json::Value::String(format!("Error: {message}")),
+        success: false,
+        operation,
+        error: Some(OperationErrorInfo {
+            code: code.to_string(),
+            message,
+        }),
+    })
+}
+
+/// Clone pack endpoint handler.
+/// Authenticates the caller like any operation, then streams the requested pack section while
+/// a blocking task reads it from the database, so the response is never held in memory whole.
+async fn clone_pack_handler(
+    registry: Arc<OperationRegistry>,
+    headers: HeaderMap,
+    Json(request): Json<PackRequest>,
+) -> Response {
+    let operation = "clone/pack".to_string();
+    let user = match registry.authenticate(extract_api_key(&headers).as_deref()) {
+        Ok(user) => user,
+        Err(e) => {
+            warn!("Rejected clone pack request: {}", e);
+            return authentication_error_response(operation, &e).into_response();
+        }
+    };
+
+    if let Err(e) = require_permission(&user, Permission::Clone, "clone repositories") {
+        return operation_error_response(operation, &e).into_response();
+    }
+    let Some(database) = registry.database().cloned() else {
+        let e = OperationError::Internal("No database configured".to_string());
+        return operation_error_response(operation, &e).into_response();
+    };
+
+    info!("Serving clone pack to user {}", user.id);
+    let (sender, receiver) = tokio::sync::mpsc::channel::<Vec<u8>>(PACK_CHANNEL_FRAMES);
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = write_pack(&database, &request, |frame| {
+            sender.blocking_send(frame).is_ok()
+        }) {
+            warn!("Clone pack for user {} failed: {}", user.id, e);
+        }
+    });
+
+    let frames = futures::stream::unfold(receiver, |mut receiver| async move {
+        receiver
+            .recv()
+            .await
+            .map(|frame| (Ok::<_, std::convert::Infallible>(frame), receiver))
+    });
+    (
+        [(CONTENT_TYPE, PACK_CONTENT_TYPE)],
+        Body::from_stream(frames),
+    )
+        .into_response()
+}
+
 /// Generic RPC endpoint handler
 async fn rpc_handler(
     registry: Arc<OperationRegistry>,
+    headers: HeaderMap,
     Json(payload): Json<HttpRequest>,
-) -> Json<OperationResponse> {
+) -> HttpResult {
     let request = OperationRequest {
         operation: payload.operation,
         args: payload.args,
     };
-    Json(registry.execute_http(request))
+    execute_authenticated(registry, headers, request).await
 }
 
 /// Create the HTTP router from registered operations.
 /// Automatically generates routes from the operation definitions in the registry.
 pub fn create_http_router(registry: Arc<OperationRegistry>) -> Router {
-    let mut api_router = Router::new().route(
-        "/rpc",
-        post({
-            let registry = registry.clone();
-            move |payload| rpc_handler(registry.clone(), payload)
-        }),
-    );
+    let mut api_router = Router::new()
+        .route(
+            "/rpc",
+            post({
+                let registry = registry.clone();
+                move |headers, payload| rpc_handler(registry.clone(), headers, payload)
+            }),
+        )
+        .route(
+            PACK_PATH,
+            post({
+                let registry = registry.clone();
+                move |headers, payload| clone_pack_handler(registry.clone(), headers, payload)
+            }),
+        );
 
     // Dynamically add routes from registered operations
     for (route, op_name) in registry.get_all_routes() {
-        let registry_for_route = registry.clone();
-        let operation_name = op_name.clone();
-
-        match route.method {
-            axum::http::Method::GET => {
-                api_router = api_router.route(
-                    &route.path,
-                    get({
-                        let registry = registry_for_route.clone();
-                        let op_name = operation_name.clone();
-                        move || {
-                            let registry = registry.clone();
-                            let op_name = op_name.clone();
-                            async move {
-                                let request = OperationRequest {
-                                    operation: op_name,
-                                    args: vec![],
-                                };
-                                Json(registry.execute_http(request))
-                            }
-                        }
-                    }),
-                );
-            }
-            axum::http::Method::POST => {
-                if route.is_json {
-                    // For JSON POST requests, use the generic RPC handler
-                    api_router = api_router.route(
-                        &route.path,
-                        post({
-                            let registry = registry_for_route.clone();
-                            let op_name = operation_name.clone();
-                            move |Json(payload): Json<HttpRequest>| {
-                                let registry = registry.clone();
-                                let op_name = op_name.clone();
-                                async move {
-                                    let request = OperationRequest {
-                                        operation: op_name,
-                                        args: payload.args,
-                                    };
-                                    Json(registry.execute_http(request))
-                                }
-                            }
-                        }),
-                    );
-                }
-            }
+        let method_filter = match route.method {
+            axum::http::Method::GET => MethodFilter::GET,
+            axum::http::Method::POST => MethodFilter::POST,
+            axum::http::Method::PUT => MethodFilter::PUT,
+            axum::http::Method::DELETE => MethodFilter::DELETE,
             _ => {
-                tracing::warn!(
+                warn!(
                     "Unsupported HTTP method for route: {} {:?}",
                     route.path,
                     route.method
                 );
+                continue;
             }
+        };
+
+        if route.is_json {
+            // JSON routes take their args from the request body, like the generic RPC handler
+            api_router = api_router.route(
+                &route.path,
+                on(method_filter, {
+                    let registry = registry.clone();
+                    let op_name = op_name.clone();
+                    move |headers: HeaderMap, Json(payload): Json<HttpRequest>| {
+                        let registry = registry.clone();
+                        let op_name = op_name.clone();
+                        async move {
+                            let request = OperationRequest {
+                                operation: op_name,
+                                args: payload.args,
+                            };
+                            execute_authenticated(registry, headers, request).await
+                        }
+                    }
+                }),
+            );
+        } else {
+            // Other routes take their args from the path and query string
+            let param_names = registry
+                .get_operation(&op_name)
+                .map(|op| op.query_parameters())
+                .unwrap_or_default();
+
+            api_router = api_router.route(
+                &route.path,
+                on(method_filter, {
+                    let registry = registry.clone();
+                    let op_name = op_name.clone();
+                    move |headers: HeaderMap,
+                          path: Option<Path<HashMap<String, String>>>,
+                          Query(query): Query<HashMap<String, String>>| {
+                        let registry = registry.clone();
+                        let op_name = op_name.clone();
+                        let param_names = param_names.clone();
+                        async move {
+                            let path_params = path.map(|Path(params)| params).unwrap_or_default();
+                            let request = OperationRequest {
+                                operation: op_name,
+                                args: collect_query_args(&param_names, &path_params, &query),
+                            };
+                            execute_authenticated(registry, headers, request).await
+                        }
+                    }
+                }),
+            );
         }
     }
 
@@ -564,7 +858,7 @@ pub async fn process_rpc_request(
     _token: WorkerToken,
     _request_id: Uuid,
     _worker_type: Symbol,
-    _perms: Obj,
+    perms: Obj,
     arguments: Vec<Var>,
     _timeout: Option<std::time::Duration>,
 ) -> Result<Var, WorkerError> {
@@ -620,8 +914,20 @@ pub async fn process_rpc_request(
         args,
     };
 
+    // Attribute the call to the VCS user whose v_obj matches the calling task's perms
+    let user = match registry.authenticate_perms(perms) {
+        Ok(user) => user,
+        Err(e) => {
+            warn!(
+                "Rejected RPC request for operation '{}' from {:?}: {}",
+                request.operation, perms, e
+            );
+            return Ok(v_error(E_PERM.msg(format!("{e}"))));
+        }
+    };
+
     // Execute the operation and return the result as a Var
-    Ok(registry.execute_var(request))
+    Ok(registry.execute_var_as(request, &user))
 }
 
 /// Create a handler closure that can be used with the RPC worker loop
@@ -106,6 +106,18 @@ pub struct OperationResponse {
     pub success: bool,
     /// The operation that was executed
     pub operation: String,
+    /// Machine-readable error details, present only when `success` is false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<OperationErrorInfo>,
+}
+
+/// Error details for a failed operation
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OperationErrorInfo {
+    /// Machine-readable error code (e.g. "not_found", "permission_denied", "conflict")
+    pub code: String,
+    /// Human-readable error message
+    pub message: String,
 }
 
 /// Request structure for HTTP requests
@@ -150,6 +162,8 @@ pub struct ChangeAbandonRequest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChangeApproveRequest {
     pub change_id: String,
+    pub force: Option<bool>,
+    pub details: Option<bool>,
 }
 
 /// Request structure for change submit operations
@@ -157,6 +171,8 @@ pub struct ChangeApproveRequest {
 pub struct ChangeSubmitRequest {
     /// Optional commit message to set for the change
     pub message: Option<String>,
+    /// Remote to submit the change to; the default remote if None
+    pub remote: Option<String>,
 }
 
 /// Request structure for change stash operations
@@ -171,6 +187,20 @@ pub struct ChangeSwitchRequest {
     pub change_id: String,
 }
 
+/// Request structure for change rebase operations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeRebaseRequest {
+    pub change_id: String,
+}
+
+/// Request structure for change diff operations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeDiffRequest {
+    pub change_id: String,
+    /// Optional merged change to compare against instead of each object's previous version
+    pub baseline_change_id: Option<String>,
+}
+
 /// Request structure for object get operations
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ObjectGetRequest {
@@ -231,6 +261,147 @@ pub struct CloneData {
     pub source: Option<String>,          // Source URL if this is a clone
 }
 
+/// Merged changes after a known change, packaged by index/calc_delta for index/update
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndexDelta {
+    pub changes: Vec<Change>, // Merged changes, oldest first
+    /// Every ref of each object the changes touch, so renames and deletions carry over
+    pub refs: Vec<(ObjectInfo, String)>,
+    /// Content of the object versions the changes record, keyed by content hash
+    pub objects: std::collections::HashMap<String, String>,
+}
+
+impl IndexDelta {
+    /// Every object (by type and name) added, modified, deleted or renamed by the changes
+    pub fn touched_objects(&self) -> std::collections::BTreeSet<(VcsObjectType, String)> {
+        let mut touched = std::collections::BTreeSet::new();
+        for change in &self.changes {
+            let tracked = change
+                .added_objects
+                .iter()
+                .chain(&change.modified_objects)
+                .chain(&change.deleted_objects)
+                .chain(change.renamed_objects.iter().flat_map(|r| [&r.from, &r.to]));
+            for obj in tracked {
+                touched.insert((obj.object_type, obj.name.clone()));
+            }
+        }
+        touched
+    }
+}
+
+/// Name of the remote used when an operation is not given one, and the one clone records
+/// the source under unless told otherwise
+pub const DEFAULT_REMOTE: &str = "origin";
+
+/// A named repository to pull from, push or submit to, with the credentials used there
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Remote {
+    pub name: String,
+    pub url: String, // Base URL of the remote worker, without /api/clone
+    /// API key sent to the remote as X-API-Key
+    pub api_key: Option<String>,
+    /// The remote's user the API key belongs to
+    pub user_id: Option<String>,
+}
+
+/// Version of the change patch format written by change/export
+pub const CHANGE_PATCH_FORMAT_VERSION: u32 = 1;
+
+/// A single change packaged for transfer between unrelated workers (change/export, change/import)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangePatch {
+    pub format_version: u32,
+    pub change: Change, // Change metadata, tracking lists and rename hints
+    pub objects: Vec<PatchObject>, // Content of every object the change touches
+}
+
+/// Content of one object (or meta) touched by a change patch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatchObject {
+    pub object_type: VcsObjectType,
+    /// Name of the object after the change
+    pub name: String,
+    /// Name of the object before the change (differs from `name` for renamed objects)
+    pub base_name: String,
+    /// Content recorded by the change; None when the change deletes or only renames the object
+    pub content: Option<String>,
+    /// Content the change was made against; None when the change adds the object
+    pub base_content: Option<String>,
+}
+
+/// Request structure for change export operations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeExportRequest {
+    pub change_id: String,
+}
+
+/// Request structure for change import operations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeImportRequest {
+    pub patch: String, // JSON-encoded ChangePatch
+}
+
+/// Request structure for git import operations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportGitRequest {
+    pub source: String, // Git URL, local repository or directory of .moo files
+}
+
+/// State of a git backup run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BackupRunStatus {
+    Queued,    // Waiting for an earlier run to finish
+    Running,   // Replaying changes into the backup repository
+    Succeeded, // Finished and (for remote targets) pushed
+    Failed,    // Stopped with an error
+}
+
+impl BackupRunStatus {
+    /// Name of the status as reported by the backup operations
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BackupRunStatus::Queued => "queued",
+            BackupRunStatus::Running => "running",
+            BackupRunStatus::Succeeded => "succeeded",
+            BackupRunStatus::Failed => "failed",
+        }
+    }
+}
+
+/// A single git backup run, recorded in the backups partition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupRun {
+    pub id: u64,                 // Sequential run number
+    pub trigger: String,         // What started the run (e.g. change/approve, backup/run)
+    pub queued_at: u64,          // When the run was requested
+    pub started_at: Option<u64>, // When the run acquired the backup repository
+    pub finished_at: Option<u64>,
+    pub status: BackupRunStatus,
+    pub commit: Option<String>, // Backup branch head after the run
+    pub commits: usize,         // Number of backup commits created
+    pub object_count: usize,    // Number of objects in the backup
+    pub error: Option<String>,  // Why the run failed (e.g. the remote diverged)
+}
+
+/// Request structure for backup history operations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupHistoryRequest {
+    pub limit: usize, // Maximum number of runs to return, newest first
+}
+
+/// Request structure for repository integrity checks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FsckRequest {
+    pub repair: bool, // Fix the problems that can be fixed without losing data
+}
+
+/// Request structure for garbage collection of object blobs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GcRequest {
+    pub dry_run: bool, // Report what would be swept without deleting anything
+}
+
 /// User permissions in the system
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum Permission {
@@ -30,6 +30,80 @@ pub fn generate_change_id(
     hasher.finalize().to_hex().to_string()
 }
 
+/// Content hash used to key object blobs in the objects partition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContentHash {
+    /// SHA-256, used by databases created before objects were keyed by Blake3
+    Sha256,
+    /// Blake3, the same hash used for change IDs
+    #[default]
+    Blake3,
+}
+
+impl ContentHash {
+    /// Every supported algorithm, used to recognize keys written under another one
+    pub const ALL: [ContentHash; 2] = [ContentHash::Sha256, ContentHash::Blake3];
+
+    /// Name used in configuration and in the stored algorithm marker
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContentHash::Sha256 => "sha256",
+            ContentHash::Blake3 => "blake3",
+        }
+    }
+
+    /// Parse an algorithm name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|hash| hash.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Hash content to a lowercase hex string
+    pub fn hash(&self, content: &[u8]) -> String {
+        match self {
+            ContentHash::Sha256 => {
+                use sha2::{Digest, Sha256};
+                format!("{:x}", Sha256::digest(content))
+            }
+            ContentHash::Blake3 => blake3::hash(content).to_hex().to_string(),
+        }
+    }
+
+    /// Find the algorithm under which `key` is the hash of `content`, if any
+    pub fn identify(key: &str, content: &[u8]) -> Option<Self> {
+        Self::ALL.into_iter().find(|hash| hash.hash(content) == key)
+    }
+}
+
+/// Run a future to completion, blocking the calling thread
+///
+/// Operations execute synchronously, but some of their work (HTTP calls to a remote) is
+/// async. On the multi-threaded tokio runtime the future is driven in place, so any writes
+/// it makes join the write batch open on this thread. A current-thread runtime (or no
+/// runtime at all) can't be blocked that way, so there the future runs on a runtime of its
+/// own on a scoped thread, which borrows the batch until it is done.
+pub fn block_on<F>(future: F) -> F::Output
+where
+    F: std::future::Future + Send,
+    F::Output: Send,
+{
+    use tokio::runtime::{Builder, Handle, RuntimeFlavor};
+
+    match Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(|| handle.block_on(future))
+        }
+        _ => crate::providers::batch::on_other_thread(|| {
+            Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to build a runtime for blocking work")
+                .block_on(future)
+        }),
+    }
+}
+
 /// Get short form of a hash ID (first 12 characters for better collision resistance)
 pub fn short_hash(hash: &str) -> String {
     hash.chars().take(12).collect()
@@ -94,6 +168,31 @@ mod tests {
         assert_eq!(id1.len(), 64);
     }
 
+    #[test]
+    fn test_content_hash() {
+        let sha256 = ContentHash::Sha256.hash(b"object");
+        let blake3 = ContentHash::Blake3.hash(b"object");
+
+        assert_eq!(sha256.len(), 64);
+        assert_eq!(blake3.len(), 64);
+        assert_ne!(sha256, blake3);
+        assert_eq!(ContentHash::default(), ContentHash::Blake3);
+
+        // Keys are recognized by the algorithm that produced them
+        assert_eq!(
+            ContentHash::identify(&sha256, b"object"),
+            Some(ContentHash::Sha256)
+        );
+        assert_eq!(
+            ContentHash::identify(&blake3, b"object"),
+            Some(ContentHash::Blake3)
+        );
+        assert_eq!(ContentHash::identify(&blake3, b"tampered"), None);
+
+        assert_eq!(ContentHash::from_name("SHA256"), Some(ContentHash::Sha256));
+        assert_eq!(ContentHash::from_name("md5"), None);
+    }
+
     #[test]
     fn test_short_hash() {
         let full_hash = "abcdef1234567890abcdef1234567890abcdef1234567890abcdef1234567890";
@@ -13,6 +13,7 @@ use serde_json::Value;
 pub struct VcsTestClient {
     base_url: String,
     database: Option<DatabaseRef>,
+    api_key: Option<String>,
 }
 
 impl VcsTestClient {
@@ -21,19 +22,27 @@ impl VcsTestClient {
         Self {
             base_url: server.base_url(),
             database: Some(server.database().clone()),
+            api_key: Some(WIZARD_API_KEY.to_string()),
         }
     }
 }
 
 impl VcsTestClient {
-    /// Create a new test client for the given server
+    /// Create a new test client for the given server, authenticated as the Wizard user
     pub fn new(server: &TestServer) -> Self {
         Self {
             base_url: server.base_url(),
             database: Some(server.database().clone()),
+            api_key: Some(WIZARD_API_KEY.to_string()),
         }
     }
 
+    /// Use a different API key for requests (None sends unauthenticated requests)
+    pub fn with_api_key(mut self, api_key: Option<&str>) -> Self {
+        self.api_key = api_key.map(|k| k.to_string());
+        self
+    }
+
     // ==================== Object Operations ====================
 
     /// Update an object with the given name and content
@@ -169,7 +178,10 @@ impl VcsTestClient {
                 args.push(baseline.to_string());
             }
             
-            let result = diff_op.execute(args, &wizard_user);
+            // Failures are surfaced as MOO error values, as an RPC caller would see them
+            let result = diff_op
+                .execute(args, &wizard_user)
+                .unwrap_or_else(|e| e.to_moo_error());
             return Ok(result);
         }
 
@@ -232,6 +244,43 @@ impl VcsTestClient {
             .await
     }
 
+    /// Rebase an idle workspace change onto the current index
+    pub async fn change_rebase(
+        &self,
+        change_id: &str,
+    ) -> Result<Value, Box<dyn std::error::Error>> {
+        self.rpc_call("change/rebase", vec![Value::String(change_id.to_string())])
+            .await
+    }
+
+    /// Get the full line-level diff of a change, optionally against a merged baseline change
+    pub async fn change_diff(
+        &self,
+        change_id: &str,
+        baseline_change_id: Option<&str>,
+    ) -> Result<Value, Box<dyn std::error::Error>> {
+        let mut args = vec![Value::String(change_id.to_string())];
+        if let Some(baseline) = baseline_change_id {
+            args.push(Value::String(baseline.to_string()));
+        }
+        self.rpc_call("change/diff", args).await
+    }
+
+    /// Export a change as a JSON patch
+    pub async fn change_export(
+        &self,
+        change_id: &str,
+    ) -> Result<Value, Box<dyn std::error::Error>> {
+        self.rpc_call("change/export", vec![Value::String(change_id.to_string())])
+            .await
+    }
+
+    /// Import a JSON patch produced by change/export as a new local change
+    pub async fn change_import(&self, patch: &str) -> Result<Value, Box<dyn std::error::Error>> {
+        self.rpc_call("change/import", vec![Value::String(patch.to_string())])
+            .await
+    }
+
     /// Approve a change by ID
     pub async fn change_approve(
         &self,
@@ -241,6 +290,24 @@ impl VcsTestClient {
             .await
     }
 
+    /// Update an object in a fresh change and approve it, returning the change ID
+    pub async fn approve_object(&self, name: &str, lines: Vec<String>) -> String {
+        self.object_update(name, lines)
+            .await
+            .expect("Failed to update object")
+            .assert_success("Update object");
+        let database = self
+            .database
+            .as_ref()
+            .expect("Approving an object needs a client with a database");
+        let (change_id, _) = DbAssertions::new(database).require_top_change();
+        self.change_approve(&change_id)
+            .await
+            .expect("Failed to approve")
+            .assert_success("Approve");
+        change_id
+    }
+
     // ==================== Meta Operations ====================
 
     /// Add an ignored property to an object's meta
@@ -343,7 +410,7 @@ impl VcsTestClient {
                 "wizard@localhost".to_string(),
                 moor_var::Obj::mk_id(0),
             );
-            let result = status_op.execute(vec![], &wizard_user);
+            let result = status_op.execute(vec![], &wizard_user)?;
             return Ok(result);
         }
 
@@ -413,36 +480,6 @@ impl VcsTestClient {
 
     /// Update index from remote source
     pub async fn index_update(&self) -> Result<Value, Box<dyn std::error::Error>> {
-        // If we have a database reference, call the async version directly
-        if let Some(ref db) = self.database {
-            let update_op = moor_vcs_worker::operations::IndexUpdateOperation::new(db.clone());
-            match update_op.update_async().await {
-                Ok(result_var) => {
-                    // Convert Var to string result
-                    let result_str = if let Some(s) = result_var.as_string() {
-                        s.to_string()
-                    } else {
-                        format!("{:?}", result_var)
-                    };
-
-                    let success = !result_str.starts_with("Error:");
-                    return Ok(json!({
-                        "success": success,
-                        "result": result_str,
-                        "operation": "index/update"
-                    }));
-                }
-                Err(e) => {
-                    return Ok(json!({
-                        "success": false,
-                        "result": format!("Error: {}", e),
-                        "operation": "index/update"
-                    }));
-                }
-            }
-        }
-
-        // Fallback to HTTP request
         self.rpc_call("index/update", vec![]).await
     }
 
@@ -453,34 +490,68 @@ impl VcsTestClient {
         self.rpc_call("clone", vec![]).await
     }
 
-    /// Import repository state from a remote URL (clone import)
+    /// Import repository state from a remote URL (clone import), authenticating to the
+    /// source with this client's API key
     pub async fn clone_import(&self, url: &str) -> Result<Value, Box<dyn std::error::Error>> {
-        // If we have a database reference, call the async version directly
-        if let Some(ref db) = self.database {
-            let clone_op = moor_vcs_worker::operations::CloneOperation::new(db.clone());
-            match clone_op.import_from_url_async(url, None).await {
-                Ok(result) => {
-                    return Ok(json!({
-                        "success": true,
-                        "result": result,
-                        "operation": "clone"
-                    }));
-                }
-                Err(e) => {
-                    return Ok(json!({
-                        "success": false,
-                        "result": format!("Error: {}", e),
-                        "operation": "clone"
-                    }));
-                }
-            }
+        let mut args = vec![Value::String(url.to_string())];
+        if let Some(api_key) = &self.api_key {
+            args.push(Value::String(api_key.clone()));
         }
+        self.rpc_call("clone", args).await
+    }
 
-        // Fallback to HTTP request
-        self.rpc_call("clone", vec![Value::String(url.to_string())])
+    /// Import a world from a git repository or directory of .moo files
+    pub async fn import_git(&self, source: &str) -> Result<Value, Box<dyn std::error::Error>> {
+        self.rpc_call("import/git", vec![Value::String(source.to_string())])
             .await
     }
 
+    // ==================== Backup Operations ====================
+
+    /// Get the git backup status
+    pub async fn backup_status(&self) -> Result<Value, Box<dyn std::error::Error>> {
+        self.rpc_call("backup/status", vec![]).await
+    }
+
+    /// List recorded git backup runs, newest first
+    pub async fn backup_history(
+        &self,
+        limit: Option<usize>,
+    ) -> Result<Value, Box<dyn std::error::Error>> {
+        let args = match limit {
+            Some(limit) => vec![Value::String(limit.to_string())],
+            None => vec![],
+        };
+        self.rpc_call("backup/history", args).await
+    }
+
+    /// Run a git backup now and wait for it to finish
+    pub async fn backup_run(&self) -> Result<Value, Box<dyn std::error::Error>> {
+        self.rpc_call("backup/run", vec![]).await
+    }
+
+    // ==================== System Operations ====================
+
+    /// Check repository consistency, optionally repairing what can be fixed safely
+    pub async fn system_fsck(&self, repair: bool) -> Result<Value, Box<dyn std::error::Error>> {
+        let args = if repair {
+            vec![Value::String("--repair".to_string())]
+        } else {
+            vec![]
+        };
+        self.rpc_call("system/fsck", args).await
+    }
+
+    /// Sweep unreachable object blobs, or only report them in a dry run
+    pub async fn system_gc(&self, dry_run: bool) -> Result<Value, Box<dyn std::error::Error>> {
+        let args = if dry_run {
+            vec![Value::String("--dry-run".to_string())]
+        } else {
+            vec![]
+        };
+        self.rpc_call("system/gc", args).await
+    }
+
     // ==================== Low-level RPC ====================
 
     /// Make a raw RPC call with the given operation and arguments
@@ -494,7 +565,22 @@ impl VcsTestClient {
             "args": args
         });
 
-        make_request("POST", &format!("{}/rpc", self.base_url), Some(request)).await
+        let (status, text) = make_request_with_api_key(
+            "POST",
+            &format!("{}/rpc", self.base_url),
+            Some(request),
+            self.api_key.as_deref(),
+        )
+        .await?;
+
+        // Operation failures come back as non-2xx responses with a JSON body describing the error
+        match serde_json::from_str(&text) {
+            Ok(json) => Ok(json),
+            Err(_) if !status.is_success() => {
+                Err(format!("HTTP error {}: {}", status, text).into())
+            }
+            Err(e) => Err(e.into()),
+        }
     }
 }
 
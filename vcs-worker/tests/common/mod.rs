@@ -9,21 +9,24 @@
 pub mod client;
 pub use client::{DbAssertions, ResponseExt, VcsTestClient};
 
-use sha2::{Digest, Sha256};
 use std::sync::Arc;
 use std::time::Duration;
 use tempfile::TempDir;
 use tokio::net::TcpListener;
 
 use moor_vcs_worker::router::create_http_router;
-use moor_vcs_worker::{Config, DatabaseRef, create_registry_with_config};
+use moor_vcs_worker::util::ContentHash;
+use moor_vcs_worker::{Config, DatabaseRef, OperationRegistry, create_registry_with_config};
 
 // Import provider traits so their methods are available
 pub use moor_vcs_worker::providers::index::IndexProvider;
 pub use moor_vcs_worker::providers::objects::ObjectsProvider;
 pub use moor_vcs_worker::providers::refs::RefsProvider;
 pub use moor_vcs_worker::providers::user::UserProvider;
-pub use moor_vcs_worker::types::User;
+pub use moor_vcs_worker::types::{DEFAULT_REMOTE, Remote, User};
+
+/// Default Wizard API key (matches the default from config.rs)
+pub const WIZARD_API_KEY: &str = "wizard-default-key-change-in-production";
 
 /// Test server managing lifecycle of HTTP server and database
 pub struct TestServer {
@@ -31,6 +34,7 @@ pub struct TestServer {
     temp_dir: TempDir,
     git_work_dir: Option<TempDir>,
     database: DatabaseRef,
+    registry: Arc<OperationRegistry>,
     _shutdown_tx: tokio::sync::oneshot::Sender<()>,
 }
 
@@ -56,7 +60,7 @@ impl TestServer {
         let port = listener.local_addr()?.port();
 
         // Create router
-        let router = create_http_router(registry);
+        let router = create_http_router(registry.clone());
 
         // Create shutdown channel
         let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel();
@@ -85,6 +89,7 @@ impl TestServer {
             temp_dir,
             git_work_dir: Some(git_work_dir),
             database,
+            registry,
             _shutdown_tx: shutdown_tx,
         })
     }
@@ -119,7 +124,7 @@ impl TestServer {
         let port = listener.local_addr()?.port();
 
         // Create router
-        let router = create_http_router(registry);
+        let router = create_http_router(registry.clone());
 
         // Create shutdown channel
         let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel();
@@ -148,6 +153,7 @@ impl TestServer {
             temp_dir,
             git_work_dir,
             database,
+            registry,
             _shutdown_tx: shutdown_tx,
         })
     }
@@ -173,6 +179,12 @@ impl TestServer {
         &self.database
     }
 
+    /// Get the operation registry for executing operations in-process
+    #[allow(dead_code)]
+    pub fn registry(&self) -> &Arc<OperationRegistry> {
+        &self.registry
+    }
+
     /// Create a high-level test client for this server
     #[allow(dead_code)]
     pub fn client(&self) -> VcsTestClient {
@@ -194,15 +206,12 @@ impl TestServer {
     /// Get the configured wizard API key for authentication tests
     #[allow(dead_code)]
     pub fn get_wizard_api_key(&self) -> String {
-        // This matches the default key from config.rs
-        "wizard-default-key-change-in-production".to_string()
+        WIZARD_API_KEY.to_string()
     }
 
-    /// Calculate SHA256 hash for object content (matches what the system does)
-    pub fn calculate_sha256(content: &str) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(content.as_bytes());
-        format!("{:x}", hasher.finalize())
+    /// Calculate the content hash key for object content (matches the default config)
+    pub fn calculate_hash(content: &str) -> String {
+        ContentHash::default().hash(content.as_bytes())
     }
 }
 
@@ -222,15 +231,54 @@ pub fn moo_to_lines(content: &str) -> Vec<String> {
     content.lines().map(|s| s.to_string()).collect()
 }
 
-/// Helper function to make HTTP requests
+/// Build an object dump with `look` and `examine` verbs returning the given values
+#[allow(dead_code)]
+pub fn object_lines(look_result: &str, examine_result: &str) -> Vec<String> {
+    moo_to_lines(&format!(
+        r#"object #3
+  name: "Test Object"
+  parent: #1
+  location: #2
+  owner: #2
+
+  verb look (this none this) owner: #2 flags: "rxd"
+    return "{look_result}";
+  endverb
+
+  verb examine (this none this) owner: #2 flags: "rxd"
+    return "{examine_result}";
+  endverb
+endobject
+"#
+    ))
+}
+
+/// Helper function to make HTTP requests, authenticated as the Wizard user
 pub async fn make_request(
     method: &str,
     url: &str,
     body: Option<serde_json::Value>,
 ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    let (status, text) = make_request_with_api_key(method, url, body, Some(WIZARD_API_KEY)).await?;
+
+    // Operation failures come back as non-2xx responses with a JSON body describing the error
+    match serde_json::from_str(&text) {
+        Ok(json) => Ok(json),
+        Err(_) if !status.is_success() => Err(format!("HTTP error {}: {}", status, text).into()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Helper function to make HTTP requests with an optional API key, returning the raw status and body
+pub async fn make_request_with_api_key(
+    method: &str,
+    url: &str,
+    body: Option<serde_json::Value>,
+    api_key: Option<&str>,
+) -> Result<(reqwest::StatusCode, String), Box<dyn std::error::Error>> {
     let client = reqwest::Client::new();
 
-    let request = match method {
+    let mut request = match method {
         "GET" => client.get(url),
         "POST" => {
             let mut req = client.post(url);
@@ -242,16 +290,15 @@ pub async fn make_request(
         _ => panic!("Unsupported HTTP method: {}", method),
     };
 
+    if let Some(key) = api_key {
+        request = request.header("X-API-Key", key);
+    }
+
     let response = request.send().await?;
     let status = response.status();
     let text = response.text().await?;
 
-    if !status.is_success() {
-        return Err(format!("HTTP error {}: {}", status, text).into());
-    }
-
-    let json: serde_json::Value = serde_json::from_str(&text)?;
-    Ok(json)
+    Ok((status, text))
 }
 
 // Re-export commonly used types for tests
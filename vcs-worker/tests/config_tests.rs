@@ -3,6 +3,7 @@
 //! This test module provides 100% test coverage for the Config module,
 //! including environment variable handling, default values, and constructor methods.
 
+use moor_vcs_worker::util::ContentHash;
 use serial_test::serial;
 use std::env;
 use std::path::PathBuf;
@@ -16,6 +17,8 @@ fn clear_vcs_env_vars() {
         env::remove_var("VCS_GAME_NAME");
         env::remove_var("VCS_GIT_BACKUP_REPO");
         env::remove_var("VCS_GIT_BACKUP_TOKEN");
+        env::remove_var("VCS_CONTENT_HASH");
+        env::remove_var("VCS_IMPORT_ROOT");
     }
 }
 
@@ -130,6 +133,46 @@ fn test_config_with_git_backup_repo() {
     clear_vcs_env_vars();
 }
 
+#[test]
+#[serial]
+fn test_config_with_content_hash() {
+    clear_vcs_env_vars();
+
+    let config = moor_vcs_worker::Config::new();
+    assert_eq!(config.content_hash, ContentHash::Blake3);
+
+    set_env_var("VCS_CONTENT_HASH", "sha256");
+    let config = moor_vcs_worker::Config::new();
+    assert_eq!(config.content_hash, ContentHash::Sha256);
+
+    // Unknown algorithms fall back to the default
+    set_env_var("VCS_CONTENT_HASH", "md5");
+    let config = moor_vcs_worker::Config::new();
+    assert_eq!(config.content_hash, ContentHash::Blake3);
+
+    clear_vcs_env_vars();
+}
+
+#[test]
+#[serial]
+fn test_config_with_import_root() {
+    clear_vcs_env_vars();
+
+    // Local imports are refused unless a root is configured
+    let config = moor_vcs_worker::Config::new();
+    assert_eq!(config.import_root, None);
+
+    set_env_var("VCS_IMPORT_ROOT", "/srv/imports");
+    let config = moor_vcs_worker::Config::new();
+    assert_eq!(config.import_root, Some(PathBuf::from("/srv/imports")));
+
+    set_env_var("VCS_IMPORT_ROOT", "");
+    let config = moor_vcs_worker::Config::new();
+    assert_eq!(config.import_root, None);
+
+    clear_vcs_env_vars();
+}
+
 #[test]
 #[serial]
 fn test_config_with_git_backup_token() {
@@ -1,3 +1,4 @@
+use moor_vcs_worker::git_backup::parse_change_id_trailer;
 use tempfile::TempDir;
 use std::fs;
 use std::thread;
@@ -6,30 +7,93 @@ use std::time::Duration;
 mod common;
 use common::*;
 
-/// Helper to set up a git repository for testing
+/// Helper to set up a git repository (with a working tree) for testing
 fn setup_git_repo() -> TempDir {
     let git_dir = TempDir::new().expect("Failed to create temp dir");
+    git2::Repository::init(git_dir.path()).expect("Failed to init git repo");
+    git_dir
+}
+
+/// Helper to list the commits reachable from a reference, newest first, as (author, subject, change ID)
+fn backup_commits(repo_path: &std::path::Path, reference: &str) -> Vec<(String, String, Option<String>)> {
+    let repo = git2::Repository::open(repo_path).expect("Failed to open git repo");
+    let mut walk = repo.revwalk().expect("Failed to walk history");
+    walk.set_sorting(git2::Sort::TOPOLOGICAL).expect("Failed to sort history");
+    if walk.push_ref(reference).is_err() {
+        return Vec::new();
+    }
+    
+    walk.map(|oid| {
+        let commit = repo
+            .find_commit(oid.expect("Failed to walk history"))
+            .expect("Failed to find commit");
+        (
+            commit.author().name().unwrap_or_default().to_string(),
+            commit.summary().unwrap_or_default().to_string(),
+            parse_change_id_trailer(commit.message().unwrap_or_default()),
+        )
+    })
+    .collect()
+}
+
+/// Helper to read a file from the tree a reference points at
+fn read_committed_file(repo_path: &std::path::Path, reference: &str, filename: &str) -> Option<String> {
+    let repo = git2::Repository::open(repo_path).expect("Failed to open git repo");
+    let tree = repo.revparse_single(reference).ok()?.peel_to_tree().ok()?;
+    let blob = tree.get_name(filename)?.to_object(&repo).ok()?.peel_to_blob().ok()?;
+    Some(String::from_utf8_lossy(blob.content()).to_string())
+}
+
+/// Helper to read a field of the git_backup entry reported by system/status
+async fn git_backup_status_field(client: &VcsTestClient, field: &str) -> String {
+    let status = client.status().await.expect("Failed to get status");
+    let moor_var::Variant::Map(status_map) = status.variant() else {
+        panic!("Status should be a map");
+    };
+    let backup = status_map
+        .get(&moor_var::Var::mk_str("git_backup"))
+        .expect("Status should report git_backup");
+    let moor_var::Variant::Map(backup_map) = backup.variant() else {
+        panic!("No git backup has been reported");
+    };
+    let value = backup_map
+        .get(&moor_var::Var::mk_str(field))
+        .expect("git_backup should report the field");
+    match value.variant() {
+        moor_var::Variant::Str(value) => value.as_str().to_string(),
+        _ => panic!("git_backup {} should be a string", field),
+    }
+}
+
+/// Helper to create an object and submit it as a change, returning the change ID
+async fn submit_object(server: &TestServer, name: &str, number: u32) -> String {
+    let client = server.client();
+    let objdef = vec![
+        format!("object #{}", number),
+        format!("  name: \"{}\"", name),
+        "  parent: #0".to_string(),
+        "  owner: #1".to_string(),
+        "  location: #0".to_string(),
+        "endobject".to_string(),
+    ].join("\n");
+    
+    client
+        .rpc_call("object/update", vec![
+            serde_json::Value::String(name.to_string()),
+            serde_json::Value::String(objdef),
+        ])
+        .await
+        .expect("Failed to update object")
+        .assert_success("object/update");
+    let (change_id, _) = server.db_assertions().require_top_change();
     
-    // Initialize git repo
-    std::process::Command::new("git")
-        .current_dir(git_dir.path())
-        .args(&["init"])
-        .output()
-        .expect("Failed to init git repo");
-    
-    std::process::Command::new("git")
-        .current_dir(git_dir.path())
-        .args(&["config", "user.email", "test@test.com"])
-        .output()
-        .expect("Failed to set git user email");
-    
-    std::process::Command::new("git")
-        .current_dir(git_dir.path())
-        .args(&["config", "user.name", "Test User"])
-        .output()
-        .expect("Failed to set git user name");
+    client
+        .rpc_call("change/submit", vec![])
+        .await
+        .expect("Failed to submit change")
+        .assert_success("change/submit");
     
-    git_dir
+    change_id
 }
 
 
@@ -91,6 +155,7 @@ async fn test_git_backup_with_local_repo() {
         .expect("Failed to update object");
     
     response.assert_success("object/update");
+    let (change_id, change) = server.db_assertions().require_top_change();
     
     // Submit the change (which triggers git backup)
     let submit_response = client
@@ -117,15 +182,262 @@ async fn test_git_backup_with_local_repo() {
     assert!(content.contains("Test Object"));
     assert!(content.contains("test_prop"));
     
-    // Check that a git commit was made
-    let log_output = std::process::Command::new("git")
-        .current_dir(git_dir.path())
-        .args(&["log", "--oneline"])
-        .output()
-        .unwrap();
+    // Check that the change was committed under its own name and author
+    let commits = backup_commits(git_dir.path(), "HEAD");
+    assert!(
+        commits.contains(&(change.author.clone(), change.name.clone(), Some(change_id.clone()))),
+        "Expected change commit not found in git log: {:?}",
+        commits
+    );
+}
+
+#[tokio::test]
+async fn test_git_backup_commits_each_change_once() {
+    let git_dir = setup_git_repo();
+    let temp_db = TempDir::new().unwrap();
     
-    let log = String::from_utf8_lossy(&log_output.stdout);
-    assert!(log.contains("VCS backup:"), "Expected commit message not found in git log");
+    let config = moor_vcs_worker::Config::with_db_path(temp_db.path().to_path_buf())
+        .with_git_backup(git_dir.path().to_str().unwrap().to_string(), None);
+    
+    let server = TestServer::start_with_config(config).await.expect("Failed to start server");
+    let client = server.client();
+    
+    let mut change_ids = Vec::new();
+    for (name, number) in [("first_object", 6), ("second_object", 7)] {
+        let objdef = vec![
+            format!("object #{}", number),
+            "  name: \"Test\"".to_string(),
+            "  parent: #0".to_string(),
+            "  owner: #1".to_string(),
+            "  location: #0".to_string(),
+            "endobject".to_string(),
+        ].join("\n");
+        
+        client
+            .rpc_call("object/update", vec![
+                serde_json::Value::String(name.to_string()),
+                serde_json::Value::String(objdef),
+            ])
+            .await
+            .expect("Failed to update object")
+            .assert_success("object/update");
+        let (change_id, _) = server.db_assertions().require_top_change();
+        change_ids.push(change_id);
+        
+        client
+            .rpc_call("change/submit", vec![])
+            .await
+            .expect("Failed to submit change")
+            .assert_success("change/submit");
+        
+        // Let each backup finish so the second one resumes from the first
+        thread::sleep(Duration::from_secs(3));
+    }
+    
+    // One commit per change, oldest first, with no snapshot commits in between
+    let backed_up: Vec<String> = backup_commits(git_dir.path(), "HEAD")
+        .into_iter()
+        .filter_map(|(_, _, change_id)| change_id)
+        .collect();
+    let mut expected = change_ids;
+    expected.reverse();
+    assert_eq!(backed_up, expected, "Expected one commit per change");
+    
+    assert!(git_dir.path().join("first_object.moo").exists());
+    assert!(git_dir.path().join("second_object.moo").exists());
+}
+
+#[tokio::test]
+async fn test_git_backup_to_bare_repo() {
+    // An empty directory is initialized as a bare repository
+    let bare_dir = TempDir::new().unwrap();
+    let temp_db = TempDir::new().unwrap();
+    
+    let config = moor_vcs_worker::Config::with_db_path(temp_db.path().to_path_buf())
+        .with_git_backup(bare_dir.path().to_str().unwrap().to_string(), None);
+    
+    let server = TestServer::start_with_config(config).await.expect("Failed to start server");
+    let change_id = submit_object(&server, "bare_object", 8).await;
+    
+    thread::sleep(Duration::from_secs(3));
+    
+    let repo = git2::Repository::open(bare_dir.path()).expect("Backup target should be a git repo");
+    assert!(repo.is_bare(), "Empty backup target should be initialized as a bare repo");
+    
+    let content = read_committed_file(bare_dir.path(), "HEAD", "bare_object.moo")
+        .expect("Object should be committed to the bare repo");
+    assert!(content.contains("object #8"));
+    
+    let commits = backup_commits(bare_dir.path(), "HEAD");
+    assert_eq!(commits.len(), 1);
+    assert_eq!(commits[0].2, Some(change_id));
+    
+    assert_eq!(git_backup_status_field(&server.client(), "status").await, "succeeded");
+}
+
+#[tokio::test]
+async fn test_git_backup_pushes_and_refuses_diverged_remote() {
+    let remote_dir = TempDir::new().unwrap();
+    git2::Repository::init_bare(remote_dir.path()).expect("Failed to init remote repo");
+    let remote_url = format!("file://{}", remote_dir.path().display());
+    let temp_db = TempDir::new().unwrap();
+    
+    let config = moor_vcs_worker::Config::with_db_path(temp_db.path().to_path_buf())
+        .with_git_backup(remote_url, None);
+    
+    let server = TestServer::start_with_config(config).await.expect("Failed to start server");
+    let client = server.client();
+    
+    // The first backup is pushed to the remote's main branch
+    let first_id = submit_object(&server, "pushed_object", 9).await;
+    thread::sleep(Duration::from_secs(3));
+    
+    let commits = backup_commits(remote_dir.path(), "refs/heads/main");
+    assert_eq!(commits.len(), 1, "Backup should be pushed to the remote");
+    assert_eq!(commits[0].2, Some(first_id));
+    assert_eq!(git_backup_status_field(&client, "status").await, "succeeded");
+    
+    // Someone rewrites the remote's history behind the backup's back
+    let rewritten = {
+        let remote = git2::Repository::open(remote_dir.path()).unwrap();
+        let signature = git2::Signature::now("Someone Else", "someone@example.com").unwrap();
+        let tree_oid = remote.treebuilder(None).unwrap().write().unwrap();
+        let tree = remote.find_tree(tree_oid).unwrap();
+        let oid = remote
+            .commit(None, &signature, &signature, "Unrelated history", &tree, &[])
+            .unwrap();
+        remote.reference("refs/heads/main", oid, true, "rewrite history").unwrap();
+        oid
+    };
+    
+    // The next backup must not overwrite it
+    submit_object(&server, "second_object", 10).await;
+    thread::sleep(Duration::from_secs(3));
+    
+    let remote = git2::Repository::open(remote_dir.path()).unwrap();
+    assert_eq!(
+        remote.refname_to_id("refs/heads/main").unwrap(),
+        rewritten,
+        "Diverged remote must not be force-pushed"
+    );
+    
+    assert_eq!(git_backup_status_field(&client, "status").await, "failed");
+    let error = git_backup_status_field(&client, "error").await;
+    assert!(error.contains("diverged"), "Expected divergence error, got: {}", error);
+}
+
+#[tokio::test]
+async fn test_backup_runs_are_serialized_and_recorded() {
+    let bare_dir = TempDir::new().unwrap();
+    let temp_db = TempDir::new().unwrap();
+    
+    let config = moor_vcs_worker::Config::with_db_path(temp_db.path().to_path_buf())
+        .with_git_backup(bare_dir.path().to_str().unwrap().to_string(), None);
+    
+    let server = TestServer::start_with_config(config).await.expect("Failed to start server");
+    let client = server.client();
+    
+    // The submit queues a background run; two manual runs race it for the same repository
+    let change_id = submit_object(&server, "serialized_object", 11).await;
+    let (first, second) = tokio::join!(client.backup_run(), client.backup_run());
+    let first = first.expect("Failed to run backup");
+    let second = second.expect("Failed to run backup");
+    first.assert_success("backup/run");
+    second.assert_success("backup/run");
+    assert_eq!(first["result"]["trigger"], "backup/run");
+    assert_eq!(first["result"]["status"], "succeeded");
+    
+    // Let the background run finish too
+    thread::sleep(Duration::from_secs(3));
+    
+    // The change was committed exactly once, however the runs interleaved
+    let commits = backup_commits(bare_dir.path(), "HEAD");
+    assert_eq!(commits.len(), 1, "Expected a single backup commit, got: {:?}", commits);
+    assert_eq!(commits[0].2, Some(change_id));
+    
+    let history = client.backup_history(None).await.expect("Failed to get backup history");
+    history.assert_success("backup/history");
+    let runs = history["result"].as_array().expect("History should be a list");
+    assert_eq!(runs.len(), 3, "Expected every run to be recorded: {}", history);
+    
+    let ids: Vec<i64> = runs.iter().map(|run| run["id"].as_i64().unwrap()).collect();
+    assert_eq!(ids, vec![3, 2, 1], "History should be newest first");
+    assert!(runs.iter().all(|run| run["status"] == "succeeded"), "{}", history);
+    assert!(runs.iter().any(|run| run["trigger"] == "change/submit"), "{}", history);
+    let total_commits: i64 = runs.iter().map(|run| run["commits"].as_i64().unwrap()).sum();
+    assert_eq!(total_commits, 1);
+    
+    let head = git2::Repository::open(bare_dir.path())
+        .unwrap()
+        .refname_to_id("HEAD")
+        .unwrap()
+        .to_string();
+    assert!(runs.iter().all(|run| run["commit"] == head.as_str()), "{}", history);
+    assert!(runs.iter().all(|run| run["object_count"] == 1), "{}", history);
+    
+    let limited = client.backup_history(Some(1)).await.expect("Failed to get backup history");
+    assert_eq!(limited["result"].as_array().map(|runs| runs.len()), Some(1));
+    
+    let status = client.backup_status().await.expect("Failed to get backup status");
+    status.assert_success("backup/status");
+    assert_eq!(status["result"]["enabled"], 1);
+    assert_eq!(status["result"]["current_run"], "");
+    assert_eq!(status["result"]["last_run"]["id"], 3);
+    assert_eq!(status["result"]["last_success"]["id"], 3);
+}
+
+#[tokio::test]
+async fn test_git_backup_fails_when_an_object_cannot_be_rendered() {
+    use moor_vcs_worker::providers::backups::BackupProvider;
+    use moor_vcs_worker::types::{BackupRunStatus, VcsObjectType};
+    
+    let bare_dir = TempDir::new().unwrap();
+    
+    // Backups aren't configured on the server, so submitting doesn't start a run
+    let server = TestServer::start().await.expect("Failed to start server");
+    submit_object(&server, "broken_object", 12).await;
+    
+    // Lose the blob of the submitted object
+    let database = server.database();
+    let hash = database
+        .refs()
+        .get_ref(VcsObjectType::MooObject, "broken_object", None)
+        .unwrap()
+        .expect("Object should have a ref");
+    database.objects().delete(&hash).unwrap();
+    
+    let config = moor_vcs_worker::Config::with_db_path(server.db_path())
+        .with_git_backup(bare_dir.path().to_str().unwrap().to_string(), None);
+    let run = database.backups().create_run("test").unwrap();
+    let run = moor_vcs_worker::git_backup::run_git_backup(database, &config, run);
+    
+    // The run fails rather than committing the change without the object
+    assert_eq!(run.status, BackupRunStatus::Failed);
+    let error = run.error.unwrap_or_default();
+    assert!(error.contains("broken_object"), "got: {}", error);
+    assert!(
+        backup_commits(bare_dir.path(), "HEAD").is_empty(),
+        "Nothing should be committed when an object can't be dumped"
+    );
+}
+
+#[tokio::test]
+async fn test_backup_operations_without_configuration() {
+    let server = TestServer::start().await.expect("Failed to start server");
+    let client = server.client();
+    
+    let response = client.backup_run().await.expect("Request should complete");
+    response.assert_failure("backup/run without a backup repo");
+    assert_eq!(response["error"]["code"], "invalid_args");
+    
+    let status = client.backup_status().await.expect("Failed to get backup status");
+    status.assert_success("backup/status");
+    assert_eq!(status["result"]["enabled"], 0);
+    assert_eq!(status["result"]["last_run"], "");
+    
+    let history = client.backup_history(None).await.expect("Failed to get backup history");
+    history.assert_success("backup/history");
+    assert_eq!(history["result"].as_array().map(|runs| runs.len()), Some(0));
 }
 
 #[tokio::test]
@@ -354,22 +666,13 @@ fn test_filename_sanitization() {
 }
 
 #[test]
-fn test_token_injection() {
-    use moor_vcs_worker::git_backup::inject_token_into_url;
-    
-    assert_eq!(
-        inject_token_into_url("https://github.com/user/repo.git", "mytoken"),
-        "https://mytoken@github.com/user/repo.git"
-    );
+fn test_remote_url_detection() {
+    use moor_vcs_worker::git_backup::is_remote_url;
     
-    assert_eq!(
-        inject_token_into_url("http://example.com/repo.git", "token123"),
-        "http://token123@example.com/repo.git"
-    );
+    assert!(is_remote_url("https://github.com/user/repo.git"));
+    assert!(is_remote_url("git@github.com:user/repo.git"));
+    assert!(is_remote_url("file:///srv/git/backup.git"));
     
-    // Non-HTTP URLs remain unchanged
-    assert_eq!(
-        inject_token_into_url("git@github.com:user/repo.git", "token"),
-        "git@github.com:user/repo.git"
-    );
+    // Local paths are committed to directly
+    assert!(!is_remote_url("/srv/git/backup.git"));
 }
@@ -13,25 +13,7 @@ use common::*;
 /// Helper to set up a git repository for testing
 fn setup_git_repo() -> TempDir {
     let git_dir = TempDir::new().expect("Failed to create temp dir");
-    
-    std::process::Command::new("git")
-        .current_dir(git_dir.path())
-        .args(&["init"])
-        .output()
-        .expect("Failed to init git repo");
-    
-    std::process::Command::new("git")
-        .current_dir(git_dir.path())
-        .args(&["config", "user.email", "test@test.com"])
-        .output()
-        .expect("Failed to set git user email");
-    
-    std::process::Command::new("git")
-        .current_dir(git_dir.path())
-        .args(&["config", "user.name", "Test User"])
-        .output()
-        .expect("Failed to set git user name");
-    
+    git2::Repository::init(git_dir.path()).expect("Failed to init git repo");
     git_dir
 }
 
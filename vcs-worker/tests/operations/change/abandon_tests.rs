@@ -23,7 +23,7 @@ async fn test_abandon_local_change_clears_top_and_cleans_up() {
     // Add some objects to the change
     let object_content_1 = moo_to_lines(&load_moo_file("test_object.moo"));
     let content_str_1 = object_content_1.join("\n");
-    let sha256_1 = TestServer::calculate_sha256(&content_str_1);
+    let sha256_1 = TestServer::calculate_hash(&content_str_1);
 
     client
         .object_update("test_object_1", object_content_1)
@@ -32,7 +32,7 @@ async fn test_abandon_local_change_clears_top_and_cleans_up() {
 
     let object_content_2 = moo_to_lines(&load_moo_file("detailed_test_object.moo"));
     let content_str_2 = object_content_2.join("\n");
-    let sha256_2 = TestServer::calculate_sha256(&content_str_2);
+    let sha256_2 = TestServer::calculate_hash(&content_str_2);
 
     client
         .object_update("test_object_2", object_content_2)
@@ -1,6 +1,7 @@
 //! Tests for change approval operations
 
 use crate::common::*;
+use moor_vcs_worker::providers::workspace::WorkspaceProvider;
 use moor_vcs_worker::types::ChangeStatus;
 
 #[tokio::test]
@@ -22,7 +23,7 @@ async fn test_approve_change_moves_to_merged() {
 
     let object_content = moo_to_lines(&load_moo_file("test_object.moo"));
     let content_str = object_content.join("\n");
-    let sha256 = TestServer::calculate_sha256(&content_str);
+    let sha256 = TestServer::calculate_hash(&content_str);
 
     client
         .object_update("approved_object", object_content)
@@ -286,3 +287,170 @@ async fn test_approve_with_empty_change_id() {
     println!("\n✅ Test passed: Cannot approve with empty change ID");
 }
 
+/// Approve a base object, stash a change editing it, approve an upstream edit and
+/// switch back to the stashed change. Returns the stashed change ID.
+async fn setup_diverged_change(
+    server: &TestServer,
+    ours: Vec<String>,
+    theirs: Vec<String>,
+) -> String {
+    let client = server.client();
+    let db = server.db_assertions();
+
+    for (lines, stash) in [
+        (object_lines("base look", "base examine"), false),
+        (ours, true),
+        (theirs, false),
+    ] {
+        client
+            .object_update("approve_object", lines)
+            .await
+            .expect("Failed to update object")
+            .assert_success("Update object");
+        let (change_id, _) = db.require_top_change();
+        if stash {
+            client.change_stash().await.expect("Failed to stash");
+        } else {
+            client
+                .change_approve(&change_id)
+                .await
+                .expect("Failed to approve")
+                .assert_success("Approve");
+        }
+    }
+
+    let stashed = server
+        .database()
+        .workspace()
+        .get_idle_changes()
+        .expect("Failed to list idle changes");
+    let change_id = stashed[0].id.clone();
+    client
+        .change_switch(&change_id)
+        .await
+        .expect("Failed to switch")
+        .assert_success("Switch");
+    change_id
+}
+
+#[tokio::test]
+async fn test_approve_refuses_conflicting_verb_edits() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+
+    println!("Test: Approve refuses a change whose verbs were also changed upstream");
+
+    let change_id = setup_diverged_change(
+        &server,
+        object_lines("our look", "base examine"),
+        object_lines("their look", "base examine"),
+    )
+    .await;
+
+    println!("\nStep 1: Approving without force...");
+    let response = client
+        .change_approve(&change_id)
+        .await
+        .expect("Request should complete");
+    response.assert_failure("Approve conflicting change");
+    assert_eq!(response["error"]["code"], "conflict");
+    let message = response["error"]["message"].as_str().unwrap_or_default();
+    assert!(
+        message.contains("approve_object") && message.contains("verbs: look"),
+        "Error should list the conflicting object and verb, got: {}",
+        message
+    );
+    assert!(
+        !message.contains("examine"),
+        "Unchanged verbs should not be listed, got: {}",
+        message
+    );
+    println!("✅ Approval refused: {}", message);
+
+    println!("\nStep 2: Approving with force...");
+    client
+        .rpc_call("change/approve", vec![json!(change_id), json!("true")])
+        .await
+        .expect("Request should complete")
+        .assert_success("Force approve");
+    let change = server
+        .database()
+        .index()
+        .get_change(&change_id)
+        .expect("Failed to get change")
+        .expect("Change should exist");
+    assert_eq!(change.status, ChangeStatus::Merged);
+    println!("✅ Forced approval merged the change");
+
+    println!("\n✅ Test passed: Conflicting verb edits require force");
+}
+
+#[tokio::test]
+async fn test_approve_allows_disjoint_verb_edits() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+
+    println!("Test: Approve allows a change whose edits don't overlap upstream edits");
+
+    let change_id = setup_diverged_change(
+        &server,
+        object_lines("our look", "base examine"),
+        object_lines("base look", "their examine"),
+    )
+    .await;
+
+    client
+        .change_approve(&change_id)
+        .await
+        .expect("Failed to approve")
+        .assert_success("Approve disjoint change");
+
+    println!("✅ Test passed: Disjoint verb edits approve without force");
+}
+
+#[tokio::test]
+async fn test_approve_top_change_with_details() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+
+    println!("Test: Approving the top change with details returns what was approved");
+
+    // Step 1: Add an object in the current change
+    println!("\nStep 1: Adding an object to the top change...");
+    client
+        .object_update("approve_object", object_lines("hello", "hi"))
+        .await
+        .expect("Failed to update object")
+        .assert_success("Update object");
+    let (change_id, _) = server.db_assertions().require_top_change();
+
+    // Step 2: Approve it with details
+    println!("\nStep 2: Approving with details...");
+    let response = client
+        .rpc_call(
+            "change/approve",
+            vec![json!(change_id), json!("false"), json!("true")],
+        )
+        .await
+        .expect("Request should complete");
+    response.assert_success("Approve with details");
+    let changes = response["result"]["changes"]
+        .as_array()
+        .expect("Approve should return object changes");
+    assert_eq!(changes.len(), 1, "got: {}", response);
+    assert_eq!(
+        changes[0]["verb_diffs"]["look"],
+        json!(["@@ -0,0 +1,1 @@", "+return \"hello\";"]),
+        "got: {}",
+        response
+    );
+    println!("✅ Approval returned the added object with its verb diffs");
+
+    println!("\n✅ Test passed: Top change approval honours details");
+}
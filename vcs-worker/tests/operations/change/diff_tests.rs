@@ -0,0 +1,176 @@
+//! Tests for whole-change diff operations
+
+use crate::common::*;
+use moor_vcs_worker::types::VerbRenameHint;
+
+#[tokio::test]
+async fn test_change_diff_includes_line_diffs_and_hints() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+
+    println!("Test: change/diff returns verb diffs and rename hints for a change");
+
+    // Step 1: Base object merged to the index
+    println!("\nStep 1: Approving base object...");
+    server
+        .client()
+        .approve_object("diff_object", object_lines("base look", "base examine"))
+        .await;
+    println!("✅ Base object approved");
+
+    // Step 2: Local change edits `look` and records a rename hint
+    println!("\nStep 2: Editing look in a local change...");
+    client
+        .object_update("diff_object", object_lines("new look", "base examine"))
+        .await
+        .expect("Failed to update object")
+        .assert_success("Update object");
+    let (change_id, _) = server.db_assertions().require_top_change();
+    let mut change = server
+        .database()
+        .index()
+        .get_change(&change_id)
+        .expect("Failed to get change")
+        .expect("Change should exist");
+    change.verb_rename_hints.push(VerbRenameHint {
+        object_name: "other_object".to_string(),
+        from_verb: "peek".to_string(),
+        to_verb: "look".to_string(),
+    });
+    server
+        .database()
+        .index()
+        .update_change(&change)
+        .expect("Failed to update change");
+    println!("✅ Local change {} created", change_id);
+
+    // Step 3: Diff the change
+    println!("\nStep 3: Running change/diff...");
+    let response = client
+        .change_diff(&change_id, None)
+        .await
+        .expect("Failed to diff change");
+    response.assert_success("Change diff");
+    let result = &response["result"];
+    assert_eq!(result["change_id"], change_id.as_str());
+    assert_eq!(result["diff"]["objects_modified"], json!(["diff_object"]));
+    assert_eq!(
+        result["diff"]["changes"][0]["verb_diffs"]["look"],
+        json!([
+            "@@ -1,1 +1,1 @@",
+            "-return \"base look\";",
+            "+return \"new look\";"
+        ]),
+        "got: {}",
+        response
+    );
+    assert!(
+        result["diff"]["changes"][0]["verb_diffs"]
+            .get("examine")
+            .is_none(),
+        "Unchanged verbs should have no diff, got: {}",
+        response
+    );
+    println!("✅ Unified diff returned for look");
+
+    assert_eq!(
+        result["verb_rename_hints"],
+        json!([{"object_name": "other_object", "from_verb": "peek", "to_verb": "look"}]),
+        "got: {}",
+        response
+    );
+    println!("✅ Rename hints returned");
+
+    println!("\n✅ Test passed: change/diff returns the complete changelist");
+}
+
+#[tokio::test]
+async fn test_change_diff_against_baseline() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+
+    println!("Test: change/diff compares against an older merged baseline");
+
+    let first_id = server
+        .client()
+        .approve_object("diff_object", object_lines("base look", "base examine"))
+        .await;
+    server
+        .client()
+        .approve_object("diff_object", object_lines("new look", "base examine"))
+        .await;
+    let third_id = server
+        .client()
+        .approve_object("diff_object", object_lines("new look", "new examine"))
+        .await;
+
+    // Against its previous version, only examine changed
+    println!("\nDiffing the last change against its previous version...");
+    let response = client
+        .change_diff(&third_id, None)
+        .await
+        .expect("Failed to diff change");
+    response.assert_success("Change diff");
+    let verb_diffs = &response["result"]["diff"]["changes"][0]["verb_diffs"];
+    assert!(verb_diffs.get("examine").is_some(), "got: {}", response);
+    assert!(verb_diffs.get("look").is_none(), "got: {}", response);
+    println!("✅ Only examine differs from the previous version");
+
+    // Against the first change, both verbs changed
+    println!("\nDiffing the last change against the first change...");
+    let response = client
+        .change_diff(&third_id, Some(&first_id))
+        .await
+        .expect("Failed to diff change");
+    response.assert_success("Change diff against baseline");
+    assert_eq!(response["result"]["baseline_change_id"], first_id.as_str());
+    let verb_diffs = &response["result"]["diff"]["changes"][0]["verb_diffs"];
+    assert_eq!(
+        verb_diffs["look"],
+        json!([
+            "@@ -1,1 +1,1 @@",
+            "-return \"base look\";",
+            "+return \"new look\";"
+        ]),
+        "got: {}",
+        response
+    );
+    assert!(verb_diffs.get("examine").is_some(), "got: {}", response);
+    println!("✅ Both verbs differ from the baseline");
+
+    println!("\n✅ Test passed: Baseline diffs span several merged changes");
+}
+
+#[tokio::test]
+async fn test_change_diff_unknown_changes() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+
+    println!("Test: change/diff reports unknown changes and baselines as not_found");
+
+    let response = client
+        .change_diff("deadbeef", None)
+        .await
+        .expect("Request should complete");
+    response.assert_failure("Diff unknown change");
+    assert_eq!(response["error"]["code"], "not_found");
+    println!("✅ Unknown change reported as not_found");
+
+    let change_id = server
+        .client()
+        .approve_object("diff_object", object_lines("base look", "base examine"))
+        .await;
+    let response = client
+        .change_diff(&change_id, Some("deadbeef"))
+        .await
+        .expect("Request should complete");
+    response.assert_failure("Diff against unknown baseline");
+    assert_eq!(response["error"]["code"], "not_found");
+    println!("✅ Unknown baseline reported as not_found");
+}
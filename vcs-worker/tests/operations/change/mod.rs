@@ -2,15 +2,21 @@
 //!
 //! This module is organized by operation type:
 //! - create_tests: Tests for creating changes
+//! - diff_tests: Tests for whole-change line-level diffs
+//! - patch_tests: Tests for exporting a change as a patch and importing it on another worker
 //! - abandon_tests: Tests for abandoning changes and cleanup
 //! - approve_tests: Tests for approving changes (merge to main history)
 //! - stash_tests: Tests for stashing changes to workspace
+//! - rebase_tests: Tests for rebasing idle changes onto a moved index
 //! - submit_tests: Tests for submitting changes (remote vs local behavior)
 
 mod abandon_tests;
 mod abandon_diff_inversion_tests;
 mod approve_tests;
 mod create_tests;
+mod diff_tests;
+mod patch_tests;
+mod rebase_tests;
 mod stash_tests;
 mod submit_tests;
 
@@ -0,0 +1,215 @@
+//! Tests for change export and import between unrelated workers
+
+use crate::common::*;
+use moor_vcs_worker::types::{ChangeStatus, VcsObjectType};
+
+/// Load the current content of an object
+fn current_dump(server: &TestServer, name: &str) -> Option<String> {
+    let sha256 = server
+        .database()
+        .refs()
+        .get_ref(VcsObjectType::MooObject, name, None)
+        .expect("Failed to get ref")?;
+    server
+        .database()
+        .objects()
+        .get(&sha256)
+        .expect("Failed to get object")
+}
+
+/// Export a change from a server, returning the patch JSON
+async fn export_change(server: &TestServer, change_id: &str) -> String {
+    let response = server
+        .client()
+        .change_export(change_id)
+        .await
+        .expect("Failed to export change");
+    response.assert_success("Export change");
+    response.require_result_str("Export change").to_string()
+}
+
+#[tokio::test]
+async fn test_change_patch_applies_on_another_worker() {
+    let source = TestServer::start()
+        .await
+        .expect("Failed to start source server");
+    let target = TestServer::start()
+        .await
+        .expect("Failed to start target server");
+
+    println!("Test: A change exported from one worker applies on another");
+
+    // Step 1: Both worlds share the same base object
+    println!("\nStep 1: Approving the same base object on both workers...");
+    source
+        .client()
+        .approve_object("patch_object", object_lines("base look", "base examine"))
+        .await;
+    target
+        .client()
+        .approve_object("patch_object", object_lines("base look", "base examine"))
+        .await;
+    println!("✅ Base object approved on both workers");
+
+    // Step 2: The source fixes `look` and adds a new object
+    println!("\nStep 2: Approving a fix on the source worker...");
+    source
+        .client()
+        .object_update("patch_object", object_lines("fixed look", "base examine"))
+        .await
+        .expect("Failed to update object")
+        .assert_success("Update object");
+    source
+        .client()
+        .object_update("patch_helper", object_lines("helper look", "base examine"))
+        .await
+        .expect("Failed to add object")
+        .assert_success("Add object");
+    let (fix_id, _) = source.db_assertions().require_top_change();
+    source
+        .client()
+        .change_approve(&fix_id)
+        .await
+        .expect("Failed to approve")
+        .assert_success("Approve fix");
+    println!("✅ Fix approved: {}", fix_id);
+
+    // Step 3: Export the fix
+    println!("\nStep 3: Exporting the fix...");
+    let patch = export_change(&source, &fix_id).await;
+    let parsed: serde_json::Value = serde_json::from_str(&patch).expect("Patch should be JSON");
+    assert_eq!(parsed["format_version"], 1);
+    assert_eq!(parsed["change"]["id"], fix_id.as_str());
+    assert_eq!(
+        parsed["objects"].as_array().map(|o| o.len()),
+        Some(2),
+        "Patch should carry both objects, got: {}",
+        patch
+    );
+    println!("✅ Patch exported ({} bytes)", patch.len());
+
+    // Step 4: Import it on the target
+    println!("\nStep 4: Importing the patch on the target worker...");
+    let response = target
+        .client()
+        .change_import(&patch)
+        .await
+        .expect("Failed to import patch");
+    response.assert_success("Import patch");
+    assert_eq!(response["result"]["source_change_id"], fix_id.as_str());
+    let imported_id = response["result"]["change_id"]
+        .as_str()
+        .expect("Import should return the new change ID")
+        .to_string();
+    println!("✅ Patch imported as change {}", imported_id);
+
+    // Step 5: The target now has a local change carrying the fix
+    println!("\nStep 5: Verifying the imported change...");
+    let (top_id, top_change) = target.db_assertions().require_top_change();
+    assert_eq!(top_id, imported_id);
+    assert_eq!(top_change.status, ChangeStatus::Local);
+    assert_eq!(top_change.name, parsed["change"]["name"].as_str().unwrap());
+    assert!(
+        top_change
+            .modified_objects
+            .iter()
+            .any(|o| o.name == "patch_object"),
+        "Fixed object should be modified"
+    );
+    assert!(
+        top_change
+            .added_objects
+            .iter()
+            .any(|o| o.name == "patch_helper"),
+        "New object should be added"
+    );
+    let fixed = current_dump(&target, "patch_object").expect("Object should exist");
+    assert!(fixed.contains("fixed look"), "Fix applied: {}", fixed);
+    assert!(
+        current_dump(&target, "patch_helper").is_some(),
+        "New object should exist on the target"
+    );
+    println!("✅ Imported change carries the fix");
+
+    println!("\n✅ Test passed: Change patches move fixes between workers");
+}
+
+#[tokio::test]
+async fn test_change_import_refuses_diverged_objects() {
+    let source = TestServer::start()
+        .await
+        .expect("Failed to start source server");
+    let target = TestServer::start()
+        .await
+        .expect("Failed to start target server");
+
+    println!("Test: Importing a patch over a diverged object is refused");
+
+    source
+        .client()
+        .approve_object("patch_object", object_lines("base look", "base examine"))
+        .await;
+    let fix_id = source
+        .client()
+        .approve_object("patch_object", object_lines("fixed look", "base examine"))
+        .await;
+    let patch = export_change(&source, &fix_id).await;
+
+    // The target's copy of the object differs from the patch's base
+    target
+        .client()
+        .approve_object("patch_object", object_lines("other look", "base examine"))
+        .await;
+
+    println!("\nImporting the patch...");
+    let response = target
+        .client()
+        .change_import(&patch)
+        .await
+        .expect("Request should complete");
+    response.assert_failure("Import over diverged object");
+    assert_eq!(response["error"]["code"], "conflict");
+    let message = response["error"]["message"].as_str().unwrap_or_default();
+    assert!(
+        message.contains("patch_object (modified locally)"),
+        "Conflict should name the object, got: {}",
+        message
+    );
+    println!("✅ Conflict reported: {}", message);
+
+    // Nothing was imported
+    let dump = current_dump(&target, "patch_object").expect("Object should exist");
+    assert!(
+        dump.contains("other look"),
+        "Target left untouched: {}",
+        dump
+    );
+    let (_, top_change) = target.db_assertions().require_top_change();
+    assert_eq!(
+        top_change.status,
+        ChangeStatus::Merged,
+        "No local change should be created"
+    );
+    println!("✅ Target left untouched");
+
+    println!("\n✅ Test passed: Diverged objects block the import");
+}
+
+#[tokio::test]
+async fn test_change_import_rejects_malformed_patch() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+
+    println!("Test: Malformed patches are rejected as invalid_args");
+
+    let response = server
+        .client()
+        .change_import("not a patch")
+        .await
+        .expect("Request should complete");
+    response.assert_failure("Import malformed patch");
+    assert_eq!(response["error"]["code"], "invalid_args");
+
+    println!("✅ Malformed patch reported as invalid_args");
+}
@@ -0,0 +1,216 @@
+//! Tests for change rebase operations
+
+use crate::common::*;
+use moor_vcs_worker::providers::workspace::WorkspaceProvider;
+use moor_vcs_worker::types::{ChangeStatus, VcsObjectType};
+
+/// Update the object in a fresh change and stash it, returning the idle change ID
+async fn stash_object(server: &TestServer, lines: Vec<String>) -> String {
+    let client = server.client();
+    client
+        .object_update("rebase_object", lines)
+        .await
+        .expect("Failed to update object")
+        .assert_success("Update object");
+    let (change_id, _) = server.db_assertions().require_top_change();
+    client
+        .change_stash()
+        .await
+        .expect("Failed to stash")
+        .assert_success("Stash");
+    change_id
+}
+
+/// Load the dump of the object version recorded in an idle change
+fn idle_object_dump(server: &TestServer, change_id: &str) -> String {
+    let change = server
+        .database()
+        .workspace()
+        .get_workspace_change(change_id)
+        .expect("Failed to get workspace change")
+        .expect("Change should exist in workspace");
+    let version = change
+        .modified_objects
+        .iter()
+        .chain(change.added_objects.iter())
+        .find(|o| o.name == "rebase_object")
+        .expect("Change should contain the object")
+        .version;
+    let sha256 = server
+        .database()
+        .refs()
+        .get_ref(VcsObjectType::MooObject, "rebase_object", Some(version))
+        .expect("Failed to get ref")
+        .expect("Ref should exist");
+    server
+        .database()
+        .objects()
+        .get(&sha256)
+        .expect("Failed to get object")
+        .expect("Object content should exist")
+}
+
+#[tokio::test]
+async fn test_rebase_merges_disjoint_verb_edits() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+
+    println!("Test: Rebase merges edits to different verbs cleanly");
+
+    // Step 1: Base object merged to the index
+    println!("\nStep 1: Approving base object...");
+    let base_id = server
+        .client()
+        .approve_object("rebase_object", object_lines("base look", "base examine"))
+        .await;
+    println!("✅ Base change approved: {}", base_id);
+
+    // Step 2: Idle change edits `look`
+    println!("\nStep 2: Stashing a change that edits look...");
+    let idle_id = stash_object(&server, object_lines("our look", "base examine")).await;
+    let idle = server
+        .database()
+        .workspace()
+        .get_workspace_change(&idle_id)
+        .expect("Failed to get workspace change")
+        .expect("Change should exist in workspace");
+    assert_eq!(
+        idle.index_change_id.as_deref(),
+        Some(base_id.as_str()),
+        "Idle change should be based on the base change"
+    );
+    println!("✅ Idle change based on {}", base_id);
+
+    // Step 3: Upstream change edits `examine` and is merged
+    println!("\nStep 3: Approving an upstream change that edits examine...");
+    let upstream_id = server
+        .client()
+        .approve_object("rebase_object", object_lines("base look", "their examine"))
+        .await;
+    println!("✅ Upstream change approved: {}", upstream_id);
+
+    // Step 4: Rebase the idle change
+    println!("\nStep 4: Rebasing the idle change...");
+    let response = client
+        .change_rebase(&idle_id)
+        .await
+        .expect("Failed to rebase");
+    response.assert_success("Rebase");
+    assert_eq!(response["result"]["status"], "rebased", "got: {}", response);
+    assert_eq!(response["result"]["index_change_id"], upstream_id.as_str());
+    assert_eq!(
+        response["result"]["merged_objects"],
+        json!(["rebase_object"]),
+        "got: {}",
+        response
+    );
+    println!("✅ Rebase reported a clean merge");
+
+    // Step 5: The idle change now carries both edits
+    println!("\nStep 5: Verifying the merged object...");
+    let dump = idle_object_dump(&server, &idle_id);
+    assert!(dump.contains("our look"), "Local edit kept: {}", dump);
+    assert!(
+        dump.contains("their examine"),
+        "Upstream edit merged: {}",
+        dump
+    );
+
+    let rebased = server
+        .database()
+        .workspace()
+        .get_workspace_change(&idle_id)
+        .expect("Failed to get workspace change")
+        .expect("Change should exist in workspace");
+    assert_eq!(rebased.status, ChangeStatus::Idle, "Change stays idle");
+    assert_eq!(
+        rebased.index_change_id.as_deref(),
+        Some(upstream_id.as_str()),
+        "Change should be re-based on the upstream change"
+    );
+    println!("✅ Merged object contains both edits");
+
+    // Step 6: Rebasing again is a no-op
+    println!("\nStep 6: Rebasing again...");
+    let again = client
+        .change_rebase(&idle_id)
+        .await
+        .expect("Failed to rebase");
+    assert_eq!(again["result"]["status"], "up_to_date", "got: {}", again);
+    println!("✅ Second rebase is up to date");
+
+    println!("\n✅ Test passed: Disjoint verb edits merge cleanly");
+}
+
+#[tokio::test]
+async fn test_rebase_reports_conflicting_verb_edits() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+
+    println!("Test: Rebase reports a conflict when both sides edit the same verb");
+
+    server
+        .client()
+        .approve_object("rebase_object", object_lines("base look", "base examine"))
+        .await;
+    let idle_id = stash_object(&server, object_lines("our look", "base examine")).await;
+    let before = idle_object_dump(&server, &idle_id);
+    server
+        .client()
+        .approve_object("rebase_object", object_lines("their look", "base examine"))
+        .await;
+
+    println!("\nRebasing the idle change...");
+    let response = client
+        .change_rebase(&idle_id)
+        .await
+        .expect("Failed to rebase");
+    response.assert_success("Rebase");
+    assert_eq!(
+        response["result"]["status"], "conflict",
+        "got: {}",
+        response
+    );
+
+    let conflicts = response["result"]["conflicts"]
+        .as_array()
+        .unwrap_or_else(|| panic!("Rebase should list conflicts: {}", response));
+    assert_eq!(conflicts.len(), 1, "One object should conflict");
+    assert_eq!(conflicts[0]["obj_id"], "rebase_object");
+    assert_eq!(conflicts[0]["verbs"], json!(["look"]));
+    assert_eq!(conflicts[0]["props"], json!([]));
+    println!("✅ Conflict reported for verb 'look'");
+
+    // The idle change is left untouched
+    assert_eq!(
+        idle_object_dump(&server, &idle_id),
+        before,
+        "Conflicting rebase must not modify the change"
+    );
+    println!("✅ Idle change left untouched");
+
+    println!("\n✅ Test passed: Conflicting verb edits are reported");
+}
+
+#[tokio::test]
+async fn test_rebase_requires_idle_change() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+
+    println!("Test: Rebase of an unknown change fails with not_found");
+
+    let response = client
+        .change_rebase("deadbeef")
+        .await
+        .expect("Request should complete");
+    response.assert_failure("Rebase unknown change");
+    assert_eq!(response["error"]["code"], "not_found");
+
+    println!("✅ Unknown change reported as not_found");
+}
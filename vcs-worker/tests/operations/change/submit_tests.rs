@@ -19,7 +19,7 @@ async fn test_submit_on_non_remote_index_instantly_approves() {
     let source_url = server
         .database()
         .index()
-        .get_source()
+        .get_remote(DEFAULT_REMOTE)
         .expect("Failed to get source URL");
     assert!(
         source_url.is_none(),
@@ -250,14 +250,20 @@ async fn test_submit_on_remote_index_sends_to_workspace() {
     server
         .database()
         .index()
-        .set_source(source_url)
+        .set_remote(&Remote {
+            name: DEFAULT_REMOTE.to_string(),
+            url: source_url.to_string(),
+            api_key: None,
+            user_id: None,
+        })
         .expect("Failed to set source URL");
 
     let configured_source = server
         .database()
         .index()
-        .get_source()
-        .expect("Failed to get source URL");
+        .get_remote(DEFAULT_REMOTE)
+        .expect("Failed to get source URL")
+        .map(|remote| remote.url);
     assert_eq!(
         configured_source,
         Some(source_url.to_string()),
@@ -359,3 +365,96 @@ async fn test_submit_on_remote_index_sends_to_workspace() {
     );
 }
 
+
+// Multi-threaded runtime: the downstream worker blocks while it calls the upstream worker,
+// and both servers run on the test's runtime
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_submit_to_upstream_lands_in_upstream_workspace() {
+    let upstream = TestServer::start()
+        .await
+        .expect("Failed to start upstream server");
+    let downstream = TestServer::start()
+        .await
+        .expect("Failed to start downstream server");
+    let upstream_client = upstream.client();
+    let downstream_client = downstream.client();
+
+    println!("Test: change/submit on a clone sends the change to the upstream workspace");
+
+    // Step 1: Give upstream some history and clone it downstream
+    println!("\nStep 1: Creating upstream history and cloning it...");
+    upstream_client
+        .object_update_from_file("base_object", "test_object.moo")
+        .await
+        .expect("Failed to update object")
+        .assert_success("Upstream update");
+    let (base_change_id, _) = upstream.db_assertions().require_top_change();
+    upstream_client
+        .change_approve(&base_change_id)
+        .await
+        .expect("Failed to approve")
+        .assert_success("Upstream approve");
+
+    downstream_client
+        .clone_import(&format!("{}/api/clone", upstream.base_url()))
+        .await
+        .expect("Failed to clone")
+        .assert_success("Clone upstream");
+    println!("✅ Downstream cloned from upstream");
+
+    // Step 2: Make a change downstream and submit it
+    println!("\nStep 2: Submitting a downstream change...");
+    downstream_client
+        .change_create("upstream_feature", "test_author", Some("Feature for upstream"))
+        .await
+        .expect("Failed to create change");
+    downstream_client
+        .object_update_from_file("feature_object", "test_object_1.moo")
+        .await
+        .expect("Failed to update object")
+        .assert_success("Downstream update");
+    let (change_id, _) = downstream.db_assertions().require_top_change();
+
+    downstream_client
+        .change_submit()
+        .await
+        .expect("Failed to submit change")
+        .assert_success("Submit change");
+    println!("✅ Change submitted downstream");
+
+    // Step 3: The change is awaiting review on both sides
+    println!("\nStep 3: Verifying the change reached the upstream workspace...");
+    let upstream_change = upstream
+        .database()
+        .workspace()
+        .get_workspace_change(&change_id)
+        .expect("Failed to check upstream workspace")
+        .expect("Change should have been submitted to the upstream workspace");
+    assert_eq!(upstream_change.name, "upstream_feature");
+    assert_eq!(upstream_change.status, ChangeStatus::Review);
+
+    let downstream_change = downstream
+        .database()
+        .workspace()
+        .get_workspace_change(&change_id)
+        .expect("Failed to check downstream workspace")
+        .expect("Change should be in the downstream workspace");
+    assert_eq!(downstream_change.status, ChangeStatus::Review);
+
+    // Upstream reviewers see it in their review queue
+    let review_list = make_request(
+        "GET",
+        &format!("{}/api/workspace/list?status=review", upstream.base_url()),
+        None,
+    )
+    .await
+    .expect("Failed to list upstream workspace");
+    let review_list = review_list.require_result_list("Upstream workspace/list");
+    assert!(
+        review_list.iter().any(|c| c["id"] == change_id.as_str()),
+        "Upstream review queue should contain the submitted change: {:?}",
+        review_list
+    );
+
+    println!("\n✅ Test passed: Change submitted to upstream workspace for review");
+}
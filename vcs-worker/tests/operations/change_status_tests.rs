@@ -4,6 +4,7 @@
 //! 1. change/status returns correct object change details
 //! 2. Numeric object IDs (like "#73") are returned as v_obj, not strings
 //! 3. Named objects (like "sysobj") are returned as strings
+//! 4. The details flag adds unified verb diffs to the JSON response
 
 use crate::common::*;
 use moor_var::{Associative, Sequence, Variant};
@@ -13,7 +14,9 @@ use moor_vcs_worker::operations::{ChangeStatusOperation, Operation};
 async fn call_change_status(server: &TestServer) -> moor_var::Var {
     let change_status_op = ChangeStatusOperation::new(server.database().clone());
     let wizard_user = server.get_wizard_user().expect("Failed to get wizard user");
-    change_status_op.execute(vec![], &wizard_user)
+    change_status_op
+        .execute(vec![], &wizard_user)
+        .unwrap_or_else(|e| e.to_moo_error())
 }
 
 #[tokio::test]
@@ -833,3 +836,69 @@ async fn test_change_status_by_short_hash_workspace() {
 
     println!("\n✅ Test passed: Short hash works for workspace changes");
 }
+
+#[tokio::test]
+async fn test_change_status_details_over_http() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+
+    println!("Test: change/status with details returns verb diffs as JSON");
+
+    // Step 1: Create an object with a verb in the current change
+    println!("\nStep 1: Adding an object with a verb...");
+    client
+        .object_update(
+            "details_obj",
+            vec![
+                "object #1",
+                "  name: \"Details Object\"",
+                "  parent: #1",
+                "  location: #2",
+                "  owner: #2",
+                "",
+                "  verb greet (this none this) owner: #2 flags: \"rxd\"",
+                "    return \"hello\";",
+                "  endverb",
+                "endobject",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        )
+        .await
+        .expect("Failed to update object")
+        .assert_success("Object update");
+
+    // Step 2: Without details the diff stays compact
+    println!("\nStep 2: Getting status without details...");
+    let plain = client
+        .change_status()
+        .await
+        .expect("Failed to get change status");
+    plain.assert_success("Plain status");
+    assert!(
+        plain["result"]["changes"][0].get("verb_diffs").is_none(),
+        "verb_diffs should only be returned on request, got: {}",
+        plain
+    );
+    println!("✅ Plain status has no verb_diffs");
+
+    // Step 3: With details the added verb is a unified diff of its code
+    println!("\nStep 3: Getting status with details...");
+    let detailed = client
+        .rpc_call("change/status", vec![json!(""), json!("true")])
+        .await
+        .expect("Failed to get change status");
+    detailed.assert_success("Detailed status");
+    assert_eq!(
+        detailed["result"]["changes"][0]["verb_diffs"]["greet"],
+        json!(["@@ -0,0 +1,1 @@", "+return \"hello\";"]),
+        "got: {}",
+        detailed
+    );
+    println!("✅ Detailed status includes the unified verb diff");
+
+    println!("\n✅ Test passed: change/status details returned over HTTP");
+}
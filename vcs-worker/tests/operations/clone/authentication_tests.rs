@@ -84,7 +84,7 @@ async fn test_clone_import_with_external_user_api_key_valid() {
     // Use the import_from_url_async method directly with API key
     let clone_op = moor_vcs_worker::operations::CloneOperation::new(target_server.database().clone());
     let result = clone_op
-        .import_from_url_async(&clone_url, Some("test-api-key-123"))
+        .import_from_url_async(&clone_url, Some("test-api-key-123"), DEFAULT_REMOTE)
         .await;
     
     assert!(result.is_ok(), "Clone import should succeed: {:?}", result);
@@ -96,8 +96,9 @@ async fn test_clone_import_with_external_user_api_key_valid() {
     let stored_api_key = target_server
         .database()
         .index()
-        .get_external_user_api_key()
+        .get_remote(DEFAULT_REMOTE)
         .expect("Failed to get API key")
+        .and_then(|remote| remote.api_key)
         .expect("API key should be set");
     
     assert_eq!(stored_api_key, "test-api-key-123", "API key should be stored");
@@ -106,8 +107,9 @@ async fn test_clone_import_with_external_user_api_key_valid() {
     let stored_user_id = target_server
         .database()
         .index()
-        .get_external_user_id()
+        .get_remote(DEFAULT_REMOTE)
         .expect("Failed to get user ID")
+        .and_then(|remote| remote.user_id)
         .expect("User ID should be set");
     
     assert_eq!(stored_user_id, "external_user_id", "User ID should be stored");
@@ -117,8 +119,9 @@ async fn test_clone_import_with_external_user_api_key_valid() {
     let stored_source = target_server
         .database()
         .index()
-        .get_source()
+        .get_remote(DEFAULT_REMOTE)
         .expect("Failed to get source")
+        .map(|remote| remote.url)
         .expect("Source should be set");
     
     assert_eq!(stored_source, mock_server.uri(), "Source URL should be stored");
@@ -193,7 +196,7 @@ async fn test_clone_import_with_external_user_api_key_invalid() {
     
     let clone_op = moor_vcs_worker::operations::CloneOperation::new(target_server.database().clone());
     let result = clone_op
-        .import_from_url_async(&clone_url, Some("invalid-api-key"))
+        .import_from_url_async(&clone_url, Some("invalid-api-key"), DEFAULT_REMOTE)
         .await;
     
     assert!(result.is_err(), "Clone should fail with invalid API key");
@@ -209,8 +212,9 @@ async fn test_clone_import_with_external_user_api_key_invalid() {
     let stored_api_key = target_server
         .database()
         .index()
-        .get_external_user_api_key()
-        .expect("Failed to get API key");
+        .get_remote(DEFAULT_REMOTE)
+        .expect("Failed to get API key")
+        .and_then(|remote| remote.api_key);
     
     assert!(stored_api_key.is_none(), "API key should NOT be stored on failure");
     println!("✅ No credentials stored on failure");
@@ -261,7 +265,7 @@ async fn test_clone_import_with_malformed_stat_response() {
     let clone_url = format!("{}/api/clone", mock_server.uri());
     let clone_op = moor_vcs_worker::operations::CloneOperation::new(target_server.database().clone());
     
-    let result = clone_op.import_from_url_async(&clone_url, Some("test-key")).await;
+    let result = clone_op.import_from_url_async(&clone_url, Some("test-key"), DEFAULT_REMOTE).await;
     assert!(result.is_err(), "Should fail with missing result field");
     println!("✅ Correctly failed: {}", result.unwrap_err());
 
@@ -281,7 +285,7 @@ async fn test_clone_import_with_malformed_stat_response() {
     let clone_url2 = format!("{}/api/clone", mock_server2.uri());
     let clone_op2 = moor_vcs_worker::operations::CloneOperation::new(target_server2.database().clone());
     
-    let result2 = clone_op2.import_from_url_async(&clone_url2, Some("test-key")).await;
+    let result2 = clone_op2.import_from_url_async(&clone_url2, Some("test-key"), DEFAULT_REMOTE).await;
     assert!(result2.is_err(), "Should fail with incomplete result array");
     println!("✅ Correctly failed: {}", result2.unwrap_err());
 
@@ -301,7 +305,7 @@ async fn test_clone_import_with_malformed_stat_response() {
     let clone_url3 = format!("{}/api/clone", mock_server3.uri());
     let clone_op3 = moor_vcs_worker::operations::CloneOperation::new(target_server3.database().clone());
     
-    let result3 = clone_op3.import_from_url_async(&clone_url3, Some("test-key")).await;
+    let result3 = clone_op3.import_from_url_async(&clone_url3, Some("test-key"), DEFAULT_REMOTE).await;
     assert!(result3.is_err(), "Should fail with non-string user_id");
     println!("✅ Correctly failed: {}", result3.unwrap_err());
 
@@ -366,7 +370,7 @@ async fn test_external_user_credentials_persistence() {
     println!("\nStep 1: Importing with external user API key...");
     let clone_op = moor_vcs_worker::operations::CloneOperation::new(target_server.database().clone());
     let result = clone_op
-        .import_from_url_async(&clone_url, Some("persistent-key-456"))
+        .import_from_url_async(&clone_url, Some("persistent-key-456"), DEFAULT_REMOTE)
         .await;
     
     assert!(result.is_ok(), "Clone should succeed");
@@ -381,8 +385,9 @@ async fn test_external_user_credentials_persistence() {
         let api_key = target_server
             .database()
             .index()
-            .get_external_user_api_key()
+            .get_remote(DEFAULT_REMOTE)
             .expect("Failed to get API key")
+            .and_then(|remote| remote.api_key)
             .expect("API key should exist");
         
         assert_eq!(api_key, "persistent-key-456", "API key should persist");
@@ -390,8 +395,9 @@ async fn test_external_user_credentials_persistence() {
         let user_id = target_server
             .database()
             .index()
-            .get_external_user_id()
+            .get_remote(DEFAULT_REMOTE)
             .expect("Failed to get user ID")
+            .and_then(|remote| remote.user_id)
             .expect("User ID should exist");
         
         assert_eq!(user_id, "persistent_user", "User ID should persist");
@@ -399,8 +405,9 @@ async fn test_external_user_credentials_persistence() {
         let source_url = target_server
             .database()
             .index()
-            .get_source()
+            .get_remote(DEFAULT_REMOTE)
             .expect("Failed to get source")
+            .map(|remote| remote.url)
             .expect("Source should exist");
         
         assert_eq!(source_url, mock_server.uri(), "Source URL should persist");
@@ -413,8 +420,9 @@ async fn test_external_user_credentials_persistence() {
     let final_api_key = target_server
         .database()
         .index()
-        .get_external_user_api_key()
+        .get_remote(DEFAULT_REMOTE)
         .expect("Failed to get API key")
+        .and_then(|remote| remote.api_key)
         .expect("API key should exist");
     
     assert!(!final_api_key.is_empty(), "API key should not be empty");
@@ -11,13 +11,7 @@ async fn test_clone_import_with_invalid_url_format() {
 
     println!("Test: Clone import with invalid URL format should fail gracefully");
 
-    let invalid_urls = vec![
-        "not-a-url",
-        "ftp://invalid-protocol.com",
-        "http://",
-        "",
-        "   ",
-    ];
+    let invalid_urls = vec!["not-a-url", "ftp://invalid-protocol.com", "http://", "   "];
 
     for invalid_url in invalid_urls {
         println!("\nTesting invalid URL: '{}'", invalid_url);
@@ -39,6 +33,15 @@ async fn test_clone_import_with_invalid_url_format() {
         println!("✅ Failed appropriately: {}", result_str);
     }
 
+    // An empty URL asks for an export rather than an import
+    println!("\nTesting empty URL");
+    let response = target_client
+        .clone_import("")
+        .await
+        .expect("Request should complete");
+    response.assert_success("Clone with an empty URL");
+    println!("✅ Empty URL exported the repository");
+
     println!("\n✅ Test passed: Clone import handles invalid URLs gracefully");
 }
 
@@ -310,7 +310,9 @@ async fn test_clone_execute_error_paths() {
     // Test 1: Export with empty repository (should succeed with empty data)
     println!("\nTest 1: Export empty repository...");
     let clone_op = moor_vcs_worker::operations::CloneOperation::new(server.database().clone());
-    let result = clone_op.execute(vec![], &wizard);
+    let result = clone_op
+        .execute(vec![], &wizard)
+        .expect("Export should not fail");
     
     match result.variant() {
         Variant::Str(_) => {
@@ -341,7 +343,9 @@ async fn test_clone_execute_error_paths() {
         .expect("Failed to approve")
         .assert_success("Approve");
 
-    let result = clone_op.execute(vec![], &wizard);
+    let result = clone_op
+        .execute(vec![], &wizard)
+        .expect("Export should not fail");
     
     match result.variant() {
         Variant::Str(s) => {
@@ -364,7 +368,9 @@ async fn test_clone_execute_error_paths() {
 
     // Test 3: Import with empty URL (should export instead)
     println!("\nTest 3: Import with empty URL (treats as export)...");
-    let result = clone_op.execute(vec!["".to_string()], &wizard);
+    let result = clone_op
+        .execute(vec!["".to_string()], &wizard)
+        .expect("Empty URL should trigger export");
     
     match result.variant() {
         Variant::Str(_) => {
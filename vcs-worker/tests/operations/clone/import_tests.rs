@@ -96,8 +96,9 @@ async fn test_clone_import_from_remote() {
     let target_source = target_server
         .database()
         .index()
-        .get_source()
+        .get_remote(DEFAULT_REMOTE)
         .expect("Failed to get source")
+        .map(|remote| remote.url)
         .expect("Source should be set");
     let expected_base_url = source_url.trim_end_matches("/api/clone");
     assert_eq!(
@@ -387,8 +388,9 @@ async fn test_clone_import_when_already_cloned() {
     let source_after_first = target_server
         .database()
         .index()
-        .get_source()
+        .get_remote(DEFAULT_REMOTE)
         .expect("Failed to get source")
+        .map(|remote| remote.url)
         .expect("Source should be set");
 
     let expected_base_url = source_url.trim_end_matches("/api/clone");
@@ -453,7 +455,7 @@ async fn test_clone_import_response_parsing_formats() {
     let clone_url1 = format!("{}/api/clone", mock_server1.uri());
     let clone_op1 = moor_vcs_worker::operations::CloneOperation::new(target1.database().clone());
     
-    let result1 = clone_op1.import_from_url_async(&clone_url1, None).await;
+    let result1 = clone_op1.import_from_url_async(&clone_url1, None, DEFAULT_REMOTE).await;
     assert!(result1.is_ok(), "Should parse result as JSON string: {:?}", result1);
     println!("✅ Parsed result as JSON string");
 
@@ -480,7 +482,7 @@ async fn test_clone_import_response_parsing_formats() {
     let clone_url2 = format!("{}/api/clone", mock_server2.uri());
     let clone_op2 = moor_vcs_worker::operations::CloneOperation::new(target2.database().clone());
     
-    let result2 = clone_op2.import_from_url_async(&clone_url2, None).await;
+    let result2 = clone_op2.import_from_url_async(&clone_url2, None, DEFAULT_REMOTE).await;
     assert!(result2.is_ok(), "Should parse result as object: {:?}", result2);
     println!("✅ Parsed result as direct object");
 
@@ -504,7 +506,7 @@ async fn test_clone_import_response_parsing_formats() {
     let clone_url3 = format!("{}/api/clone", mock_server3.uri());
     let clone_op3 = moor_vcs_worker::operations::CloneOperation::new(target3.database().clone());
     
-    let result3 = clone_op3.import_from_url_async(&clone_url3, None).await;
+    let result3 = clone_op3.import_from_url_async(&clone_url3, None, DEFAULT_REMOTE).await;
     assert!(result3.is_ok(), "Should parse direct CloneData: {:?}", result3);
     println!("✅ Parsed direct CloneData");
 
@@ -522,7 +524,7 @@ async fn test_clone_import_response_parsing_formats() {
     let clone_url4 = format!("{}/api/clone", mock_server4.uri());
     let clone_op4 = moor_vcs_worker::operations::CloneOperation::new(target4.database().clone());
     
-    let result4 = clone_op4.import_from_url_async(&clone_url4, None).await;
+    let result4 = clone_op4.import_from_url_async(&clone_url4, None, DEFAULT_REMOTE).await;
     assert!(result4.is_err(), "Should fail with invalid JSON");
     println!("✅ Correctly rejected invalid JSON: {}", result4.unwrap_err());
 
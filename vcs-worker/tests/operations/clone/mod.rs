@@ -5,9 +5,11 @@
 //! - import_tests: Tests for clone import from remote URLs
 //! - authentication_tests: Tests for external user API key authentication
 //! - error_handling_tests: Tests for error conditions and edge cases
+//! - pack_tests: Tests for streaming, resumable clone packs
 
 mod authentication_tests;
 mod error_handling_tests;
 mod export_tests;
 mod import_tests;
+mod pack_tests;
 
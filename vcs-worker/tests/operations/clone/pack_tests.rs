@@ -0,0 +1,533 @@
+//! Tests for streaming clone packs
+//!
+//! These tests verify:
+//! 1. Cloning from a worker streams a pack and reproduces its refs, blobs and history
+//! 2. The pack endpoint serves each section as compressed frames closed by an end frame,
+//!    leaving out blobs it doesn't have and refusing callers without Clone permission
+//! 3. An interrupted blob transfer resumes by asking only for the blobs still missing, and
+//!    an interrupted ref list is fetched again
+//! 4. A blob that doesn't match its hash fails the clone without importing anything
+
+use crate::common::*;
+use moor_vcs_worker::clone_pack::{PACK_CONTENT_TYPE, PackFrame, PackReader, encode_frame};
+use moor_vcs_worker::operations::CloneOperation;
+use moor_vcs_worker::types::{Change, ChangeStatus, ObjectInfo, VcsObjectType};
+use serde_json::Value;
+use std::collections::HashSet;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Repository state read straight from a source database
+struct SourceState {
+    refs: Vec<(ObjectInfo, String)>,
+    objects: Vec<(String, String)>,
+    changes: Vec<Change>,
+}
+
+/// Approve a change adding two objects on a fresh source server
+async fn source_with_two_objects() -> TestServer {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start source server");
+    let client = server.client();
+    client
+        .object_update_from_file("first_object", "test_object.moo")
+        .await
+        .expect("Failed to update first object")
+        .assert_success("Update first object");
+    client
+        .object_update_from_file("second_object", "test_object_1.moo")
+        .await
+        .expect("Failed to update second object")
+        .assert_success("Update second object");
+    let (change_id, _) = server.db_assertions().require_top_change();
+    client
+        .change_approve(&change_id)
+        .await
+        .expect("Failed to approve")
+        .assert_success("Approve");
+    server
+}
+
+/// Read the refs, distinct blobs and merged changes of a server
+fn source_state(server: &TestServer) -> SourceState {
+    let database = server.database();
+    let refs: Vec<(ObjectInfo, String)> = database
+        .refs()
+        .get_all_refs()
+        .expect("Failed to get refs")
+        .into_iter()
+        .collect();
+
+    let mut seen = HashSet::new();
+    let mut objects = Vec::new();
+    for (_, hash) in &refs {
+        if seen.insert(hash.clone()) {
+            let content = database
+                .objects()
+                .get(hash)
+                .expect("Failed to get object")
+                .expect("Object should exist");
+            objects.push((hash.clone(), content));
+        }
+    }
+
+    let changes = database
+        .index()
+        .get_change_order()
+        .expect("Failed to get change order")
+        .iter()
+        .filter_map(|id| {
+            database
+                .index()
+                .get_change(id)
+                .expect("Failed to get change")
+        })
+        .filter(|change| change.status == ChangeStatus::Merged)
+        .collect();
+
+    SourceState {
+        refs,
+        objects,
+        changes,
+    }
+}
+
+/// Encode frames as a pack response body
+fn pack_body(frames: &[PackFrame]) -> Vec<u8> {
+    frames
+        .iter()
+        .flat_map(|frame| encode_frame(frame).expect("Failed to encode frame"))
+        .collect()
+}
+
+/// Decode a complete pack response body
+fn decode_pack(body: &[u8]) -> Vec<PackFrame> {
+    let mut reader = PackReader::new();
+    reader.push(body);
+    let mut frames = Vec::new();
+    while let Some(frame) = reader.next_frame().expect("Pack should decode") {
+        frames.push(frame);
+    }
+    frames
+}
+
+/// Mount the refs and changes sections of a pack for `state` on a mock source
+async fn mount_refs_and_changes(mock_server: &MockServer, state: &SourceState) {
+    let tip = state.changes.last().map(|change| change.id.clone());
+    Mock::given(method("POST"))
+        .and(path("/api/clone/pack"))
+        .and(body_partial_json(json!({"section": "refs"})))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            pack_body(&[
+                PackFrame::Header { version: 1, tip },
+                PackFrame::Refs {
+                    refs: state.refs.clone(),
+                },
+                PackFrame::End {
+                    count: state.refs.len(),
+                },
+            ]),
+            PACK_CONTENT_TYPE,
+        ))
+        .mount(mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/clone/pack"))
+        .and(body_partial_json(json!({"section": "changes"})))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            pack_body(&[
+                PackFrame::Changes {
+                    changes: state.changes.clone(),
+                },
+                PackFrame::End {
+                    count: state.changes.len(),
+                },
+            ]),
+            PACK_CONTENT_TYPE,
+        ))
+        .mount(mock_server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_clone_streams_pack_from_worker() {
+    let source_server = source_with_two_objects().await;
+    let target_server = TestServer::start()
+        .await
+        .expect("Failed to start target server");
+    let target_db = target_server.db_assertions();
+
+    println!("Test: Cloning from a worker streams a clone pack");
+
+    let response = target_server
+        .client()
+        .clone_import(&format!("{}/api/clone", source_server.base_url()))
+        .await
+        .expect("Failed to clone");
+    response.assert_success("Clone");
+    println!("✅ Clone succeeded: {}", response["result"]);
+
+    let state = source_state(&source_server);
+    for (obj_info, hash) in &state.refs {
+        let target_hash = target_server
+            .database()
+            .refs()
+            .get_ref(obj_info.object_type, &obj_info.name, Some(obj_info.version))
+            .expect("Failed to get ref")
+            .expect("Ref should be cloned");
+        assert_eq!(
+            &target_hash, hash,
+            "Ref for '{}' should match",
+            obj_info.name
+        );
+        target_db.assert_sha256_exists(hash);
+    }
+    let order = target_server
+        .database()
+        .index()
+        .get_change_order()
+        .expect("Failed to get change order");
+    let source_order: Vec<String> = state.changes.iter().map(|c| c.id.clone()).collect();
+    assert_eq!(order, source_order, "History should match the source");
+    println!(
+        "✅ Target has {} refs, {} blobs and {} changes",
+        state.refs.len(),
+        state.objects.len(),
+        order.len()
+    );
+
+    println!("\n✅ Test passed: Clone pack reproduces the source");
+}
+
+#[tokio::test]
+async fn test_pack_endpoint_serves_sections() {
+    let source_server = source_with_two_objects().await;
+    let state = source_state(&source_server);
+    let pack_url = format!("{}/api/clone/pack", source_server.base_url());
+    let http = reqwest::Client::new();
+
+    println!("Test: The pack endpoint streams each section as frames");
+
+    let request_pack = |request: Value, api_key: Option<String>| {
+        let mut builder = http.post(&pack_url).json(&request);
+        if let Some(api_key) = api_key {
+            builder = builder.header("X-API-Key", api_key);
+        }
+        async move {
+            let response = builder.send().await.expect("Pack request failed");
+            let status = response.status();
+            let body = response.bytes().await.expect("Failed to read pack");
+            (status, body)
+        }
+    };
+    let wizard_key = Some(source_server.get_wizard_api_key());
+
+    // Step 1: The refs section opens with a header naming the tip
+    println!("\nStep 1: Requesting refs...");
+    let (status, body) = request_pack(json!({"section": "refs"}), wizard_key.clone()).await;
+    assert!(status.is_success(), "Refs request failed: {status}");
+    let frames = decode_pack(&body);
+    let tip = match &frames[0] {
+        PackFrame::Header { version, tip } => {
+            assert_eq!(*version, 1);
+            tip.clone().expect("Source has a merged change")
+        }
+        other => panic!("Pack should open with a header, got {}", other.kind()),
+    };
+    assert_eq!(Some(&tip), state.changes.last().map(|c| &c.id));
+    let refs: Vec<(ObjectInfo, String)> = frames
+        .iter()
+        .flat_map(|frame| match frame {
+            PackFrame::Refs { refs } => refs.clone(),
+            _ => Vec::new(),
+        })
+        .collect();
+    assert_eq!(refs.len(), state.refs.len());
+    assert!(matches!(frames.last(), Some(PackFrame::End { count }) if *count == refs.len()));
+    println!("✅ Header with tip {} and {} refs", tip, refs.len());
+
+    // Step 2: Objects are sent by hash, leaving out unknown ones
+    println!("\nStep 2: Requesting objects...");
+    let (hash, content) = &state.objects[0];
+    let (status, body) = request_pack(
+        json!({"section": "objects", "hashes": [hash, "not-a-stored-hash"]}),
+        wizard_key.clone(),
+    )
+    .await;
+    assert!(status.is_success(), "Objects request failed: {status}");
+    let frames = decode_pack(&body);
+    match &frames[..] {
+        [PackFrame::Objects { objects }, PackFrame::End { count: 1 }] => {
+            assert_eq!(objects, &vec![(hash.clone(), content.clone())]);
+        }
+        other => panic!(
+            "Expected one objects frame and an end frame, got {:?}",
+            other.iter().map(|f| f.kind()).collect::<Vec<_>>()
+        ),
+    }
+    println!("✅ Known object sent, unknown one left out");
+
+    // Step 3: Changes up to the tip
+    println!("\nStep 3: Requesting changes...");
+    let (status, body) = request_pack(
+        json!({"section": "changes", "until": tip}),
+        wizard_key.clone(),
+    )
+    .await;
+    assert!(status.is_success(), "Changes request failed: {status}");
+    let ids: Vec<String> = decode_pack(&body)
+        .iter()
+        .flat_map(|frame| match frame {
+            PackFrame::Changes { changes } => changes.iter().map(|c| c.id.clone()).collect(),
+            _ => Vec::new(),
+        })
+        .collect();
+    assert_eq!(ids.last(), Some(&tip));
+    println!("✅ {} changes ending at the tip", ids.len());
+
+    // Step 4: Callers without Clone permission are refused
+    println!("\nStep 4: Requesting as Everyone...");
+    let (status, _) = request_pack(json!({"section": "refs"}), None).await;
+    assert_eq!(status, reqwest::StatusCode::FORBIDDEN);
+    println!("✅ Refused with {}", status);
+
+    println!("\n✅ Test passed: Pack endpoint serves every section");
+}
+
+#[tokio::test]
+async fn test_clone_pack_resumes_interrupted_transfer() {
+    let source_server = source_with_two_objects().await;
+    let state = source_state(&source_server);
+    assert!(state.objects.len() >= 2, "Source should have several blobs");
+    let mock_server = MockServer::start().await;
+
+    println!("Test: An interrupted blob transfer resumes with the missing blobs");
+
+    // The first objects response is cut off after one blob; the retry gets the rest
+    mount_refs_and_changes(&mock_server, &state).await;
+    Mock::given(method("POST"))
+        .and(path("/api/clone/pack"))
+        .and(body_partial_json(json!({"section": "objects"})))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            pack_body(&[PackFrame::Objects {
+                objects: vec![state.objects[0].clone()],
+            }]),
+            PACK_CONTENT_TYPE,
+        ))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/clone/pack"))
+        .and(body_partial_json(json!({"section": "objects"})))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            pack_body(&[
+                PackFrame::Objects {
+                    objects: state.objects[1..].to_vec(),
+                },
+                PackFrame::End {
+                    count: state.objects.len() - 1,
+                },
+            ]),
+            PACK_CONTENT_TYPE,
+        ))
+        .mount(&mock_server)
+        .await;
+
+    let target_server = TestServer::start()
+        .await
+        .expect("Failed to start target server");
+    let clone_op = CloneOperation::new(target_server.database().clone());
+    let result = clone_op
+        .import_from_url_async(
+            &format!("{}/api/clone", mock_server.uri()),
+            None,
+            DEFAULT_REMOTE,
+        )
+        .await;
+    assert!(
+        result.is_ok(),
+        "Clone should resume and succeed: {result:?}"
+    );
+    println!("✅ Clone succeeded");
+
+    // The retry only asked for the blobs that hadn't arrived
+    let requested: Vec<Vec<String>> = mock_server
+        .received_requests()
+        .await
+        .expect("Requests should be recorded")
+        .iter()
+        .filter_map(|request| {
+            let body: Value = serde_json::from_slice(&request.body).ok()?;
+            (body["section"] == "objects")
+                .then(|| serde_json::from_value(body["hashes"].clone()).unwrap())
+        })
+        .collect();
+    let remaining: Vec<String> = state.objects[1..].iter().map(|(h, _)| h.clone()).collect();
+    assert_eq!(requested.len(), 2, "Blobs should be requested twice");
+    assert_eq!(requested[0].len(), state.objects.len());
+    assert_eq!(requested[1], remaining, "Resume should skip received blobs");
+    println!(
+        "✅ Resumed with {} of {} blobs",
+        remaining.len(),
+        state.objects.len()
+    );
+
+    let target_db = target_server.db_assertions();
+    for (hash, _) in &state.objects {
+        target_db.assert_sha256_exists(hash);
+    }
+    target_db.assert_ref_exists(VcsObjectType::MooObject, "first_object");
+    target_db.assert_ref_exists(VcsObjectType::MooObject, "second_object");
+    println!("✅ Every blob and ref is in place");
+
+    println!("\n✅ Test passed: Interrupted clone resumed");
+}
+
+#[tokio::test]
+async fn test_clone_pack_retries_interrupted_refs() {
+    let source_server = source_with_two_objects().await;
+    let state = source_state(&source_server);
+    let mock_server = MockServer::start().await;
+
+    println!("Test: An interrupted ref list is fetched again");
+
+    // The first refs response is cut off after the header; the retry gets the full list
+    Mock::given(method("POST"))
+        .and(path("/api/clone/pack"))
+        .and(body_partial_json(json!({"section": "refs"})))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            pack_body(&[PackFrame::Header {
+                version: 1,
+                tip: state.changes.last().map(|change| change.id.clone()),
+            }]),
+            PACK_CONTENT_TYPE,
+        ))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&mock_server)
+        .await;
+    mount_refs_and_changes(&mock_server, &state).await;
+    Mock::given(method("POST"))
+        .and(path("/api/clone/pack"))
+        .and(body_partial_json(json!({"section": "objects"})))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            pack_body(&[
+                PackFrame::Objects {
+                    objects: state.objects.clone(),
+                },
+                PackFrame::End {
+                    count: state.objects.len(),
+                },
+            ]),
+            PACK_CONTENT_TYPE,
+        ))
+        .mount(&mock_server)
+        .await;
+
+    let target_server = TestServer::start()
+        .await
+        .expect("Failed to start target server");
+    let clone_op = CloneOperation::new(target_server.database().clone());
+    let result = clone_op
+        .import_from_url_async(
+            &format!("{}/api/clone", mock_server.uri()),
+            None,
+            DEFAULT_REMOTE,
+        )
+        .await;
+    assert!(
+        result.is_ok(),
+        "Clone should retry the refs and succeed: {result:?}"
+    );
+    println!("✅ Clone succeeded");
+
+    let refs_requests = mock_server
+        .received_requests()
+        .await
+        .expect("Requests should be recorded")
+        .iter()
+        .filter(|request| {
+            serde_json::from_slice::<Value>(&request.body)
+                .is_ok_and(|body| body["section"] == "refs")
+        })
+        .count();
+    assert_eq!(refs_requests, 2, "Refs should be requested twice");
+    println!("✅ Refs fetched again after the interruption");
+
+    let target_db = target_server.db_assertions();
+    target_db.assert_ref_exists(VcsObjectType::MooObject, "first_object");
+    target_db.assert_ref_exists(VcsObjectType::MooObject, "second_object");
+    println!("✅ Every ref is in place");
+
+    println!("\n✅ Test passed: Interrupted ref list retried");
+}
+
+#[tokio::test]
+async fn test_clone_pack_rejects_corrupted_object() {
+    let source_server = source_with_two_objects().await;
+    let state = source_state(&source_server);
+    let mock_server = MockServer::start().await;
+
+    println!("Test: A blob that doesn't match its hash fails the clone");
+
+    let mut objects = state.objects.clone();
+    objects[0].1.push_str("\n// tampered");
+    mount_refs_and_changes(&mock_server, &state).await;
+    Mock::given(method("POST"))
+        .and(path("/api/clone/pack"))
+        .and(body_partial_json(json!({"section": "objects"})))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            pack_body(&[
+                PackFrame::Objects { objects },
+                PackFrame::End {
+                    count: state.objects.len(),
+                },
+            ]),
+            PACK_CONTENT_TYPE,
+        ))
+        .mount(&mock_server)
+        .await;
+
+    let target_server = TestServer::start()
+        .await
+        .expect("Failed to start target server");
+    let clone_op = CloneOperation::new(target_server.database().clone());
+    let result = clone_op
+        .import_from_url_async(
+            &format!("{}/api/clone", mock_server.uri()),
+            None,
+            DEFAULT_REMOTE,
+        )
+        .await;
+
+    let error = result.expect_err("Clone should fail").to_string();
+    assert!(
+        error.contains("does not match its content hash"),
+        "Unexpected error: {error}"
+    );
+    println!("✅ Clone failed: {}", error);
+
+    let database = target_server.database();
+    assert!(
+        database
+            .refs()
+            .get_ref(VcsObjectType::MooObject, "first_object", None)
+            .expect("Failed to get ref")
+            .is_none(),
+        "No refs should be imported"
+    );
+    assert!(
+        database
+            .objects()
+            .get(&state.objects[0].0)
+            .expect("Failed to get object")
+            .is_none(),
+        "The corrupted blob should not be stored"
+    );
+    println!("✅ Nothing was imported");
+
+    println!("\n✅ Test passed: Corrupted clone refused");
+}
@@ -0,0 +1,270 @@
+//! Tests for concurrent mutations
+//!
+//! These tests verify:
+//! 1. A write batch fails with a concurrent modification error, writing nothing, if a key
+//!    it read was changed by another batch before it committed
+//! 2. The same holds for keys a batch scanned, such as the versions of an object it
+//!    numbered a new version after
+//! 3. Many simultaneous object/update calls never drop an object from the local change;
+//!    callers that collide get a retryable `concurrent_modification` error instead
+//! 4. Committing a write batch from exclusive work panics in debug builds instead of
+//!    waiting for the commit lock forever
+
+use crate::common::*;
+use moor_vcs_worker::database::ObjectsTreeError;
+use moor_vcs_worker::types::VcsObjectType;
+
+const TASKS: usize = 16;
+const OBJECTS_PER_TASK: usize = 4;
+const MAX_ATTEMPTS: usize = 100;
+
+/// A minimal object dump with a distinct object number
+fn object_dump(index: usize) -> Vec<String> {
+    vec![
+        format!("object #{}", 20000 + index),
+        format!("  name: \"Stress Object {index}\""),
+        "  parent: #1".to_string(),
+        "  location: #2".to_string(),
+        "  owner: #2".to_string(),
+        "endobject".to_string(),
+    ]
+}
+
+#[tokio::test]
+async fn test_write_batch_detects_lost_update() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+    let db = server.db_assertions();
+    let database = server.database().clone();
+
+    println!("Test: A batch whose reads went stale is rejected");
+
+    client
+        .object_update_from_file("test_object", "test_object.moo")
+        .await
+        .expect("Failed to update object")
+        .assert_success("Object update");
+    let (change_id, _) = db.require_top_change();
+
+    let result: Result<(), ObjectsTreeError> = database.write_batch(|| {
+        let mut change = database
+            .index()
+            .get_change(&change_id)
+            .unwrap()
+            .expect("Change should exist");
+
+        // Another thread renames the change and commits first
+        let other = database.clone();
+        let other_id = change_id.clone();
+        std::thread::spawn(move || {
+            other
+                .write_batch(|| -> Result<(), ObjectsTreeError> {
+                    let mut change = other.index().get_change(&other_id).unwrap().unwrap();
+                    change.name = "renamed elsewhere".to_string();
+                    other.index().update_change(&change).unwrap();
+                    Ok(())
+                })
+                .expect("Other batch should commit");
+        })
+        .join()
+        .unwrap();
+
+        change.description = Some("written from a stale read".to_string());
+        database.index().update_change(&change).unwrap();
+        Ok(())
+    });
+
+    match result {
+        Err(ObjectsTreeError::ConcurrentModification(msg)) => {
+            println!("✅ Stale batch rejected: {msg}");
+        }
+        other => panic!("Expected a concurrent modification error, got {other:?}"),
+    }
+
+    let change = database
+        .index()
+        .get_change(&change_id)
+        .unwrap()
+        .expect("Change should exist");
+    assert_eq!(change.name, "renamed elsewhere");
+    assert_eq!(
+        change.description, None,
+        "The rejected batch should write nothing"
+    );
+    println!("✅ The first writer's update was kept");
+}
+
+#[tokio::test]
+async fn test_write_batch_detects_scanned_range_changes() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let database = server.database().clone();
+
+    println!("Test: A batch whose scans went stale is rejected");
+
+    let result: Result<(), ObjectsTreeError> = database.write_batch(|| {
+        // Numbering the next version scans the object's refs
+        let version = database
+            .refs()
+            .get_next_version(VcsObjectType::MooObject, "scanned")
+            .unwrap();
+        assert_eq!(version, 1);
+
+        // Another thread adds the same version and commits first
+        let other = database.clone();
+        std::thread::spawn(move || {
+            other
+                .write_batch(|| -> Result<(), ObjectsTreeError> {
+                    other
+                        .refs()
+                        .update_ref(VcsObjectType::MooObject, "scanned", 1, "first")
+                        .unwrap();
+                    Ok(())
+                })
+                .expect("Other batch should commit");
+        })
+        .join()
+        .unwrap();
+
+        database
+            .refs()
+            .update_ref(VcsObjectType::MooObject, "scanned", version, "second")
+            .unwrap();
+        Ok(())
+    });
+
+    match result {
+        Err(ObjectsTreeError::ConcurrentModification(msg)) => {
+            println!("✅ Stale batch rejected: {msg}");
+        }
+        other => panic!("Expected a concurrent modification error, got {other:?}"),
+    }
+    assert_eq!(
+        database
+            .refs()
+            .get_ref(VcsObjectType::MooObject, "scanned", Some(1))
+            .unwrap()
+            .as_deref(),
+        Some("first"),
+        "The rejected batch should not overwrite version 1"
+    );
+    println!("✅ The first writer's version was kept");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn test_concurrent_object_updates_are_not_lost() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let db = server.db_assertions();
+
+    println!(
+        "Test: {} tasks each update {} objects at once",
+        TASKS, OBJECTS_PER_TASK
+    );
+
+    let mut handles = Vec::new();
+    for task in 0..TASKS {
+        let client = server.client();
+        handles.push(tokio::spawn(async move {
+            let mut retries = 0;
+            for n in 0..OBJECTS_PER_TASK {
+                let index = task * OBJECTS_PER_TASK + n;
+                let name = format!("stress_object_{index}");
+
+                let mut attempts = 0;
+                loop {
+                    attempts += 1;
+                    let response = client
+                        .object_update(&name, object_dump(index))
+                        .await
+                        .expect("Request failed");
+                    if response.is_success() {
+                        break;
+                    }
+                    assert_eq!(
+                        response["error"]["code"], "concurrent_modification",
+                        "Only concurrent modification errors are expected: {response}"
+                    );
+                    assert!(
+                        attempts < MAX_ATTEMPTS,
+                        "'{name}' kept conflicting after {attempts} attempts"
+                    );
+                    retries += 1;
+                }
+            }
+            retries
+        }));
+    }
+
+    let mut retries = 0;
+    for handle in handles {
+        retries += handle.await.expect("Task panicked");
+    }
+    println!("✅ All updates succeeded after {} retries", retries);
+
+    // Every object must be in the one local change, with a ref pointing at its blob
+    let (change_id, _) = db.require_top_change();
+    let change = server
+        .database()
+        .index()
+        .get_change(&change_id)
+        .expect("Failed to get change")
+        .expect("Top change should exist");
+    assert_eq!(
+        server
+            .database()
+            .index()
+            .list_changes()
+            .expect("Failed to list changes")
+            .len(),
+        1,
+        "Concurrent updates should share one local change"
+    );
+
+    for index in 0..TASKS * OBJECTS_PER_TASK {
+        let name = format!("stress_object_{index}");
+        assert!(
+            change.added_objects.iter().any(|obj| obj.name == name),
+            "'{name}' was lost from the local change"
+        );
+        let hash = db.assert_ref_exists(moor_vcs_worker::types::VcsObjectType::MooObject, &name);
+        db.assert_sha256_exists(&hash);
+    }
+    assert_eq!(change.added_objects.len(), TASKS * OBJECTS_PER_TASK);
+    println!(
+        "✅ All {} objects are in change {}",
+        TASKS * OBJECTS_PER_TASK,
+        change_id
+    );
+}
+
+// Release builds skip the check and would wait for the lock forever
+#[cfg(debug_assertions)]
+#[tokio::test]
+#[should_panic(expected = "must not commit inside exclusively")]
+async fn test_exclusive_work_cannot_commit_a_batch() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let database = server.database().clone();
+
+    println!("Test: Exclusive work that commits a batch is caught");
+
+    let _: Result<(), ObjectsTreeError> = database.exclusively(|| {
+        database.write_batch(|| {
+            database
+                .index()
+                .set_remote(&Remote {
+                    name: "exclusive".to_string(),
+                    url: "http://127.0.0.1:1".to_string(),
+                    api_key: None,
+                    user_id: None,
+                })
+                .map_err(|e| ObjectsTreeError::SerializationError(e.to_string()))
+        })
+    });
+}
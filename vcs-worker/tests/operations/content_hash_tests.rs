@@ -0,0 +1,215 @@
+//! Tests for content-addressed object storage
+//!
+//! These tests verify:
+//! 1. Objects are keyed by the configured content hash
+//! 2. Corrupted objects are refused by object/get and clone export
+//! 3. Objects written under another hash are migrated, with refs following them
+//! 4. Clone import re-keys objects from a source using another hash
+
+use crate::common::*;
+use moor_vcs_worker::types::VcsObjectType;
+use moor_vcs_worker::util::ContentHash;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_corrupted_object_is_refused() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+    let db = server.db_assertions();
+
+    println!("Test: Objects whose content no longer matches their key are never served");
+
+    // Step 1: Create an object
+    println!("\nStep 1: Creating object...");
+    let content = moo_to_lines(&load_moo_file("test_object.moo"));
+    client
+        .object_update("test_object", content.clone())
+        .await
+        .expect("Failed to update object")
+        .assert_success("Object update");
+
+    let hash = db.assert_ref_exists(VcsObjectType::MooObject, "test_object");
+    assert_eq!(
+        hash,
+        ContentHash::Blake3.hash(content.join("\n").as_bytes()),
+        "Objects should be keyed by Blake3 by default"
+    );
+    println!("✅ Object stored under its Blake3 hash");
+
+    // Step 2: Corrupt the stored value behind the provider's back
+    println!("\nStep 2: Corrupting stored object...");
+    server
+        .database()
+        .objects()
+        .store(&hash, "object #1\nendobject")
+        .expect("Failed to overwrite object");
+
+    let error = server
+        .database()
+        .objects()
+        .get(&hash)
+        .expect_err("Corrupted object should not be returned");
+    assert!(
+        error.to_string().contains("Corrupted"),
+        "Expected corruption error, got: {}",
+        error
+    );
+    println!("✅ Provider reports corruption: {}", error);
+
+    // Step 3: object/get refuses it
+    println!("\nStep 3: Getting corrupted object...");
+    let response = client
+        .object_get("test_object")
+        .await
+        .expect("Request should complete");
+    response.assert_failure("Get corrupted object");
+    assert_eq!(response["error"]["code"], "internal");
+    println!("✅ object/get refused the corrupted object");
+
+    // Step 4: clone export refuses it
+    println!("\nStep 4: Exporting clone...");
+    let response = client
+        .clone_export()
+        .await
+        .expect("Request should complete");
+    response.assert_failure("Export with corrupted object");
+    println!("✅ Clone export refused the corrupted object");
+}
+
+#[tokio::test]
+async fn test_content_hash_migration() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+    let db = server.db_assertions();
+
+    println!("Test: Objects stored under SHA-256 are re-keyed to Blake3");
+
+    // Step 1: Simulate an object written by an older worker
+    println!("\nStep 1: Storing a SHA-256 keyed object...");
+    let content = moo_to_lines(&load_moo_file("test_object.moo")).join("\n");
+    let legacy_hash = ContentHash::Sha256.hash(content.as_bytes());
+    server
+        .database()
+        .objects()
+        .store(&legacy_hash, &content)
+        .expect("Failed to store object");
+    server
+        .database()
+        .refs()
+        .update_ref(VcsObjectType::MooObject, "legacy_object", 1, &legacy_hash)
+        .expect("Failed to store ref");
+    println!("✅ Legacy object stored as {}", legacy_hash);
+
+    // Step 2: Migrate
+    println!("\nStep 2: Migrating...");
+    let rekeyed = server
+        .database()
+        .migrate_content_hash()
+        .expect("Migration should succeed");
+    assert_eq!(rekeyed, 1, "Exactly the legacy object should be re-keyed");
+
+    let new_hash = ContentHash::Blake3.hash(content.as_bytes());
+    let ref_hash = db.assert_ref_exists(VcsObjectType::MooObject, "legacy_object");
+    assert_eq!(ref_hash, new_hash, "Ref should follow the object");
+    db.assert_sha256_exists(&new_hash);
+    assert!(
+        !server
+            .database()
+            .objects()
+            .get_all_objects()
+            .expect("All objects should verify")
+            .contains_key(&legacy_hash),
+        "Legacy key should be removed"
+    );
+    assert_eq!(
+        server
+            .database()
+            .refs()
+            .get_content_hash()
+            .expect("Failed to read content hash"),
+        Some(ContentHash::Blake3)
+    );
+    println!("✅ Object and ref moved to {}", new_hash);
+
+    // Step 3: Migrating again is a no-op
+    let rekeyed = server
+        .database()
+        .migrate_content_hash()
+        .expect("Second migration should succeed");
+    assert_eq!(rekeyed, 0);
+    println!("✅ Second migration re-keyed nothing");
+
+    // Step 4: The object is readable through the API
+    println!("\nStep 4: Reading migrated object...");
+    let response = client
+        .object_get("legacy_object")
+        .await
+        .expect("Request should complete");
+    response.assert_success("Get migrated object");
+    println!("✅ Migrated object is readable");
+}
+
+#[tokio::test]
+async fn test_clone_import_rekeys_objects() {
+    let source_db_dir = TempDir::new().expect("Failed to create temp dir");
+    let config = moor_vcs_worker::Config::with_db_path(source_db_dir.path().to_path_buf())
+        .with_content_hash(ContentHash::Sha256);
+    let source_server = TestServer::start_with_config(config)
+        .await
+        .expect("Failed to start source server");
+    let target_server = TestServer::start()
+        .await
+        .expect("Failed to start target server");
+
+    println!("Test: Cloning from a SHA-256 worker re-keys objects to the local hash");
+
+    // Step 1: Create and merge an object on the source
+    println!("\nStep 1: Creating state on source server...");
+    let source_client = source_server.client();
+    source_client
+        .object_update_from_file("source_object", "test_object.moo")
+        .await
+        .expect("Failed to update object")
+        .assert_success("Object update");
+    let (change_id, _) = source_server.db_assertions().require_top_change();
+    source_client
+        .change_approve(&change_id)
+        .await
+        .expect("Failed to approve change")
+        .assert_success("Approve change");
+
+    let source_hash = source_server
+        .db_assertions()
+        .assert_ref_exists(VcsObjectType::MooObject, "source_object");
+    println!("✅ Source stores the object as {}", source_hash);
+
+    // Step 2: Clone it
+    println!("\nStep 2: Cloning from source to target...");
+    target_server
+        .client()
+        .clone_import(&format!("{}/api/clone", source_server.base_url()))
+        .await
+        .expect("Failed to import clone")
+        .assert_success("Clone import");
+
+    // Step 3: Target keys the object by Blake3 and can read it
+    println!("\nStep 3: Verifying target keys...");
+    let target_hash = target_server
+        .db_assertions()
+        .assert_ref_exists(VcsObjectType::MooObject, "source_object");
+    assert_ne!(target_hash, source_hash, "Target should re-key the object");
+
+    let content = target_server
+        .database()
+        .objects()
+        .get(&target_hash)
+        .expect("Object should verify")
+        .expect("Object should exist");
+    assert_eq!(target_hash, ContentHash::Blake3.hash(content.as_bytes()));
+    assert_eq!(source_hash, ContentHash::Sha256.hash(content.as_bytes()));
+    println!("✅ Target stores the object as {}", target_hash);
+}
@@ -0,0 +1,179 @@
+//! Tests for typed operation errors
+//!
+//! These tests verify:
+//! 1. Failed operations return the matching HTTP status and `error.code`
+//! 2. The `result` field still carries the "Error: ..." message
+//! 3. RPC callers receive the matching MOO error value
+
+use crate::common::*;
+use moor_var::{E_INVIND, E_PERM};
+use moor_vcs_worker::operations::OperationRequest;
+
+/// Call an operation over /rpc with an optional API key and return the status and parsed body
+async fn rpc_with_status(
+    server: &TestServer,
+    api_key: Option<&str>,
+    operation: &str,
+    args: Vec<&str>,
+) -> (reqwest::StatusCode, serde_json::Value) {
+    let (status, text) = make_request_with_api_key(
+        "POST",
+        &format!("{}/rpc", server.base_url()),
+        Some(json!({"operation": operation, "args": args})),
+        api_key,
+    )
+    .await
+    .expect("Request should complete");
+    let body = serde_json::from_str(&text).expect("Response should be JSON");
+    (status, body)
+}
+
+#[tokio::test]
+async fn test_missing_change_returns_not_found() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let wizard_key = server.get_wizard_api_key();
+
+    println!("Test: Unknown change ID returns 404 not_found");
+
+    let (status, body) = rpc_with_status(
+        &server,
+        Some(&wizard_key),
+        "change/approve",
+        vec!["deadbeef"],
+    )
+    .await;
+
+    assert_eq!(status, reqwest::StatusCode::NOT_FOUND);
+    body.assert_failure("Approve unknown change");
+    assert_eq!(body["error"]["code"], "not_found");
+    assert!(
+        body.require_result_str("change/approve")
+            .starts_with("Error: "),
+        "Result should carry the error message, got: {}",
+        body
+    );
+
+    println!("✅ Missing change reported as not_found");
+}
+
+#[tokio::test]
+async fn test_missing_permission_returns_permission_denied() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+
+    println!("Test: Everyone calling user/list returns 403 permission_denied");
+
+    let (status, body) = rpc_with_status(&server, None, "user/list", vec![]).await;
+
+    assert_eq!(status, reqwest::StatusCode::FORBIDDEN);
+    body.assert_failure("List users as Everyone");
+    assert_eq!(body["error"]["code"], "permission_denied");
+
+    println!("✅ Missing permission reported as permission_denied");
+}
+
+#[tokio::test]
+async fn test_second_local_change_returns_conflict() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let wizard_key = server.get_wizard_api_key();
+
+    println!("Test: Creating a change while one is local returns 409 conflict");
+
+    let (status, _) = rpc_with_status(
+        &server,
+        Some(&wizard_key),
+        "change/create",
+        vec!["first", "wizard"],
+    )
+    .await;
+    assert!(status.is_success(), "First change should be created");
+
+    let (status, body) = rpc_with_status(
+        &server,
+        Some(&wizard_key),
+        "change/create",
+        vec!["second", "wizard"],
+    )
+    .await;
+
+    assert_eq!(status, reqwest::StatusCode::CONFLICT);
+    assert_eq!(body["error"]["code"], "conflict");
+
+    println!("✅ Second local change reported as conflict");
+}
+
+#[tokio::test]
+async fn test_missing_arguments_return_invalid_args() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let wizard_key = server.get_wizard_api_key();
+
+    println!("Test: change/approve without arguments returns 400 invalid_args");
+
+    let (status, body) =
+        rpc_with_status(&server, Some(&wizard_key), "change/approve", vec![]).await;
+
+    assert_eq!(status, reqwest::StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "invalid_args");
+    assert!(
+        body["error"]["message"]
+            .as_str()
+            .is_some_and(|msg| msg.contains("change ID")),
+        "Error message should describe the missing argument, got: {}",
+        body
+    );
+
+    println!("✅ Missing arguments reported as invalid_args");
+}
+
+#[tokio::test]
+async fn test_rpc_errors_map_to_moo_error_codes() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+
+    println!("Test: RPC callers receive matching MOO error codes");
+
+    let everyone = server
+        .database()
+        .users()
+        .get_everyone_user()
+        .expect("Everyone user should exist");
+    let wizard = server.get_wizard_user().expect("Failed to get wizard user");
+
+    let denied = server.registry().execute_var_as(
+        OperationRequest {
+            operation: "user/list".to_string(),
+            args: vec![],
+        },
+        &everyone,
+    );
+    assert_eq!(
+        denied.as_error().map(|e| e.err_type),
+        Some(E_PERM),
+        "Permission failures should be E_PERM, got: {:?}",
+        denied
+    );
+
+    let missing = server.registry().execute_var_as(
+        OperationRequest {
+            operation: "change/approve".to_string(),
+            args: vec!["deadbeef".to_string()],
+        },
+        &wizard,
+    );
+    assert_eq!(
+        missing.as_error().map(|e| e.err_type),
+        Some(E_INVIND),
+        "Missing changes should be E_INVIND, got: {:?}",
+        missing
+    );
+
+    println!("✅ MOO error codes match the error kind");
+}
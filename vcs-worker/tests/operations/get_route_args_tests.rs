@@ -0,0 +1,98 @@
+//! Integration tests for GET routes taking their arguments from the path and query string
+//!
+//! These tests verify:
+//! 1. Query-string parameters are passed to the operation in declared order
+//! 2. Path parameters (e.g. `/api/object/get/:object_name`) are passed through
+//! 3. Missing optional parameters fall back to the operation's defaults
+
+use crate::common::*;
+
+/// Make an authenticated GET request and parse the JSON response
+async fn get_json(url: &str) -> serde_json::Value {
+    make_request("GET", url, None)
+        .await
+        .expect("GET request should succeed")
+}
+
+#[tokio::test]
+async fn test_index_list_honors_limit_and_page_query() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+
+    println!("Test: GET /api/index/list honors ?limit=&page=");
+
+    let client = server.client();
+    let lines = moo_to_lines(&load_moo_file("test_object.moo"));
+    let first = client.approve_object("first_object", lines.clone()).await;
+    let second = client.approve_object("second_object", lines).await;
+
+    let page0 = get_json(&format!("{}/api/index/list?limit=1", server.base_url())).await;
+    let page0 = page0.require_result_list("index/list page 0");
+    assert_eq!(page0.len(), 1, "limit=1 should return a single change");
+    assert_eq!(page0[0]["change_id"], second.as_str(), "Newest change first");
+
+    let page1 = get_json(&format!("{}/api/index/list?limit=1&page=1", server.base_url())).await;
+    let page1 = page1.require_result_list("index/list page 1");
+    assert_eq!(page1.len(), 1);
+    assert_eq!(page1[0]["change_id"], first.as_str(), "Second page has the older change");
+
+    println!("✅ Pagination parameters honored");
+}
+
+#[tokio::test]
+async fn test_index_calc_delta_reads_change_id_from_query() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+
+    println!("Test: GET /api/index/calc_delta?change_id= passes the change ID");
+
+    let client = server.client();
+    let lines = moo_to_lines(&load_moo_file("test_object.moo"));
+    let first = client.approve_object("first_object", lines.clone()).await;
+    let second = client.approve_object("second_object", lines).await;
+
+    let response = get_json(&format!(
+        "{}/api/index/calc_delta?change_id={}",
+        server.base_url(),
+        first
+    ))
+    .await;
+    let change_ids = response["result"]["change_ids"]
+        .as_array()
+        .unwrap_or_else(|| panic!("Delta should contain change_ids: {}", response));
+    assert_eq!(change_ids.len(), 1);
+    assert_eq!(change_ids[0], second.as_str());
+
+    // Without the parameter the operation reports the missing argument
+    let missing = get_json(&format!("{}/api/index/calc_delta", server.base_url())).await;
+    assert!(
+        missing.require_result_str("calc_delta without change_id").contains("change_id"),
+        "Missing change_id should be reported, got: {}",
+        missing
+    );
+    assert_eq!(missing["error"]["code"], "invalid_args");
+
+    println!("✅ change_id read from query string");
+}
+
+#[tokio::test]
+async fn test_object_get_reads_name_from_path() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+
+    println!("Test: GET /api/object/get/:object_name passes the object name");
+
+    let client = server.client();
+    let lines = moo_to_lines(&load_moo_file("test_object.moo"));
+    client.approve_object("path_object", lines).await;
+
+    let response = get_json(&format!("{}/api/object/get/path_object", server.base_url())).await;
+    response.assert_success("object/get via path");
+    let objdef = response.require_result_list("object/get via path");
+    assert!(!objdef.is_empty(), "Object should be found, got: {}", response);
+
+    println!("✅ Object name read from path");
+}
@@ -0,0 +1,353 @@
+//! Tests for importing a world from a git repository of .moo files
+
+use crate::common::*;
+use moor_vcs_worker::types::{Change, VcsObjectType};
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Build an objdef with the given object name
+fn objdef(name: &str) -> String {
+    format!(
+        r#"object #3
+  name: "{name}"
+  parent: #1
+  location: #2
+  owner: #2
+endobject
+"#
+    )
+}
+
+/// Run git in a repository as the given author, at the given commit time
+fn git(repo: &Path, author: &str, timestamp: u64, args: &[&str]) {
+    let output = std::process::Command::new("git")
+        .current_dir(repo)
+        .env("GIT_AUTHOR_DATE", format!("@{timestamp} +0000"))
+        .env("GIT_COMMITTER_DATE", format!("@{timestamp} +0000"))
+        .args([
+            "-c",
+            &format!("user.name={author}"),
+            "-c",
+            "user.email=author@example.com",
+        ])
+        .args(args)
+        .output()
+        .expect("Failed to run git");
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Start a server that accepts local import sources under the system temp directory
+async fn start_import_server() -> (TestServer, TempDir) {
+    let db_dir = TempDir::new().expect("Failed to create temp dir");
+    let config = moor_vcs_worker::Config::with_db_path(db_dir.path().to_path_buf())
+        .with_import_root(std::env::temp_dir());
+    let server = TestServer::start_with_config(config)
+        .await
+        .expect("Failed to start test server");
+    (server, db_dir)
+}
+
+/// Load the merged changes of a server in index order
+fn merged_changes(server: &TestServer) -> Vec<Change> {
+    let index = server.database().index();
+    index
+        .get_change_order()
+        .expect("Failed to get change order")
+        .iter()
+        .map(|id| {
+            index
+                .get_change(id)
+                .expect("Failed to get change")
+                .expect("Change should exist")
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_import_git_replays_history() {
+    let (server, _db_dir) = start_import_server().await;
+    let repo = TempDir::new().expect("Failed to create temp dir");
+    let path = repo.path();
+
+    println!("Test: import/git turns each commit into a merged change");
+
+    // Step 1: Build a history of .moo files
+    println!("\nStep 1: Building git history...");
+    git(path, "alice", 1700000000, &["init", "--quiet"]);
+    std::fs::write(path.join("room.moo"), objdef("Room")).unwrap();
+    std::fs::write(path.join("thing.moo"), objdef("Thing")).unwrap();
+    git(path, "alice", 1700000000, &["add", "-A"]);
+    git(
+        path,
+        "alice",
+        1700000000,
+        &["commit", "-q", "-m", "Add room and thing"],
+    );
+
+    std::fs::write(path.join("room.moo"), objdef("Bigger Room")).unwrap();
+    git(path, "bob", 1700000100, &["mv", "thing.moo", "widget.moo"]);
+    git(path, "bob", 1700000100, &["add", "-A"]);
+    git(
+        path,
+        "bob",
+        1700000100,
+        &["commit", "-q", "-m", "Grow room\n\nAlso renames thing"],
+    );
+
+    std::fs::write(path.join("README.md"), "Not an object").unwrap();
+    git(path, "bob", 1700000200, &["add", "-A"]);
+    git(
+        path,
+        "bob",
+        1700000200,
+        &["commit", "-q", "-m", "Add readme"],
+    );
+
+    git(path, "carol", 1700000300, &["rm", "-q", "room.moo"]);
+    git(
+        path,
+        "carol",
+        1700000300,
+        &[
+            "commit",
+            "-q",
+            "-m",
+            "Remove room\n\nVCS-Change-Id: 0123456789abcdef",
+        ],
+    );
+    println!("✅ Repository has 4 commits, 3 touching .moo files");
+
+    // Step 2: Import it
+    println!("\nStep 2: Importing the repository...");
+    let response = server
+        .client()
+        .import_git(path.to_str().unwrap())
+        .await
+        .expect("Failed to import");
+    response.assert_success("Import git");
+    assert_eq!(response["result"]["changes"], 3, "got: {}", response);
+    assert_eq!(response["result"]["objects"], 1, "got: {}", response);
+    println!("✅ Imported 3 changes");
+
+    // Step 3: Each commit became a merged change
+    println!("\nStep 3: Verifying the imported changes...");
+    let changes = merged_changes(&server);
+    assert_eq!(changes.len(), 3);
+
+    assert_eq!(changes[0].name, "Add room and thing");
+    assert_eq!(changes[0].author, "alice");
+    assert_eq!(changes[0].timestamp, 1700000000);
+    assert_eq!(changes[0].added_objects.len(), 2);
+
+    assert_eq!(changes[1].name, "Grow room");
+    assert_eq!(
+        changes[1].description.as_deref(),
+        Some("Also renames thing")
+    );
+    assert_eq!(changes[1].author, "bob");
+    assert!(
+        changes[1]
+            .modified_objects
+            .iter()
+            .any(|o| o.name == "room" && o.version == 2),
+        "room should be modified to version 2: {:?}",
+        changes[1].modified_objects
+    );
+    assert_eq!(changes[1].renamed_objects.len(), 1);
+    assert_eq!(changes[1].renamed_objects[0].from.name, "thing");
+    assert_eq!(changes[1].renamed_objects[0].to.name, "widget");
+
+    assert_eq!(changes[2].id, "0123456789abcdef");
+    assert_eq!(changes[2].name, "Remove room");
+    assert_eq!(changes[2].description, None);
+    assert_eq!(changes[2].deleted_objects.len(), 1);
+    assert_eq!(changes[2].deleted_objects[0].name, "room");
+    println!("✅ Changes carry authors, messages, renames and deletions");
+
+    // Step 4: The resulting world matches the repository
+    println!("\nStep 4: Verifying the resulting objects...");
+    let objects = server
+        .database()
+        .index()
+        .compute_complete_object_list()
+        .expect("Failed to compute object list");
+    let names: Vec<&str> = objects.iter().map(|o| o.name.as_str()).collect();
+    assert_eq!(names, vec!["widget"]);
+    server
+        .db_assertions()
+        .assert_ref_exists(VcsObjectType::MooObject, "widget");
+    println!("✅ Only the renamed object remains");
+
+    println!("\n✅ Test passed: git history round-trips into the index");
+}
+
+#[tokio::test]
+async fn test_import_git_plain_directory() {
+    let (server, _db_dir) = start_import_server().await;
+    let dir = TempDir::new().expect("Failed to create temp dir");
+
+    println!("Test: import/git imports a plain directory as a single change");
+
+    std::fs::write(dir.path().join("room.moo"), objdef("Room")).unwrap();
+    std::fs::write(dir.path().join("thing.moo"), objdef("Thing")).unwrap();
+    std::fs::write(dir.path().join("notes.txt"), "Not an object").unwrap();
+
+    let response = server
+        .client()
+        .import_git(dir.path().to_str().unwrap())
+        .await
+        .expect("Failed to import");
+    response.assert_success("Import directory");
+
+    let changes = merged_changes(&server);
+    assert_eq!(changes.len(), 1);
+    let mut added: Vec<&str> = changes[0]
+        .added_objects
+        .iter()
+        .map(|o| o.name.as_str())
+        .collect();
+    added.sort();
+    assert_eq!(added, vec!["room", "thing"]);
+    println!("✅ Directory imported as one change with both objects");
+}
+
+#[tokio::test]
+async fn test_import_git_rejects_invalid_objects() {
+    let (server, _db_dir) = start_import_server().await;
+    let client = server.client();
+    let dir = TempDir::new().expect("Failed to create temp dir");
+
+    println!("Test: import/git refuses invalid objdefs without touching the world");
+
+    // Existing world
+    client
+        .object_update("existing", moo_to_lines(&objdef("Existing")))
+        .await
+        .expect("Failed to update object")
+        .assert_success("Update object");
+
+    std::fs::write(dir.path().join("broken.moo"), "this is not an objdef").unwrap();
+
+    let response = client
+        .import_git(dir.path().to_str().unwrap())
+        .await
+        .expect("Request should complete");
+    response.assert_failure("Import invalid objdef");
+    assert_eq!(response["error"]["code"], "invalid_args");
+    let message = response["error"]["message"].as_str().unwrap_or_default();
+    assert!(message.contains("broken"), "got: {}", message);
+    println!("✅ Invalid objdef reported: {}", message);
+
+    server
+        .db_assertions()
+        .assert_object_in_top_change("existing");
+    println!("✅ Existing world left untouched");
+
+    let missing = dir.path().join("missing");
+    let response = client
+        .import_git(missing.to_str().unwrap())
+        .await
+        .expect("Request should complete");
+    response.assert_failure("Import missing directory");
+    assert_eq!(response["error"]["code"], "invalid_args");
+    println!("✅ Missing source reported as invalid_args");
+}
+
+#[tokio::test]
+async fn test_import_git_confines_local_sources_to_import_root() {
+    let (server, _db_dir) = start_import_server().await;
+
+    println!("Test: import/git only reads local sources inside the import root");
+
+    // Step 1: Paths outside the root are refused, including through `..` and file:// URLs
+    println!("\nStep 1: Importing from outside the import root...");
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let escape = format!("{}/../../etc", dir.path().display());
+    for source in ["/etc", escape.as_str(), "file:///etc"] {
+        let response = server
+            .client()
+            .import_git(source)
+            .await
+            .expect("Request should complete");
+        response.assert_failure("Import outside root");
+        assert_eq!(
+            response["error"]["code"], "permission_denied",
+            "{} should be refused: {}",
+            source, response
+        );
+    }
+    println!("✅ Sources outside the root refused");
+
+    // Step 2: Without a configured root, local imports are disabled
+    println!("\nStep 2: Importing on a server without an import root...");
+    let default_server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    std::fs::write(dir.path().join("room.moo"), objdef("Room")).unwrap();
+    let response = default_server
+        .client()
+        .import_git(dir.path().to_str().unwrap())
+        .await
+        .expect("Request should complete");
+    response.assert_failure("Import without root");
+    assert_eq!(response["error"]["code"], "permission_denied");
+    println!("✅ Local import refused when no root is configured");
+}
+
+#[tokio::test]
+async fn test_import_git_keeps_local_changes() {
+    let (server, _db_dir) = start_import_server().await;
+    let client = server.client();
+    let dir = TempDir::new().expect("Failed to create temp dir");
+
+    println!("Test: import/git keeps unmerged local work on top of the imported history");
+
+    // Step 1: Unmerged local work
+    println!("\nStep 1: Creating a local change...");
+    client
+        .object_update("existing", moo_to_lines(&objdef("Existing")))
+        .await
+        .expect("Failed to update object")
+        .assert_success("Update object");
+    let (local_id, _) = server.db_assertions().require_top_change();
+    println!("✅ Local change {} created", local_id);
+
+    // Step 2: Import a directory over it
+    println!("\nStep 2: Importing a directory...");
+    std::fs::write(dir.path().join("room.moo"), objdef("Room")).unwrap();
+    let response = client
+        .import_git(dir.path().to_str().unwrap())
+        .await
+        .expect("Failed to import");
+    response.assert_success("Import directory");
+    let local_changes = response["result"]["local_changes"]
+        .as_array()
+        .expect("local_changes should be a list");
+    assert_eq!(local_changes.len(), 1, "got: {}", response);
+    assert_eq!(local_changes[0]["change_id"], local_id.as_str());
+    println!("✅ Import reported the preserved change");
+
+    // Step 3: The local change is still on top, with its object intact
+    println!("\nStep 3: Verifying the local change survived...");
+    let (top_id, top) = server.db_assertions().require_top_change();
+    assert_eq!(top_id, local_id, "Local change should stay on top");
+    assert!(
+        top.added_objects.iter().any(|o| o.name == "existing"),
+        "Local change should still add 'existing': {:?}",
+        top.added_objects
+    );
+    server
+        .db_assertions()
+        .assert_ref_exists(VcsObjectType::MooObject, "existing");
+    let merged = merged_changes(&server);
+    assert!(
+        merged.iter().any(|c| c.name.starts_with("Import from")),
+        "Imported change should be in the index"
+    );
+    println!("✅ Local work kept on top of the imported history");
+}
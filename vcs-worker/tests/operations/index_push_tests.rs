@@ -0,0 +1,267 @@
+//! Tests for index/push and index/receive
+//!
+//! These tests verify:
+//! 1. index/push sends changes merged on a clone to the source, which appends them to its
+//!    history together with their refs and blobs
+//! 2. A push is rejected with a conflict once the source has merged a change the clone
+//!    lacks, leaving the source untouched
+//! 3. A push leaves the clone's open local change behind, even on an object it pushes
+
+use crate::common::*;
+use moor_vcs_worker::types::{ChangeStatus, VcsObjectType};
+use serde_json::Value;
+
+/// Get the change order of a server's index
+fn change_order(server: &TestServer) -> Vec<String> {
+    server
+        .database()
+        .index()
+        .get_change_order()
+        .expect("Failed to get change order")
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_push_fast_forwards_source() {
+    let source_server = TestServer::start()
+        .await
+        .expect("Failed to start source server");
+    let target_server = TestServer::start()
+        .await
+        .expect("Failed to start target server");
+    let target_client = target_server.client();
+
+    println!("Test: index/push appends locally merged changes to the source");
+
+    // Step 1: Source has a merged base object, cloned to target
+    println!("\nStep 1: Cloning the base object...");
+    let base_id = source_server
+        .client()
+        .approve_object(
+            "base_object",
+            moo_to_lines(&load_moo_file("test_object_1.moo")),
+        )
+        .await;
+    target_client
+        .clone_import(&format!("{}/api/clone", source_server.base_url()))
+        .await
+        .expect("Failed to clone")
+        .assert_success("Clone");
+    println!("✅ Target cloned base change {}", base_id);
+
+    // Step 2: Target merges a change of its own
+    println!("\nStep 2: Approving a change on target...");
+    let pushed_id = target_server
+        .client()
+        .approve_object(
+            "pushed_object",
+            moo_to_lines(&load_moo_file("test_object_2.moo")),
+        )
+        .await;
+    let pushed_hash = target_server
+        .db_assertions()
+        .assert_ref_exists(VcsObjectType::MooObject, "pushed_object");
+    println!("✅ Target merged change {}", pushed_id);
+
+    // Step 3: Push it
+    println!("\nStep 3: Pushing to source...");
+    let response = target_client
+        .rpc_call("index/push", vec![])
+        .await
+        .expect("Push request should complete");
+    response.assert_success("Push");
+    println!("Push response: {}", response["result"]);
+    assert_eq!(response["result"]["base_change_id"], base_id.as_str());
+    let change_ids: Vec<&str> = response["result"]["change_ids"]
+        .as_array()
+        .expect("Response should list the pushed changes")
+        .iter()
+        .filter_map(Value::as_str)
+        .collect();
+    assert_eq!(change_ids, vec![pushed_id.as_str()]);
+    println!("✅ Push reported change {}", pushed_id);
+
+    // Step 4: Source has the change, its ref and its blob
+    println!("\nStep 4: Verifying the source...");
+    assert_eq!(
+        change_order(&source_server),
+        vec![base_id, pushed_id.clone()]
+    );
+    let source_db = source_server.db_assertions();
+    assert_eq!(
+        source_db.assert_ref_exists(VcsObjectType::MooObject, "pushed_object"),
+        pushed_hash
+    );
+    source_db.assert_sha256_exists(&pushed_hash);
+    println!("✅ Source fast-forwarded to {}", pushed_id);
+
+    // Step 5: Pushing again has nothing to send
+    println!("\nStep 5: Pushing again...");
+    let response = target_client
+        .rpc_call("index/push", vec![])
+        .await
+        .expect("Push request should complete");
+    response.assert_success("Second push");
+    assert_eq!(response["result"]["change_ids"], Value::Array(vec![]));
+    println!("✅ Nothing left to push");
+
+    println!("\n✅ Test passed: index/push fast-forwards the source");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_push_rejected_when_histories_diverge() {
+    let source_server = TestServer::start()
+        .await
+        .expect("Failed to start source server");
+    let target_server = TestServer::start()
+        .await
+        .expect("Failed to start target server");
+    let target_client = target_server.client();
+
+    println!("Test: index/push is rejected when the source has moved on");
+
+    // Step 1: Clone a base change, then merge a change on each side
+    println!("\nStep 1: Diverging the histories...");
+    source_server
+        .client()
+        .approve_object(
+            "base_object",
+            moo_to_lines(&load_moo_file("test_object_1.moo")),
+        )
+        .await;
+    target_client
+        .clone_import(&format!("{}/api/clone", source_server.base_url()))
+        .await
+        .expect("Failed to clone")
+        .assert_success("Clone");
+    target_server
+        .client()
+        .approve_object(
+            "pushed_object",
+            moo_to_lines(&load_moo_file("test_object_2.moo")),
+        )
+        .await;
+    source_server
+        .client()
+        .approve_object(
+            "upstream_object",
+            moo_to_lines(&load_moo_file("test_object_3.moo")),
+        )
+        .await;
+    let source_order = change_order(&source_server);
+    println!("✅ Source and target each merged a change");
+
+    // Step 2: The push fails with a conflict
+    println!("\nStep 2: Pushing to source...");
+    let response = target_client
+        .rpc_call("index/push", vec![])
+        .await
+        .expect("Push request should complete");
+    response.assert_failure("Push");
+    assert_eq!(response["error"]["code"], "conflict");
+    let message = response["error"]["message"].as_str().unwrap_or_default();
+    assert!(
+        message.contains("index/update"),
+        "Error should suggest updating first: {}",
+        message
+    );
+    println!("✅ Push rejected: {}", message);
+
+    // Step 3: The source is untouched
+    println!("\nStep 3: Verifying the source...");
+    assert_eq!(change_order(&source_server), source_order);
+    source_server
+        .db_assertions()
+        .assert_ref_not_exists(VcsObjectType::MooObject, "pushed_object");
+    println!("✅ Source history unchanged");
+
+    println!("\n✅ Test passed: Diverged pushes are rejected");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_push_leaves_local_change_behind() {
+    let source_server = TestServer::start()
+        .await
+        .expect("Failed to start source server");
+    let target_server = TestServer::start()
+        .await
+        .expect("Failed to start target server");
+    let target_client = target_server.client();
+    let target_db = target_server.db_assertions();
+
+    println!("Test: index/push sends only merged versions while a local change is open");
+
+    // Step 1: Target clones a base change and merges an object of its own
+    println!("\nStep 1: Cloning and approving a change on target...");
+    let base_id = source_server
+        .client()
+        .approve_object(
+            "base_object",
+            moo_to_lines(&load_moo_file("test_object_1.moo")),
+        )
+        .await;
+    target_client
+        .clone_import(&format!("{}/api/clone", source_server.base_url()))
+        .await
+        .expect("Failed to clone")
+        .assert_success("Clone");
+    let pushed_id = target_server
+        .client()
+        .approve_object(
+            "pushed_object",
+            moo_to_lines(&load_moo_file("test_object_2.moo")),
+        )
+        .await;
+    let pushed_hash = target_db.assert_ref_exists(VcsObjectType::MooObject, "pushed_object");
+    println!("✅ Target merged change {}", pushed_id);
+
+    // Step 2: Target modifies the pushed object again in a local change
+    println!("\nStep 2: Opening a local change on the pushed object...");
+    target_client
+        .object_update_from_file("pushed_object", "test_object_3.moo")
+        .await
+        .expect("Failed to modify object")
+        .assert_success("Modify object locally");
+    let (local_id, _) = target_db.require_top_change();
+    let local_hash = target_db.assert_ref_exists(VcsObjectType::MooObject, "pushed_object");
+    assert_ne!(
+        local_hash, pushed_hash,
+        "Target should have a local version 2"
+    );
+    println!("✅ Target has local change {}", local_id);
+
+    // Step 3: Push succeeds with the merged change only
+    println!("\nStep 3: Pushing to source...");
+    let response = target_client
+        .rpc_call("index/push", vec![])
+        .await
+        .expect("Push request should complete");
+    response.assert_success("Push");
+    let change_ids: Vec<&str> = response["result"]["change_ids"]
+        .as_array()
+        .expect("Response should list the pushed changes")
+        .iter()
+        .filter_map(Value::as_str)
+        .collect();
+    assert_eq!(change_ids, vec![pushed_id.as_str()]);
+    println!("✅ Push sent change {}", pushed_id);
+
+    // Step 4: Source ends at the merged version, target keeps its local change
+    println!("\nStep 4: Verifying both sides...");
+    assert_eq!(change_order(&source_server), vec![base_id, pushed_id]);
+    let source_db = source_server.db_assertions();
+    assert_eq!(
+        source_db.assert_ref_exists(VcsObjectType::MooObject, "pushed_object"),
+        pushed_hash
+    );
+    source_db.assert_sha256_not_exists(&local_hash);
+    let (top_id, top_change) = target_db.require_top_change();
+    assert_eq!(top_id, local_id);
+    assert_eq!(top_change.status, ChangeStatus::Local);
+    assert_eq!(
+        target_db.assert_ref_exists(VcsObjectType::MooObject, "pushed_object"),
+        local_hash
+    );
+    println!("✅ Source has version 1, target still has its local version 2");
+
+    println!("\n✅ Test passed: index/push leaves local work behind");
+}
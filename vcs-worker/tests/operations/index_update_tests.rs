@@ -6,6 +6,10 @@
 //! 3. Update fails gracefully without source URL
 //! 4. Update works correctly when already up-to-date
 //! 5. Integration between calc_delta and update operations
+//! 6. Update applies the delta on top of the local index instead of re-cloning, keeping
+//!    unmerged local work and downloading only the blobs the new changes record
+//! 7. Versions of the source's own unmerged work are not pulled along with the changes
+//! 8. calc_delta refuses callers without Clone permission, like clone does
 
 use crate::common::*;
 use moor_vcs_worker::types::VcsObjectType;
@@ -24,7 +28,7 @@ async fn test_update_without_source_url_fails() {
     let source = server
         .database()
         .index()
-        .get_source()
+        .get_remote(DEFAULT_REMOTE)
         .expect("Failed to get source");
     assert!(source.is_none(), "Should have no source URL initially");
     println!("✅ No source URL configured");
@@ -697,6 +701,55 @@ async fn test_calc_delta_with_non_existent_change_id() {
     println!("\n✅ Test passed: calc_delta fails with non-existent change ID");
 }
 
+#[tokio::test]
+async fn test_calc_delta_requires_clone_permission() {
+    let source_server = TestServer::start()
+        .await
+        .expect("Failed to start source server");
+    let source_client = source_server.client();
+
+    println!("Test: calc_delta is refused to callers without Clone permission");
+
+    // Step 1: Source has a merged change
+    println!("\nStep 1: Creating a merged change...");
+    source_client
+        .object_update_from_file("object_1", "test_object.moo")
+        .await
+        .expect("Failed to update object")
+        .assert_success("Update object");
+    let (change_id, _) = source_server.db_assertions().require_top_change();
+    source_client
+        .change_approve(&change_id)
+        .await
+        .expect("Failed to approve")
+        .assert_success("Approve");
+    println!("✅ Change {} merged", change_id);
+
+    // Step 2: Everyone asks for the delta
+    println!("\nStep 2: Requesting the delta as Everyone...");
+    let (status, text) = make_request_with_api_key(
+        "POST",
+        &format!("{}/rpc", source_server.base_url()),
+        Some(serde_json::json!({"operation": "index/calc_delta", "args": [change_id]})),
+        None,
+    )
+    .await
+    .expect("Request should complete");
+    assert_eq!(status, reqwest::StatusCode::FORBIDDEN);
+    let body: serde_json::Value = serde_json::from_str(&text).expect("Response should be JSON");
+    body.assert_failure("calc_delta as Everyone");
+    assert_eq!(body["error"]["code"], "permission_denied");
+    assert!(
+        body.get("result")
+            .is_none_or(|result| result.get("delta").is_none()),
+        "No delta should be returned: {}",
+        body
+    );
+    println!("✅ Everyone refused with {}", status);
+
+    println!("\n✅ Test passed: calc_delta requires Clone permission");
+}
+
 #[tokio::test]
 async fn test_calc_delta_with_empty_change_id() {
     let source_server = TestServer::start()
@@ -823,3 +876,252 @@ async fn test_calc_delta_with_malformed_change_id() {
 
     println!("\n✅ Test passed: calc_delta handles malformed IDs gracefully");
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_update_applies_delta_incrementally() {
+    let source_server = TestServer::start()
+        .await
+        .expect("Failed to start source server");
+    let target_server = TestServer::start()
+        .await
+        .expect("Failed to start target server");
+
+    let source_client = source_server.client();
+    let source_db = source_server.db_assertions();
+    let target_client = target_server.client();
+    let target_db = target_server.db_assertions();
+
+    println!("Test: Update should apply only the new changes on top of the local index");
+
+    // Step 1: Source has one merged change, cloned to target
+    println!("\nStep 1: Creating and cloning initial state...");
+    source_client
+        .object_update_from_file("object_1", "test_object.moo")
+        .await
+        .expect("Failed to update object 1")
+        .assert_success("Update object 1");
+    let (change_1_id, _) = source_db.require_top_change();
+    source_client
+        .change_approve(&change_1_id)
+        .await
+        .expect("Failed to approve")
+        .assert_success("Approve 1");
+    let object_1_v1 = source_db.assert_ref_exists(VcsObjectType::MooObject, "object_1");
+
+    let source_url = format!("{}/api/clone", source_server.base_url());
+    target_client
+        .clone_import(&source_url)
+        .await
+        .expect("Failed to clone")
+        .assert_success("Clone");
+    println!("✅ Target cloned");
+
+    // Step 2: Target starts local work that is not on the source
+    println!("\nStep 2: Creating local work on target...");
+    target_client
+        .object_update_from_file("local_object", "test_object_1.moo")
+        .await
+        .expect("Failed to update local object")
+        .assert_success("Update local object");
+    let (local_id, _) = target_db.require_top_change();
+    println!("✅ Target has local change {}", local_id);
+
+    // Step 3: Source adds an object and modifies the existing one
+    println!("\nStep 3: Adding a change on source...");
+    source_client
+        .object_update_from_file("object_2", "detailed_test_object.moo")
+        .await
+        .expect("Failed to update object 2")
+        .assert_success("Update object 2");
+    source_client
+        .object_update_from_file("object_1", "test_object_2.moo")
+        .await
+        .expect("Failed to modify object 1")
+        .assert_success("Modify object 1");
+    let (change_2_id, _) = source_db.require_top_change();
+    source_client
+        .change_approve(&change_2_id)
+        .await
+        .expect("Failed to approve")
+        .assert_success("Approve 2");
+
+    // The delta carries the change record and only the two blobs it records
+    let delta_response = source_client
+        .index_calc_delta(&change_1_id)
+        .await
+        .expect("calc_delta should complete");
+    delta_response.assert_success("calc_delta");
+    let delta: moor_vcs_worker::types::IndexDelta = serde_json::from_str(
+        delta_response["result"]["delta"]
+            .as_str()
+            .expect("Delta should include the encoded delta"),
+    )
+    .expect("Delta should parse");
+    assert_eq!(delta.changes.len(), 1);
+    assert_eq!(delta.changes[0].id, change_2_id);
+    assert_eq!(
+        delta.objects.len(),
+        2,
+        "Only the new versions should be sent"
+    );
+    assert!(
+        !delta.objects.contains_key(&object_1_v1),
+        "The version the target already has should not be sent"
+    );
+    println!("✅ Delta has 1 change and 2 blobs");
+
+    // Step 4: Update the target over RPC and examine the returned diff
+    println!("\nStep 4: Updating target...");
+    let update_response = target_client
+        .rpc_call("index/update", vec![])
+        .await
+        .expect("Update request should complete");
+    update_response.assert_success("Update");
+    let diff = &update_response["result"];
+    println!("Update diff: {}", diff);
+    assert!(
+        diff["objects_added"].to_string().contains("object_2"),
+        "Diff should list the added object"
+    );
+    assert!(
+        diff["objects_modified"].to_string().contains("object_1"),
+        "Diff should list the modified object"
+    );
+    println!("✅ Update returned the merged diff");
+
+    // Step 5: New change slots in before the local work, which is untouched
+    println!("\nStep 5: Verifying target index...");
+    let order = target_server
+        .database()
+        .index()
+        .get_change_order()
+        .expect("Failed to get change order");
+    assert_eq!(order, vec![change_1_id, change_2_id, local_id.clone()]);
+    let (top_id, _) = target_db.require_top_change();
+    assert_eq!(top_id, local_id, "Local change should stay on top");
+    target_db.assert_ref_exists(VcsObjectType::MooObject, "local_object");
+
+    let object_1_v2 = target_server
+        .database()
+        .refs()
+        .get_ref(VcsObjectType::MooObject, "object_1", Some(2))
+        .expect("Failed to get ref")
+        .expect("object_1 version 2 should be pulled");
+    target_db.assert_sha256_exists(&object_1_v2);
+    let object_2 = target_db.assert_ref_exists(VcsObjectType::MooObject, "object_2");
+    target_db.assert_sha256_exists(&object_2);
+    println!("✅ Target has the new change, refs and blobs alongside its local work");
+
+    println!("\n✅ Test passed: Update applies deltas incrementally");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_update_skips_source_local_versions() {
+    let source_server = TestServer::start()
+        .await
+        .expect("Failed to start source server");
+    let target_server = TestServer::start()
+        .await
+        .expect("Failed to start target server");
+
+    let source_client = source_server.client();
+    let source_db = source_server.db_assertions();
+    let target_client = target_server.client();
+    let target_db = target_server.db_assertions();
+
+    println!("Test: Update should not pull versions from the source's open local change");
+
+    // Step 1: Source has one merged change, cloned to target
+    println!("\nStep 1: Creating and cloning initial state...");
+    source_client
+        .object_update_from_file("object_1", "test_object.moo")
+        .await
+        .expect("Failed to update object 1")
+        .assert_success("Update object 1");
+    let (change_1_id, _) = source_db.require_top_change();
+    source_client
+        .change_approve(&change_1_id)
+        .await
+        .expect("Failed to approve")
+        .assert_success("Approve 1");
+    target_client
+        .clone_import(&format!("{}/api/clone", source_server.base_url()))
+        .await
+        .expect("Failed to clone")
+        .assert_success("Clone");
+    println!("✅ Target cloned");
+
+    // Step 2: Source merges a modification, then modifies the object again locally
+    println!("\nStep 2: Merging version 2 and opening version 3 on source...");
+    source_client
+        .object_update_from_file("object_1", "test_object_2.moo")
+        .await
+        .expect("Failed to modify object 1")
+        .assert_success("Modify object 1");
+    let (change_2_id, _) = source_db.require_top_change();
+    source_client
+        .change_approve(&change_2_id)
+        .await
+        .expect("Failed to approve")
+        .assert_success("Approve 2");
+    let object_1_v2 = source_db.assert_ref_exists(VcsObjectType::MooObject, "object_1");
+    source_client
+        .object_update_from_file("object_1", "test_object_3.moo")
+        .await
+        .expect("Failed to modify object 1 again")
+        .assert_success("Modify object 1 locally");
+    assert_ne!(
+        source_db.assert_ref_exists(VcsObjectType::MooObject, "object_1"),
+        object_1_v2,
+        "Source should have a local version 3"
+    );
+    println!("✅ Source has merged version 2 and local version 3");
+
+    // Step 3: The delta stops at the merged version
+    println!("\nStep 3: Calculating the delta...");
+    let delta_response = source_client
+        .index_calc_delta(&change_1_id)
+        .await
+        .expect("calc_delta should complete");
+    delta_response.assert_success("calc_delta");
+    let delta: moor_vcs_worker::types::IndexDelta = serde_json::from_str(
+        delta_response["result"]["delta"]
+            .as_str()
+            .expect("Delta should include the encoded delta"),
+    )
+    .expect("Delta should parse");
+    let versions: Vec<u64> = delta
+        .refs
+        .iter()
+        .filter(|(info, _)| info.name == "object_1")
+        .map(|(info, _)| info.version)
+        .collect();
+    assert_eq!(versions, vec![1, 2], "Only merged versions should be sent");
+    println!("✅ Delta sends versions {:?}", versions);
+
+    // Step 4: Update the target
+    println!("\nStep 4: Updating target...");
+    target_client
+        .rpc_call("index/update", vec![])
+        .await
+        .expect("Update request should complete")
+        .assert_success("Update");
+    assert_eq!(
+        target_db.assert_ref_exists(VcsObjectType::MooObject, "object_1"),
+        object_1_v2,
+        "Target should end at the merged version"
+    );
+    target_db.assert_sha256_exists(&object_1_v2);
+    let object_1_v3 = target_server
+        .database()
+        .refs()
+        .get_ref(VcsObjectType::MooObject, "object_1", Some(3))
+        .expect("Failed to get ref");
+    assert_eq!(
+        object_1_v3, None,
+        "The source's local version should stay there"
+    );
+    println!("✅ Target pulled version 2 only");
+
+    println!("\n✅ Test passed: Update leaves the source's local work behind");
+}
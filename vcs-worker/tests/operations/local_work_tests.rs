@@ -0,0 +1,279 @@
+//! Tests for preserving local work across clone and index/update
+//!
+//! These tests verify:
+//! 1. index/update rebases local and idle changes onto the new changes, moving object
+//!    versions the remote now uses, and reports a change that conflicts without touching it
+//! 2. Cloning over a repository keeps its local change, refs and blobs on top of the
+//!    imported history
+
+use crate::common::*;
+use moor_vcs_worker::providers::workspace::WorkspaceProvider;
+use moor_vcs_worker::types::{ChangeStatus, VcsObjectType};
+use serde_json::Value;
+
+/// Update the shared object in a fresh change and stash it, returning the idle change ID
+async fn stash_object(server: &TestServer, lines: Vec<String>) -> String {
+    let client = server.client();
+    client
+        .object_update("shared_object", lines)
+        .await
+        .expect("Failed to update object")
+        .assert_success("Update object");
+    let (change_id, _) = server.db_assertions().require_top_change();
+    client
+        .change_stash()
+        .await
+        .expect("Failed to stash")
+        .assert_success("Stash");
+    change_id
+}
+
+/// Load the dump of the shared object version recorded in a workspace change
+fn workspace_object_dump(server: &TestServer, change_id: &str) -> String {
+    let change = server
+        .database()
+        .workspace()
+        .get_workspace_change(change_id)
+        .expect("Failed to get workspace change")
+        .expect("Change should exist in workspace");
+    let version = change
+        .modified_objects
+        .iter()
+        .chain(change.added_objects.iter())
+        .find(|o| o.name == "shared_object")
+        .expect("Change should contain the object")
+        .version;
+    let hash = server
+        .database()
+        .refs()
+        .get_ref(VcsObjectType::MooObject, "shared_object", Some(version))
+        .expect("Failed to get ref")
+        .expect("Ref should exist");
+    server
+        .database()
+        .objects()
+        .get(&hash)
+        .expect("Failed to get object")
+        .expect("Object content should exist")
+}
+
+/// Find the reported outcome for a change in an index/update response
+fn local_change_status(response: &Value, change_id: &str) -> String {
+    response["result"]["local_changes"]
+        .as_array()
+        .expect("Response should list local changes")
+        .iter()
+        .find(|c| c["change_id"] == change_id)
+        .unwrap_or_else(|| panic!("No report for change {change_id}: {response}"))["status"]
+        .as_str()
+        .expect("Status should be a string")
+        .to_string()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_update_rebases_local_work() {
+    let source_server = TestServer::start()
+        .await
+        .expect("Failed to start source server");
+    let target_server = TestServer::start()
+        .await
+        .expect("Failed to start target server");
+    let target_client = target_server.client();
+    let target_db = target_server.db_assertions();
+
+    println!("Test: index/update rebases local work and reports conflicts");
+
+    // Step 1: Source has a merged base object, cloned to target
+    println!("\nStep 1: Cloning the base object...");
+    let base_id = source_server
+        .client()
+        .approve_object("shared_object", object_lines("base look", "base examine"))
+        .await;
+    target_client
+        .clone_import(&format!("{}/api/clone", source_server.base_url()))
+        .await
+        .expect("Failed to clone")
+        .assert_success("Clone");
+    println!("✅ Target cloned base change {}", base_id);
+
+    // Step 2: Target stashes two edits and starts a local change
+    println!("\nStep 2: Creating local work on target...");
+    let clean_id = stash_object(&target_server, object_lines("our look", "base examine")).await;
+    let conflicting_id =
+        stash_object(&target_server, object_lines("base look", "our examine")).await;
+    target_client
+        .object_update_from_file("local_object", "test_object_1.moo")
+        .await
+        .expect("Failed to update local object")
+        .assert_success("Update local object");
+    let (local_id, _) = target_db.require_top_change();
+    let local_hash = target_db.assert_ref_exists(VcsObjectType::MooObject, "local_object");
+    println!(
+        "✅ Idle changes {} and {}, local change {}",
+        clean_id, conflicting_id, local_id
+    );
+
+    // Step 3: Source edits examine, taking the version the target's first stash uses
+    println!("\nStep 3: Approving an upstream edit...");
+    let upstream_id = source_server
+        .client()
+        .approve_object("shared_object", object_lines("base look", "their examine"))
+        .await;
+    println!("✅ Upstream change approved: {}", upstream_id);
+
+    // Step 4: Update the target
+    println!("\nStep 4: Updating target...");
+    let response = target_client
+        .rpc_call("index/update", vec![])
+        .await
+        .expect("Update request should complete");
+    response.assert_success("Update");
+    println!("Update response: {}", response["result"]);
+    assert!(
+        response["result"]["objects_modified"]
+            .to_string()
+            .contains("shared_object"),
+        "Diff should list the upstream edit"
+    );
+    assert_eq!(local_change_status(&response, &clean_id), "rebased");
+    assert_eq!(local_change_status(&response, &conflicting_id), "conflict");
+    assert_eq!(local_change_status(&response, &local_id), "rebased");
+    println!("✅ Update reported each local change");
+
+    // Step 5: The clean stash carries both edits and is based on the upstream change
+    println!("\nStep 5: Verifying the rebased stash...");
+    let dump = workspace_object_dump(&target_server, &clean_id);
+    assert!(dump.contains("our look"), "Local edit kept: {}", dump);
+    assert!(
+        dump.contains("their examine"),
+        "Upstream edit merged: {}",
+        dump
+    );
+    let clean = target_server
+        .database()
+        .workspace()
+        .get_workspace_change(&clean_id)
+        .expect("Failed to get workspace change")
+        .expect("Change should exist in workspace");
+    assert_eq!(clean.status, ChangeStatus::Idle);
+    assert_eq!(clean.index_change_id.as_deref(), Some(upstream_id.as_str()));
+    println!("✅ Stash rebased onto {}", upstream_id);
+
+    // Step 6: The conflicting stash is untouched but still has its content
+    println!("\nStep 6: Verifying the conflicting stash...");
+    let dump = workspace_object_dump(&target_server, &conflicting_id);
+    assert!(dump.contains("our examine"), "Local edit kept: {}", dump);
+    assert!(!dump.contains("their examine"), "Nothing merged: {}", dump);
+    let conflicting = target_server
+        .database()
+        .workspace()
+        .get_workspace_change(&conflicting_id)
+        .expect("Failed to get workspace change")
+        .expect("Change should exist in workspace");
+    assert_eq!(
+        conflicting.index_change_id.as_deref(),
+        Some(base_id.as_str())
+    );
+    println!("✅ Conflicting stash left for manual resolution");
+
+    // Step 7: The local change stays on top of the new history with its blob
+    println!("\nStep 7: Verifying the local change...");
+    let order = target_server
+        .database()
+        .index()
+        .get_change_order()
+        .expect("Failed to get change order");
+    assert_eq!(order, vec![base_id, upstream_id, local_id.clone()]);
+    let (top_id, _) = target_db.require_top_change();
+    assert_eq!(top_id, local_id, "Local change should stay on top");
+    assert_eq!(
+        target_db.assert_ref_exists(VcsObjectType::MooObject, "local_object"),
+        local_hash
+    );
+    target_db.assert_sha256_exists(&local_hash);
+    println!("✅ Local change kept on top");
+
+    println!("\n✅ Test passed: Local work survives index/update");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_clone_preserves_local_change() {
+    let source_server = TestServer::start()
+        .await
+        .expect("Failed to start source server");
+    let target_server = TestServer::start()
+        .await
+        .expect("Failed to start target server");
+    let target_client = target_server.client();
+    let target_db = target_server.db_assertions();
+    let clone_url = format!("{}/api/clone", source_server.base_url());
+
+    println!("Test: Cloning again keeps the local change, refs and blobs");
+
+    // Step 1: Clone, then start local work
+    println!("\nStep 1: Cloning and creating local work...");
+    let base_id = source_server
+        .client()
+        .approve_object("shared_object", object_lines("base look", "base examine"))
+        .await;
+    target_client
+        .clone_import(&clone_url)
+        .await
+        .expect("Failed to clone")
+        .assert_success("Clone");
+    target_client
+        .object_update_from_file("local_object", "test_object_1.moo")
+        .await
+        .expect("Failed to update local object")
+        .assert_success("Update local object");
+    let (local_id, _) = target_db.require_top_change();
+    let local_hash = target_db.assert_ref_exists(VcsObjectType::MooObject, "local_object");
+    println!("✅ Local change {} created", local_id);
+
+    // Step 2: Clone again over it
+    println!("\nStep 2: Cloning again...");
+    let response = target_client
+        .clone_import(&clone_url)
+        .await
+        .expect("Failed to clone");
+    response.assert_success("Clone");
+    let message = response["result"].as_str().unwrap_or_default();
+    assert!(
+        message.contains("preserving local changes"),
+        "Clone should report the preserved change: {}",
+        message
+    );
+    println!("✅ {}", message);
+
+    // Step 3: The local change is still on top, with its ref and blob
+    println!("\nStep 3: Verifying local work...");
+    let order = target_server
+        .database()
+        .index()
+        .get_change_order()
+        .expect("Failed to get change order");
+    assert_eq!(order, vec![base_id, local_id.clone()]);
+    let (top_id, _) = target_db.require_top_change();
+    assert_eq!(top_id, local_id);
+    let change = target_server
+        .database()
+        .index()
+        .get_change(&local_id)
+        .expect("Failed to get change")
+        .expect("Local change should survive the clone");
+    assert_eq!(change.status, ChangeStatus::Local);
+    assert!(
+        change
+            .added_objects
+            .iter()
+            .any(|o| o.name == "local_object")
+    );
+    assert_eq!(
+        target_db.assert_ref_exists(VcsObjectType::MooObject, "local_object"),
+        local_hash
+    );
+    target_db.assert_sha256_exists(&local_hash);
+    println!("✅ Local change, ref and blob kept");
+
+    println!("\n✅ Test passed: Local work survives a clone");
+}
@@ -317,7 +317,9 @@ async fn test_meta_tracking_moo_var_output() {
     let change_status_op =
         moor_vcs_worker::operations::ChangeStatusOperation::new(server.database().clone());
     let wizard_user = server.get_wizard_user().expect("Failed to get wizard user");
-    let result = change_status_op.execute(vec![], &wizard_user);
+    let result = change_status_op
+        .execute(vec![], &wizard_user)
+        .expect("change/status should succeed");
 
     // Step 3: Verify meta is present in the MOO var
     println!("\nStep 3: Verifying meta in MOO var output...");
@@ -505,7 +507,9 @@ async fn test_meta_tracking_in_object_history() {
     let history_op =
         moor_vcs_worker::operations::ObjectHistoryOperation::new(server.database().clone());
     let wizard_user = server.get_wizard_user().expect("Failed to get wizard user");
-    let result = history_op.execute(vec!["test_meta_track".to_string()], &wizard_user);
+    let result = history_op
+        .execute(vec!["test_meta_track".to_string()], &wizard_user)
+        .expect("object/history should succeed");
 
     // Step 4: Verify meta appears in history
     println!("\nStep 4: Verifying meta in history...");
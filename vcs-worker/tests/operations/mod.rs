@@ -9,21 +9,43 @@
 //! - workspace: Workspace operations
 //! - meta: Meta operations (add/remove/clear ignored properties and verbs)
 //! - change_switch_tests: Tests for change/switch operation
+//! - get_route_args_tests: Tests for path/query-string arguments on GET routes
+//! - error_response_tests: Tests for HTTP status codes and error codes of failed operations
+//! - import_git_tests: Tests for importing a world from a git repository of .moo files
+//! - content_hash_tests: Tests for content-addressed object storage and hash migration
+//! - system_fsck_tests: Tests for the system/fsck repository integrity check
+//! - system_gc_tests: Tests for garbage collection of unreachable object blobs
+//! - write_batch_tests: Tests for all-or-nothing writes across partitions
+//! - concurrency_tests: Tests for conflict detection between concurrent operations
+//! - local_work_tests: Tests for keeping unmerged local work across clone and index/update
+//! - index_push_tests: Tests for pushing merged changes upstream with index/push
+//! - remote_tests: Tests for named remotes and their per-remote credentials
 
 mod blake3_hash_tests;
 mod change;
 mod change_status_tests;
 mod change_switch_tests;
 mod clone;
+mod concurrency_tests;
+mod content_hash_tests;
+mod error_response_tests;
+mod get_route_args_tests;
+mod import_git_tests;
 mod index_operations;
+mod index_push_tests;
 mod index_update_tests;
+mod local_work_tests;
 mod meta;
 mod object;
 mod object_diff_operation_tests;
+mod remote_tests;
+mod system_fsck_tests;
+mod system_gc_tests;
 mod system_status_tests;
 mod test_wizard_user;
 mod user;
 mod workspace_approve_tests;
 mod workspace_operations;
+mod write_batch_tests;
 
 // Future test modules:
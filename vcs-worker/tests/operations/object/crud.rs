@@ -34,7 +34,7 @@ async fn test_object_create_and_verify() {
 
     // Verify the object exists with correct hash
     let object_dump_str = object_content.join("\n");
-    let sha256_hash = TestServer::calculate_sha256(&object_dump_str);
+    let sha256_hash = TestServer::calculate_hash(&object_dump_str);
     println!("Calculated SHA256: {}", sha256_hash);
 
     db.assert_sha256_exists(&sha256_hash);
@@ -96,7 +96,7 @@ async fn test_object_content_integrity() {
     let object_dump = load_moo_file("detailed_test_object.moo");
     let object_content = moo_to_lines(&object_dump);
     let object_dump_str = object_content.join("\n");
-    let sha256_hash = TestServer::calculate_sha256(&object_dump_str);
+    let sha256_hash = TestServer::calculate_hash(&object_dump_str);
 
     println!("Calculated SHA256: {}", sha256_hash);
 
@@ -284,7 +284,7 @@ async fn test_object_update_twice_in_same_change_trims_unused_sha256() {
         .assert_success("Object update v1");
 
     let object_dump_v1_str = object_content_v1.join("\n");
-    let sha256_v1 = TestServer::calculate_sha256(&object_dump_v1_str);
+    let sha256_v1 = TestServer::calculate_hash(&object_dump_v1_str);
     println!("First update SHA256: {}", sha256_v1);
 
     db.assert_sha256_exists(&sha256_v1);
@@ -322,7 +322,7 @@ async fn test_object_update_twice_in_same_change_trims_unused_sha256() {
     let object_dump_v2 = load_moo_file("detailed_test_object.moo");
     let object_content_v2 = moo_to_lines(&object_dump_v2);
     let object_dump_v2_str = object_content_v2.join("\n");
-    let sha256_v2 = TestServer::calculate_sha256(&object_dump_v2_str);
+    let sha256_v2 = TestServer::calculate_hash(&object_dump_v2_str);
     println!("Second update SHA256: {}", sha256_v2);
 
     assert_ne!(
@@ -25,7 +25,7 @@ async fn test_duplicate_content_detection() {
     let object_dump = load_moo_file("test_object.moo");
     let object_content = moo_to_lines(&object_dump);
     let content_str = object_content.join("\n");
-    let sha256_hash = TestServer::calculate_sha256(&content_str);
+    let sha256_hash = TestServer::calculate_hash(&content_str);
 
     println!("\nStep 1: Creating object with content A...");
 
@@ -93,7 +93,7 @@ async fn test_sha256_cleanup_on_content_change() {
     println!("\nStep 1: Creating object with content A...");
     let content_a_lines = moo_to_lines(&load_moo_file("test_object.moo"));
     let content_a_str = content_a_lines.join("\n");
-    let sha256_a = TestServer::calculate_sha256(&content_a_str);
+    let sha256_a = TestServer::calculate_hash(&content_a_str);
 
     client
         .object_update(object_name, content_a_lines)
@@ -114,7 +114,7 @@ async fn test_sha256_cleanup_on_content_change() {
     println!("\nStep 2: Updating object with content B...");
     let content_b_lines = moo_to_lines(&load_moo_file("detailed_test_object.moo"));
     let content_b_str = content_b_lines.join("\n");
-    let sha256_b = TestServer::calculate_sha256(&content_b_str);
+    let sha256_b = TestServer::calculate_hash(&content_b_str);
 
     assert_ne!(
         sha256_a, sha256_b,
@@ -164,11 +164,11 @@ async fn test_content_flip_flop_version_handling() {
     // Prepare Content A and B
     let content_a_lines = moo_to_lines(&load_moo_file("test_object.moo"));
     let content_a_str = content_a_lines.join("\n");
-    let sha256_a = TestServer::calculate_sha256(&content_a_str);
+    let sha256_a = TestServer::calculate_hash(&content_a_str);
 
     let content_b_lines = moo_to_lines(&load_moo_file("detailed_test_object.moo"));
     let content_b_str = content_b_lines.join("\n");
-    let sha256_b = TestServer::calculate_sha256(&content_b_str);
+    let sha256_b = TestServer::calculate_hash(&content_b_str);
 
     println!("\nSHA256_A: {}", sha256_a);
     println!("SHA256_B: {}", sha256_b);
@@ -278,7 +278,7 @@ async fn test_multiple_objects_same_content() {
     // Use the same content for two different objects
     let content_lines = moo_to_lines(&load_moo_file("test_object.moo"));
     let content_str = content_lines.join("\n");
-    let sha256 = TestServer::calculate_sha256(&content_str);
+    let sha256 = TestServer::calculate_hash(&content_str);
 
     println!("\nShared SHA256: {}", sha256);
 
@@ -319,7 +319,7 @@ async fn test_multiple_objects_same_content() {
     println!("\nUpdating object_1 with different content...");
     let new_content_lines = moo_to_lines(&load_moo_file("detailed_test_object.moo"));
     let new_content_str = new_content_lines.join("\n");
-    let new_sha256 = TestServer::calculate_sha256(&new_content_str);
+    let new_sha256 = TestServer::calculate_hash(&new_content_str);
     println!("New SHA256: {}", new_sha256);
 
     client
@@ -13,6 +13,7 @@
 //! - hint_tests: Tests for verb and property rename hints system
 //! - switch_tests: Tests for object/switch operation (moving objects between changes)
 //! - object_diff_tests: Unit tests for object_diff module (verb counting with multiple names)
+//! - object_merge_tests: Unit tests for object_merge module (three-way verb and property merge)
 
 mod crud;
 mod delete_tests;
@@ -22,6 +23,7 @@ mod history_tests;
 mod lifecycle;
 mod list;
 mod object_diff_tests;
+mod object_merge_tests;
 mod rename_edge_cases_tests;
 mod rename_modified_object_refs_test;
 mod rename_update_integration;
@@ -0,0 +1,192 @@
+//! Unit tests for object_merge module
+//!
+//! These tests verify three-way merging of object definitions at the verb
+//! and property level, and that overlapping edits are reported as conflicts.
+
+use moor_compiler::ObjectDefinition;
+use moor_vcs_worker::object_merge::merge_object_definitions;
+
+// Helper function to create a verb definition with the given owner
+fn create_verb_def(name: &str, owner: i32) -> moor_compiler::ObjVerbDef {
+    use moor_common::model::VerbArgsSpec;
+    use moor_common::util::BitEnum;
+    use moor_compiler::ObjVerbDef;
+    use moor_var::{Obj, Symbol, program::ProgramType};
+
+    ObjVerbDef {
+        names: vec![Symbol::mk(name)],
+        argspec: VerbArgsSpec::this_none_this(),
+        owner: Obj::mk_id(owner),
+        flags: BitEnum::new(),
+        program: ProgramType::MooR(Default::default()),
+    }
+}
+
+// Helper function to create a property definition
+fn create_prop_def(name: &str, value: i64) -> moor_compiler::ObjPropDef {
+    use moor_common::model::{PropFlag, PropPerms};
+    use moor_common::util::BitEnum;
+    use moor_compiler::ObjPropDef;
+    use moor_var::{Obj, Symbol};
+
+    ObjPropDef {
+        name: Symbol::mk(name),
+        perms: PropPerms::new(Obj::mk_id(1), BitEnum::<PropFlag>::new()),
+        value: Some(moor_var::v_int(value)),
+    }
+}
+
+// Helper function to create an object with two verbs and one property
+fn create_object_def() -> ObjectDefinition {
+    use moor_common::util::BitEnum;
+    use moor_var::Obj;
+
+    ObjectDefinition {
+        oid: Obj::mk_id(1),
+        parent: Obj::mk_id(0),
+        location: Obj::mk_id(0),
+        owner: Obj::mk_id(1),
+        name: "test".to_string(),
+        flags: BitEnum::new(),
+        verbs: vec![create_verb_def("look", 1), create_verb_def("examine", 1)],
+        property_definitions: vec![create_prop_def("description", 1)],
+        property_overrides: vec![],
+    }
+}
+
+#[test]
+fn test_disjoint_edits_merge_cleanly() {
+    let base = create_object_def();
+
+    let mut ours = create_object_def();
+    ours.verbs[0] = create_verb_def("look", 2);
+    ours.property_definitions
+        .push(create_prop_def("ours_only", 1));
+
+    let mut theirs = create_object_def();
+    theirs.verbs[1] = create_verb_def("examine", 3);
+    theirs.property_definitions[0] = create_prop_def("description", 2);
+
+    let merge = merge_object_definitions("test", Some(base), ours, theirs);
+
+    assert!(
+        merge.conflict.is_empty(),
+        "Expected no conflicts, got {:?}",
+        merge.conflict
+    );
+    let owners: Vec<_> = merge.merged.verbs.iter().map(|v| v.owner).collect();
+    assert_eq!(
+        owners,
+        vec![moor_var::Obj::mk_id(2), moor_var::Obj::mk_id(3)],
+        "Each side's verb edit should be kept"
+    );
+    assert_eq!(merge.merged.property_definitions.len(), 2);
+    assert_eq!(
+        merge.merged.property_definitions[0].value,
+        Some(moor_var::v_int(2)),
+        "Upstream property edit should be kept"
+    );
+}
+
+#[test]
+fn test_same_verb_edited_on_both_sides_conflicts() {
+    let base = create_object_def();
+
+    let mut ours = create_object_def();
+    ours.verbs[0] = create_verb_def("look", 2);
+    ours.property_definitions[0] = create_prop_def("description", 2);
+
+    let mut theirs = create_object_def();
+    theirs.verbs[0] = create_verb_def("look", 3);
+    theirs.property_definitions[0] = create_prop_def("description", 3);
+
+    let merge = merge_object_definitions("test", Some(base), ours, theirs);
+
+    assert_eq!(merge.conflict.verbs, vec!["look".to_string()]);
+    assert_eq!(merge.conflict.props, vec!["description".to_string()]);
+    assert!(merge.conflict.attributes.is_empty());
+}
+
+#[test]
+fn test_identical_edits_and_upstream_deletes() {
+    let base = create_object_def();
+
+    let mut ours = create_object_def();
+    ours.verbs[0] = create_verb_def("look", 2);
+
+    let mut theirs = create_object_def();
+    theirs.verbs[0] = create_verb_def("look", 2);
+    // Upstream deleted a verb we didn't touch
+    theirs.verbs.remove(1);
+
+    let merge = merge_object_definitions("test", Some(base), ours, theirs);
+
+    assert!(merge.conflict.is_empty(), "got {:?}", merge.conflict);
+    assert_eq!(
+        merge.merged.verbs.len(),
+        1,
+        "Upstream delete should be applied"
+    );
+    assert_eq!(merge.merged.verbs[0].owner, moor_var::Obj::mk_id(2));
+}
+
+#[test]
+fn test_conflicting_attribute_edit() {
+    let base = create_object_def();
+
+    let mut ours = create_object_def();
+    ours.parent = moor_var::Obj::mk_id(5);
+
+    let mut theirs = create_object_def();
+    theirs.parent = moor_var::Obj::mk_id(6);
+    theirs.name = "renamed".to_string();
+
+    let merge = merge_object_definitions("test", Some(base), ours, theirs);
+
+    assert_eq!(merge.conflict.attributes, vec!["parent".to_string()]);
+    assert_eq!(
+        merge.merged.name, "renamed",
+        "Non-conflicting attribute should merge"
+    );
+}
+
+#[test]
+fn test_same_named_verbs_merge_independently() {
+    use moor_common::model::{ArgSpec, PrepSpec, VerbArgsSpec};
+
+    // A second "look" verb that only differs by its argspec
+    let with_second_look = |obj: &mut ObjectDefinition, owner: i32| {
+        let mut verb = create_verb_def("look", owner);
+        verb.argspec = VerbArgsSpec {
+            dobj: ArgSpec::Any,
+            prep: PrepSpec::Any,
+            iobj: ArgSpec::Any,
+        };
+        obj.verbs.push(verb);
+    };
+
+    let mut base = create_object_def();
+    with_second_look(&mut base, 1);
+
+    // Each side edits a different one of the two "look" verbs
+    let mut ours = create_object_def();
+    ours.verbs[0] = create_verb_def("look", 2);
+    with_second_look(&mut ours, 1);
+
+    let mut theirs = create_object_def();
+    with_second_look(&mut theirs, 3);
+
+    let merge = merge_object_definitions("test", Some(base), ours, theirs);
+
+    assert!(merge.conflict.is_empty(), "got {:?}", merge.conflict);
+    let owners: Vec<_> = merge.merged.verbs.iter().map(|v| v.owner).collect();
+    assert_eq!(
+        owners,
+        vec![
+            moor_var::Obj::mk_id(2),
+            moor_var::Obj::mk_id(1),
+            moor_var::Obj::mk_id(3)
+        ],
+        "Both same-named verbs should be kept with each side's edit"
+    );
+}
@@ -342,7 +342,7 @@ async fn test_rename_back_after_rename_and_add_deletes_added_object_with_cleanup
     let original_name = "original_object";
     let original_content = moo_to_lines(&load_moo_file("test_object.moo"));
     let original_content_str = original_content.join("\n");
-    let original_sha256 = TestServer::calculate_sha256(&original_content_str);
+    let original_sha256 = TestServer::calculate_hash(&original_content_str);
 
     client
         .object_update(original_name, original_content)
@@ -394,7 +394,7 @@ async fn test_rename_back_after_rename_and_add_deletes_added_object_with_cleanup
     );
     let new_content = moo_to_lines(&load_moo_file("detailed_test_object.moo"));
     let new_content_str = new_content.join("\n");
-    let new_sha256 = TestServer::calculate_sha256(&new_content_str);
+    let new_sha256 = TestServer::calculate_hash(&new_content_str);
 
     client
         .object_update(original_name, new_content)
@@ -823,7 +823,7 @@ async fn test_submit_on_local_index_auto_approves_and_returns_no_diff() {
     let source = server
         .database()
         .index()
-        .get_source()
+        .get_remote(DEFAULT_REMOTE)
         .expect("Failed to get source");
 
     assert!(
@@ -11,6 +11,7 @@
 //! 7. Deleted verb detection
 //! 8. Error handling for missing objects
 //! 9. Error handling for invalid change IDs
+//! 10. Unified verb diffs and property before/after values
 
 use crate::common::*;
 use moor_var::{Associative, Sequence, Variant};
@@ -709,3 +710,135 @@ async fn test_object_diff_nonexistent_change() {
     println!("✅ Test passed: Error handling for invalid change ID works");
 }
 
+
+#[tokio::test]
+async fn test_object_diff_unified_diff_and_props() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+    let db = server.db_assertions();
+
+    println!("Test: Object diff includes unified verb diffs and property values");
+
+    client
+        .change_create("first", "wizard", Some("Initial"))
+        .await
+        .expect("Failed to create change");
+    client
+        .object_update(
+            "#123",
+            vec![
+                "object #123",
+                "  name: \"Test Object\"",
+                "  parent: #1",
+                "  location: #2",
+                "  owner: #2",
+                "",
+                "  property description (owner: #2, flags: \"rc\") = \"old\";",
+                "",
+                "  verb test_verb (this none this) owner: #2 flags: \"rxd\"",
+                "    x = 1;",
+                "    return x;",
+                "  endverb",
+                "endobject",
+            ].iter().map(|s| s.to_string()).collect(),
+        )
+        .await
+        .expect("Failed to create object")
+        .assert_success("Object creation");
+    let (first_id, _) = db.require_top_change();
+    client
+        .change_approve(&first_id)
+        .await
+        .expect("Failed to approve");
+
+    client
+        .change_create("second", "wizard", Some("Modified"))
+        .await
+        .expect("Failed to create change");
+    client
+        .object_update(
+            "#123",
+            vec![
+                "object #123",
+                "  name: \"Test Object\"",
+                "  parent: #1",
+                "  location: #2",
+                "  owner: #2",
+                "",
+                "  property description (owner: #2, flags: \"rc\") = \"new\";",
+                "",
+                "  verb test_verb (this none this) owner: #2 flags: \"rxd\"",
+                "    x = 2;",
+                "    return x;",
+                "  endverb",
+                "endobject",
+            ].iter().map(|s| s.to_string()).collect(),
+        )
+        .await
+        .expect("Failed to update object")
+        .assert_success("Object update");
+    let (second_id, _) = db.require_top_change();
+
+    let result = client
+        .object_diff("#123", &second_id, None)
+        .await
+        .expect("Failed to run diff");
+
+    let Variant::Map(result_map) = result.variant() else {
+        panic!("Result should be a map, got: {:?}", result);
+    };
+
+    // The verb entry carries a unified diff of the decompiled code
+    let changes = result_map
+        .get(&moor_var::Var::mk_str("changes"))
+        .expect("Result should have changes");
+    let Variant::List(changes_list) = changes.variant() else {
+        panic!("Changes should be a list");
+    };
+    assert_eq!(changes_list.len(), 1, "One verb should differ");
+    let Variant::Map(verb_change) = changes_list[0].variant() else {
+        panic!("Change should be a map");
+    };
+    let diff = verb_change
+        .get(&moor_var::Var::mk_str("diff"))
+        .expect("Change should have a unified diff");
+    let Variant::List(diff_lines) = diff.variant() else {
+        panic!("Diff should be a list");
+    };
+    let diff_lines: Vec<String> = diff_lines
+        .iter()
+        .map(|l| l.as_string().expect("Diff lines should be strings").to_string())
+        .collect();
+    println!("Unified diff: {:?}", diff_lines);
+    assert_eq!(
+        diff_lines,
+        vec!["@@ -1,2 +1,2 @@", "-x = 1;", "+x = 2;", " return x;"],
+        "Unexpected unified diff"
+    );
+    println!("✅ Unified verb diff returned");
+
+    // The modified property carries its before/after literal values
+    let props = result_map
+        .get(&moor_var::Var::mk_str("props"))
+        .expect("Result should have props");
+    let Variant::List(props_list) = props.variant() else {
+        panic!("Props should be a list");
+    };
+    assert_eq!(props_list.len(), 1, "One property should differ");
+    let Variant::Map(prop_change) = props_list[0].variant() else {
+        panic!("Prop change should be a map");
+    };
+    let field = |name: &str| {
+        prop_change
+            .get(&moor_var::Var::mk_str(name))
+            .and_then(|v| v.as_string().map(|s| s.to_string()))
+    };
+    assert_eq!(field("prop").as_deref(), Some("description"));
+    assert_eq!(field("before").as_deref(), Some("\"old\""));
+    assert_eq!(field("after").as_deref(), Some("\"new\""));
+    println!("✅ Property before/after values returned");
+
+    println!("✅ Test passed: Line-level details included in object diff");
+}
@@ -0,0 +1,433 @@
+//! Tests for named remotes
+//!
+//! These tests verify:
+//! 1. remote/add validates and stores a remote with its credentials, remote/list shows it
+//!    without the API key, and remote/remove forgets it
+//! 2. clone into a named remote keeps origin, and index/update pulls from the named remote
+//! 3. change/submit sends the change for review on the named remote instead of origin
+//! 4. system/status only contacts remotes when asked to, then reports how far each one is
+//!    ahead and behind
+
+use crate::common::*;
+use moor_vcs_worker::providers::workspace::WorkspaceProvider;
+use moor_vcs_worker::types::{ChangeStatus, VcsObjectType};
+use serde_json::Value;
+
+/// Add a remote pointing at another test server, authenticated as its Wizard
+async fn add_remote(client: &VcsTestClient, name: &str, server: &TestServer) -> Value {
+    client
+        .rpc_call(
+            "remote/add",
+            vec![
+                Value::String(name.to_string()),
+                Value::String(server.base_url()),
+                Value::String(WIZARD_API_KEY.to_string()),
+            ],
+        )
+        .await
+        .expect("Remote add request should complete")
+}
+
+/// Find a remote by name in a list of remotes
+fn find_remote<'a>(remotes: &'a [Value], name: &str) -> Option<&'a Value> {
+    remotes.iter().find(|remote| remote["name"] == name)
+}
+
+/// Get the per-remote entries of system/status, comparing with each remote
+async fn status_remotes(client: &VcsTestClient) -> Vec<Value> {
+    let response = client
+        .rpc_call("status", vec![Value::String("--remotes".to_string())])
+        .await
+        .expect("Status request should complete");
+    response.assert_success("Status");
+    response["result"]["remotes"]
+        .as_array()
+        .expect("Status should list remotes")
+        .clone()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_remote_add_list_remove() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let production = TestServer::start()
+        .await
+        .expect("Failed to start production server");
+    let client = server.client();
+
+    println!("Test: remote/add, remote/list and remote/remove manage named remotes");
+
+    // Step 1: Add a remote with an API key
+    println!("\nStep 1: Adding remote 'production'...");
+    let response = add_remote(&client, "production", &production).await;
+    response.assert_success("Add remote");
+    assert_eq!(response["result"]["url"], production.base_url().as_str());
+    assert_eq!(response["result"]["has_api_key"], 1);
+    assert!(
+        !response["result"]["user_id"]
+            .as_str()
+            .unwrap_or_default()
+            .is_empty(),
+        "The remote's user should be stored: {}",
+        response
+    );
+    println!("✅ Remote added: {}", response["result"]);
+
+    // Step 2: Adding it again is a conflict, and bad names are rejected
+    println!("\nStep 2: Adding duplicate and invalid remotes...");
+    let response = add_remote(&client, "production", &production).await;
+    response.assert_failure("Add duplicate remote");
+    assert_eq!(response["error"]["code"], "conflict");
+    let response = add_remote(&client, "prod server", &production).await;
+    response.assert_failure("Add remote with invalid name");
+    assert_eq!(response["error"]["code"], "invalid_args");
+    println!("✅ Duplicate and invalid remotes rejected");
+
+    // Step 3: Everyone cannot manage remotes
+    println!("\nStep 3: Adding a remote as Everyone...");
+    let response = add_remote(&server.client().with_api_key(None), "staging", &production).await;
+    response.assert_failure("Add remote as Everyone");
+    assert_eq!(response["error"]["code"], "permission_denied");
+    println!("✅ Everyone cannot add remotes");
+
+    // Step 4: The remote is listed without its API key
+    println!("\nStep 4: Listing remotes...");
+    let response = client
+        .rpc_call("remote/list", vec![])
+        .await
+        .expect("Remote list request should complete");
+    let remotes = response.require_result_list("List remotes");
+    assert_eq!(remotes.len(), 1, "Only production should be listed");
+    let remote = find_remote(remotes, "production").expect("production should be listed");
+    assert!(
+        remote.get("api_key").is_none(),
+        "API key must not be listed"
+    );
+    assert!(
+        !response.to_string().contains(WIZARD_API_KEY),
+        "API key must not appear in the response"
+    );
+    println!("✅ Remote listed without its API key");
+
+    // Step 5: Remove it, twice
+    println!("\nStep 5: Removing remote 'production'...");
+    client
+        .rpc_call(
+            "remote/remove",
+            vec![Value::String("production".to_string())],
+        )
+        .await
+        .expect("Remote remove request should complete")
+        .assert_success("Remove remote");
+    let removed = server
+        .database()
+        .index()
+        .get_remote("production")
+        .expect("Failed to get remote");
+    assert!(removed.is_none(), "Remote should be gone");
+    let response = client
+        .rpc_call(
+            "remote/remove",
+            vec![Value::String("production".to_string())],
+        )
+        .await
+        .expect("Remote remove request should complete");
+    response.assert_failure("Remove missing remote");
+    assert_eq!(response["error"]["code"], "not_found");
+    println!("✅ Remote removed");
+
+    println!("\n✅ Test passed: Remotes can be added, listed and removed");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_clone_and_update_from_named_remote() {
+    let source_server = TestServer::start()
+        .await
+        .expect("Failed to start source server");
+    let staging_server = TestServer::start()
+        .await
+        .expect("Failed to start staging server");
+    let target_server = TestServer::start()
+        .await
+        .expect("Failed to start target server");
+    let target_client = target_server.client();
+
+    println!("Test: clone and index/update use a named remote without touching origin");
+
+    // Step 1: Staging and target both clone the source
+    println!("\nStep 1: Cloning the source into staging and target...");
+    source_server
+        .client()
+        .approve_object(
+            "base_object",
+            moo_to_lines(&load_moo_file("test_object_1.moo")),
+        )
+        .await;
+    let clone_url = format!("{}/api/clone", source_server.base_url());
+    staging_server
+        .client()
+        .clone_import(&clone_url)
+        .await
+        .expect("Failed to clone")
+        .assert_success("Staging clone");
+    target_client
+        .clone_import(&clone_url)
+        .await
+        .expect("Failed to clone")
+        .assert_success("Target clone");
+    println!("✅ Staging and target cloned the source");
+
+    // Step 2: Clone staging into a named remote
+    println!("\nStep 2: Cloning staging as remote 'staging'...");
+    let response = target_client
+        .rpc_call(
+            "clone",
+            vec![
+                Value::String(format!("{}/api/clone", staging_server.base_url())),
+                Value::String(WIZARD_API_KEY.to_string()),
+                Value::String("staging".to_string()),
+            ],
+        )
+        .await
+        .expect("Clone request should complete");
+    response.assert_success("Clone into named remote");
+    let index = target_server.database().index();
+    let origin = index
+        .get_remote(DEFAULT_REMOTE)
+        .expect("Failed to get origin")
+        .expect("Origin should be kept");
+    assert_eq!(origin.url, source_server.base_url());
+    let staging = index
+        .get_remote("staging")
+        .expect("Failed to get staging")
+        .expect("Staging should be recorded");
+    assert_eq!(staging.url, staging_server.base_url());
+    assert_eq!(staging.api_key.as_deref(), Some(WIZARD_API_KEY));
+    println!("✅ Origin kept and staging recorded");
+
+    // Step 3: Staging moves on, and target updates from it by name
+    println!("\nStep 3: Updating from 'staging'...");
+    let staging_change = staging_server
+        .client()
+        .approve_object(
+            "staging_object",
+            moo_to_lines(&load_moo_file("test_object_2.moo")),
+        )
+        .await;
+    let response = target_client
+        .rpc_call("index/update", vec![Value::String("staging".to_string())])
+        .await
+        .expect("Update request should complete");
+    response.assert_success("Update from staging");
+    let change_order = index
+        .get_change_order()
+        .expect("Failed to get change order");
+    assert_eq!(change_order.last(), Some(&staging_change));
+    target_server
+        .db_assertions()
+        .assert_ref_exists(VcsObjectType::MooObject, "staging_object");
+    println!("✅ Target pulled change {} from staging", staging_change);
+
+    // Step 4: Unknown remotes are reported as missing
+    println!("\nStep 4: Updating from an unknown remote...");
+    let response = target_client
+        .rpc_call("index/update", vec![Value::String("missing".to_string())])
+        .await
+        .expect("Update request should complete");
+    response.assert_failure("Update from unknown remote");
+    assert_eq!(response["error"]["code"], "not_found");
+    println!("✅ Unknown remote rejected");
+
+    println!("\n✅ Test passed: Named remotes can be cloned and updated from");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_submit_to_named_remote() {
+    let source_server = TestServer::start()
+        .await
+        .expect("Failed to start source server");
+    let production_server = TestServer::start()
+        .await
+        .expect("Failed to start production server");
+    let target_server = TestServer::start()
+        .await
+        .expect("Failed to start target server");
+    let target_client = target_server.client();
+
+    println!("Test: change/submit sends the change to the named remote");
+
+    // Step 1: Target clones the source and adds production
+    println!("\nStep 1: Setting up origin and production...");
+    source_server
+        .client()
+        .approve_object(
+            "base_object",
+            moo_to_lines(&load_moo_file("test_object_1.moo")),
+        )
+        .await;
+    target_client
+        .clone_import(&format!("{}/api/clone", source_server.base_url()))
+        .await
+        .expect("Failed to clone")
+        .assert_success("Clone");
+    add_remote(&target_client, "production", &production_server)
+        .await
+        .assert_success("Add remote");
+    println!("✅ Origin and production configured");
+
+    // Step 2: Submit a change to production
+    println!("\nStep 2: Submitting a change to production...");
+    target_client
+        .object_update_from_file("submitted_object", "test_object_2.moo")
+        .await
+        .expect("Failed to update object")
+        .assert_success("Update object");
+    let (change_id, _) = target_server.db_assertions().require_top_change();
+    let response = target_client
+        .rpc_call(
+            "change/submit",
+            vec![
+                Value::String("Submitted to production".to_string()),
+                Value::String("production".to_string()),
+            ],
+        )
+        .await
+        .expect("Submit request should complete");
+    response.assert_success("Submit to production");
+    println!("✅ Change {} submitted", change_id);
+
+    // Step 3: Production has it for review, origin does not
+    println!("\nStep 3: Verifying where the change went...");
+    let submitted = production_server
+        .database()
+        .workspace()
+        .get_workspace_change(&change_id)
+        .expect("Failed to get workspace change")
+        .expect("Production should have the change");
+    assert_eq!(submitted.status, ChangeStatus::Review);
+    assert_eq!(
+        submitted.description.as_deref(),
+        Some("Submitted to production")
+    );
+    let on_origin = source_server
+        .database()
+        .workspace()
+        .get_workspace_change(&change_id)
+        .expect("Failed to get workspace change");
+    assert!(on_origin.is_none(), "Origin should not receive the change");
+    println!("✅ Change is under review on production only");
+
+    println!("\n✅ Test passed: change/submit honours the remote name");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_status_reports_ahead_and_behind() {
+    let source_server = TestServer::start()
+        .await
+        .expect("Failed to start source server");
+    let target_server = TestServer::start()
+        .await
+        .expect("Failed to start target server");
+    let target_client = target_server.client();
+
+    println!("Test: system/status reports per-remote ahead and behind counts");
+
+    // Step 1: Fresh clone is level with origin
+    println!("\nStep 1: Cloning the source...");
+    source_server
+        .client()
+        .approve_object(
+            "base_object",
+            moo_to_lines(&load_moo_file("test_object_1.moo")),
+        )
+        .await;
+    target_client
+        .clone_import(&format!("{}/api/clone", source_server.base_url()))
+        .await
+        .expect("Failed to clone")
+        .assert_success("Clone");
+    let remotes = status_remotes(&target_client).await;
+    let origin = find_remote(&remotes, DEFAULT_REMOTE).expect("origin should be reported");
+    assert_eq!(origin["ahead"], 0);
+    assert_eq!(origin["behind"], 0);
+    assert_eq!(origin["error"], "");
+    println!("✅ Clone is level with origin");
+
+    // Step 2: A change merged on target puts it ahead
+    println!("\nStep 2: Approving a change on target...");
+    target_server
+        .client()
+        .approve_object(
+            "local_object",
+            moo_to_lines(&load_moo_file("test_object_2.moo")),
+        )
+        .await;
+    let remotes = status_remotes(&target_client).await;
+    let origin = find_remote(&remotes, DEFAULT_REMOTE).expect("origin should be reported");
+    assert_eq!(origin["ahead"], 1);
+    assert_eq!(origin["behind"], 0);
+    println!("✅ Target is 1 ahead");
+
+    // Step 3: A change merged on the source puts it behind as well
+    println!("\nStep 3: Approving a change on the source...");
+    source_server
+        .client()
+        .approve_object(
+            "upstream_object",
+            moo_to_lines(&load_moo_file("test_object_3.moo")),
+        )
+        .await;
+    let response = target_client
+        .rpc_call("status", vec![Value::String("true".to_string())])
+        .await
+        .expect("Status request should complete");
+    let remotes = response["result"]["remotes"]
+        .as_array()
+        .expect("Status should list remotes");
+    let origin = find_remote(remotes, DEFAULT_REMOTE).expect("origin should be reported");
+    assert_eq!(origin["ahead"], 1);
+    assert_eq!(origin["behind"], 1);
+    assert_eq!(response["result"]["pending_updates"], 1);
+    println!("✅ Target is 1 ahead and 1 behind");
+
+    // Step 4: An unreachable remote reports its error instead of failing status
+    println!("\nStep 4: Adding an unreachable remote...");
+    target_server
+        .database()
+        .index()
+        .set_remote(&Remote {
+            name: "offline".to_string(),
+            url: "http://127.0.0.1:1".to_string(),
+            api_key: None,
+            user_id: None,
+        })
+        .expect("Failed to set remote");
+    let remotes = status_remotes(&target_client).await;
+    let offline = find_remote(&remotes, "offline").expect("offline should be reported");
+    assert_eq!(offline["ahead"], -1);
+    assert_eq!(offline["behind"], -1);
+    assert_ne!(offline["error"], "");
+    println!("✅ Unreachable remote reported: {}", offline["error"]);
+
+    // Step 5: A plain status lists the remotes without contacting them
+    println!("\nStep 5: Getting status without checking remotes...");
+    let response = target_client
+        .rpc_call("status", vec![])
+        .await
+        .expect("Status request should complete");
+    response.assert_success("Status");
+    let remotes = response["result"]["remotes"]
+        .as_array()
+        .expect("Status should list remotes");
+    let offline = find_remote(remotes, "offline").expect("offline should be listed");
+    assert!(
+        offline.get("error").is_none(),
+        "Unchecked remote should not be compared: {}",
+        offline
+    );
+    assert_eq!(response["result"]["pending_updates"], 0);
+    println!("✅ Remotes listed without being contacted");
+
+    println!("\n✅ Test passed: system/status compares each remote");
+}
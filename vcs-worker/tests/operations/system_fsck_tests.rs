@@ -0,0 +1,247 @@
+//! Integration tests for system/fsck operation
+//!
+//! These tests verify:
+//! 1. A repository built through normal operations checks clean
+//! 2. Dangling refs, orphaned and corrupted blobs, and missing changes are reported
+//! 3. Repair drops missing changes from the index order and stashes extra Local changes
+//! 4. Rename loops within a change are reported
+
+use crate::common::*;
+use moor_vcs_worker::providers::workspace::WorkspaceProvider;
+use moor_vcs_worker::types::{Change, ChangeStatus, ObjectInfo, RenamedObject, VcsObjectType};
+use serde_json::Value;
+
+/// Get one of the fsck result lists as strings
+fn fsck_list(response: &Value, key: &str) -> Vec<String> {
+    response["result"][key]
+        .as_array()
+        .unwrap_or_else(|| panic!("'{}' should be a list: {}", key, response))
+        .iter()
+        .map(|v| v.as_str().unwrap_or_default().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_fsck_clean_repository() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+
+    println!("Test: A repository built through normal operations checks clean");
+
+    client
+        .object_update_from_file("test_object", "test_object.moo")
+        .await
+        .expect("Failed to update object")
+        .assert_success("Object update");
+
+    let response = client.system_fsck(false).await.expect("Failed to run fsck");
+    response.assert_success("Fsck");
+    assert_eq!(response["result"]["clean"], 1, "got: {}", response);
+    for key in [
+        "dangling_refs",
+        "missing_blobs",
+        "orphaned_blobs",
+        "corrupted_blobs",
+        "missing_changes",
+        "duplicate_local_changes",
+        "rename_loops",
+        "repairs",
+    ] {
+        assert!(
+            fsck_list(&response, key).is_empty(),
+            "{} should be empty",
+            key
+        );
+    }
+    println!("✅ Repository is clean");
+}
+
+#[tokio::test]
+async fn test_fsck_reports_and_repairs_problems() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+    let database = server.database();
+
+    println!("Test: fsck reports inconsistencies and repairs the safe ones");
+
+    // Step 1: Build a repository, then damage it behind the providers' backs
+    println!("\nStep 1: Creating inconsistencies...");
+    client
+        .object_update_from_file("test_object", "test_object.moo")
+        .await
+        .expect("Failed to update object")
+        .assert_success("Object update");
+
+    let orphan_hash = TestServer::calculate_hash("orphaned content");
+    database
+        .objects()
+        .store(&orphan_hash, "orphaned content")
+        .expect("Failed to store orphan");
+
+    let corrupted_hash = TestServer::calculate_hash("original content");
+    database
+        .objects()
+        .store(&corrupted_hash, "tampered content")
+        .expect("Failed to store corrupted blob");
+
+    let missing_hash = TestServer::calculate_hash("never stored");
+    database
+        .refs()
+        .update_ref(VcsObjectType::MooObject, "ghost", 1, &missing_hash)
+        .expect("Failed to store dangling ref");
+
+    database
+        .index()
+        .append_change_to_order("deadbeef")
+        .expect("Failed to append missing change");
+    println!("✅ Added an orphan, a corrupted blob, a dangling ref and a missing change");
+
+    // Step 2: Check without repairing
+    println!("\nStep 2: Checking...");
+    let response = client.system_fsck(false).await.expect("Failed to run fsck");
+    response.assert_success("Fsck");
+    assert_eq!(response["result"]["clean"], 0);
+    assert_eq!(
+        fsck_list(&response, "dangling_refs"),
+        vec![format!("ghost v1 -> {missing_hash}")]
+    );
+    assert!(fsck_list(&response, "orphaned_blobs").contains(&orphan_hash));
+    assert_eq!(
+        fsck_list(&response, "corrupted_blobs"),
+        vec![corrupted_hash]
+    );
+    assert_eq!(fsck_list(&response, "missing_changes"), vec!["deadbeef"]);
+    assert!(fsck_list(&response, "repairs").is_empty());
+    println!("✅ All inconsistencies reported, nothing repaired");
+
+    // Step 3: Repair
+    println!("\nStep 3: Repairing...");
+    let response = client.system_fsck(true).await.expect("Failed to run fsck");
+    response.assert_success("Fsck repair");
+    let repairs = fsck_list(&response, "repairs");
+    assert_eq!(repairs.len(), 1, "got: {:?}", repairs);
+    assert!(repairs[0].contains("deadbeef"), "got: {:?}", repairs);
+
+    let order = database
+        .index()
+        .get_change_order()
+        .expect("Failed to get change order");
+    assert!(!order.contains(&"deadbeef".to_string()));
+    println!("✅ Missing change dropped from the index order");
+
+    // Step 4: Only the problems repair must not touch remain
+    println!("\nStep 4: Checking again...");
+    let response = client.system_fsck(false).await.expect("Failed to run fsck");
+    assert!(fsck_list(&response, "missing_changes").is_empty());
+    assert_eq!(fsck_list(&response, "dangling_refs").len(), 1);
+    assert_eq!(fsck_list(&response, "corrupted_blobs").len(), 1);
+    println!("✅ Content problems are left for a human to resolve");
+
+    // Invalid repair flags are rejected
+    let response = client
+        .rpc_call("system/fsck", vec![Value::String("maybe".to_string())])
+        .await
+        .expect("Request should complete");
+    response.assert_failure("Invalid repair flag");
+    assert_eq!(response["error"]["code"], "invalid_args");
+}
+
+#[tokio::test]
+async fn test_fsck_duplicate_local_changes_and_rename_loops() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+    let database = server.database();
+
+    println!("Test: fsck stashes extra Local changes and reports rename loops");
+
+    // Step 1: A second Local change in the index, beneath the top change
+    println!("\nStep 1: Creating a duplicate Local change...");
+    let duplicate = database
+        .index()
+        .create_blank_change(Some("builder".to_string()))
+        .expect("Failed to create change");
+    database
+        .index()
+        .append_change_to_order(&duplicate.id)
+        .expect("Failed to append change");
+
+    client
+        .object_update_from_file("test_object", "test_object.moo")
+        .await
+        .expect("Failed to update object")
+        .assert_success("Object update");
+    let (top_change_id, _) = server.db_assertions().require_top_change();
+    assert_ne!(top_change_id, duplicate.id);
+
+    // Step 2: An idle change whose renames loop back on themselves
+    let looping = Change {
+        id: "looping-change".to_string(),
+        name: "swap".to_string(),
+        description: None,
+        author: "builder".to_string(),
+        timestamp: 1700000000,
+        status: ChangeStatus::Idle,
+        added_objects: vec![],
+        modified_objects: vec![],
+        deleted_objects: vec![],
+        renamed_objects: ["a", "b"]
+            .iter()
+            .zip(["b", "a"].iter())
+            .map(|(from, to)| RenamedObject {
+                from: ObjectInfo {
+                    object_type: VcsObjectType::MooObject,
+                    name: from.to_string(),
+                    version: 1,
+                },
+                to: ObjectInfo {
+                    object_type: VcsObjectType::MooObject,
+                    name: to.to_string(),
+                    version: 1,
+                },
+            })
+            .collect(),
+        index_change_id: None,
+        verb_rename_hints: vec![],
+        property_rename_hints: vec![],
+    };
+    database
+        .workspace()
+        .store_workspace_change(&looping)
+        .expect("Failed to store workspace change");
+    println!("✅ Created a duplicate Local change and a looping rename");
+
+    // Step 3: Repair
+    println!("\nStep 3: Repairing...");
+    let response = client.system_fsck(true).await.expect("Failed to run fsck");
+    response.assert_success("Fsck repair");
+
+    let duplicates = fsck_list(&response, "duplicate_local_changes");
+    assert_eq!(duplicates.len(), 1, "got: {:?}", duplicates);
+    assert!(duplicates[0].contains(&duplicate.id[..12]));
+
+    let loops = fsck_list(&response, "rename_loops");
+    assert_eq!(loops.len(), 1, "got: {:?}", loops);
+    assert!(loops[0].ends_with("a -> b -> a"), "got: {:?}", loops);
+
+    // The duplicate was stashed; the top change is still the working change
+    let order = database
+        .index()
+        .get_change_order()
+        .expect("Failed to get change order");
+    assert!(!order.contains(&duplicate.id));
+    let stashed = database
+        .workspace()
+        .get_workspace_change(&duplicate.id)
+        .expect("Failed to get workspace change")
+        .expect("Duplicate should be stashed in the workspace");
+    assert_eq!(stashed.status, ChangeStatus::Idle);
+    let (still_top, _) = server.db_assertions().require_top_change();
+    assert_eq!(still_top, top_change_id);
+    println!("✅ Duplicate stashed as Idle, working change kept");
+}
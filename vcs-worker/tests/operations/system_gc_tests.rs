@@ -0,0 +1,153 @@
+//! Integration tests for system/gc operation
+//!
+//! These tests verify:
+//! 1. A dry run reports unreachable blobs and their size without deleting them
+//! 2. A sweep deletes unreachable blobs, reports the bytes reclaimed, and keeps reachable ones
+//! 3. Invalid flags are rejected
+//! 4. system/fsck reports exactly the blobs a sweep would delete
+
+use crate::common::*;
+use serde_json::Value;
+
+#[tokio::test]
+async fn test_gc_sweeps_unreachable_blobs() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+    let database = server.database();
+
+    println!("Test: system/gc sweeps blobs nothing can reach");
+
+    // Step 1: A reachable object and an unreachable blob
+    println!("\nStep 1: Creating a reachable object and an orphaned blob...");
+    client
+        .object_update_from_file("test_object", "test_object.moo")
+        .await
+        .expect("Failed to update object")
+        .assert_success("Object update");
+
+    let orphan = "orphaned content";
+    let orphan_hash = TestServer::calculate_hash(orphan);
+    database
+        .objects()
+        .store(&orphan_hash, orphan)
+        .expect("Failed to store orphan");
+    let orphan_bytes = (orphan_hash.len() + orphan.len()) as u64;
+    println!("✅ Orphan stored as {}", orphan_hash);
+
+    // Step 2: Dry run
+    println!("\nStep 2: Dry run...");
+    let response = client.system_gc(true).await.expect("Failed to run gc");
+    response.assert_success("Gc dry run");
+    assert_eq!(response["result"]["dry_run"], 1);
+    assert_eq!(response["result"]["swept_blobs"], 1, "got: {}", response);
+    assert_eq!(response["result"]["swept"][0], orphan_hash.as_str());
+    assert_eq!(response["result"]["reclaimed_bytes"], orphan_bytes);
+    assert!(
+        database
+            .objects()
+            .get(&orphan_hash)
+            .expect("Failed to get orphan")
+            .is_some(),
+        "Dry run should not delete anything"
+    );
+    println!("✅ Dry run reports the orphan and leaves it in place");
+
+    // Step 3: Sweep
+    println!("\nStep 3: Sweeping...");
+    let response = client.system_gc(false).await.expect("Failed to run gc");
+    response.assert_success("Gc");
+    assert_eq!(response["result"]["dry_run"], 0);
+    assert_eq!(response["result"]["swept_blobs"], 1);
+    assert_eq!(
+        response["result"]["reachable_blobs"].as_i64(),
+        response["result"]["total_blobs"]
+            .as_i64()
+            .map(|total| total - 1)
+    );
+    assert_eq!(response["result"]["reclaimed_bytes"], orphan_bytes);
+    assert!(
+        database
+            .objects()
+            .get(&orphan_hash)
+            .expect("Failed to get orphan")
+            .is_none(),
+        "Orphan should be swept"
+    );
+
+    client
+        .object_get("test_object")
+        .await
+        .expect("Failed to get object")
+        .assert_success("Get reachable object after gc");
+    println!("✅ Orphan swept, reachable object kept");
+
+    // Step 4: Nothing left to sweep
+    let response = client.system_gc(false).await.expect("Failed to run gc");
+    assert_eq!(response["result"]["swept_blobs"], 0);
+    assert_eq!(response["result"]["reclaimed_bytes"], 0);
+    println!("✅ Second sweep reclaims nothing");
+}
+
+#[tokio::test]
+async fn test_fsck_orphans_match_gc_sweep() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+
+    println!("Test: system/fsck orphaned_blobs match what system/gc would sweep");
+
+    // Step 1: Leave a superseded version and a stray blob behind
+    println!("\nStep 1: Creating unreachable blobs...");
+    client
+        .object_update_from_file("test_object", "test_object.moo")
+        .await
+        .expect("Failed to update object")
+        .assert_success("Object update");
+    client
+        .object_update_from_file("test_object", "test_object_1.moo")
+        .await
+        .expect("Failed to update object")
+        .assert_success("Second object update");
+    let orphan = "orphaned content";
+    server
+        .database()
+        .objects()
+        .store(&TestServer::calculate_hash(orphan), orphan)
+        .expect("Failed to store orphan");
+    println!("✅ Unreachable blobs created");
+
+    // Step 2: Compare the fsck report with a gc dry run
+    println!("\nStep 2: Comparing fsck and gc...");
+    let fsck = client.system_fsck(false).await.expect("Failed to run fsck");
+    fsck.assert_success("Fsck");
+    let gc = client.system_gc(true).await.expect("Failed to run gc");
+    gc.assert_success("Gc dry run");
+
+    let orphaned = fsck["result"]["orphaned_blobs"]
+        .as_array()
+        .expect("orphaned_blobs should be a list");
+    let swept = gc["result"]["swept"]
+        .as_array()
+        .expect("swept should be a list");
+    assert!(!swept.is_empty(), "Expected unreachable blobs: {}", gc);
+    assert_eq!(orphaned, swept, "fsck: {}\ngc: {}", fsck, gc);
+    println!("✅ fsck reports the {} blobs gc would sweep", swept.len());
+}
+
+#[tokio::test]
+async fn test_gc_rejects_invalid_flag() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+
+    let response = server
+        .client()
+        .rpc_call("system/gc", vec![Value::String("maybe".to_string())])
+        .await
+        .expect("Request should complete");
+    response.assert_failure("Invalid dry_run flag");
+    assert_eq!(response["error"]["code"], "invalid_args");
+}
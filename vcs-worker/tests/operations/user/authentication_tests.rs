@@ -0,0 +1,251 @@
+//! Tests for per-request authentication of the HTTP API via X-API-Key / Authorization: Bearer
+
+use crate::common::*;
+
+/// Call user/stat over /rpc with the given headers and return the status and parsed body
+async fn stat_with_headers(
+    server: &TestServer,
+    headers: &[(&str, &str)],
+) -> (reqwest::StatusCode, serde_json::Value) {
+    let client = reqwest::Client::new();
+    let mut request = client
+        .post(format!("{}/rpc", server.base_url()))
+        .json(&json!({"operation": "user/stat", "args": []}));
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+
+    let response = request.send().await.expect("Request should complete");
+    let status = response.status();
+    let body = response.json().await.expect("Response should be JSON");
+    (status, body)
+}
+
+#[tokio::test]
+async fn test_request_without_api_key_runs_as_everyone() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+
+    println!("Test: Requests without an API key run as the Everyone user");
+
+    let (status, body) = stat_with_headers(&server, &[]).await;
+    assert!(status.is_success(), "Anonymous request should be accepted");
+
+    let result = body.require_result_list("user/stat");
+    assert_eq!(result[0], "Everyone", "Anonymous caller should be Everyone");
+    assert!(
+        result[3].as_array().unwrap().is_empty(),
+        "Everyone should have no permissions"
+    );
+
+    println!("✅ Anonymous request resolved to Everyone");
+}
+
+#[tokio::test]
+async fn test_request_with_wizard_api_key_runs_as_wizard() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+
+    println!("Test: X-API-Key header resolves to the key's owner");
+
+    let (status, body) =
+        stat_with_headers(&server, &[("X-API-Key", &server.get_wizard_api_key())]).await;
+    assert!(status.is_success());
+    assert_eq!(body.require_result_list("user/stat")[0], "Wizard");
+
+    println!("✅ Wizard key resolved to Wizard");
+}
+
+#[tokio::test]
+async fn test_request_with_bearer_token_runs_as_user() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+
+    println!("Test: Authorization: Bearer header resolves to the key's owner");
+
+    server
+        .database()
+        .users()
+        .create_user(
+            "alice".to_string(),
+            "alice@example.com".to_string(),
+            moor_var::Obj::mk_id(200),
+        )
+        .expect("Failed to create user");
+    let api_key = server
+        .database()
+        .users()
+        .generate_api_key("alice")
+        .expect("Failed to generate API key");
+
+    let bearer = format!("Bearer {api_key}");
+    let (status, body) = stat_with_headers(&server, &[("Authorization", &bearer)]).await;
+    assert!(status.is_success());
+    assert_eq!(body.require_result_list("user/stat")[0], "alice");
+
+    println!("✅ Bearer token resolved to alice");
+}
+
+#[tokio::test]
+async fn test_request_with_unknown_api_key_is_rejected() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+
+    println!("Test: Unknown API keys are rejected with 401");
+
+    let (status, body) = stat_with_headers(&server, &[("X-API-Key", "not-a-real-key")]).await;
+    assert_eq!(status, reqwest::StatusCode::UNAUTHORIZED);
+    body.assert_failure("Unknown API key");
+
+    // Operation routes are protected the same way as /rpc
+    let (status, _) = make_request_with_api_key(
+        "GET",
+        &format!("{}/api/user/stat", server.base_url()),
+        None,
+        Some("not-a-real-key"),
+    )
+    .await
+    .expect("Request should complete");
+    assert_eq!(status, reqwest::StatusCode::UNAUTHORIZED);
+
+    println!("✅ Unknown API key rejected");
+}
+
+#[tokio::test]
+async fn test_request_from_disabled_user_is_rejected() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+
+    println!("Test: Keys belonging to disabled users are rejected with 403");
+
+    server
+        .database()
+        .users()
+        .create_user(
+            "mallory".to_string(),
+            "mallory@example.com".to_string(),
+            moor_var::Obj::mk_id(201),
+        )
+        .expect("Failed to create user");
+    let api_key = server
+        .database()
+        .users()
+        .generate_api_key("mallory")
+        .expect("Failed to generate API key");
+    server
+        .database()
+        .users()
+        .disable_user("mallory")
+        .expect("Failed to disable user");
+
+    let (status, body) = stat_with_headers(&server, &[("X-API-Key", &api_key)]).await;
+    assert_eq!(status, reqwest::StatusCode::FORBIDDEN);
+    body.assert_failure("Disabled user");
+
+    println!("✅ Disabled user rejected");
+}
+
+#[tokio::test]
+async fn test_permissions_are_enforced_for_authenticated_user() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let wizard = server.client();
+    let anonymous = server.client().with_api_key(None);
+
+    println!("Test: Permission checks apply to the authenticated caller");
+
+    wizard
+        .object_update_from_file("test_object", "test_object.moo")
+        .await
+        .expect("Failed to update object")
+        .assert_success("Update object");
+    let (change_id, _) = server.db_assertions().require_top_change();
+
+    // Everyone lacks ApproveChanges, so the approval must be refused
+    let response = anonymous
+        .change_approve(&change_id)
+        .await
+        .expect("Request should complete");
+    let result = response.require_result_str("Anonymous approve");
+    assert!(
+        result.contains("does not have permission"),
+        "Anonymous approve should be refused, got: {result}"
+    );
+
+    // The Wizard can still approve it
+    wizard
+        .change_approve(&change_id)
+        .await
+        .expect("Failed to approve")
+        .assert_success("Wizard approve");
+
+    println!("✅ Permissions enforced per caller");
+}
+
+#[tokio::test]
+async fn test_anonymous_requests_cannot_modify_state() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let wizard = server.client();
+    let anonymous = server.client().with_api_key(None);
+
+    println!("Test: Everyone cannot edit objects, changes or the index");
+
+    // Step 1: Anonymous object edits are refused
+    println!("\nStep 1: Updating an object anonymously...");
+    let response = anonymous
+        .object_update_from_file("test_object", "test_object.moo")
+        .await
+        .expect("Request should complete");
+    response.assert_failure("Anonymous object update");
+    assert_eq!(response["error"]["code"], "permission_denied");
+    server.db_assertions().assert_no_top_change();
+    println!("✅ Anonymous object update refused");
+
+    // Step 2: Someone else's change cannot be abandoned or switched away
+    println!("\nStep 2: Touching the Wizard's change anonymously...");
+    wizard
+        .object_update_from_file("test_object", "test_object.moo")
+        .await
+        .expect("Failed to update object")
+        .assert_success("Wizard object update");
+    let (change_id, _) = server.db_assertions().require_top_change();
+    for (operation, args) in [
+        ("change/abandon", vec![]),
+        ("change/create", vec![json!("anonymous_change")]),
+        ("object/delete", vec![json!("test_object")]),
+        ("meta/clear_ignored_verbs", vec![json!("test_object")]),
+    ] {
+        let response = anonymous
+            .rpc_call(operation, args)
+            .await
+            .expect("Request should complete");
+        response.assert_failure(operation);
+        assert_eq!(response["error"]["code"], "permission_denied");
+    }
+    let (top_change_id, _) = server.db_assertions().require_top_change();
+    assert_eq!(
+        top_change_id, change_id,
+        "The Wizard's change should be kept"
+    );
+    println!("✅ Anonymous change operations refused");
+
+    // Step 3: The index cannot be rewritten from a remote
+    println!("\nStep 3: Updating the index anonymously...");
+    let response = anonymous
+        .rpc_call("index/update", vec![])
+        .await
+        .expect("Request should complete");
+    response.assert_failure("Anonymous index update");
+    assert_eq!(response["error"]["code"], "permission_denied");
+    println!("✅ Anonymous index update refused");
+
+    println!("\n✅ Test passed: Anonymous requests cannot modify state");
+}
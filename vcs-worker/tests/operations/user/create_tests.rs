@@ -100,20 +100,17 @@ async fn test_create_user_no_permission() {
         &alice,
     );
     
-    // Check that the result is an error about permissions
-    let result_str = if let Some(s) = result.as_string() {
-        s.to_string()
-    } else if let Some(e) = result.as_error() {
-        e.msg.as_ref().map(|s| s.to_string()).unwrap_or_else(|| format!("{:?}", e))
-    } else {
-        format!("{:?}", result)
-    };
-    
-    assert!(
-        result_str.contains("Error") || result_str.contains("permission"),
-        "Expected permission error, got: {}",
-        result_str
-    );
+    // Check that the result is a permission error
+    match result {
+        Err(moor_vcs_worker::operations::OperationError::PermissionDenied(msg)) => {
+            assert!(
+                msg.contains("permission"),
+                "Expected permission error, got: {}",
+                msg
+            );
+        }
+        other => panic!("Expected PermissionDenied, got: {:?}", other),
+    }
     
     // Verify bob was not created
     let bob = server.database().users().get_user("bob").expect("Failed to query user");
@@ -17,13 +17,13 @@ async fn test_external_user_in_clone() {
     server
         .database()
         .index()
-        .set_external_user_api_key("test-api-key-123")
-        .expect("Failed to set external user API key");
-    server
-        .database()
-        .index()
-        .set_external_user_id("external-user-id")
-        .expect("Failed to set external user ID");
+        .set_remote(&Remote {
+            name: DEFAULT_REMOTE.to_string(),
+            url: "http://example.com/vcs".to_string(),
+            api_key: Some("test-api-key-123".to_string()),
+            user_id: Some("external-user-id".to_string()),
+        })
+        .expect("Failed to set external user credentials");
     println!("✅ External user credentials stored");
 
     // Retrieve and verify
@@ -31,14 +31,16 @@ async fn test_external_user_in_clone() {
     let api_key = server
         .database()
         .index()
-        .get_external_user_api_key()
+        .get_remote(DEFAULT_REMOTE)
         .expect("Failed to get external user API key")
+        .and_then(|remote| remote.api_key)
         .expect("External user API key not found");
     let user_id = server
         .database()
         .index()
-        .get_external_user_id()
+        .get_remote(DEFAULT_REMOTE)
         .expect("Failed to get external user ID")
+        .and_then(|remote| remote.user_id)
         .expect("External user ID not found");
 
     assert_eq!(api_key, "test-api-key-123");
@@ -1,6 +1,7 @@
 //! Integration tests for user management operations
 //!
 //! This module is organized by operation type:
+//! - authentication_tests: Tests for per-request API key authentication
 //! - create_tests: Tests for creating users
 //! - delete_tests: Tests for deleting users
 //! - enable_disable_tests: Tests for enabling/disabling users
@@ -8,12 +9,15 @@
 //! - api_keys_tests: Tests for generating and deleting API keys
 //! - list_tests: Tests for listing users
 //! - external_user_tests: Tests for external user configuration (clone operations)
+//! - rpc_perms_tests: Tests for attributing MOO RPC calls to users via perms
 
 mod api_keys_tests;
+mod authentication_tests;
 mod create_tests;
 mod delete_tests;
 mod enable_disable_tests;
 mod external_user_tests;
 mod list_tests;
 mod permissions_tests;
+mod rpc_perms_tests;
 
@@ -0,0 +1,128 @@
+//! Tests for attributing MOO RPC calls to VCS users via the caller's perms object
+
+use crate::common::*;
+use moor_vcs_worker::operations::OperationRequest;
+use moor_vcs_worker::types::Permission;
+
+#[tokio::test]
+async fn test_rpc_perms_resolve_to_matching_user() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+
+    println!("Test: RPC perms object resolves to the user with that v_obj");
+
+    server
+        .database()
+        .users()
+        .create_user(
+            "builder".to_string(),
+            "builder@example.com".to_string(),
+            moor_var::Obj::mk_id(42),
+        )
+        .expect("Failed to create user");
+
+    let user = server
+        .registry()
+        .authenticate_perms(moor_var::Obj::mk_id(42))
+        .expect("Perms should resolve");
+    assert_eq!(user.id, "builder");
+
+    println!("✅ #42 resolved to builder");
+}
+
+#[tokio::test]
+async fn test_rpc_unknown_perms_fall_back_to_everyone() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+
+    println!("Test: RPC perms without a matching user fall back to Everyone");
+
+    let user = server
+        .registry()
+        .authenticate_perms(moor_var::Obj::mk_id(9999))
+        .expect("Perms should resolve");
+    assert_eq!(user.id, "Everyone");
+    assert!(user.permissions.is_empty());
+
+    println!("✅ Unknown perms resolved to Everyone");
+}
+
+#[tokio::test]
+async fn test_rpc_perms_of_disabled_user_are_rejected() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+
+    println!("Test: RPC perms belonging to a disabled user are rejected");
+
+    server
+        .database()
+        .users()
+        .create_user(
+            "banned".to_string(),
+            "banned@example.com".to_string(),
+            moor_var::Obj::mk_id(43),
+        )
+        .expect("Failed to create user");
+    server
+        .database()
+        .users()
+        .disable_user("banned")
+        .expect("Failed to disable user");
+
+    let result = server
+        .registry()
+        .authenticate_perms(moor_var::Obj::mk_id(43));
+    assert!(result.is_err(), "Disabled user should be rejected");
+
+    println!("✅ Disabled user rejected");
+}
+
+#[tokio::test]
+async fn test_rpc_builder_cannot_approve_without_permission() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+
+    println!("Test: A builder calling over RPC cannot approve their own change");
+
+    server
+        .database()
+        .users()
+        .create_user(
+            "builder".to_string(),
+            "builder@example.com".to_string(),
+            moor_var::Obj::mk_id(42),
+        )
+        .expect("Failed to create user");
+    server
+        .database()
+        .users()
+        .add_permission("builder", Permission::SubmitChanges)
+        .expect("Failed to add permission");
+
+    client
+        .object_update_from_file("test_object", "test_object.moo")
+        .await
+        .expect("Failed to update object")
+        .assert_success("Update object");
+    let (change_id, _) = server.db_assertions().require_top_change();
+
+    let builder = server
+        .registry()
+        .authenticate_perms(moor_var::Obj::mk_id(42))
+        .expect("Perms should resolve");
+    let result = server.registry().execute_var_as(
+        OperationRequest {
+            operation: "change/approve".to_string(),
+            args: vec![change_id],
+        },
+        &builder,
+    );
+
+    assert!(result.as_error().is_some(), "Approve should be refused");
+    println!("✅ Builder approval refused: {:?}", result);
+}
@@ -28,7 +28,12 @@ async fn test_approve_change_from_workspace_adds_to_index() {
     server
         .database()
         .index()
-        .set_source(source_url)
+        .set_remote(&Remote {
+            name: DEFAULT_REMOTE.to_string(),
+            url: source_url.to_string(),
+            api_key: None,
+            user_id: None,
+        })
         .expect("Failed to set source URL");
     println!("✅ Source URL configured: {}", source_url);
 
@@ -218,7 +223,12 @@ async fn test_multiple_workspace_approvals_maintain_order() {
     server
         .database()
         .index()
-        .set_source("http://example.com/vcs")
+        .set_remote(&Remote {
+            name: DEFAULT_REMOTE.to_string(),
+            url: "http://example.com/vcs".to_string(),
+            api_key: None,
+            user_id: None,
+        })
         .expect("Failed to set source URL");
 
     // Step 2: Create and submit first change
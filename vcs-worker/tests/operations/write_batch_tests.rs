@@ -0,0 +1,187 @@
+//! Tests for cross-partition write batches
+//!
+//! These tests verify:
+//! 1. Writes made inside a batch are visible to it but not to other threads until it commits
+//! 2. A batch that fails leaves nothing behind in any partition
+//! 3. Nested batches join the outer one and are discarded with it
+//! 4. Batched operations still apply every write when they succeed
+
+use crate::common::*;
+use moor_vcs_worker::database::ObjectsTreeError;
+use moor_vcs_worker::types::VcsObjectType;
+
+#[tokio::test]
+async fn test_write_batch_is_all_or_nothing() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let database = server.database().clone();
+
+    println!("Test: A failed batch writes nothing, a successful one writes everything");
+
+    let content = "object content";
+    let hash = TestServer::calculate_hash(content);
+
+    // Step 1: A batch that fails after writing to two partitions
+    println!("\nStep 1: Failing batch...");
+    let result: Result<(), ObjectsTreeError> = database.write_batch(|| {
+        database.objects().store(&hash, content).unwrap();
+        database
+            .refs()
+            .update_ref(VcsObjectType::MooObject, "batched", 1, &hash)
+            .unwrap();
+
+        // The batch reads its own writes
+        assert_eq!(
+            database
+                .refs()
+                .get_ref(VcsObjectType::MooObject, "batched", None)
+                .unwrap(),
+            Some(hash.clone())
+        );
+        assert!(database.objects().get(&hash).unwrap().is_some());
+
+        // Other threads do not see them yet
+        let other = database.clone();
+        let seen = std::thread::spawn(move || {
+            other
+                .refs()
+                .get_ref(VcsObjectType::MooObject, "batched", None)
+                .unwrap()
+        })
+        .join()
+        .unwrap();
+        assert_eq!(seen, None, "Staged writes should not leak to other threads");
+
+        Err(ObjectsTreeError::InvalidArgument("abort".to_string()))
+    });
+    assert!(result.is_err());
+    assert_eq!(
+        database
+            .refs()
+            .get_ref(VcsObjectType::MooObject, "batched", None)
+            .unwrap(),
+        None
+    );
+    assert!(database.objects().get(&hash).unwrap().is_none());
+    println!("✅ Failed batch left no blob and no ref");
+
+    // Step 2: The same batch succeeding
+    println!("\nStep 2: Successful batch...");
+    let result: Result<(), ObjectsTreeError> = database.write_batch(|| {
+        database.objects().store(&hash, content).unwrap();
+        database
+            .refs()
+            .update_ref(VcsObjectType::MooObject, "batched", 1, &hash)
+            .unwrap();
+        Ok(())
+    });
+    result.expect("Batch should commit");
+    assert_eq!(
+        database
+            .refs()
+            .get_ref(VcsObjectType::MooObject, "batched", None)
+            .unwrap(),
+        Some(hash.clone())
+    );
+    assert!(database.objects().get(&hash).unwrap().is_some());
+    println!("✅ Committed batch wrote blob and ref");
+}
+
+#[tokio::test]
+async fn test_nested_write_batch_joins_outer() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let database = server.database().clone();
+
+    println!("Test: A nested batch is discarded with the outer batch");
+
+    let result: Result<(), ObjectsTreeError> = database.write_batch(|| {
+        let inner: Result<(), ObjectsTreeError> = database.write_batch(|| {
+            database
+                .refs()
+                .update_ref(VcsObjectType::MooObject, "nested", 1, "abc")
+                .unwrap();
+            Ok(())
+        });
+        inner.expect("Inner batch should succeed");
+        Err(ObjectsTreeError::InvalidArgument("abort".to_string()))
+    });
+    assert!(result.is_err());
+    assert_eq!(
+        database
+            .refs()
+            .get_ref(VcsObjectType::MooObject, "nested", None)
+            .unwrap(),
+        None,
+        "Inner writes should be discarded with the outer batch"
+    );
+    println!("✅ Nested batch discarded");
+}
+
+#[tokio::test]
+async fn test_batched_operations_apply_all_writes() {
+    let server = TestServer::start()
+        .await
+        .expect("Failed to start test server");
+    let client = server.client();
+    let db = server.db_assertions();
+
+    println!("Test: object/update, change/approve and change/abandon commit their writes");
+
+    // object/update writes a blob, a ref and the change together
+    client
+        .object_update_from_file("test_object", "test_object.moo")
+        .await
+        .expect("Failed to update object")
+        .assert_success("Object update");
+    let hash = db.assert_ref_exists(VcsObjectType::MooObject, "test_object");
+    db.assert_sha256_exists(&hash);
+    let (change_id, _) = db.require_top_change();
+
+    // change/approve merges the change
+    client
+        .change_approve(&change_id)
+        .await
+        .expect("Failed to approve change")
+        .assert_success("Approve change");
+    let change = server
+        .database()
+        .index()
+        .get_change(&change_id)
+        .expect("Failed to get change")
+        .expect("Approved change should exist");
+    assert_eq!(change.status, moor_vcs_worker::types::ChangeStatus::Merged);
+    assert!(
+        server
+            .database()
+            .index()
+            .get_top_change()
+            .expect("Failed to get top change")
+            .is_none()
+    );
+    println!("✅ Update and approval committed");
+
+    // change/abandon removes a new local change
+    client
+        .object_update_from_file("other_object", "test_object_1.moo")
+        .await
+        .expect("Failed to update object")
+        .assert_success("Object update");
+    let (local_id, _) = db.require_top_change();
+    client
+        .change_abandon()
+        .await
+        .expect("Failed to abandon change")
+        .assert_success("Abandon change");
+    assert!(
+        server
+            .database()
+            .index()
+            .get_change(&local_id)
+            .expect("Failed to get change")
+            .is_none()
+    );
+    println!("✅ Abandon committed");
+}